@@ -1,3 +1,11 @@
 fn main() {
+  // Release builds must embed the license cache key; without it offline grace
+  // is silently disabled for every user (see `license::CACHE_SIGNING_KEY`).
+  println!("cargo:rerun-if-env-changed=LBQ_LICENSE_CACHE_KEY");
+  if std::env::var("PROFILE").as_deref() == Ok("release")
+    && std::env::var("LBQ_LICENSE_CACHE_KEY").map_or(true, |key| key.is_empty())
+  {
+    panic!("LBQ_LICENSE_CACHE_KEY must be set for release builds (HMAC key for cached license validations)");
+  }
   tauri_build::build()
 }
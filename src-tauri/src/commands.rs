@@ -1,28 +1,35 @@
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use serde_json::Value;
 use tauri::{AppHandle, Emitter};
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::data::{converter, loader, storage, validator};
-use crate::engine::{builder, executor, monte_carlo, optimizer, sr, walk_forward};
+use crate::data::{converter, importers, loader, storage, validator};
+use crate::engine::{builder, executor, metrics, monte_carlo, optimizer, sensitivity, sr, validation, walk_forward};
 use crate::engine::executor::SubBarData;
 use crate::errors::AppError;
 use crate::models::builder::BuilderConfig;
-use crate::models::config::{DataFormat, InstrumentConfig, TickPipeline, TickStorageFormat, Timeframe};
+use crate::models::candle::{Candle, TickColumns};
+use crate::models::config::{ColumnMapping, DataFormat, InstrumentConfig, SourceFormat, TickPipeline, TickStorageFormat, Timeframe};
 use crate::models::project::Project;
-use crate::models::result::{BacktestMetrics, BacktestResults, MonteCarloConfig, MonteCarloResult, OosResult, OptimizationConfig, OptimizationMethod, OptimizationResult, WalkForwardConfig, WalkForwardResult};
+use crate::models::result::{BacktestMetrics, BacktestResults, BacktestStep, BatchBacktestResult, MonteCarloConfig, MonteCarloResult, OosResult, OptimizationConfig, OptimizationMethod, OptimizationOutput, RetainedOptimization, SeasonalityReport, WalkForwardConfig, WalkForwardResult};
 use crate::models::sr_result::SrConfig;
-use crate::models::strategy::{BacktestConfig, BacktestPrecision, Strategy};
+use crate::models::strategy::{BacktestConfig, BacktestPrecision, ConversionRates, DrawdownBasis, ExitPriority, QuoteConversion, Strategy};
 use crate::models::symbol::Symbol;
 use crate::models::trade::TradeResult;
-use crate::utils::{codegen, export};
+use crate::utils::{bundle, chart_png, codegen, export};
 use crate::AppState;
 
 // ── Data Commands ──
 
 /// Upload a CSV file, validate, convert to storage format, generate timeframes, store in DB.
+///
+/// `column_mapping` optionally assigns source columns to OHLCV/bid/ask targets for
+/// files whose column names or order differ from the supported layouts.
+/// `source_format` selects a vendor-specific importer (HistData, OANDA) instead
+/// of generic auto-detection.
 #[tauri::command]
 pub async fn upload_csv(
     app: AppHandle,
@@ -31,23 +38,37 @@ pub async fn upload_csv(
     symbol_name: String,
     instrument_config: InstrumentConfig,
     tick_storage_format: Option<TickStorageFormat>,
+    column_mapping: Option<ColumnMapping>,
+    source_format: Option<SourceFormat>,
+    repair_bars: Option<bool>,
 ) -> Result<Symbol, AppError> {
     let tick_storage_format = tick_storage_format.unwrap_or_default();
     // 0. Sanitize symbol name (prevent path traversal)
     sanitize_symbol_name(&symbol_name)?;
 
     let path = PathBuf::from(&file_path);
+    let source_format = source_format.unwrap_or_default();
 
-    // 1. Validate CSV
+    // 1. Validate CSV (generic files only — vendor exports have a fixed layout)
     emit_progress(&app, 5, "Validating CSV...");
-    let validation = validator::validate_csv(&path)?;
-    info!(
-        "Validated CSV: format={:?}, sample={}",
-        validation.format, validation.row_count_sample
-    );
+    let validation = if source_format == SourceFormat::Generic {
+        let validation = validator::validate_csv(&path, column_mapping.as_ref())?;
+        info!(
+            "Validated CSV: format={:?}, sample={}, datetime={} ({:?})",
+            validation.format,
+            validation.row_count_sample,
+            validation.datetime_format.describe(),
+            validation.datetime_layout
+        );
+        Some(validation)
+    } else {
+        info!("Importing {:?} export", source_format);
+        None
+    };
+    let format = validation.as_ref().map_or(DataFormat::Bar, |v| v.format);
 
     // 2. Determine base timeframe from format
-    let base_timeframe = match validation.format {
+    let base_timeframe = match format {
         DataFormat::Tick => Timeframe::Tick,
         DataFormat::Bar => Timeframe::M1, // default, user can change later
     };
@@ -58,15 +79,15 @@ pub async fn upload_csv(
     std::fs::create_dir_all(&symbol_dir)?;
 
     // 4. Load and process data
-    let (total_rows, start_date, end_date, timeframe_paths) =
-        if validation.format == DataFormat::Tick {
+    let (total_rows, start_date, end_date, timeframe_paths) = match &validation {
+        Some(validation) if validation.format == DataFormat::Tick => {
             // ── Tick data: streaming CSV → yearly Parquet (low memory) ──
             let tick_dir = symbol_dir.join("tick");
             let tick_raw_dir = symbol_dir.join("tick_raw");
 
             let (total_rows, start_date, end_date) = loader::stream_tick_csv_to_parquet(
                 &path,
-                &validation,
+                validation,
                 &tick_dir,
                 &tick_raw_dir,
                 tick_storage_format,
@@ -83,10 +104,39 @@ pub async fn upload_csv(
             timeframe_paths.insert("tick_raw".into(), tick_raw_dir.to_string_lossy().into());
 
             (total_rows, start_date, end_date, timeframe_paths)
-        } else {
+        }
+        _ => {
             // ── Bar data: standard flow (single CSV read) ──
             emit_progress(&app, 15, "Loading CSV data...");
-            let df = loader::load_csv_to_dataframe(&path, &validation, instrument_config.tz_offset_hours)?;
+            let df = match &validation {
+                Some(validation) => loader::load_csv_to_dataframe(
+                    &path,
+                    validation,
+                    instrument_config.tz_offset_hours,
+                )?,
+                None => importers::load_source_to_dataframe(
+                    &path,
+                    source_format,
+                    instrument_config.tz_offset_hours,
+                )?,
+            };
+            let df = loader::normalize_bar_timestamps(df, instrument_config.timestamp_is)?;
+
+            emit_progress(&app, 30, "Checking bar integrity...");
+            let (df, integrity) = validator::check_bar_integrity(df, repair_bars.unwrap_or(false))?;
+            if integrity.issue_count() > 0 {
+                warn!(
+                    "Bar integrity: {} inverted high/low, {} open out of range, {} close out of range, \
+                     {} with non-finite prices ({})",
+                    integrity.inverted_high_low,
+                    integrity.open_out_of_range,
+                    integrity.close_out_of_range,
+                    integrity.non_finite,
+                    if integrity.repaired { "repaired" } else { "imported as-is" }
+                );
+                let _ = app.emit("bar-integrity", &integrity);
+            }
+
             let total_rows = df.height();
             info!("Loaded {} rows from CSV", total_rows);
 
@@ -97,7 +147,8 @@ pub async fn upload_csv(
                 converter::generate_all_timeframes(&df, base_timeframe, &symbol_dir)?;
 
             (total_rows, start_date, end_date, timeframe_paths)
-        };
+        }
+    };
 
     // 6. Create symbol and store in DB
     emit_progress(&app, 90, "Saving to database...");
@@ -232,37 +283,78 @@ fn shift_date_string(date_str: &str, delta_ms: i64) -> String {
         .unwrap_or_else(|_| date_str.to_string())
 }
 
-/// Preview first N rows of a symbol's data for a given timeframe.
+/// Preview a page of `limit` rows, starting at `offset`, of a symbol's data for a
+/// given timeframe.
 #[tauri::command]
 pub async fn preview_data(
     state: tauri::State<'_, AppState>,
     symbol_id: String,
     timeframe: String,
+    offset: Option<usize>,
     limit: usize,
 ) -> Result<Vec<Value>, AppError> {
-    let db = state.db.lock().await;
-    let symbol = storage::get_symbol_by_id(&db, &symbol_id)?;
+    let mut symbol = {
+        let db = state.db.lock().await;
+        storage::get_symbol_by_id(&db, &symbol_id)?
+    };
+    let parquet_path = resolve_timeframe_path(&state, &mut symbol, &timeframe).await?;
 
-    let parquet_path = symbol
-        .timeframe_paths
-        .get(&timeframe)
-        .ok_or_else(|| {
-            AppError::NotFound(format!(
-                "Timeframe {} not available for {}",
-                timeframe, symbol.name
-            ))
-        })?;
-
-    // Lazy scan with row limit pushed down — avoids loading the full Parquet into memory.
-    let df = loader::scan_parquet_lazy(&PathBuf::from(parquet_path))?
-        .limit(limit as u32)
-        .collect()
-        .map_err(|e| AppError::Internal(format!("preview collect: {}", e)))?;
+    // Lazy scan with the slice pushed down — avoids loading the full Parquet into memory.
+    let df = loader::read_parquet_slice(&PathBuf::from(&parquet_path), offset.unwrap_or(0), limit)?;
 
     // Convert to Vec<serde_json::Value> for the frontend
     dataframe_to_json(&df)
 }
 
+/// Total number of rows stored for a symbol's timeframe (for paging previews).
+#[tauri::command]
+pub async fn count_rows(
+    state: tauri::State<'_, AppState>,
+    symbol_id: String,
+    timeframe: String,
+) -> Result<usize, AppError> {
+    let mut symbol = {
+        let db = state.db.lock().await;
+        storage::get_symbol_by_id(&db, &symbol_id)?
+    };
+    let parquet_path = resolve_timeframe_path(&state, &mut symbol, &timeframe).await?;
+    loader::count_parquet_rows(&PathBuf::from(&parquet_path))
+}
+
+/// Resolve the Parquet path stored for a timeframe key.
+///
+/// Custom timeframes (e.g. `"m3"`, `"h8"`) that have not been built yet are
+/// resampled from the symbol's stored data on first use; the new path is
+/// persisted so later requests hit the cached file.
+async fn resolve_timeframe_path(
+    state: &AppState,
+    symbol: &mut Symbol,
+    key: &str,
+) -> Result<String, AppError> {
+    if let Some(path) = symbol.timeframe_paths.get(key) {
+        return Ok(path.clone());
+    }
+
+    let not_available = || {
+        AppError::NotFound(format!("Timeframe {} not available for {}", key, symbol.name))
+    };
+    let tf = match key.parse::<Timeframe>() {
+        Ok(tf @ Timeframe::Custom { .. }) => tf,
+        _ => return Err(not_available()),
+    };
+    if let Some(path) = symbol.timeframe_paths.get(tf.as_str().as_ref()) {
+        return Ok(path.clone());
+    }
+
+    let symbol_dir = state.data_dir.join("symbols").join(&symbol.name);
+    let path = converter::resample_timeframe(&symbol.timeframe_paths, tf, &symbol_dir)?;
+    symbol.timeframe_paths.insert(tf.as_str().into_owned(), path.clone());
+
+    let db = state.db.lock().await;
+    storage::update_symbol_timeframe_paths(&db, &symbol.id, &symbol.timeframe_paths)?;
+    Ok(path)
+}
+
 /// Placeholder greet command (for testing communication).
 #[tauri::command]
 pub async fn greet(name: String) -> Result<String, AppError> {
@@ -271,6 +363,131 @@ pub async fn greet(name: String) -> Result<String, AppError> {
 
 // ── Backtest Commands ──
 
+/// Sub-bar timeframe and stored Parquet path for `M1TickSimulation`.
+/// The magnifier (M1 unless configured) must be M1, M5 or M15; an explicitly
+/// configured one must also be finer than the backtest timeframe.
+fn magnifier_path<'a>(
+    symbol: &'a Symbol,
+    config: &BacktestConfig,
+) -> Result<(Timeframe, &'a str), AppError> {
+    let magnifier = config.magnifier_timeframe.unwrap_or(Timeframe::M1);
+    if !matches!(magnifier, Timeframe::M1 | Timeframe::M5 | Timeframe::M15) {
+        return Err(AppError::InvalidConfig(format!(
+            "Magnifier timeframe must be M1, M5 or M15, got {}",
+            magnifier
+        )));
+    }
+    if config.magnifier_timeframe.is_some() && magnifier.minutes() >= config.timeframe.minutes() {
+        return Err(AppError::InvalidConfig(format!(
+            "Magnifier timeframe {} must be finer than the backtest timeframe {}",
+            magnifier, config.timeframe
+        )));
+    }
+    let path = symbol
+        .timeframe_paths
+        .get(magnifier.as_str().as_ref())
+        .ok_or_else(|| AppError::NotFound(format!("{} data not available for tick simulation", magnifier)))?;
+    Ok((magnifier, path))
+}
+
+/// Quote→account rates from a conversion symbol's `timeframe` closes; `invert` uses
+/// `1 / close`. Bars without a positive close are skipped.
+fn conversion_rates(candles: &[Candle], invert: bool, timeframe: Timeframe) -> ConversionRates {
+    let mut series = ConversionRates { bar_micros: timeframe.minutes() as i64 * 60_000_000, ..Default::default() };
+    for c in candles.iter().filter(|c| c.close > 0.0) {
+        series.timestamps.push(c.timestamp);
+        series.rates.push(if invert { 1.0 / c.close } else { c.close });
+    }
+    series
+}
+
+/// Start of the window conversion rates are loaded over: a week before `start_date`, so
+/// the first bars of the run already have a completed conversion bar behind them.
+fn conversion_window_start(start_date: &str) -> String {
+    start_date
+        .get(..10)
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|d| (d - chrono::Duration::days(7)).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Check the quote conversion of `config` and, for a symbol source, load its rates at
+/// the backtest timeframe from a week before `start_date` through `end_date`.
+async fn resolve_quote_conversion(
+    state: &AppState,
+    config: &mut BacktestConfig,
+    start_date: &str,
+    end_date: &str,
+) -> Result<(), AppError> {
+    match &mut config.quote_conversion {
+        None => Ok(()),
+        Some(QuoteConversion::Constant { rate }) => {
+            if rate.is_finite() && *rate > 0.0 {
+                Ok(())
+            } else {
+                Err(AppError::InvalidConfig(format!("Conversion rate must be positive, got {}", rate)))
+            }
+        }
+        Some(QuoteConversion::Symbol { symbol_id, .. }) if symbol_id.is_empty() => {
+            Err(AppError::InvalidConfig("Select a conversion symbol before running".into()))
+        }
+        Some(QuoteConversion::Symbol { symbol_id, invert, rates }) => {
+            let db = state.db.lock().await;
+            let mut symbol = storage::get_symbol_by_id(&db, symbol_id)?;
+            drop(db);
+
+            let path = resolve_timeframe_path(state, &mut symbol, &config.timeframe.as_str()).await?;
+            let mut lf = loader::scan_parquet_lazy(&PathBuf::from(&path))?;
+            if let Some(f) = loader::build_date_filter(&conversion_window_start(start_date), end_date) {
+                lf = lf.filter(f);
+            }
+            let df = lf.collect()
+                .map_err(|e| AppError::Internal(format!("conversion lazy collect: {}", e)))?;
+            let series = conversion_rates(&executor::candles_from_dataframe(&df)?, *invert, config.timeframe);
+            if series.rates.is_empty() {
+                return Err(AppError::InvalidConfig(format!(
+                    "Conversion symbol {} has no data in the backtest range",
+                    symbol.name
+                )));
+            }
+            info!("Quote conversion: {} rates from {}", series.rates.len(), symbol.name);
+            *rates = std::sync::Arc::new(series);
+            Ok(())
+        }
+    }
+}
+
+const RAW_TICKS_MISSING: &str =
+    "Raw tick data (bid/ask) not available. Re-import tick data to enable real spread mode.";
+
+/// Downgrade `RealTickRealSpread` to `RealTickCustomSpread` when the symbol has no raw
+/// bid/ask ticks and `fallback_on_missing_raw` is set, so the effective precision ends
+/// up in the results' `backtest_config`. Returns the warning to surface; without the
+/// flag a missing `tick_raw` stays an error.
+fn apply_precision_fallback(symbol: &Symbol, config: &mut BacktestConfig) -> Result<Option<String>, AppError> {
+    if config.precision != BacktestPrecision::RealTickRealSpread
+        || symbol.timeframe_paths.contains_key("tick_raw")
+    {
+        return Ok(None);
+    }
+    if !config.fallback_on_missing_raw {
+        return Err(AppError::NotFound(RAW_TICKS_MISSING.into()));
+    }
+    if !symbol.timeframe_paths.contains_key("tick") {
+        return Err(AppError::NotFound(format!(
+            "{} has neither raw nor custom-spread tick data",
+            symbol.name
+        )));
+    }
+    config.precision = BacktestPrecision::RealTickCustomSpread;
+    let msg = format!(
+        "{} has no raw bid/ask ticks: ran with RealTickCustomSpread using the strategy's spread instead of RealTickRealSpread.",
+        symbol.name
+    );
+    warn!("{}", msg);
+    Ok(Some(msg))
+}
+
 /// Load sub-bar data based on the precision mode and symbol configuration.
 /// Uses partitioned yearly Parquet files (skips irrelevant years entirely).
 /// Falls back to single-file lazy scan for backward compatibility with old imports.
@@ -287,21 +504,32 @@ fn load_sub_bar_data(
         BacktestPrecision::OpenPricesOnly | BacktestPrecision::SelectedTfOnly => Ok(SubBarData::None),
 
         BacktestPrecision::M1TickSimulation => {
-            let m1_path = symbol
-                .timeframe_paths
-                .get("m1")
-                .ok_or_else(|| AppError::NotFound("M1 data not available for tick simulation".into()))?;
-            let mut lf = loader::scan_parquet_lazy(&PathBuf::from(m1_path))?;
+            let (magnifier, sub_path) = magnifier_path(symbol, config)?;
+            let mut lf = loader::scan_parquet_lazy(&PathBuf::from(sub_path))?;
             if let Some(f) = &date_filter {
                 lf = lf.filter(f.clone());
             }
             let filtered_df = lf.collect()
-                .map_err(|e| AppError::Internal(format!("M1 lazy collect: {}", e)))?;
+                .map_err(|e| AppError::Internal(format!("{} lazy collect: {}", magnifier, e)))?;
             let candles = executor::candles_from_dataframe(&filtered_df)?;
-            info!("Loaded {} M1 sub-bars for tick simulation", candles.len());
+            info!("Loaded {} {} sub-bars for tick simulation", candles.len(), magnifier);
             Ok(SubBarData::Candles(candles))
         }
 
+        BacktestPrecision::RealTickCustomSpread if config.tick_chunk_bars.is_some() => {
+            let tick_path = symbol
+                .timeframe_paths
+                .get("tick")
+                .ok_or_else(|| AppError::NotFound("Tick data not available for custom spread mode".into()))?;
+            let half_spread = symbol.instrument_config.pips_to_price(strategy.trading_costs.spread_pips) / 2.0;
+            let chunk_bars = config.tick_chunk_bars.unwrap_or_default();
+            info!("Streaming ticks with custom spread in windows of {} bars", chunk_bars);
+            Ok(SubBarData::TickChunks {
+                source: Box::new(DiskTickSource { path: tick_path.clone(), half_spread: Some(half_spread), is_binary: false }),
+                chunk_bars,
+            })
+        }
+
         BacktestPrecision::RealTickCustomSpread => {
             let tick_path = symbol
                 .timeframe_paths
@@ -314,7 +542,7 @@ fn load_sub_bar_data(
                 &config.start_date,
                 &config.end_date,
             )?;
-            let half_spread = strategy.trading_costs.spread_pips * symbol.instrument_config.pip_size / 2.0;
+            let half_spread = symbol.instrument_config.pips_to_price(strategy.trading_costs.spread_pips) / 2.0;
             let ticks = executor::tick_columns_from_ohlcv_with_spread(&filtered_df, half_spread)?;
             info!("Loaded {} ticks as TickColumns with custom spread ({:.1} pips)", ticks.len(), strategy.trading_costs.spread_pips);
             Ok(SubBarData::Ticks(ticks))
@@ -324,7 +552,7 @@ fn load_sub_bar_data(
             let tick_raw_path = symbol
                 .timeframe_paths
                 .get("tick_raw")
-                .ok_or_else(|| AppError::NotFound("Raw tick data (bid/ask) not available. Re-import tick data to enable real spread mode.".into()))?;
+                .ok_or_else(|| AppError::NotFound(RAW_TICKS_MISSING.into()))?;
 
             // Auto-detect storage format by checking file extension of first file found
             let is_binary = std::path::Path::new(tick_raw_path.as_str()).is_dir() && {
@@ -336,6 +564,15 @@ fn load_sub_bar_data(
                     .unwrap_or(false)
             };
 
+            if let Some(chunk_bars) = config.tick_chunk_bars {
+                info!("Streaming raw ticks in windows of {} bars ({})",
+                    chunk_bars, if is_binary { "binary" } else { "parquet" });
+                return Ok(SubBarData::TickChunks {
+                    source: Box::new(DiskTickSource { path: tick_raw_path.clone(), half_spread: None, is_binary }),
+                    chunk_bars,
+                });
+            }
+
             let ticks = if is_binary {
                 executor::tick_columns_from_binary_dir(
                     tick_raw_path,
@@ -364,59 +601,60 @@ fn load_sub_bar_data(
     result
 }
 
+/// Reads one window of tick data from disk per call — backs `SubBarData::TickChunks`.
+struct DiskTickSource {
+    path: String,
+    /// `Some(half_spread)` for tick files stored as OHLCV (custom spread mode);
+    /// `None` for raw bid/ask data.
+    half_spread: Option<f64>,
+    is_binary: bool,
+}
+
+impl executor::TickSource for DiskTickSource {
+    fn load_range(&self, from_us: i64, to_us: i64) -> Result<TickColumns, AppError> {
+        let start = executor::micros_to_datetime_string(from_us);
+        let end = if to_us == i64::MAX { String::new() } else { executor::micros_to_datetime_string(to_us) };
+        let loaded = if self.is_binary {
+            executor::tick_columns_from_binary_dir(&self.path, &start, &end)
+        } else if let Some(half_spread) = self.half_spread {
+            loader::scan_tick_partitioned(&self.path, &["datetime", "close"], &start, &end)
+                .and_then(|df| executor::tick_columns_from_ohlcv_with_spread(&df, half_spread))
+        } else {
+            loader::scan_tick_partitioned(&self.path, &["datetime", "bid", "ask"], &start, &end)
+                .and_then(|df| executor::tick_columns_from_dataframe(&df))
+        };
+        match loaded {
+            // A window with no partition file (e.g. a gap year) simply has no ticks
+            Err(AppError::NoDataInRange) | Err(AppError::NotFound(_)) => Ok(TickColumns {
+                timestamps: Vec::new(),
+                bids: Vec::new(),
+                asks: Vec::new(),
+            }),
+            // Loaders filter by whole days or inclusive bounds — trim to the exact window
+            other => other.map(|ticks| ticks.retain_range(from_us, to_us)),
+        }
+    }
+}
+
 /// Run a backtest with the given strategy and configuration.
 #[tauri::command]
 pub async fn run_backtest(
     app: AppHandle,
     state: tauri::State<'_, AppState>,
     strategy: Strategy,
-    config: BacktestConfig,
+    mut config: BacktestConfig,
 ) -> Result<BacktestResults, AppError> {
     info!("Running backtest: strategy={}, symbol={}, precision={:?}",
         strategy.name, config.symbol_id, config.precision);
 
-    // Reset cancel flag
-    state.cancel_flag.store(false, Ordering::Relaxed);
-
-    // Load symbol to get instrument config and parquet path
-    let db = state.db.lock().await;
-    let symbol = storage::get_symbol_by_id(&db, &config.symbol_id)?;
-    drop(db); // Release lock before long operation
-
-    let timeframe_key = config.timeframe.as_str().to_string();
-    let parquet_path = symbol
-        .timeframe_paths
-        .get(&timeframe_key)
-        .ok_or_else(|| {
-            AppError::NotFound(format!(
-                "Timeframe {} not available for {}",
-                timeframe_key, symbol.name
-            ))
-        })?;
-
-    // Lazy-load parquet with date filter pushdown → only filtered rows materialized
-    let date_filter = loader::build_date_filter(&config.start_date, &config.end_date);
-    let mut lf = loader::scan_parquet_lazy(&PathBuf::from(parquet_path))?;
-    if let Some(f) = &date_filter {
-        lf = lf.filter(f.clone());
-    }
-    let df = lf.collect()
-        .map_err(|e| AppError::Internal(format!("candle lazy collect: {}", e)))?;
-    let candles = executor::candles_from_dataframe(&df)?;
-    if candles.is_empty() {
-        return Err(AppError::NoDataInRange);
-    }
-
-    info!("Backtest data: {} candles after date filter", candles.len());
-
-    // Load sub-bar data for precision mode
-    let sub_bars = load_sub_bar_data(&symbol, &strategy, &config)?;
+    let PreparedBacktest { candles, sub_bars, instrument, warnings: validation_warnings } =
+        prepare_backtest(&state, &strategy, &mut config).await?;
 
     // Run the backtest (blocking computation in async context)
     let cancel_flag = state.cancel_flag.clone();
-    let instrument = symbol.instrument_config.clone();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let mut result = tokio::task::spawn_blocking(move || {
+        let throttle = ProgressThrottle::new(PROGRESS_EVENT_INTERVAL);
         executor::run_backtest(
             &candles,
             &sub_bars,
@@ -425,6 +663,9 @@ pub async fn run_backtest(
             &instrument,
             &cancel_flag,
             |pct, current, total| {
+                if !throttle.should_emit(pct) {
+                    return;
+                }
                 let _ = app.emit(
                     "backtest-progress",
                     serde_json::json!({
@@ -438,6 +679,7 @@ pub async fn run_backtest(
     })
     .await
     .map_err(|e| AppError::BacktestExecution(format!("Task join error: {}", e)))??;
+    result.warnings.splice(0..0, validation_warnings);
 
     info!(
         "Backtest complete: {} trades, net profit: {:.2}",
@@ -448,6 +690,198 @@ pub async fn run_backtest(
     Ok(result)
 }
 
+/// Replay a backtest bar by bar: emits a `backtest-step` event with the new equity
+/// points and closed trades every `step_interval` bars, optionally pausing
+/// `step_delay_ms` after each one, then returns the full results. `cancel_backtest`
+/// stops the replay at the next step.
+#[tauri::command]
+pub async fn run_backtest_stepped(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    strategy: Strategy,
+    mut config: BacktestConfig,
+    step_interval: usize,
+    step_delay_ms: Option<u64>,
+) -> Result<BacktestResults, AppError> {
+    info!("Running stepped backtest: strategy={}, symbol={}, every {} bars",
+        strategy.name, config.symbol_id, step_interval);
+
+    let PreparedBacktest { candles, sub_bars, instrument, warnings: validation_warnings } =
+        prepare_backtest(&state, &strategy, &mut config).await?;
+
+    let cancel_flag = state.cancel_flag.clone();
+    let delay = step_delay_ms.filter(|&ms| ms > 0).map(Duration::from_millis);
+
+    let mut result = tokio::task::spawn_blocking(move || {
+        let on_step = |step: BacktestStep| {
+            let _ = app.emit("backtest-step", &step);
+            if let Some(delay) = delay {
+                std::thread::sleep(delay);
+            }
+        };
+        let observer = executor::StepObserver { interval: step_interval, on_step: &on_step };
+        executor::run_backtest_stepped(&candles, &sub_bars, &strategy, &config, &instrument, &cancel_flag, &observer)
+    })
+    .await
+    .map_err(|e| AppError::BacktestExecution(format!("Task join error: {}", e)))??;
+    result.warnings.splice(0..0, validation_warnings);
+
+    info!("Stepped backtest complete: {} trades", result.trades.len());
+    Ok(result)
+}
+
+/// Candles, sub-bar data and instrument for a single backtest, plus the validation and
+/// precision-fallback warnings collected while loading them.
+struct PreparedBacktest {
+    candles: Vec<Candle>,
+    sub_bars: SubBarData,
+    instrument: InstrumentConfig,
+    warnings: Vec<String>,
+}
+
+/// Validate the strategy, reset the backtest cancel flag and load everything a single
+/// backtest run needs. Resolves the quote conversion and precision fallback in `config`.
+async fn prepare_backtest(
+    state: &AppState,
+    strategy: &Strategy,
+    config: &mut BacktestConfig,
+) -> Result<PreparedBacktest, AppError> {
+    let mut validation_warnings = validation::ensure_valid(strategy)?;
+    let SymbolData { symbol, candles, precision_warning } = load_symbol_data(state, config).await?;
+
+    // Load sub-bar data for precision mode
+    validation_warnings.extend(precision_warning);
+    let sub_bars = load_sub_bar_data(&symbol, strategy, config)?;
+
+    Ok(PreparedBacktest {
+        candles,
+        sub_bars,
+        instrument: symbol.instrument_config.clone(),
+        warnings: validation_warnings,
+    })
+}
+
+/// Symbol and candles shared by every strategy backtested on one `BacktestConfig`.
+struct SymbolData {
+    symbol: Symbol,
+    candles: Vec<Candle>,
+    precision_warning: Option<String>,
+}
+
+/// Reset the backtest cancel flag and load the symbol and its candles for `config`'s
+/// date range. Resolves the quote conversion and precision fallback in `config`; sub-bar
+/// data is left to the caller since it depends on the strategy.
+async fn load_symbol_data(state: &AppState, config: &mut BacktestConfig) -> Result<SymbolData, AppError> {
+    // Reset cancel flag
+    state.cancel_flag.store(false, Ordering::Relaxed);
+
+    // Load symbol to get instrument config and parquet path
+    let db = state.db.lock().await;
+    let mut symbol = storage::get_symbol_by_id(&db, &config.symbol_id)?;
+    drop(db); // Release lock before long operation
+
+    let parquet_path =
+        resolve_timeframe_path(state, &mut symbol, &config.timeframe.as_str()).await?;
+
+    // Lazy-load parquet with date filter pushdown → only filtered rows materialized
+    let date_filter = loader::build_date_filter(&config.start_date, &config.end_date);
+    let mut lf = loader::scan_parquet_lazy(&PathBuf::from(&parquet_path))?;
+    if let Some(f) = &date_filter {
+        lf = lf.filter(f.clone());
+    }
+    let df = lf.collect()
+        .map_err(|e| AppError::Internal(format!("candle lazy collect: {}", e)))?;
+    let candles = executor::candles_from_dataframe(&df)?;
+    if candles.is_empty() {
+        return Err(AppError::NoDataInRange);
+    }
+
+    info!("Backtest data: {} candles after date filter", candles.len());
+
+    let (start_date, end_date) = (config.start_date.clone(), config.end_date.clone());
+    resolve_quote_conversion(state, config, &start_date, &end_date).await?;
+
+    let precision_warning = apply_precision_fallback(&symbol, config)?;
+    Ok(SymbolData { symbol, candles, precision_warning })
+}
+
+/// Run a list of strategies on the same symbol and date range and return one
+/// metrics row per strategy, in input order. Candles are loaded once; sub-bar data
+/// is loaded once per distinct custom spread (the only strategy input it depends on).
+#[tauri::command]
+pub async fn run_backtest_batch(
+    state: tauri::State<'_, AppState>,
+    strategies: Vec<Strategy>,
+    mut config: BacktestConfig,
+) -> Result<Vec<BatchBacktestResult>, AppError> {
+    info!("Running batch backtest: {} strategies, symbol={}, precision={:?}",
+        strategies.len(), config.symbol_id, config.precision);
+
+    if strategies.is_empty() {
+        return Err(AppError::InvalidConfig("No strategies to backtest".into()));
+    }
+    let mut validation_warnings = strategies
+        .iter()
+        .map(|s| {
+            validation::ensure_valid(s).map_err(|e| match e {
+                AppError::InvalidConfig(msg) => AppError::InvalidConfig(format!("{}: {}", s.name, msg)),
+                other => other,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let SymbolData { symbol, candles, precision_warning } = load_symbol_data(&state, &mut config).await?;
+    if let Some(w) = precision_warning {
+        validation_warnings.iter_mut().for_each(|ws| ws.push(w.clone()));
+    }
+
+    // Group strategies by the spread their sub-bar data is built with.
+    let mut groups: Vec<(f64, Vec<usize>)> = Vec::new();
+    for (i, s) in strategies.iter().enumerate() {
+        let key = if config.precision == BacktestPrecision::RealTickCustomSpread {
+            s.trading_costs.spread_pips
+        } else {
+            0.0
+        };
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push(i),
+            None => groups.push((key, vec![i])),
+        }
+    }
+
+    let cancel_flag = state.cancel_flag.clone();
+    let instrument = symbol.instrument_config.clone();
+
+    let results = tokio::task::spawn_blocking(move || -> Result<Vec<BatchBacktestResult>, AppError> {
+        let mut slots: Vec<Option<BatchBacktestResult>> = vec![None; strategies.len()];
+        for (_, members) in &groups {
+            let group: Vec<Strategy> = members.iter().map(|&i| strategies[i].clone()).collect();
+            let sub_bars = load_sub_bar_data(&symbol, &group[0], &config)?;
+            let rows = executor::run_backtest_batch(
+                &candles, &sub_bars, &group, &config, &instrument, &cancel_flag,
+            )?;
+            for (&i, row) in members.iter().zip(rows) {
+                slots[i] = Some(row);
+            }
+        }
+        Ok(slots
+            .into_iter()
+            .zip(validation_warnings)
+            .filter_map(|(row, warnings)| {
+                row.map(|mut r| {
+                    r.warnings.splice(0..0, warnings);
+                    r
+                })
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| AppError::BacktestExecution(format!("Task join error: {}", e)))??;
+
+    info!("Batch backtest complete: {} strategies", results.len());
+    Ok(results)
+}
+
 /// Cancel a running backtest.
 #[tauri::command]
 pub async fn cancel_backtest(
@@ -458,6 +892,56 @@ pub async fn cancel_backtest(
     Ok(())
 }
 
+// ── Saved Backtest Commands ──
+
+/// Save a completed backtest (results as returned by `run_backtest`) with the strategy
+/// that produced it. An empty `name` gets a generated one. Returns the listing metadata.
+#[tauri::command]
+pub async fn save_backtest_result(
+    state: tauri::State<'_, AppState>,
+    name: Option<String>,
+    strategy: Strategy,
+    results: serde_json::Value,
+) -> Result<storage::SavedBacktestMeta, AppError> {
+    let db = state.db.lock().await;
+    let symbol_id = results["backtest_config"]["symbol_id"].as_str().unwrap_or("");
+    let symbol_name = storage::get_symbol_by_id(&db, symbol_id)
+        .map(|s| s.name)
+        .unwrap_or_else(|_| symbol_id.to_string());
+    let saved = storage::SavedBacktest::new(name, strategy, results, &symbol_name);
+    storage::insert_backtest_result(&db, &saved)?;
+    Ok(saved.meta)
+}
+
+/// List saved backtests (metadata only), most recent first.
+#[tauri::command]
+pub async fn list_backtest_results(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<storage::SavedBacktestMeta>, AppError> {
+    let db = state.db.lock().await;
+    storage::get_all_backtest_results(&db)
+}
+
+/// Load a saved backtest with its strategy and full results.
+#[tauri::command]
+pub async fn load_backtest_result(
+    state: tauri::State<'_, AppState>,
+    result_id: String,
+) -> Result<storage::SavedBacktest, AppError> {
+    let db = state.db.lock().await;
+    storage::get_backtest_result_by_id(&db, &result_id)
+}
+
+/// Delete a saved backtest.
+#[tauri::command]
+pub async fn delete_backtest_result(
+    state: tauri::State<'_, AppState>,
+    result_id: String,
+) -> Result<(), AppError> {
+    let db = state.db.lock().await;
+    storage::delete_backtest_result_by_id(&db, &result_id)
+}
+
 // ── Strategy Commands ──
 
 /// Save a strategy (insert or update). Returns the strategy ID.
@@ -504,16 +988,80 @@ pub async fn delete_strategy(
     storage::delete_strategy_by_id(&db, &strategy_id)
 }
 
+/// Export a strategy, its backtest config and the symbol's metadata (not its data)
+/// as a versioned JSON bundle.
+#[tauri::command]
+pub async fn export_strategy_bundle(
+    state: tauri::State<'_, AppState>,
+    strategy: Strategy,
+    backtest_config: BacktestConfig,
+    file_path: String,
+) -> Result<(), AppError> {
+    let (symbol, conversion_symbol) = {
+        let db = state.db.lock().await;
+        let symbol = storage::get_symbol_by_id(&db, &backtest_config.symbol_id)?;
+        let conversion_symbol = match &backtest_config.quote_conversion {
+            Some(QuoteConversion::Symbol { symbol_id, .. }) => Some(storage::get_symbol_by_id(&db, symbol_id)?),
+            _ => None,
+        };
+        (symbol, conversion_symbol)
+    };
+    info!("Exporting strategy bundle '{}' to {}", strategy.name, file_path);
+    let bundle = bundle::StrategyBundle::new(strategy, backtest_config, &symbol, conversion_symbol.as_ref());
+    bundle::write_bundle(&bundle, &PathBuf::from(&file_path))
+}
+
+/// Result of importing a strategy bundle. Nothing is saved; the frontend decides
+/// whether to keep the strategy.
+#[derive(Debug, serde::Serialize)]
+pub struct ImportedBundle {
+    pub strategy: Strategy,
+    /// `symbol_id` and a quote-conversion `symbol_id` are remapped to the matching
+    /// local symbols, or left empty.
+    pub backtest_config: BacktestConfig,
+    /// Local symbol with the bundle's symbol name, if any.
+    pub symbol_id: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Import a strategy bundle, validating that its indicators run on this engine and
+/// mapping the bundled symbols to local ones by name.
+#[tauri::command]
+pub async fn import_strategy_bundle(
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+) -> Result<ImportedBundle, AppError> {
+    let mut bundle = bundle::read_bundle(&PathBuf::from(&file_path))?;
+    let symbols = {
+        let db = state.db.lock().await;
+        storage::get_all_symbols(&db)?
+    };
+    let warnings = bundle.remap_symbol_ids(&symbols);
+
+    info!(
+        "Imported strategy bundle '{}' ({} warnings)",
+        bundle.strategy.name,
+        warnings.len()
+    );
+    let symbol_id = Some(bundle.backtest_config.symbol_id.clone()).filter(|id| !id.is_empty());
+    Ok(ImportedBundle {
+        strategy: bundle.strategy,
+        symbol_id,
+        backtest_config: bundle.backtest_config,
+        warnings,
+    })
+}
+
 // ── Optimization Commands ──
 
-/// Run optimization (Grid Search or Genetic Algorithm).
+/// Run optimization (Grid Search, Genetic Algorithm or Random Search).
 #[tauri::command]
 pub async fn run_optimization(
     app: AppHandle,
     state: tauri::State<'_, AppState>,
     strategy: Strategy,
-    optimization_config: OptimizationConfig,
-) -> Result<Vec<OptimizationResult>, AppError> {
+    mut optimization_config: OptimizationConfig,
+) -> Result<OptimizationOutput, AppError> {
     info!(
         "Running {:?} optimization: {} parameter ranges, precision={:?}",
         optimization_config.method,
@@ -524,26 +1072,36 @@ pub async fn run_optimization(
     // Reset the optimization-specific cancel flag (separate from backtest cancel).
     state.optimization_cancel_flag.store(false, Ordering::Relaxed);
 
+    let bt = &optimization_config.backtest_config;
+    let oos = &optimization_config.oos_periods;
+    optimizer::validate_oos_periods(
+        &bt.start_date,
+        &bt.end_date,
+        oos,
+        optimization_config.allow_oos_overlap,
+    )?;
+
+    // Conversion rates must also cover the OOS periods
+    let start_date = oos.iter().map(|p| p.start_date.clone()).fold(bt.start_date.clone(), std::cmp::min);
+    let end_date = oos.iter().map(|p| p.end_date.clone()).fold(bt.end_date.clone(), std::cmp::max);
+    resolve_quote_conversion(&state, &mut optimization_config.backtest_config, &start_date, &end_date).await?;
+
     // Load symbol to get instrument config and parquet path
     let bt_config = &optimization_config.backtest_config;
     let db = state.db.lock().await;
-    let symbol = storage::get_symbol_by_id(&db, &bt_config.symbol_id)?;
+    let mut symbol = storage::get_symbol_by_id(&db, &bt_config.symbol_id)?;
     drop(db);
 
-    let timeframe_key = bt_config.timeframe.as_str().to_string();
-    let parquet_path = symbol
-        .timeframe_paths
-        .get(&timeframe_key)
-        .ok_or_else(|| {
-            AppError::NotFound(format!(
-                "Timeframe {} not available for {}",
-                timeframe_key, symbol.name
-            ))
-        })?;
+    // Applies to the OOS runs too, which clone this config
+    let precision_warning = apply_precision_fallback(&symbol, &mut optimization_config.backtest_config)?;
+    let bt_config = &optimization_config.backtest_config;
+
+    let parquet_path =
+        resolve_timeframe_path(&state, &mut symbol, &bt_config.timeframe.as_str()).await?;
 
     // Lazy-load parquet with date filter pushdown → only filtered rows materialized
     let date_filter = loader::build_date_filter(&bt_config.start_date, &bt_config.end_date);
-    let mut lf = loader::scan_parquet_lazy(&PathBuf::from(parquet_path))?;
+    let mut lf = loader::scan_parquet_lazy(&PathBuf::from(&parquet_path))?;
     if let Some(f) = &date_filter {
         lf = lf.filter(f.clone());
     }
@@ -564,7 +1122,7 @@ pub async fn run_optimization(
     let mut oos_data: Vec<(String, Vec<crate::models::candle::Candle>, SubBarData)> = Vec::new();
     for period in &oos_periods {
         let oos_date_filter = loader::build_date_filter(&period.start_date, &period.end_date);
-        let mut oos_lf = loader::scan_parquet_lazy(&PathBuf::from(parquet_path))?;
+        let mut oos_lf = loader::scan_parquet_lazy(&PathBuf::from(&parquet_path))?;
         if let Some(f) = &oos_date_filter {
             oos_lf = oos_lf.filter(f.clone());
         }
@@ -584,14 +1142,21 @@ pub async fn run_optimization(
 
     let cancel_flag = state.optimization_cancel_flag.clone();
     let instrument = symbol.instrument_config.clone();
+    let keep_all = optimization_config.keep_all_results;
+    let param_names: Vec<String> = optimization_config.parameter_ranges.iter().map(|r| r.display_name.clone()).collect();
+    let oos_labels: Vec<String> = oos_periods.iter().map(|p| p.label.clone()).collect();
 
-    let result = tokio::task::spawn_blocking(move || {
+    let (mut result, retained) = tokio::task::spawn_blocking(move || {
         let bt_config = &optimization_config.backtest_config;
         let ranges = &optimization_config.parameter_ranges;
         let objectives = &optimization_config.objectives;
 
         let opt_start = std::time::Instant::now();
+        let throttle = ProgressThrottle::new(PROGRESS_EVENT_INTERVAL);
         let progress_cb = |pct: u8, current: usize, total: usize, best: f64| {
+            if !throttle.should_emit(pct) {
+                return;
+            }
             let eta = if pct > 2 && pct < 100 {
                 let elapsed = opt_start.elapsed().as_secs_f64();
                 let remaining = (elapsed / pct as f64) * (100.0 - pct as f64);
@@ -611,8 +1176,9 @@ pub async fn run_optimization(
             );
         };
 
-        let mut results = match optimization_config.method {
-            OptimizationMethod::GridSearch => optimizer::run_grid_search(
+        let mut sensitivity_report = None;
+        let mut all = match optimization_config.method {
+            OptimizationMethod::GridSearch => optimizer::run_grid_search_all(
                 &candles,
                 &sub_bars,
                 &strategy,
@@ -622,14 +1188,18 @@ pub async fn run_optimization(
                 objectives,
                 &cancel_flag,
                 progress_cb,
-            ),
+            )
+            .inspect(|all| {
+                // Sensitivity needs every evaluated combination, not just the top N
+                sensitivity_report = Some(sensitivity::compute_sensitivity(all, ranges));
+            }),
             OptimizationMethod::GeneticAlgorithm => {
                 let ga_config = optimization_config.ga_config.as_ref().ok_or_else(|| {
                     AppError::OptimizationError(
                         "Genetic Algorithm config required".into(),
                     )
                 })?;
-                optimizer::run_genetic_algorithm(
+                optimizer::run_genetic_algorithm_all(
                     &candles,
                     &sub_bars,
                     &strategy,
@@ -642,8 +1212,35 @@ pub async fn run_optimization(
                     progress_cb,
                 )
             }
+            OptimizationMethod::RandomSearch => {
+                let rs_config = optimization_config.random_search_config.as_ref().ok_or_else(|| {
+                    AppError::OptimizationError(
+                        "Random Search config required".into(),
+                    )
+                })?;
+                optimizer::run_random_search_all(
+                    &candles,
+                    &sub_bars,
+                    &strategy,
+                    bt_config,
+                    &instrument,
+                    ranges,
+                    objectives,
+                    rs_config,
+                    &cancel_flag,
+                    progress_cb,
+                )
+            }
         }?;
 
+        // The UI gets the top N; the full set is only kept when asked for
+        let mut results = if keep_all {
+            all.iter().take(optimizer::MAX_RESULTS).cloned().collect()
+        } else {
+            all.truncate(optimizer::MAX_RESULTS);
+            std::mem::take(&mut all)
+        };
+
         // Run OOS evaluation for each top result
         if !oos_data.is_empty() && !results.is_empty() {
             info!("Running OOS evaluation: {} results × {} periods", results.len(), oos_data.len());
@@ -655,6 +1252,10 @@ pub async fn run_optimization(
                     info!("OOS evaluation cancelled by user");
                     break;
                 }
+                // Combinations that failed in-sample have nothing to validate
+                if opt_result.failure_reason.is_some() {
+                    continue;
+                }
 
                 // Reconstruct the strategy with this result's params.
                 // If a parameter name is missing, skip this result rather than
@@ -723,15 +1324,57 @@ pub async fn run_optimization(
             }
         }
 
-        Ok::<Vec<OptimizationResult>, AppError>(results)
+        // Copy the OOS metrics into the retained set; drop equity curves below the top N
+        let retained = keep_all.then(|| {
+            all[..results.len()].clone_from_slice(&results);
+            all.iter_mut().skip(results.len()).for_each(|r| r.equity_curve = Vec::new());
+            all
+        });
+
+        let cancelled = cancel_flag.load(std::sync::atomic::Ordering::Relaxed);
+        Ok::<_, AppError>((
+            OptimizationOutput {
+                results,
+                sensitivity: sensitivity_report,
+                retained_count: None,
+                cancelled,
+                warnings: Vec::new(),
+            },
+            retained,
+        ))
     })
     .await
     .map_err(|e| AppError::OptimizationError(format!("Task join error: {}", e)))??;
 
-    info!("Optimization complete: {} results", result.len());
+    result.retained_count = retained.as_ref().map(Vec::len);
+    result.warnings.extend(precision_warning);
+    *state.last_optimization.lock().await = retained.map(|results| RetainedOptimization { param_names, oos_labels, results });
+
+    if result.cancelled {
+        info!("Optimization cancelled: returning {} partial results", result.results.len());
+    } else {
+        info!("Optimization complete: {} results", result.results.len());
+    }
     Ok(result)
 }
 
+/// Export every combination retained by the last `keep_all_results` optimization
+/// (parameters, all metrics and OOS metrics) to a CSV file.
+#[tauri::command]
+pub async fn export_optimization_csv(
+    state: tauri::State<'_, AppState>,
+    file_path: String,
+) -> Result<(), AppError> {
+    let retained = state.last_optimization.lock().await;
+    let run = retained.as_ref().ok_or_else(|| {
+        AppError::OptimizationError("No full optimization results to export: run the optimization with all results kept".into())
+    })?;
+    info!("Exporting {} optimization results to CSV: {}", run.results.len(), file_path);
+    export::write_optimization_csv(&run.results, &run.param_names, &run.oos_labels, &PathBuf::from(&file_path))?;
+    info!("Optimization results exported successfully");
+    Ok(())
+}
+
 /// Cancel a running optimization.
 #[tauri::command]
 pub async fn cancel_optimization(
@@ -754,7 +1397,7 @@ pub async fn run_walk_forward(
     app: AppHandle,
     state: tauri::State<'_, AppState>,
     strategy: Strategy,
-    wf_config: WalkForwardConfig,
+    mut wf_config: WalkForwardConfig,
 ) -> Result<WalkForwardResult, AppError> {
     info!(
         "Running Walk-Forward Analysis: {} windows, IS={:.0}%, strategy={}",
@@ -765,20 +1408,17 @@ pub async fn run_walk_forward(
 
     state.optimization_cancel_flag.store(false, Ordering::Relaxed);
 
+    let bt = &wf_config.optimization_config.backtest_config;
+    let (start_date, end_date) = (bt.start_date.clone(), bt.end_date.clone());
+    resolve_quote_conversion(&state, &mut wf_config.optimization_config.backtest_config, &start_date, &end_date).await?;
+
     let bt_config = &wf_config.optimization_config.backtest_config;
     let db = state.db.lock().await;
-    let symbol = storage::get_symbol_by_id(&db, &bt_config.symbol_id)?;
+    let mut symbol = storage::get_symbol_by_id(&db, &bt_config.symbol_id)?;
     drop(db);
 
-    let timeframe_key = bt_config.timeframe.as_str().to_string();
-    let parquet_path = symbol
-        .timeframe_paths
-        .get(&timeframe_key)
-        .ok_or_else(|| AppError::NotFound(format!(
-            "Timeframe {} not available for {}",
-            timeframe_key, symbol.name
-        )))?
-        .clone();
+    let parquet_path =
+        resolve_timeframe_path(&state, &mut symbol, &bt_config.timeframe.as_str()).await?;
 
     let date_filter = loader::build_date_filter(&bt_config.start_date, &bt_config.end_date);
     let mut lf = loader::scan_parquet_lazy(&PathBuf::from(&parquet_path))?;
@@ -798,6 +1438,7 @@ pub async fn run_walk_forward(
     let instrument = symbol.instrument_config.clone();
 
     let result = tokio::task::spawn_blocking(move || {
+        let throttle = ProgressThrottle::new(PROGRESS_EVENT_INTERVAL);
         walk_forward::run_walk_forward(
             &candles,
             &strategy,
@@ -805,6 +1446,9 @@ pub async fn run_walk_forward(
             &instrument,
             &cancel_flag,
             |pct, current, total| {
+                if !throttle.should_emit(pct) {
+                    return;
+                }
                 let _ = app.emit(
                     "walk-forward-progress",
                     serde_json::json!({
@@ -865,16 +1509,30 @@ pub async fn run_monte_carlo(
     Ok(result)
 }
 
+/// Bucket realized trade PnL by entry hour and weekday.
+#[tauri::command]
+pub async fn seasonality_report(trades: Vec<TradeResult>) -> Result<SeasonalityReport, AppError> {
+    info!("Computing seasonality report for {} trades", trades.len());
+    Ok(metrics::seasonality_report(&trades))
+}
+
 // ── Export Commands ──
 
 /// Export trades to a CSV file.
+///
+/// With `journal` set, writes the detailed trade journal (R-multiples, running equity,
+/// drawdown); without it, the simple trade list.
 #[tauri::command]
 pub async fn export_trades_csv(
     trades: Vec<TradeResult>,
     file_path: String,
+    journal: Option<export::TradeJournalOptions>,
 ) -> Result<(), AppError> {
     info!("Exporting {} trades to CSV: {}", trades.len(), file_path);
-    export::write_trades_csv(&trades, &PathBuf::from(&file_path))?;
+    match journal {
+        Some(options) => export::write_trade_journal_csv(&trades, &options, &PathBuf::from(&file_path))?,
+        None => export::write_trades_csv(&trades, &PathBuf::from(&file_path))?,
+    }
     info!("Trades exported successfully");
     Ok(())
 }
@@ -903,6 +1561,20 @@ pub async fn export_report_html(
     Ok(())
 }
 
+/// Export the equity curve (and optionally the drawdown curve) as a PNG image.
+#[tauri::command]
+pub async fn export_equity_png(
+    results: BacktestResults,
+    file_path: String,
+    options: Option<chart_png::EquityPngOptions>,
+) -> Result<(), AppError> {
+    let options = options.unwrap_or_default();
+    info!("Exporting {}x{} equity curve PNG to: {}", options.width, options.height, file_path);
+    chart_png::write_equity_png(&results, &options, &PathBuf::from(&file_path))?;
+    info!("Equity curve PNG exported successfully");
+    Ok(())
+}
+
 /// Export raw tick data for a symbol to a CSV file in MetaTrader 5 import format.
 ///
 /// MT5 format: `Date,Bid,Ask,Last,Volume,Flags`
@@ -934,35 +1606,93 @@ pub async fn export_tick_data_mt5(
 
 // ── Code Generation Commands ──
 
+/// Instrument config of the symbol the generated code targets, if one was given.
+async fn codegen_instrument(
+    state: &AppState,
+    symbol_id: Option<&str>,
+) -> Result<Option<InstrumentConfig>, AppError> {
+    let Some(symbol_id) = symbol_id else { return Ok(None) };
+    let db = state.db.lock().await;
+    Ok(Some(storage::get_symbol_by_id(&db, symbol_id)?.instrument_config))
+}
+
 /// Generate strategy code for MQL5 or PineScript.
+///
+/// `account` carries the backtest's initial capital and leverage into the generated
+/// code; defaults to 10 000 without leverage. `symbol_id` carries the symbol's pip size
+/// into the points-per-pip input; without it the code infers pips from quote digits.
+/// `emit_alerts` adds webhook `alert()` calls to the PineScript export (ignored for
+/// MQL5). `verification_samples` embeds the engine's indicator values on the last few
+/// of these candles for parity checks.
 #[tauri::command]
 pub async fn generate_strategy_code(
+    state: tauri::State<'_, AppState>,
     language: String,
     strategy: Strategy,
+    account: Option<codegen::AccountSettings>,
+    symbol_id: Option<String>,
+    emit_alerts: Option<bool>,
+    verification_samples: Option<Vec<Candle>>,
 ) -> Result<codegen::CodeGenerationResult, AppError> {
     info!("Generating {} code for strategy: {}", language, strategy.name);
+    let warnings = validation::ensure_valid(&strategy)?;
+    let account = account.unwrap_or_default();
+    let instrument = codegen_instrument(&state, symbol_id.as_deref()).await?;
 
-    let result = match language.to_lowercase().as_str() {
-        "mql5" => codegen::generate_mql5(&strategy)?,
-        "pinescript" => codegen::generate_pinescript(&strategy)?,
+    let mut result = match language.to_lowercase().as_str() {
+        "mql5" => codegen::generate_mql5(&strategy, &account, instrument.as_ref())?,
+        "pinescript" => codegen::generate_pinescript(&strategy, &account, instrument.as_ref(), emit_alerts.unwrap_or(false))?,
         _ => return Err(AppError::InvalidConfig(format!(
             "Unsupported language: {}. Use 'mql5' or 'pinescript'",
             language
         ))),
     };
 
+    if let Some(candles) = verification_samples.filter(|c| !c.is_empty()) {
+        codegen::embed_verification_samples(&mut result, &strategy, &candles)?;
+    }
+    result.warnings.splice(0..0, warnings);
+
     let total_lines: usize = result.files.iter().map(|f| f.code.lines().count()).sum();
     info!("Code generation complete: {} files, {} total lines", result.files.len(), total_lines);
     Ok(result)
 }
 
+/// Save generated code files (EA/script + `BT_*` indicators) into the directory
+/// `path`, or into a single zip archive at `path` when `zip` is set. Returns the
+/// written paths.
+#[tauri::command]
+pub async fn save_generated_code(
+    result: codegen::CodeGenerationResult,
+    path: String,
+    zip: Option<bool>,
+) -> Result<Vec<String>, AppError> {
+    if result.files.is_empty() {
+        return Err(AppError::InvalidConfig("No generated files to save".into()));
+    }
+    let path = PathBuf::from(&path);
+    let written = if zip.unwrap_or(false) {
+        info!("Saving {} generated files to zip: {}", result.files.len(), path.display());
+        export::write_code_zip(&result.files, &path)?;
+        vec![path]
+    } else {
+        info!("Saving {} generated files to {}", result.files.len(), path.display());
+        export::write_code_files(&result.files, &path)?
+    };
+    Ok(written.iter().map(|p| p.to_string_lossy().into_owned()).collect())
+}
+
 // ── Helpers ──
 
-/// Emit conversion progress to the frontend.
 // ── Download Commands ──
 
+/// Upper bound for parallel Dukascopy fetches per download.
+const MAX_DOWNLOAD_CONCURRENCY: usize = 32;
+
 /// Download historical tick data from Dukascopy servers and import it.
 /// `base_timeframe` can be "tick" (raw ticks) or "m1" (aggregate to 1-minute OHLCV bars).
+/// `concurrency` hourly files are fetched in parallel, starting at most
+/// `max_requests_per_second` requests per second (0 = unlimited).
 #[tauri::command]
 pub async fn download_dukascopy(
     app: AppHandle,
@@ -980,6 +1710,8 @@ pub async fn download_dukascopy(
     ignore_flats: Option<bool>,
     retry_on_empty: Option<bool>,
     use_cache: Option<bool>,
+    concurrency: Option<usize>,
+    max_requests_per_second: Option<f64>,
 ) -> Result<Symbol, AppError> {
     use crate::data::dukascopy;
 
@@ -1009,6 +1741,22 @@ pub async fn download_dukascopy(
         )));
     }
 
+    let defaults = dukascopy::DownloadOptions::default();
+    let concurrency = concurrency.unwrap_or(defaults.max_concurrent);
+    if !(1..=MAX_DOWNLOAD_CONCURRENCY).contains(&concurrency) {
+        return Err(AppError::InvalidConfig(format!(
+            "Invalid concurrency: {} (must be between 1 and {})",
+            concurrency, MAX_DOWNLOAD_CONCURRENCY
+        )));
+    }
+    let max_requests_per_second = max_requests_per_second.unwrap_or(defaults.max_requests_per_second);
+    if !max_requests_per_second.is_finite() || max_requests_per_second < 0.0 {
+        return Err(AppError::InvalidConfig(format!(
+            "Invalid max_requests_per_second: {} (must be >= 0, 0 = unlimited)",
+            max_requests_per_second
+        )));
+    }
+
     // Build download options
     let effective_use_cache = use_cache.unwrap_or(true);
     let dl_opts = std::sync::Arc::new(dukascopy::DownloadOptions {
@@ -1020,7 +1768,9 @@ pub async fn download_dukascopy(
         } else {
             None
         },
-        ..dukascopy::DownloadOptions::default()
+        max_concurrent: concurrency,
+        max_requests_per_second,
+        ..defaults
     });
 
     // Generate symbol ID early so we can insert a pending entry before downloading
@@ -1040,6 +1790,8 @@ pub async fn download_dukascopy(
         "ignore_flats": ignore_flats,
         "retry_on_empty": retry_on_empty,
         "use_cache": use_cache,
+        "concurrency": concurrency,
+        "max_requests_per_second": max_requests_per_second,
     });
     let pending_symbol = Symbol {
         id: symbol_id.clone(),
@@ -1143,6 +1895,9 @@ pub async fn download_dukascopy(
                         delimiter: b',',
                         row_count_sample: 0,
                         column_count: 4,
+                        datetime_format: validator::DateTimeFormat::Pattern("%Y-%m-%d %H:%M:%S%.f"),
+                        datetime_layout: validator::DateTimeLayout::Combined { column: 0 },
+                        column_mapping: None,
                     };
 
                     let app_clone = app.clone();
@@ -1275,6 +2030,8 @@ pub async fn cancel_download(
 // ── License Commands ──
 
 /// Validate a license key and optionally save credentials.
+/// If the server is unreachable, the last-known tier of matching saved credentials
+/// is kept for up to `OFFLINE_GRACE_DAYS`.
 #[tauri::command]
 pub async fn validate_license(
     state: tauri::State<'_, AppState>,
@@ -1282,19 +2039,26 @@ pub async fn validate_license(
     license_key: String,
     remember: bool,
 ) -> Result<crate::license::LicenseResponse, AppError> {
+    let now = chrono::Utc::now().timestamp();
     let response = crate::license::validate_license(&username, &license_key).await;
+    let saved = crate::license::load_credentials(&state.data_dir);
+    let response = crate::license::apply_offline_grace(response, saved.as_ref(), &username, &license_key, now);
 
     if response.valid {
         // Update the tier in app state
         let mut tier = state.license_tier.lock().await;
         *tier = response.tier;
 
-        // Persist credentials if "remember me" is checked
-        if remember {
-            crate::license::save_credentials(&state.data_dir, &username, &license_key)?;
-        } else {
-            // If not remembering, clear any previously saved credentials
-            crate::license::clear_credentials(&state.data_dir)?;
+        // Only an online confirmation refreshes the saved credentials — an offline
+        // grace hit must not extend its own window.
+        if response.status == crate::license::ValidationStatus::Valid {
+            // Persist credentials if "remember me" is checked
+            if remember {
+                crate::license::save_credentials(&state.data_dir, &username, &license_key, response.tier, now)?;
+            } else {
+                // If not remembering, clear any previously saved credentials
+                crate::license::clear_credentials(&state.data_dir)?;
+            }
         }
     }
 
@@ -1311,6 +2075,9 @@ pub async fn load_saved_license(
     return Ok(Some(crate::license::SavedCredentials {
         username: "laubil".to_string(),
         license_key: "LBQ-CEYZ-VGPE-ZDW9".to_string(),
+        last_validated_at: None,
+        last_tier: None,
+        signature: None,
     }));
 
     #[cfg(not(debug_assertions))]
@@ -1330,6 +2097,8 @@ pub async fn clear_license(
 
 /// Start background license monitor that re-validates every hour.
 /// Emits "license-tier-changed" event if the tier changes.
+/// A rejected key drops to Free immediately; an unreachable server keeps the
+/// last-known tier until the offline grace period runs out.
 #[tauri::command]
 pub async fn start_license_monitor(
     app: AppHandle,
@@ -1349,12 +2118,23 @@ pub async fn start_license_monitor(
                 None => continue,
             };
 
+            let now = chrono::Utc::now().timestamp();
             let response =
                 crate::license::validate_license(&creds.username, &creds.license_key).await;
-            let new_tier = if response.valid {
-                response.tier
-            } else {
-                crate::license::LicenseTier::Free
+            let new_tier = match response.status {
+                crate::license::ValidationStatus::Valid => {
+                    if let Err(e) = crate::license::record_validation(&data_dir, response.tier, now) {
+                        tracing::warn!("Failed to record license validation: {}", e);
+                    }
+                    response.tier
+                }
+                crate::license::ValidationStatus::Invalid => crate::license::LicenseTier::Free,
+                crate::license::ValidationStatus::Unreachable => {
+                    let cached = crate::license::apply_offline_grace(
+                        response, Some(&creds), &creds.username, &creds.license_key, now,
+                    );
+                    if cached.valid { cached.tier } else { crate::license::LicenseTier::Free }
+                }
             };
 
             let mut current = license_tier.lock().await;
@@ -1432,6 +2212,44 @@ fn emit_progress(app: &AppHandle, percent: u8, message: &str) {
     );
 }
 
+/// Minimum wall-clock gap between two progress events of one long-running task.
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wall-clock throttle for engine progress callbacks, so huge runs don't flood the
+/// Tauri event channel. Each payload carries the full state (percent, counters, best),
+/// so dropping an update coalesces it into the next one that gets through.
+/// The 100% event always passes. Lock-free: optimizer callbacks fire from rayon threads.
+struct ProgressThrottle {
+    start: Instant,
+    interval_ms: u64,
+    /// Milliseconds since `start` of the last emitted event, `u64::MAX` before the first.
+    last_ms: AtomicU64,
+}
+
+impl ProgressThrottle {
+    fn new(interval: Duration) -> Self {
+        Self { start: Instant::now(), interval_ms: interval.as_millis() as u64, last_ms: AtomicU64::new(u64::MAX) }
+    }
+
+    /// Whether an event at `pct` should be emitted now.
+    fn should_emit(&self, pct: u8) -> bool {
+        self.should_emit_at(pct, self.start.elapsed())
+    }
+
+    fn should_emit_at(&self, pct: u8, elapsed: Duration) -> bool {
+        if pct >= 100 {
+            return true;
+        }
+        let now = elapsed.as_millis() as u64;
+        let last = self.last_ms.load(Ordering::Relaxed);
+        if last != u64::MAX && now < last.saturating_add(self.interval_ms) {
+            return false;
+        }
+        // Only one of several racing threads wins the slot
+        self.last_ms.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+    }
+}
+
 /// Convert a DataFrame to a Vec of JSON objects for the frontend.
 ///
 /// Uses columnar access: each column is scanned once sequentially, which is
@@ -1624,6 +2442,21 @@ pub async fn start_builder(
         // Eliminates ~60-80% of wasted compute on zero-trade random strategies.
         early_stop_no_trades_pct: Some(0.30),
         pending_order_expiry_bars: None,
+        use_heikin_ashi: false,
+        equity_sampling: None,
+        tick_chunk_bars: None,
+        compounding: true,
+        stop_out_equity: None,
+        magnifier_timeframe: None,
+        account_currency: None,
+        quote_conversion: None,
+        warmup_bars: None,
+        exit_priority: ExitPriority::default(),
+        fallback_on_missing_raw: false,
+        drawdown_basis: DrawdownBasis::default(),
+        overtrading_threshold: None,
+        regime: None,
+        slippage_seed: None,
     };
 
     // Channel + drain thread: builder sends progress events through a channel,
@@ -1909,7 +2742,7 @@ fn load_sr_sub_bar_data(
                 &config.start_date,
                 &config.end_date,
             )?;
-            let half_spread = config.trading_costs.spread_pips * symbol.instrument_config.pip_size / 2.0;
+            let half_spread = symbol.instrument_config.pips_to_price(config.trading_costs.spread_pips) / 2.0;
             let ticks = executor::tick_columns_from_ohlcv_with_spread(&filtered_df, half_spread)?;
             info!("SR: Loaded {} ticks with custom spread ({:.1} pips)", ticks.len(), config.trading_costs.spread_pips);
             Ok(SubBarData::Ticks(ticks))
@@ -1966,7 +2799,7 @@ pub async fn run_sr_backtest(
     initial_capital: f64,
 ) -> Result<BacktestResults, AppError> {
     use crate::engine::sr::runner::{build_sr_cache_pub, build_atr_series_pub, sr_backtest_full};
-    use crate::models::strategy::{BacktestConfig, BacktestPrecision};
+    use crate::models::strategy::{BacktestConfig, BacktestPrecision, DrawdownBasis, ExitPriority};
 
     let db = state.db.lock().await;
     let symbol = storage::get_symbol_by_id(&db, &symbol_id)?;
@@ -2000,11 +2833,26 @@ pub async fn run_sr_backtest(
         precision: BacktestPrecision::SelectedTfOnly,
         early_stop_no_trades_pct: None,
         pending_order_expiry_bars: None,
+        use_heikin_ashi: false,
+        equity_sampling: None,
+        tick_chunk_bars: None,
+        compounding: true,
+        stop_out_equity: None,
+        magnifier_timeframe: None,
+        account_currency: None,
+        quote_conversion: None,
+        warmup_bars: None,
+        exit_priority: ExitPriority::default(),
+        fallback_on_missing_raw: false,
+        drawdown_basis: DrawdownBasis::default(),
+        overtrading_threshold: None,
+        regime: None,
+        slippage_seed: None,
     };
 
     // Build a minimal pool from the leaves used in the strategy trees
     let pool = collect_leaves_from_strategy(&strategy);
-    let cache = build_sr_cache_pub(&pool, &candles)?;
+    let cache = build_sr_cache_pub(&pool, &candles, instrument.session_start_hour)?;
     let atr = build_atr_series_pub(&candles);
     let atr_arc = std::sync::Arc::new(atr);
 
@@ -2063,14 +2911,18 @@ pub async fn cancel_sr_builder(
     Ok(())
 }
 
-/// Generate MQL5 code for an SR strategy.
+/// Generate MQL5 code for an SR strategy. `symbol_id` works like in
+/// [`generate_strategy_code`].
 #[tauri::command]
 pub async fn generate_sr_code(
+    state: tauri::State<'_, AppState>,
     strategy: crate::models::sr_result::SrStrategy,
     name: String,
+    symbol_id: Option<String>,
 ) -> Result<codegen::CodeGenerationResult, AppError> {
     info!("Generating MQL5 code for SR strategy: {}", name);
-    codegen::generate_sr_mql5(&strategy, &name)
+    let instrument = codegen_instrument(&state, symbol_id.as_deref()).await?;
+    codegen::generate_sr_mql5(&strategy, &name, instrument.as_ref())
 }
 
 // ── SR Session Persistence Commands ──
@@ -2210,3 +3062,167 @@ pub async fn export_sr_results_csv(
     info!("Exported {} SR items to CSV: {}", front.len(), file_path);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_throttle_coalesces_rapid_updates() {
+        let throttle = ProgressThrottle::new(Duration::from_millis(100));
+        // One call per millisecond for one second → first call, then one per 100ms
+        let emitted = (0..1000u64)
+            .filter(|&ms| throttle.should_emit_at((ms / 11) as u8, Duration::from_millis(ms)))
+            .count();
+        assert_eq!(emitted, 10);
+        // Updates inside the interval are dropped until it elapses
+        assert!(!throttle.should_emit_at(95, Duration::from_millis(950)));
+        assert!(!throttle.should_emit_at(96, Duration::from_millis(999)));
+        assert!(throttle.should_emit_at(97, Duration::from_millis(1000)));
+        assert!(!throttle.should_emit_at(98, Duration::from_millis(1050)));
+        // ...but the final 100% always gets through, even inside the interval
+        assert!(throttle.should_emit_at(100, Duration::from_millis(1051)));
+    }
+
+    fn symbol_with_paths(keys: &[&str]) -> Symbol {
+        Symbol {
+            id: "sym".into(),
+            name: "EURUSD".into(),
+            base_timeframe: Timeframe::M1,
+            upload_date: String::new(),
+            total_rows: 0,
+            start_date: String::new(),
+            end_date: String::new(),
+            timeframe_paths: keys.iter().map(|k| (k.to_string(), format!("/data/{k}.parquet"))).collect(),
+            instrument_config: InstrumentConfig::default(),
+            status: "complete".into(),
+            download_params: None,
+        }
+    }
+
+    fn config(timeframe: Timeframe, magnifier: Option<Timeframe>) -> BacktestConfig {
+        BacktestConfig {
+            symbol_id: "sym".into(),
+            timeframe,
+            start_date: String::new(),
+            end_date: String::new(),
+            initial_capital: 10_000.0,
+            leverage: 1.0,
+            precision: BacktestPrecision::M1TickSimulation,
+            early_stop_no_trades_pct: None,
+            pending_order_expiry_bars: None,
+            use_heikin_ashi: false,
+            equity_sampling: None,
+            tick_chunk_bars: None,
+            compounding: true,
+            stop_out_equity: None,
+            magnifier_timeframe: magnifier,
+            account_currency: None,
+            quote_conversion: None,
+            warmup_bars: None,
+            exit_priority: ExitPriority::default(),
+            fallback_on_missing_raw: false,
+            drawdown_basis: DrawdownBasis::default(),
+            overtrading_threshold: None,
+            regime: None,
+            slippage_seed: None,
+        }
+    }
+
+    fn real_spread_config(fallback: bool) -> BacktestConfig {
+        BacktestConfig {
+            precision: BacktestPrecision::RealTickRealSpread,
+            fallback_on_missing_raw: fallback,
+            ..config(Timeframe::H1, None)
+        }
+    }
+
+    #[test]
+    fn test_missing_raw_ticks_fall_back_to_custom_spread() {
+        let symbol = symbol_with_paths(&["h1", "tick"]);
+        let mut cfg = real_spread_config(true);
+        let warning = apply_precision_fallback(&symbol, &mut cfg).unwrap();
+        assert_eq!(cfg.precision, BacktestPrecision::RealTickCustomSpread);
+        assert!(warning.unwrap().contains("RealTickCustomSpread"));
+
+        // Raw ticks present: nothing to downgrade
+        let symbol = symbol_with_paths(&["h1", "tick", "tick_raw"]);
+        let mut cfg = real_spread_config(true);
+        assert_eq!(apply_precision_fallback(&symbol, &mut cfg).unwrap(), None);
+        assert_eq!(cfg.precision, BacktestPrecision::RealTickRealSpread);
+    }
+
+    #[test]
+    fn test_missing_raw_ticks_error_without_fallback() {
+        let symbol = symbol_with_paths(&["h1", "tick"]);
+        let mut cfg = real_spread_config(false);
+        assert!(matches!(apply_precision_fallback(&symbol, &mut cfg), Err(AppError::NotFound(_))));
+        assert_eq!(cfg.precision, BacktestPrecision::RealTickRealSpread);
+
+        // The fallback itself needs custom-spread tick data
+        let symbol = symbol_with_paths(&["h1"]);
+        let mut cfg = real_spread_config(true);
+        assert!(matches!(apply_precision_fallback(&symbol, &mut cfg), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_magnifier_path_selects_configured_resolution() {
+        let symbol = symbol_with_paths(&["m1", "m5", "m15", "h1"]);
+
+        let (tf, path) = magnifier_path(&symbol, &config(Timeframe::H1, Some(Timeframe::M5))).unwrap();
+        assert_eq!(tf, Timeframe::M5);
+        assert_eq!(path, "/data/m5.parquet");
+
+        // Unset keeps the M1 default
+        let (tf, path) = magnifier_path(&symbol, &config(Timeframe::H1, None)).unwrap();
+        assert_eq!(tf, Timeframe::M1);
+        assert_eq!(path, "/data/m1.parquet");
+    }
+
+    #[test]
+    fn test_magnifier_must_be_finer_than_timeframe() {
+        let symbol = symbol_with_paths(&["m1", "m5", "m15", "h1"]);
+        for (tf, magnifier) in [(Timeframe::M15, Timeframe::M15), (Timeframe::M5, Timeframe::M15), (Timeframe::H1, Timeframe::H1)] {
+            assert!(matches!(
+                magnifier_path(&symbol, &config(tf, Some(magnifier))),
+                Err(AppError::InvalidConfig(_))
+            ));
+        }
+        assert!(matches!(
+            magnifier_path(&symbol_with_paths(&["m1"]), &config(Timeframe::H1, Some(Timeframe::M5))),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_magnifier_on_m1_chart() {
+        // M1TickSimulation on an M1 chart uses the M1 bars themselves as sub-bars
+        let symbol = symbol_with_paths(&["m1", "h1"]);
+        let (tf, path) = magnifier_path(&symbol, &config(Timeframe::M1, None)).unwrap();
+        assert_eq!(tf, Timeframe::M1);
+        assert_eq!(path, "/data/m1.parquet");
+    }
+
+    #[test]
+    fn test_conversion_rates_from_symbol_closes() {
+        const MINUTE: i64 = 60_000_000;
+        let bar = |minute: i64, close: f64| Candle { timestamp: minute * MINUTE, close, ..Default::default() };
+        let candles = [bar(0, 150.0), bar(1, 0.0), bar(2, 125.0)];
+
+        let direct = conversion_rates(&candles, false, Timeframe::M1);
+        assert_eq!(direct.timestamps, vec![0, 2 * MINUTE]);
+        assert_eq!(direct.rates, vec![150.0, 125.0]);
+
+        // USDJPY closes give JPY→USD rates when inverted
+        let inverted = conversion_rates(&candles, true, Timeframe::M1);
+        assert_eq!(inverted.rates, vec![1.0 / 150.0, 1.0 / 125.0]);
+        // The 02:00 bar is still open at 02:30; its close only counts from 03:00
+        assert_eq!(inverted.rate_at(2 * MINUTE + MINUTE / 2), Some(1.0 / 150.0));
+        assert_eq!(inverted.rate_at(3 * MINUTE), Some(1.0 / 125.0));
+        assert_eq!(inverted.rate_at(MINUTE / 2), None);
+
+        assert_eq!(conversion_window_start("2024-03-10"), "2024-03-03");
+        assert_eq!(conversion_window_start("2024-03-10 12:00:00"), "2024-03-03");
+        assert_eq!(conversion_window_start(""), "");
+    }
+}
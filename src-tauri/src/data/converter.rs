@@ -87,10 +87,56 @@ pub fn generate_timeframes_from_partitions(
     generate_all_timeframes(&m1_df, Timeframe::M1, symbol_dir)
 }
 
+/// Resample stored bar data into an arbitrary timeframe on demand.
+///
+/// Uses the coarsest stored standard timeframe whose length evenly divides the
+/// target (e.g. M15 for 45m, H4 for 8h, M1 for 3m) so every target bar is built
+/// from whole source bars. Writes `{symbol_dir}/{tf}.parquet` and returns its path.
+pub fn resample_timeframe(
+    timeframe_paths: &HashMap<String, String>,
+    tf: Timeframe,
+    symbol_dir: &Path,
+) -> Result<String, AppError> {
+    let target = tf.minutes();
+    if target == 0 {
+        return Err(AppError::TimeframeConversion(
+            "Cannot resample into tick timeframe".to_string(),
+        ));
+    }
+
+    let (source_tf, source_path) = Timeframe::M1
+        .higher_timeframes()
+        .into_iter()
+        .chain(std::iter::once(Timeframe::M1))
+        .filter(|src| target % src.minutes() == 0)
+        .filter_map(|src| timeframe_paths.get(src.as_str().as_ref()).map(|p| (src, p)))
+        .max_by_key(|(src, _)| src.minutes())
+        .ok_or_else(|| {
+            AppError::TimeframeConversion(format!(
+                "No stored timeframe evenly divides {}",
+                tf.as_str()
+            ))
+        })?;
+
+    info!("Resampling {} from stored {}...", tf.as_str(), source_tf.as_str());
+
+    let df = scan_parquet_lazy(Path::new(source_path))?
+        .collect()
+        .map_err(|e| AppError::ParquetConversion(format!("collect {}: {}", source_tf, e)))?;
+
+    let mut tf_df = aggregate_to_timeframe(&df, &tf)?;
+    let tf_path = symbol_dir.join(format!("{}.parquet", tf.as_str()));
+    write_parquet(&mut tf_df, &tf_path)?;
+    info!("  → {} rows in {}", tf_df.height(), tf.as_str());
+
+    Ok(tf_path.to_string_lossy().to_string())
+}
+
 /// Aggregate a DataFrame to the target timeframe using Polars group_by_dynamic.
 fn aggregate_to_timeframe(df: &DataFrame, tf: &Timeframe) -> Result<DataFrame, AppError> {
     let duration = tf.polars_duration();
 
+    let aggs = bar_aggregations(df);
     let lf = df.clone().lazy();
 
     // Ensure datetime is sorted before group_by_dynamic
@@ -101,8 +147,8 @@ fn aggregate_to_timeframe(df: &DataFrame, tf: &Timeframe) -> Result<DataFrame, A
             col("datetime"),
             [],
             DynamicGroupOptions {
-                every: Duration::parse(duration),
-                period: Duration::parse(duration),
+                every: Duration::parse(&duration),
+                period: Duration::parse(&duration),
                 offset: Duration::parse("0ns"),
                 label: Label::Left,
                 include_boundaries: false,
@@ -111,15 +157,111 @@ fn aggregate_to_timeframe(df: &DataFrame, tf: &Timeframe) -> Result<DataFrame, A
                 ..Default::default()
             },
         )
-        .agg([
-            col("open").first().alias("open"),
-            col("high").max().alias("high"),
-            col("low").min().alias("low"),
-            col("close").last().alias("close"),
-            col("volume").sum().alias("volume"),
-        ]);
+        .agg(aggs);
 
     agg_lf
         .collect()
         .map_err(|e| AppError::TimeframeConversion(format!("aggregate {} failed: {}", duration, e)))
 }
+
+/// OHLCV bar aggregations, plus the optional per-bar fields present in `df`.
+///
+/// `avg_spread` is averaged over the bar and `open_interest` takes the last
+/// value, so tick-derived and futures data keep those fields at every timeframe.
+pub(crate) fn bar_aggregations(df: &DataFrame) -> Vec<Expr> {
+    let mut aggs = vec![
+        col("open").first().alias("open"),
+        col("high").max().alias("high"),
+        col("low").min().alias("low"),
+        col("close").last().alias("close"),
+        col("volume").sum().alias("volume"),
+    ];
+    if df.column("avg_spread").is_ok() {
+        aggs.push(col("avg_spread").mean().alias("avg_spread"));
+    }
+    if df.column("open_interest").is_ok() {
+        aggs.push(col("open_interest").last().alias("open_interest"));
+    }
+    aggs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::loader::build_ohlcv_dataframe;
+
+    /// Six consecutive M1 bars starting at 2024-01-15 00:00 UTC.
+    fn m1_bars() -> DataFrame {
+        let start = 1_705_276_800_000_000_i64;
+        let datetimes = (0..6).map(|i| start + i * 60_000_000).collect();
+        build_ohlcv_dataframe(
+            datetimes,
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            vec![1.5, 2.9, 3.1, 4.5, 5.2, 6.3],
+            vec![0.5, 1.8, 2.7, 3.6, 4.9, 5.4],
+            vec![2.0, 3.0, 2.8, 5.0, 5.1, 5.9],
+            vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0],
+        )
+        .unwrap()
+    }
+
+    fn f64_col(df: &DataFrame, name: &str) -> Vec<f64> {
+        df.column(name).unwrap().f64().unwrap().into_no_null_iter().collect()
+    }
+
+    #[test]
+    fn test_aggregate_m1_to_3_minutes() {
+        let tf: Timeframe = "3m".parse().unwrap();
+        assert_eq!(tf, Timeframe::Custom { minutes: 3 });
+        assert_eq!(tf.minutes(), 3);
+
+        let df = aggregate_to_timeframe(&m1_bars(), &tf).unwrap();
+        assert_eq!(df.height(), 2);
+        assert_eq!(f64_col(&df, "open"), vec![1.0, 4.0]);
+        assert_eq!(f64_col(&df, "high"), vec![3.1, 6.3]);
+        assert_eq!(f64_col(&df, "low"), vec![0.5, 3.6]);
+        assert_eq!(f64_col(&df, "close"), vec![2.8, 5.9]);
+        assert_eq!(f64_col(&df, "volume"), vec![60.0, 150.0]);
+    }
+
+    #[test]
+    fn test_aggregate_keeps_optional_bar_fields() {
+        let mut df = m1_bars();
+        df.with_column(Series::new("avg_spread".into(), [1.0, 2.0, 3.0, 0.5, 0.5, 2.0])).unwrap();
+        df.with_column(Series::new("open_interest".into(), [10.0, 11.0, 12.0, 13.0, 14.0, 15.0]))
+            .unwrap();
+
+        let df = aggregate_to_timeframe(&df, &Timeframe::Custom { minutes: 3 }).unwrap();
+        assert_eq!(f64_col(&df, "avg_spread"), vec![2.0, 1.0]);
+        assert_eq!(f64_col(&df, "open_interest"), vec![12.0, 15.0]);
+        assert!(aggregate_to_timeframe(&m1_bars(), &Timeframe::M5).unwrap().column("avg_spread").is_err());
+    }
+
+    #[test]
+    fn test_resample_timeframe_writes_custom_parquet() {
+        let dir = std::env::temp_dir().join(format!("converter_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = generate_all_timeframes(&m1_bars(), Timeframe::M1, &dir).unwrap();
+
+        let tf = Timeframe::Custom { minutes: 3 };
+        let path = resample_timeframe(&paths, tf, &dir).unwrap();
+        assert!(path.ends_with("m3.parquet"));
+
+        let df = scan_parquet_lazy(Path::new(&path)).unwrap().collect().unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(f64_col(&df, "close"), vec![2.8, 5.9]);
+    }
+
+    #[test]
+    fn test_custom_timeframe_keys() {
+        assert_eq!("h2".parse::<Timeframe>().unwrap(), Timeframe::Custom { minutes: 120 });
+        assert_eq!("8h".parse::<Timeframe>().unwrap().as_str(), "h8");
+        assert_eq!("60m".parse::<Timeframe>().unwrap(), Timeframe::H1);
+        assert_eq!(Timeframe::Custom { minutes: 2880 }.as_str(), "d2");
+        assert!("0m".parse::<Timeframe>().is_err());
+        assert!("1分".parse::<Timeframe>().is_err());
+        assert!("5µ".parse::<Timeframe>().is_err());
+        assert!("".parse::<Timeframe>().is_err());
+        assert_eq!(Timeframe::Custom { minutes: 3 }.higher_timeframes(), vec![Timeframe::M15, Timeframe::M30, Timeframe::H1, Timeframe::H4, Timeframe::D1]);
+    }
+}
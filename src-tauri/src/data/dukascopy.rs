@@ -1,16 +1,19 @@
 use std::io::Write as IoWrite;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
 
 use byteorder::{BigEndian, ReadBytesExt};
 use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use futures::stream::{self, Stream, StreamExt};
 use polars::prelude::*;
 use tracing::{debug, info, warn};
 
 use crate::errors::AppError;
 use crate::models::config::TickStorageFormat;
 
+use super::converter::bar_aggregations;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Public types
 // ─────────────────────────────────────────────────────────────────────────────
@@ -20,14 +23,21 @@ use crate::models::config::TickStorageFormat;
 pub struct DownloadOptions {
     /// If true, skip hours that return an empty file (no ticks).
     pub ignore_flats: bool,
-    /// If true, retry a failed HTTP request once before giving up.
+    /// If true, retry a failed HTTP request up to `max_retries` times with
+    /// exponential backoff before giving up.
     pub retry_on_empty: bool,
+    /// Retries per hourly file after the first failed attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on every further attempt.
+    pub retry_backoff_ms: u64,
     /// If true, cache the decompressed bi5 bytes on disk.
     pub use_cache: bool,
     /// Directory for the bi5 cache. Required when `use_cache` is true.
     pub cache_dir: Option<PathBuf>,
-    /// Maximum number of concurrent HTTP requests (unused — sequential for now).
+    /// Maximum number of hourly files fetched in parallel.
     pub max_concurrent: usize,
+    /// Upper bound on HTTP requests started per second (cache hits are free). 0 = unlimited.
+    pub max_requests_per_second: f64,
 }
 
 impl Default for DownloadOptions {
@@ -35,9 +45,12 @@ impl Default for DownloadOptions {
         Self {
             ignore_flats: true,
             retry_on_empty: true,
+            max_retries: 4,
+            retry_backoff_ms: 500,
             use_cache: true,
             cache_dir: None,
             max_concurrent: 8,
+            max_requests_per_second: 20.0,
         }
     }
 }
@@ -123,30 +136,31 @@ fn save_to_cache(
 // Bi5 fetching and parsing
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Download and decompress a single bi5 file. Returns decompressed bytes or None if empty.
-async fn fetch_bi5(
-    client: &reqwest::Client,
-    symbol: &str,
+/// One hourly bi5 file of a symbol.
+struct HourSlot<'a> {
+    symbol: &'a str,
     year: i32,
     month: u32,
     day: u32,
     hour: u32,
-    opts: &DownloadOptions,
-) -> Result<Option<Vec<u8>>, AppError> {
-    // Check cache first
-    if opts.use_cache {
-        if let Some(cache_dir) = &opts.cache_dir {
-            if let Some(cached) = load_from_cache(cache_dir, symbol, year, month, day, hour) {
-                return Ok(if cached.is_empty() { None } else { Some(cached) });
-            }
-        }
+}
+
+impl HourSlot<'_> {
+    fn url(&self) -> String {
+        duka_url(self.symbol, self.year, self.month, self.day, self.hour)
     }
 
-    let url = duka_url(symbol, year, month, day, hour);
+    fn label(&self) -> String {
+        format!("{}-{:02}-{:02} {:02}h", self.year, self.month, self.day, self.hour)
+    }
+}
+
+/// Download and decompress a single bi5 file. Returns decompressed bytes or None if empty.
+async fn fetch_bi5(client: &reqwest::Client, url: &str) -> Result<Option<Vec<u8>>, AppError> {
     debug!("Fetching: {}", url);
 
     let response = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .map_err(|e| AppError::DownloadError(format!("HTTP request failed for {}: {}", url, e)))?;
@@ -154,12 +168,6 @@ async fn fetch_bi5(
     if response.status() == reqwest::StatusCode::NOT_FOUND
         || response.status() == reqwest::StatusCode::NO_CONTENT
     {
-        // Store empty marker in cache to avoid re-requesting
-        if opts.use_cache {
-            if let Some(cache_dir) = &opts.cache_dir {
-                save_to_cache(cache_dir, symbol, year, month, day, hour, &[]);
-            }
-        }
         return Ok(None);
     }
 
@@ -177,11 +185,6 @@ async fn fetch_bi5(
         .map_err(|e| AppError::DownloadError(format!("Failed to read response body: {}", e)))?;
 
     if compressed.is_empty() {
-        if opts.use_cache {
-            if let Some(cache_dir) = &opts.cache_dir {
-                save_to_cache(cache_dir, symbol, year, month, day, hour, &[]);
-            }
-        }
         return Ok(None);
     }
 
@@ -190,18 +193,57 @@ async fn fetch_bi5(
     lzma_rs::lzma_decompress(&mut std::io::BufReader::new(compressed.as_ref()), &mut decompressed)
         .map_err(|e| AppError::DownloadError(format!("LZMA decompress failed for {}: {}", url, e)))?;
 
-    if decompressed.is_empty() {
-        return Ok(None);
-    }
+    Ok(if decompressed.is_empty() { None } else { Some(decompressed) })
+}
+
+/// Backoff before retry number `attempt` (1-based): `retry_backoff_ms · 2^(attempt-1)`, capped at 30 s.
+fn retry_delay(opts: &DownloadOptions, attempt: u32) -> std::time::Duration {
+    let ms = opts.retry_backoff_ms.saturating_mul(1 << (attempt - 1).min(16));
+    std::time::Duration::from_millis(ms.min(30_000))
+}
 
-    // Save to cache
-    if opts.use_cache {
-        if let Some(cache_dir) = &opts.cache_dir {
-            save_to_cache(cache_dir, symbol, year, month, day, hour, &decompressed);
+/// Fetch one hourly file, cache first.
+///
+/// A cached file (an empty one marks an hour without ticks) is returned without touching
+/// the network, which is what lets an interrupted download resume. Otherwise `fetch_remote`
+/// is retried with exponential backoff, calling `on_retry(attempt, error)` before each
+/// retry, and a successful result is cached.
+async fn fetch_hour<F, Fut>(
+    slot: &HourSlot<'_>,
+    opts: &DownloadOptions,
+    mut fetch_remote: F,
+    mut on_retry: impl FnMut(u32, &AppError),
+) -> Result<Option<Vec<u8>>, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<Vec<u8>>, AppError>>,
+{
+    let cache_dir = opts.cache_dir.as_deref().filter(|_| opts.use_cache);
+    if let Some(dir) = cache_dir {
+        if let Some(cached) = load_from_cache(dir, slot.symbol, slot.year, slot.month, slot.day, slot.hour) {
+            return Ok(if cached.is_empty() { None } else { Some(cached) });
         }
     }
 
-    Ok(Some(decompressed))
+    let max_attempts = if opts.retry_on_empty { opts.max_retries + 1 } else { 1 };
+    let mut attempt = 1;
+    let data = loop {
+        match fetch_remote().await {
+            Ok(data) => break data,
+            Err(e) if attempt < max_attempts => {
+                on_retry(attempt, &e);
+                tokio::time::sleep(retry_delay(opts, attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    if let Some(dir) = cache_dir {
+        let bytes = data.as_deref().unwrap_or(&[]);
+        save_to_cache(dir, slot.symbol, slot.year, slot.month, slot.day, slot.hour, bytes);
+    }
+    Ok(data)
 }
 
 /// Parse decompressed bi5 bytes into tick records.
@@ -388,6 +430,12 @@ fn flush_year(
         .zip(buf.asks.iter())
         .map(|(b, a)| (b + a) / 2.0)
         .collect();
+    let spreads: Vec<f64> = buf
+        .bids
+        .iter()
+        .zip(buf.asks.iter())
+        .map(|(b, a)| a - b)
+        .collect();
 
     let volumes: Vec<f64> = buf
         .bid_vols
@@ -397,7 +445,7 @@ fn flush_year(
         .collect();
 
     let tick_path = tick_dir.join(format!("{}.parquet", year));
-    let mut tick_df = build_tick_ohlcv_df(&timestamps, &mids, &volumes)?;
+    let mut tick_df = build_tick_ohlcv_df(&timestamps, &mids, &spreads, &volumes)?;
     write_parquet_to_path(&mut tick_df, &tick_path)?;
 
     // Raw tick file
@@ -448,11 +496,12 @@ pub async fn download_symbol_direct(
     std::fs::create_dir_all(tick_raw_dir)
         .map_err(|e| AppError::FileWrite(format!("create tick_raw_dir: {}", e)))?;
 
-    let client = build_client()?;
+    let fetcher = HourFetcher::new(duka_symbol, &options)?;
     let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
 
     let total = total_hours(start, end).max(1);
     let mut done: u64 = 0;
+    report_cached_hours(duka_symbol, start, end, &options, &progress);
 
     // Year buffers
     let mut year_bufs: std::collections::HashMap<i32, YearBuffer> =
@@ -462,13 +511,13 @@ pub async fn download_symbol_direct(
     let mut global_min_ts = i64::MAX;
     let mut global_max_ts = i64::MIN;
 
-    for (year, month, day, hour, hour_us) in HourIter::new(start, end) {
-        if cancel_flag.load(Ordering::Relaxed) {
-            return Err(AppError::DownloadCancelled);
-        }
+    let mut hours = fetcher.fetch_range(start, end, &progress);
+    while let Some((slot, hour_us, data)) = next_unless_cancelled(&mut hours, cancel_flag).await? {
+        let HourSlot { year, month, day, hour, .. } = slot;
 
         done += 1;
         let pct = ((done * 100) / total).min(99) as u8;
+        fetcher.pct.store(pct, Ordering::Relaxed);
         if done % 24 == 0 {
             progress(
                 pct,
@@ -479,13 +528,8 @@ pub async fn download_symbol_direct(
             );
         }
 
-        let data = match fetch_bi5_with_retry(&client, duka_symbol, year, month, day, hour, &options).await {
-            Ok(Some(d)) => d,
-            Ok(None) => continue,
-            Err(e) => {
-                warn!("Error fetching {}-{:02}-{:02} {:02}h: {}", year, month, day, hour, e);
-                continue;
-            }
+        let Some(data) = data else {
+            continue;
         };
 
         let ticks = parse_bi5(&data, hour_us, point_value, options.ignore_flats);
@@ -532,7 +576,7 @@ pub async fn download_symbol_direct(
     let start_date = format_us(global_min_ts);
     let end_date = format_us(global_max_ts);
 
-    progress(100, "Download complete");
+    progress(100, &fetcher.completion_message());
     info!(
         "download_symbol_direct: {} ticks, {} → {}",
         total_rows, start_date, end_date
@@ -552,10 +596,11 @@ pub async fn download_symbol(
     cancel_flag: &AtomicBool,
     progress: impl Fn(u8, &str),
 ) -> Result<(), AppError> {
-    let client = build_client()?;
+    let fetcher = HourFetcher::new(duka_symbol, &options)?;
 
     let total = total_hours(start, end).max(1);
     let mut done: u64 = 0;
+    report_cached_hours(duka_symbol, start, end, &options, &progress);
 
     if let Some(parent) = output_csv.parent() {
         std::fs::create_dir_all(parent)
@@ -572,13 +617,13 @@ pub async fn download_symbol(
 
     let mut total_rows: usize = 0;
 
-    for (year, month, day, hour, hour_us) in HourIter::new(start, end) {
-        if cancel_flag.load(Ordering::Relaxed) {
-            return Err(AppError::DownloadCancelled);
-        }
+    let mut hours = fetcher.fetch_range(start, end, &progress);
+    while let Some((slot, hour_us, data)) = next_unless_cancelled(&mut hours, cancel_flag).await? {
+        let HourSlot { year, month, day, .. } = slot;
 
         done += 1;
         let pct = ((done * 100) / total).min(99) as u8;
+        fetcher.pct.store(pct, Ordering::Relaxed);
         if done % 24 == 0 {
             progress(
                 pct,
@@ -586,13 +631,8 @@ pub async fn download_symbol(
             );
         }
 
-        let data = match fetch_bi5_with_retry(&client, duka_symbol, year, month, day, hour, &options).await {
-            Ok(Some(d)) => d,
-            Ok(None) => continue,
-            Err(e) => {
-                warn!("Error fetching {}-{:02}-{:02} {:02}h: {}", year, month, day, hour, e);
-                continue;
-            }
+        let Some(data) = data else {
+            continue;
         };
 
         let ticks = parse_bi5(&data, hour_us, point_value, options.ignore_flats);
@@ -615,7 +655,7 @@ pub async fn download_symbol(
 
     writer.flush().map_err(|e| AppError::FileWrite(e.to_string()))?;
 
-    progress(100, "Download complete");
+    progress(100, &fetcher.completion_message());
     info!("download_symbol: {} ticks written to {}", total_rows, output_csv.display());
 
     Ok(())
@@ -634,24 +674,25 @@ pub async fn download_symbol_m1_candles(
     cancel_flag: &AtomicBool,
     progress: impl Fn(u8, &str),
 ) -> Result<DataFrame, AppError> {
-    let client = build_client()?;
+    let fetcher = HourFetcher::new(duka_symbol, &options)?;
     let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
 
     let total = total_hours(start, end).max(1);
     let mut done: u64 = 0;
+    report_cached_hours(duka_symbol, start, end, &options, &progress);
 
     let mut all_timestamps: Vec<i64> = Vec::new();
     let mut all_bids: Vec<f64> = Vec::new();
     let mut all_asks: Vec<f64> = Vec::new();
     let mut all_volumes: Vec<f64> = Vec::new();
 
-    for (year, month, day, hour, hour_us) in HourIter::new(start, end) {
-        if cancel_flag.load(Ordering::Relaxed) {
-            return Err(AppError::DownloadCancelled);
-        }
+    let mut hours = fetcher.fetch_range(start, end, &progress);
+    while let Some((slot, hour_us, data)) = next_unless_cancelled(&mut hours, cancel_flag).await? {
+        let HourSlot { year, month, day, .. } = slot;
 
         done += 1;
         let pct = ((done * 100) / total).min(99) as u8;
+        fetcher.pct.store(pct, Ordering::Relaxed);
         if done % 24 == 0 {
             progress(
                 pct,
@@ -659,13 +700,8 @@ pub async fn download_symbol_m1_candles(
             );
         }
 
-        let data = match fetch_bi5_with_retry(&client, duka_symbol, year, month, day, hour, &options).await {
-            Ok(Some(d)) => d,
-            Ok(None) => continue,
-            Err(e) => {
-                warn!("Error {}-{:02}-{:02} {:02}h: {}", year, month, day, hour, e);
-                continue;
-            }
+        let Some(data) = data else {
+            continue;
         };
 
         let ticks = parse_bi5(&data, hour_us, point_value, options.ignore_flats);
@@ -684,6 +720,7 @@ pub async fn download_symbol_m1_candles(
         ));
     }
 
+    progress(99, &fetcher.completion_message());
     progress(100, "Aggregating to M1...");
 
     // Build tick DataFrame (mid price)
@@ -692,6 +729,11 @@ pub async fn download_symbol_m1_candles(
         .zip(all_asks.iter())
         .map(|(b, a)| (b + a) / 2.0)
         .collect();
+    let spreads: Vec<f64> = all_bids
+        .iter()
+        .zip(all_asks.iter())
+        .map(|(b, a)| a - b)
+        .collect();
 
     let dt_series = Series::new("datetime".into(), &all_timestamps)
         .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
@@ -704,6 +746,7 @@ pub async fn download_symbol_m1_candles(
         Series::new("low".into(), &mids).into_column(),
         Series::new("close".into(), &mids).into_column(),
         Series::new("volume".into(), &all_volumes).into_column(),
+        Series::new("avg_spread".into(), &spreads).into_column(),
     ])
     .map_err(|e| AppError::ParquetConversion(format!("tick df: {}", e)))?;
 
@@ -722,26 +765,191 @@ pub async fn download_symbol_m1_candles(
 // Internal helpers
 // ─────────────────────────────────────────────────────────────────────────────
 
-async fn fetch_bi5_with_retry(
-    client: &reqwest::Client,
+/// Spaces out request starts to at most `max_per_second`, shared by every in-flight fetch
+/// of one download. Zero or a negative rate disables the limit.
+struct RateLimiter {
+    interval: Option<std::time::Duration>,
+    next_slot: Mutex<tokio::time::Instant>,
+}
+
+impl RateLimiter {
+    fn new(max_per_second: f64) -> Self {
+        let interval = (max_per_second > 0.0)
+            .then(|| std::time::Duration::from_secs_f64(1.0 / max_per_second));
+        Self { interval, next_slot: Mutex::new(tokio::time::Instant::now()) }
+    }
+
+    /// Wait for the next free request slot.
+    async fn acquire(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = (*next).max(tokio::time::Instant::now());
+            *next = slot + interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Run `fetch` over `items` with at most `concurrency` futures in flight, yielding results
+/// in input order. Futures are polled on the caller's task rather than spawned.
+fn fetch_pool<I, F, Fut>(items: I, concurrency: usize, fetch: F) -> impl Stream<Item = Fut::Output> + Unpin
+where
+    I: IntoIterator,
+    I::IntoIter: Unpin,
+    F: FnMut(I::Item) -> Fut + Unpin,
+    Fut: std::future::Future,
+{
+    stream::iter(items).map(fetch).buffered(concurrency.max(1))
+}
+
+/// Next item of `stream`, or `DownloadCancelled` as soon as `cancel_flag` is set. Callers
+/// return on cancellation, which drops the stream and aborts the requests still in flight.
+async fn next_unless_cancelled<S: Stream + Unpin>(
+    stream: &mut S,
+    cancel_flag: &AtomicBool,
+) -> Result<Option<S::Item>, AppError> {
+    let cancelled = async {
+        while !cancel_flag.load(Ordering::Relaxed) {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    };
+    tokio::select! {
+        biased;
+        _ = cancelled => Err(AppError::DownloadCancelled),
+        item = stream.next() => Ok(item),
+    }
+}
+
+/// Shared state of one download's hourly fetches: HTTP client, options, rate limit, the
+/// progress percentage reported alongside retry messages and the hours given up on.
+struct HourFetcher<'a> {
+    client: reqwest::Client,
+    symbol: &'a str,
+    opts: &'a DownloadOptions,
+    limiter: RateLimiter,
+    pct: AtomicU8,
+    failed: Mutex<Vec<String>>,
+}
+
+impl<'a> HourFetcher<'a> {
+    fn new(symbol: &'a str, opts: &'a DownloadOptions) -> Result<Self, AppError> {
+        Ok(Self {
+            client: build_client()?,
+            symbol,
+            opts,
+            limiter: RateLimiter::new(opts.max_requests_per_second),
+            pct: AtomicU8::new(0),
+            failed: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Fetch every hour of `[start, end)`, `max_concurrent` at a time, in hour order.
+    /// Yields `(slot, hour_start_us, bytes)`; hours that failed every retry yield `None`.
+    fn fetch_range<'s>(
+        &'s self,
+        start: NaiveDate,
+        end: NaiveDate,
+        progress: &'s impl Fn(u8, &str),
+    ) -> impl Stream<Item = (HourSlot<'a>, i64, Option<Vec<u8>>)> + Unpin + 's {
+        fetch_pool(HourIter::new(start, end), self.opts.max_concurrent, move |(year, month, day, hour, hour_us)| {
+            let slot = HourSlot { symbol: self.symbol, year, month, day, hour };
+            async move {
+                let data = self.fetch(&slot, progress).await;
+                (slot, hour_us, data)
+            }
+        })
+    }
+
+    /// `fetch_hour` over HTTP, reporting retries through the download's `progress` callback.
+    async fn fetch(&self, slot: &HourSlot<'_>, progress: &impl Fn(u8, &str)) -> Option<Vec<u8>> {
+        let url = slot.url();
+        let remote = || async {
+            self.limiter.acquire().await;
+            fetch_bi5(&self.client, &url).await
+        };
+        let on_retry = |attempt: u32, e: &AppError| {
+            warn!("Error fetching {} (attempt {}): {}", slot.label(), attempt, e);
+            progress(
+                self.pct.load(Ordering::Relaxed),
+                &format!("Retrying {} ({}/{})...", slot.label(), attempt, self.opts.max_retries),
+            );
+        };
+        let result = fetch_hour(slot, self.opts, remote, on_retry).await;
+        self.skip_failed(slot, result, progress)
+    }
+
+    /// Pass a fetched hour through, or record it as failed and skip it. A failed hour is
+    /// never cached, so starting the same download again retries just the skipped hours.
+    fn skip_failed(
+        &self,
+        slot: &HourSlot<'_>,
+        result: Result<Option<Vec<u8>>, AppError>,
+        progress: &impl Fn(u8, &str),
+    ) -> Option<Vec<u8>> {
+        match result {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Giving up on {}: {}", slot.label(), e);
+                progress(self.pct.load(Ordering::Relaxed), &format!("Skipping {}: {}", slot.label(), e));
+                self.failed.lock().unwrap_or_else(|e| e.into_inner()).push(slot.label());
+                None
+            }
+        }
+    }
+
+    /// Final progress message, listing the hours skipped after every retry failed.
+    fn completion_message(&self) -> String {
+        let failed = self.failed.lock().unwrap_or_else(|e| e.into_inner());
+        if failed.is_empty() {
+            return "Download complete".to_string();
+        }
+        const LISTED: usize = 5;
+        let mut hours = failed.iter().take(LISTED).cloned().collect::<Vec<_>>().join(", ");
+        if failed.len() > LISTED {
+            hours.push_str(&format!(" and {} more", failed.len() - LISTED));
+        }
+        let resume = if self.opts.use_cache && self.opts.cache_dir.is_some() {
+            " — restart the download to retry them"
+        } else {
+            ""
+        };
+        format!("Download complete, {} hour(s) failed and were skipped: {}{}", failed.len(), hours, resume)
+    }
+}
+
+/// Report how many hours of the range are already in the bi5 cache (resume progress).
+fn report_cached_hours(
     symbol: &str,
-    year: i32,
-    month: u32,
-    day: u32,
-    hour: u32,
+    start: NaiveDate,
+    end: NaiveDate,
     opts: &DownloadOptions,
-) -> Result<Option<Vec<u8>>, AppError> {
-    let result = fetch_bi5(client, symbol, year, month, day, hour, opts).await;
-    if result.is_err() && opts.retry_on_empty {
-        // Wait briefly then retry
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        fetch_bi5(client, symbol, year, month, day, hour, opts).await
-    } else {
-        result
+    progress: &impl Fn(u8, &str),
+) {
+    let Some(dir) = opts.cache_dir.as_deref().filter(|_| opts.use_cache) else {
+        return;
+    };
+    let cached = count_cached_hours(dir, symbol, start, end);
+    if cached > 0 {
+        info!("Resuming {}: {} hours already cached", symbol, cached);
+        progress(
+            0,
+            &format!("Resuming: {} of {} hours already downloaded", cached, total_hours(start, end)),
+        );
     }
 }
 
+fn count_cached_hours(cache_dir: &Path, symbol: &str, start: NaiveDate, end: NaiveDate) -> u64 {
+    HourIter::new(start, end)
+        .filter(|&(y, m, d, h, _)| cache_path(cache_dir, symbol, y, m, d, h).is_file())
+        .count() as u64
+}
+
 fn aggregate_ticks_to_m1(tick_df: DataFrame) -> Result<DataFrame, AppError> {
+    let aggs = bar_aggregations(&tick_df);
     let lf = tick_df
         .lazy()
         .sort(["datetime"], SortMultipleOptions::default());
@@ -760,13 +968,7 @@ fn aggregate_ticks_to_m1(tick_df: DataFrame) -> Result<DataFrame, AppError> {
             ..Default::default()
         },
     )
-    .agg([
-        col("open").first().alias("open"),
-        col("high").max().alias("high"),
-        col("low").min().alias("low"),
-        col("close").last().alias("close"),
-        col("volume").sum().alias("volume"),
-    ])
+    .agg(aggs)
     .collect()
     .map_err(|e| AppError::TimeframeConversion(format!("aggregate M1: {}", e)))
 }
@@ -774,6 +976,7 @@ fn aggregate_ticks_to_m1(tick_df: DataFrame) -> Result<DataFrame, AppError> {
 fn build_tick_ohlcv_df(
     datetimes: &[i64],
     mids: &[f64],
+    spreads: &[f64],
     volumes: &[f64],
 ) -> Result<DataFrame, AppError> {
     let dt_series = Series::new("datetime".into(), datetimes)
@@ -787,6 +990,7 @@ fn build_tick_ohlcv_df(
         Series::new("low".into(), mids).into_column(),
         Series::new("close".into(), mids).into_column(),
         Series::new("volume".into(), volumes).into_column(),
+        Series::new("avg_spread".into(), spreads).into_column(),
     ])
     .map_err(|e| AppError::ParquetConversion(format!("tick ohlcv df: {}", e)))
 }
@@ -871,3 +1075,217 @@ fn format_us_datetime(us: i64) -> String {
         None => format!("{}", us),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::sync::atomic::AtomicUsize;
+
+    fn test_options(cache_dir: &Path) -> DownloadOptions {
+        DownloadOptions {
+            retry_backoff_ms: 1,
+            cache_dir: Some(cache_dir.to_path_buf()),
+            ..DownloadOptions::default()
+        }
+    }
+
+    fn slot(hour: u32) -> HourSlot<'static> {
+        HourSlot { symbol: "EURUSD", year: 2024, month: 1, day: 15, hour }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_retries_and_succeeds() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let opts = test_options(dir);
+        let calls = Cell::new(0);
+        let retries = RefCell::new(Vec::new());
+
+        let data = fetch_hour(
+            &slot(9),
+            &opts,
+            || {
+                calls.set(calls.get() + 1);
+                let n = calls.get();
+                async move {
+                    if n < 3 {
+                        Err(AppError::DownloadError("connection reset".into()))
+                    } else {
+                        Ok(Some(vec![1, 2, 3]))
+                    }
+                }
+            },
+            |attempt, _| retries.borrow_mut().push(attempt),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(data, Some(vec![1, 2, 3]));
+        assert_eq!(calls.get(), 3);
+        assert_eq!(*retries.borrow(), vec![1, 2]);
+        assert_eq!(load_from_cache(dir, "EURUSD", 2024, 1, 15, 9), Some(vec![1, 2, 3]));
+
+        // A file that never comes back fails after max_retries + 1 attempts, uncached
+        calls.set(0);
+        let failed = fetch_hour(
+            &slot(10),
+            &opts,
+            || {
+                calls.set(calls.get() + 1);
+                async { Err(AppError::DownloadError("HTTP 503".into())) }
+            },
+            |_, _| {},
+        )
+        .await;
+
+        assert!(failed.is_err());
+        assert_eq!(calls.get(), opts.max_retries + 1);
+        assert_eq!(load_from_cache(dir, "EURUSD", 2024, 1, 15, 10), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pool_caps_concurrency_and_keeps_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let flag = AtomicBool::new(false);
+
+        let mut pool = fetch_pool(0..20, 3, |i| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(5 + (i % 3) * 5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        });
+        let mut order = Vec::new();
+        while let Some(i) = next_unless_cancelled(&mut pool, &flag).await.unwrap() {
+            order.push(i);
+        }
+
+        assert_eq!(order, (0..20).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_in_flight_fetches() {
+        let started = AtomicUsize::new(0);
+        let finished = AtomicUsize::new(0);
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let canceller = {
+            let flag = flag.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                flag.store(true, Ordering::Relaxed);
+            })
+        };
+        let t0 = std::time::Instant::now();
+        let mut pool = fetch_pool(0..100, 4, |_| {
+            let (started, finished) = (&started, &finished);
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let result = next_unless_cancelled(&mut pool, &flag).await;
+        drop(pool);
+        canceller.await.unwrap();
+
+        assert!(matches!(result, Err(AppError::DownloadCancelled)));
+        assert!(t0.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(started.load(Ordering::SeqCst), 4);
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::new(100.0);
+        let t0 = tokio::time::Instant::now();
+        for _ in 0..6 {
+            limiter.acquire().await;
+        }
+        // First slot is immediate, the other five wait 10 ms each
+        assert!(t0.elapsed() >= std::time::Duration::from_millis(50));
+
+        let unlimited = RateLimiter::new(0.0);
+        let t0 = tokio::time::Instant::now();
+        for _ in 0..1000 {
+            unlimited.acquire().await;
+        }
+        assert!(t0.elapsed() < std::time::Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_cached_hours() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let opts = test_options(dir);
+        save_to_cache(dir, "EURUSD", 2024, 1, 15, 0, &[7, 7]);
+        save_to_cache(dir, "EURUSD", 2024, 1, 15, 1, &[]); // hour without ticks
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 16).unwrap();
+        assert_eq!(count_cached_hours(dir, "EURUSD", start, end), 2);
+
+        let calls = Cell::new(0);
+        let mut fetched = Vec::new();
+        for hour in 0..3 {
+            let data = fetch_hour(
+                &slot(hour),
+                &opts,
+                || {
+                    calls.set(calls.get() + 1);
+                    async { Ok(Some(vec![9])) }
+                },
+                |_, _| {},
+            )
+            .await
+            .unwrap();
+            fetched.push(data);
+        }
+
+        // Only the uncached hour hit the network
+        assert_eq!(calls.get(), 1);
+        assert_eq!(fetched, vec![Some(vec![7, 7]), None, Some(vec![9])]);
+    }
+
+    #[tokio::test]
+    async fn test_permanently_failing_hour_is_skipped_and_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let opts = test_options(tmp.path());
+        let fetcher = HourFetcher::new("EURUSD", &opts).unwrap();
+        let messages = RefCell::new(Vec::new());
+        let progress = |_: u8, msg: &str| messages.borrow_mut().push(msg.to_string());
+
+        let mut fetched = Vec::new();
+        for hour in 0..3 {
+            let result = fetch_hour(
+                &slot(hour),
+                &opts,
+                || async move {
+                    if hour == 1 {
+                        Err(AppError::DownloadError("HTTP 503".into()))
+                    } else {
+                        Ok(Some(vec![hour as u8]))
+                    }
+                },
+                |_, _| {},
+            )
+            .await;
+            fetched.push(fetcher.skip_failed(&slot(hour), result, &progress));
+        }
+
+        // The download carries on past the failed hour, which stays uncached for a retry
+        assert_eq!(fetched, vec![Some(vec![0]), None, Some(vec![2])]);
+        assert_eq!(*fetcher.failed.lock().unwrap(), vec!["2024-01-15 01h".to_string()]);
+        assert_eq!(load_from_cache(tmp.path(), "EURUSD", 2024, 1, 15, 1), None);
+        assert!(messages.borrow().iter().any(|m| m.starts_with("Skipping 2024-01-15 01h")));
+        let done = fetcher.completion_message();
+        assert!(done.contains("1 hour(s) failed"), "{}", done);
+        assert!(done.contains("2024-01-15 01h"), "{}", done);
+    }
+}
@@ -0,0 +1,96 @@
+//! HistData.com "Generic ASCII" M1 bars.
+//!
+//! Layout (no header, `;` separated):
+//! `YYYYMMDD HHMMSS;open;high;low;close;volume`
+//!
+//! Timestamps are Eastern Standard Time *without* daylight saving (fixed UTC-5),
+//! so they are shifted forward five hours to UTC.
+
+use std::path::Path;
+
+use polars::prelude::DataFrame;
+use tracing::warn;
+
+use crate::data::loader::{build_ohlcv_dataframe, parse_f64};
+use crate::errors::AppError;
+
+/// Fixed offset of HistData timestamps (EST, no DST) relative to UTC.
+const HISTDATA_UTC_OFFSET_HOURS: i64 = -5;
+
+pub fn load_m1_csv(path: &Path, tz_offset_hours: f64) -> Result<DataFrame, AppError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::FileRead(format!("{}: {}", path.display(), e)))?;
+
+    let to_utc_us = -HISTDATA_UTC_OFFSET_HOURS * 3_600_000_000;
+    let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
+
+    let mut datetimes: Vec<i64> = Vec::new();
+    let mut opens: Vec<f64> = Vec::new();
+    let mut highs: Vec<f64> = Vec::new();
+    let mut lows: Vec<f64> = Vec::new();
+    let mut closes: Vec<f64> = Vec::new();
+    let mut volumes: Vec<f64> = Vec::new();
+
+    for (row_num, line) in content.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+        let cols: Vec<&str> = line.split(';').map(|s| s.trim()).collect();
+        if cols.len() < 5 {
+            warn!("HistData: skipping malformed row {}: {:?}", row_num + 1, line);
+            continue;
+        }
+
+        let dt = chrono::NaiveDateTime::parse_from_str(cols[0], "%Y%m%d %H%M%S").map_err(|_| {
+            AppError::CsvParseError {
+                row: row_num + 1,
+                message: format!("Expected HistData datetime 'YYYYMMDD HHMMSS', got '{}'", cols[0]),
+            }
+        })?;
+
+        datetimes.push(dt.and_utc().timestamp_micros() + to_utc_us + tz_offset_us);
+        opens.push(parse_f64(cols[1], row_num, "Open")?);
+        highs.push(parse_f64(cols[2], row_num, "High")?);
+        lows.push(parse_f64(cols[3], row_num, "Low")?);
+        closes.push(parse_f64(cols[4], row_num, "Close")?);
+        volumes.push(cols.get(5).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0));
+    }
+
+    if datetimes.is_empty() {
+        return Err(AppError::InvalidCsvFormat("No valid HistData rows found".to_string()));
+    }
+
+    build_ohlcv_dataframe(datetimes, opens, highs, lows, closes, volumes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::loader::get_date_range;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_load_histdata_m1() {
+        let df = load_m1_csv(&fixture("histdata_m1.csv"), 0.0).unwrap();
+        assert_eq!(df.height(), 3);
+
+        let open: Vec<f64> = df.column("open").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let close: Vec<f64> = df.column("close").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let volume: Vec<f64> = df.column("volume").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(open, vec![1.10427, 1.10429, 1.10431]);
+        assert_eq!(close, vec![1.10429, 1.10431, 1.10425]);
+        assert_eq!(volume, vec![0.0, 0.0, 0.0]);
+
+        // 17:00 EST → 22:00 UTC
+        let (start, end) = get_date_range(&df).unwrap();
+        assert_eq!(start, "2024-01-02 22:00:00.000");
+        assert_eq!(end, "2024-01-02 22:02:00.000");
+    }
+
+    #[test]
+    fn test_histdata_applies_user_offset_after_utc() {
+        let df = load_m1_csv(&fixture("histdata_m1.csv"), 2.0).unwrap();
+        let (start, _) = get_date_range(&df).unwrap();
+        assert_eq!(start, "2024-01-03 00:00:00.000");
+    }
+}
@@ -0,0 +1,34 @@
+//! Parsers for third-party CSV exports with a fixed, known layout.
+//!
+//! Each importer normalizes its source into the same OHLCV DataFrame shape that
+//! `loader::load_csv_to_dataframe` produces (`datetime` as UTC microseconds plus
+//! `open`/`high`/`low`/`close`/`volume`), so the regular timeframe-generation
+//! pipeline can run on the result unchanged.
+
+pub mod histdata;
+pub mod oanda;
+
+use std::path::Path;
+
+use polars::prelude::DataFrame;
+
+use crate::errors::AppError;
+use crate::models::config::SourceFormat;
+
+/// Load a CSV exported by a known data vendor into an OHLCV DataFrame.
+///
+/// `tz_offset_hours` is applied after the vendor's own timestamps have been
+/// normalized to UTC, exactly as for generic CSV imports.
+pub fn load_source_to_dataframe(
+    path: &Path,
+    source_format: SourceFormat,
+    tz_offset_hours: f64,
+) -> Result<DataFrame, AppError> {
+    match source_format {
+        SourceFormat::HistData => histdata::load_m1_csv(path, tz_offset_hours),
+        SourceFormat::Oanda => oanda::load_candles_csv(path, tz_offset_hours),
+        SourceFormat::Generic => Err(AppError::UnsupportedFormat(
+            "Generic CSV files are loaded through the validator, not a source importer".into(),
+        )),
+    }
+}
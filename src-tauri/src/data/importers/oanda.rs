@@ -0,0 +1,147 @@
+//! OANDA candle exports (v20 REST candles flattened to CSV).
+//!
+//! Expects a header row. The timestamp column (`time`) may be RFC 3339
+//! (`2024-01-02T22:00:00.000000000Z`) or UNIX seconds (`1704232800.000000000`);
+//! both are normalized to UTC. Prices are taken from mid candles when present
+//! (`mid_o` … `mid_c`), otherwise from bid candles or plain `open` … `close`
+//! columns. Rows with `complete=false` (still-forming candles) are skipped.
+
+use std::path::Path;
+
+use polars::prelude::DataFrame;
+use tracing::warn;
+
+use crate::data::loader::{build_ohlcv_dataframe, parse_f64};
+use crate::errors::AppError;
+
+const TIME_COLUMNS: &[&str] = &["time", "timestamp", "datetime", "date"];
+const OPEN_COLUMNS: &[&str] = &["mid_o", "mid.o", "o", "open", "bid_o", "bid.o"];
+const HIGH_COLUMNS: &[&str] = &["mid_h", "mid.h", "h", "high", "bid_h", "bid.h"];
+const LOW_COLUMNS: &[&str] = &["mid_l", "mid.l", "l", "low", "bid_l", "bid.l"];
+const CLOSE_COLUMNS: &[&str] = &["mid_c", "mid.c", "c", "close", "bid_c", "bid.c"];
+
+pub fn load_candles_csv(path: &Path, tz_offset_hours: f64) -> Result<DataFrame, AppError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| AppError::FileRead(format!("{}: {}", path.display(), e)))?;
+
+    let headers: Vec<String> = rdr
+        .headers()
+        .map_err(|e| AppError::InvalidCsvFormat(e.to_string()))?
+        .iter()
+        .map(|h| h.trim().trim_matches('"').to_lowercase())
+        .collect();
+
+    let find = |candidates: &[&str], label: &str| -> Result<usize, AppError> {
+        candidates
+            .iter()
+            .find_map(|c| headers.iter().position(|h| h == c))
+            .ok_or_else(|| {
+                AppError::InvalidCsvFormat(format!(
+                    "OANDA export is missing a {} column (expected one of: {})",
+                    label,
+                    candidates.join(", ")
+                ))
+            })
+    };
+    let time_idx = find(TIME_COLUMNS, "time")?;
+    let open_idx = find(OPEN_COLUMNS, "open")?;
+    let high_idx = find(HIGH_COLUMNS, "high")?;
+    let low_idx = find(LOW_COLUMNS, "low")?;
+    let close_idx = find(CLOSE_COLUMNS, "close")?;
+    let vol_idx = headers.iter().position(|h| h == "volume");
+    let complete_idx = headers.iter().position(|h| h == "complete");
+
+    let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
+
+    let mut datetimes: Vec<i64> = Vec::new();
+    let mut opens: Vec<f64> = Vec::new();
+    let mut highs: Vec<f64> = Vec::new();
+    let mut lows: Vec<f64> = Vec::new();
+    let mut closes: Vec<f64> = Vec::new();
+    let mut volumes: Vec<f64> = Vec::new();
+
+    for (row_num, record) in rdr.records().enumerate() {
+        let record = match record {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("OANDA: skipping unreadable row {}: {}", row_num + 1, e);
+                continue;
+            }
+        };
+        let field = |i: usize| record.get(i).unwrap_or("").trim();
+
+        if complete_idx.is_some_and(|i| field(i).eq_ignore_ascii_case("false")) {
+            continue;
+        }
+
+        let dt_us = parse_oanda_time(field(time_idx)).ok_or_else(|| AppError::CsvParseError {
+            row: row_num + 1,
+            message: format!("Cannot parse OANDA time: '{}'", field(time_idx)),
+        })?;
+
+        datetimes.push(dt_us + tz_offset_us);
+        opens.push(parse_f64(field(open_idx), row_num, "Open")?);
+        highs.push(parse_f64(field(high_idx), row_num, "High")?);
+        lows.push(parse_f64(field(low_idx), row_num, "Low")?);
+        closes.push(parse_f64(field(close_idx), row_num, "Close")?);
+        volumes.push(vol_idx.and_then(|i| field(i).parse::<f64>().ok()).unwrap_or(0.0));
+    }
+
+    if datetimes.is_empty() {
+        return Err(AppError::InvalidCsvFormat("No complete OANDA candles found".to_string()));
+    }
+
+    build_ohlcv_dataframe(datetimes, opens, highs, lows, closes, volumes)
+}
+
+/// Parse an RFC 3339 or UNIX-seconds OANDA timestamp into UTC microseconds.
+fn parse_oanda_time(s: &str) -> Option<i64> {
+    let s = s.trim_matches('"');
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(dt.timestamp_micros());
+    }
+    let secs = s.parse::<f64>().ok()?;
+    Some((secs * 1_000_000.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::loader::get_date_range;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_load_oanda_mid_candles() {
+        let df = load_candles_csv(&fixture("oanda_candles.csv"), 0.0).unwrap();
+        // The trailing incomplete candle is dropped.
+        assert_eq!(df.height(), 2);
+
+        let high: Vec<f64> = df.column("high").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let low: Vec<f64> = df.column("low").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let volume: Vec<f64> = df.column("volume").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(high, vec![1.10452, 1.10480]);
+        assert_eq!(low, vec![1.10401, 1.10430]);
+        assert_eq!(volume, vec![152.0, 98.0]);
+
+        // "+02:00" offset on the second row is normalized to UTC.
+        let (start, end) = get_date_range(&df).unwrap();
+        assert_eq!(start, "2024-01-02 22:00:00.000");
+        assert_eq!(end, "2024-01-02 22:01:00.000");
+    }
+
+    #[test]
+    fn test_parse_oanda_unix_time() {
+        assert_eq!(parse_oanda_time("1704232800.000000000"), Some(1_704_232_800_000_000));
+        assert_eq!(
+            parse_oanda_time("2024-01-02T22:00:00.000000000Z"),
+            Some(1_704_232_800_000_000)
+        );
+        assert_eq!(parse_oanda_time("not a time"), None);
+    }
+}
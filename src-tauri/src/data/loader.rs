@@ -7,9 +7,9 @@ use polars::prelude::*;
 use tracing::{info, warn};
 
 use crate::errors::AppError;
-use crate::models::config::{DataFormat, TickStorageFormat};
+use crate::models::config::{BarTimestamp, DataFormat, TickStorageFormat};
 
-use super::validator::ValidationResult;
+use super::validator::{DateTimeFormat, DateTimeLayout, ValidationResult};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CSV → DataFrame
@@ -46,57 +46,44 @@ fn load_bar_csv(
     };
 
     let sep = validation.delimiter as char;
+    let split_layout = matches!(validation.datetime_layout, DateTimeLayout::Split { .. });
     let headers: Vec<String> = if validation.has_header {
         header_line
             .split(sep)
             .map(|s| s.trim().trim_matches('"').to_lowercase())
             .collect()
     } else {
-        // Assign default names based on column count
-        match validation.column_count {
+        // Assign default names based on column count and detected timestamp layout
+        match (split_layout, validation.column_count) {
             // MT4/MT5: Date, Time, O, H, L, C, TickVol, Vol, Spread
-            9 => vec!["date", "time", "open", "high", "low", "close", "tick_volume", "volume", "spread"]
+            (true, 9) => vec!["date", "time", "open", "high", "low", "close", "tick_volume", "volume", "spread"]
                 .into_iter().map(String::from).collect(),
             // Date, Time, O, H, L, C, TickVol, Vol
-            8 => vec!["date", "time", "open", "high", "low", "close", "tick_volume", "volume"]
+            (true, 8) => vec!["date", "time", "open", "high", "low", "close", "tick_volume", "volume"]
                 .into_iter().map(String::from).collect(),
             // Date, Time, O, H, L, C, Vol
-            7 => vec!["date", "time", "open", "high", "low", "close", "volume"]
+            (true, _) => vec!["date", "time", "open", "high", "low", "close", "volume"]
                 .into_iter().map(String::from).collect(),
             // DateTime, O, H, L, C, Vol
-            _ => vec!["datetime", "open", "high", "low", "close", "volume"]
+            (false, _) => vec!["datetime", "open", "high", "low", "close", "volume"]
                 .into_iter().map(String::from).collect(),
         }
     };
 
-    let has_separate_date_time = headers.contains(&"date".to_string())
-        && headers.contains(&"time".to_string());
-
-    // Determine column indices
-    let dt_idx = if has_separate_date_time {
-        None
-    } else {
-        headers.iter().position(|h| h == "datetime")
-    };
-    let date_idx = headers.iter().position(|h| h == "date");
-    let time_idx = headers.iter().position(|h| h == "time");
-    let open_idx = headers
-        .iter()
-        .position(|h| h == "open")
+    // Determine column indices (explicit mapping wins over header names)
+    let mapping = validation.column_mapping.as_ref();
+    let open_idx = column_index(mapping.map(|m| m.open), &headers, "open")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Open' column".to_string()))?;
-    let high_idx = headers
-        .iter()
-        .position(|h| h == "high")
+    let high_idx = column_index(mapping.map(|m| m.high), &headers, "high")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'High' column".to_string()))?;
-    let low_idx = headers
-        .iter()
-        .position(|h| h == "low")
+    let low_idx = column_index(mapping.map(|m| m.low), &headers, "low")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Low' column".to_string()))?;
-    let close_idx = headers
-        .iter()
-        .position(|h| h == "close")
+    let close_idx = column_index(mapping.map(|m| m.close), &headers, "close")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Close' column".to_string()))?;
-    let vol_idx = headers.iter().position(|h| h == "volume");
+    let vol_idx = column_index(mapping.map(|m| m.volume), &headers, "volume");
+    let oi_idx = ["open_interest", "openinterest", "oi"]
+        .iter()
+        .find_map(|name| headers.iter().position(|h| h == name));
 
     let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
 
@@ -106,6 +93,7 @@ fn load_bar_csv(
     let mut lows: Vec<f64> = Vec::new();
     let mut closes: Vec<f64> = Vec::new();
     let mut volumes: Vec<f64> = Vec::new();
+    let mut open_interest: Vec<Option<f64>> = Vec::new();
 
     for (row_num, line) in lines.enumerate() {
         let cols: Vec<&str> = line.split(sep).collect();
@@ -114,17 +102,8 @@ fn load_bar_csv(
             continue;
         }
 
-        let dt_us = if has_separate_date_time {
-            let date_str = date_idx.map(|i| cols.get(i).copied().unwrap_or("")).unwrap_or("");
-            let time_str = time_idx.map(|i| cols.get(i).copied().unwrap_or("")).unwrap_or("");
-            parse_date_time(date_str.trim(), time_str.trim(), row_num)?
-        } else {
-            let dt_str = dt_idx
-                .and_then(|i| cols.get(i).copied())
-                .unwrap_or("")
-                .trim();
-            parse_datetime(dt_str, row_num)?
-        };
+        let dt_str = validation.datetime_layout.extract(|i| cols.get(i).copied());
+        let dt_us = parse_detected_datetime(&dt_str, validation.datetime_format, row_num)?;
 
         let open = parse_f64(cols.get(open_idx).copied().unwrap_or(""), row_num, "Open")?;
         let high = parse_f64(cols.get(high_idx).copied().unwrap_or(""), row_num, "High")?;
@@ -141,9 +120,17 @@ fn load_bar_csv(
         lows.push(low);
         closes.push(close);
         volumes.push(volume);
+        if let Some(i) = oi_idx {
+            open_interest.push(cols.get(i).and_then(|s| s.trim().parse::<f64>().ok()));
+        }
     }
 
-    build_ohlcv_dataframe(datetimes, opens, highs, lows, closes, volumes)
+    let mut df = build_ohlcv_dataframe(datetimes, opens, highs, lows, closes, volumes)?;
+    if oi_idx.is_some() {
+        df.with_column(Series::new("open_interest".into(), open_interest))
+            .map_err(|e| AppError::ParquetConversion(format!("open_interest column: {}", e)))?;
+    }
+    Ok(df)
 }
 
 fn load_tick_csv(
@@ -174,16 +161,12 @@ fn load_tick_csv(
             .collect()
     };
 
-    let dt_idx = headers.iter().position(|h| h == "datetime").unwrap_or(0);
-    let bid_idx = headers
-        .iter()
-        .position(|h| h == "bid")
+    let mapping = validation.column_mapping.as_ref();
+    let bid_idx = column_index(mapping.map(|m| m.bid), &headers, "bid")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Bid' column".to_string()))?;
-    let ask_idx = headers
-        .iter()
-        .position(|h| h == "ask")
+    let ask_idx = column_index(mapping.map(|m| m.ask), &headers, "ask")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Ask' column".to_string()))?;
-    let vol_idx = headers.iter().position(|h| h == "volume");
+    let vol_idx = column_index(mapping.map(|m| m.volume), &headers, "volume");
 
     let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
 
@@ -199,7 +182,8 @@ fn load_tick_csv(
             continue;
         }
 
-        let dt_us = parse_datetime(cols.get(dt_idx).copied().unwrap_or("").trim(), row_num)?;
+        let dt_str = validation.datetime_layout.extract(|i| cols.get(i).copied());
+        let dt_us = parse_detected_datetime(&dt_str, validation.datetime_format, row_num)?;
         let bid = parse_f64(cols.get(bid_idx).copied().unwrap_or(""), row_num, "Bid")?;
         let ask = parse_f64(cols.get(ask_idx).copied().unwrap_or(""), row_num, "Ask")?;
         let volume = vol_idx
@@ -220,17 +204,26 @@ fn load_tick_csv(
         .map(|(b, a)| (b + a) / 2.0)
         .collect();
 
-    build_ohlcv_dataframe(
+    let spreads: Vec<f64> = bids
+        .iter()
+        .zip(asks.iter())
+        .map(|(b, a)| a - b)
+        .collect();
+
+    let mut df = build_ohlcv_dataframe(
         datetimes,
         mid.clone(),
         mid.clone(),
         mid.clone(),
         mid,
         volumes,
-    )
+    )?;
+    df.with_column(Series::new("avg_spread".into(), spreads))
+        .map_err(|e| AppError::ParquetConversion(format!("avg_spread column: {}", e)))?;
+    Ok(df)
 }
 
-fn build_ohlcv_dataframe(
+pub(crate) fn build_ohlcv_dataframe(
     datetimes: Vec<i64>,
     opens: Vec<f64>,
     highs: Vec<f64>,
@@ -255,6 +248,49 @@ fn build_ohlcv_dataframe(
     Ok(df)
 }
 
+/// Normalize bar timestamps to the internal bar-open convention.
+///
+/// Close-stamped bars are shifted back by one bar width, taken as the smallest
+/// positive gap between consecutive rows (gaps only ever span whole bars).
+/// Must run before resampling so derived timeframes bucket the right bars.
+pub fn normalize_bar_timestamps(
+    df: DataFrame,
+    timestamp_is: BarTimestamp,
+) -> Result<DataFrame, AppError> {
+    if timestamp_is == BarTimestamp::BarOpen || df.height() < 2 {
+        return Ok(df);
+    }
+
+    let ts = df
+        .column("datetime")
+        .map_err(|_| AppError::InvalidCsvFormat("DataFrame has no 'datetime' column".into()))?
+        .cast(&DataType::Int64)
+        .map_err(|e| AppError::ParquetConversion(format!("cast datetime to i64: {}", e)))?;
+    let ts: Vec<i64> = ts
+        .i64()
+        .map_err(|e| AppError::ParquetConversion(e.to_string()))?
+        .into_no_null_iter()
+        .collect();
+    let bar_us = ts
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|&d| d > 0)
+        .min()
+        .ok_or_else(|| {
+            AppError::InvalidCsvFormat("Cannot infer bar width from identical timestamps".into())
+        })?;
+    info!("Shifting close-stamped bars back by {} s", bar_us / 1_000_000);
+
+    df.lazy()
+        .with_column(
+            (col("datetime").cast(DataType::Int64) - lit(bar_us))
+                .cast(DataType::Datetime(TimeUnit::Microseconds, None))
+                .alias("datetime"),
+        )
+        .collect()
+        .map_err(|e| AppError::ParquetConversion(format!("collect shift: {}", e)))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Date Range
 // ─────────────────────────────────────────────────────────────────────────────
@@ -345,6 +381,34 @@ pub fn scan_parquet_lazy(path: &Path) -> Result<LazyFrame, AppError> {
     }
 }
 
+/// Read `limit` contiguous rows starting at `offset` from a Parquet file or directory.
+///
+/// The slice is pushed down into the lazy scan so only the requested rows are
+/// materialized. An offset past the end yields an empty frame.
+pub fn read_parquet_slice(path: &Path, offset: usize, limit: usize) -> Result<DataFrame, AppError> {
+    scan_parquet_lazy(path)?
+        .slice(offset as i64, limit as IdxSize)
+        .collect()
+        .map_err(|e| AppError::ParquetConversion(format!("read parquet slice: {}", e)))
+}
+
+/// Count the rows of a Parquet file or directory without loading the data.
+pub fn count_parquet_rows(path: &Path) -> Result<usize, AppError> {
+    let df = scan_parquet_lazy(path)?
+        .select([len().alias("rows")])
+        .collect()
+        .map_err(|e| AppError::ParquetConversion(format!("count parquet rows: {}", e)))?;
+    let rows = df
+        .column("rows")
+        .and_then(|c| c.cast(&DataType::UInt64))
+        .map_err(|e| AppError::ParquetConversion(format!("count parquet rows: {}", e)))?
+        .u64()
+        .map_err(|e| AppError::ParquetConversion(format!("count parquet rows: {}", e)))?
+        .get(0)
+        .unwrap_or(0);
+    Ok(rows as usize)
+}
+
 /// Build an optional date filter expression for Polars `LazyFrame::filter`.
 ///
 /// Filters the `datetime` column (Datetime Microseconds UTC) to `[start_date, end_date]`.
@@ -460,16 +524,12 @@ pub fn stream_tick_csv_to_parquet(
         default_headers.iter().map(|s| s.to_string()).collect()
     };
 
-    let dt_idx = headers.iter().position(|h| h == "datetime").unwrap_or(0);
-    let bid_idx = headers
-        .iter()
-        .position(|h| h == "bid")
+    let mapping = validation.column_mapping.as_ref();
+    let bid_idx = column_index(mapping.map(|m| m.bid), &headers, "bid")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Bid' column in tick CSV".into()))?;
-    let ask_idx = headers
-        .iter()
-        .position(|h| h == "ask")
+    let ask_idx = column_index(mapping.map(|m| m.ask), &headers, "ask")
         .ok_or_else(|| AppError::InvalidCsvFormat("Missing 'Ask' column in tick CSV".into()))?;
-    let vol_idx = headers.iter().position(|h| h == "volume");
+    let vol_idx = column_index(mapping.map(|m| m.volume), &headers, "volume");
 
     let tz_offset_us = (tz_offset_hours * 3_600_000_000.0) as i64;
 
@@ -492,21 +552,25 @@ pub fn stream_tick_csv_to_parquet(
             }
         }
 
-        let dt_str = record.get(dt_idx).unwrap_or("").trim();
+        let dt_str = validation.datetime_layout.extract(|i| record.get(i));
         if dt_str.is_empty() {
             continue;
         }
 
-        // Fast-path datetime parser — falls back to chrono only for unusual formats.
-        let dt_us = match parse_datetime_fast(dt_str.as_bytes())
-            .or_else(|| parse_datetime(dt_str, total_rows).ok())
-        {
-            Some(v) => v + tz_offset_us,
-            None => {
-                warn!("Row {}: cannot parse datetime '{}'", total_rows + 1, dt_str);
-                continue;
-            }
+        // Fast-path datetime parser — falls back to the detected format (then chrono)
+        // only for unusual layouts. The year is read straight from the string when
+        // the fast path matched, since it is then guaranteed to be year-first.
+        let (dt_us, year) = match parse_datetime_fast(dt_str.as_bytes()) {
+            Some(v) => (v, year_from_str(dt_str.as_bytes())),
+            None => match parse_detected_datetime(&dt_str, validation.datetime_format, total_rows) {
+                Ok(v) => (v, year_from_us(v)),
+                Err(_) => {
+                    warn!("Row {}: cannot parse datetime '{}'", total_rows + 1, dt_str);
+                    continue;
+                }
+            },
         };
+        let dt_us = dt_us + tz_offset_us;
 
         let bid = match record.get(bid_idx).unwrap_or("").trim().parse::<f64>() {
             Ok(v) => v,
@@ -521,8 +585,6 @@ pub fn stream_tick_csv_to_parquet(
             .and_then(|s| s.trim().parse::<f64>().ok())
             .unwrap_or(0.0);
 
-        // Extract year directly from the timestamp string — avoids DateTime construction.
-        let year = year_from_str(dt_str.as_bytes());
         year_buckets.entry(year).or_insert_with(YearBucket::new).push(dt_us, bid, ask, volume);
 
         total_rows += 1;
@@ -618,8 +680,14 @@ fn flush_year_bucket(
         .zip(bucket.asks.iter())
         .map(|(b, a)| (b + a) / 2.0)
         .collect();
+    let spreads: Vec<f64> = bucket
+        .bids
+        .iter()
+        .zip(bucket.asks.iter())
+        .map(|(b, a)| a - b)
+        .collect();
 
-    let mut tick_df = build_tick_ohlcv_df(&bucket.datetimes, &mids, &bucket.volumes)?;
+    let mut tick_df = build_tick_ohlcv_df(&bucket.datetimes, &mids, &spreads, &bucket.volumes)?;
 
     // Write OHLCV tick parquet
     let tick_path = tick_dir.join(format!("{}.parquet", year));
@@ -644,6 +712,7 @@ fn flush_year_bucket(
 fn build_tick_ohlcv_df(
     datetimes: &[i64],
     mids: &[f64],
+    spreads: &[f64],
     volumes: &[f64],
 ) -> Result<DataFrame, AppError> {
     let dt_series = Series::new("datetime".into(), datetimes)
@@ -657,6 +726,7 @@ fn build_tick_ohlcv_df(
         Series::new("low".into(), mids).into_column(),
         Series::new("close".into(), mids).into_column(),
         Series::new("volume".into(), volumes).into_column(),
+        Series::new("avg_spread".into(), spreads).into_column(),
     ])
     .map_err(|e| AppError::ParquetConversion(format!("tick ohlcv df: {}", e)))
 }
@@ -937,6 +1007,14 @@ fn year_from_str(s: &[u8]) -> i32 {
     1970
 }
 
+/// Extract the year from a microsecond timestamp (slow path for non year-first formats).
+fn year_from_us(us: i64) -> i32 {
+    use chrono::Datelike;
+    chrono::DateTime::from_timestamp_micros(us)
+        .map(|dt| dt.year())
+        .unwrap_or(1970)
+}
+
 /// Fast-path parser for the two most common tick datetime formats:
 ///   "YYYY-MM-DD HH:MM:SS.ffffff"  (ISO with fractional seconds)
 ///   "YYYY-MM-DD HH:MM:SS"         (ISO without fractional seconds)
@@ -1072,12 +1150,26 @@ fn parse_datetime(s: &str, row: usize) -> Result<i64, AppError> {
     })
 }
 
-fn parse_date_time(date_str: &str, time_str: &str, row: usize) -> Result<i64, AppError> {
-    let combined = format!("{} {}", date_str.trim(), time_str.trim());
-    parse_datetime(&combined, row)
+/// Resolve a column index: an explicit mapping (outer `Some`) is authoritative,
+/// otherwise the column is looked up by its lower-cased header name.
+fn column_index(mapped: Option<Option<usize>>, headers: &[String], name: &str) -> Option<usize> {
+    match mapped {
+        Some(idx) => idx,
+        None => headers.iter().position(|h| h == name),
+    }
 }
 
-fn parse_f64(s: &str, row: usize, field: &str) -> Result<f64, AppError> {
+/// Parse a timestamp using the format detected by the validator, falling back
+/// to the generic multi-format parser for rows that deviate from it.
+fn parse_detected_datetime(s: &str, format: DateTimeFormat, row: usize) -> Result<i64, AppError> {
+    match format.parse(s) {
+        Some(us) => Ok(us),
+        None => parse_datetime(s, row),
+    }
+}
+
+/// Parse a CSV price cell, ignoring surrounding whitespace and quotes.
+pub(crate) fn parse_f64(s: &str, row: usize, field: &str) -> Result<f64, AppError> {
     let trimmed = s.trim().trim_matches('"');
     trimmed.parse::<f64>().map_err(|_| AppError::CsvParseError {
         row: row + 1,
@@ -1085,3 +1177,127 @@ fn parse_f64(s: &str, row: usize, field: &str) -> Result<f64, AppError> {
     })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::validator::validate_csv;
+    use crate::models::config::ColumnMapping;
+
+    /// Write `content` to a unique file in the system temp dir.
+    fn write_fixture(content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("loader_{}.csv", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn f64_col(df: &DataFrame, name: &str) -> Vec<f64> {
+        df.column(name).unwrap().f64().unwrap().into_no_null_iter().collect()
+    }
+
+    #[test]
+    fn test_parquet_slice_returns_contiguous_rows() {
+        let path = std::env::temp_dir().join(format!("loader_{}.parquet", uuid::Uuid::new_v4()));
+        let mut df = df!("close" => (0..100).map(|i| i as f64).collect::<Vec<_>>()).unwrap();
+        write_parquet(&mut df, &path).unwrap();
+
+        let page = read_parquet_slice(&path, 40, 10).unwrap();
+        let tail = read_parquet_slice(&path, 95, 10).unwrap();
+        let past_end = read_parquet_slice(&path, 200, 10).unwrap();
+        let rows = count_parquet_rows(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(f64_col(&page, "close"), (40..50).map(|i| i as f64).collect::<Vec<_>>());
+        assert_eq!(f64_col(&tail, "close"), vec![95.0, 96.0, 97.0, 98.0, 99.0]);
+        assert_eq!(past_end.height(), 0);
+        assert_eq!(rows, 100);
+    }
+
+    #[test]
+    fn test_bar_csv_with_shuffled_and_extra_columns() {
+        let path = write_fixture(
+            "ticker,vol,c,stamp,l,notes,o,h\n\
+             EURUSD,120,1.1005,2024-01-15 09:30:00,1.0990,x,1.1000,1.1010\n\
+             EURUSD,95,1.1010,2024-01-15 09:31:00,1.1000,y,1.1005,1.1015\n",
+        );
+        let mapping = ColumnMapping {
+            datetime: 3,
+            open: Some(6),
+            high: Some(7),
+            low: Some(4),
+            close: Some(2),
+            volume: Some(1),
+            ..Default::default()
+        };
+        let validation = validate_csv(&path, Some(&mapping)).unwrap();
+        let df = load_csv_to_dataframe(&path, &validation, 0.0).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(df.height(), 2);
+        assert_eq!(f64_col(&df, "open"), vec![1.1000, 1.1005]);
+        assert_eq!(f64_col(&df, "high"), vec![1.1010, 1.1015]);
+        assert_eq!(f64_col(&df, "low"), vec![1.0990, 1.1000]);
+        assert_eq!(f64_col(&df, "close"), vec![1.1005, 1.1010]);
+        assert_eq!(f64_col(&df, "volume"), vec![120.0, 95.0]);
+        let (start, end) = get_date_range(&df).unwrap();
+        assert_eq!(start, "2024-01-15 09:30:00.000");
+        assert_eq!(end, "2024-01-15 09:31:00.000");
+    }
+
+    #[test]
+    fn test_close_stamped_m1_aligns_with_open_stamped() {
+        use crate::data::converter::generate_all_timeframes;
+        use crate::models::config::Timeframe;
+
+        // 12 M1 bars from 09:00 — the close-stamped feed labels each bar one minute later
+        let base = 1_705_309_200_000_000_i64; // 2024-01-15 09:00:00
+        let closes: Vec<f64> = (0..12).map(|i| 1.1 + i as f64 * 0.0001).collect();
+        let bars = |offset_us: i64| {
+            let dts = (0..12).map(|i| base + offset_us + i * 60_000_000).collect();
+            build_ohlcv_dataframe(dts, closes.clone(), closes.clone(), closes.clone(), closes.clone(), vec![1.0; 12])
+                .unwrap()
+        };
+        let open_df = normalize_bar_timestamps(bars(0), BarTimestamp::BarOpen).unwrap();
+        let close_df = normalize_bar_timestamps(bars(60_000_000), BarTimestamp::BarClose).unwrap();
+        assert!(open_df.equals(&close_df));
+
+        let read_m5 = |df: &DataFrame| {
+            let dir = std::env::temp_dir().join(format!("loader_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let paths = generate_all_timeframes(df, Timeframe::M1, &dir).unwrap();
+            let m5 = scan_parquet_lazy(Path::new(&paths["m5"])).unwrap().collect().unwrap();
+            std::fs::remove_dir_all(&dir).ok();
+            m5
+        };
+        let (open_m5, close_m5) = (read_m5(&open_df), read_m5(&close_df));
+        assert_eq!(open_m5.height(), 3);
+        assert!(open_m5.equals(&close_m5));
+        assert_eq!(get_date_range(&close_m5).unwrap().0, "2024-01-15 09:00:00.000");
+        // 09:00 M5 candle is built from the 09:00–09:04 bars: close of the 5th bar
+        assert_eq!(f64_col(&close_m5, "close")[0], closes[4]);
+    }
+
+    #[test]
+    fn test_tick_csv_with_split_date_time_mapping() {
+        let path = write_fixture(
+            "ask_px;day;flag;bid_px;clock\n\
+             1.1002;15/01/2024;0;1.1000;09:30:00\n\
+             1.1004;16/01/2024;0;1.1002;09:30:01\n",
+        );
+        let mapping = ColumnMapping {
+            datetime: 1,
+            time: Some(4),
+            bid: Some(3),
+            ask: Some(0),
+            ..Default::default()
+        };
+        let validation = validate_csv(&path, Some(&mapping)).unwrap();
+        assert_eq!(validation.format, DataFormat::Tick);
+        let df = load_csv_to_dataframe(&path, &validation, 0.0).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(f64_col(&df, "close"), vec![1.1001, 1.1003]);
+        let (start, end) = get_date_range(&df).unwrap();
+        assert_eq!(start, "2024-01-15 09:30:00.000");
+        assert_eq!(end, "2024-01-16 09:30:01.000");
+    }
+}
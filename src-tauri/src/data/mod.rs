@@ -1,5 +1,6 @@
 pub mod converter;
 pub mod dukascopy;
+pub mod importers;
 pub mod loader;
 pub mod storage;
 pub mod validator;
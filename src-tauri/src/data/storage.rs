@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use rusqlite::{Connection, params};
 use tracing::info;
 
@@ -53,6 +55,22 @@ pub fn initialize_database(db_path: &str) -> Result<Connection, AppError> {
             config_json TEXT NOT NULL,
             front_json  TEXT NOT NULL
         );
+
+        CREATE TABLE IF NOT EXISTS backtest_results (
+            id               TEXT PRIMARY KEY,
+            name             TEXT NOT NULL,
+            created_at       TEXT NOT NULL,
+            strategy_name    TEXT NOT NULL,
+            symbol_id        TEXT NOT NULL,
+            timeframe        TEXT NOT NULL,
+            start_date       TEXT NOT NULL,
+            end_date         TEXT NOT NULL,
+            total_trades     INTEGER NOT NULL,
+            net_profit       REAL NOT NULL,
+            total_return_pct REAL NOT NULL,
+            strategy_json    TEXT NOT NULL,
+            results_json     TEXT NOT NULL
+        );
         ",
     )?;
 
@@ -168,6 +186,20 @@ pub fn update_symbol_tz(
     Ok(())
 }
 
+/// Replace the stored timeframe → path map (e.g. after an on-demand resample).
+pub fn update_symbol_timeframe_paths(
+    db: &Connection,
+    id: &str,
+    timeframe_paths: &HashMap<String, String>,
+) -> Result<(), AppError> {
+    let paths_json = serde_json::to_string(timeframe_paths)?;
+    db.execute(
+        "UPDATE symbols SET timeframe_paths = ?1 WHERE id = ?2",
+        params![paths_json, id],
+    )?;
+    Ok(())
+}
+
 /// Insert a symbol with status "downloading" (before download completes).
 pub fn insert_pending_symbol(db: &Connection, symbol: &Symbol) -> Result<(), AppError> {
     insert_symbol(db, symbol)
@@ -422,3 +454,250 @@ pub fn delete_sr_session_by_id(db: &Connection, id: &str) -> Result<(), AppError
     info!("Deleted SR session: {}", id);
     Ok(())
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Saved Backtest CRUD
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Listing metadata for a saved backtest (no trades or curves).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedBacktestMeta {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub strategy_name: String,
+    pub symbol_id: String,
+    pub timeframe: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub total_trades: usize,
+    pub net_profit: f64,
+    pub total_return_pct: f64,
+}
+
+/// A saved backtest: its metadata, the strategy that produced it and the full
+/// `BacktestResults` (which carries the `BacktestConfig`).
+///
+/// `results` stays raw JSON: non-finite metrics serialize as `null`, which
+/// wouldn't deserialize back into `f64`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SavedBacktest {
+    #[serde(flatten)]
+    pub meta: SavedBacktestMeta,
+    pub strategy: Strategy,
+    pub results: serde_json::Value,
+}
+
+impl SavedBacktest {
+    /// Build a new saved backtest, filling the metadata from `results`.
+    /// Without a `name`, one is generated from strategy, symbol, timeframe and time.
+    pub fn new(
+        name: Option<String>,
+        strategy: Strategy,
+        results: serde_json::Value,
+        symbol_name: &str,
+    ) -> Self {
+        let created_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let config = &results["backtest_config"];
+        let metrics = &results["metrics"];
+        let text = |v: &serde_json::Value| v.as_str().unwrap_or("").to_string();
+        let timeframe = text(&config["timeframe"]);
+        let name = name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| {
+                format!("{} · {} {} · {}", strategy.name, symbol_name, timeframe, created_at)
+            });
+        Self {
+            meta: SavedBacktestMeta {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                created_at,
+                strategy_name: strategy.name.clone(),
+                symbol_id: text(&config["symbol_id"]),
+                timeframe,
+                start_date: text(&config["start_date"]),
+                end_date: text(&config["end_date"]),
+                total_trades: metrics["total_trades"].as_u64().unwrap_or(0) as usize,
+                net_profit: metrics["net_profit"].as_f64().unwrap_or(0.0),
+                total_return_pct: metrics["total_return_pct"].as_f64().unwrap_or(0.0),
+            },
+            strategy,
+            results,
+        }
+    }
+}
+
+/// Insert a saved backtest into the database.
+pub fn insert_backtest_result(db: &Connection, saved: &SavedBacktest) -> Result<(), AppError> {
+    let m = &saved.meta;
+    db.execute(
+        "INSERT INTO backtest_results (id, name, created_at, strategy_name, symbol_id, timeframe,
+             start_date, end_date, total_trades, net_profit, total_return_pct, strategy_json, results_json)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            m.id,
+            m.name,
+            m.created_at,
+            m.strategy_name,
+            m.symbol_id,
+            m.timeframe,
+            m.start_date,
+            m.end_date,
+            m.total_trades as i64,
+            m.net_profit,
+            m.total_return_pct,
+            serde_json::to_string(&saved.strategy)?,
+            serde_json::to_string(&saved.results)?,
+        ],
+    )?;
+    info!("Saved backtest result: {} ({})", m.name, m.id);
+    Ok(())
+}
+
+const BACKTEST_META_COLUMNS: &str = "id, name, created_at, strategy_name, symbol_id, timeframe, \
+     start_date, end_date, total_trades, net_profit, total_return_pct";
+
+fn row_to_backtest_meta(row: &rusqlite::Row<'_>) -> Result<SavedBacktestMeta, rusqlite::Error> {
+    Ok(SavedBacktestMeta {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        strategy_name: row.get(3)?,
+        symbol_id: row.get(4)?,
+        timeframe: row.get(5)?,
+        start_date: row.get(6)?,
+        end_date: row.get(7)?,
+        total_trades: row.get::<_, i64>(8)? as usize,
+        net_profit: row.get(9)?,
+        total_return_pct: row.get(10)?,
+    })
+}
+
+/// List saved backtests, most recent first. Metadata only; the payload columns aren't read.
+pub fn get_all_backtest_results(db: &Connection) -> Result<Vec<SavedBacktestMeta>, AppError> {
+    let mut stmt = db.prepare(&format!(
+        "SELECT {} FROM backtest_results ORDER BY created_at DESC",
+        BACKTEST_META_COLUMNS
+    ))?;
+    let rows = stmt
+        .query_map([], row_to_backtest_meta)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Retrieve a saved backtest with its strategy and full results.
+pub fn get_backtest_result_by_id(db: &Connection, id: &str) -> Result<SavedBacktest, AppError> {
+    let mut stmt = db.prepare(&format!(
+        "SELECT {}, strategy_json, results_json FROM backtest_results WHERE id = ?1",
+        BACKTEST_META_COLUMNS
+    ))?;
+    let (meta, strategy_json, results_json) = stmt
+        .query_row(params![id], |row| {
+            Ok((row_to_backtest_meta(row)?, row.get::<_, String>(11)?, row.get::<_, String>(12)?))
+        })
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Backtest result not found: {}", id))
+            }
+            other => AppError::Database(other.to_string()),
+        })?;
+    Ok(SavedBacktest {
+        meta,
+        strategy: serde_json::from_str(&strategy_json)?,
+        results: serde_json::from_str(&results_json)?,
+    })
+}
+
+/// Delete a saved backtest by ID.
+pub fn delete_backtest_result_by_id(db: &Connection, id: &str) -> Result<(), AppError> {
+    db.execute("DELETE FROM backtest_results WHERE id = ?1", params![id])?;
+    info!("Deleted backtest result: {}", id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_strategy() -> Strategy {
+        serde_json::from_value(serde_json::json!({
+            "id": "s1",
+            "name": "MA Cross",
+            "created_at": "2024-01-01 00:00:00",
+            "updated_at": "2024-01-01 00:00:00",
+            "long_entry_rules": [],
+            "long_exit_rules": [],
+            "position_sizing": { "sizing_type": "FixedLots", "value": 1.0 },
+            "trading_costs": {
+                "spread_pips": 1.0,
+                "commission_type": "FixedPerLot",
+                "commission_value": 0.0,
+                "slippage_pips": 0.0,
+                "slippage_random": false
+            },
+            "trade_direction": "Long"
+        }))
+        .unwrap()
+    }
+
+    fn sample_results() -> serde_json::Value {
+        serde_json::json!({
+            "trades": [{ "entry_price": 1.1, "exit_price": 1.2, "pnl": 100.0 }],
+            "equity_curve": [{ "timestamp": "2024-01-02 00:00", "equity": 10100.0 }],
+            "metrics": {
+                "net_profit": 1250.5,
+                "total_return_pct": 12.505,
+                "total_trades": 42,
+                // Non-finite metrics arrive as null and must survive the round trip.
+                "annualized_return_pct": null
+            },
+            "backtest_config": {
+                "symbol_id": "sym-1",
+                "timeframe": "h1",
+                "start_date": "2024-01-01",
+                "end_date": "2024-06-30"
+            }
+        })
+    }
+
+    #[test]
+    fn test_saved_backtest_reloads_identically() {
+        let db = initialize_database(":memory:").unwrap();
+        let saved = SavedBacktest::new(None, sample_strategy(), sample_results(), "EURUSD");
+        assert!(saved.meta.name.starts_with("MA Cross · EURUSD h1 · "), "{}", saved.meta.name);
+        insert_backtest_result(&db, &saved).unwrap();
+
+        let loaded = get_backtest_result_by_id(&db, &saved.meta.id).unwrap();
+        assert_eq!(loaded.meta, saved.meta);
+        assert_eq!(loaded.results, sample_results());
+        assert_eq!(
+            serde_json::to_string(&loaded.strategy).unwrap(),
+            serde_json::to_string(&saved.strategy).unwrap()
+        );
+
+        delete_backtest_result_by_id(&db, &saved.meta.id).unwrap();
+        assert!(matches!(
+            get_backtest_result_by_id(&db, &saved.meta.id),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_backtest_results_returns_metadata_only() {
+        let db = initialize_database(":memory:").unwrap();
+        let saved = SavedBacktest::new(Some("Baseline".into()), sample_strategy(), sample_results(), "EURUSD");
+        insert_backtest_result(&db, &saved).unwrap();
+
+        let list = get_all_backtest_results(&db).unwrap();
+        assert_eq!(list, vec![saved.meta.clone()]);
+        let meta = &list[0];
+        assert_eq!(meta.name, "Baseline");
+        assert_eq!(meta.symbol_id, "sym-1");
+        assert_eq!(meta.total_trades, 42);
+        assert_eq!(meta.net_profit, 1250.5);
+
+        let json = serde_json::to_value(meta).unwrap();
+        assert!(json.get("results").is_none() && json.get("strategy").is_none());
+    }
+}
@@ -1,9 +1,13 @@
 use std::path::Path;
 
+use polars::prelude::*;
+use serde::Serialize;
+
 use crate::errors::AppError;
-use crate::models::config::DataFormat;
+use crate::models::config::{ColumnMapping, DataFormat};
 
 /// Result of CSV validation and format detection.
+#[derive(Debug)]
 pub struct ValidationResult {
     /// Whether the data is tick or bar (OHLCV) format.
     pub format: DataFormat,
@@ -15,13 +19,126 @@ pub struct ValidationResult {
     pub row_count_sample: usize,
     /// Number of columns detected.
     pub column_count: usize,
+    /// Encoding of the timestamp values, detected from the sampled rows.
+    pub datetime_format: DateTimeFormat,
+    /// Which column(s) hold the timestamp.
+    pub datetime_layout: DateTimeLayout,
+    /// Explicit user column mapping; when set, the loader uses it instead of
+    /// header names or positional defaults.
+    pub column_mapping: Option<ColumnMapping>,
+}
+
+/// How timestamp values are encoded in the CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeFormat {
+    /// A chrono pattern containing both date and time (e.g. `%Y-%m-%d %H:%M:%S%.f`).
+    Pattern(&'static str),
+    /// A chrono pattern containing only a date; the time is midnight.
+    DateOnly(&'static str),
+    /// Integer seconds since the Unix epoch.
+    EpochSeconds,
+    /// Integer milliseconds since the Unix epoch.
+    EpochMillis,
+    /// Integer microseconds since the Unix epoch.
+    EpochMicros,
+}
+
+impl DateTimeFormat {
+    /// Parse a timestamp string into microseconds since epoch, or `None` if it
+    /// does not match this format.
+    pub fn parse(&self, s: &str) -> Option<i64> {
+        let s = s.trim().trim_matches('"');
+        match self {
+            DateTimeFormat::Pattern(p) => chrono::NaiveDateTime::parse_from_str(s, p)
+                .ok()
+                .map(|ndt| ndt.and_utc().timestamp_micros()),
+            DateTimeFormat::DateOnly(p) => chrono::NaiveDate::parse_from_str(s, p)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|ndt| ndt.and_utc().timestamp_micros()),
+            DateTimeFormat::EpochSeconds => parse_epoch_digits(s).map(|v| v * 1_000_000),
+            DateTimeFormat::EpochMillis => parse_epoch_digits(s).map(|v| v * 1_000),
+            DateTimeFormat::EpochMicros => parse_epoch_digits(s),
+        }
+    }
+
+    /// Human-readable description, used in logs and error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            DateTimeFormat::Pattern(p) | DateTimeFormat::DateOnly(p) => p.to_string(),
+            DateTimeFormat::EpochSeconds => "epoch seconds".to_string(),
+            DateTimeFormat::EpochMillis => "epoch milliseconds".to_string(),
+            DateTimeFormat::EpochMicros => "epoch microseconds".to_string(),
+        }
+    }
 }
 
+/// Where the timestamp lives within a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeLayout {
+    /// A single column holding date and time together.
+    Combined { column: usize },
+    /// Separate date and time columns, joined with a space before parsing.
+    Split { date_column: usize, time_column: usize },
+}
+
+impl DateTimeLayout {
+    /// Extract the timestamp text of a row, joining split date/time columns.
+    pub fn extract<'a>(&self, field: impl Fn(usize) -> Option<&'a str>) -> String {
+        match *self {
+            DateTimeLayout::Combined { column } => field(column).unwrap_or("").trim().to_string(),
+            DateTimeLayout::Split { date_column, time_column } => format!(
+                "{} {}",
+                field(date_column).unwrap_or("").trim(),
+                field(time_column).unwrap_or("").trim()
+            ),
+        }
+    }
+}
+
+/// Combined date+time patterns, tried in priority order. Year-first layouts come
+/// first; for slash dates, month-first (US) is preferred over day-first unless a
+/// sampled row rules it out.
+const DATETIME_PATTERNS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y.%m.%d %H:%M:%S%.f",
+    "%Y.%m.%d %H:%M",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y/%m/%d %H:%M",
+    "%Y%m%d %H:%M:%S%.f",
+    "%Y%m%d %H%M%S",
+    "%m/%d/%Y %H:%M:%S%.f",
+    "%m/%d/%Y %H:%M",
+    "%d/%m/%Y %H:%M:%S%.f",
+    "%d/%m/%Y %H:%M",
+    "%d.%m.%Y %H:%M:%S%.f",
+    "%d.%m.%Y %H:%M",
+    "%d-%m-%Y %H:%M:%S%.f",
+    "%d-%m-%Y %H:%M",
+];
+
+/// Date-only patterns (daily bars), tried after the combined patterns.
+const DATE_ONLY_PATTERNS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y.%m.%d",
+    "%Y/%m/%d",
+    "%Y%m%d",
+    "%m/%d/%Y",
+    "%d/%m/%Y",
+    "%d.%m.%Y",
+];
+
 /// Validate a CSV file and detect its format (Tick or Bar).
 ///
 /// Reads up to the first 100 rows to determine delimiter, header presence,
-/// column count, and data format.
-pub fn validate_csv(path: &Path) -> Result<ValidationResult, AppError> {
+/// column count, and data format. When `column_mapping` is given, the format and
+/// timestamp columns come from the mapping, which is checked against the file.
+pub fn validate_csv(
+    path: &Path,
+    column_mapping: Option<&ColumnMapping>,
+) -> Result<ValidationResult, AppError> {
     if !path.exists() {
         return Err(AppError::FileNotFound(path.to_string_lossy().to_string()));
     }
@@ -29,12 +146,19 @@ pub fn validate_csv(path: &Path) -> Result<ValidationResult, AppError> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| AppError::FileRead(format!("{}: {}", path.display(), e)))?;
 
+    validate_csv_content(&content, column_mapping)
+}
+
+fn validate_csv_content(
+    content: &str,
+    column_mapping: Option<&ColumnMapping>,
+) -> Result<ValidationResult, AppError> {
     if content.trim().is_empty() {
         return Err(AppError::InvalidCsvFormat("File is empty".to_string()));
     }
 
     // Detect delimiter
-    let delimiter = detect_delimiter(&content);
+    let delimiter = detect_delimiter(content);
 
     // Split lines and find header
     let mut lines = content.lines().filter(|l| !l.trim().is_empty());
@@ -59,15 +183,35 @@ pub fn validate_csv(path: &Path) -> Result<ValidationResult, AppError> {
     // Determine if the first row is a header by checking if first cell parses as a number
     let has_header = is_header_row(&headers);
 
-    // Detect format from column names (if header) or column count
-    let format = if has_header {
+    // Detect format from the mapping, column names (if header) or column count
+    let format = if let Some(mapping) = column_mapping {
+        validate_column_mapping(mapping, column_count)?;
+        mapping.data_format()
+    } else if has_header {
         detect_format_from_headers(&headers)?
     } else {
         detect_format_from_column_count(column_count)?
     };
 
-    // Count sample rows (skip header if present)
-    let row_count_sample = lines.take(100).count();
+    // Sample data rows (skip header if present). Without a header the first
+    // line is itself a data row.
+    let sample: Vec<Vec<&str>> = (if has_header { None } else { Some(first_line) })
+        .into_iter()
+        .chain(lines)
+        .take(100)
+        .map(|l| l.split(delimiter as char).map(|s| s.trim()).collect())
+        .collect();
+    let row_count_sample = sample.len();
+
+    let datetime_layout = match column_mapping {
+        Some(ColumnMapping { datetime, time: Some(time), .. }) => DateTimeLayout::Split {
+            date_column: *datetime,
+            time_column: *time,
+        },
+        Some(mapping) => DateTimeLayout::Combined { column: mapping.datetime },
+        None => detect_datetime_layout(&headers, has_header, format, sample.first().map(Vec::as_slice)),
+    };
+    let datetime_format = detect_datetime_format(&sample, datetime_layout)?;
 
     Ok(ValidationResult {
         format,
@@ -75,25 +219,230 @@ pub fn validate_csv(path: &Path) -> Result<ValidationResult, AppError> {
         delimiter,
         row_count_sample,
         column_count,
+        datetime_format,
+        datetime_layout,
+        column_mapping: column_mapping.cloned(),
     })
 }
 
-/// Detect the field delimiter by checking comma vs semicolon vs tab counts in the first line.
+/// Check that a user column mapping covers every required target for its format,
+/// references existing columns only, and maps no column twice.
+fn validate_column_mapping(mapping: &ColumnMapping, column_count: usize) -> Result<(), AppError> {
+    let required: &[(&str, Option<usize>)] = match mapping.data_format() {
+        DataFormat::Tick => &[("bid", mapping.bid), ("ask", mapping.ask)],
+        DataFormat::Bar => &[
+            ("open", mapping.open),
+            ("high", mapping.high),
+            ("low", mapping.low),
+            ("close", mapping.close),
+        ],
+    };
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|(_, idx)| idx.is_none())
+        .map(|(name, _)| *name)
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::CsvValidation(format!(
+            "Column mapping is missing required target(s): {}",
+            missing.join(", ")
+        )));
+    }
+
+    let mapped = [
+        ("datetime", Some(mapping.datetime)),
+        ("time", mapping.time),
+        ("open", mapping.open),
+        ("high", mapping.high),
+        ("low", mapping.low),
+        ("close", mapping.close),
+        ("volume", mapping.volume),
+        ("bid", mapping.bid),
+        ("ask", mapping.ask),
+    ];
+    let mut seen: Vec<(usize, &str)> = Vec::new();
+    for (name, idx) in mapped {
+        let Some(idx) = idx else { continue };
+        if idx >= column_count {
+            return Err(AppError::CsvValidation(format!(
+                "Column mapping for '{}' points to column {} but the file has {} columns",
+                name, idx, column_count
+            )));
+        }
+        if let Some((_, other)) = seen.iter().find(|(i, _)| *i == idx) {
+            return Err(AppError::CsvValidation(format!(
+                "Column {} is mapped to both '{}' and '{}'",
+                idx, other, name
+            )));
+        }
+        seen.push((idx, name));
+    }
+    Ok(())
+}
+
+/// Candidate delimiters, in tie-break priority order.
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// Sniff the field delimiter from the first lines of the file.
+///
+/// A delimiter is only a candidate if it appears the same (non-zero) number of
+/// times on every sampled line; among candidates the one producing the most
+/// columns wins. Falls back to the most frequent character on the first line.
 fn detect_delimiter(content: &str) -> u8 {
-    let first_line = content.lines().next().unwrap_or("");
-    let comma_count = first_line.chars().filter(|&c| c == ',').count();
-    let semicolon_count = first_line.chars().filter(|&c| c == ';').count();
-    let tab_count = first_line.chars().filter(|&c| c == '\t').count();
-
-    if tab_count > comma_count && tab_count > semicolon_count {
-        b'\t'
-    } else if semicolon_count > comma_count {
-        b';'
+    let sample: Vec<&str> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(20)
+        .collect();
+    let first_line = sample.first().copied().unwrap_or("");
+    let count = |line: &str, d: u8| line.bytes().filter(|&b| b == d).count();
+
+    let consistent = DELIMITERS
+        .iter()
+        .copied()
+        .filter(|&d| {
+            let n = count(first_line, d);
+            n > 0 && sample.iter().all(|l| count(l, d) == n)
+        })
+        .max_by_key(|&d| (count(first_line, d), std::cmp::Reverse(delimiter_rank(d))));
+
+    consistent.unwrap_or_else(|| {
+        DELIMITERS
+            .iter()
+            .copied()
+            .max_by_key(|&d| (count(first_line, d), std::cmp::Reverse(delimiter_rank(d))))
+            .unwrap_or(b',')
+    })
+}
+
+fn delimiter_rank(d: u8) -> usize {
+    DELIMITERS.iter().position(|&x| x == d).unwrap_or(usize::MAX)
+}
+
+/// Determine which column(s) hold the timestamp.
+///
+/// With a header, separate `date` + `time` columns mean a split layout; otherwise
+/// the first column named like a timestamp is used. Without a header, a second
+/// column that looks like a clock time (`HH:MM[:SS]`) means a split layout.
+fn detect_datetime_layout(
+    headers: &[&str],
+    has_header: bool,
+    format: DataFormat,
+    first_row: Option<&[&str]>,
+) -> DateTimeLayout {
+    if has_header {
+        let normalized: Vec<String> = headers
+            .iter()
+            .map(|h| h.trim().trim_matches('"').to_lowercase())
+            .collect();
+        let date_idx = normalized.iter().position(|h| h == "date");
+        let time_idx = normalized.iter().position(|h| h == "time");
+        if let (Some(date_column), Some(time_column)) = (date_idx, time_idx) {
+            return DateTimeLayout::Split { date_column, time_column };
+        }
+        let column = normalized
+            .iter()
+            .position(|h| {
+                matches!(
+                    h.as_str(),
+                    "datetime" | "timestamp" | "date" | "time" | "gmt time" | "local time"
+                )
+            })
+            .unwrap_or(0);
+        return DateTimeLayout::Combined { column };
+    }
+
+    let second_is_time = first_row
+        .and_then(|row| row.get(1))
+        .map(|cell| looks_like_time(cell))
+        .unwrap_or(false);
+    if format == DataFormat::Bar && second_is_time {
+        DateTimeLayout::Split { date_column: 0, time_column: 1 }
     } else {
-        b','
+        DateTimeLayout::Combined { column: 0 }
     }
 }
 
+/// True for clock times such as `12:30`, `12:30:45` or `12:30:45.123`.
+fn looks_like_time(cell: &str) -> bool {
+    let cell = cell.trim().trim_matches('"');
+    let hms = cell.split('.').next().unwrap_or("");
+    let parts: Vec<&str> = hms.split(':').collect();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.len() <= 2 && p.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Detect the timestamp encoding by trying each known format against every
+/// sampled row. The first format that parses all rows wins, so an ambiguous
+/// `03/04/2024` is resolved by any later row with a day above 12.
+fn detect_datetime_format(
+    sample: &[Vec<&str>],
+    layout: DateTimeLayout,
+) -> Result<DateTimeFormat, AppError> {
+    let values: Vec<String> = sample
+        .iter()
+        .map(|row| layout.extract(|i| row.get(i).copied()))
+        .filter(|v| !v.is_empty())
+        .collect();
+
+    let Some(first) = values.first() else {
+        // No data rows to sniff — assume the canonical ISO layout.
+        return Ok(DateTimeFormat::Pattern(DATETIME_PATTERNS[0]));
+    };
+
+    if let Some(epoch) = detect_epoch_format(first) {
+        if values.iter().all(|v| epoch.parse(v).is_some()) {
+            return Ok(epoch);
+        }
+    }
+
+    let candidates = DATETIME_PATTERNS
+        .iter()
+        .map(|p| DateTimeFormat::Pattern(p))
+        .chain(DATE_ONLY_PATTERNS.iter().map(|p| DateTimeFormat::DateOnly(p)));
+
+    for candidate in candidates {
+        if values.iter().all(|v| candidate.parse(v).is_some()) {
+            return Ok(candidate);
+        }
+    }
+
+    let tried: Vec<&str> = DATETIME_PATTERNS
+        .iter()
+        .chain(DATE_ONLY_PATTERNS.iter())
+        .copied()
+        .collect();
+    Err(AppError::CsvValidation(format!(
+        "Unrecognized datetime '{}'. Tried epoch seconds/milliseconds/microseconds and patterns: {}",
+        first,
+        tried.join(", ")
+    )))
+}
+
+/// Classify an all-digit timestamp by magnitude: 10 digits = seconds,
+/// 13 = milliseconds, 16 = microseconds.
+fn detect_epoch_format(value: &str) -> Option<DateTimeFormat> {
+    let digits = value.trim().trim_matches('"');
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match digits.len() {
+        9..=10 => Some(DateTimeFormat::EpochSeconds),
+        12..=13 => Some(DateTimeFormat::EpochMillis),
+        15..=16 => Some(DateTimeFormat::EpochMicros),
+        _ => None,
+    }
+}
+
+fn parse_epoch_digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse::<i64>().ok()
+}
+
 /// Heuristic: if the first cell of the first row cannot be parsed as a float,
 /// it's likely a header row (e.g. "DateTime", "Date", "Open").
 fn is_header_row(cells: &[&str]) -> bool {
@@ -148,3 +497,272 @@ fn detect_format_from_column_count(count: usize) -> Result<DataFormat, AppError>
         ))),
     }
 }
+
+/// Bars with inconsistent OHLC found by [`check_bar_integrity`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BarIntegrityReport {
+    /// Bars whose high was below their low.
+    pub inverted_high_low: usize,
+    /// Bars whose open lay outside [low, high] (after any high/low swap).
+    pub open_out_of_range: usize,
+    /// Bars whose close lay outside [low, high] (after any high/low swap).
+    pub close_out_of_range: usize,
+    /// Bars with a missing or non-finite price; counted but never repaired.
+    pub non_finite: usize,
+    /// True when the flagged bars were repaired; otherwise they were imported as-is.
+    pub repaired: bool,
+}
+
+impl BarIntegrityReport {
+    /// Number of problems found (a bar can count more than once).
+    pub fn issue_count(&self) -> usize {
+        self.inverted_high_low + self.open_out_of_range + self.close_out_of_range + self.non_finite
+    }
+}
+
+/// Check every bar for `high < low` and an open/close outside `[low, high]`.
+///
+/// With `repair`, inverted high/low are swapped and open/close are then clamped into
+/// the range; otherwise the frame is returned unchanged and the bars are only counted.
+/// Bars with a NaN or infinite price have no usable range and are only counted.
+pub fn check_bar_integrity(df: DataFrame, repair: bool) -> Result<(DataFrame, BarIntegrityReport), AppError> {
+    let column = |name: &str| -> Result<Vec<f64>, AppError> {
+        let series = df
+            .column(name)
+            .map_err(|_| AppError::InvalidCsvFormat(format!("DataFrame has no '{}' column", name)))?
+            .cast(&DataType::Float64)
+            .map_err(|e| AppError::ParquetConversion(format!("cast {} to f64: {}", name, e)))?;
+        let values = series
+            .f64()
+            .map_err(|e| AppError::ParquetConversion(e.to_string()))?
+            .into_iter()
+            .map(|v| v.unwrap_or(f64::NAN))
+            .collect();
+        Ok(values)
+    };
+    let mut open = column("open")?;
+    let mut high = column("high")?;
+    let mut low = column("low")?;
+    let mut close = column("close")?;
+
+    let mut report = BarIntegrityReport { repaired: repair, ..Default::default() };
+    for i in 0..high.len() {
+        if ![open[i], high[i], low[i], close[i]].iter().all(|v| v.is_finite()) {
+            report.non_finite += 1;
+            continue;
+        }
+        let (mut h, mut l) = (high[i], low[i]);
+        if h < l {
+            report.inverted_high_low += 1;
+            std::mem::swap(&mut h, &mut l);
+        }
+        if open[i] < l || open[i] > h {
+            report.open_out_of_range += 1;
+        }
+        if close[i] < l || close[i] > h {
+            report.close_out_of_range += 1;
+        }
+        if repair {
+            high[i] = h;
+            low[i] = l;
+            open[i] = open[i].clamp(l, h);
+            close[i] = close[i].clamp(l, h);
+        }
+    }
+
+    if !repair || report.issue_count() == 0 {
+        return Ok((df, report));
+    }
+    let mut df = df;
+    for (name, values) in [("open", open), ("high", high), ("low", low), ("close", close)] {
+        df.with_column(Series::new(name.into(), values))
+            .map_err(|e| AppError::ParquetConversion(format!("replace {}: {}", name, e)))?;
+    }
+    Ok((df, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> i64 {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .and_utc()
+            .timestamp_micros()
+    }
+
+    #[test]
+    fn test_semicolon_us_dates_split_columns() {
+        let csv = "Date;Time;Open;High;Low;Close;Volume\n\
+                   01/15/2024;09:30;1.1000;1.1010;1.0990;1.1005;120\n\
+                   01/15/2024;09:31;1.1005;1.1015;1.1000;1.1010;95\n";
+        let v = validate_csv_content(csv, None).unwrap();
+        assert_eq!(v.delimiter, b';');
+        assert!(v.has_header);
+        assert_eq!(v.format, DataFormat::Bar);
+        assert_eq!(v.datetime_layout, DateTimeLayout::Split { date_column: 0, time_column: 1 });
+        assert_eq!(v.datetime_format, DateTimeFormat::Pattern("%m/%d/%Y %H:%M"));
+        assert_eq!(v.datetime_format.parse("01/15/2024 09:30"), Some(ts("2024-01-15 09:30:00")));
+    }
+
+    #[test]
+    fn test_epoch_millis_ticks_without_header() {
+        let csv = "1705311000000,1.10001,1.10012,1.5\n1705311000250,1.10002,1.10013,0.75\n";
+        let v = validate_csv_content(csv, None).unwrap();
+        assert_eq!(v.delimiter, b',');
+        assert!(!v.has_header);
+        assert_eq!(v.format, DataFormat::Tick);
+        assert_eq!(v.row_count_sample, 2);
+        assert_eq!(v.datetime_layout, DateTimeLayout::Combined { column: 0 });
+        assert_eq!(v.datetime_format, DateTimeFormat::EpochMillis);
+        assert_eq!(v.datetime_format.parse("1705311000250"), Some(1_705_311_000_250_000));
+    }
+
+    #[test]
+    fn test_tab_separated_mt5_export() {
+        let csv = "2024.01.15\t09:30:00\t1.1000\t1.1010\t1.0990\t1.1005\t120\t0\t8\n\
+                   2024.01.15\t09:31:00\t1.1005\t1.1015\t1.1000\t1.1010\t95\t0\t7\n";
+        let v = validate_csv_content(csv, None).unwrap();
+        assert_eq!(v.delimiter, b'\t');
+        assert_eq!(v.column_count, 9);
+        assert_eq!(v.format, DataFormat::Bar);
+        assert_eq!(v.datetime_layout, DateTimeLayout::Split { date_column: 0, time_column: 1 });
+        assert_eq!(v.datetime_format, DateTimeFormat::Pattern("%Y.%m.%d %H:%M:%S%.f"));
+    }
+
+    #[test]
+    fn test_day_first_dates_disambiguated_by_later_rows() {
+        // 03/04 is ambiguous; 25/04 can only be day-first.
+        let csv = "Timestamp,Open,High,Low,Close,Volume\n\
+                   03/04/2024 00:00,1.1,1.2,1.0,1.15,10\n\
+                   25/04/2024 00:00,1.1,1.2,1.0,1.15,10\n";
+        let v = validate_csv_content(csv, None).unwrap();
+        assert_eq!(v.datetime_layout, DateTimeLayout::Combined { column: 0 });
+        assert_eq!(v.datetime_format, DateTimeFormat::Pattern("%d/%m/%Y %H:%M"));
+        assert_eq!(v.datetime_format.parse("03/04/2024 00:00"), Some(ts("2024-04-03 00:00:00")));
+    }
+
+    #[test]
+    fn test_iso_with_fractional_seconds() {
+        let csv = "DateTime,Bid,Ask,Volume\n2024-01-15 09:30:00.125,1.1,1.1002,1\n2024-01-15 09:30:01,1.1,1.1002,1\n";
+        let v = validate_csv_content(csv, None).unwrap();
+        assert_eq!(v.format, DataFormat::Tick);
+        assert_eq!(v.datetime_format, DateTimeFormat::Pattern("%Y-%m-%d %H:%M:%S%.f"));
+        assert_eq!(
+            v.datetime_format.parse("2024-01-15 09:30:00.125"),
+            Some(ts("2024-01-15 09:30:00") + 125_000)
+        );
+    }
+
+    #[test]
+    fn test_column_mapping_overrides_detection() {
+        let csv = "px_c,stamp,px_o,px_h,px_l\n1.15,2024-01-15 09:30:00,1.1,1.2,1.0\n";
+        let mapping = ColumnMapping {
+            datetime: 1,
+            open: Some(2),
+            high: Some(3),
+            low: Some(4),
+            close: Some(0),
+            ..Default::default()
+        };
+        let v = validate_csv_content(csv, Some(&mapping)).unwrap();
+        assert_eq!(v.format, DataFormat::Bar);
+        assert_eq!(v.datetime_layout, DateTimeLayout::Combined { column: 1 });
+        assert_eq!(v.column_mapping, Some(mapping));
+    }
+
+    #[test]
+    fn test_column_mapping_missing_required_target() {
+        let csv = "a,b,c,d,e\n2024-01-15 09:30:00,1.1,1.2,1.0,1.15\n";
+        let mapping = ColumnMapping {
+            datetime: 0,
+            open: Some(1),
+            high: Some(2),
+            low: Some(3),
+            ..Default::default()
+        };
+        let err = validate_csv_content(csv, Some(&mapping)).unwrap_err().to_string();
+        assert!(err.contains("close"), "{}", err);
+
+        let out_of_range = ColumnMapping { close: Some(7), ..mapping.clone() };
+        assert!(validate_csv_content(csv, Some(&out_of_range)).is_err());
+
+        let duplicate = ColumnMapping { close: Some(1), ..mapping };
+        assert!(validate_csv_content(csv, Some(&duplicate)).is_err());
+    }
+
+    #[test]
+    fn test_unrecognized_datetime_lists_patterns() {
+        let csv = "DateTime,Open,High,Low,Close,Volume\nyesterday,1,2,0.5,1.5,10\n";
+        let err = validate_csv_content(csv, None).unwrap_err().to_string();
+        assert!(err.contains("yesterday"));
+        assert!(err.contains("%Y-%m-%d %H:%M:%S%.f"));
+        assert!(err.contains("%m/%d/%Y"));
+    }
+
+    fn bars(open: &[f64], high: &[f64], low: &[f64], close: &[f64]) -> DataFrame {
+        df!(
+            "open" => open,
+            "high" => high,
+            "low" => low,
+            "close" => close,
+        )
+        .unwrap()
+    }
+
+    fn values(df: &DataFrame, name: &str) -> Vec<f64> {
+        df.column(name).unwrap().f64().unwrap().into_no_null_iter().collect()
+    }
+
+    #[test]
+    fn test_inverted_high_low_flagged_and_swapped() {
+        // Second bar has high and low the wrong way round
+        let df = bars(&[1.10, 1.12], &[1.11, 1.10], &[1.09, 1.13], &[1.105, 1.12]);
+
+        let (unchanged, report) = check_bar_integrity(df.clone(), false).unwrap();
+        assert_eq!(report.inverted_high_low, 1);
+        assert_eq!(report.issue_count(), 1);
+        assert!(!report.repaired);
+        assert_eq!(values(&unchanged, "high"), vec![1.11, 1.10]);
+
+        let (fixed, report) = check_bar_integrity(df, true).unwrap();
+        assert_eq!(report.inverted_high_low, 1);
+        assert!(report.repaired);
+        assert_eq!(values(&fixed, "high"), vec![1.11, 1.13]);
+        assert_eq!(values(&fixed, "low"), vec![1.09, 1.10]);
+        assert_eq!(values(&fixed, "close"), vec![1.105, 1.12]);
+    }
+
+    #[test]
+    fn test_out_of_range_close_flagged_and_clamped() {
+        // Close above the high on bar 0, below the low on bar 1; bar 2 is clean
+        let df = bars(&[1.10, 1.10, 1.10], &[1.11, 1.11, 1.11], &[1.09, 1.09, 1.09], &[1.115, 1.08, 1.10]);
+
+        let (_, report) = check_bar_integrity(df.clone(), false).unwrap();
+        assert_eq!(report, BarIntegrityReport { close_out_of_range: 2, ..Default::default() });
+
+        let (fixed, report) = check_bar_integrity(df, true).unwrap();
+        assert_eq!(report.close_out_of_range, 2);
+        assert_eq!(values(&fixed, "close"), vec![1.11, 1.09, 1.10]);
+        assert_eq!(values(&fixed, "open"), vec![1.10, 1.10, 1.10]);
+        // A repaired frame passes a second check
+        let (_, recheck) = check_bar_integrity(fixed, false).unwrap();
+        assert_eq!(recheck.issue_count(), 0);
+    }
+
+    #[test]
+    fn test_non_finite_bars_counted_and_left_unrepaired() {
+        // NaN high on bar 0, NaN low on bar 1; bar 2 has its close above the high
+        let df = bars(&[1.10, 1.10, 1.10], &[f64::NAN, 1.11, 1.11], &[1.09, f64::NAN, 1.09], &[1.10, 1.10, 1.12]);
+
+        let (fixed, report) = check_bar_integrity(df, true).unwrap();
+        assert_eq!(
+            report,
+            BarIntegrityReport { close_out_of_range: 1, non_finite: 2, repaired: true, ..Default::default() }
+        );
+        assert!(values(&fixed, "high")[0].is_nan());
+        assert!(values(&fixed, "low")[1].is_nan());
+        assert_eq!(values(&fixed, "close"), vec![1.10, 1.10, 1.11]);
+    }
+}
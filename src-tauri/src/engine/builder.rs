@@ -462,6 +462,8 @@ impl GrammarContext {
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         };
         let right = Operand {
             operand_type: OperandType::Constant,
@@ -474,6 +476,8 @@ impl GrammarContext {
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         };
         let rule = Rule {
             id: "probe".to_string(),
@@ -481,6 +485,9 @@ impl GrammarContext {
             comparator: Comparator::GreaterThan,
             right_operand: right,
             logical_operator: Some(LogicalOperator::And),
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         };
         let strat = Strategy {
             id: "probe".to_string(),
@@ -511,7 +518,12 @@ impl GrammarContext {
             entry_order: OrderType::Market,
             entry_order_offset_pips: 0.0,
             close_after_bars: None,
+            min_hold_bars: None,
+            max_hold_bars: None,
             move_sl_to_be: false,
+            close_on_opposite_signal: false,
+            reverse_on_opposite_signal: false,
+            session_exit: None,
             entry_order_indicator: None,
         };
         Some(strat)
@@ -774,6 +786,8 @@ fn random_indicator_operand(grammar: &GrammarContext, rng: &mut impl Rng) -> Ope
         compound_left: None,
         compound_op: None,
         compound_right: None,
+        percent_of: None,
+        transform: None,
     }
 }
 
@@ -799,6 +813,8 @@ fn random_compound_operand(grammar: &GrammarContext, rng: &mut impl Rng, phase:
         compound_left: Some(Box::new(left)),
         compound_op: Some(op),
         compound_right: Some(Box::new(right)),
+        percent_of: None,
+        transform: None,
     }
 }
 
@@ -825,6 +841,8 @@ fn random_simple_operand(grammar: &GrammarContext, rng: &mut impl Rng, phase: f6
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     } else {
         let value = if rng.gen_bool(0.6) {
@@ -843,6 +861,8 @@ fn random_simple_operand(grammar: &GrammarContext, rng: &mut impl Rng, phase: f6
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     }
 }
@@ -910,6 +930,7 @@ fn random_indicator_params(
         | IndicatorType::HighestInRange
         | IndicatorType::LowestInRange
         | IndicatorType::Reflex
+        | IndicatorType::SuperSmoother
         | IndicatorType::BearsPower
         | IndicatorType::BullsPower
         | IndicatorType::Fractal
@@ -1100,6 +1121,17 @@ fn random_indicator_params(
             )
         }
 
+        // Donchian Channel: period, triple output
+        IndicatorType::DonchianChannel => {
+            let period = rand_period_step(rng, pmin, pmax, pstep);
+            let output_field = match rng.gen_range(0..3) {
+                0 => "upper",
+                1 => "middle",
+                _ => "lower",
+            };
+            (IndicatorParams { period: Some(period), ..Default::default() }, Some(output_field.to_string()))
+        }
+
         // GannHiLo: period, single output only (no secondary)
         IndicatorType::GannHiLo => {
             let period = rand_period_step(rng, pmin, pmax, pstep);
@@ -1193,6 +1225,200 @@ fn random_indicator_params(
             (IndicatorParams { period: Some(period), ..Default::default() }, None)
         }
 
+        // TRIX: period + signal, dual output like MACD (None = TRIX line, "signal" = signal line)
+        IndicatorType::TRIX => {
+            let period = biased_period(rng, 15, 5, pmin.max(2), pmax.min(50));
+            let signal = biased_period(rng, 9, 3, 3, 15);
+            let output_field: Option<&str> = match rng.gen_range(0..2) {
+                0 => None,
+                _ => Some("signal"),
+            };
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    signal_period: Some(signal),
+                    ..Default::default()
+                },
+                output_field.map(|s| s.to_string()),
+            )
+        }
+
+        // Accumulation/Distribution: no params (cumulative from the first bar)
+        IndicatorType::AccumulationDistribution => (IndicatorParams::default(), None),
+
+        // Chaikin Money Flow: single period
+        IndicatorType::ChaikinMoneyFlow => {
+            let period = biased_period(rng, 20, 5, pmin, pmax);
+            (IndicatorParams { period: Some(period), ..Default::default() }, None)
+        }
+
+        // Force Index: EMA smoothing period
+        IndicatorType::ForceIndex => {
+            let period = biased_period(rng, 13, 5, pmin.max(2), pmax);
+            (IndicatorParams { period: Some(period), ..Default::default() }, None)
+        }
+
+        // Ease of Movement: SMA period; the volume scale only rescales the output
+        IndicatorType::EaseOfMovement => {
+            let period = biased_period(rng, 14, 5, pmin.max(2), pmax);
+            (IndicatorParams { period: Some(period), multiplier: Some(10_000.0), ..Default::default() }, None)
+        }
+
+        // Mass Index: summation period plus the EMA length of the range ratio
+        IndicatorType::MassIndex => {
+            let period = biased_period(rng, 25, 5, pmin.max(5), pmax);
+            let ema_period = biased_period(rng, 9, 2, 3, 20);
+            (IndicatorParams { period: Some(period), fast_period: Some(ema_period), ..Default::default() }, None)
+        }
+
+        // Klinger: fast/slow EMAs of volume force plus a signal EMA (None = KVO, "signal" = signal line)
+        IndicatorType::Klinger => {
+            let fast = biased_period(rng, 34, 8, pmin.max(5), pmax);
+            let slow_min = fast + 5;
+            let slow = biased_period(rng, 55, 10, slow_min, (fast + 40).max(slow_min));
+            let signal = biased_period(rng, 13, 3, 3, 21);
+            let output_field: Option<&str> = match rng.gen_range(0..2) {
+                0 => None,
+                _ => Some("signal"),
+            };
+            (
+                IndicatorParams {
+                    fast_period: Some(fast),
+                    slow_period: Some(slow),
+                    signal_period: Some(signal),
+                    ..Default::default()
+                },
+                output_field.map(|s| s.to_string()),
+            )
+        }
+
+        // VWMA / ZLEMA / WMA: single period moving averages
+        IndicatorType::VWMA | IndicatorType::ZLEMA | IndicatorType::WMA => {
+            let period = biased_period(rng, 20, 5, pmin.max(2), pmax);
+            (IndicatorParams { period: Some(period), ..Default::default() }, None)
+        }
+
+        // DPO: single period
+        IndicatorType::DPO => {
+            let period = biased_period(rng, 20, 5, pmin.max(2), pmax);
+            (IndicatorParams { period: Some(period), ..Default::default() }, None)
+        }
+
+        // Elder Impulse: EMA period plus MACD(fast, slow, signal); discrete -1/0/+1 output
+        IndicatorType::ElderImpulse => {
+            let period = biased_period(rng, 13, 4, pmin.max(2), pmax);
+            let fast = biased_period(rng, 12, 4, pmin, pmax.min(pmin + 10));
+            let slow_min = (fast + 2).max(pmin);
+            let slow = biased_period(rng, 26, 6, slow_min, (fast + 20).min(pmax + 10).max(slow_min));
+            let signal = biased_period(rng, 9, 3, 3, 15);
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    fast_period: Some(fast),
+                    slow_period: Some(slow),
+                    signal_period: Some(signal),
+                    ..Default::default()
+                },
+                None,
+            )
+        }
+
+        // PPO: same parameter shape and outputs as MACD
+        IndicatorType::PPO => {
+            let fast = biased_period(rng, 12, 4, pmin, pmax.min(pmin + 10));
+            let slow_min = (fast + 2).max(pmin);
+            let slow = biased_period(rng, 26, 6, slow_min, (fast + 20).min(pmax + 10).max(slow_min));
+            let signal = biased_period(rng, 9, 3, 3, 15);
+            // None = PPO line (primary), "signal" = signal line, "histogram" = histogram
+            let output_field: Option<&str> = match rng.gen_range(0..3) {
+                0 => None,
+                1 => Some("signal"),
+                _ => Some("histogram"),
+            };
+            (
+                IndicatorParams {
+                    fast_period: Some(fast),
+                    slow_period: Some(slow),
+                    signal_period: Some(signal),
+                    ..Default::default()
+                },
+                output_field.map(|s| s.to_string()),
+            )
+        }
+
+        // Connors RSI: short price RSI, streak RSI and ROC percent-rank lookback
+        IndicatorType::ConnorsRSI => {
+            let period = biased_period(rng, 3, 1, 2, 10);
+            let streak = biased_period(rng, 2, 1, 2, 5);
+            let rank = biased_period(rng, 100, 25, 20, 200);
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    fast_period: Some(streak),
+                    slow_period: Some(rank),
+                    ..Default::default()
+                },
+                None,
+            )
+        }
+
+        // Roofing Filter: smoother period + longer high-pass cutoff
+        IndicatorType::RoofingFilter => {
+            let period = biased_period(rng, 10, 3, pmin.max(2), pmax.min(30));
+            let hp_period = biased_period(rng, 48, 10, period * 2, 100);
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    slow_period: Some(hp_period),
+                    ..Default::default()
+                },
+                None,
+            )
+        }
+
+        // Coppock: WMA period + short/long ROC periods
+        IndicatorType::Coppock => {
+            let period = biased_period(rng, 10, 3, pmin.max(2), pmax.min(30));
+            let fast = biased_period(rng, 11, 3, 3, 25);
+            let slow = biased_period(rng, 14, 4, fast + 1, 40);
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    fast_period: Some(fast),
+                    slow_period: Some(slow),
+                    ..Default::default()
+                },
+                None,
+            )
+        }
+
+        // StochasticRSI: RSI period + stochastic k/d, dual output like Stochastic
+        IndicatorType::StochasticRSI => {
+            let period = biased_period(rng, 14, 4, pmin.max(2), pmax);
+            let k = biased_period(rng, 14, 5, pmin.max(3), pmax.min(21));
+            let d = rand_period(rng, 3, 5);
+            // None = %K (primary), "d" = %D (secondary)
+            let output_field: Option<&str> = match rng.gen_range(0..2) {
+                0 => None,
+                _ => Some("d"),
+            };
+            (
+                IndicatorParams {
+                    period: Some(period),
+                    k_period: Some(k),
+                    d_period: Some(d),
+                    ..Default::default()
+                },
+                output_field.map(|s| s.to_string()),
+            )
+        }
+
+        // Choppiness: single period, log10(n) needs n >= 2
+        IndicatorType::Choppiness => {
+            let period = biased_period(rng, 14, 4, pmin.max(2), pmax.max(2));
+            (IndicatorParams { period: Some(period), ..Default::default() }, None)
+        }
+
         // HighestIndex: single period
         IndicatorType::HighestIndex => {
             let period = rand_period_step(rng, pmin, pmax, pstep);
@@ -1283,6 +1509,8 @@ fn random_operand(grammar: &GrammarContext, rng: &mut impl Rng, phase: f64) -> O
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     } else {
         // Constant — biased toward oscillator-useful ranges.
@@ -1302,6 +1530,8 @@ fn random_operand(grammar: &GrammarContext, rng: &mut impl Rng, phase: f64) -> O
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     }
 }
@@ -1334,6 +1564,9 @@ fn random_rule(grammar: &GrammarContext, rng: &mut impl Rng, last: bool, phase:
         comparator,
         right_operand: right,
         logical_operator,
+        open_parens: 0,
+        close_parens: 0,
+        tolerance: None,
     }
 }
 
@@ -1347,6 +1580,9 @@ fn mirror_rules(rules: &[Rule]) -> Vec<Rule> {
             comparator: mirror_comparator(r.comparator),
             right_operand: r.right_operand.clone(),
             logical_operator: r.logical_operator,
+            open_parens: r.open_parens,
+            close_parens: r.close_parens,
+            tolerance: r.tolerance,
         })
         .collect()
 }
@@ -1439,6 +1675,7 @@ fn random_trailing_stop(grammar: &GrammarContext, rng: &mut impl Rng) -> Option<
         value,
         atr_period,
         activation_pips: None,
+        step_pips: None,
     })
 }
 
@@ -1629,7 +1866,12 @@ fn generate_random_strategy(
         entry_order: random_entry_order(grammar, rng),
         entry_order_offset_pips: rand_range_stepped(rng, 5.0, 30.0, 5.0),
         close_after_bars: random_close_after_bars(grammar, rng),
+        min_hold_bars: None,
+        max_hold_bars: None,
         move_sl_to_be: random_move_sl_be(grammar, rng),
+        close_on_opposite_signal: false,
+        reverse_on_opposite_signal: false,
+        session_exit: None,
         entry_order_indicator: random_order_price_indicator(grammar, rng),
     }
 }
@@ -3453,6 +3695,7 @@ pub fn run_builder(
                 let _ = pre_compute_indicators_with_shared_cache(
                     probe,
                     fitness_candles,
+                    instrument.session_start_hour,
                     &persistent_cache,
                 );
             });
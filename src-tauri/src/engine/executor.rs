@@ -5,28 +5,32 @@ use chrono::Datelike;
 use polars::prelude::*;
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
+use rayon::prelude::*;
 use tracing::info;
 
 use crate::errors::AppError;
 use crate::models::candle::{Candle, TickColumns};
-use crate::models::config::InstrumentConfig;
-use crate::models::result::{BacktestResults, DrawdownPoint, EquityPoint};
+use crate::models::config::{InstrumentConfig, SubBarBoundary, Timeframe};
+use crate::models::result::{BacktestResults, BacktestStep, BatchBacktestResult, DrawdownPoint, EquityPoint, RollingRatioPoint};
 use crate::models::strategy::{
-    BacktestConfig, BacktestPrecision, CloseTradesAt, IndicatorConfig, IndicatorType, OrderType,
-    Strategy, TradeDirection, TradingHours,
+    BacktestConfig, BacktestPrecision, CloseTradesAt, DrawdownBasis, ExitPriority, IndicatorConfig, IndicatorType, OrderType,
+    RegimeConfig, RegimeIndicator, RuleGroup, SessionExit, StopLossType, Strategy, TradeDirection, TradingHours,
 };
-use crate::models::trade::{CloseReason, TradeResult};
+use crate::models::trade::{CloseReason, MarketRegime, TradeResult};
 
-use super::metrics::{calculate_direction_metrics, calculate_metrics};
+use super::metrics::{
+    apply_benchmark_metrics, apply_overtrading_threshold, buy_and_hold_curve, calculate_direction_metrics, calculate_metrics,
+    calculate_metrics_from_stats, rolling_periods_per_year, rolling_ratio_scale, rolling_risk_ratios, BenchmarkStats, EquityStats, RollingRatios,
+};
 use super::orders;
 use super::position::{
-    calculate_lots, calculate_stop_loss, calculate_take_profit,
+    calculate_lots, calculate_stop_loss, calculate_take_profit, chande_kroll_levels,
     calculate_trailing_stop_distance, calculate_swap_charge, check_sl_tp_hit,
     check_sl_tp_hit_bar_direction, check_sl_tp_hit_open_only, enforce_stops_level_sl, enforce_stops_level_tp,
-    should_charge_swap, update_mae_mfe_ba, update_trailing_stop,
-    OpenPosition, PendingOrder,
+    should_charge_swap, trailing_step_price, trailing_stop_level, update_mae_mfe_ba,
+    update_chande_kroll_stop, update_trailing_stop, OpenPosition, PendingOrder,
 };
-use super::strategy::{compile_rules_streaming, compute_candle_pattern_cache, compute_daily_ohlc, compute_time_cache, evaluate_rule_groups, evaluate_rules, evaluate_rules_fast, init_strategy_hashes, max_lookback, pre_compute_indicators, pre_compute_indicators_with_shared_cache, precompute_cross_prev_vals, strategy_uses_candle_patterns, strategy_uses_time_fields};
+use super::strategy::{compile_rules_streaming, compute_candle_pattern_cache, compute_daily_ohlc, compute_time_cache, evaluate_rule_groups, evaluate_rules, evaluate_rules_fast, fired_group_indices, init_strategy_hashes, max_lookback, pre_compute_indicators, pre_compute_indicators_with_shared_cache, precompute_cross_prev_vals, strategy_uses_candle_patterns, strategy_uses_time_fields};
 use super::strategy::IndicatorCache;
 use super::streaming;
 
@@ -44,6 +48,19 @@ pub enum SubBarData {
     /// Columnar tick data (SoA layout) with i64 timestamps for tick-level resolution.
     /// Uses struct-of-arrays layout for better cache locality and no per-tick allocations.
     Ticks(TickColumns),
+    /// Tick data loaded on demand, `chunk_bars` TF candles at a time, so only the
+    /// current region is resident. `run_backtest` swaps each window in as a
+    /// `Ticks` value; other consumers see it as having no sub-bar data.
+    TickChunks {
+        source: Box<dyn TickSource>,
+        chunk_bars: usize,
+    },
+}
+
+/// Lazy tick loader backing `SubBarData::TickChunks`.
+pub trait TickSource: Send + Sync {
+    /// Ticks with `from_us <= timestamp < to_us`, in time order.
+    fn load_range(&self, from_us: i64, to_us: i64) -> Result<TickColumns, AppError>;
 }
 
 // ══════════════════════════════════════════════════════════════
@@ -64,7 +81,71 @@ pub fn run_backtest(
     cancel_flag: &AtomicBool,
     progress_callback: impl Fn(u8, usize, usize),
 ) -> Result<BacktestResults, AppError> {
-    run_backtest_inner(candles, sub_bars, strategy, config, instrument, cancel_flag, progress_callback, None)
+    run_backtest_inner(candles, sub_bars, strategy, config, instrument, cancel_flag, progress_callback, None, None)
+}
+
+/// Receives the output of a stepped (replay) backtest in increments.
+pub struct StepObserver<'a> {
+    /// Bars between steps. A final step carries whatever is left once the run ends.
+    pub interval: usize,
+    pub on_step: &'a dyn Fn(BacktestStep),
+}
+
+/// Run a backtest that reports its progress as [`BacktestStep`]s every `observer.interval`
+/// bars. The cancel flag is checked after each step, so a slow observer (e.g. one pacing
+/// a replay) can still be interrupted between steps. Returns the same results as
+/// [`run_backtest`].
+pub fn run_backtest_stepped(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    cancel_flag: &AtomicBool,
+    observer: &StepObserver,
+) -> Result<BacktestResults, AppError> {
+    if observer.interval == 0 {
+        return Err(AppError::InvalidConfig("step interval must be at least 1 bar".into()));
+    }
+    run_backtest_inner(candles, sub_bars, strategy, config, instrument, cancel_flag, |_, _, _| {}, None, Some(observer))
+}
+
+/// How much of the curves and trade list earlier steps have already reported.
+#[derive(Default)]
+struct StepCursor {
+    equity: usize,
+    drawdown: usize,
+    trades: usize,
+}
+
+impl StepCursor {
+    fn has_pending(&self, equity_curve: &[EquityPoint], trades: &[TradeResult]) -> bool {
+        equity_curve.len() > self.equity || trades.len() > self.trades
+    }
+
+    /// Build the step holding everything after the cursor, then advance it.
+    fn take(
+        &mut self,
+        bar_index: usize,
+        total_bars: usize,
+        equity_curve: &[EquityPoint],
+        drawdown_curve: &[DrawdownPoint],
+        trades: &[TradeResult],
+        in_position: bool,
+    ) -> BacktestStep {
+        let step = BacktestStep {
+            bar_index,
+            total_bars,
+            equity_points: equity_curve[self.equity..].to_vec(),
+            drawdown_points: drawdown_curve[self.drawdown..].to_vec(),
+            new_trades: trades[self.trades..].to_vec(),
+            in_position,
+        };
+        self.equity = equity_curve.len();
+        self.drawdown = drawdown_curve.len();
+        self.trades = trades.len();
+        step
+    }
 }
 
 /// Internal implementation allowing an optional shared indicator cache for optimization.
@@ -78,7 +159,38 @@ pub fn run_backtest_with_cache(
     progress_callback: impl Fn(u8, usize, usize),
     shared_cache: Arc<IndicatorCache>,
 ) -> Result<BacktestResults, AppError> {
-    run_backtest_inner(candles, sub_bars, strategy, config, instrument, cancel_flag, progress_callback, Some(shared_cache))
+    run_backtest_inner(candles, sub_bars, strategy, config, instrument, cancel_flag, progress_callback, Some(shared_cache), None)
+}
+
+/// Run several strategies over the same candles and sub-bar data in parallel.
+/// Results keep the input order and match individual `run_backtest` calls; the
+/// first error (including `BacktestCancelled`) aborts the batch.
+pub fn run_backtest_batch(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategies: &[Strategy],
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    cancel_flag: &AtomicBool,
+) -> Result<Vec<BatchBacktestResult>, AppError> {
+    strategies
+        .par_iter()
+        .map(|strategy| {
+            // The per-run check only fires every 1000 bars; don't start new runs once cancelled.
+            if cancel_flag.load(Ordering::Relaxed) {
+                return Err(AppError::BacktestCancelled);
+            }
+            let result = run_backtest(
+                candles, sub_bars, strategy, config, instrument, cancel_flag, |_, _, _| {},
+            )?;
+            Ok(BatchBacktestResult {
+                strategy_id: strategy.id.clone(),
+                strategy_name: strategy.name.clone(),
+                metrics: result.metrics,
+                warnings: result.warnings,
+            })
+        })
+        .collect()
 }
 
 fn run_backtest_inner(
@@ -90,6 +202,7 @@ fn run_backtest_inner(
     cancel_flag: &AtomicBool,
     progress_callback: impl Fn(u8, usize, usize),
     shared_indicator_cache: Option<Arc<IndicatorCache>>,
+    step_observer: Option<&StepObserver>,
 ) -> Result<BacktestResults, AppError> {
     let total_bars = candles.len();
     info!("Starting backtest: {} bars, strategy={}, precision={:?}",
@@ -105,17 +218,32 @@ fn run_backtest_inner(
     init_strategy_hashes(&mut strategy_owned);
     let strategy = &strategy_owned;
 
+    // Signal series vs. execution series.
+    // With `use_heikin_ashi`, indicators, daily OHLC, candle patterns and rule
+    // evaluation all read `signal_candles` (HA), while every fill, SL/TP check,
+    // ATR distance, cost and equity mark keeps reading the raw `candles`.
+    // HA prices are synthetic — HA close is the bar average and HA open depends on
+    // the previous HA bar — so filling at them would book trades at levels the
+    // market never printed and make HA strategies look far better than they are.
+    let ha_candles;
+    let signal_candles: &[Candle] = if config.use_heikin_ashi {
+        ha_candles = heikin_ashi_candles(candles);
+        &ha_candles
+    } else {
+        candles
+    };
+
     // Pre-compute all indicators (use shared cache in optimizer context).
     // Use `ref` so we borrow the Arc rather than moving it — the same Arc is
     // reused below for ATR caching without an extra clone.
     let cache = if let Some(ref shared) = shared_indicator_cache {
-        pre_compute_indicators_with_shared_cache(strategy, candles, shared)?
+        pre_compute_indicators_with_shared_cache(strategy, signal_candles, instrument.session_start_hour, shared)?
     } else {
-        pre_compute_indicators(strategy, candles)?
+        pre_compute_indicators(strategy, signal_candles, instrument.session_start_hour)?
     };
 
     // Pre-compute daily OHLC boundaries for Daily price fields
-    let daily_ohlc = compute_daily_ohlc(candles);
+    let daily_ohlc = compute_daily_ohlc(signal_candles, instrument.session_start_hour);
 
     // Pre-compute time cache for BarTime operands (only if used)
     let time_cache = if strategy_uses_time_fields(strategy) {
@@ -126,7 +254,7 @@ fn run_backtest_inner(
 
     // Pre-compute candle pattern cache (only if used)
     let pattern_cache = if strategy_uses_candle_patterns(strategy) {
-        Some(compute_candle_pattern_cache(candles))
+        Some(compute_candle_pattern_cache(signal_candles))
     } else {
         None
     };
@@ -137,7 +265,7 @@ fn run_backtest_inner(
     // the EMA formula again, saving O(N) work per strategy per ATR component.
     let atr_values = if let Some(ref sc) = shared_indicator_cache {
         let sl_period = strategy.stop_loss.as_ref()
-            .filter(|sl| matches!(sl.sl_type, crate::models::strategy::StopLossType::ATR))
+            .filter(|sl| sl.sl_type.uses_atr())
             .and_then(|sl| sl.atr_period);
         let tp_period = strategy.take_profit.as_ref()
             .filter(|tp| matches!(tp.tp_type, crate::models::strategy::TakeProfitType::ATR))
@@ -154,20 +282,30 @@ fn run_backtest_inner(
         compute_atr_if_needed(strategy, candles)
     };
 
+    // Chande Kroll stop levels, trailed once per completed bar in Phase 5
+    let chande_kroll = strategy
+        .stop_loss
+        .as_ref()
+        .filter(|sl| sl.sl_type == StopLossType::ChandeKroll)
+        .and_then(|sl| Some(chande_kroll_levels(candles, atr_values.for_sl.as_deref()?, sl.atr_period?, sl.value)));
+
     // Pre-compute order-price indicator values (for Stop/Limit target price)
-    let order_price_values = compute_order_price_indicator(strategy, candles);
+    let order_price_values = compute_order_price_indicator(strategy, candles, instrument.session_start_hour);
 
-    let lookback = max_lookback(strategy);
+    // Skip the user warmup when it exceeds what the indicators strictly need
+    let lookback = max_lookback(strategy).max(config.warmup_bars.unwrap_or(0));
     // Must start at least at bar 1: the loop uses i-1 for indicator values
     let start_bar = lookback.max(1).min(total_bars);
 
     // Pre-compute spread in price units (used for bid/ask OHLC derivation)
     let spread = orders::spread_price(&strategy.trading_costs, instrument);
 
-    // Seeded from OS entropy — provides a stable sequence within a single run.
-    // Passed to order functions so random slippage is reproducible across re-runs
-    // when the same seed is used.
-    let mut rng = SmallRng::from_entropy();
+    // Passed to order functions for random slippage. A `slippage_seed` makes the draws
+    // reproducible across re-runs; without one they come from OS entropy.
+    let mut rng = match config.slippage_seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
 
     let mut equity = config.initial_capital;
     let mut peak_equity = equity;
@@ -178,12 +316,18 @@ fn run_backtest_inner(
     // avoid N × DrawdownPoint heap allocations + N × String::clone() per evaluation.
     let skip_drawdown_curve = shared_indicator_cache.is_some();
 
+    // With `equity_sampling` the stored points are picked bar by bar; metric inputs still see every bar
+    let mut sampled = config
+        .equity_sampling
+        .map(|max_points| SampledCurves::new(candles, start_bar, max_points, config, !skip_drawdown_curve));
+    let curve_capacity = config.equity_sampling.map_or(total_bars, |max_points| max_points.min(total_bars));
+
     let mut trades: Vec<TradeResult> = Vec::new();
-    let mut equity_curve: Vec<EquityPoint> = Vec::with_capacity(total_bars);
+    let mut equity_curve: Vec<EquityPoint> = Vec::with_capacity(curve_capacity);
     let mut drawdown_curve: Vec<DrawdownPoint> = if skip_drawdown_curve {
         Vec::new()
     } else {
-        Vec::with_capacity(total_bars)
+        Vec::with_capacity(curve_capacity)
     };
     // Track consecutive losses for AntiMartingale position sizing
     let mut consecutive_losses: u32 = 0;
@@ -198,20 +342,34 @@ fn run_backtest_inner(
         TradeDirection::Short | TradeDirection::Both
     );
 
-    // Tick-mode gate: Phase 2.5 only runs for real-tick precision modes
+    // Tick-mode gate: Phase 2.5 only runs for real-tick precision modes.
+    // Disabled for Heikin-Ashi: an HA bar only exists once the raw bar has closed,
+    // so there is no meaningful in-progress HA candle to evaluate tick by tick.
     let is_tick_mode = matches!(
         config.precision,
         crate::models::strategy::BacktestPrecision::RealTickCustomSpread
             | crate::models::strategy::BacktestPrecision::RealTickRealSpread
-    );
+    ) && !config.use_heikin_ashi;
 
     // Sub-bar cursor for O(n+m) range lookups
     let mut sub_cursor: usize = 0;
 
+    // Chunked tick mode: the resident window covers bars [.., window_end_bar).
+    // The cursor restarts at 0 on each window, keeping lookups O(n+m) overall.
+    let tick_chunks = match sub_bars {
+        SubBarData::TickChunks { source, chunk_bars } => Some((source, (*chunk_bars).max(1))),
+        _ => None,
+    };
+    let mut tick_window = SubBarData::None;
+    let mut window_end_bar = 0usize;
+
     // Daily trade tracking
     let mut daily_trade_count: usize = 0;
     let mut current_date = String::new();
 
+    // Entry signals suppressed by `max_spread_pips`, reported in the result warnings
+    let mut spread_skips = SpreadSkips::default();
+
     // MT5-matching execution model:
     // At bar i, evaluate rules using bar[i-1]'s indicator data (last completed bar)
     // but bar[i]'s time (time_offset=1). Execute entries/exits at bar[i]'s open.
@@ -222,7 +380,13 @@ fn run_backtest_inner(
         start_bar + ((total_bars - start_bar) as f32 * pct.clamp(0.0, 1.0)) as usize
     });
 
+    // Bar at which equity hit `stop_out_equity`, ending the run
+    let mut stop_out_bar: Option<usize> = None;
+    let mut step_cursor = StepCursor::default();
+
     for i in start_bar..total_bars {
+        let trades_at_bar_start = trades.len();
+
         // Check cancellation
         if i % 1000 == 0 {
             if cancel_flag.load(Ordering::Relaxed) {
@@ -246,11 +410,17 @@ fn run_backtest_inner(
                     long_metrics: None,
                     short_metrics: None,
                     warnings: vec![],
+                    benchmark_curve: vec![],
+                    stopped_out: false,
+                    rolling_ratios: vec![],
                 });
             }
         }
 
         let candle = &candles[i];
+        // Quote→account rate for sizing and swap: the latest conversion close known when
+        // this bar opens.
+        let known_quote_rate = || orders::quote_to_account_rate(config, &candle.datetime);
         let next_dt = if i + 1 < total_bars {
             candles[i + 1].datetime.as_str()
         } else {
@@ -259,6 +429,23 @@ fn run_backtest_inner(
         let candle_ts = candle.timestamp;
         let next_ts = if i + 1 < total_bars { candles[i + 1].timestamp } else { i64::MAX };
 
+        if let Some((source, chunk_bars)) = tick_chunks {
+            if i >= window_end_bar {
+                window_end_bar = (i + chunk_bars).min(total_bars);
+                let to_ts = if window_end_bar < total_bars { candles[window_end_bar].timestamp } else { i64::MAX };
+                // Free the old window before loading the next so only one is resident
+                drop(std::mem::replace(&mut tick_window, SubBarData::None));
+                // `load_range` is half-open; shift it by 1µs so end-inclusive windows keep
+                // the tick stamped on their closing boundary.
+                let shift = i64::from(instrument.subbar_boundary == SubBarBoundary::EndInclusive);
+                tick_window = SubBarData::Ticks(
+                    source.load_range(candle_ts.saturating_add(shift), to_ts.saturating_add(shift))?,
+                );
+                sub_cursor = 0;
+            }
+        }
+        let sub_bars: &SubBarData = if tick_chunks.is_some() { &tick_window } else { sub_bars };
+
         // Find sub-bar range for this TF candle (advance cursor)
         let (sub_start, sub_end) = find_subbar_range(
             sub_bars,
@@ -267,6 +454,7 @@ fn run_backtest_inner(
             next_dt,
             candle_ts,
             next_ts,
+            instrument.subbar_boundary,
         );
 
         // ── Phase 0: Fill or expire pending limit/stop order ──
@@ -283,11 +471,12 @@ fn run_backtest_inner(
                 let expired = i.saturating_sub(pending.created_bar) > expiry_bars;
                 if filled {
                     let fill_price = orders::apply_entry_costs(pending.target_price, pending.direction, &strategy.trading_costs, instrument, &mut rng);
+                    let (entry_spread, entry_slippage) = entry_cost_split(pending.direction, pending.target_price, fill_price, spread);
                     let sl_price = strategy.stop_loss.as_ref().map(|sl_cfg| {
                         let sl = calculate_stop_loss(sl_cfg, fill_price, pending.direction, pending.atr_for_sl, instrument);
                         enforce_stops_level_sl(sl, fill_price, pending.direction, instrument)
                     });
-                    let fill_lots = calculate_lots(&strategy.position_sizing, equity, fill_price, sl_price, instrument, consecutive_losses);
+                    let fill_lots = calculate_lots(&strategy.position_sizing, config.sizing_equity(equity), fill_price, sl_price, instrument, consecutive_losses, known_quote_rate()?);
                     let tp_price = strategy.take_profit.as_ref().map(|tp_cfg| {
                         let tp = calculate_take_profit(tp_cfg, fill_price, sl_price, pending.direction, pending.atr_for_tp, instrument);
                         enforce_stops_level_tp(tp, fill_price, pending.direction, instrument)
@@ -302,6 +491,7 @@ fn run_backtest_inner(
                         entry_time: candle.datetime.clone(),
                         lots: fill_lots,
                         stop_loss: sl_price,
+                        initial_stop_loss: sl_price,
                         take_profit: tp_price,
                         trailing_stop_distance: ts_distance,
                         highest_since_entry: candle.high,
@@ -311,10 +501,15 @@ fn run_backtest_inner(
                         trailing_stop_activated: false,
                         last_swap_date: candle.datetime[..10.min(candle.datetime.len())].to_string(),
                         accumulated_swap: 0.0,
+                        entry_spread,
+                        entry_slippage,
                         sl_moved_to_be: false,
                         trailing_activation_dist: strategy.trailing_stop.as_ref()
                             .and_then(|ts| ts.activation_pips)
-                            .map(|pips| pips * instrument.pip_size),
+                            .map(|pips| instrument.pips_to_price(pips)),
+                        trailing_step: strategy.trailing_stop.as_ref()
+                            .and_then(|ts| trailing_step_price(ts, instrument)),
+                        entry_reason: pending.entry_reason.clone(),
                     });
                     daily_trade_count += 1;
                     pending_order = None;
@@ -324,64 +519,136 @@ fn run_backtest_inner(
             }
         }
 
+        // ExitPriority::StopsFirst: an SL/TP reachable anywhere in this bar pre-empts the
+        // bar-open exits below and is left to Phase 3. Probed on a copy so the trailing
+        // stop and MAE/MFE are only advanced once.
+        let stops_pre_empt = |pos: &OpenPosition| -> Result<bool, AppError> {
+            Ok(config.exit_priority == ExitPriority::StopsFirst
+                && resolve_exit(
+                    &mut pos.clone(), candle, sub_bars, sub_start, sub_end, instrument, spread, config.precision, cancel_flag,
+                )?
+                .is_some())
+        };
+
+        // Entry signal for `dir`, evaluated like the exits below (used by Phases 1 and 2)
+        let entry_signal = |dir: TradeDirection| -> bool {
+            let (groups, rules) = match dir {
+                TradeDirection::Short => (&strategy.short_entry_groups, &strategy.short_entry_rules),
+                TradeDirection::Long | TradeDirection::Both => (&strategy.long_entry_groups, &strategy.long_entry_rules),
+            };
+            if !groups.is_empty() {
+                evaluate_rule_groups(groups, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+            } else {
+                !rules.is_empty() && evaluate_rules(rules, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+            }
+        };
+        // Entry annotation for Phases 2 and 2.5. Bar-open entries name the rule groups
+        // that fired; tick entries always fire from the flat rules, so they report those.
+        let entry_annotation = |dir: TradeDirection, from_groups: bool| -> String {
+            let (label, groups, rule_count) = match dir {
+                TradeDirection::Short => ("Short entry", &strategy.short_entry_groups, strategy.short_entry_rules.len()),
+                TradeDirection::Long | TradeDirection::Both => ("Long entry", &strategy.long_entry_groups, strategy.long_entry_rules.len()),
+            };
+            if !from_groups {
+                return rule_set_annotation(label, &[], &[], rule_count);
+            }
+            let fired = fired_group_indices(groups, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1);
+            rule_set_annotation(label, groups, &fired, rule_count)
+        };
+        // Set when an opposite entry signal closes the position: reverse into that
+        // direction in Phase 2, or (close-only) skip entries for the rest of the bar
+        let mut reverse_into: Option<TradeDirection> = None;
+        let mut entry_blocked = false;
+
         // ── Phase 1: Rule-based exit at bar[i] open ──
         // Evaluate using bar[i-1]'s indicator data, bar[i]'s time (time_offset=1)
         if let Some(ref pos) = position {
-            let exit_signal = match pos.direction {
+            let held_min = strategy.min_hold_bars
+                .map_or(true, |min| i.saturating_sub(pos.entry_bar) >= min as usize);
+            let exit_signal = held_min && match pos.direction {
                 TradeDirection::Long | TradeDirection::Both => {
                     if !strategy.long_exit_groups.is_empty() {
-                        evaluate_rule_groups(&strategy.long_exit_groups, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+                        evaluate_rule_groups(&strategy.long_exit_groups, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
                     } else {
-                        !strategy.long_exit_rules.is_empty() && evaluate_rules(&strategy.long_exit_rules, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+                        !strategy.long_exit_rules.is_empty() && evaluate_rules(&strategy.long_exit_rules, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
                     }
                 }
                 TradeDirection::Short => {
                     if !strategy.short_exit_groups.is_empty() {
-                        evaluate_rule_groups(&strategy.short_exit_groups, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+                        evaluate_rule_groups(&strategy.short_exit_groups, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
                     } else {
-                        !strategy.short_exit_rules.is_empty() && evaluate_rules(&strategy.short_exit_rules, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
+                        !strategy.short_exit_rules.is_empty() && evaluate_rules(&strategy.short_exit_rules, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
                     }
                 }
             };
-            if exit_signal
-            {
+            let opposite = match pos.direction {
+                TradeDirection::Short => TradeDirection::Long,
+                TradeDirection::Long | TradeDirection::Both => TradeDirection::Short,
+            };
+            let opposite_signal = (strategy.close_on_opposite_signal || strategy.reverse_on_opposite_signal)
+                && held_min
+                && !exit_signal
+                && entry_signal(opposite);
+            if (exit_signal || opposite_signal) && !stops_pre_empt(pos)? {
                 let exit_price = candle.open;
-                let trade = close_position(
-                    pos, exit_price, &candle.datetime, i, CloseReason::Signal,
+                let mut trade = close_position(
+                    pos, exit_price, &candle.datetime, i, CloseReason::Signal, None,
                     instrument, strategy, config, &mut rng,
-                );
+                )?;
+                let (label, groups, rule_count) = match (opposite_signal, pos.direction) {
+                    (true, TradeDirection::Short) => ("Opposite long entry", &strategy.long_entry_groups, strategy.long_entry_rules.len()),
+                    (true, _) => ("Opposite short entry", &strategy.short_entry_groups, strategy.short_entry_rules.len()),
+                    (false, TradeDirection::Short) => ("Short exit", &strategy.short_exit_groups, strategy.short_exit_rules.len()),
+                    (false, _) => ("Long exit", &strategy.long_exit_groups, strategy.long_exit_rules.len()),
+                };
+                let fired = fired_group_indices(groups, i - 1, &cache, signal_candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1);
+                trade.exit_reason = Some(rule_set_annotation(label, groups, &fired, rule_count));
                 // Swap was already deducted from equity per-bar; only PnL and commission remain
                 equity += trade.pnl - trade.commission;
                 if trade.pnl >= 1e-6 { consecutive_losses = 0; }
                 else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
                 trades.push(trade);
                 position = None;
+                if opposite_signal {
+                    if strategy.reverse_on_opposite_signal {
+                        reverse_into = Some(opposite);
+                    } else {
+                        entry_blocked = true;
+                    }
+                }
             }
         }
 
-        // ── Phase 1.5: Close after N bars ──
+        // ── Phase 1.5: Close after N bars / max hold ──
         if let Some(ref pos) = position {
-            if let Some(max_bars) = strategy.close_after_bars {
-                if i.saturating_sub(pos.entry_bar) >= max_bars as usize {
-                    let trade = close_position(
-                        pos, candle.open, &candle.datetime, i, CloseReason::ExitAfterBars,
-                        instrument, strategy, config, &mut rng,
-                    );
-                    equity += trade.pnl - trade.commission;
-                    if trade.pnl >= 1e-6 { consecutive_losses = 0; }
-                    else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
-                    trades.push(trade);
-                    position = None;
-                }
+            let held = i.saturating_sub(pos.entry_bar);
+            let reason = if strategy.close_after_bars.is_some_and(|n| held >= n as usize) {
+                Some(CloseReason::ExitAfterBars)
+            } else if strategy.max_hold_bars.is_some_and(|n| held >= n as usize) {
+                Some(CloseReason::MaxHold)
+            } else {
+                None
+            };
+            let reason = if reason.is_some() && stops_pre_empt(pos)? { None } else { reason };
+            if let Some(reason) = reason {
+                let trade = close_position(
+                    pos, candle.open, &candle.datetime, i, reason, None,
+                    instrument, strategy, config, &mut rng,
+                )?;
+                equity += trade.pnl - trade.commission;
+                if trade.pnl >= 1e-6 { consecutive_losses = 0; }
+                else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
+                trades.push(trade);
+                position = None;
             }
         }
 
         // ── Phase 2: Rule-based entry at bar[i] open ──
         // Evaluate using bar[i-1]'s indicator data, bar[i]'s time (time_offset=1)
-        if position.is_none() && pending_order.is_none() {
-            let bar_date = &candle.datetime[..10.min(candle.datetime.len())];
-            if bar_date != current_date {
-                current_date = bar_date.to_string();
+        if position.is_none() && pending_order.is_none() && !entry_blocked {
+            let bar_date = candle.session_day(instrument.session_start_hour);
+            if *bar_date != *current_date {
+                current_date = bar_date.into_owned();
                 daily_trade_count = 0;
             }
 
@@ -392,31 +659,40 @@ fn run_backtest_inner(
                 });
             let under_daily_limit = strategy.max_daily_trades
                 .map_or(true, |max| daily_trade_count < max as usize);
-            // Max spread filter (bar-mode: fixed spread configured by user)
-            let within_spread = strategy.trading_costs.max_spread_pips
-                .map_or(true, |max_sp| strategy.trading_costs.spread_pips <= max_sp);
+            // Max spread filter: real ask-bid of the opening tick in tick modes,
+            // otherwise the fixed spread configured by the user
+            let within_spread = strategy.trading_costs.max_spread_pips.map_or(true, |max_sp| {
+                let spread_pips = match *sub_bars {
+                    SubBarData::Ticks(ref ticks) if sub_start < sub_end => {
+                        instrument.price_to_pips(ticks.asks[sub_start] - ticks.bids[sub_start])
+                    }
+                    _ => strategy.trading_costs.spread_pips,
+                };
+                spread_pips <= max_sp
+            });
 
-            if within_hours && under_daily_limit && within_spread {
+            if within_hours && under_daily_limit {
                 let mut entry_dir: Option<TradeDirection> = None;
 
-                let long_entry_signal = if !strategy.long_entry_groups.is_empty() {
-                    evaluate_rule_groups(&strategy.long_entry_groups, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
-                } else {
-                    !strategy.long_entry_rules.is_empty() && evaluate_rules(&strategy.long_entry_rules, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
-                };
-                let short_entry_signal = if !strategy.short_entry_groups.is_empty() {
-                    evaluate_rule_groups(&strategy.short_entry_groups, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
-                } else {
-                    !strategy.short_entry_rules.is_empty() && evaluate_rules(&strategy.short_entry_rules, i - 1, &cache, candles, Some(&daily_ohlc), time_cache.as_ref(), pattern_cache.as_ref(), 1)
-                };
-
-                if can_go_long && long_entry_signal {
+                if let Some(dir) = reverse_into {
+                    // The opposite signal that closed the position opens the new one
+                    let allowed = if dir == TradeDirection::Short { can_go_short } else { can_go_long };
+                    if allowed {
+                        entry_dir = Some(dir);
+                    }
+                } else if can_go_long && entry_signal(TradeDirection::Long) {
                     entry_dir = Some(TradeDirection::Long);
-                } else if can_go_short && short_entry_signal {
+                } else if can_go_short && entry_signal(TradeDirection::Short) {
                     entry_dir = Some(TradeDirection::Short);
                 }
+                if entry_dir.is_some() && !within_spread {
+                    entry_dir = None;
+                    spread_skips.record(i, &candle.datetime);
+                }
 
                 if let Some(dir) = entry_dir {
+                    let entry_reason = Some(entry_annotation(dir, true));
+
                     // Use bar[i-1]'s ATR (last completed bar before entry).
                     // Each component uses its own ATR period to avoid silent
                     // "first found wins" contamination across SL/TP/trailing stop.
@@ -456,11 +732,13 @@ fn run_backtest_inner(
                             } else {
                                 orders::apply_entry_costs(raw_price, dir, &strategy.trading_costs, instrument, &mut rng)
                             };
+                            let (entry_spread, entry_slippage) =
+                                entry_cost_split(dir, raw_price, entry_price, if spread_from_ticks { 0.0 } else { spread });
                             let sl_price = strategy.stop_loss.as_ref().map(|sl_cfg| {
                                 let sl = calculate_stop_loss(sl_cfg, entry_price, dir, atr_for_sl, instrument);
                                 enforce_stops_level_sl(sl, entry_price, dir, instrument)
                             });
-                            let lots = calculate_lots(&strategy.position_sizing, equity, entry_price, sl_price, instrument, consecutive_losses);
+                            let lots = calculate_lots(&strategy.position_sizing, config.sizing_equity(equity), entry_price, sl_price, instrument, consecutive_losses, known_quote_rate()?);
                             let tp_price = strategy.take_profit.as_ref().map(|tp_cfg| {
                                 let tp = calculate_take_profit(tp_cfg, entry_price, sl_price, dir, atr_for_tp, instrument);
                                 enforce_stops_level_tp(tp, entry_price, dir, instrument)
@@ -475,6 +753,7 @@ fn run_backtest_inner(
                                 entry_time: entry_dt,
                                 lots,
                                 stop_loss: sl_price,
+                                initial_stop_loss: sl_price,
                                 take_profit: tp_price,
                                 trailing_stop_distance: ts_distance,
                                 highest_since_entry: candle.high + if dir == TradeDirection::Short { spread } else { 0.0 },
@@ -484,10 +763,15 @@ fn run_backtest_inner(
                                 trailing_stop_activated: false,
                                 last_swap_date: candle.datetime[..10.min(candle.datetime.len())].to_string(),
                                 accumulated_swap: 0.0,
+                                entry_spread,
+                                entry_slippage,
                                 sl_moved_to_be: false,
                                 trailing_activation_dist: strategy.trailing_stop.as_ref()
                                     .and_then(|ts| ts.activation_pips)
-                                    .map(|pips| pips * instrument.pip_size),
+                                    .map(|pips| instrument.pips_to_price(pips)),
+                                trailing_step: strategy.trailing_stop.as_ref()
+                                    .and_then(|ts| trailing_step_price(ts, instrument)),
+                                entry_reason,
                             });
                             daily_trade_count += 1;
                         }
@@ -520,7 +804,7 @@ fn run_backtest_inner(
                                 }
                             } else {
                                 // Fallback: fixed pip offset from bar open
-                                let offset = strategy.entry_order_offset_pips * instrument.pip_size;
+                                let offset = instrument.pips_to_price(strategy.entry_order_offset_pips);
                                 match (dir, order_type) {
                                     (TradeDirection::Long,  OrderType::Limit) => raw_price - offset,
                                     (TradeDirection::Long,  OrderType::Stop)  => raw_price + offset,
@@ -530,7 +814,7 @@ fn run_backtest_inner(
                                 }
                             };
                             // Pre-calculate lots using signal-bar price as proxy
-                            let proxy_lots = calculate_lots(&strategy.position_sizing, equity, target, None, instrument, consecutive_losses);
+                            let proxy_lots = calculate_lots(&strategy.position_sizing, config.sizing_equity(equity), target, None, instrument, consecutive_losses, known_quote_rate()?);
                             pending_order = Some(PendingOrder {
                                 direction: dir,
                                 order_type,
@@ -540,6 +824,7 @@ fn run_backtest_inner(
                                 atr_for_sl,
                                 atr_for_tp,
                                 atr_for_ts,
+                                entry_reason,
                             });
                             daily_trade_count += 1;
                         }
@@ -554,7 +839,7 @@ fn run_backtest_inner(
         // Entry fires at the first tick where the rules become true; subsequent ticks
         // in this bar are handed to Phase 3 (SL/TP) as the start of the open position.
         let mut phase3_sub_start = sub_start; // adjusted when entry fires mid-bar
-        if is_tick_mode && position.is_none() && i > 0 {
+        if is_tick_mode && position.is_none() && !entry_blocked && i > 0 {
             if let SubBarData::Ticks(ref ticks) = *sub_bars {
                 if sub_start < sub_end {
                     let streaming_state =
@@ -603,6 +888,8 @@ fn run_backtest_inner(
                             low: running_low,
                             close: tick_mid,
                             volume: 0.0,
+                            open_interest: candle.open_interest,
+                            avg_spread: candle.avg_spread,
                         };
 
                         // Trading-hours guard: use integer arithmetic — no String allocation per tick
@@ -616,9 +903,9 @@ fn run_backtest_inner(
                         // Max spread filter — use real bid-ask spread from tick data
                         let within_spread = strategy.trading_costs.max_spread_pips.map_or(
                             true,
-                            |max_sp| (tick_ask - tick_bid) / instrument.pip_size <= max_sp,
+                            |max_sp| instrument.price_to_pips(tick_ask - tick_bid) <= max_sp,
                         );
-                        if !within_hours || !under_daily_limit || !within_spread {
+                        if !within_hours || !under_daily_limit {
                             continue 'tick_entry;
                         }
 
@@ -643,6 +930,10 @@ fn run_backtest_inner(
                         {
                             tick_dir = Some(TradeDirection::Short);
                         }
+                        if tick_dir.is_some() && !within_spread {
+                            spread_skips.record(i, &candle.datetime);
+                            continue 'tick_entry;
+                        }
 
                         if let Some(dir) = tick_dir {
                             // Execute at tick's actual bid/ask price (spread already embedded)
@@ -654,6 +945,7 @@ fn run_backtest_inner(
                             let entry_price = orders::apply_slippage_only(
                                 raw_price, dir, &strategy.trading_costs, instrument, &mut rng,
                             );
+                            let (entry_spread, entry_slippage) = entry_cost_split(dir, raw_price, entry_price, 0.0);
 
                             // ATR from bar[i-1] (last completed bar)
                             let get_atr = |v: &Option<Vec<f64>>| -> Option<f64> {
@@ -671,8 +963,8 @@ fn run_backtest_inner(
                                 enforce_stops_level_sl(sl, entry_price, dir, instrument)
                             });
                             let lots = calculate_lots(
-                                &strategy.position_sizing, equity, entry_price,
-                                sl_price, instrument, consecutive_losses,
+                                &strategy.position_sizing, config.sizing_equity(equity), entry_price,
+                                sl_price, instrument, consecutive_losses, known_quote_rate()?,
                             );
                             let tp_price = strategy.take_profit.as_ref().map(|tp_cfg| {
                                 let tp = calculate_take_profit(tp_cfg, entry_price, sl_price, dir, atr_for_tp, instrument);
@@ -691,6 +983,7 @@ fn run_backtest_inner(
                                 entry_time: tick_dt,
                                 lots,
                                 stop_loss: sl_price,
+                                initial_stop_loss: sl_price,
                                 take_profit: tp_price,
                                 trailing_stop_distance: ts_distance,
                                 // running_high/low are mid-prices (avg of bid+ask ticks).
@@ -705,10 +998,15 @@ fn run_backtest_inner(
                                     [..10.min(candle.datetime.len())]
                                     .to_string(),
                                 accumulated_swap: 0.0,
+                                entry_spread,
+                                entry_slippage,
                                 sl_moved_to_be: false,
                                 trailing_activation_dist: strategy.trailing_stop.as_ref()
                                     .and_then(|ts| ts.activation_pips)
-                                    .map(|pips| pips * instrument.pip_size),
+                                    .map(|pips| instrument.pips_to_price(pips)),
+                                trailing_step: strategy.trailing_stop.as_ref()
+                                    .and_then(|ts| trailing_step_price(ts, instrument)),
+                                entry_reason: Some(entry_annotation(dir, false)),
                             });
                             daily_trade_count += 1;
                             // Phase 3 must start from the tick AFTER entry
@@ -722,14 +1020,22 @@ fn run_backtest_inner(
 
         // ── Phase 3: Check SL/TP for existing position ──
         if let Some(ref mut pos) = position {
-            let exit_result = resolve_exit(
-                pos, candle, sub_bars, phase3_sub_start, sub_end, instrument, spread, config.precision,
-            );
+            let exit_result = match resolve_exit(
+                pos, candle, sub_bars, phase3_sub_start, sub_end, instrument, spread, config.precision, cancel_flag,
+            ) {
+                Ok(exit) => exit,
+                Err(e) => {
+                    info!("Backtest cancelled inside sub-bars of bar {}/{}", i, total_bars);
+                    return Err(e);
+                }
+            };
 
             if let Some((exit_price, exit_time, reason)) = exit_result {
+                // Bar and M1 fills come off the configured bid/ask; tick fills carry their own spread
+                let embedded_spread = if matches!(*sub_bars, SubBarData::Ticks(_)) { 0.0 } else { spread };
                 let trade = close_position(
-                    pos, exit_price, &exit_time, i, reason, instrument, strategy, config, &mut rng,
-                );
+                    pos, exit_price, &exit_time, i, reason, Some(embedded_spread), instrument, strategy, config, &mut rng,
+                )?;
                 equity += trade.pnl - trade.commission;
                 if trade.pnl >= 1e-6 { consecutive_losses = 0; }
                 else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
@@ -743,9 +1049,26 @@ fn run_backtest_inner(
             if should_close_at_time(&strategy.close_trades_at, &candle.datetime) {
                 let exit_price = candle.close;
                 let trade = close_position(
-                    pos, exit_price, &candle.datetime, i, CloseReason::TimeClose,
+                    pos, exit_price, &candle.datetime, i, CloseReason::TimeClose, None,
                     instrument, strategy, config, &mut rng,
-                );
+                )?;
+                equity += trade.pnl - trade.commission;
+                if trade.pnl >= 1e-6 { consecutive_losses = 0; }
+                else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
+                trades.push(trade);
+                position = None;
+            }
+        }
+
+        // ── Phase 4.2: Market-on-close on the session's final bar ──
+        if let Some(ref pos) = position {
+            if strategy.session_exit == Some(SessionExit::MarketOnClose)
+                && is_last_bar_of_session(candles, i, instrument.session_start_hour)
+            {
+                let trade = close_position(
+                    pos, candle.close, &candle.datetime, i, CloseReason::SessionClose, None,
+                    instrument, strategy, config, &mut rng,
+                )?;
                 equity += trade.pnl - trade.commission;
                 if trade.pnl >= 1e-6 { consecutive_losses = 0; }
                 else if trade.pnl <= -1e-6 { consecutive_losses = consecutive_losses.saturating_add(1); }
@@ -758,7 +1081,9 @@ fn run_backtest_inner(
         if let Some(ref mut pos) = position {
             let (charge, multiplier) = should_charge_swap(pos, &candle.datetime, instrument);
             if charge {
-                let swap = calculate_swap_charge(pos.direction, pos.lots, pos.entry_price, instrument, multiplier);
+                let swap = calculate_swap_charge(
+                    pos.direction, pos.lots, pos.entry_price, instrument, multiplier, known_quote_rate()?,
+                );
                 pos.accumulated_swap += swap;
                 pos.last_swap_date = candle.datetime[..10.min(candle.datetime.len())].to_string();
                 equity += swap; // swap is negative when it costs the trader
@@ -771,6 +1096,9 @@ fn run_backtest_inner(
                 let ba = orders::BidAskOhlc::from_candle(candle, spread);
                 update_trailing_stop(pos, &ba);
             }
+            if let Some(ref levels) = chande_kroll {
+                update_chande_kroll_stop(pos, levels, i);
+            }
         }
 
         // ── Phase 5.5: Move SL to breakeven ──
@@ -794,13 +1122,19 @@ fn run_backtest_inner(
 
         // ── Phase 6: Record equity and drawdown ──
         let unrealized = if let Some(ref pos) = position {
-            let pnl_pips = orders::calculate_pnl_pips(
+            // Mark at the price the position would close at: bid for longs, ask for shorts
+            let mark = match pos.direction {
+                TradeDirection::Short => candle.close + spread,
+                TradeDirection::Long | TradeDirection::Both => candle.close,
+            };
+            orders::calculate_pnl(
                 pos.direction,
                 pos.entry_price,
-                candle.close,
+                mark,
+                pos.lots,
                 instrument,
-            );
-            pnl_pips * instrument.pip_value * pos.lots
+                orders::quote_to_account_rate(config, &candle.datetime)?,
+            )
         } else {
             0.0
         };
@@ -809,38 +1143,84 @@ fn run_backtest_inner(
         if current_equity > peak_equity {
             peak_equity = current_equity;
         }
-        let drawdown_pct = if peak_equity > 0.0 {
+        let mut drawdown_pct = if peak_equity > 0.0 {
             (peak_equity - current_equity) / peak_equity * 100.0
         } else {
             0.0
         };
+        if config.drawdown_basis == DrawdownBasis::Intrabar && !skip_drawdown_curve && peak_equity > 0.0 {
+            if let Some(ref pos) = position {
+                // A position opened mid-bar only sees the sub-bars after its entry
+                let start = if pos.entry_bar == i { phase3_sub_start } else { sub_start };
+                let mark = worst_intrabar_mark(pos.direction, candle, sub_bars, start, sub_end, spread);
+                let worst_equity = equity + orders::calculate_pnl(
+                    pos.direction,
+                    pos.entry_price,
+                    mark,
+                    pos.lots,
+                    instrument,
+                    orders::quote_to_account_rate(config, &candle.datetime)?,
+                );
+                drawdown_pct = drawdown_pct.max((peak_equity - worst_equity) / peak_equity * 100.0);
+            }
+        }
 
-        equity_curve.push(EquityPoint {
-            timestamp: candle.datetime.clone(),
-            equity: current_equity,
-        });
-        if !skip_drawdown_curve {
-            drawdown_curve.push(DrawdownPoint {
+        if let Some(ref mut sampled) = sampled {
+            let trade_event = trades.len() > trades_at_bar_start || position.as_ref().is_some_and(|p| p.entry_bar == i);
+            sampled.record(i, current_equity, drawdown_pct, trade_event, &mut equity_curve, &mut drawdown_curve);
+        } else {
+            equity_curve.push(EquityPoint {
                 timestamp: candle.datetime.clone(),
-                drawdown_pct,
+                equity: current_equity,
             });
+            if !skip_drawdown_curve {
+                drawdown_curve.push(DrawdownPoint {
+                    timestamp: candle.datetime.clone(),
+                    drawdown_pct,
+                });
+            }
+        }
+
+        // ── Phase 7: Equity stop-out ──
+        if config.stop_out_equity.is_some_and(|floor| current_equity <= floor) {
+            info!("Backtest stopped out at bar {}/{} (equity {:.2})", i, total_bars, current_equity);
+            stop_out_bar = Some(i);
+            break;
+        }
+
+        // The last bar's step waits until after the loop, once an open position is settled
+        if let Some(observer) = step_observer.filter(|_| i + 1 < total_bars) {
+            if (i + 1 - start_bar) % observer.interval == 0 {
+                // A sampled run holds its latest bar back; store it so the step reaches bar `i`
+                if let Some(ref mut sampled) = sampled {
+                    sampled.flush_pending(&mut equity_curve, &mut drawdown_curve);
+                }
+                (observer.on_step)(step_cursor.take(i, total_bars, &equity_curve, &drawdown_curve, &trades, position.is_some()));
+                if cancel_flag.load(Ordering::Relaxed) {
+                    info!("Stepped backtest cancelled at bar {}/{}", i, total_bars);
+                    return Err(AppError::BacktestCancelled);
+                }
+            }
         }
     }
 
-    // ── 4. Close any remaining position at end of data ──
+    // ── 4. Close any remaining position at end of data (or at the stop-out bar) ──
+    let last_bar = stop_out_bar.unwrap_or(total_bars - 1);
     if let Some(ref pos) = position {
-        let last_candle = &candles[total_bars - 1];
+        let last_candle = &candles[last_bar];
+        let reason = if stop_out_bar.is_some() { CloseReason::StopOut } else { CloseReason::EndOfData };
         let trade = close_position(
             pos,
             last_candle.close,
             &last_candle.datetime,
-            total_bars - 1,
-            CloseReason::EndOfData,
+            last_bar,
+            reason,
+            None,
             instrument,
             strategy,
             config,
             &mut rng,
-        );
+        )?;
         equity += trade.pnl - trade.commission;
         // Note: swap was already deducted from equity per-bar; no adjustment needed here
         trades.push(trade);
@@ -855,21 +1235,50 @@ fn run_backtest_inner(
         } else {
             0.0
         };
-        if let Some(last) = equity_curve.last_mut() {
-            last.equity = equity;
-        }
-        if !skip_drawdown_curve {
-            if let Some(last) = drawdown_curve.last_mut() {
-                last.drawdown_pct = final_dd_pct;
+        if let Some(ref mut sampled) = sampled {
+            sampled.settle_last(equity, final_dd_pct);
+        } else {
+            if let Some(last) = equity_curve.last_mut() {
+                last.equity = equity;
             }
+            if !skip_drawdown_curve {
+                if let Some(last) = drawdown_curve.last_mut() {
+                    last.drawdown_pct = final_dd_pct;
+                }
+            }
+        }
+    }
+    if let Some(ref mut sampled) = sampled {
+        sampled.finish(last_bar, &mut equity_curve, &mut drawdown_curve);
+    }
+
+    if let Some(observer) = step_observer {
+        if step_cursor.has_pending(&equity_curve, &trades) {
+            (observer.on_step)(step_cursor.take(last_bar, total_bars, &equity_curve, &drawdown_curve, &trades, false));
         }
     }
 
     progress_callback(100, total_bars, total_bars);
     info!("Backtest complete: {} trades", trades.len());
 
+    // Regime is read from the raw candles: it describes the market, not the signal series.
+    if let Some(ref regime) = config.regime {
+        let regimes = regime_series(candles, regime)?;
+        tag_trade_regimes(&mut trades, candles, &regimes);
+    }
+
     // ── 5. Calculate metrics ──
-    let metrics = calculate_metrics(&trades, &equity_curve, config.initial_capital, config.timeframe);
+    let mut metrics = match sampled {
+        Some(ref sampled) => {
+            let bars = &candles[start_bar..=last_bar];
+            let bar_of = |ts: &str| bars.partition_point(|c| c.datetime.as_str() <= ts).saturating_sub(1);
+            calculate_metrics_from_stats(&trades, &sampled.stats, bar_of, config.initial_capital, config.timeframe)
+        }
+        None => calculate_metrics(&trades, &equity_curve, config.initial_capital, config.timeframe),
+    };
+    if let Some(threshold) = config.overtrading_threshold {
+        apply_overtrading_threshold(&mut metrics, threshold);
+    }
 
     let returns: Vec<f64> = trades.iter().map(|t| t.pnl).collect();
 
@@ -883,14 +1292,38 @@ fn run_backtest_inner(
             .filter(|t| matches!(t.direction, TradeDirection::Short))
             .cloned()
             .collect();
-        (
-            calculate_direction_metrics(&long_trades, config.initial_capital, config.timeframe),
-            calculate_direction_metrics(&short_trades, config.initial_capital, config.timeframe),
-        )
+        let direction_metrics = |trades: &[TradeResult]| {
+            let mut m = calculate_direction_metrics(trades, config.initial_capital, config.timeframe)?;
+            if let Some(threshold) = config.overtrading_threshold {
+                apply_overtrading_threshold(&mut m, threshold);
+            }
+            Some(m)
+        };
+        (direction_metrics(&long_trades), direction_metrics(&short_trades))
     } else {
         (None, None)
     };
 
+    // Buy-and-hold benchmark over the same bars as the equity curve, and rolling
+    // Sharpe/Sortino over a tenth of the requested run (a stop-out does not shrink it),
+    // at least 20 bars (full backtest only).
+    // Uses raw closes even in Heikin-Ashi mode — the benchmark holds the real instrument.
+    let (benchmark_curve, rolling_ratios) = match sampled {
+        Some(sampled) => {
+            if let Some((ref stats, _)) = sampled.benchmark {
+                stats.apply(&mut metrics);
+            }
+            (sampled.benchmark_curve, sampled.rolling_ratios)
+        }
+        None if shared_indicator_cache.is_none() => {
+            let curve = buy_and_hold_curve(&candles[start_bar..=last_bar], config.initial_capital);
+            apply_benchmark_metrics(&mut metrics, &equity_curve, &curve);
+            let window = ((total_bars - start_bar) / 10).max(20);
+            (curve, rolling_risk_ratios(&equity_curve, window, config.timeframe))
+        }
+        None => (Vec::new(), Vec::new()),
+    };
+
     // Emit warnings about configuration that may affect accuracy
     let mut warnings = Vec::new();
     if matches!(config.precision, crate::models::strategy::BacktestPrecision::SelectedTfOnly | crate::models::strategy::BacktestPrecision::OpenPricesOnly)
@@ -903,6 +1336,20 @@ fn run_backtest_inner(
             config.precision, config.timeframe.as_str()
         ));
     }
+    if let Some(first) = &spread_skips.first {
+        warnings.push(format!(
+            "Skipped {} entry signal(s) with spread above the {} pip maximum (first at {}).",
+            spread_skips.count,
+            strategy.trading_costs.max_spread_pips.unwrap_or_default(),
+            first
+        ));
+    }
+    if let (Some(bar), Some(floor)) = (stop_out_bar, config.stop_out_equity) {
+        warnings.push(format!(
+            "Stopped out at {}: equity fell to the {:.2} floor.",
+            candles[bar].datetime, floor
+        ));
+    }
 
     Ok(BacktestResults {
         trades,
@@ -914,6 +1361,9 @@ fn run_backtest_inner(
         long_metrics,
         short_metrics,
         warnings,
+        benchmark_curve,
+        stopped_out: stop_out_bar.is_some(),
+        rolling_ratios,
     })
 }
 
@@ -921,10 +1371,27 @@ fn run_backtest_inner(
 // Sub-bar resolution
 // ══════════════════════════════════════════════════════════════
 
+/// How many sub-bars (M1 candles or ticks) are processed between cancellation checks
+/// inside a single TF bar. Keeps cancel responsive on bars with millions of ticks.
+pub(crate) const SUBBAR_CANCEL_CHECK_INTERVAL: usize = 65_536;
+
+/// True when `cancel_flag` is set and sub-bar index `j` is a check point.
+#[inline(always)]
+fn subbar_cancelled(cancel_flag: Option<&AtomicBool>, j: usize) -> bool {
+    j != 0
+        && j % SUBBAR_CANCEL_CHECK_INTERVAL == 0
+        && cancel_flag.is_some_and(|f| f.load(Ordering::Relaxed))
+}
+
 /// Find the sub-bar range [start, end) for a TF candle and advance the cursor.
 /// O(n+m) total across all candles in the backtest.
 /// For Candles: uses string datetime comparison.
 /// For Ticks: uses i64 microsecond timestamp comparison (~10x faster).
+///
+/// `boundary` decides who owns a sub-bar stamped exactly on a candle open:
+/// `StartInclusive` buckets by `[candle_ts, next_ts)`, `EndInclusive` by
+/// `(candle_ts, next_ts]`. The last candle (`next_dt` empty / `next_ts == i64::MAX`)
+/// takes every remaining sub-bar under either convention.
 pub(crate) fn find_subbar_range(
     sub_bars: &SubBarData,
     cursor: &mut usize,
@@ -932,47 +1399,54 @@ pub(crate) fn find_subbar_range(
     next_dt: &str,
     candle_ts: i64,
     next_ts: i64,
+    boundary: SubBarBoundary,
 ) -> (usize, usize) {
-    match sub_bars {
-        SubBarData::None => (0, 0),
+    let end_inclusive = boundary == SubBarBoundary::EndInclusive;
+    let (start, end) = match sub_bars {
+        // TickChunks is resolved into a resident `Ticks` window by `run_backtest`
+        SubBarData::None | SubBarData::TickChunks { .. } => return (0, 0),
         SubBarData::Candles(subs) => {
             let total = subs.len();
+            let before = |a: &str, b: &str| if end_inclusive { a <= b } else { a < b };
             // Skip past sub-bars before this candle (string comparison)
-            while *cursor < total && subs[*cursor].datetime.as_str() < candle_dt {
+            while *cursor < total && before(subs[*cursor].datetime.as_str(), candle_dt) {
                 *cursor += 1;
             }
             let start = *cursor;
             let mut end = start;
             if !next_dt.is_empty() {
-                while end < total && subs[end].datetime.as_str() < next_dt {
+                while end < total && before(subs[end].datetime.as_str(), next_dt) {
                     end += 1;
                 }
             } else {
                 end = total;
             }
-            *cursor = end;
             (start, end)
         }
         SubBarData::Ticks(ticks) => {
             let total = ticks.len();
             let ts = &ticks.timestamps;
+            let before = |a: i64, b: i64| if end_inclusive { a <= b } else { a < b };
             // i64 comparison — ~10x faster than string comparison per step
-            while *cursor < total && ts[*cursor] < candle_ts {
+            while *cursor < total && before(ts[*cursor], candle_ts) {
                 *cursor += 1;
             }
             let start = *cursor;
             let mut end = start;
             if next_ts < i64::MAX {
-                while end < total && ts[end] < next_ts {
+                while end < total && before(ts[end], next_ts) {
                     end += 1;
                 }
             } else {
                 end = total;
             }
-            *cursor = end;
             (start, end)
         }
-    }
+    };
+    // A boundary sub-bar must land in exactly one candle: ranges never overlap or run backwards.
+    debug_assert!(start <= end, "sub-bar range inverted: {start}..{end}");
+    *cursor = end;
+    (start, end)
 }
 
 /// Resolve SL/TP exit for an open position.
@@ -980,7 +1454,8 @@ pub(crate) fn find_subbar_range(
 /// `spread` is the full spread in price units (used to derive BidAsk prices in bar mode).
 /// `precision` controls whether SL/TP are checked only at bar open (OpenPricesOnly) or
 /// across the full OHLC range (SelectedTfOnly and above).
-/// Returns (exit_price, exit_time, reason) if an exit is triggered.
+/// Returns (exit_price, exit_time, reason) if an exit is triggered, or
+/// `BacktestCancelled` if `cancel_flag` is set while walking sub-bars.
 fn resolve_exit(
     pos: &mut OpenPosition,
     candle: &Candle,
@@ -990,9 +1465,10 @@ fn resolve_exit(
     instrument: &InstrumentConfig,
     spread: f64,
     precision: BacktestPrecision,
-) -> Option<(f64, String, CloseReason)> {
-    match sub_bars {
-        SubBarData::None => {
+    cancel_flag: &AtomicBool,
+) -> Result<Option<(f64, String, CloseReason)>, AppError> {
+    Ok(match sub_bars {
+        SubBarData::None | SubBarData::TickChunks { .. } => {
             let ba = orders::BidAskOhlc::from_candle(candle, spread);
             update_mae_mfe_ba(pos, &ba, instrument);
             if matches!(precision, BacktestPrecision::OpenPricesOnly) {
@@ -1007,13 +1483,13 @@ fn resolve_exit(
         }
         SubBarData::Candles(subs) => {
             // M1TickSimulation: iterate M1 sub-candles
-            process_subbars_candle(pos, subs, sub_start, sub_end, instrument, spread)
+            process_subbars_candle(pos, subs, sub_start, sub_end, instrument, spread, Some(cancel_flag))?
         }
         SubBarData::Ticks(ticks) => {
             // RealTick modes: optimized columnar tick processing
-            process_subbars_tick_columnar(pos, ticks, sub_start, sub_end, instrument)
+            process_subbars_tick_columnar(pos, ticks, sub_start, sub_end, instrument, Some(cancel_flag))?
         }
-    }
+    })
 }
 
 /// Process M1 sub-candles for SL/TP resolution within a TF bar.
@@ -1024,6 +1500,9 @@ fn resolve_exit(
 /// same-bar SL/TP conflicts on M1 candles. On M1, the bar direction (open→close)
 /// is a reliable indicator of which extreme was reached first, matching MT5's
 /// "1 minute OHLC" mode behaviour.
+///
+/// `cancel_flag` is polled every `SUBBAR_CANCEL_CHECK_INTERVAL` sub-candles;
+/// `None` disables the check.
 pub(crate) fn process_subbars_candle(
     pos: &mut OpenPosition,
     sub_candles: &[Candle],
@@ -1031,8 +1510,12 @@ pub(crate) fn process_subbars_candle(
     end: usize,
     instrument: &InstrumentConfig,
     spread: f64,
-) -> Option<(f64, String, CloseReason)> {
+    cancel_flag: Option<&AtomicBool>,
+) -> Result<Option<(f64, String, CloseReason)>, AppError> {
     for i in start..end {
+        if subbar_cancelled(cancel_flag, i - start) {
+            return Err(AppError::BacktestCancelled);
+        }
         let sc = &sub_candles[i];
         let ba = orders::BidAskOhlc::from_candle(sc, spread);
         // Update MAE/MFE using bid/ask split
@@ -1041,12 +1524,12 @@ pub(crate) fn process_subbars_candle(
         // On M1 bars the direction (open→close) reliably indicates which extreme
         // was reached first, unlike H1+ bars where the heuristic is unreliable.
         if let Some((exit_price, reason)) = check_sl_tp_hit_bar_direction(pos, &ba, sc.open, sc.close) {
-            return Some((exit_price, sc.datetime.clone(), reason));
+            return Ok(Some((exit_price, sc.datetime.clone(), reason)));
         }
         // Update trailing stop for next sub-bar
         update_trailing_stop(pos, &ba);
     }
-    None
+    Ok(None)
 }
 
 /// High-performance tick processing using columnar (SoA) data layout.
@@ -1057,18 +1540,21 @@ pub(crate) fn process_subbars_candle(
 /// - Contiguous f64 slices for bid/ask maximize CPU cache hits
 /// - No String allocations during iteration (timestamps are i64)
 /// - Exit time string conversion only happens on the rare trade-close event
+///
+/// `cancel_flag` is polled every `SUBBAR_CANCEL_CHECK_INTERVAL` ticks;
+/// `None` disables the check.
 pub(crate) fn process_subbars_tick_columnar(
     pos: &mut OpenPosition,
     ticks: &TickColumns,
     start: usize,
     end: usize,
     instrument: &InstrumentConfig,
-) -> Option<(f64, String, CloseReason)> {
+    cancel_flag: Option<&AtomicBool>,
+) -> Result<Option<(f64, String, CloseReason)>, AppError> {
     if start >= end {
-        return None;
+        return Ok(None);
     }
 
-    let pip_size = instrument.pip_size;
     let bids = &ticks.bids[start..end];
     let asks = &ticks.asks[start..end];
     let timestamps = &ticks.timestamps[start..end];
@@ -1077,11 +1563,14 @@ pub(crate) fn process_subbars_tick_columnar(
     if is_long {
         // ── Long: exits at bid ──
         for j in 0..bids.len() {
+            if subbar_cancelled(cancel_flag, j) {
+                return Err(AppError::BacktestCancelled);
+            }
             let bid = bids[j];
 
             // MAE/MFE (inlined — no function call overhead)
-            let adverse = (pos.entry_price - bid) / pip_size;
-            let favorable = (bid - pos.entry_price) / pip_size;
+            let adverse = instrument.price_to_pips(pos.entry_price - bid);
+            let favorable = instrument.price_to_pips(bid - pos.entry_price);
             if adverse > pos.mae_pips { pos.mae_pips = adverse; }
             if favorable > pos.mfe_pips { pos.mfe_pips = favorable; }
 
@@ -1089,13 +1578,13 @@ pub(crate) fn process_subbars_tick_columnar(
             if let Some(sl) = pos.stop_loss {
                 if bid <= sl {
                     let reason = if pos.trailing_stop_activated { CloseReason::TrailingStop } else { CloseReason::StopLoss };
-                    return Some((bid, micros_to_datetime_string(timestamps[j]), reason));
+                    return Ok(Some((bid, micros_to_datetime_string(timestamps[j]), reason)));
                 }
             }
             // TP check (long exits at bid — limit fills at TP level)
             if let Some(tp) = pos.take_profit {
                 if bid >= tp {
-                    return Some((tp, micros_to_datetime_string(timestamps[j]), CloseReason::TakeProfit));
+                    return Ok(Some((tp, micros_to_datetime_string(timestamps[j]), CloseReason::TakeProfit)));
                 }
             }
             // Trailing stop (track highest bid)
@@ -1107,7 +1596,7 @@ pub(crate) fn process_subbars_tick_columnar(
                 };
                 if activated && bid > pos.highest_since_entry {
                     pos.highest_since_entry = bid;
-                    let new_sl = bid - distance;
+                    let new_sl = trailing_stop_level(pos, bid, distance);
                     let moved = match pos.stop_loss {
                         Some(ref mut sl) if new_sl > *sl => { *sl = new_sl; true }
                         None => { pos.stop_loss = Some(new_sl); true }
@@ -1120,11 +1609,14 @@ pub(crate) fn process_subbars_tick_columnar(
     } else {
         // ── Short: exits at ask ──
         for j in 0..asks.len() {
+            if subbar_cancelled(cancel_flag, j) {
+                return Err(AppError::BacktestCancelled);
+            }
             let ask = asks[j];
 
             // MAE/MFE
-            let adverse = (ask - pos.entry_price) / pip_size;
-            let favorable = (pos.entry_price - ask) / pip_size;
+            let adverse = instrument.price_to_pips(ask - pos.entry_price);
+            let favorable = instrument.price_to_pips(pos.entry_price - ask);
             if adverse > pos.mae_pips { pos.mae_pips = adverse; }
             if favorable > pos.mfe_pips { pos.mfe_pips = favorable; }
 
@@ -1132,13 +1624,13 @@ pub(crate) fn process_subbars_tick_columnar(
             if let Some(sl) = pos.stop_loss {
                 if ask >= sl {
                     let reason = if pos.trailing_stop_activated { CloseReason::TrailingStop } else { CloseReason::StopLoss };
-                    return Some((ask, micros_to_datetime_string(timestamps[j]), reason));
+                    return Ok(Some((ask, micros_to_datetime_string(timestamps[j]), reason)));
                 }
             }
             // TP check (short exits at ask — limit fills at TP level)
             if let Some(tp) = pos.take_profit {
                 if ask <= tp {
-                    return Some((tp, micros_to_datetime_string(timestamps[j]), CloseReason::TakeProfit));
+                    return Ok(Some((tp, micros_to_datetime_string(timestamps[j]), CloseReason::TakeProfit)));
                 }
             }
             // Trailing stop (track lowest ask)
@@ -1150,7 +1642,7 @@ pub(crate) fn process_subbars_tick_columnar(
                 };
                 if activated && ask < pos.lowest_since_entry {
                     pos.lowest_since_entry = ask;
-                    let new_sl = ask + distance;
+                    let new_sl = trailing_stop_level(pos, ask, distance);
                     let moved = match pos.stop_loss {
                         Some(ref mut sl) if new_sl < *sl => { *sl = new_sl; true }
                         None => { pos.stop_loss = Some(new_sl); true }
@@ -1161,7 +1653,7 @@ pub(crate) fn process_subbars_tick_columnar(
             }
         }
     }
-    None
+    Ok(None)
 }
 
 // ══════════════════════════════════════════════════════════════
@@ -1169,34 +1661,48 @@ pub(crate) fn process_subbars_tick_columnar(
 // ══════════════════════════════════════════════════════════════
 
 /// Close a position and create a TradeResult.
+///
+/// `embedded_spread` is `None` for a bid-quoted bar price, which still pays the exit
+/// side of the spread here, or `Some(spread)` for a fill already on the executable
+/// side with `spread` (price units) built into it.
 fn close_position(
     pos: &OpenPosition,
     exit_price: f64,
     exit_time: &str,
     exit_bar: usize,
     reason: CloseReason,
+    embedded_spread: Option<f64>,
     instrument: &InstrumentConfig,
     strategy: &Strategy,
     config: &BacktestConfig,
     rng: &mut SmallRng,
-) -> TradeResult {
-    // Apply exit costs (slippage on exit)
-    let adjusted_exit = orders::apply_exit_costs(
-        exit_price,
-        pos.direction,
-        &strategy.trading_costs,
-        instrument,
-        rng,
-    );
-    let pnl = orders::calculate_pnl(pos.direction, pos.entry_price, adjusted_exit, pos.lots, instrument);
+) -> Result<TradeResult, AppError> {
+    // Bid-quoted bar prices still need the exit side of the spread; SL/TP fills
+    // and tick prices are already on the executable side and only slip.
+    let (adjusted_exit, exit_spread) = match embedded_spread {
+        Some(spread) => (
+            orders::apply_exit_slippage_only(exit_price, pos.direction, &strategy.trading_costs, instrument, rng),
+            spread,
+        ),
+        None => (
+            orders::apply_exit_costs(exit_price, pos.direction, &strategy.trading_costs, instrument, rng),
+            orders::spread_price(&strategy.trading_costs, instrument),
+        ),
+    };
+    // Only shorts pay the spread on exit (buying back at ask)
+    let exit_spread = if pos.direction == TradeDirection::Short { exit_spread } else { 0.0 };
+    let exit_slippage = ((adjusted_exit - exit_price).abs() - if embedded_spread.is_none() { exit_spread } else { 0.0 }).max(0.0);
+    let quote_to_account = orders::quote_to_account_rate(config, exit_time)?;
+    let pnl = orders::calculate_pnl(pos.direction, pos.entry_price, adjusted_exit, pos.lots, instrument, quote_to_account);
     let pnl_pips =
         orders::calculate_pnl_pips(pos.direction, pos.entry_price, adjusted_exit, instrument);
-    let commission =
-        orders::calculate_commission(&strategy.trading_costs, pos.lots, pos.entry_price, instrument);
+    let commission = orders::calculate_commission(
+        &strategy.trading_costs, pos.lots, pos.entry_price, instrument, quote_to_account,
+    );
     let duration_bars = exit_bar - pos.entry_bar;
     let mpb = config.timeframe.minutes().max(1);
 
-    TradeResult {
+    Ok(TradeResult {
         id: uuid::Uuid::new_v4().to_string(),
         direction: pos.direction,
         entry_time: pos.entry_time.clone(),
@@ -1208,11 +1714,259 @@ fn close_position(
         pnl_pips,
         commission,
         swap: pos.accumulated_swap,
+        spread_cost: orders::price_distance_value(pos.entry_spread + exit_spread, pos.lots, instrument, quote_to_account),
+        slippage_cost: orders::price_distance_value(pos.entry_slippage + exit_slippage, pos.lots, instrument, quote_to_account),
         close_reason: reason,
         duration_bars,
         duration_time: format_duration_bars(duration_bars, mpb),
         mae: pos.mae_pips,
         mfe: pos.mfe_pips,
+        initial_risk_pips: pos.initial_risk_pips(instrument),
+        entry_reason: pos.entry_reason.clone(),
+        exit_reason: Some(exit_annotation(pos.direction, reason, exit_price, instrument)),
+        regime: None,
+    })
+}
+
+/// Split the adverse move from a `raw` entry price to the `filled` price into
+/// (spread, slippage), in price units. `spread` is the full spread the cost model
+/// added to the fill; only longs pay it on entry (buying at ask).
+fn entry_cost_split(direction: TradeDirection, raw: f64, filled: f64, spread: f64) -> (f64, f64) {
+    let spread = if direction == TradeDirection::Short { 0.0 } else { spread };
+    (spread, ((filled - raw).abs() - spread).max(0.0))
+}
+
+/// Annotation for the rule set behind an entry/exit signal.
+///
+/// Group-based rule sets name the 1-based group(s) that evaluated true (or how many
+/// groups they contain when none is recorded as fired); flat rule lists report how
+/// many rules they contain.
+fn rule_set_annotation(label: &str, groups: &[RuleGroup], fired: &[usize], rule_count: usize) -> String {
+    if groups.is_empty() {
+        return format!("{} rules ({})", label, rule_count);
+    }
+    if fired.is_empty() {
+        return format!("{} groups ({})", label, groups.len());
+    }
+    let ids: Vec<String> = fired.iter().map(|g| (g + 1).to_string()).collect();
+    if ids.len() == 1 {
+        format!("{} group {}", label, ids[0])
+    } else {
+        format!("{} groups {}", label, ids.join("+"))
+    }
+}
+
+/// Default exit annotation derived from the close reason.
+///
+/// `level` is the pre-slippage exit price, i.e. the SL/TP/trailing level that was hit.
+/// Signal exits are refined by the caller with the exit rule set that fired.
+fn exit_annotation(direction: TradeDirection, reason: CloseReason, level: f64, instrument: &InstrumentConfig) -> String {
+    let side = if direction == TradeDirection::Short { "Short" } else { "Long" };
+    let digits = instrument.digits;
+    match reason {
+        CloseReason::Signal => format!("{} exit signal", side),
+        CloseReason::StopLoss => format!("Stop loss hit at {:.*}", digits, level),
+        CloseReason::TakeProfit => format!("Take profit hit at {:.*}", digits, level),
+        CloseReason::TrailingStop => format!("Trailing stop hit at {:.*}", digits, level),
+        CloseReason::EndOfData => "End of data".to_string(),
+        CloseReason::TimeClose => "Time close".to_string(),
+        CloseReason::ExitAfterBars => "Exit after bars".to_string(),
+        CloseReason::MaxHold => "Max hold".to_string(),
+        CloseReason::StopOut => "Equity stop-out".to_string(),
+        CloseReason::SessionClose => "Market on session close".to_string(),
+    }
+}
+
+/// Worst price a position could have been marked at within the current bar: the lowest
+/// bid for longs, the highest ask for shorts, over sub-bars `start..end` — or the bar's
+/// own low/high when there is no sub-bar data.
+fn worst_intrabar_mark(
+    direction: TradeDirection,
+    candle: &Candle,
+    sub_bars: &SubBarData,
+    start: usize,
+    end: usize,
+    spread: f64,
+) -> f64 {
+    let short = direction == TradeDirection::Short;
+    match *sub_bars {
+        SubBarData::Candles(ref subs) if start < end => {
+            let subs = &subs[start..end];
+            if short {
+                subs.iter().map(|c| c.high).fold(f64::MIN, f64::max) + spread
+            } else {
+                subs.iter().map(|c| c.low).fold(f64::MAX, f64::min)
+            }
+        }
+        SubBarData::Ticks(ref ticks) if start < end => {
+            if short {
+                ticks.asks[start..end].iter().copied().fold(f64::MIN, f64::max)
+            } else {
+                ticks.bids[start..end].iter().copied().fold(f64::MAX, f64::min)
+            }
+        }
+        _ if short => candle.high + spread,
+        _ => candle.low,
+    }
+}
+
+/// Curves stored under `BacktestConfig::equity_sampling`, picked while the run progresses.
+///
+/// The stride is fixed from the run length up front: up to half of `max_points` goes to
+/// every `stride`-th bar, the rest to bars where a trade opened or closed (first come,
+/// first served), plus the last bar — so no more than `max_points` points are ever pushed.
+/// Every bar still feeds the metric inputs, so metrics match an unsampled run.
+struct SampledCurves<'a> {
+    candles: &'a [Candle],
+    start_bar: usize,
+    initial_capital: f64,
+    timeframe: Timeframe,
+    stride: usize,
+    event_budget: usize,
+    events_kept: usize,
+    /// Whether the drawdown curve, benchmark and rolling ratios are produced (full backtest only)
+    full_run: bool,
+    stats: EquityStats,
+    /// Benchmark co-moments and the first close the buy-and-hold position was bought at
+    benchmark: Option<(BenchmarkStats, f64)>,
+    /// Rolling Sharpe/Sortino fed every bar, left unannualised: the annualisation depends
+    /// on where the run ends, which a stop-out only decides at the end.
+    rolling: Option<RollingRatios>,
+    benchmark_curve: Vec<EquityPoint>,
+    rolling_ratios: Vec<RollingRatioPoint>,
+    /// Latest bar, held back one step: settling an open position after the loop
+    /// rewrites the last point.
+    pending: Option<SampledPoint>,
+}
+
+struct SampledPoint {
+    bar: usize,
+    equity: f64,
+    drawdown_pct: f64,
+    trade_event: bool,
+}
+
+impl<'a> SampledCurves<'a> {
+    fn new(candles: &'a [Candle], start_bar: usize, max_points: usize, config: &BacktestConfig, full_run: bool) -> Self {
+        let bars = candles.len() - start_bar;
+        let max_points = max_points.max(2);
+        let stride = bars.div_ceil((max_points / 2).max(1)).max(1);
+        let (benchmark, rolling) = match candles.get(start_bar) {
+            Some(first) if full_run => (
+                (first.close > 0.0).then(|| (BenchmarkStats::default(), first.close)),
+                Some(RollingRatios::new((bars / 10).max(20), 1.0)),
+            ),
+            _ => (None, None),
+        };
+        Self {
+            candles,
+            start_bar,
+            initial_capital: config.initial_capital,
+            timeframe: config.timeframe,
+            stride,
+            event_budget: max_points.saturating_sub(bars.div_ceil(stride) + 1),
+            events_kept: 0,
+            full_run,
+            stats: EquityStats::default(),
+            benchmark,
+            rolling,
+            benchmark_curve: Vec::new(),
+            rolling_ratios: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Record bar `bar`, storing the previous one if it is sampled.
+    fn record(
+        &mut self,
+        bar: usize,
+        equity: f64,
+        drawdown_pct: f64,
+        trade_event: bool,
+        equity_curve: &mut Vec<EquityPoint>,
+        drawdown_curve: &mut Vec<DrawdownPoint>,
+    ) {
+        let point = SampledPoint { bar, equity, drawdown_pct, trade_event };
+        if let Some(prev) = self.pending.replace(point) {
+            self.flush(prev, false, equity_curve, drawdown_curve);
+        }
+    }
+
+    /// Store the held-back bar now, for a replay step. Only valid before the last bar.
+    fn flush_pending(&mut self, equity_curve: &mut Vec<EquityPoint>, drawdown_curve: &mut Vec<DrawdownPoint>) {
+        if let Some(point) = self.pending.take() {
+            self.flush(point, false, equity_curve, drawdown_curve);
+        }
+    }
+
+    /// Overwrite the held-back last bar with the settled equity.
+    fn settle_last(&mut self, equity: f64, drawdown_pct: f64) {
+        if let Some(ref mut last) = self.pending {
+            last.equity = equity;
+            last.drawdown_pct = drawdown_pct;
+        }
+    }
+
+    /// Store the last bar, which is always kept, then annualise the rolling ratios over
+    /// `start_bar..=last_bar` the way an unsampled run does.
+    fn finish(&mut self, last_bar: usize, equity_curve: &mut Vec<EquityPoint>, drawdown_curve: &mut Vec<DrawdownPoint>) {
+        if let Some(last) = self.pending.take() {
+            self.flush(last, true, equity_curve, drawdown_curve);
+        }
+        if self.rolling.is_none() {
+            return;
+        }
+        let scale = rolling_ratio_scale(rolling_periods_per_year(
+            &self.candles[self.start_bar].datetime,
+            &self.candles[last_bar].datetime,
+            last_bar + 1 - self.start_bar,
+            self.timeframe,
+        ));
+        for point in &mut self.rolling_ratios {
+            point.sharpe = point.sharpe.map(|r| r * scale);
+            point.sortino = point.sortino.map(|r| r * scale);
+        }
+    }
+
+    fn flush(
+        &mut self,
+        point: SampledPoint,
+        is_last: bool,
+        equity_curve: &mut Vec<EquityPoint>,
+        drawdown_curve: &mut Vec<DrawdownPoint>,
+    ) {
+        let candle = &self.candles[point.bar];
+        self.stats.push(&candle.datetime, point.equity);
+        let initial_capital = self.initial_capital;
+        let benchmark = self.benchmark.as_mut().map(|(stats, first_close)| {
+            let held = initial_capital * candle.close / *first_close;
+            stats.push(point.equity, held);
+            held
+        });
+        let ratios = self.rolling.as_mut().map(|rolling| rolling.push(point.equity));
+
+        let idx = point.bar - self.start_bar;
+        let keep = if is_last || idx % self.stride == 0 {
+            true
+        } else if point.trade_event && self.events_kept < self.event_budget {
+            self.events_kept += 1;
+            true
+        } else {
+            false
+        };
+        if !keep {
+            return;
+        }
+        equity_curve.push(EquityPoint { timestamp: candle.datetime.clone(), equity: point.equity });
+        if self.full_run {
+            drawdown_curve.push(DrawdownPoint { timestamp: candle.datetime.clone(), drawdown_pct: point.drawdown_pct });
+        }
+        if let Some(equity) = benchmark {
+            self.benchmark_curve.push(EquityPoint { timestamp: candle.datetime.clone(), equity });
+        }
+        if let Some((sharpe, sortino)) = ratios {
+            self.rolling_ratios.push(RollingRatioPoint { timestamp: candle.datetime.clone(), sharpe, sortino });
+        }
     }
 }
 
@@ -1247,7 +2001,7 @@ fn compute_atr_if_needed(strategy: &Strategy, candles: &[Candle]) -> AtrValues {
             strategy
                 .stop_loss
                 .as_ref()
-                .filter(|sl| matches!(sl.sl_type, crate::models::strategy::StopLossType::ATR))
+                .filter(|sl| sl.sl_type.uses_atr())
                 .and_then(|sl| sl.atr_period),
             candles,
         ),
@@ -1270,10 +2024,42 @@ fn compute_atr_if_needed(strategy: &Strategy, candles: &[Candle]) -> AtrValues {
     }
 }
 
+/// Transform raw OHLC candles into Heikin-Ashi candles.
+///
+/// HA close = (O+H+L+C)/4, HA open = midpoint of the previous HA body (seeded with
+/// (O+C)/2 on the first bar), HA high/low = extremes of the raw range and the HA body.
+/// Timestamps and volume are copied unchanged so the series stays index-aligned
+/// with the raw candles.
+pub fn heikin_ashi_candles(candles: &[Candle]) -> Vec<Candle> {
+    let mut out: Vec<Candle> = Vec::with_capacity(candles.len());
+    for (i, c) in candles.iter().enumerate() {
+        let ha_close = (c.open + c.high + c.low + c.close) / 4.0;
+        let ha_open = if i == 0 {
+            (c.open + c.close) / 2.0
+        } else {
+            (out[i - 1].open + out[i - 1].close) / 2.0
+        };
+        out.push(Candle {
+            timestamp: c.timestamp,
+            datetime: c.datetime.clone(),
+            open: ha_open,
+            high: c.high.max(ha_open).max(ha_close),
+            low: c.low.min(ha_open).min(ha_close),
+            close: ha_close,
+            volume: c.volume,
+            open_interest: c.open_interest,
+            avg_spread: c.avg_spread,
+        });
+    }
+    out
+}
+
 /// Pre-compute the primary output values of the order-price indicator, if configured.
-fn compute_order_price_indicator(strategy: &Strategy, candles: &[Candle]) -> Option<Vec<f64>> {
+/// Daily indicators (pivots) roll over at `session_start_hour`, like the rule indicators.
+fn compute_order_price_indicator(strategy: &Strategy, candles: &[Candle], session_start_hour: u8) -> Option<Vec<f64>> {
     let opi = strategy.entry_order_indicator.as_ref()?;
-    match super::indicators::compute_indicator(&opi.indicator, candles) {
+    let slices = super::indicators::CandleSlices::from_candles(candles).with_session_start_hour(session_start_hour);
+    match super::indicators::compute_indicator_with_slices(&opi.indicator, &slices, candles) {
         Ok(output) => Some(output.primary),
         Err(_) => None,
     }
@@ -1297,6 +2083,52 @@ fn compute_atr_for_period(period: Option<usize>, candles: &[Candle]) -> Option<V
     }
 }
 
+/// Classify every bar as trending or ranging. Warm-up bars (and every bar when there is
+/// not enough data for the indicator) are `None`.
+pub fn regime_series(candles: &[Candle], regime: &RegimeConfig) -> Result<Vec<Option<MarketRegime>>, AppError> {
+    let indicator_type = match regime.indicator {
+        RegimeIndicator::ADX => IndicatorType::ADX,
+        RegimeIndicator::Choppiness => IndicatorType::Choppiness,
+    };
+    let config = IndicatorConfig {
+        indicator_type,
+        params: crate::models::strategy::IndicatorParams {
+            period: Some(regime.period),
+            ..Default::default()
+        },
+        output_field: None,
+        cached_hash: 0,
+    };
+    let values = match super::indicators::compute_indicator(&config, candles) {
+        Ok(output) => output.primary,
+        Err(AppError::InsufficientData { .. }) => return Ok(vec![None; candles.len()]),
+        Err(e) => return Err(e),
+    };
+    Ok(values
+        .iter()
+        .map(|&v| {
+            if !v.is_finite() {
+                return None;
+            }
+            let trending = match regime.indicator {
+                RegimeIndicator::ADX => v >= regime.threshold,
+                RegimeIndicator::Choppiness => v < regime.threshold,
+            };
+            Some(if trending { MarketRegime::Trending } else { MarketRegime::Ranging })
+        })
+        .collect())
+}
+
+/// Tag each trade with the regime of the last closed bar before its entry, so the label
+/// only uses information available when the trade was opened.
+fn tag_trade_regimes(trades: &mut [TradeResult], candles: &[Candle], regimes: &[Option<MarketRegime>]) {
+    for trade in trades.iter_mut() {
+        // Number of bars opened at or before the entry; the entry bar is the last of them.
+        let bars_to_entry = candles.partition_point(|c| c.datetime.as_str() <= trade.entry_time.as_str());
+        trade.regime = bars_to_entry.checked_sub(2).and_then(|i| regimes[i]);
+    }
+}
+
 /// Cache-aware ATR computation.  Checks the shared `IndicatorCache` first;
 /// on a miss, computes the ATR, stores it in the cache, then returns a clone of
 /// the primary Vec.  The clone is a fast memcpy; the computation (EMA-based ATR
@@ -1377,6 +2209,34 @@ fn should_close_at_time(close_at: &Option<CloseTradesAt>, datetime: &str) -> boo
     }
 }
 
+/// Entry signals suppressed by `TradingCosts::max_spread_pips`, counted once per bar.
+#[derive(Default)]
+struct SpreadSkips {
+    count: usize,
+    /// Time of the first bar with a suppressed entry.
+    first: Option<String>,
+    last_bar: Option<usize>,
+}
+
+impl SpreadSkips {
+    fn record(&mut self, bar: usize, datetime: &str) {
+        if self.last_bar == Some(bar) {
+            return;
+        }
+        self.last_bar = Some(bar);
+        self.count += 1;
+        self.first.get_or_insert_with(|| datetime.to_string());
+    }
+}
+
+/// True when `candles[i]` is the final bar of its session, i.e. the next bar starts a
+/// new session day. The last bar of the data is left to the end-of-data close.
+fn is_last_bar_of_session(candles: &[Candle], i: usize, session_start_hour: u8) -> bool {
+    candles
+        .get(i + 1)
+        .is_some_and(|next| next.session_day(session_start_hour) != candles[i].session_day(session_start_hour))
+}
+
 /// Check if a given hour:minute is within the configured trading hours window.
 /// Handles ranges that cross midnight (e.g. 22:00 → 06:00).
 fn is_within_trading_hours(hours: &TradingHours, h: u8, m: u8) -> bool {
@@ -1546,6 +2406,8 @@ pub fn candles_from_dataframe(df: &DataFrame) -> Result<Vec<Candle>, AppError> {
     let low_v = chunked_f64_to_vec(low_ca);
     let close_v = chunked_f64_to_vec(close_ca);
     let volume_v = chunked_f64_to_vec(volume_ca);
+    let open_interest_v = optional_f64_column(df, "open_interest")?;
+    let avg_spread_v = optional_f64_column(df, "avg_spread")?;
 
     let mut candles = Vec::with_capacity(len);
     for i in 0..len {
@@ -1557,12 +2419,23 @@ pub fn candles_from_dataframe(df: &DataFrame) -> Result<Vec<Candle>, AppError> {
             low: low_v[i],
             close: close_v[i],
             volume: volume_v[i],
+            open_interest: open_interest_v.as_ref().and_then(|v| v[i]),
+            avg_spread: avg_spread_v.as_ref().and_then(|v| v[i]),
         });
     }
 
     Ok(candles)
 }
 
+/// Values of an optional f64 column, or `None` when the DataFrame doesn't have it.
+fn optional_f64_column(df: &DataFrame, name: &str) -> Result<Option<Vec<Option<f64>>>, AppError> {
+    let Ok(column) = df.column(name) else {
+        return Ok(None);
+    };
+    let ca = column.f64().map_err(|e| AppError::Internal(format!("{} column: {}", name, e)))?;
+    Ok(Some(ca.into_iter().collect()))
+}
+
 /// Convert a raw tick Parquet DataFrame (datetime, bid, ask) to TickColumns (SoA).
 /// Extracts i64 timestamps directly from Polars — NO per-tick String allocations.
 pub fn tick_columns_from_dataframe(df: &DataFrame) -> Result<TickColumns, AppError> {
@@ -1817,3 +2690,1398 @@ pub fn tick_columns_from_binary_dir(
 
     Ok(TickColumns { timestamps, bids, asks })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::strategy::*;
+
+    /// Bars with a long upper body: raw close 101 sits above 100, but the HA
+    /// close (bar average) is 98 and stays below it.
+    fn make_candles(n: usize) -> Vec<Candle> {
+        (0..n)
+            .map(|i| Candle {
+                datetime: format!("2024-01-02 {:02}:00", i),
+                open: 95.0,
+                high: 102.0,
+                low: 94.0,
+                close: 101.0,
+                volume: 1000.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn close_above(value: f64) -> Vec<Rule> {
+        vec![Rule {
+            id: "r1".to_string(),
+            left_operand: Operand {
+                operand_type: OperandType::Price,
+                indicator: None,
+                price_field: Some(PriceField::Close),
+                constant_value: None,
+                time_field: None,
+                candle_pattern: None,
+                offset: None,
+                compound_left: None,
+                compound_op: None,
+                compound_right: None,
+                percent_of: None,
+                transform: None,
+            },
+            comparator: Comparator::GreaterThan,
+            right_operand: Operand {
+                operand_type: OperandType::Constant,
+                indicator: None,
+                price_field: None,
+                constant_value: Some(value),
+                time_field: None,
+                candle_pattern: None,
+                offset: None,
+                compound_left: None,
+                compound_op: None,
+                compound_right: None,
+                percent_of: None,
+                transform: None,
+            },
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }]
+    }
+
+    fn make_strategy(long_entry_rules: Vec<Rule>) -> Strategy {
+        Strategy {
+            id: "s1".to_string(),
+            name: "test".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            long_entry_rules,
+            short_entry_rules: vec![],
+            long_exit_rules: vec![],
+            short_exit_rules: vec![],
+            long_entry_groups: vec![],
+            short_entry_groups: vec![],
+            long_exit_groups: vec![],
+            short_exit_groups: vec![],
+            position_sizing: PositionSizing {
+                sizing_type: PositionSizingType::FixedLots,
+                value: 1.0,
+                decrease_factor: 0.9,
+            },
+            stop_loss: None,
+            take_profit: None,
+            trailing_stop: None,
+            trading_costs: TradingCosts {
+                spread_pips: 0.0,
+                commission_type: CommissionType::FixedPerLot,
+                commission_value: 0.0,
+                slippage_pips: 0.0,
+                slippage_random: false,
+                max_spread_pips: None,
+            },
+            trade_direction: TradeDirection::Long,
+            trading_hours: None,
+            max_daily_trades: None,
+            close_trades_at: None,
+            entry_order: OrderType::Market,
+            entry_order_offset_pips: 0.0,
+            close_after_bars: None,
+            min_hold_bars: None,
+            max_hold_bars: None,
+            move_sl_to_be: false,
+            close_on_opposite_signal: false,
+            reverse_on_opposite_signal: false,
+            session_exit: None,
+            entry_order_indicator: None,
+        }
+    }
+
+    fn make_config(use_heikin_ashi: bool) -> BacktestConfig {
+        BacktestConfig {
+            symbol_id: "sym".to_string(),
+            timeframe: Timeframe::H1,
+            start_date: "2024-01-01".to_string(),
+            end_date: "2024-12-31".to_string(),
+            initial_capital: 10_000.0,
+            leverage: 1.0,
+            precision: BacktestPrecision::SelectedTfOnly,
+            early_stop_no_trades_pct: None,
+            pending_order_expiry_bars: None,
+            use_heikin_ashi,
+            equity_sampling: None,
+            tick_chunk_bars: None,
+            compounding: true,
+            stop_out_equity: None,
+            magnifier_timeframe: None,
+            account_currency: None,
+            quote_conversion: None,
+            warmup_bars: None,
+            exit_priority: ExitPriority::default(),
+            fallback_on_missing_raw: false,
+            drawdown_basis: DrawdownBasis::default(),
+            overtrading_threshold: None,
+            regime: None,
+            slippage_seed: None,
+        }
+    }
+
+    fn run(candles: &[Candle], strategy: &Strategy, config: &BacktestConfig) -> BacktestResults {
+        let cancel = AtomicBool::new(false);
+        run_backtest(
+            candles, &SubBarData::None, strategy, config,
+            &InstrumentConfig::default(), &cancel, |_, _, _| {},
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stepped_backtest_emits_a_snapshot_per_interval() {
+        let candles = make_candles(23);
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.close_after_bars = Some(2);
+        let config = make_config(false);
+        let steps = std::cell::RefCell::new(Vec::new());
+        let observer = StepObserver { interval: 5, on_step: &|s| steps.borrow_mut().push(s) };
+        let results = run_backtest_stepped(
+            &candles, &SubBarData::None, &strategy, &config,
+            &InstrumentConfig::default(), &AtomicBool::new(false), &observer,
+        )
+        .unwrap();
+        let steps = steps.into_inner();
+
+        // 22 bars run after the first: four full 5-bar steps, then a final step with
+        // the last 2 bars and the end-of-data close
+        assert_eq!(results.equity_curve.len(), 22);
+        assert_eq!(steps.len(), 5);
+        let bars: Vec<usize> = steps.iter().map(|s| s.bar_index).collect();
+        assert_eq!(bars, vec![5, 10, 15, 20, 22]);
+        assert!(steps[..4].iter().all(|s| s.equity_points.len() == 5));
+        assert_eq!(steps[4].equity_points.len(), 2);
+        assert!(!steps[4].in_position);
+
+        // Steps partition the run: together they hold every equity point and trade
+        let streamed: Vec<&str> = steps.iter().flat_map(|s| &s.new_trades).map(|t| t.id.as_str()).collect();
+        let final_ids: Vec<&str> = results.trades.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(streamed, final_ids);
+        assert_eq!(steps.iter().map(|s| s.drawdown_points.len()).sum::<usize>(), 22);
+    }
+
+    #[test]
+    fn test_stepped_backtest_streams_the_settled_last_point() {
+        // 20 bars at interval 5: a step falls on the last bar, with the position still open
+        let candles = make_candles(21);
+        let strategy = make_strategy(close_above(100.0));
+        for equity_sampling in [None, Some(100)] {
+            let config = BacktestConfig { equity_sampling, ..make_config(false) };
+            let steps = std::cell::RefCell::new(Vec::new());
+            let observer = StepObserver { interval: 5, on_step: &|s| steps.borrow_mut().push(s) };
+            let results = run_backtest_stepped(
+                &candles, &SubBarData::None, &strategy, &config,
+                &InstrumentConfig::default(), &AtomicBool::new(false), &observer,
+            )
+            .unwrap();
+            let steps = steps.into_inner();
+
+            assert_eq!(results.trades.last().unwrap().close_reason, CloseReason::EndOfData);
+            let last_step = steps.last().unwrap();
+            assert_eq!(last_step.bar_index, 20);
+            assert!(!last_step.in_position);
+            assert_eq!(last_step.new_trades.len(), 1);
+            let streamed = last_step.equity_points.last().unwrap();
+            let stored = results.equity_curve.last().unwrap();
+            assert_eq!(streamed.timestamp, stored.timestamp);
+            assert_eq!(streamed.equity, stored.equity);
+            assert_eq!(
+                last_step.drawdown_points.last().unwrap().drawdown_pct,
+                results.drawdown_curve.last().unwrap().drawdown_pct,
+            );
+        }
+    }
+
+    #[test]
+    fn test_stepped_backtest_cancels_between_steps() {
+        let candles = make_candles(23);
+        let strategy = make_strategy(close_above(100.0));
+        let cancel = AtomicBool::new(false);
+        let steps = std::cell::Cell::new(0);
+        let on_step = |_: BacktestStep| {
+            steps.set(steps.get() + 1);
+            if steps.get() == 2 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+        let observer = StepObserver { interval: 5, on_step: &on_step };
+        let result = run_backtest_stepped(
+            &candles, &SubBarData::None, &strategy, &make_config(false),
+            &InstrumentConfig::default(), &cancel, &observer,
+        );
+        assert!(matches!(result, Err(AppError::BacktestCancelled)));
+        assert_eq!(steps.get(), 2);
+
+        let zero = StepObserver { interval: 0, on_step: &|_| {} };
+        let result = run_backtest_stepped(
+            &candles, &SubBarData::None, &strategy, &make_config(false),
+            &InstrumentConfig::default(), &AtomicBool::new(false), &zero,
+        );
+        assert!(matches!(result, Err(AppError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_trades_in_high_adx_period_are_tagged_trending() {
+        // 60 choppy bars alternating between 100 and 101, then 60 bars of a steady climb.
+        let candles: Vec<Candle> = (0..120)
+            .map(|i| {
+                let close = if i < 60 { 100.0 + (i % 2) as f64 } else { 101.0 + (i - 59) as f64 };
+                Candle {
+                    datetime: format!("2024-01-{:02} {:02}:00", i / 24 + 1, i % 24),
+                    open: close - 0.2,
+                    high: close + 0.5,
+                    low: close - 0.5,
+                    close,
+                    volume: 1000.0,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.close_after_bars = Some(1);
+        let mut config = make_config(false);
+        config.regime = Some(RegimeConfig { indicator: RegimeIndicator::ADX, period: 14, threshold: 25.0 });
+
+        let results = run(&candles, &strategy, &config);
+        let regime_at = |time: &str| results.trades.iter().find(|t| t.entry_time.as_str() >= time).unwrap().regime;
+        assert_eq!(results.trades[0].regime, None, "ADX is still warming up");
+        assert_eq!(regime_at("2024-01-03 00:00"), Some(MarketRegime::Ranging));
+        assert_eq!(regime_at("2024-01-05 12:00"), Some(MarketRegime::Trending));
+        assert_eq!(results.trades.last().unwrap().regime, Some(MarketRegime::Trending));
+
+        let m = &results.metrics;
+        let tagged = results.trades.iter().filter(|t| t.regime.is_some()).count();
+        assert_eq!(m.trending_trades + m.ranging_trades, tagged);
+        assert!(m.trending_trades > 0 && m.ranging_trades > 0);
+        let trending_pnl: f64 = results.trades.iter()
+            .filter(|t| t.regime == Some(MarketRegime::Trending))
+            .map(|t| t.pnl - t.commission + t.swap)
+            .sum();
+        assert!((m.trending_net_profit - trending_pnl).abs() < 1e-9);
+        // Every trending trade rides the climb
+        assert!((m.trending_win_rate_pct - 100.0).abs() < 1e-9);
+
+        // Without a regime config nothing is tagged
+        let untagged = run(&candles, &strategy, &make_config(false));
+        assert!(untagged.trades.iter().all(|t| t.regime.is_none()));
+        assert_eq!(untagged.metrics.trending_trades + untagged.metrics.ranging_trades, 0);
+    }
+
+    fn open_long(entry_price: f64) -> OpenPosition {
+        OpenPosition {
+            direction: TradeDirection::Long,
+            entry_price,
+            entry_bar: 0,
+            entry_time: String::new(),
+            lots: 1.0,
+            stop_loss: None,
+            initial_stop_loss: None,
+            take_profit: None,
+            trailing_stop_distance: None,
+            highest_since_entry: entry_price,
+            lowest_since_entry: entry_price,
+            mae_pips: 0.0,
+            mfe_pips: 0.0,
+            trailing_stop_activated: false,
+            last_swap_date: String::new(),
+            accumulated_swap: 0.0,
+            entry_spread: 0.0,
+            entry_slippage: 0.0,
+            sl_moved_to_be: false,
+            trailing_activation_dist: None,
+            trailing_step: None,
+            entry_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_subbar_tick_loop_honours_cancel() {
+        // Steadily falling bids with no SL/TP: the loop would otherwise walk every tick
+        let n = SUBBAR_CANCEL_CHECK_INTERVAL * 3;
+        let ticks = TickColumns {
+            timestamps: (0..n as i64).collect(),
+            bids: (0..n).map(|j| 1.0 - j as f64 * 1e-7).collect(),
+            asks: (0..n).map(|j| 1.0001 - j as f64 * 1e-7).collect(),
+        };
+        let instrument = InstrumentConfig::default();
+
+        let cancel = AtomicBool::new(true);
+        let mut pos = open_long(1.0);
+        let result = process_subbars_tick_columnar(&mut pos, &ticks, 0, n, &instrument, Some(&cancel));
+        assert!(matches!(result, Err(AppError::BacktestCancelled)));
+        // Stopped at the first check point: MAE reflects only the ticks before it
+        let expected_mae = (SUBBAR_CANCEL_CHECK_INTERVAL - 1) as f64 * 1e-3;
+        assert!((pos.mae_pips - expected_mae).abs() < 1e-6, "mae {}", pos.mae_pips);
+
+        // Without a cancel request (or without a flag) every tick is processed
+        let cancel = AtomicBool::new(false);
+        let mut pos = open_long(1.0);
+        let result = process_subbars_tick_columnar(&mut pos, &ticks, 0, n, &instrument, Some(&cancel));
+        assert!(matches!(result, Ok(None)));
+        assert!((pos.mae_pips - (n - 1) as f64 * 1e-3).abs() < 1e-6);
+        let mut pos = open_long(1.0);
+        assert!(matches!(process_subbars_tick_columnar(&mut pos, &ticks, 0, n, &instrument, None), Ok(None)));
+    }
+
+    #[test]
+    fn test_trailing_stop_waits_for_activation() {
+        // 10-pip trail that only starts once the position is 20 pips in profit
+        let mut pos = open_long(1.1000);
+        pos.stop_loss = Some(1.0950);
+        pos.trailing_stop_distance = Some(0.0010);
+        pos.trailing_activation_dist = Some(0.0020);
+        let bar = |high: f64| orders::BidAskOhlc::from_candle(
+            &Candle { open: 1.1000, high, low: 1.0995, close: high, ..Default::default() },
+            0.0,
+        );
+
+        update_trailing_stop(&mut pos, &bar(1.1015));
+        assert_eq!(pos.stop_loss, Some(1.0950));
+        assert!(!pos.trailing_stop_activated);
+
+        update_trailing_stop(&mut pos, &bar(1.1025));
+        assert!((pos.stop_loss.unwrap() - 1.1015).abs() < 1e-9);
+        assert!(pos.trailing_stop_activated);
+
+        update_trailing_stop(&mut pos, &bar(1.1040));
+        assert!((pos.stop_loss.unwrap() - 1.1030).abs() < 1e-9);
+
+        // Tick sub-bars: no movement below the threshold, then trails tick by tick
+        let instrument = InstrumentConfig::default();
+        let bids = vec![1.1010, 1.1019, 1.1022, 1.1030];
+        let ticks = TickColumns {
+            timestamps: (0..bids.len() as i64).collect(),
+            asks: bids.iter().map(|b| b + 0.0001).collect(),
+            bids,
+        };
+        let mut pos = open_long(1.1000);
+        pos.stop_loss = Some(1.0950);
+        pos.trailing_stop_distance = Some(0.0010);
+        pos.trailing_activation_dist = Some(0.0020);
+        let result = process_subbars_tick_columnar(&mut pos, &ticks, 0, 2, &instrument, None);
+        assert!(matches!(result, Ok(None)));
+        assert_eq!(pos.stop_loss, Some(1.0950));
+        let result = process_subbars_tick_columnar(&mut pos, &ticks, 2, 4, &instrument, None);
+        assert!(matches!(result, Ok(None)));
+        assert!((pos.stop_loss.unwrap() - 1.1020).abs() < 1e-9);
+        assert!(pos.trailing_stop_activated);
+    }
+
+    #[test]
+    fn test_chande_kroll_stop_tightens_as_volatility_falls() {
+        // Flat market around 1.1000: 20 wide bars (±50 pips) then 20 narrow ones (±5 pips)
+        let candles: Vec<Candle> = (0..40)
+            .map(|i| {
+                let half_range = if i < 20 { 0.0050 } else { 0.0005 };
+                Candle { open: 1.1000, high: 1.1000 + half_range, low: 1.1000 - half_range, close: 1.1000, ..Default::default() }
+            })
+            .collect();
+        let atr = compute_atr_for_period(Some(5), &candles).unwrap();
+        let levels = chande_kroll_levels(&candles, &atr, 5, 2.0);
+
+        let mut long = open_long(1.1000);
+        long.stop_loss = Some(1.1000 - 2.0 * atr[19]);
+        let mut short = open_long(1.1000);
+        short.direction = TradeDirection::Short;
+        short.stop_loss = Some(1.1000 + 2.0 * atr[19]);
+
+        let mut long_stops = Vec::new();
+        let mut short_stops = Vec::new();
+        for i in 20..40 {
+            update_chande_kroll_stop(&mut long, &levels, i);
+            update_chande_kroll_stop(&mut short, &levels, i);
+            long_stops.push(long.stop_loss.unwrap());
+            short_stops.push(short.stop_loss.unwrap());
+        }
+
+        // The stop only ever tightens, and ends far closer than it started
+        assert!(long_stops.windows(2).all(|w| w[1] >= w[0]));
+        assert!(short_stops.windows(2).all(|w| w[1] <= w[0]));
+        assert!(long_stops[19] - long_stops[0] > 0.0050, "{:?}", long_stops);
+        assert!(short_stops[0] - short_stops[19] > 0.0050, "{:?}", short_stops);
+        // Still on the losing side of a flat market, and flagged as trailing
+        assert!(long_stops[19] < 1.1000 && short_stops[19] > 1.1000);
+        assert!(long.trailing_stop_activated && short.trailing_stop_activated);
+    }
+
+    /// Long above 100, short below it; the single close at 99 (bar 3) is the opposite signal.
+    fn opposite_signal_run(close_on_opposite: bool, reverse_on_opposite: bool) -> Vec<TradeResult> {
+        let candles: Vec<Candle> = [101.0, 101.0, 101.0, 99.0, 101.0, 101.0, 101.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle {
+                datetime: format!("2024-01-02 {:02}:00", i),
+                open: 100.0,
+                high: 102.0,
+                low: 98.0,
+                close,
+                ..Default::default()
+            })
+            .collect();
+        let mut strategy = make_strategy(close_above(100.0));
+        let mut below = close_above(100.0);
+        below[0].comparator = Comparator::LessThan;
+        strategy.short_entry_rules = below;
+        strategy.trade_direction = TradeDirection::Both;
+        strategy.close_on_opposite_signal = close_on_opposite;
+        strategy.reverse_on_opposite_signal = reverse_on_opposite;
+        run(&candles, &strategy, &make_config(false)).trades
+    }
+
+    #[test]
+    fn test_opposite_signal_closes_or_reverses() {
+        // Default: the short signal is ignored while long
+        let ignored = opposite_signal_run(false, false);
+        assert_eq!(ignored.len(), 1);
+
+        // Close-only: the long closes at bar 4 and nothing opens until the next long signal
+        let closed = opposite_signal_run(true, false);
+        let dirs: Vec<TradeDirection> = closed.iter().map(|t| t.direction).collect();
+        assert_eq!(dirs, vec![TradeDirection::Long, TradeDirection::Long]);
+        assert_eq!(closed[0].close_reason, CloseReason::Signal);
+        assert_eq!(closed[0].exit_time, "2024-01-02 04:00");
+        assert!(closed[0].exit_reason.as_deref().unwrap().starts_with("Opposite short entry"));
+        assert_eq!(closed[1].entry_time, "2024-01-02 05:00");
+
+        // Reverse: long → short at bar 4, then the long signal reverses back at bar 5
+        let reversed = opposite_signal_run(false, true);
+        let dirs: Vec<TradeDirection> = reversed.iter().map(|t| t.direction).collect();
+        assert_eq!(dirs, vec![TradeDirection::Long, TradeDirection::Short, TradeDirection::Long]);
+        assert_eq!(reversed[1].entry_time, "2024-01-02 04:00");
+        assert_eq!(reversed[1].exit_time, "2024-01-02 05:00");
+        assert_eq!(reversed[2].entry_time, "2024-01-02 05:00");
+    }
+    #[test]
+    fn test_step_trailing_stop_moves_in_steps() {
+        // 10-pip trail stepping every 10 pips of profit: 1.0990 until +10, then 1.1000 until +20
+        let mut pos = open_long(1.1000);
+        pos.stop_loss = Some(1.0950);
+        pos.trailing_stop_distance = Some(0.0010);
+        pos.trailing_step = Some(0.0010);
+        let bar = |high: f64| orders::BidAskOhlc::from_candle(
+            &Candle { open: 1.1000, high, low: 1.0995, close: high, ..Default::default() },
+            0.0,
+        );
+        for (high, expected) in [(1.1005, 1.0990), (1.1012, 1.1000), (1.1019, 1.1000), (1.1021, 1.1010)] {
+            update_trailing_stop(&mut pos, &bar(high));
+            assert!((pos.stop_loss.unwrap() - expected).abs() < 1e-9, "high {high}: {:?}", pos.stop_loss);
+        }
+
+        // Same jumps tick by tick; short side mirrors them
+        let instrument = InstrumentConfig::default();
+        let bids = vec![1.1005, 1.1012, 1.1019, 1.1021];
+        let ticks = TickColumns {
+            timestamps: (0..bids.len() as i64).collect(),
+            asks: bids.iter().map(|b| b + 0.0001).collect(),
+            bids,
+        };
+        let mut pos = open_long(1.1000);
+        pos.stop_loss = Some(1.0950);
+        pos.trailing_stop_distance = Some(0.0010);
+        pos.trailing_step = Some(0.0010);
+        for (i, expected) in [1.0990, 1.1000, 1.1000, 1.1010].into_iter().enumerate() {
+            let result = process_subbars_tick_columnar(&mut pos, &ticks, i, i + 1, &instrument, None);
+            assert!(matches!(result, Ok(None)));
+            assert!((pos.stop_loss.unwrap() - expected).abs() < 1e-9, "tick {i}: {:?}", pos.stop_loss);
+        }
+
+        let mut pos = open_long(1.1000);
+        pos.direction = TradeDirection::Short;
+        pos.lowest_since_entry = 1.1000;
+        pos.stop_loss = Some(1.1050);
+        pos.trailing_stop_distance = Some(0.0010);
+        pos.trailing_step = Some(0.0010);
+        crate::engine::position::update_trailing_stop_tick(&mut pos, 1.0984, 1.0985);
+        assert!((pos.stop_loss.unwrap() - 1.1000).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_subbar_candle_loop_honours_cancel() {
+        let n = SUBBAR_CANCEL_CHECK_INTERVAL + 10;
+        let subs: Vec<Candle> = (0..n)
+            .map(|_| Candle { open: 1.0, high: 1.0001, low: 0.9999, close: 1.0, ..Default::default() })
+            .collect();
+        let instrument = InstrumentConfig::default();
+        let cancel = AtomicBool::new(true);
+        let mut pos = open_long(1.0);
+        let result = process_subbars_candle(&mut pos, &subs, 0, n, &instrument, 0.0, Some(&cancel));
+        assert!(matches!(result, Err(AppError::BacktestCancelled)));
+        // A range shorter than the interval never polls the flag
+        let result = process_subbars_candle(&mut pos, &subs, 0, 100, &instrument, 0.0, Some(&cancel));
+        assert!(matches!(result, Ok(None)));
+    }
+
+    #[test]
+    fn test_heikin_ashi_candles() {
+        let ha = heikin_ashi_candles(&make_candles(3));
+        // Bar 0: open = (95+101)/2 = 98, close = (95+102+94+101)/4 = 98
+        assert!((ha[0].open - 98.0).abs() < 1e-10);
+        assert!((ha[0].close - 98.0).abs() < 1e-10);
+        // Bar 1: open = midpoint of previous HA body
+        assert!((ha[1].open - 98.0).abs() < 1e-10);
+        assert!((ha[1].high - 102.0).abs() < 1e-10);
+        assert!((ha[1].low - 94.0).abs() < 1e-10);
+        assert_eq!(ha[2].datetime, "2024-01-02 02:00");
+    }
+
+    #[test]
+    fn test_heikin_ashi_signals_use_ha_values() {
+        let candles = make_candles(10);
+        let strategy = make_strategy(close_above(100.0));
+
+        // Raw close 101 > 100 → entry fires
+        let raw = run(&candles, &strategy, &make_config(false));
+        assert_eq!(raw.trades.len(), 1);
+
+        // HA close 98 < 100 → no entry
+        let ha = run(&candles, &strategy, &make_config(true));
+        assert!(ha.trades.is_empty());
+    }
+
+    #[test]
+    fn test_heikin_ashi_fills_use_raw_prices() {
+        let mut candles = make_candles(10);
+        let last = candles.len() - 1;
+        candles[last].close = 99.5;
+        // HA close 98 > 97 → entry on bar 1
+        let strategy = make_strategy(close_above(97.0));
+
+        let result = run(&candles, &strategy, &make_config(true));
+        assert_eq!(result.trades.len(), 1);
+        let trade = &result.trades[0];
+        // Entry at raw open of bar 1, not the HA open (98)
+        assert!((trade.entry_price - 95.0).abs() < 1e-10);
+        // End-of-data exit at raw close, not the HA close
+        assert_eq!(trade.close_reason, CloseReason::EndOfData);
+        assert!((trade.exit_price - 99.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_trade_entry_exit_annotations() {
+        let candles = make_candles(10);
+        let config = make_config(false);
+
+        // Exit rule fires one bar after entry → signal close
+        let mut signal = make_strategy(close_above(100.0));
+        signal.long_exit_rules = close_above(100.0);
+        let signal_trade = run(&candles, &signal, &config).trades[0].clone();
+        assert_eq!(signal_trade.close_reason, CloseReason::Signal);
+        assert_eq!(signal_trade.entry_reason.as_deref(), Some("Long entry rules (1)"));
+        assert_eq!(signal_trade.exit_reason.as_deref(), Some("Long exit rules (1)"));
+
+        // 1% stop below the 95 entry is breached by the 94 low of the entry bar
+        let mut stopped = make_strategy(close_above(100.0));
+        stopped.stop_loss = Some(StopLoss { sl_type: StopLossType::Percentage, value: 1.0, atr_period: None });
+        let sl_trade = run(&candles, &stopped, &config).trades[0].clone();
+        assert_eq!(sl_trade.close_reason, CloseReason::StopLoss);
+        assert_eq!(sl_trade.entry_reason, signal_trade.entry_reason);
+        let exit_reason = sl_trade.exit_reason.as_deref().unwrap();
+        assert!(exit_reason.starts_with("Stop loss hit at"), "{}", exit_reason);
+        assert_ne!(sl_trade.exit_reason, signal_trade.exit_reason);
+    }
+
+    #[test]
+    fn test_fixed_capital_sizing_keeps_lots_constant() {
+        // Every trade enters at 95 and is stopped 1% lower by the 94 low, so equity
+        // falls trade after trade; 1-point pips keep the risk-based lots well above min_lot.
+        let candles = make_candles(12);
+        let instrument = InstrumentConfig { pip_size: 1.0, pip_value: 1.0, lot_size: 1.0, ..Default::default() };
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.position_sizing = PositionSizing {
+            sizing_type: PositionSizingType::RiskBased,
+            value: 1.0,
+            decrease_factor: 0.9,
+        };
+        strategy.stop_loss = Some(StopLoss { sl_type: StopLossType::Percentage, value: 1.0, atr_period: None });
+
+        let lots = |compounding: bool| -> Vec<f64> {
+            let config = BacktestConfig { compounding, ..make_config(false) };
+            let cancel = AtomicBool::new(false);
+            run_backtest(&candles, &SubBarData::None, &strategy, &config, &instrument, &cancel, |_, _, _| {})
+                .unwrap()
+                .trades
+                .iter()
+                .map(|t| t.lots)
+                .collect()
+        };
+
+        let compounded = lots(true);
+        let fixed = lots(false);
+        assert!(fixed.len() >= 3);
+        assert!(fixed.iter().all(|&l| l == fixed[0]), "fixed-capital lots: {:?}", fixed);
+        assert_eq!(fixed[0], compounded[0]);
+        assert!(compounded.last().unwrap() < &compounded[0], "compounded lots: {:?}", compounded);
+    }
+
+    #[test]
+    fn test_stop_out_equity_halts_losing_run() {
+        // Price falls 0.5 per bar under an always-long strategy: 100 lots lose 50 per bar
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let candles: Vec<Candle> = (0..100)
+            .map(|i| {
+                let price = 100.0 - 0.5 * i as f64;
+                Candle {
+                    datetime: (start + chrono::Duration::hours(i)).format("%Y-%m-%d %H:%M").to_string(),
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: 1.0,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let instrument = InstrumentConfig { pip_size: 1.0, pip_value: 1.0, lot_size: 1.0, ..Default::default() };
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.position_sizing.value = 100.0;
+
+        let run_with = |stop_out_equity: Option<f64>| {
+            let config = BacktestConfig { stop_out_equity, ..make_config(false) };
+            let cancel = AtomicBool::new(false);
+            run_backtest(&candles, &SubBarData::None, &strategy, &config, &instrument, &cancel, |_, _, _| {})
+                .unwrap()
+        };
+
+        let full = run_with(None);
+        assert!(!full.stopped_out);
+        assert_eq!(full.trades[0].close_reason, CloseReason::EndOfData);
+
+        // Entry at bar 1 (99.5); open P&L reaches -2000 at bar 41 (79.5)
+        let stopped = run_with(Some(8_000.0));
+        assert!(stopped.stopped_out);
+        assert_eq!(stopped.trades.len(), 1);
+        let trade = &stopped.trades[0];
+        assert_eq!(trade.close_reason, CloseReason::StopOut);
+        assert_eq!(trade.exit_time, candles[41].datetime);
+        assert!((trade.pnl + 2_000.0).abs() < 1e-6, "pnl {}", trade.pnl);
+        let last = stopped.equity_curve.last().unwrap();
+        assert_eq!(last.timestamp, candles[41].datetime);
+        assert!((last.equity - 8_000.0).abs() < 1e-6);
+        assert!(stopped.warnings.iter().any(|w| w.starts_with("Stopped out")));
+    }
+
+    #[test]
+    fn test_equity_sampling_caps_curve_keeps_metrics() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let make = |drift: f64| -> Vec<Candle> {
+            (0..500)
+                .map(|i| {
+                    let close = 100.0 + 5.0 * (i as f64 / 7.0).sin() - drift * i as f64;
+                    Candle {
+                        datetime: (start + chrono::Duration::hours(i)).format("%Y-%m-%d %H:%M").to_string(),
+                        open: close - 0.2,
+                        high: close + 0.5,
+                        low: close - 0.5,
+                        close,
+                        volume: 1000.0,
+                        ..Default::default()
+                    }
+                })
+                .collect()
+        };
+        let assert_sampling_matches = |candles: &[Candle], strategy: &Strategy, mut config: BacktestConfig, instrument: &InstrumentConfig| {
+            let cancel = AtomicBool::new(false);
+            let full = run_backtest(candles, &SubBarData::None, strategy, &config, instrument, &cancel, |_, _, _| {}).unwrap();
+            config.equity_sampling = Some(50);
+            let sampled = run_backtest(candles, &SubBarData::None, strategy, &config, instrument, &cancel, |_, _, _| {}).unwrap();
+
+            assert!(full.trades.len() > 5);
+            assert!(full.equity_curve.len() > 50);
+            assert!(sampled.equity_curve.len() <= 50);
+            assert_eq!(sampled.drawdown_curve.len(), sampled.equity_curve.len());
+            assert_eq!(sampled.benchmark_curve.len(), sampled.equity_curve.len());
+            assert_eq!(sampled.rolling_ratios.len(), sampled.equity_curve.len());
+            assert_eq!(sampled.equity_curve[0].timestamp, full.equity_curve[0].timestamp);
+            assert_eq!(
+                sampled.equity_curve.last().unwrap().equity,
+                full.equity_curve.last().unwrap().equity,
+            );
+            assert_eq!(
+                serde_json::to_string(&sampled.metrics).unwrap(),
+                serde_json::to_string(&full.metrics).unwrap(),
+            );
+            // Stored points carry the same values as the unsampled run at those bars
+            for (i, point) in sampled.equity_curve.iter().enumerate() {
+                let bar = full.equity_curve.iter().position(|p| p.timestamp == point.timestamp).unwrap();
+                assert_eq!(point.equity, full.equity_curve[bar].equity);
+                assert_eq!(sampled.drawdown_curve[i].drawdown_pct, full.drawdown_curve[bar].drawdown_pct);
+                assert_eq!(sampled.benchmark_curve[i].equity, full.benchmark_curve[bar].equity);
+                assert_eq!(sampled.rolling_ratios[i].sharpe, full.rolling_ratios[bar].sharpe);
+                assert_eq!(sampled.rolling_ratios[i].sortino, full.rolling_ratios[bar].sortino);
+            }
+            full
+        };
+
+        let mut strategy = make_strategy(close_above(102.0));
+        strategy.close_after_bars = Some(5);
+        assert_sampling_matches(&make(0.0), &strategy, make_config(false), &InstrumentConfig::default());
+
+        // A stop-out ends the run early: the rolling window stays a tenth of the requested
+        // run, while the annualisation follows the bars actually run
+        let candles = make(0.05);
+        let instrument = InstrumentConfig { pip_size: 1.0, pip_value: 1.0, lot_size: 1.0, ..Default::default() };
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.position_sizing.value = 100.0;
+        strategy.close_after_bars = Some(5);
+        let config = BacktestConfig { stop_out_equity: Some(8_500.0), ..make_config(false) };
+        let full = assert_sampling_matches(&candles, &strategy, config, &instrument);
+        assert!(full.stopped_out);
+        assert!(full.equity_curve.len() < candles.len() / 2, "ran {} bars", full.equity_curve.len());
+    }
+
+    #[test]
+    fn test_sampled_replay_steps_reach_the_step_bar() {
+        let candles = make_candles(43);
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.close_after_bars = Some(2);
+        let config = BacktestConfig { equity_sampling: Some(100), ..make_config(false) };
+        let steps = std::cell::RefCell::new(Vec::new());
+        let observer = StepObserver { interval: 10, on_step: &|s| steps.borrow_mut().push(s) };
+        let results = run_backtest_stepped(
+            &candles, &SubBarData::None, &strategy, &config,
+            &InstrumentConfig::default(), &AtomicBool::new(false), &observer,
+        )
+        .unwrap();
+        let steps = steps.into_inner();
+
+        // With room for every bar, each step ends on its own bar rather than the one before
+        assert_eq!(results.equity_curve.len(), 42);
+        for step in &steps[..steps.len() - 1] {
+            assert_eq!(step.equity_points.last().unwrap().timestamp, candles[step.bar_index].datetime);
+        }
+        let streamed: Vec<_> = steps.iter().flat_map(|s| &s.equity_points).map(|p| p.timestamp.clone()).collect();
+        let stored: Vec<_> = results.equity_curve.iter().map(|p| p.timestamp.clone()).collect();
+        assert_eq!(streamed, stored);
+    }
+
+    #[test]
+    fn test_order_price_indicator_rolls_at_session_start_hour() {
+        let bar = |dt: &str, high: f64, low: f64, close: f64| Candle {
+            datetime: dt.to_string(), open: close, high, low, close, ..Default::default()
+        };
+        let candles = vec![
+            bar("2024-01-02 17:00", 108.0, 100.0, 102.0),
+            bar("2024-01-03 02:00", 110.0, 103.0, 105.0),
+            bar("2024-01-03 16:00", 109.0, 104.0, 105.0),
+            bar("2024-01-03 17:00", 106.0, 104.0, 105.5),
+        ];
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.entry_order_indicator = Some(OrderPriceConfig {
+            indicator: IndicatorConfig {
+                indicator_type: IndicatorType::Pivots,
+                params: IndicatorParams::default(),
+                output_field: None,
+                cached_hash: 0,
+            },
+            multiplier: 1.0,
+            base_price_stop: PriceField::High,
+            base_price_limit: PriceField::Low,
+        });
+
+        // Session days: the 02:00 and 16:00 bars still belong to the session opened at 17:00
+        let session = compute_order_price_indicator(&strategy, &candles, 17).unwrap();
+        assert!(session[2].is_nan());
+        assert!((session[3] - (110.0 + 100.0 + 105.0) / 3.0).abs() < 1e-12);
+
+        // Calendar days roll at midnight
+        let calendar = compute_order_price_indicator(&strategy, &candles, 0).unwrap();
+        assert!((calendar[1] - (108.0 + 100.0 + 102.0) / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_daily_trade_limit_resets_at_session_start() {
+        // Hourly bars from 12:00 on Jan 2 to 07:00 on Jan 3, always signalling long
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let candles: Vec<Candle> = (0..20)
+            .map(|i| Candle {
+                datetime: (start + chrono::Duration::hours(i)).format("%Y-%m-%d %H:%M").to_string(),
+                open: 100.0,
+                high: 102.0,
+                low: 99.0,
+                close: 101.0,
+                volume: 1000.0,
+                ..Default::default()
+            })
+            .collect();
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.close_after_bars = Some(1);
+        strategy.max_daily_trades = Some(1);
+        let config = make_config(false);
+
+        let entries = |session_start_hour: u8| -> Vec<String> {
+            let instrument = InstrumentConfig { session_start_hour, ..Default::default() };
+            let cancel = AtomicBool::new(false);
+            run_backtest(&candles, &SubBarData::None, &strategy, &config, &instrument, &cancel, |_, _, _| {})
+                .unwrap()
+                .trades
+                .into_iter()
+                .map(|t| t.entry_time)
+                .collect()
+        };
+
+        // Calendar days: the second trade waits for midnight
+        assert_eq!(entries(0), vec!["2024-01-02 13:00", "2024-01-03 00:00"]);
+        // 17:00 sessions: the second trade comes at 17:00 and midnight does not reset the count
+        assert_eq!(entries(17), vec!["2024-01-02 13:00", "2024-01-02 17:00"]);
+    }
+
+    #[test]
+    fn test_market_on_close_exits_on_last_session_bar() {
+        // Hourly bars from 12:00 on Jan 2 to 07:00 on Jan 3 with distinct closes
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let candles: Vec<Candle> = (0..20)
+            .map(|i| Candle {
+                datetime: (start + chrono::Duration::hours(i)).format("%Y-%m-%d %H:%M").to_string(),
+                open: 100.0,
+                high: 110.0,
+                low: 99.0,
+                close: 101.0 + i as f64 * 0.25,
+                volume: 1000.0,
+                ..Default::default()
+            })
+            .collect();
+        let mut strategy = make_strategy(close_above(100.0));
+        let config = make_config(false);
+        let trades = |strategy: &Strategy| {
+            let instrument = InstrumentConfig { session_start_hour: 17, ..Default::default() };
+            let cancel = AtomicBool::new(false);
+            run_backtest(&candles, &SubBarData::None, strategy, &config, &instrument, &cancel, |_, _, _| {})
+                .unwrap()
+                .trades
+        };
+
+        let held = trades(&strategy);
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].close_reason, CloseReason::EndOfData);
+
+        strategy.session_exit = Some(SessionExit::MarketOnClose);
+        let closed = trades(&strategy);
+        // 16:00 is the final bar before the 17:00 session roll
+        assert_eq!(closed[0].close_reason, CloseReason::SessionClose);
+        assert_eq!(closed[0].exit_time, "2024-01-02 16:00");
+        assert_eq!(closed[0].exit_price, candles[4].close);
+        // The next session re-enters and runs into the end of the data
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[1].close_reason, CloseReason::EndOfData);
+    }
+
+    #[test]
+    fn test_cross_pair_trades_convert_pnl_with_constant_rate() {
+        // Rising market: every trade closes 2 bars (0.02) above its entry
+        let candles: Vec<Candle> = make_candles(10)
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let shift = i as f64 * 0.01;
+                Candle { open: c.open + shift, high: c.high + shift, low: c.low + shift, close: c.close + shift, ..c }
+            })
+            .collect();
+        let mut strategy = make_strategy(close_above(100.0));
+        strategy.close_after_bars = Some(2);
+        strategy.trading_costs.commission_value = 7.0;
+        let mut config = make_config(false);
+
+        let quote = run(&candles, &strategy, &config);
+        config.account_currency = Some("USD".into());
+        config.quote_conversion = Some(QuoteConversion::Constant { rate: 1.25 });
+        let converted = run(&candles, &strategy, &config);
+
+        assert!(!quote.trades.is_empty());
+        assert_eq!(quote.trades.len(), converted.trades.len());
+        for (q, c) in quote.trades.iter().zip(&converted.trades) {
+            assert!(q.pnl.abs() > 0.0);
+            assert!((c.pnl - q.pnl * 1.25).abs() < 1e-6, "{} vs {}", c.pnl, q.pnl);
+            assert_eq!(c.pnl_pips, q.pnl_pips);
+            assert_eq!(c.commission, q.commission); // per-lot commission is in account currency
+        }
+    }
+
+    /// In-memory `TickSource` that copies out the requested window.
+    struct VecTickSource(TickColumns);
+
+    impl TickSource for VecTickSource {
+        fn load_range(&self, from_us: i64, to_us: i64) -> Result<TickColumns, AppError> {
+            let ts = &self.0.timestamps;
+            let (a, b) = (ts.partition_point(|&t| t < from_us), ts.partition_point(|&t| t < to_us));
+            Ok(TickColumns {
+                timestamps: ts[a..b].to_vec(),
+                bids: self.0.bids[a..b].to_vec(),
+                asks: self.0.asks[a..b].to_vec(),
+            })
+        }
+    }
+
+    /// A tick every 5 minutes over `hours` hours, oscillating around 100.
+    fn make_ticks(hours: i64) -> TickColumns {
+        let n = hours * 12;
+        let timestamps: Vec<i64> = (0..n).map(|j| 1_704_067_200_000_000 + j * 300_000_000).collect();
+        let bids: Vec<f64> = (0..n).map(|j| 100.0 + 3.0 * (j as f64 / 40.0).sin()).collect();
+        let asks = bids.iter().map(|b| b + 0.0002).collect();
+        TickColumns { timestamps, bids, asks }
+    }
+
+    /// H1 candles aggregated from the bid side of `ticks`.
+    fn candles_from_ticks(ticks: &TickColumns) -> Vec<Candle> {
+        ticks.timestamps.chunks(12).zip(ticks.bids.chunks(12))
+            .map(|(ts, bids)| Candle {
+                timestamp: ts[0],
+                datetime: micros_to_datetime_string(ts[0]),
+                open: bids[0],
+                high: bids.iter().cloned().fold(f64::MIN, f64::max),
+                low: bids.iter().cloned().fold(f64::MAX, f64::min),
+                close: bids[bids.len() - 1],
+                volume: 12.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tick_chunks_match_in_memory_ticks() {
+        let candles = candles_from_ticks(&make_ticks(300));
+        let mut strategy = make_strategy(close_above(101.0));
+        strategy.stop_loss = Some(StopLoss { sl_type: StopLossType::Pips, value: 150.0, atr_period: None });
+        strategy.take_profit = Some(TakeProfit { tp_type: TakeProfitType::Pips, value: 200.0, atr_period: None });
+        let mut config = make_config(false);
+        config.precision = BacktestPrecision::RealTickRealSpread;
+
+        let cancel = AtomicBool::new(false);
+        let instrument = InstrumentConfig::default();
+        let in_memory = run_backtest(
+            &candles, &SubBarData::Ticks(make_ticks(300)), &strategy, &config, &instrument, &cancel, |_, _, _| {},
+        ).unwrap();
+        let chunked = SubBarData::TickChunks { source: Box::new(VecTickSource(make_ticks(300))), chunk_bars: 7 };
+        let streamed = run_backtest(&candles, &chunked, &strategy, &config, &instrument, &cancel, |_, _, _| {}).unwrap();
+
+        assert!(in_memory.trades.len() > 3);
+        assert!(in_memory.trades.iter().any(|t| t.close_reason != CloseReason::EndOfData));
+        assert_eq!(streamed.trades.len(), in_memory.trades.len());
+        for (a, b) in streamed.trades.iter().zip(&in_memory.trades) {
+            assert_eq!(a.entry_time, b.entry_time);
+            assert_eq!(a.exit_time, b.exit_time);
+            assert_eq!(a.entry_price, b.entry_price);
+            assert_eq!(a.exit_price, b.exit_price);
+            assert_eq!(a.close_reason, b.close_reason);
+        }
+    }
+
+    #[test]
+    fn test_tick_entry_annotation_with_groups() {
+        // The group never fires at bar open, so every entry comes from the flat rule
+        // evaluated tick by tick
+        let ticks = make_ticks(48);
+        let candles = candles_from_ticks(&ticks);
+        let mut strategy = make_strategy(close_above(101.0));
+        strategy.long_entry_groups = vec![RuleGroup {
+            id: "g1".to_string(),
+            rules: close_above(1_000.0),
+            internal: LogicalOperator::And,
+            join: None,
+        }];
+        strategy.close_after_bars = Some(1);
+        let mut config = make_config(false);
+        config.precision = BacktestPrecision::RealTickRealSpread;
+
+        let cancel = AtomicBool::new(false);
+        let result = run_backtest(
+            &candles, &SubBarData::Ticks(ticks), &strategy, &config, &InstrumentConfig::default(), &cancel, |_, _, _| {},
+        ).unwrap();
+        assert!(!result.trades.is_empty());
+        for trade in &result.trades {
+            assert_eq!(trade.entry_reason.as_deref(), Some("Long entry rules (1)"));
+        }
+
+        // A group set with nothing recorded as fired still reads sensibly
+        assert_eq!(rule_set_annotation("Long entry", &strategy.long_entry_groups, &[], 1), "Long entry groups (1)");
+    }
+
+    #[test]
+    fn test_max_spread_suppresses_entry_on_wide_tick_spread() {
+        // Flat 2-pip spread except during bar 1, where news widens it to 100 pips
+        let n = 5 * 12;
+        let timestamps: Vec<i64> = (0..n).map(|j| 1_704_067_200_000_000 + j * 300_000_000).collect();
+        let bids = vec![100.0; n as usize];
+        let asks = (0..n).map(|j| if (12..24).contains(&j) { 100.01 } else { 100.0002 }).collect();
+        let ticks = TickColumns { timestamps, bids, asks };
+        let candles = candles_from_ticks(&ticks);
+        let mut strategy = make_strategy(close_above(99.0));
+        let mut config = make_config(false);
+        config.precision = BacktestPrecision::RealTickRealSpread;
+        let sub_bars = SubBarData::Ticks(ticks);
+
+        let run = |strategy: &Strategy| {
+            let cancel = AtomicBool::new(false);
+            run_backtest(&candles, &sub_bars, strategy, &config, &InstrumentConfig::default(), &cancel, |_, _, _| {})
+                .unwrap()
+        };
+
+        let unfiltered = run(&strategy);
+        assert_eq!(unfiltered.trades[0].entry_time, candles[1].datetime);
+
+        strategy.trading_costs.max_spread_pips = Some(5.0);
+        let filtered = run(&strategy);
+        // No entry at bar 1's open or on any of its ticks; the next bar enters normally
+        assert_eq!(filtered.trades[0].entry_time, candles[2].datetime);
+        assert!((filtered.trades[0].entry_price - 100.0002).abs() < 1e-9);
+        assert!(filtered.warnings.iter().any(|w| w.starts_with("Skipped 1 entry signal(s)")));
+    }
+
+    /// Walk three TF candles at 0/60/120 and collect each one's sub-bar range.
+    fn bucket_subbars(sub_bars: &SubBarData, boundary: SubBarBoundary) -> Vec<(usize, usize)> {
+        let opens = [("00", 0i64), ("01", 60), ("02", 120)];
+        let mut cursor = 0;
+        (0..opens.len())
+            .map(|i| {
+                let (next_dt, next_ts) = opens.get(i + 1).map_or(("", i64::MAX), |&(d, t)| (d, t));
+                find_subbar_range(sub_bars, &mut cursor, opens[i].0, next_dt, opens[i].1, next_ts, boundary)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_subbar_range_boundary_ticks() {
+        // Ticks exactly on each candle open (0, 60, 120) plus stragglers after the last open
+        let stamps = [0i64, 30, 60, 90, 120, 150, 180];
+        let ticks = SubBarData::Ticks(TickColumns {
+            timestamps: stamps.to_vec(),
+            bids: vec![1.0; stamps.len()],
+            asks: vec![1.0001; stamps.len()],
+        });
+        // [open, next_open): the boundary tick opens the next candle
+        assert_eq!(bucket_subbars(&ticks, SubBarBoundary::StartInclusive), vec![(0, 2), (2, 4), (4, 7)]);
+        // (open, next_open]: the boundary tick closes the current candle; the very first
+        // tick sits on the previous (absent) candle's close and is skipped
+        assert_eq!(bucket_subbars(&ticks, SubBarBoundary::EndInclusive), vec![(1, 3), (3, 5), (5, 7)]);
+    }
+
+    #[test]
+    fn test_subbar_range_boundary_candles() {
+        let subs = SubBarData::Candles(
+            ["00", "00:30", "01", "01:30", "02", "02:30", "03"]
+                .iter()
+                .map(|dt| Candle { datetime: dt.to_string(), ..Default::default() })
+                .collect(),
+        );
+        assert_eq!(bucket_subbars(&subs, SubBarBoundary::StartInclusive), vec![(0, 2), (2, 4), (4, 7)]);
+        assert_eq!(bucket_subbars(&subs, SubBarBoundary::EndInclusive), vec![(1, 3), (3, 5), (5, 7)]);
+    }
+
+    #[test]
+    fn test_tick_chunks_keep_boundary_ticks_end_inclusive() {
+        // Chunked windows must bucket boundary ticks exactly like the in-memory path
+        let candles = candles_from_ticks(&make_ticks(300));
+        let mut strategy = make_strategy(close_above(101.0));
+        strategy.stop_loss = Some(StopLoss { sl_type: StopLossType::Pips, value: 150.0, atr_period: None });
+        let mut config = make_config(false);
+        config.precision = BacktestPrecision::RealTickRealSpread;
+        let instrument = InstrumentConfig { subbar_boundary: SubBarBoundary::EndInclusive, ..Default::default() };
+
+        let cancel = AtomicBool::new(false);
+        let in_memory = run_backtest(
+            &candles, &SubBarData::Ticks(make_ticks(300)), &strategy, &config, &instrument, &cancel, |_, _, _| {},
+        ).unwrap();
+        let chunked = SubBarData::TickChunks { source: Box::new(VecTickSource(make_ticks(300))), chunk_bars: 7 };
+        let streamed = run_backtest(&candles, &chunked, &strategy, &config, &instrument, &cancel, |_, _, _| {}).unwrap();
+
+        assert!(in_memory.trades.len() > 3);
+        assert_eq!(streamed.trades.len(), in_memory.trades.len());
+        for (a, b) in streamed.trades.iter().zip(&in_memory.trades) {
+            assert_eq!(a.exit_time, b.exit_time);
+            assert_eq!(a.exit_price, b.exit_price);
+        }
+    }
+
+    /// Steadily rising bars: every bar makes a higher high, so +DI and ADX are positive.
+    fn rising_candles(n: usize) -> Vec<Candle> {
+        (0..n)
+            .map(|i| {
+                let base = 100.0 + i as f64;
+                Candle {
+                    datetime: format!("2024-01-{:02} {:02}:00", 1 + i / 24, i % 24),
+                    open: base,
+                    high: base + 2.0,
+                    low: base - 1.0,
+                    close: base + 1.0,
+                    volume: 1000.0,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn adx_above(period: usize, value: f64) -> Vec<Rule> {
+        let mut rules = close_above(value);
+        rules[0].left_operand = Operand {
+            operand_type: OperandType::Indicator,
+            indicator: Some(IndicatorConfig {
+                indicator_type: IndicatorType::ADX,
+                params: IndicatorParams { period: Some(period), ..Default::default() },
+                output_field: None,
+                cached_hash: 0,
+            }),
+            price_field: None,
+            ..rules[0].left_operand.clone()
+        };
+        rules
+    }
+
+    #[test]
+    fn test_adx_entry_waits_for_two_periods() {
+        let period = 5;
+        let candles = rising_candles(40);
+        let strategy = make_strategy(adx_above(period, 0.0));
+        let cancel = AtomicBool::new(false);
+        let result = run_backtest(
+            &candles, &SubBarData::None, &strategy, &make_config(false), &InstrumentConfig::default(), &cancel, |_, _, _| {},
+        ).unwrap();
+
+        let first = result.trades.first().expect("ADX entry should fire once warm");
+        let entry_bar = candles.iter().position(|c| c.datetime == first.entry_time).unwrap();
+        assert!(entry_bar >= 2 * period, "entered at bar {entry_bar}");
+    }
+
+    #[test]
+    fn test_warmup_bars_delays_first_entry() {
+        let candles = rising_candles(40);
+        let strategy = make_strategy(close_above(0.0));
+        let cancel = AtomicBool::new(false);
+        let run = |config: &BacktestConfig| {
+            let result = run_backtest(
+                &candles, &SubBarData::None, &strategy, config, &InstrumentConfig::default(), &cancel, |_, _, _| {},
+            ).unwrap();
+            candles.iter().position(|c| c.datetime == result.trades[0].entry_time).unwrap()
+        };
+
+        // Default: only the 1-bar minimum is skipped
+        assert_eq!(run(&make_config(false)), 1);
+        let config = BacktestConfig { warmup_bars: Some(25), ..make_config(false) };
+        assert_eq!(run(&config), 25);
+    }
+
+    #[test]
+    fn test_max_hold_bars_force_closes() {
+        let candles = rising_candles(30);
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.max_hold_bars = Some(4);
+        let cancel = AtomicBool::new(false);
+        let result = run_backtest(
+            &candles, &SubBarData::None, &strategy, &make_config(false), &InstrumentConfig::default(), &cancel, |_, _, _| {},
+        ).unwrap();
+
+        let first = &result.trades[0];
+        assert_eq!(first.close_reason, CloseReason::MaxHold);
+        assert_eq!(first.duration_bars, 4);
+        // The always-true entry re-opens on the following bars and each hold is capped
+        assert!(result.trades.len() > 3);
+        assert!(result.trades.iter().all(|t| t.duration_bars <= 4));
+    }
+
+    #[test]
+    fn test_min_hold_bars_suppresses_early_exit() {
+        let candles = rising_candles(30);
+        let mut strategy = make_strategy(close_above(0.0));
+        // Exit rule is true on every bar
+        strategy.long_exit_rules = close_above(0.0);
+        let cancel = AtomicBool::new(false);
+        let run = |strategy: &Strategy| run_backtest(
+            &candles, &SubBarData::None, strategy, &make_config(false), &InstrumentConfig::default(), &cancel, |_, _, _| {},
+        ).unwrap();
+
+        let baseline = run(&strategy);
+        assert_eq!(baseline.trades[0].close_reason, CloseReason::Signal);
+        assert_eq!(baseline.trades[0].duration_bars, 1);
+
+        strategy.min_hold_bars = Some(5);
+        let held = run(&strategy);
+        assert_eq!(held.trades[0].close_reason, CloseReason::Signal);
+        assert_eq!(held.trades[0].duration_bars, 5);
+    }
+
+    #[test]
+    fn test_cost_breakdown_reconciles_gross_and_net() {
+        let candles = rising_candles(30);
+        let instrument = InstrumentConfig::default();
+        for short in [false, true] {
+            let mut strategy = make_strategy(close_above(0.0));
+            if short {
+                strategy.short_entry_rules = std::mem::take(&mut strategy.long_entry_rules);
+                strategy.trade_direction = TradeDirection::Short;
+            }
+            strategy.close_after_bars = Some(2);
+            strategy.trading_costs.spread_pips = 2.0;
+            strategy.trading_costs.slippage_pips = 0.5;
+            strategy.trading_costs.commission_value = 7.0;
+            let result = run(&candles, &strategy, &make_config(false));
+
+            assert!(!result.trades.is_empty());
+            let sign = if short { -1.0 } else { 1.0 };
+            let mut gross = 0.0;
+            for t in &result.trades {
+                // Spread once per round trip, slippage on both fills
+                let pip_money = instrument.pip_value * t.lots;
+                assert!((t.spread_cost - 2.0 * pip_money).abs() < 1e-6, "{}", t.spread_cost);
+                assert!((t.slippage_cost - 1.0 * pip_money).abs() < 1e-6, "{}", t.slippage_cost);
+                // Price move between the two fills, plus the 3 pips of spread and slippage they absorbed
+                let fill_pips = instrument.price_to_pips(sign * (t.exit_price - t.entry_price));
+                gross += (fill_pips + 3.0) * pip_money;
+            }
+            let m = &result.metrics;
+            assert!((m.gross_pnl_before_costs - gross).abs() < 1e-6, "{} vs {}", m.gross_pnl_before_costs, gross);
+            assert!((m.total_spread_cost - result.trades.iter().map(|t| t.spread_cost).sum::<f64>()).abs() < 1e-6);
+            assert!(m.total_slippage_cost > 0.0 && m.total_commission_charged > 0.0);
+            let costs = m.total_spread_cost + m.total_slippage_cost + m.total_commission_charged - m.total_swap_charged;
+            assert!((m.total_costs - costs).abs() < 1e-6);
+            assert!((m.gross_pnl_before_costs - m.total_costs - m.net_profit).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_exit_priority_signal_vs_take_profit_same_bar() {
+        // Entry at bar k's open; bar k+1 opens 1.0 higher and reaches +3.0, so both the
+        // always-true exit signal (at the open) and a +2.5 take profit qualify on bar k+1.
+        let candles = rising_candles(30);
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.long_exit_rules = close_above(0.0);
+        strategy.take_profit = Some(TakeProfit { tp_type: TakeProfitType::Pips, value: 25_000.0, atr_period: None });
+        let mut config = make_config(false);
+
+        let signal_first = run(&candles, &strategy, &config);
+        let t = &signal_first.trades[0];
+        assert_eq!(t.close_reason, CloseReason::Signal);
+        assert_eq!(t.duration_bars, 1);
+        assert!((t.exit_price - (t.entry_price + 1.0)).abs() < 1e-9);
+
+        config.exit_priority = ExitPriority::StopsFirst;
+        let stops_first = run(&candles, &strategy, &config);
+        let t = &stops_first.trades[0];
+        assert_eq!(t.close_reason, CloseReason::TakeProfit);
+        assert_eq!(t.duration_bars, 1);
+        assert!((t.exit_price - (t.entry_price + 2.5)).abs() < 1e-9);
+        assert_eq!(signal_first.trades[0].entry_time, t.entry_time);
+    }
+
+    #[test]
+    fn test_intrabar_drawdown_sees_subbar_dip() {
+        // A long held from bar 1 closes every bar at 101, but bar 10 dips to 60 inside
+        let mut candles = make_candles(20);
+        candles[10].low = 60.0;
+        let subs: Vec<Candle> = candles
+            .iter()
+            .flat_map(|c| {
+                let hour = &c.datetime[..13];
+                let dip = if c.low < 94.0 { c.low } else { 94.0 };
+                [
+                    Candle { datetime: format!("{hour}:00"), open: 95.0, high: 102.0, low: 94.0, close: 98.0, ..Default::default() },
+                    Candle { datetime: format!("{hour}:30"), open: 98.0, high: 102.0, low: dip, close: 101.0, ..Default::default() },
+                ]
+            })
+            .collect();
+        let sub_bars = SubBarData::Candles(subs);
+        let strategy = make_strategy(close_above(0.0));
+        let mut config = make_config(false);
+        config.precision = BacktestPrecision::M1TickSimulation;
+        let run_with = |config: &BacktestConfig| {
+            run_backtest(&candles, &sub_bars, &strategy, config, &InstrumentConfig::default(), &AtomicBool::new(false), |_, _, _| {})
+                .unwrap()
+        };
+        let max_dd = |r: &BacktestResults| r.drawdown_curve.iter().map(|p| p.drawdown_pct).fold(0.0, f64::max);
+
+        let bar_close = run_with(&config);
+        config.drawdown_basis = DrawdownBasis::Intrabar;
+        let intrabar = run_with(&config);
+
+        assert_eq!(bar_close.drawdown_curve.len(), intrabar.drawdown_curve.len());
+        for (b, t) in bar_close.drawdown_curve.iter().zip(&intrabar.drawdown_curve) {
+            assert!(t.drawdown_pct >= b.drawdown_pct - 1e-12, "{} < {} at {}", t.drawdown_pct, b.drawdown_pct, t.timestamp);
+        }
+        assert!(max_dd(&intrabar) > max_dd(&bar_close) + 1.0, "{} vs {}", max_dd(&intrabar), max_dd(&bar_close));
+        let dip = intrabar.drawdown_curve.iter().position(|p| p.drawdown_pct == max_dd(&intrabar)).unwrap();
+        assert_eq!(intrabar.drawdown_curve[dip].timestamp, candles[10].datetime);
+        // The equity curve itself stays on bar-close equity
+        assert_eq!(bar_close.equity_curve.last().unwrap().equity, intrabar.equity_curve.last().unwrap().equity);
+
+        // Without sub-bar data the bar's own low bounds the dip
+        let no_subs = run_backtest(&candles, &SubBarData::None, &strategy, &config, &InstrumentConfig::default(), &AtomicBool::new(false), |_, _, _| {})
+            .unwrap();
+        assert!((max_dd(&no_subs) - max_dd(&intrabar)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stops_first_leaves_signal_exit_when_stops_untouched() {
+        let candles = rising_candles(30);
+        let mut strategy = make_strategy(close_above(0.0));
+        strategy.long_exit_rules = close_above(0.0);
+        // Far out of reach: the signal exit must still fire at the open
+        strategy.take_profit = Some(TakeProfit { tp_type: TakeProfitType::Pips, value: 1_000_000.0, atr_period: None });
+        let mut config = make_config(false);
+        config.exit_priority = ExitPriority::StopsFirst;
+
+        let result = run(&candles, &strategy, &config);
+        assert_eq!(result.trades[0].close_reason, CloseReason::Signal);
+        assert_eq!(result.trades[0].duration_bars, 1);
+    }
+
+    fn batch_strategies() -> Vec<Strategy> {
+        let mut early = make_strategy(close_above(105.0));
+        early.name = "early".into();
+        early.close_after_bars = Some(3);
+        let mut late = make_strategy(close_above(130.0));
+        late.id = "s2".into();
+        late.name = "late".into();
+        late.close_after_bars = Some(5);
+        let mut short = make_strategy(vec![]);
+        short.id = "s3".into();
+        short.name = "short".into();
+        short.trade_direction = TradeDirection::Short;
+        short.short_entry_rules = close_above(110.0);
+        short.close_after_bars = Some(2);
+        vec![early, late, short]
+    }
+
+    #[test]
+    fn test_batch_matches_individual_runs() {
+        let candles = rising_candles(60);
+        let config = make_config(false);
+        let strategies = batch_strategies();
+        let cancel = AtomicBool::new(false);
+
+        let batch = run_backtest_batch(
+            &candles, &SubBarData::None, &strategies, &config,
+            &InstrumentConfig::default(), &cancel,
+        )
+        .unwrap();
+
+        assert_eq!(batch.len(), strategies.len());
+        for (row, strategy) in batch.iter().zip(&strategies) {
+            assert_eq!(row.strategy_id, strategy.id);
+            assert_eq!(row.strategy_name, strategy.name);
+            let single = run(&candles, strategy, &config);
+            assert!(single.metrics.total_trades > 0, "{} should trade", strategy.name);
+            assert_eq!(
+                serde_json::to_string(&row.metrics).unwrap(),
+                serde_json::to_string(&single.metrics).unwrap(),
+                "metrics differ for {}",
+                strategy.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_batch_respects_cancel_flag() {
+        let cancel = AtomicBool::new(true);
+        let err = run_backtest_batch(
+            &rising_candles(60), &SubBarData::None, &batch_strategies(), &make_config(false),
+            &InstrumentConfig::default(), &cancel,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::BacktestCancelled));
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::errors::AppError;
 use crate::models::candle::Candle;
-use crate::models::strategy::{IndicatorConfig, IndicatorType};
+use crate::models::strategy::{AtrSmoothing, EmaSeed, IndicatorConfig, IndicatorType, PivotType, RsiSmoothing};
 
 /// Output of an indicator computation. Multi-output indicators use secondary/tertiary.
 #[derive(Debug, Clone)]
@@ -28,6 +28,9 @@ pub struct CandleSlices {
     pub low: Vec<f64>,
     pub close: Vec<f64>,
     pub volume: Vec<f64>,
+    /// Hour at which day-based indicators (Pivots) roll to a new trading day.
+    /// See `InstrumentConfig::session_start_hour`.
+    pub session_start_hour: u8,
 }
 
 impl CandleSlices {
@@ -46,7 +49,13 @@ impl CandleSlices {
             close.push(c.close);
             volume.push(c.volume);
         }
-        Self { open, high, low, close, volume }
+        Self { open, high, low, close, volume, session_start_hour: 0 }
+    }
+
+    /// Roll daily resets at `hour` instead of midnight.
+    pub fn with_session_start_hour(mut self, hour: u8) -> Self {
+        self.session_start_hour = hour;
+        self
     }
 }
 
@@ -72,6 +81,13 @@ pub fn compute_indicator_with_slices(
     let volume = &slices.volume;
     let open = &slices.open;
 
+    if config.indicator_type.uses_volume() && !volume.iter().any(|&v| v > 0.0) {
+        return Err(AppError::MissingVolumeData(format!(
+            "{:?} needs bar volume, but the loaded data has no non-zero volume (was it imported without a volume column?)",
+            config.indicator_type
+        )));
+    }
+
     match config.indicator_type {
         IndicatorType::SMA => {
             let period = require_period(&config.params)?;
@@ -87,7 +103,7 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             check_data_len(len, period)?;
             Ok(IndicatorOutput {
-                primary: ema(&close, period),
+                primary: ema_seeded(&close, period, config.params.ema_seed.unwrap_or_default()),
                 secondary: None,
                 tertiary: None,
                 extra: None,
@@ -97,7 +113,7 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             check_data_len(len, period + 1)?;
             Ok(IndicatorOutput {
-                primary: rsi(&close, period),
+                primary: rsi(&close, period, config.params.rsi_smoothing.unwrap_or_default()),
                 secondary: None,
                 tertiary: None,
                 extra: None,
@@ -117,7 +133,7 @@ pub fn compute_indicator_with_slices(
                 .signal_period
                 .ok_or_else(|| AppError::InvalidIndicatorParams("MACD requires signal_period".into()))?;
             check_data_len(len, slow)?;
-            let (macd_line, signal_line, histogram) = macd(&close, fast, slow, signal);
+            let (macd_line, signal_line, histogram) = macd(&close, fast, slow, signal, config.params.ema_seed.unwrap_or_default());
             Ok(IndicatorOutput {
                 primary: macd_line,
                 secondary: Some(signal_line),
@@ -129,7 +145,8 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             let std_dev_mult = config.params.std_dev.unwrap_or(2.0);
             check_data_len(len, period)?;
-            let (upper, middle, lower) = bollinger_bands(&close, period, std_dev_mult);
+            let sample = require_stddev_sample(&config.params, period)?;
+            let (upper, middle, lower) = bollinger_bands(&close, period, std_dev_mult, sample);
             Ok(IndicatorOutput {
                 primary: middle,
                 secondary: Some(upper),
@@ -141,7 +158,7 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             check_data_len(len, period + 1)?;
             Ok(IndicatorOutput {
-                primary: atr(&high, &low, &close, period),
+                primary: atr(&high, &low, &close, period, config.params.atr_smoothing.unwrap_or_default()),
                 secondary: None,
                 tertiary: None,
                 extra: None,
@@ -311,14 +328,24 @@ pub fn compute_indicator_with_slices(
             let (upper, middle, lower) = keltner_channel(&high, &low, &close, period, mult);
             Ok(IndicatorOutput { primary: middle, secondary: Some(upper), tertiary: Some(lower), extra: None })
         }
+        IndicatorType::DonchianChannel => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period)?;
+            let (upper, middle, lower) = donchian_channel(high, low, period);
+            Ok(IndicatorOutput { primary: middle, secondary: Some(upper), tertiary: Some(lower), extra: None })
+        }
         IndicatorType::LaguerreRSI => {
             let gamma = config.params.gamma.unwrap_or(0.8);
             Ok(IndicatorOutput { primary: laguerre_rsi(&close, gamma), secondary: None, tertiary: None, extra: None })
         }
         IndicatorType::LinearRegression => {
             let period = require_period(&config.params)?;
+            let std_dev_mult = config.params.std_dev.unwrap_or(2.0);
             check_data_len(len, period)?;
-            Ok(IndicatorOutput { primary: linear_regression(&close, period), secondary: None, tertiary: None, extra: None })
+            let (fitted, slope, upper, lower) = linear_regression(&close, period, std_dev_mult);
+            let mut extra = HashMap::new();
+            extra.insert("slope".to_string(), slope);
+            Ok(IndicatorOutput { primary: fitted, secondary: Some(upper), tertiary: Some(lower), extra: Some(extra) })
         }
         IndicatorType::Momentum => {
             let period = require_period(&config.params)?;
@@ -329,7 +356,8 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             let mult = config.params.multiplier.unwrap_or(3.0);
             check_data_len(len, period + 1)?;
-            Ok(IndicatorOutput { primary: supertrend(&high, &low, &close, period, mult), secondary: None, tertiary: None, extra: None })
+            let smoothing = config.params.atr_smoothing.unwrap_or_default();
+            Ok(IndicatorOutput { primary: supertrend(&high, &low, &close, period, mult, smoothing), secondary: None, tertiary: None, extra: None })
         }
         IndicatorType::TrueRange => {
             Ok(IndicatorOutput { primary: true_range(&high, &low, &close), secondary: None, tertiary: None, extra: None })
@@ -337,7 +365,8 @@ pub fn compute_indicator_with_slices(
         IndicatorType::StdDev => {
             let period = require_period(&config.params)?;
             check_data_len(len, period)?;
-            Ok(IndicatorOutput { primary: std_dev(&close, period), secondary: None, tertiary: None, extra: None })
+            let sample = require_stddev_sample(&config.params, period)?;
+            Ok(IndicatorOutput { primary: std_dev(&close, period, sample), secondary: None, tertiary: None, extra: None })
         }
         IndicatorType::Reflex => {
             let period = require_period(&config.params)?;
@@ -345,7 +374,7 @@ pub fn compute_indicator_with_slices(
             Ok(IndicatorOutput { primary: reflex(&close, period), secondary: None, tertiary: None, extra: None })
         }
         IndicatorType::Pivots => {
-            let extra = pivots(candles);
+            let extra = pivots(candles, config.params.pivot_type.unwrap_or_default(), slices.session_start_hour);
             let primary = extra.get("pp").cloned().unwrap_or_else(|| vec![f64::NAN; len]);
             Ok(IndicatorOutput { primary, secondary: None, tertiary: None, extra: Some(extra) })
         }
@@ -360,6 +389,20 @@ pub fn compute_indicator_with_slices(
             let (vi_plus, vi_minus) = vortex(&high, &low, &close, period);
             Ok(IndicatorOutput { primary: vi_plus, secondary: Some(vi_minus), tertiary: None, extra: None })
         }
+        IndicatorType::MassIndex => {
+            let period = require_period(&config.params)?;
+            let ema_period = config.params.fast_period.unwrap_or(9);
+            check_data_len(len, 2 * ema_period.saturating_sub(1) + period)?;
+            Ok(IndicatorOutput { primary: mass_index(high, low, period, ema_period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::Klinger => {
+            let fast = config.params.fast_period.unwrap_or(34);
+            let slow = config.params.slow_period.unwrap_or(55);
+            let signal = config.params.signal_period.unwrap_or(13);
+            check_data_len(len, fast.max(slow) + 1)?;
+            let (kvo, signal_line) = klinger(high, low, close, volume, fast, slow, signal);
+            Ok(IndicatorOutput { primary: kvo, secondary: Some(signal_line), tertiary: None, extra: None })
+        }
         IndicatorType::AvgVolume => {
             let period = require_period(&config.params)?;
             check_data_len(len, period)?;
@@ -405,8 +448,147 @@ pub fn compute_indicator_with_slices(
             let period = require_period(&config.params)?;
             let fast = config.params.fast_period.unwrap_or(20);
             let slow = config.params.slow_period.unwrap_or(50);
+            let smoothing = config.params.slowing.unwrap_or(3);
             check_data_len(len, slow + period)?;
-            Ok(IndicatorOutput { primary: schaff_trend_cycle(&close, period, fast, slow), secondary: None, tertiary: None, extra: None })
+            Ok(IndicatorOutput { primary: schaff_trend_cycle(&close, period, fast, slow, smoothing), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::Choppiness => {
+            let period = require_period(&config.params)?;
+            if period < 2 {
+                return Err(AppError::InvalidIndicatorParams("Choppiness requires period >= 2".into()));
+            }
+            check_data_len(len, period + 1)?;
+            Ok(IndicatorOutput { primary: choppiness(high, low, close, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::StochasticRSI => {
+            let period = require_period(&config.params)?;
+            let k_period = config
+                .params
+                .k_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("StochasticRSI requires k_period".into()))?;
+            let d_period = config
+                .params
+                .d_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("StochasticRSI requires d_period".into()))?;
+            check_data_len(len, period + k_period)?;
+            let slowing = config.params.slowing.unwrap_or(3);
+            let (k, d) = stochastic_rsi(close, period, k_period, d_period, slowing);
+            Ok(IndicatorOutput { primary: k, secondary: Some(d), tertiary: None, extra: None })
+        }
+        IndicatorType::TRIX => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, 3 * period.saturating_sub(1) + 2)?;
+            let line = trix(close, period);
+            let signal = config.params.signal_period.map(|sp| ema_on_slice(&line, sp));
+            Ok(IndicatorOutput { primary: line, secondary: signal, tertiary: None, extra: None })
+        }
+        IndicatorType::AccumulationDistribution => {
+            Ok(IndicatorOutput { primary: accumulation_distribution(high, low, close, volume), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::ChaikinMoneyFlow => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period)?;
+            Ok(IndicatorOutput { primary: chaikin_money_flow(high, low, close, volume, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::ForceIndex => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period + 1)?;
+            Ok(IndicatorOutput { primary: force_index(close, volume, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::EaseOfMovement => {
+            let period = require_period(&config.params)?;
+            let scale = config.params.multiplier.unwrap_or(10_000.0);
+            check_data_len(len, period + 1)?;
+            Ok(IndicatorOutput { primary: ease_of_movement(high, low, volume, period, scale), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::VWMA => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period)?;
+            Ok(IndicatorOutput { primary: vwma(close, volume, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::WMA => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period)?;
+            Ok(IndicatorOutput { primary: wma(close, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::ZLEMA => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period + period.saturating_sub(1) / 2)?;
+            Ok(IndicatorOutput { primary: zlema(close, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::DPO => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, period + period / 2 + 1)?;
+            Ok(IndicatorOutput { primary: dpo(close, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::PPO => {
+            let fast = config
+                .params
+                .fast_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("PPO requires fast_period".into()))?;
+            let slow = config
+                .params
+                .slow_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("PPO requires slow_period".into()))?;
+            let signal = config
+                .params
+                .signal_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("PPO requires signal_period".into()))?;
+            check_data_len(len, slow)?;
+            let (ppo_line, signal_line, histogram) = ppo(close, fast, slow, signal, config.params.ema_seed.unwrap_or_default());
+            Ok(IndicatorOutput {
+                primary: ppo_line,
+                secondary: Some(signal_line),
+                tertiary: Some(histogram),
+                extra: None,
+            })
+        }
+        IndicatorType::ElderImpulse => {
+            let period = require_period(&config.params)?;
+            let fast = config
+                .params
+                .fast_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("Elder Impulse requires fast_period".into()))?;
+            let slow = config
+                .params
+                .slow_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("Elder Impulse requires slow_period".into()))?;
+            let signal = config
+                .params
+                .signal_period
+                .ok_or_else(|| AppError::InvalidIndicatorParams("Elder Impulse requires signal_period".into()))?;
+            check_data_len(len, period.max(slow) + 1)?;
+            Ok(IndicatorOutput { primary: elder_impulse(close, period, fast, slow, signal), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::Coppock => {
+            let period = require_period(&config.params)?;
+            let fast = config.params.fast_period.unwrap_or(11);
+            let slow = config.params.slow_period.unwrap_or(14);
+            check_data_len(len, fast.max(slow) + period)?;
+            Ok(IndicatorOutput { primary: coppock(close, period, fast, slow), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::SuperSmoother => {
+            let period = require_period(&config.params)?;
+            check_data_len(len, 3)?;
+            Ok(IndicatorOutput { primary: super_smoother(close, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::RoofingFilter => {
+            let period = require_period(&config.params)?;
+            let hp_period = config.params.slow_period.unwrap_or(48);
+            check_data_len(len, 3)?;
+            Ok(IndicatorOutput { primary: roofing_filter(close, hp_period, period), secondary: None, tertiary: None, extra: None })
+        }
+        IndicatorType::ConnorsRSI => {
+            let period = require_period(&config.params)?;
+            let streak_period = config.params.fast_period.unwrap_or(2);
+            let rank_period = config.params.slow_period.unwrap_or(100);
+            check_data_len(len, period.max(streak_period).max(rank_period + 1) + 1)?;
+            Ok(IndicatorOutput {
+                primary: connors_rsi(close, period, streak_period, rank_period),
+                secondary: None,
+                tertiary: None,
+                extra: None,
+            })
         }
     }
 }
@@ -434,6 +616,19 @@ fn require_period(
         .ok_or_else(|| AppError::InvalidIndicatorParams("period parameter is required".into()))
 }
 
+/// The `sample_stddev` flag, rejected on windows shorter than 2 bars where the N-1
+/// estimator is undefined.
+fn require_stddev_sample(
+    params: &crate::models::strategy::IndicatorParams,
+    period: usize,
+) -> Result<bool, AppError> {
+    let sample = params.sample_stddev.unwrap_or(false);
+    if sample && period < 2 {
+        return Err(AppError::InvalidIndicatorParams("sample standard deviation requires a period of at least 2".into()));
+    }
+    Ok(sample)
+}
+
 fn check_data_len(available: usize, needed: usize) -> Result<(), AppError> {
     if available < needed {
         return Err(AppError::InsufficientData { needed, available });
@@ -441,20 +636,136 @@ fn check_data_len(available: usize, needed: usize) -> Result<(), AppError> {
     Ok(())
 }
 
-// ── SMA ──
+// ── Rolling window sums ──
 
-/// Simple Moving Average. First `period-1` values are NaN.
-pub fn sma(data: &[f64], period: usize) -> Vec<f64> {
+/// Neumaier-compensated accumulator. A rolling window adds one value and removes
+/// another per bar; the compensation term keeps that from drifting over long series.
+#[derive(Debug, Default, Clone, Copy)]
+struct CompensatedSum {
+    sum: f64,
+    comp: f64,
+}
+
+impl CompensatedSum {
+    fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.comp += (self.sum - t) + x;
+        } else {
+            self.comp += (x - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    fn value(&self) -> f64 {
+        self.sum + self.comp
+    }
+}
+
+/// Sum of the last `period` values, in one pass. First `period-1` values are NaN.
+/// Windows holding a non-finite value are NaN; the running sum is rebuilt from scratch
+/// once that value leaves the window, so a data gap doesn't poison later windows.
+pub(crate) fn rolling_sum(data: &[f64], period: usize) -> Vec<f64> {
     let len = data.len();
     let mut result = vec![f64::NAN; len];
     if period == 0 || len < period {
         return result;
     }
-    let mut sum: f64 = data[..period].iter().sum();
-    result[period - 1] = sum / period as f64;
-    for i in period..len {
-        sum += data[i] - data[i - period];
-        result[i] = sum / period as f64;
+    let mut acc = CompensatedSum::default();
+    let mut last_bad: Option<usize> = None;
+    for (i, &v) in data.iter().enumerate() {
+        if !v.is_finite() {
+            last_bad = Some(i);
+        }
+        if let Some(bad) = last_bad {
+            if i < bad + period {
+                continue;
+            }
+            acc = CompensatedSum::default();
+            for &w in &data[i + 1 - period..=i] {
+                acc.add(w);
+            }
+            result[i] = acc.value();
+            last_bad = None;
+            continue;
+        }
+        acc.add(v);
+        if i >= period {
+            acc.add(-data[i - period]);
+        }
+        if i + 1 >= period {
+            result[i] = acc.value();
+        }
+    }
+    result
+}
+
+/// Rolling mean and sum of squared deviations from that mean (M2) over `period` bars,
+/// in one pass. Variance is `M2 / N` (population) or `M2 / (N-1)` (sample).
+///
+/// Sums are taken on values shifted by a recent price so that M2 = Σx² - (Σx)²/N
+/// doesn't cancel catastrophically at price levels far from zero. The shift is reset
+/// to the window start every `period` bars (a full re-sum, so still O(1) per bar).
+/// As in [`rolling_sum`], windows holding a non-finite value are NaN and the sums are
+/// rebuilt once it leaves the window.
+pub(crate) fn rolling_mean_m2(data: &[f64], period: usize) -> (Vec<f64>, Vec<f64>) {
+    let len = data.len();
+    let mut mean = vec![f64::NAN; len];
+    let mut m2 = vec![f64::NAN; len];
+    if period == 0 || len < period {
+        return (mean, m2);
+    }
+    let n = period as f64;
+    let mut shift = data[0];
+    let mut sum = CompensatedSum::default();
+    let mut sum_sq = CompensatedSum::default();
+    let mut last_bad: Option<usize> = None;
+    for i in 0..len {
+        if !data[i].is_finite() {
+            last_bad = Some(i);
+        }
+        let mut rebuild = i >= period && (i + 1) % period == 0;
+        if let Some(bad) = last_bad {
+            if i < bad + period {
+                continue;
+            }
+            last_bad = None;
+            rebuild = true;
+        }
+        if rebuild {
+            shift = data[i + 1 - period];
+            sum = CompensatedSum::default();
+            sum_sq = CompensatedSum::default();
+            for &v in &data[i + 1 - period..=i] {
+                sum.add(v - shift);
+                sum_sq.add((v - shift) * (v - shift));
+            }
+        } else {
+            let d = data[i] - shift;
+            sum.add(d);
+            sum_sq.add(d * d);
+            if i >= period {
+                let old = data[i - period] - shift;
+                sum.add(-old);
+                sum_sq.add(-old * old);
+            }
+        }
+        if i + 1 >= period {
+            let s = sum.value();
+            mean[i] = shift + s / n;
+            m2[i] = (sum_sq.value() - s * s / n).max(0.0);
+        }
+    }
+    (mean, m2)
+}
+
+// ── SMA ──
+
+/// Simple Moving Average. First `period-1` values are NaN.
+pub fn sma(data: &[f64], period: usize) -> Vec<f64> {
+    let mut result = rolling_sum(data, period);
+    for v in result.iter_mut() {
+        *v /= period as f64;
     }
     result
 }
@@ -464,12 +775,27 @@ pub fn sma(data: &[f64], period: usize) -> Vec<f64> {
 /// Exponential Moving Average. First `period-1` values are NaN;
 /// value at index `period-1` is seeded with SMA.
 pub fn ema(data: &[f64], period: usize) -> Vec<f64> {
+    ema_seeded(data, period, EmaSeed::SmaSeed)
+}
+
+/// EMA with a choice of seed: `SmaSeed` as [`ema`], `FirstValue` starts from `data[0]`
+/// and is defined from the first bar (TradingView's `ta.ema`).
+pub fn ema_seeded(data: &[f64], period: usize, seed: EmaSeed) -> Vec<f64> {
     let len = data.len();
     let mut result = vec![f64::NAN; len];
     if period == 0 || len < period {
         return result;
     }
     let multiplier = 2.0 / (period as f64 + 1.0);
+    if seed == EmaSeed::FirstValue {
+        let mut prev = data[0];
+        result[0] = prev;
+        for (out, &x) in result.iter_mut().zip(data).skip(1) {
+            prev = (x - prev) * multiplier + prev;
+            *out = prev;
+        }
+        return result;
+    }
     // Seed with SMA
     let seed: f64 = data[..period].iter().sum::<f64>() / period as f64;
     result[period - 1] = seed;
@@ -481,11 +807,30 @@ pub fn ema(data: &[f64], period: usize) -> Vec<f64> {
 
 /// EMA computed on a pre-computed slice (e.g., for signal line on MACD values).
 fn ema_on_slice(data: &[f64], period: usize) -> Vec<f64> {
+    ema_on_slice_seeded(data, period, EmaSeed::SmaSeed)
+}
+
+/// [`ema_on_slice`] with a choice of seed; `FirstValue` starts at the first non-NaN value.
+fn ema_on_slice_seeded(data: &[f64], period: usize, seed: EmaSeed) -> Vec<f64> {
     let len = data.len();
     let mut result = vec![f64::NAN; len];
     if period == 0 || len < period {
         return result;
     }
+    if seed == EmaSeed::FirstValue {
+        let Some(start) = data.iter().position(|v| !v.is_nan()) else { return result };
+        let multiplier = 2.0 / (period as f64 + 1.0);
+        let mut prev = data[start];
+        result[start] = prev;
+        for (out, &x) in result.iter_mut().zip(data).skip(start + 1) {
+            // NaN gaps carry the last value forward
+            if !x.is_nan() {
+                prev = (x - prev) * multiplier + prev;
+            }
+            *out = prev;
+        }
+        return result;
+    }
     // Find first non-NaN window of `period` consecutive values for seed
     let mut start = None;
     for i in 0..=len - period {
@@ -515,7 +860,9 @@ fn ema_on_slice(data: &[f64], period: usize) -> Vec<f64> {
 // ── RSI ──
 
 /// Relative Strength Index. First `period` values are NaN.
-pub fn rsi(close: &[f64], period: usize) -> Vec<f64> {
+/// `Wilder` smooths gains/losses with Wilder's RMA; `Sma` is Cutler's RSI, a plain
+/// average of the last `period` gains and losses.
+pub fn rsi(close: &[f64], period: usize, smoothing: RsiSmoothing) -> Vec<f64> {
     let len = close.len();
     let mut result = vec![f64::NAN; len];
     if period == 0 || len < period + 1 {
@@ -534,41 +881,52 @@ pub fn rsi(close: &[f64], period: usize) -> Vec<f64> {
         }
     }
 
-    // First average: simple average of first `period` changes
-    let mut avg_gain: f64 = gains[1..=period].iter().sum::<f64>() / period as f64;
-    let mut avg_loss: f64 = losses[1..=period].iter().sum::<f64>() / period as f64;
-
-    result[period] = if avg_loss == 0.0 {
-        100.0
-    } else {
-        100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
-    };
-
-    // Smoothed averages
-    for i in (period + 1)..len {
-        avg_gain = (avg_gain * (period as f64 - 1.0) + gains[i]) / period as f64;
-        avg_loss = (avg_loss * (period as f64 - 1.0) + losses[i]) / period as f64;
-        result[i] = if avg_loss == 0.0 {
+    let rsi_value = |avg_gain: f64, avg_loss: f64| {
+        if avg_loss == 0.0 {
             100.0
         } else {
             100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
-        };
+        }
+    };
+
+    // First average: simple average of first `period` changes
+    let mut sum_gain: f64 = gains[1..=period].iter().sum::<f64>();
+    let mut sum_loss: f64 = losses[1..=period].iter().sum::<f64>();
+    let mut avg_gain = sum_gain / period as f64;
+    let mut avg_loss = sum_loss / period as f64;
+    result[period] = rsi_value(avg_gain, avg_loss);
+
+    for i in (period + 1)..len {
+        match smoothing {
+            RsiSmoothing::Wilder => {
+                avg_gain = (avg_gain * (period as f64 - 1.0) + gains[i]) / period as f64;
+                avg_loss = (avg_loss * (period as f64 - 1.0) + losses[i]) / period as f64;
+            }
+            RsiSmoothing::Sma => {
+                sum_gain += gains[i] - gains[i - period];
+                sum_loss += losses[i] - losses[i - period];
+                avg_gain = sum_gain.max(0.0) / period as f64;
+                avg_loss = sum_loss.max(0.0) / period as f64;
+            }
+        }
+        result[i] = rsi_value(avg_gain, avg_loss);
     }
     result
 }
 
 // ── MACD ──
 
-/// MACD: returns (macd_line, signal_line, histogram).
+/// MACD: returns (macd_line, signal_line, histogram). `seed` applies to all three EMAs.
 pub fn macd(
     close: &[f64],
     fast_period: usize,
     slow_period: usize,
     signal_period: usize,
+    seed: EmaSeed,
 ) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
     let len = close.len();
-    let fast_ema = ema(close, fast_period);
-    let slow_ema = ema(close, slow_period);
+    let fast_ema = ema_seeded(close, fast_period, seed);
+    let slow_ema = ema_seeded(close, slow_period, seed);
 
     let mut macd_line = vec![f64::NAN; len];
     for i in 0..len {
@@ -577,7 +935,7 @@ pub fn macd(
         }
     }
 
-    let signal_line = ema_on_slice(&macd_line, signal_period);
+    let signal_line = ema_on_slice_seeded(&macd_line, signal_period, seed);
 
     let mut histogram = vec![f64::NAN; len];
     for i in 0..len {
@@ -589,48 +947,74 @@ pub fn macd(
     (macd_line, signal_line, histogram)
 }
 
+// ── PPO ──
+
+/// Percentage Price Oscillator: MACD normalised by the slow EMA,
+/// 100 × (fast EMA − slow EMA) / slow EMA, seeded like [`macd`].
+/// Returns (ppo_line, signal_line, histogram).
+pub fn ppo(
+    close: &[f64],
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+    seed: EmaSeed,
+) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let len = close.len();
+    let fast_ema = ema_seeded(close, fast_period, seed);
+    let slow_ema = ema_seeded(close, slow_period, seed);
+
+    let mut ppo_line = vec![f64::NAN; len];
+    for i in 0..len {
+        if !fast_ema[i].is_nan() && !slow_ema[i].is_nan() && slow_ema[i] != 0.0 {
+            ppo_line[i] = 100.0 * (fast_ema[i] - slow_ema[i]) / slow_ema[i];
+        }
+    }
+
+    let signal_line = ema_on_slice_seeded(&ppo_line, signal_period, seed);
+
+    let mut histogram = vec![f64::NAN; len];
+    for i in 0..len {
+        if !ppo_line[i].is_nan() && !signal_line[i].is_nan() {
+            histogram[i] = ppo_line[i] - signal_line[i];
+        }
+    }
+
+    (ppo_line, signal_line, histogram)
+}
+
 // ── Bollinger Bands ──
 
+/// Variance divisor: N for population (default, matches TradingView), N-1 for sample.
+/// Sample variance of a single value is undefined (0 / 0 → NaN); `compute_indicator`
+/// rejects that combination up front.
+fn variance_divisor(period: usize, sample: bool) -> f64 {
+    if sample {
+        period.saturating_sub(1) as f64
+    } else {
+        period as f64
+    }
+}
+
 /// Bollinger Bands: returns (upper, middle, lower).
+/// `sample` selects the N-1 standard deviation instead of population (N).
 pub fn bollinger_bands(
     close: &[f64],
     period: usize,
     std_dev_mult: f64,
+    sample: bool,
 ) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
     let len = close.len();
-    let middle = sma(close, period);
+    let divisor = variance_divisor(period, sample);
+    let (middle, m2) = rolling_mean_m2(close, period);
     let mut upper = vec![f64::NAN; len];
     let mut lower = vec![f64::NAN; len];
-
-    if len >= period {
-        // First window: compute sq_sum from scratch
-        let first_mean = middle[period - 1];
-        let mut sq_sum = close[..period]
-            .iter()
-            .map(|&v| { let d = v - first_mean; d * d })
-            .sum::<f64>();
-        let mut std_dev = (sq_sum / period as f64).sqrt();
-        upper[period - 1] = first_mean + std_dev_mult * std_dev;
-        lower[period - 1] = first_mean - std_dev_mult * std_dev;
-
-        // Subsequent windows: update sq_sum incrementally — O(N) total
-        for i in period..len {
-            let old_v = close[i - period];
-            let new_v = close[i];
-            let old_mean = middle[i - 1];
-            let new_mean = middle[i];
-            sq_sum += (new_v - old_v) * (new_v + old_v - new_mean - old_mean);
-            // Periodic full recompute every 256 bars to prevent numerical drift
-            if (i & 0xFF) == 0 {
-                sq_sum = close[i + 1 - period..=i]
-                    .iter()
-                    .map(|&v| { let d = v - new_mean; d * d })
-                    .sum::<f64>();
-            }
-            std_dev = (sq_sum.max(0.0) / period as f64).sqrt();
-            upper[i] = new_mean + std_dev_mult * std_dev;
-            lower[i] = new_mean - std_dev_mult * std_dev;
+    for i in period.saturating_sub(1)..len {
+        if m2[i].is_nan() {
+            continue;
         }
+        let std_dev = (m2[i] / divisor).sqrt();
+        upper[i] = middle[i] + std_dev_mult * std_dev;
+        lower[i] = middle[i] - std_dev_mult * std_dev;
     }
 
     (upper, middle, lower)
@@ -638,8 +1022,9 @@ pub fn bollinger_bands(
 
 // ── ATR ──
 
-/// Average True Range.
-pub fn atr(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<f64> {
+/// Average True Range. All smoothing methods start with an SMA of the first `period`
+/// True Ranges at index `period - 1`; they differ only in how later bars are averaged.
+pub fn atr(high: &[f64], low: &[f64], close: &[f64], period: usize, smoothing: AtrSmoothing) -> Vec<f64> {
     let len = high.len();
     let mut tr = vec![0.0f64; len];
 
@@ -652,18 +1037,23 @@ pub fn atr(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<f64>
         tr[i] = hl.max(hc).max(lc);
     }
 
-    // ATR is SMA of TR (using Wilder's smoothing: first value is SMA, then smoothed)
     let mut result = vec![f64::NAN; len];
-    if len < period {
+    if period == 0 || len < period {
         return result;
     }
-    let mut atr_val: f64 = tr[..period].iter().sum::<f64>() / period as f64;
-    result[period - 1] = atr_val;
-    for i in period..len {
-        atr_val = (atr_val * (period as f64 - 1.0) + tr[i]) / period as f64;
-        result[i] = atr_val;
+    match smoothing {
+        AtrSmoothing::Wilder => {
+            let mut atr_val: f64 = tr[..period].iter().sum::<f64>() / period as f64;
+            result[period - 1] = atr_val;
+            for i in period..len {
+                atr_val = (atr_val * (period as f64 - 1.0) + tr[i]) / period as f64;
+                result[i] = atr_val;
+            }
+            result
+        }
+        AtrSmoothing::Sma => sma(&tr, period),
+        AtrSmoothing::Ema => ema(&tr, period),
     }
-    result
 }
 
 // ── Stochastic ──
@@ -708,6 +1098,34 @@ pub fn stochastic(
     (slowed_k, d)
 }
 
+// ── Stochastic RSI ──
+
+/// Stochastic RSI — the stochastic formula applied to RSI(`period`) over `k_period` bars.
+/// %K is smoothed by SMA(`slowing`), %D = SMA(%K, `d_period`). Returns (%K, %D), both 0–100.
+/// The stochastic runs only on the valid part of the RSI series, so its warm-up windows
+/// never mix in the NaN prefix.
+pub fn stochastic_rsi(
+    close: &[f64],
+    period: usize,
+    k_period: usize,
+    d_period: usize,
+    slowing: usize,
+) -> (Vec<f64>, Vec<f64>) {
+    let len = close.len();
+    let mut k = vec![f64::NAN; len];
+    let mut d = vec![f64::NAN; len];
+    // rsi() leaves exactly `period` leading NaNs
+    if k_period == 0 || len < period + k_period {
+        return (k, d);
+    }
+    let rsi_vals = rsi(close, period, RsiSmoothing::Wilder);
+    let valid = &rsi_vals[period..];
+    let (sk, sd) = stochastic(valid, valid, valid, k_period, d_period, slowing);
+    k[period..].copy_from_slice(&sk);
+    d[period..].copy_from_slice(&sd);
+    (k, d)
+}
+
 /// SMA computed on a slice that may contain NaN values.
 fn sma_on_slice(data: &[f64], period: usize) -> Vec<f64> {
     let len = data.len();
@@ -806,10 +1224,12 @@ pub fn cci(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<f64>
 
     // Typical price
     let tp: Vec<f64> = (0..len).map(|i| (high[i] + low[i] + close[i]) / 3.0).collect();
+    let tp_mean = sma(&tp, period);
 
+    // Mean absolute deviation depends on each window's mean, so it can't be rolled
     for i in (period - 1)..len {
         let window = &tp[i + 1 - period..=i];
-        let mean = window.iter().sum::<f64>() / period as f64;
+        let mean = tp_mean[i];
         let mean_dev = window.iter().map(|v| (v - mean).abs()).sum::<f64>() / period as f64;
         result[i] = if mean_dev == 0.0 {
             0.0
@@ -932,9 +1352,10 @@ pub fn parabolic_sar(
     result
 }
 
-// ── WMA (helper for Hull MA) ──
+// ── WMA ──
 
-/// Weighted Moving Average. First `period-1` values are NaN.
+/// Linearly Weighted Moving Average (weights 1..=period, newest heaviest; MT5 `MODE_LWMA`).
+/// First `period-1` values are NaN. Also the building block of Hull MA.
 fn wma(data: &[f64], period: usize) -> Vec<f64> {
     let len = data.len();
     let mut result = vec![f64::NAN; len];
@@ -1268,7 +1689,8 @@ fn hull_ma(close: &[f64], period: usize) -> Vec<f64> {
 
 // ── Ichimoku ──
 
-/// Ichimoku Kinko Hyo. Returns extra map with keys: tenkan, kijun, senkou_a, senkou_b, chikou.
+/// Ichimoku Kinko Hyo. Returns extra map with keys: tenkan, kijun, senkou_a, senkou_b, chikou,
+/// plus cloud_top / cloud_bottom (the higher / lower of the two spans) for cloud rules.
 fn ichimoku(
     high: &[f64],
     low: &[f64],
@@ -1323,12 +1745,21 @@ fn ichimoku(
     // Chikou = close (no look-ahead for backtesting)
     let chikou = close.to_vec();
 
+    // NaN while either span is still warming up
+    let (cloud_top, cloud_bottom): (Vec<f64>, Vec<f64>) = senkou_a
+        .iter()
+        .zip(&senkou_b)
+        .map(|(&a, &b)| if a.is_nan() || b.is_nan() { (f64::NAN, f64::NAN) } else { (a.max(b), a.min(b)) })
+        .unzip();
+
     let mut map = HashMap::new();
     map.insert("tenkan".to_string(), tenkan);
     map.insert("kijun".to_string(), kijun);
     map.insert("senkou_a".to_string(), senkou_a);
     map.insert("senkou_b".to_string(), senkou_b);
     map.insert("chikou".to_string(), chikou);
+    map.insert("cloud_top".to_string(), cloud_top);
+    map.insert("cloud_bottom".to_string(), cloud_bottom);
     map
 }
 
@@ -1363,6 +1794,17 @@ fn keltner_channel(
     (upper, middle, lower)
 }
 
+// ── Donchian Channel ──
+
+/// Donchian Channel: returns (upper, middle, lower) where upper is the highest high and
+/// lower the lowest low of the last `period` bars (current bar included), middle their average.
+fn donchian_channel(high: &[f64], low: &[f64], period: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let upper = highest_in_range(high, period);
+    let lower = lowest_in_range(low, period);
+    let middle = upper.iter().zip(&lower).map(|(u, l)| (u + l) / 2.0).collect();
+    (upper, middle, lower)
+}
+
 // ── Laguerre RSI ──
 
 /// Laguerre RSI (0..1 range). Uses gamma smoothing parameter.
@@ -1403,10 +1845,17 @@ fn laguerre_rsi(close: &[f64], gamma: f64) -> Vec<f64> {
 
 // ── Linear Regression ──
 
-/// Linear Regression fitted value at last bar of rolling window.
-fn linear_regression(close: &[f64], period: usize) -> Vec<f64> {
+/// Linear Regression channel over a rolling window: the fitted value at the last bar,
+/// the slope (price change per bar), and bands at ± `std_dev_mult` population standard
+/// deviations of the window's residuals around the fitted line.
+///
+/// Returns `(fitted, slope, upper, lower)`.
+fn linear_regression(close: &[f64], period: usize, std_dev_mult: f64) -> (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>) {
     let len = close.len();
     let mut result = vec![f64::NAN; len];
+    let mut slope = vec![f64::NAN; len];
+    let mut upper = vec![f64::NAN; len];
+    let mut lower = vec![f64::NAN; len];
 
     for i in (period - 1)..len {
         let window = &close[i + 1 - period..=i];
@@ -1428,10 +1877,20 @@ fn linear_regression(close: &[f64], period: usize) -> Vec<f64> {
         if denom != 0.0 {
             let b = (n * sum_xy - sum_x * sum_y) / denom;
             let a = (sum_y - b * sum_x) / n;
-            result[i] = a + b * (n - 1.0);
-        }
-    }
-    result
+            let fitted = a + b * (n - 1.0);
+            let ss_res: f64 = window
+                .iter()
+                .enumerate()
+                .map(|(j, &y)| (y - (a + b * j as f64)).powi(2))
+                .sum();
+            let band = std_dev_mult * (ss_res / n).sqrt();
+            result[i] = fitted;
+            slope[i] = b;
+            upper[i] = fitted + band;
+            lower[i] = fitted - band;
+        }
+    }
+    (result, slope, upper, lower)
 }
 
 // ── Momentum ──
@@ -1455,9 +1914,10 @@ fn supertrend(
     close: &[f64],
     period: usize,
     multiplier: f64,
+    smoothing: AtrSmoothing,
 ) -> Vec<f64> {
     let len = high.len();
-    let atr_vals = atr(high, low, close, period);
+    let atr_vals = atr(high, low, close, period, smoothing);
     let mut result = vec![f64::NAN; len];
     let mut final_upper = vec![f64::NAN; len];
     let mut final_lower = vec![f64::NAN; len];
@@ -1539,21 +1999,44 @@ fn true_range(high: &[f64], low: &[f64], close: &[f64]) -> Vec<f64> {
     result
 }
 
-// ── Standard Deviation ──
+// ── Choppiness Index ──
 
-/// Rolling standard deviation of close over `period` bars.
-fn std_dev(close: &[f64], period: usize) -> Vec<f64> {
-    let len = close.len();
+/// Choppiness Index = 100 * log10(sum(TR, n) / (HH(n) - LL(n))) / log10(n).
+/// Near 100 = consolidation, near 0 = trending. The first value is at index `period`
+/// so every TR in the window uses a previous close. Zero-range windows yield NaN.
+fn choppiness(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<f64> {
+    let len = high.len();
     let mut result = vec![f64::NAN; len];
-    for i in (period - 1)..len {
-        let window = &close[i + 1 - period..=i];
-        let mean = window.iter().sum::<f64>() / period as f64;
-        let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / period as f64;
-        result[i] = variance.sqrt();
+    if period < 2 || len < period + 1 {
+        return result;
+    }
+    let tr = true_range(high, low, close);
+    let hh = highest_in_range(high, period);
+    let ll = lowest_in_range(low, period);
+    let log_n = (period as f64).log10();
+
+    let mut tr_sum: f64 = tr[1..=period].iter().sum();
+    for i in period..len {
+        if i > period {
+            tr_sum += tr[i] - tr[i - period];
+        }
+        let range = hh[i] - ll[i];
+        if range > 0.0 {
+            result[i] = 100.0 * (tr_sum / range).log10() / log_n;
+        }
     }
     result
 }
 
+// ── Standard Deviation ──
+
+/// Rolling standard deviation of close over `period` bars (population, or N-1 when `sample`).
+fn std_dev(close: &[f64], period: usize, sample: bool) -> Vec<f64> {
+    let divisor = variance_divisor(period, sample);
+    let (_, m2) = rolling_mean_m2(close, period);
+    m2.into_iter().map(|v| (v / divisor).sqrt()).collect()
+}
+
 // ── Reflex ──
 
 /// Ehlers Reflex indicator. Super Smoother + cycle measurement.
@@ -1564,13 +2047,9 @@ fn reflex(close: &[f64], period: usize) -> Vec<f64> {
         return result;
     }
 
-    let pi = std::f64::consts::PI;
     // SqReflex uses period * 0.5 as the half-period for the Super Smoother:
     // a1 = EXP(-1.414 * PI / (period * 0.5)) = EXP(-2.828 * PI / period)
-    let a1 = (-1.414 * pi / (period as f64 * 0.5)).exp();
-    let coeff2 = 2.0 * a1 * (1.414 * pi / (period as f64 * 0.5)).cos();
-    let coeff3 = -(a1 * a1);
-    let coeff1 = 1.0 - coeff2 - coeff3;
+    let (coeff1, coeff2, coeff3) = super_smoother_coeffs(period as f64 * 0.5);
 
     // Super Smoother filter
     let mut filt = vec![0.0f64; len];
@@ -1602,9 +2081,65 @@ fn reflex(close: &[f64], period: usize) -> Vec<f64> {
 
 // ── Pivots ──
 
-/// Classic pivot points from previous day's HLC.
+/// `[pp, r1, r2, r3, s1, s2, s3]` for one day's high, low and close.
+fn pivot_levels(pivot_type: PivotType, high: f64, low: f64, close: f64) -> [f64; 7] {
+    let range = high - low;
+    match pivot_type {
+        PivotType::Classic => {
+            let pp = (high + low + close) / 3.0;
+            [
+                pp,
+                2.0 * pp - low,
+                pp + range,
+                high + 2.0 * (pp - low),
+                2.0 * pp - high,
+                pp - range,
+                low - 2.0 * (high - pp),
+            ]
+        }
+        PivotType::Fibonacci => {
+            let pp = (high + low + close) / 3.0;
+            [
+                pp,
+                pp + 0.382 * range,
+                pp + 0.618 * range,
+                pp + range,
+                pp - 0.382 * range,
+                pp - 0.618 * range,
+                pp - range,
+            ]
+        }
+        PivotType::Camarilla => {
+            let pp = (high + low + close) / 3.0;
+            [
+                pp,
+                close + range * 1.1 / 12.0,
+                close + range * 1.1 / 6.0,
+                close + range * 1.1 / 4.0,
+                close - range * 1.1 / 12.0,
+                close - range * 1.1 / 6.0,
+                close - range * 1.1 / 4.0,
+            ]
+        }
+        PivotType::Woodie => {
+            let pp = (high + low + 2.0 * close) / 4.0;
+            [
+                pp,
+                2.0 * pp - low,
+                pp + range,
+                high + 2.0 * (pp - low),
+                2.0 * pp - high,
+                pp - range,
+                low - 2.0 * (high - pp),
+            ]
+        }
+    }
+}
+
+/// Pivot points from previous day's HLC, using the formula set of `pivot_type`.
+/// Days roll over at `session_start_hour` (see [`Candle::session_day`]).
 /// Returns extra map: pp, r1, r2, r3, s1, s2, s3.
-fn pivots(candles: &[Candle]) -> HashMap<String, Vec<f64>> {
+fn pivots(candles: &[Candle], pivot_type: PivotType, session_start_hour: u8) -> HashMap<String, Vec<f64>> {
     let len = candles.len();
     let mut pp = vec![f64::NAN; len];
     let mut r1 = vec![f64::NAN; len];
@@ -1625,13 +2160,9 @@ fn pivots(candles: &[Candle]) -> HashMap<String, Vec<f64>> {
     let mut day_started = false;
 
     for i in 0..len {
-        let current_date = if candles[i].datetime.len() >= 10 {
-            &candles[i].datetime[..10]
-        } else {
-            &candles[i].datetime
-        };
+        let current_date = candles[i].session_day(session_start_hour);
 
-        if current_date != prev_date {
+        if *current_date != *prev_date {
             if day_started {
                 prev_day_high = current_day_high;
                 prev_day_low = current_day_low;
@@ -1649,14 +2180,14 @@ fn pivots(candles: &[Candle]) -> HashMap<String, Vec<f64>> {
         }
 
         if !prev_day_high.is_nan() {
-            let pivot = (prev_day_high + prev_day_low + prev_day_close) / 3.0;
-            pp[i] = pivot;
-            r1[i] = 2.0 * pivot - prev_day_low;
-            s1[i] = 2.0 * pivot - prev_day_high;
-            r2[i] = pivot + (prev_day_high - prev_day_low);
-            s2[i] = pivot - (prev_day_high - prev_day_low);
-            r3[i] = prev_day_high + 2.0 * (pivot - prev_day_low);
-            s3[i] = prev_day_low - 2.0 * (prev_day_high - pivot);
+            let levels = pivot_levels(pivot_type, prev_day_high, prev_day_low, prev_day_close);
+            pp[i] = levels[0];
+            r1[i] = levels[1];
+            r2[i] = levels[2];
+            r3[i] = levels[3];
+            s1[i] = levels[4];
+            s2[i] = levels[5];
+            s3[i] = levels[6];
         }
     }
 
@@ -1714,13 +2245,13 @@ fn vortex(high: &[f64], low: &[f64], close: &[f64], period: usize) -> (Vec<f64>,
         tr[i] = hl.max(hc).max(lc);
     }
 
+    let sum_vm_plus = rolling_sum(&vm_plus, period);
+    let sum_vm_minus = rolling_sum(&vm_minus, period);
+    let sum_tr = rolling_sum(&tr, period);
     for i in period..len {
-        let sum_vm_plus: f64 = vm_plus[(i + 1 - period)..=i].iter().sum();
-        let sum_vm_minus: f64 = vm_minus[(i + 1 - period)..=i].iter().sum();
-        let sum_tr: f64 = tr[(i + 1 - period)..=i].iter().sum();
-        if sum_tr != 0.0 {
-            vi_plus[i] = sum_vm_plus / sum_tr;
-            vi_minus[i] = sum_vm_minus / sum_tr;
+        if sum_tr[i] != 0.0 {
+            vi_plus[i] = sum_vm_plus[i] / sum_tr[i];
+            vi_minus[i] = sum_vm_minus[i] / sum_tr[i];
         }
     }
 
@@ -1734,35 +2265,414 @@ pub fn avg_volume(volume: &[f64], period: usize) -> Vec<f64> {
     sma(volume, period)
 }
 
-// ── BBWidthRatio ──
+// ── TRIX ──
 
-/// Bollinger Bands Width Ratio = (2 * std_dev_mult * StdDev) / SMA.
-/// Matches SqBBWidthRatio. Measures relative volatility as a fraction of price.
-pub fn bb_width_ratio(close: &[f64], period: usize, std_dev_mult: f64) -> Vec<f64> {
+/// TRIX — 1-bar percent rate of change of a triple-smoothed EMA of close.
+/// First value at index 3 × (period - 1) + 1.
+pub fn trix(close: &[f64], period: usize) -> Vec<f64> {
     let len = close.len();
     let mut result = vec![f64::NAN; len];
-    if period == 0 || len < period {
-        return result;
-    }
-    for i in (period - 1)..len {
-        let window = &close[i + 1 - period..=i];
-        let mean = window.iter().sum::<f64>() / period as f64;
-        let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / period as f64;
-        let sd = variance.sqrt();
-        if mean.abs() > 1e-10 {
-            result[i] = (2.0 * std_dev_mult * sd) / mean;
+    let e1 = ema(close, period);
+    let e2 = ema_on_slice(&e1, period);
+    let e3 = ema_on_slice(&e2, period);
+    for i in 1..len {
+        if e3[i].is_finite() && e3[i - 1].is_finite() && e3[i - 1] != 0.0 {
+            result[i] = (e3[i] - e3[i - 1]) / e3[i - 1] * 100.0;
         }
     }
     result
 }
 
-// ── EfficiencyRatio ──
+// ── Accumulation/Distribution & Chaikin Money Flow ──
 
-/// Kaufman Efficiency Ratio (0..1). Matches SqEfficiencyRatio.
-/// ER = |price[i] - price[i-period]| / sum(|price[j] - price[j-1]|)
-pub fn efficiency_ratio(close: &[f64], period: usize) -> Vec<f64> {
-    let len = close.len();
-    let mut result = vec![f64::NAN; len];
+/// Money-flow volume per bar: CLV × volume, where the close-location value is
+/// CLV = ((close - low) - (high - close)) / (high - low). Zero-range bars get CLV = 0.
+fn money_flow_volume(high: &[f64], low: &[f64], close: &[f64], volume: &[f64]) -> Vec<f64> {
+    (0..close.len())
+        .map(|i| {
+            let range = high[i] - low[i];
+            let clv = if range.abs() < 1e-10 {
+                0.0
+            } else {
+                ((close[i] - low[i]) - (high[i] - close[i])) / range
+            };
+            clv * volume[i]
+        })
+        .collect()
+}
+
+/// Accumulation/Distribution line — running sum of money-flow volume from the first bar.
+pub fn accumulation_distribution(high: &[f64], low: &[f64], close: &[f64], volume: &[f64]) -> Vec<f64> {
+    let mut total = 0.0;
+    money_flow_volume(high, low, close, volume)
+        .into_iter()
+        .map(|mfv| {
+            total += mfv;
+            total
+        })
+        .collect()
+}
+
+/// Chaikin Money Flow — sum of money-flow volume over `period` bars divided by the
+/// volume over the same bars. Ranges from -1 to 1; NaN when the window has no volume.
+pub fn chaikin_money_flow(high: &[f64], low: &[f64], close: &[f64], volume: &[f64], period: usize) -> Vec<f64> {
+    let len = close.len();
+    let mut result = vec![f64::NAN; len];
+    if period == 0 || len < period {
+        return result;
+    }
+    let mfv = money_flow_volume(high, low, close, volume);
+    let mut mfv_sum: f64 = mfv[..period - 1].iter().sum();
+    let mut vol_sum: f64 = volume[..period - 1].iter().sum();
+    for i in (period - 1)..len {
+        mfv_sum += mfv[i];
+        vol_sum += volume[i];
+        if i >= period {
+            mfv_sum -= mfv[i - period];
+            vol_sum -= volume[i - period];
+        }
+        if vol_sum > 0.0 {
+            result[i] = mfv_sum / vol_sum;
+        }
+    }
+    result
+}
+
+// ── Force Index & Ease of Movement ──
+
+/// Force Index — EMA over `period` of (close - previous close) × volume.
+/// First value at index `period`.
+pub fn force_index(close: &[f64], volume: &[f64], period: usize) -> Vec<f64> {
+    let raw: Vec<f64> = (0..close.len())
+        .map(|i| if i == 0 { f64::NAN } else { (close[i] - close[i - 1]) * volume[i] })
+        .collect();
+    ema_on_slice(&raw, period)
+}
+
+/// Ease of Movement — SMA over `period` of the midpoint move divided by the box ratio
+/// `(volume / scale) / (high - low)`. Zero-range and zero-volume bars contribute 0.
+/// First value at index `period`.
+pub fn ease_of_movement(high: &[f64], low: &[f64], volume: &[f64], period: usize, scale: f64) -> Vec<f64> {
+    let len = high.len();
+    let mut result = vec![f64::NAN; len];
+    if len < 2 {
+        return result;
+    }
+    let emv: Vec<f64> = (1..len)
+        .map(|i| {
+            let range = high[i] - low[i];
+            if range.abs() < 1e-10 || volume[i] <= 0.0 {
+                return 0.0;
+            }
+            let distance = (high[i] + low[i]) / 2.0 - (high[i - 1] + low[i - 1]) / 2.0;
+            distance * range * scale / volume[i]
+        })
+        .collect();
+    for (i, v) in sma(&emv, period).into_iter().enumerate() {
+        result[i + 1] = v;
+    }
+    result
+}
+
+// ── VWMA & ZLEMA ──
+
+/// Volume-Weighted Moving Average — `sum(close × volume) / sum(volume)` over `period`.
+/// Windows with no volume fall back to the simple average of close.
+/// First `period-1` values are NaN.
+pub fn vwma(close: &[f64], volume: &[f64], period: usize) -> Vec<f64> {
+    let weighted: Vec<f64> = close.iter().zip(volume).map(|(c, v)| c * v).collect();
+    let pv_sum = rolling_sum(&weighted, period);
+    let vol_sum = rolling_sum(volume, period);
+    let mean = sma(close, period);
+    (0..close.len())
+        .map(|i| if vol_sum[i] > 0.0 { pv_sum[i] / vol_sum[i] } else { mean[i] })
+        .collect()
+}
+
+/// Zero-Lag EMA — EMA over `period` of `close + (close - close[lag])` with
+/// `lag = (period - 1) / 2`, removing most of the EMA's lag on trends.
+/// First value at index `lag + period - 1`.
+pub fn zlema(close: &[f64], period: usize) -> Vec<f64> {
+    let lag = period.saturating_sub(1) / 2;
+    let delagged: Vec<f64> = (0..close.len())
+        .map(|i| if i < lag { f64::NAN } else { 2.0 * close[i] - close[i - lag] })
+        .collect();
+    ema_on_slice(&delagged, period)
+}
+
+// ── Elder Impulse ──
+
+/// Elder Impulse System — `+1` when both the EMA over `period` and the MACD histogram
+/// rise versus the prior bar, `-1` when both fall, `0` otherwise. NaN until both
+/// series have a previous value.
+pub fn elder_impulse(close: &[f64], period: usize, fast: usize, slow: usize, signal: usize) -> Vec<f64> {
+    let trend = ema(close, period);
+    let (_, _, hist) = macd(close, fast, slow, signal, EmaSeed::SmaSeed);
+    let mut result = vec![f64::NAN; close.len()];
+    for i in 1..close.len() {
+        let (dt, dh) = (trend[i] - trend[i - 1], hist[i] - hist[i - 1]);
+        if dt.is_nan() || dh.is_nan() {
+            continue;
+        }
+        result[i] = if dt > 0.0 && dh > 0.0 {
+            1.0
+        } else if dt < 0.0 && dh < 0.0 {
+            -1.0
+        } else {
+            0.0
+        };
+    }
+    result
+}
+
+// ── Mass Index ──
+
+/// Mass Index (Dorsey) — sum over `period` of `EMA(range) / EMA(EMA(range))`, with
+/// `range = high - low` and both EMAs over `ema_period`. Sits near `period` in steady
+/// ranges; a range expansion bulges it above (classically 27 then back below 26.5).
+/// First value at index `2 × (ema_period - 1) + period - 1`.
+pub fn mass_index(high: &[f64], low: &[f64], period: usize, ema_period: usize) -> Vec<f64> {
+    let len = high.len();
+    let mut result = vec![f64::NAN; len];
+    let range: Vec<f64> = high.iter().zip(low).map(|(h, l)| h - l).collect();
+    let single = ema(&range, ema_period);
+    let double = ema_on_slice(&single, ema_period);
+    let first = 2 * ema_period.saturating_sub(1);
+    if ema_period == 0 || first >= len {
+        return result;
+    }
+    // A flat double EMA means no range at all — count it as no expansion
+    let ratio: Vec<f64> = (first..len)
+        .map(|i| if double[i].abs() < 1e-12 { 1.0 } else { single[i] / double[i] })
+        .collect();
+    for (i, v) in rolling_sum(&ratio, period).into_iter().enumerate() {
+        result[first + i] = v;
+    }
+    result
+}
+
+// ── Klinger Volume Oscillator ──
+
+/// Klinger Volume Oscillator — returns `(kvo, signal)`.
+///
+/// Volume force is `volume × |2·(dm/cm − 1)| × trend × 100`, with `trend` = ±1 from the
+/// change in `(high + low + close) / 3` (unchanged keeps the prior trend), `dm = high − low`,
+/// and `cm` accumulating `dm` while the trend holds and restarting from the prior bar's
+/// `dm` on a flip. Bar 0 has no prior typical price; bar 1 seeds `cm = dm`, so its force
+/// is 0. KVO = EMA(VF, fast) − EMA(VF, slow); signal = EMA(KVO, signal).
+pub fn klinger(
+    high: &[f64],
+    low: &[f64],
+    close: &[f64],
+    volume: &[f64],
+    fast: usize,
+    slow: usize,
+    signal: usize,
+) -> (Vec<f64>, Vec<f64>) {
+    let len = close.len();
+    let typical = |i: usize| (high[i] + low[i] + close[i]) / 3.0;
+    let mut force = vec![f64::NAN; len];
+    let mut trend = 0.0;
+    let mut cm = 0.0;
+    for i in 1..len {
+        let dm = high[i] - low[i];
+        let (tp, prev_tp) = (typical(i), typical(i - 1));
+        let bar_trend = if tp > prev_tp {
+            1.0
+        } else if tp < prev_tp {
+            -1.0
+        } else if trend == 0.0 {
+            1.0
+        } else {
+            trend
+        };
+        cm = if trend == 0.0 {
+            dm
+        } else if bar_trend == trend {
+            cm + dm
+        } else {
+            (high[i - 1] - low[i - 1]) + dm
+        };
+        trend = bar_trend;
+        force[i] = if cm > 0.0 { volume[i] * (2.0 * (dm / cm - 1.0)).abs() * trend * 100.0 } else { 0.0 };
+    }
+
+    let fast_ema = ema_on_slice(&force, fast);
+    let slow_ema = ema_on_slice(&force, slow);
+    let kvo: Vec<f64> = fast_ema.iter().zip(&slow_ema).map(|(f, s)| f - s).collect();
+    let signal_line = ema_on_slice(&kvo, signal);
+    (kvo, signal_line)
+}
+
+// ── Super Smoother / Roofing Filter ──
+
+/// Two-pole Super Smoother coefficients `(c1, c2, c3)` for a critical period:
+/// `filt[i] = c1 * (x[i] + x[i-1]) / 2 + c2 * filt[i-1] + c3 * filt[i-2]`.
+fn super_smoother_coeffs(period: f64) -> (f64, f64, f64) {
+    let pi = std::f64::consts::PI;
+    let a1 = (-1.414 * pi / period).exp();
+    let coeff2 = 2.0 * a1 * (1.414 * pi / period).cos();
+    let coeff3 = -(a1 * a1);
+    (1.0 - coeff2 - coeff3, coeff2, coeff3)
+}
+
+/// Runs the Super Smoother recursion over `src`, seeding the first two values
+/// with the input itself. Callers mask the warmup bars.
+fn super_smooth(src: &[f64], period: usize) -> Vec<f64> {
+    let len = src.len();
+    let (coeff1, coeff2, coeff3) = super_smoother_coeffs(period as f64);
+    let mut filt = src.to_vec();
+    for i in 2..len {
+        filt[i] = coeff1 * (src[i] + src[i - 1]) / 2.0 + coeff2 * filt[i - 1] + coeff3 * filt[i - 2];
+    }
+    filt
+}
+
+/// Ehlers Super Smoother — two-pole Butterworth-style low-pass filter on close.
+/// The first two bars only seed the recursion and are NaN.
+pub fn super_smoother(close: &[f64], period: usize) -> Vec<f64> {
+    let mut result = super_smooth(close, period.max(1));
+    for v in result.iter_mut().take(2) {
+        *v = f64::NAN;
+    }
+    result
+}
+
+/// Ehlers Roofing Filter — two-pole high-pass at `hp_period` to remove the trend,
+/// followed by a Super Smoother at `period` to remove noise. Oscillates around zero.
+/// The first two bars only seed the recursion and are NaN.
+pub fn roofing_filter(close: &[f64], hp_period: usize, period: usize) -> Vec<f64> {
+    let len = close.len();
+    let pi = std::f64::consts::PI;
+    let w = 0.707 * 2.0 * pi / hp_period.max(1) as f64;
+    let alpha1 = (w.cos() + w.sin() - 1.0) / w.cos();
+    let c_hp = (1.0 - alpha1 / 2.0) * (1.0 - alpha1 / 2.0);
+
+    let mut hp = vec![0.0f64; len];
+    for i in 2..len {
+        hp[i] = c_hp * (close[i] - 2.0 * close[i - 1] + close[i - 2]) + 2.0 * (1.0 - alpha1) * hp[i - 1]
+            - (1.0 - alpha1) * (1.0 - alpha1) * hp[i - 2];
+    }
+
+    let mut result = super_smooth(&hp, period.max(1));
+    for v in result.iter_mut().take(2) {
+        *v = f64::NAN;
+    }
+    result
+}
+
+// ── Connors RSI ──
+
+/// Consecutive up/down close count: +n after n higher closes, -n after n lower
+/// closes, 0 on an unchanged close.
+fn streak(close: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0f64; close.len()];
+    for i in 1..close.len() {
+        result[i] = if close[i] > close[i - 1] {
+            result[i - 1].max(0.0) + 1.0
+        } else if close[i] < close[i - 1] {
+            result[i - 1].min(0.0) - 1.0
+        } else {
+            0.0
+        };
+    }
+    result
+}
+
+/// Percentage (0–100) of the previous `lookback` values strictly below the current one.
+/// NaN until a full window of finite values is available.
+fn percent_rank(values: &[f64], lookback: usize) -> Vec<f64> {
+    let len = values.len();
+    let mut result = vec![f64::NAN; len];
+    if lookback == 0 {
+        return result;
+    }
+    for i in lookback..len {
+        let window = &values[i - lookback..i];
+        if !values[i].is_finite() || window.iter().any(|v| !v.is_finite()) {
+            continue;
+        }
+        let below = window.iter().filter(|&&v| v < values[i]).count();
+        result[i] = 100.0 * below as f64 / lookback as f64;
+    }
+    result
+}
+
+/// Connors RSI — mean of RSI(close, `rsi_period`), RSI(streak, `streak_period`) and
+/// PercentRank(ROC(1), `rank_period`). Range 0–100.
+/// First value at index max(rsi_period, streak_period, rank_period + 1).
+pub fn connors_rsi(close: &[f64], rsi_period: usize, streak_period: usize, rank_period: usize) -> Vec<f64> {
+    let price_rsi = rsi(close, rsi_period, RsiSmoothing::Wilder);
+    let streak_rsi = rsi(&streak(close), streak_period, RsiSmoothing::Wilder);
+    let rank = percent_rank(&roc(close, 1), rank_period);
+    (0..close.len())
+        .map(|i| {
+            if price_rsi[i].is_finite() && streak_rsi[i].is_finite() && rank[i].is_finite() {
+                (price_rsi[i] + streak_rsi[i] + rank[i]) / 3.0
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+// ── DPO ──
+
+/// Detrended Price Oscillator — close minus the SMA displaced `period / 2 + 1` bars
+/// into the past. Only uses the already-closed SMA, so there is no look-ahead.
+/// First value at index period + period / 2.
+pub fn dpo(close: &[f64], period: usize) -> Vec<f64> {
+    let len = close.len();
+    let mut result = vec![f64::NAN; len];
+    let shift = period / 2 + 1;
+    let ma = sma(close, period);
+    for i in shift..len {
+        if ma[i - shift].is_finite() {
+            result[i] = close[i] - ma[i - shift];
+        }
+    }
+    result
+}
+
+// ── Coppock Curve ──
+
+/// Coppock Curve — WMA over `period` of ROC(`slow_period`) + ROC(`fast_period`).
+/// NaN until both ROCs and the full WMA window are valid (index max(fast, slow) + period - 1).
+pub fn coppock(close: &[f64], period: usize, fast_period: usize, slow_period: usize) -> Vec<f64> {
+    let fast = roc(close, fast_period);
+    let slow = roc(close, slow_period);
+    let sum: Vec<f64> = fast.iter().zip(&slow).map(|(f, s)| f + s).collect();
+    wma(&sum, period)
+}
+
+// ── BBWidthRatio ──
+
+/// Bollinger Bands Width Ratio = (2 * std_dev_mult * StdDev) / SMA.
+/// Matches SqBBWidthRatio. Measures relative volatility as a fraction of price.
+pub fn bb_width_ratio(close: &[f64], period: usize, std_dev_mult: f64) -> Vec<f64> {
+    let len = close.len();
+    let mut result = vec![f64::NAN; len];
+    if period == 0 || len < period {
+        return result;
+    }
+    let (means, m2) = rolling_mean_m2(close, period);
+    for i in (period - 1)..len {
+        let mean = means[i];
+        let sd = (m2[i] / period as f64).sqrt();
+        if mean.abs() > 1e-10 {
+            result[i] = (2.0 * std_dev_mult * sd) / mean;
+        }
+    }
+    result
+}
+
+// ── EfficiencyRatio ──
+
+/// Kaufman Efficiency Ratio (0..1). Matches SqEfficiencyRatio.
+/// ER = |price[i] - price[i-period]| / sum(|price[j] - price[j-1]|)
+pub fn efficiency_ratio(close: &[f64], period: usize) -> Vec<f64> {
+    let len = close.len();
+    let mut result = vec![f64::NAN; len];
     if period == 0 || len <= period {
         return result;
     }
@@ -1865,7 +2775,7 @@ pub fn qqe(close: &[f64], rsi_period: usize, sf: usize, wf: f64) -> (Vec<f64>, V
         return (empty.clone(), empty);
     }
 
-    let rsi_vals = rsi(close, rsi_period);
+    let rsi_vals = rsi(close, rsi_period, RsiSmoothing::Wilder);
     let wilders = rsi_period * 2 - 1;
     let sf_alpha = 2.0 / (sf as f64 + 1.0);
     let w_alpha = 2.0 / (wilders as f64 + 1.0);
@@ -1944,17 +2854,17 @@ pub fn qqe(close: &[f64], rsi_period: usize, sf: usize, wf: f64) -> (Vec<f64>, V
 // ── SchaffTrendCycle ──
 
 /// Schaff Trend Cycle. Matches SqSchaffTrendCycle.
-/// MACD → double stochastic with EMA smoothing (alpha=0.5).
-pub fn schaff_trend_cycle(close: &[f64], period: usize, fast: usize, slow: usize) -> Vec<f64> {
+/// MACD → double stochastic, each pass smoothed by an EMA over `smoothing` bars
+/// (SQX default 3, i.e. alpha = 0.5). Output is bounded to 0–100.
+pub fn schaff_trend_cycle(close: &[f64], period: usize, fast: usize, slow: usize, smoothing: usize) -> Vec<f64> {
     let len = close.len();
-    let mut result = vec![f64::NAN; len];
     if len < slow + period {
-        return result;
+        return vec![f64::NAN; len];
     }
 
     let fast_ema = ema(close, fast);
     let slow_ema = ema(close, slow);
-    let alpha = 0.5_f64;
+    let alpha = 2.0 / (1.0 + smoothing.max(1) as f64);
 
     // MACD line
     let mut macd_line = vec![f64::NAN; len];
@@ -1964,60 +2874,41 @@ pub fn schaff_trend_cycle(close: &[f64], period: usize, fast: usize, slow: usize
         }
     }
 
-    // First stochastic of MACD
-    let mut fk1 = vec![f64::NAN; len];
-    let mut fd1 = vec![f64::NAN; len];
-    for i in 0..len {
-        if macd_line[i].is_nan() || i + 1 < period {
-            continue;
-        }
-        let start = i + 1 - period;
-        let mut lo = f64::INFINITY;
-        let mut hi = f64::NEG_INFINITY;
-        for j in start..=i {
-            if !macd_line[j].is_nan() {
-                lo = lo.min(macd_line[j]);
-                hi = hi.max(macd_line[j]);
-            }
-        }
-        let range = hi - lo;
-        fk1[i] = if range > 1e-10 { 100.0 * (macd_line[i] - lo) / range } else {
-            if fd1[i.saturating_sub(1)].is_nan() { 0.0 } else { fk1[i - 1] }
-        };
-        fd1[i] = if fd1[i.saturating_sub(1)].is_nan() || i == 0 {
-            fk1[i]
-        } else {
-            fd1[i - 1] + alpha * (fk1[i] - fd1[i - 1])
-        };
-    }
+    // First stochastic of MACD, then a second stochastic of its smoothed %D
+    let fd1 = smoothed_stochastic(&macd_line, period, alpha);
+    smoothed_stochastic(&fd1, period, alpha)
+}
 
-    // Second stochastic of FD1
-    let mut fk2 = vec![f64::NAN; len];
+/// One STC pass: %K of `src` over `period` bars, EMA-smoothed with `alpha`.
+/// A flat window carries the previous %K forward.
+fn smoothed_stochastic(src: &[f64], period: usize, alpha: f64) -> Vec<f64> {
+    let len = src.len();
+    let mut fk = vec![f64::NAN; len];
+    let mut fd = vec![f64::NAN; len];
     for i in 0..len {
-        if fd1[i].is_nan() || i + 1 < period {
+        if src[i].is_nan() || i + 1 < period {
             continue;
         }
         let start = i + 1 - period;
         let mut lo = f64::INFINITY;
         let mut hi = f64::NEG_INFINITY;
-        for j in start..=i {
-            if !fd1[j].is_nan() {
-                lo = lo.min(fd1[j]);
-                hi = hi.max(fd1[j]);
+        for &v in &src[start..=i] {
+            if !v.is_nan() {
+                lo = lo.min(v);
+                hi = hi.max(v);
             }
         }
         let range = hi - lo;
-        fk2[i] = if range > 1e-10 { 100.0 * (fd1[i] - lo) / range } else {
-            if result[i.saturating_sub(1)].is_nan() { 0.0 } else { fk2[i - 1] }
+        fk[i] = if range > 1e-10 { 100.0 * (src[i] - lo) / range } else {
+            if fd[i.saturating_sub(1)].is_nan() { 0.0 } else { fk[i - 1] }
         };
-        result[i] = if result[i.saturating_sub(1)].is_nan() || i == 0 {
-            fk2[i]
+        fd[i] = if fd[i.saturating_sub(1)].is_nan() || i == 0 {
+            fk[i]
         } else {
-            result[i - 1] + alpha * (fk2[i] - result[i - 1])
+            fd[i - 1] + alpha * (fk[i] - fd[i - 1])
         };
     }
-
-    result
+    fd
 }
 
 // ══════════════════════════════════════════════════════════════
@@ -2056,6 +2947,16 @@ mod tests {
         assert_approx(result[9], 9.0, 1e-10, "SMA[9]");
     }
 
+    #[test]
+    fn test_wma_basic() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 6.0];
+        let result = wma(&data, 3);
+        assert!(result[1].is_nan());
+        // (1*1 + 2*2 + 3*3) / 6
+        assert_approx(result[2], 14.0 / 6.0, 1e-10, "WMA[2]");
+        assert_approx(result[4], (3.0 + 8.0 + 18.0) / 6.0, 1e-10, "WMA[4]");
+    }
+
     #[test]
     fn test_ema_basic() {
         let data = vec![22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29];
@@ -2075,7 +2976,7 @@ mod tests {
             44.0, 44.34, 44.09, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
             45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
         ];
-        let result = rsi(&data, 14);
+        let result = rsi(&data, 14, RsiSmoothing::Wilder);
         // First 14 values should be NaN
         for i in 0..14 {
             assert!(result[i].is_nan(), "RSI[{}] should be NaN", i);
@@ -2087,7 +2988,7 @@ mod tests {
     #[test]
     fn test_macd_basic() {
         let data: Vec<f64> = (1..=50).map(|i| 100.0 + (i as f64) * 0.5).collect();
-        let (macd_line, signal, hist) = macd(&data, 12, 26, 9);
+        let (macd_line, signal, hist) = macd(&data, 12, 26, 9, EmaSeed::SmaSeed);
         assert_eq!(macd_line.len(), 50);
         // MACD line should have valid values starting from index 25 (slow period - 1)
         assert!(macd_line[25].is_finite());
@@ -2101,10 +3002,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ppo_matches_macd_sign() {
+        // Oscillating series with a drift so both lines cross zero several times
+        let data: Vec<f64> = (0..200)
+            .map(|i| 100.0 + 0.05 * i as f64 + 8.0 * (i as f64 / 15.0).sin())
+            .collect();
+        let (macd_line, macd_signal, _) = macd(&data, 12, 26, 9, EmaSeed::SmaSeed);
+        let (ppo_line, ppo_signal, ppo_hist) = ppo(&data, 12, 26, 9, EmaSeed::SmaSeed);
+        assert!(ppo_line[24].is_nan() && ppo_line[25].is_finite());
+        for i in 25..200 {
+            assert_eq!(ppo_line[i].signum(), macd_line[i].signum(), "line sign at {i}");
+            // PPO is the MACD line rescaled by the slow EMA
+            let slow = ema(&data, 26)[i];
+            assert_approx(ppo_line[i], 100.0 * macd_line[i] / slow, 1e-9, "ppo vs macd");
+        }
+        for i in 33..200 {
+            assert!(ppo_signal[i].is_finite() && macd_signal[i].is_finite());
+            assert_approx(ppo_hist[i], ppo_line[i] - ppo_signal[i], 1e-12, "ppo histogram");
+        }
+        assert!(ppo_line[25..].iter().any(|v| *v > 0.0) && ppo_line[25..].iter().any(|v| *v < 0.0));
+
+        // Scale-free: the same shape at 100× the price level gives the same PPO
+        let scaled: Vec<f64> = data.iter().map(|v| v * 100.0).collect();
+        let (scaled_line, _, _) = ppo(&scaled, 12, 26, 9, EmaSeed::SmaSeed);
+        assert_approx(scaled_line[150], ppo_line[150], 1e-9, "scaled ppo");
+    }
+
+    /// Deterministic wavy price series around `level` with bar-to-bar noise.
+    fn wavy_series(len: usize, level: f64, amp: f64) -> Vec<f64> {
+        (0..len)
+            .map(|i| {
+                let x = i as f64;
+                level + amp * ((x / 37.0).sin() + 0.3 * (x * 1.7).sin() + 0.1 * (x * 12.9).cos()) + x * amp * 1e-3
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_helpers_match_naive_windows() {
+        // Recompute every window from scratch and compare against the one-pass helpers,
+        // at FX-like and index-like price levels, over long series where drift would show.
+        for (level, amp) in [(1.1, 0.01), (40_000.0, 500.0)] {
+            let data = wavy_series(20_000, level, amp);
+            for period in [2, 20, 200] {
+                let sums = rolling_sum(&data, period);
+                let (means, m2) = rolling_mean_m2(&data, period);
+                let sd = std_dev(&data, period, false);
+                let (upper, middle, _) = bollinger_bands(&data, period, 2.0, true);
+                assert!(sums[period - 2].is_nan() && sd[period - 2].is_nan() && upper[period - 2].is_nan());
+                for i in (period - 1)..data.len() {
+                    let window = &data[i + 1 - period..=i];
+                    let sum: f64 = window.iter().sum();
+                    let mean = sum / period as f64;
+                    let naive_m2: f64 = window.iter().map(|v| (v - mean).powi(2)).sum();
+                    let tol = level * 1e-12;
+                    assert_approx(sums[i], sum, tol * period as f64, "rolling sum");
+                    assert_approx(means[i], mean, tol, "rolling mean");
+                    assert_approx(middle[i], mean, tol, "bollinger middle");
+                    // M2 cancels Σx² against (Σx)²/N, so compare the deviations it implies
+                    assert_approx(m2[i].sqrt(), naive_m2.sqrt(), tol * period as f64, "rolling m2");
+                    assert_approx(sd[i], (naive_m2 / period as f64).sqrt(), tol, "std_dev");
+                    let naive_upper = mean + 2.0 * (naive_m2 / (period - 1) as f64).sqrt();
+                    assert_approx(upper[i], naive_upper, tol * 2.0, "bollinger upper (sample)");
+                }
+            }
+        }
+
+        // CCI keeps its per-window mean deviation but takes the mean from the rolling sum
+        let high = wavy_series(5_000, 100.0, 2.0);
+        let low: Vec<f64> = high.iter().map(|h| h - 0.5).collect();
+        let close: Vec<f64> = high.iter().map(|h| h - 0.2).collect();
+        let out = cci(&high, &low, &close, 20);
+        let tp: Vec<f64> = (0..high.len()).map(|i| (high[i] + low[i] + close[i]) / 3.0).collect();
+        for i in 19..tp.len() {
+            let window = &tp[i - 19..=i];
+            let mean = window.iter().sum::<f64>() / 20.0;
+            let mean_dev = window.iter().map(|v| (v - mean).abs()).sum::<f64>() / 20.0;
+            assert_approx(out[i], (tp[i] - mean) / (0.015 * mean_dev), 1e-8, "cci");
+        }
+    }
+
     #[test]
     fn test_bollinger_bands_basic() {
         let data = vec![20.0; 20]; // Constant price
-        let (upper, middle, lower) = bollinger_bands(&data, 20, 2.0);
+        let (upper, middle, lower) = bollinger_bands(&data, 20, 2.0, false);
         // For constant data, std dev = 0, so upper = middle = lower
         assert_approx(middle[19], 20.0, 1e-10, "BB middle");
         assert_approx(upper[19], 20.0, 1e-10, "BB upper (no volatility)");
@@ -2116,13 +3098,213 @@ mod tests {
         let high = vec![48.70, 48.72, 48.90, 48.87, 48.82];
         let low = vec![47.79, 48.14, 48.39, 48.37, 48.24];
         let close = vec![48.16, 48.61, 48.75, 48.63, 48.74];
-        let result = atr(&high, &low, &close, 3);
+        let result = atr(&high, &low, &close, 3, AtrSmoothing::Wilder);
         assert!(result[0].is_nan());
         assert!(result[1].is_nan());
         assert!(result[2].is_finite(), "ATR[2] should be finite");
         assert!(result[2] > 0.0, "ATR should be positive");
     }
 
+    #[test]
+    fn test_stddev_population_vs_sample() {
+        // Classic dataset: mean 5, squared deviations sum to 32
+        let data = vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let pop = std_dev(&data, 8, false);
+        let smp = std_dev(&data, 8, true);
+        assert!(pop[6].is_nan());
+        assert_approx(pop[7], 2.0, 1e-12, "population sd = sqrt(32 / 8)");
+        assert_approx(smp[7], (32.0f64 / 7.0).sqrt(), 1e-12, "sample sd = sqrt(32 / 7)");
+
+        let (upper, middle, lower) = bollinger_bands(&data, 8, 2.0, false);
+        assert_approx(middle[7], 5.0, 1e-12, "middle");
+        assert_approx(upper[7], 9.0, 1e-12, "population upper");
+        assert_approx(lower[7], 1.0, 1e-12, "population lower");
+        let (upper, _, lower) = bollinger_bands(&data, 8, 2.0, true);
+        assert_approx(upper[7], 5.0 + 2.0 * (32.0f64 / 7.0).sqrt(), 1e-12, "sample upper");
+        assert_approx(lower[7], 5.0 - 2.0 * (32.0f64 / 7.0).sqrt(), 1e-12, "sample lower");
+
+        // Default params keep population
+        let candles: Vec<Candle> = data.iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::StdDev,
+            params: crate::models::strategy::IndicatorParams { period: Some(8), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[7], 2.0, 1e-12, "default");
+        config.params.sample_stddev = Some(true);
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[7], (32.0f64 / 7.0).sqrt(), 1e-12, "sample param");
+
+        // A single-bar sample window is rejected rather than producing NaN
+        config.params.period = Some(1);
+        assert!(matches!(compute_indicator(&config, &candles), Err(AppError::InvalidIndicatorParams(_))));
+    }
+
+    #[test]
+    fn test_linear_regression_channel_slope_and_bands() {
+        // A perfect line: fitted = close, slope = step, bands collapse onto the line
+        let line: Vec<f64> = (0..10).map(|i| 100.0 + 0.5 * i as f64).collect();
+        let (fitted, slope, upper, lower) = linear_regression(&line, 5, 2.0);
+        assert!(fitted[3].is_nan() && slope[3].is_nan());
+        assert_approx(fitted[9], 104.5, 1e-9, "fitted on a line");
+        assert_approx(slope[9], 0.5, 1e-12, "slope on a line");
+        assert_approx(upper[9], 104.5, 1e-9, "upper on a line");
+        assert_approx(lower[9], 104.5, 1e-9, "lower on a line");
+
+        // Noisy trends: slope sign follows the trend, bands sit symmetrically around the fit
+        let up = wavy_series(300, 100.0, 1.0).iter().enumerate().map(|(i, v)| v + 0.2 * i as f64).collect::<Vec<_>>();
+        let down = up.iter().rev().copied().collect::<Vec<_>>();
+        for (series, rising) in [(&up, true), (&down, false)] {
+            let (fitted, slope, upper, lower) = linear_regression(series, 20, 2.0);
+            for i in 19..series.len() {
+                assert_eq!(slope[i] > 0.0, rising, "slope sign at {}", i);
+                assert!(upper[i] > fitted[i] && lower[i] < fitted[i]);
+                assert_approx(upper[i] - fitted[i], fitted[i] - lower[i], 1e-9, "band symmetry");
+            }
+        }
+
+        // Known residuals: y = 1, 3, 2 → fit 1.5 + 0.5x, residuals -0.5, 1, -0.5
+        let (fitted, slope, upper, _) = linear_regression(&[1.0, 3.0, 2.0], 3, 1.0);
+        assert_approx(fitted[2], 2.5, 1e-12, "fitted");
+        assert_approx(slope[2], 0.5, 1e-12, "slope");
+        assert_approx(upper[2] - fitted[2], 0.5f64.sqrt(), 1e-12, "residual sd = sqrt(1.5 / 3)");
+
+        // Outputs wired through compute_indicator: bands in secondary/tertiary, slope in extra
+        let candles: Vec<Candle> = up.iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::LinearRegression,
+            params: crate::models::strategy::IndicatorParams { period: Some(20), std_dev: Some(1.5), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        let out = compute_indicator(&config, &candles).unwrap();
+        let (fitted, slope, upper, lower) = linear_regression(&up, 20, 1.5);
+        assert_eq!(out.primary[200], fitted[200]);
+        assert_eq!(out.secondary.unwrap()[200], upper[200]);
+        assert_eq!(out.tertiary.unwrap()[200], lower[200]);
+        assert_eq!(out.extra.unwrap()["slope"][200], slope[200]);
+    }
+
+    #[test]
+    fn test_atr_smoothing_methods() {
+        // Flat close with symmetric ranges → TR = 2a = [2, 4, 6, 2, 6]
+        let a = [1.0, 2.0, 3.0, 1.0, 3.0];
+        let high: Vec<f64> = a.iter().map(|x| 10.0 + x).collect();
+        let low: Vec<f64> = a.iter().map(|x| 10.0 - x).collect();
+        let close = vec![10.0; 5];
+
+        let wilder = atr(&high, &low, &close, 3, AtrSmoothing::Wilder);
+        let sma_atr = atr(&high, &low, &close, 3, AtrSmoothing::Sma);
+        let ema_atr = atr(&high, &low, &close, 3, AtrSmoothing::Ema);
+
+        // Identical SMA seed
+        for v in [&wilder, &sma_atr, &ema_atr] {
+            assert!(v[1].is_nan());
+            assert_approx(v[2], 4.0, 1e-12, "seed");
+        }
+        assert_approx(wilder[3], 10.0 / 3.0, 1e-12, "wilder[3]");
+        assert_approx(wilder[4], 38.0 / 9.0, 1e-12, "wilder[4]");
+        assert_approx(sma_atr[3], 4.0, 1e-12, "sma[3]");
+        assert_approx(sma_atr[4], 14.0 / 3.0, 1e-12, "sma[4]");
+        assert_approx(ema_atr[3], 3.0, 1e-12, "ema[3]");
+        assert_approx(ema_atr[4], 4.5, 1e-12, "ema[4]");
+
+        // Dispatch honours the param; omitting it keeps Wilder
+        let candles: Vec<Candle> = (0..5)
+            .map(|i| Candle { high: high[i], low: low[i], close: close[i], ..Default::default() })
+            .collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::ATR,
+            params: crate::models::strategy::IndicatorParams { period: Some(3), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[4], 38.0 / 9.0, 1e-12, "default");
+        config.params.atr_smoothing = Some(AtrSmoothing::Ema);
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[4], 4.5, 1e-12, "ema param");
+    }
+
+    #[test]
+    fn test_ema_sma_seed_vs_first_value() {
+        let close = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let sma_seed = ema(&close, 3);
+        let first_value = ema_seeded(&close, 3, EmaSeed::FirstValue);
+
+        // SMA seed: undefined until bar 2, then seeded with (1 + 2 + 3) / 3
+        assert!(sma_seed[0].is_nan() && sma_seed[1].is_nan());
+        assert_approx(sma_seed[2], 2.0, 1e-12, "sma seed");
+        assert_approx(sma_seed[3], 3.0, 1e-12, "sma seed + 1");
+        // First value: defined from bar 0 with alpha = 0.5
+        assert_eq!(first_value[0], 1.0);
+        assert_approx(first_value[1], 1.5, 1e-12, "first value + 1");
+        assert_approx(first_value[2], 2.25, 1e-12, "first value + 2");
+        assert_approx(first_value[3], 3.125, 1e-12, "first value + 3");
+
+        let (line, signal, hist) = macd(&close, 2, 3, 2, EmaSeed::FirstValue);
+        assert_eq!((line[0], signal[0], hist[0]), (0.0, 0.0, 0.0));
+        assert!(line[1] > 0.0 && signal[1] > 0.0);
+        let (line, signal, _) = macd(&close, 2, 3, 2, EmaSeed::SmaSeed);
+        assert!(line[1].is_nan() && signal[2].is_nan());
+        assert!(!signal[3].is_nan());
+        let (line, signal, hist) = ppo(&close, 2, 3, 2, EmaSeed::FirstValue);
+        assert_eq!((line[0], signal[0], hist[0]), (0.0, 0.0, 0.0));
+        assert!(line[1] > 0.0 && signal[1] > 0.0);
+
+        let candles: Vec<Candle> = close.iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::EMA,
+            params: crate::models::strategy::IndicatorParams { period: Some(3), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert!(compute_indicator(&config, &candles).unwrap().primary[1].is_nan());
+        let default_key = config.cache_key();
+        config.params.ema_seed = Some(EmaSeed::FirstValue);
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[1], 1.5, 1e-12, "first value param");
+        assert_ne!(config.cache_key(), default_key);
+    }
+
+    #[test]
+    fn test_rsi_wilder_vs_sma() {
+        let close: Vec<f64> = (0..120)
+            .map(|i| 100.0 + 0.1 * i as f64 + 3.0 * (i as f64 / 6.0).sin() + ((i * 7) % 5) as f64 * 0.3)
+            .collect();
+        let wilder = rsi(&close, 14, RsiSmoothing::Wilder);
+        let cutler = rsi(&close, 14, RsiSmoothing::Sma);
+
+        // Same SMA seed, then the averages drift apart
+        assert!(wilder[13].is_nan() && cutler[13].is_nan());
+        assert_approx(cutler[14], wilder[14], 1e-12, "seed");
+        let mut max_diff: f64 = 0.0;
+        for i in 14..120 {
+            assert!((0.0..=100.0).contains(&wilder[i]), "wilder out of range at {i}");
+            assert!((0.0..=100.0).contains(&cutler[i]), "cutler out of range at {i}");
+            max_diff = max_diff.max((wilder[i] - cutler[i]).abs());
+        }
+        assert!(max_diff > 0.1, "variants should differ, max diff {max_diff}");
+        assert!(max_diff < 25.0, "variants should stay close, max diff {max_diff}");
+
+        // Cutler's RSI is the plain average of the last 14 changes
+        let (gain, loss) = close[100..=114].windows(2).fold((0.0, 0.0), |(g, l), w| {
+            let change = w[1] - w[0];
+            if change > 0.0 { (g + change, l) } else { (g, l - change) }
+        });
+        assert_approx(cutler[114], 100.0 - 100.0 / (1.0 + gain / loss), 1e-9, "cutler window");
+
+        let candles: Vec<Candle> = close.iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::RSI,
+            params: crate::models::strategy::IndicatorParams { period: Some(14), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[100], wilder[100], 1e-12, "default");
+        let default_key = config.cache_key();
+        config.params.rsi_smoothing = Some(RsiSmoothing::Sma);
+        assert_approx(compute_indicator(&config, &candles).unwrap().primary[100], cutler[100], 1e-12, "sma param");
+        assert_ne!(config.cache_key(), default_key);
+    }
+
     #[test]
     fn test_stochastic_basic() {
         let high = vec![130.0, 132.0, 131.0, 133.0, 135.0, 134.0, 136.0, 138.0, 137.0, 139.0];
@@ -2198,6 +3380,589 @@ mod tests {
         assert!(result[29] > 0.0, "ADX should be positive");
     }
 
+    #[test]
+    fn test_choppiness_regimes() {
+        // Zigzag between 100 and 101: lots of travel, little net range → choppy
+        let close: Vec<f64> = (0..40).map(|i| if i % 2 == 0 { 100.0 } else { 101.0 }).collect();
+        let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+        let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+        let choppy = choppiness(&high, &low, &close, 14);
+        assert!(choppy[13].is_nan(), "first value needs period + 1 bars");
+        assert!(choppy[14].is_finite());
+        assert!(choppy[39] > 80.0, "zigzag should score high, got {}", choppy[39]);
+
+        // Clean uptrend: range grows with every bar → trending
+        let close: Vec<f64> = (0..40).map(|i| 100.0 + i as f64).collect();
+        let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+        let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+        let trend = choppiness(&high, &low, &close, 14);
+        assert!(trend[39] < 25.0, "trend should score low, got {}", trend[39]);
+        assert_approx(trend[39], 100.0 * (21.0f64 / 14.0).log10() / 14f64.log10(), 1e-9, "trend chop");
+    }
+
+    #[test]
+    fn test_choppiness_params() {
+        let candles: Vec<Candle> = (0..10)
+            .map(|i| Candle { open: 100.0, high: 101.0 + i as f64, low: 99.0, close: 100.0, ..Default::default() })
+            .collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::Choppiness,
+            params: crate::models::strategy::IndicatorParams { period: Some(10), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        // Needs period + 1 bars
+        assert!(compute_indicator(&config, &candles).is_err());
+        config.params.period = Some(1);
+        assert!(compute_indicator(&config, &candles).is_err());
+        config.params.period = Some(9);
+        assert!(compute_indicator(&config, &candles).unwrap().primary[9].is_finite());
+    }
+
+    #[test]
+    fn test_stochastic_rsi_bounds_and_sensitivity() {
+        // Slow sine wave with a gentle drift: RSI stays in a narrow band
+        let close: Vec<f64> = (0..200)
+            .map(|i| 100.0 + 0.02 * i as f64 + (i as f64 * 0.15).sin())
+            .collect();
+        let (k, d) = stochastic_rsi(&close, 14, 14, 3, 3);
+        assert_eq!(k.len(), close.len());
+        // RSI valid from 14, raw %K from 14 + 13, slowed %K two bars later, %D two more
+        assert!(k[28].is_nan());
+        assert!(k[29].is_finite());
+        assert!(d[30].is_nan());
+        assert!(d[31].is_finite());
+        for v in k.iter().chain(d.iter()).filter(|v| v.is_finite()) {
+            assert!((0.0..=100.0).contains(v), "StochRSI out of range: {}", v);
+        }
+
+        let r = rsi(&close, 14, RsiSmoothing::Wilder);
+        let range = |vals: &[f64]| {
+            let finite = vals[40..].iter().copied().filter(|v| v.is_finite());
+            finite.clone().fold(f64::NEG_INFINITY, f64::max) - finite.fold(f64::INFINITY, f64::min)
+        };
+        assert!(
+            range(&k) > range(&r) * 1.5,
+            "StochRSI range {} should exceed RSI range {}",
+            range(&k),
+            range(&r)
+        );
+    }
+
+    #[test]
+    fn test_stochastic_rsi_params() {
+        let candles: Vec<Candle> = (0..40)
+            .map(|i| Candle { open: 100.0, high: 101.0, low: 99.0, close: 100.0 + (i % 3) as f64, ..Default::default() })
+            .collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::StochasticRSI,
+            params: crate::models::strategy::IndicatorParams { period: Some(14), k_period: Some(14), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert!(compute_indicator(&config, &candles).is_err(), "d_period is required");
+        config.params.d_period = Some(3);
+        let out = compute_indicator(&config, &candles).unwrap();
+        assert!(out.primary[39].is_finite());
+        assert!(out.secondary.unwrap()[39].is_finite());
+        // Needs period + k_period bars
+        config.params.k_period = Some(30);
+        assert!(compute_indicator(&config, &candles).is_err());
+    }
+
+    #[test]
+    fn test_trix_crosses_zero_on_reversal() {
+        // 60 bars up, then 60 bars down
+        let close: Vec<f64> = (0..120)
+            .map(|i| if i < 60 { 100.0 + i as f64 } else { 160.0 - (i - 60) as f64 })
+            .collect();
+        let t = trix(&close, 5);
+        assert!(t[12].is_nan(), "first value needs 3 * (period - 1) + 2 bars");
+        assert!(t[13].is_finite());
+        assert!(t[20..60].iter().all(|v| *v > 0.0), "uptrend should give positive TRIX");
+        assert!(t[100..].iter().all(|v| *v < 0.0), "downtrend should give negative TRIX");
+        let cross = (61..120).find(|&i| t[i - 1] > 0.0 && t[i] <= 0.0);
+        assert!(cross.is_some_and(|i| i < 75), "TRIX should cross zero shortly after the reversal");
+
+        let candles: Vec<Candle> = close.iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let mut config = IndicatorConfig {
+            indicator_type: IndicatorType::TRIX,
+            params: crate::models::strategy::IndicatorParams { period: Some(5), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert!(compute_indicator(&config, &candles).unwrap().secondary.is_none());
+        config.params.signal_period = Some(3);
+        let out = compute_indicator(&config, &candles).unwrap();
+        let signal = out.secondary.unwrap();
+        assert!(signal[14].is_nan());
+        assert!(signal[15].is_finite());
+    }
+
+    #[test]
+    fn test_accumulation_distribution_hand_computed() {
+        // CLV: bar0 (12-10)-(14-12) / 4 = 0, bar1 (14-11)-(15-14) / 4 = 0.5,
+        //      bar2 (12-12)-(16-12) / 4 = -1, bar3 zero range → 0, bar4 (9-8)-(10-9) / 2 = 0
+        let high = vec![14.0, 15.0, 16.0, 13.0, 10.0];
+        let low = vec![10.0, 11.0, 12.0, 13.0, 8.0];
+        let close = vec![12.0, 14.0, 12.0, 13.0, 9.0];
+        let volume = vec![100.0, 200.0, 50.0, 400.0, 300.0];
+        let mfv = money_flow_volume(&high, &low, &close, &volume);
+        assert_eq!(mfv, vec![0.0, 100.0, -50.0, 0.0, 0.0]);
+
+        let ad = accumulation_distribution(&high, &low, &close, &volume);
+        assert_eq!(ad, vec![0.0, 100.0, 50.0, 50.0, 50.0]);
+    }
+
+    #[test]
+    fn test_chaikin_money_flow_hand_computed() {
+        let high = vec![14.0, 15.0, 16.0, 13.0, 10.0];
+        let low = vec![10.0, 11.0, 12.0, 13.0, 8.0];
+        let close = vec![12.0, 14.0, 12.0, 13.0, 9.0];
+        let volume = vec![100.0, 200.0, 50.0, 400.0, 300.0];
+        let cmf = chaikin_money_flow(&high, &low, &close, &volume, 3);
+        assert!(cmf[0].is_nan() && cmf[1].is_nan());
+        assert_approx(cmf[2], 50.0 / 350.0, 1e-12, "cmf[2]");
+        assert_approx(cmf[3], 50.0 / 650.0, 1e-12, "cmf[3]");
+        assert_approx(cmf[4], -50.0 / 750.0, 1e-12, "cmf[4]");
+
+        // No volume in the window → undefined rather than a division by zero
+        let cmf = chaikin_money_flow(&high, &low, &close, &[0.0; 5], 3);
+        assert!(cmf.iter().all(|v| v.is_nan()));
+
+        let candles: Vec<Candle> = (0..5)
+            .map(|i| Candle { high: high[i], low: low[i], close: close[i], volume: volume[i], ..Default::default() })
+            .collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::ChaikinMoneyFlow,
+            params: crate::models::strategy::IndicatorParams { period: Some(3), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        let out = compute_indicator(&config, &candles).unwrap();
+        assert_approx(out.primary[4], -50.0 / 750.0, 1e-12, "dispatched cmf");
+    }
+
+    #[test]
+    fn test_force_index_hand_computed() {
+        // Raw force: bar1 (11-10)×200 = 200, bar2 (10.5-11)×100 = -50, bar3 (12-10.5)×100 = 150
+        // EMA(2): seed (200 - 50) / 2 = 75, then 75 + (150 - 75) × 2/3 = 125
+        let close = vec![10.0, 11.0, 10.5, 12.0];
+        let volume = vec![100.0, 200.0, 100.0, 100.0];
+        let fi = force_index(&close, &volume, 2);
+        assert!(fi[0].is_nan() && fi[1].is_nan());
+        assert_approx(fi[2], 75.0, 1e-12, "fi[2]");
+        assert_approx(fi[3], 125.0, 1e-12, "fi[3]");
+
+        // Zero-volume bars carry no force but keep the EMA defined
+        let fi = force_index(&close, &[0.0; 4], 2);
+        assert_eq!(&fi[2..], &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_vwma_weights_by_volume() {
+        // Heavy volume on the 20.0 bar pulls the average toward it: (10·100 + 20·800 + 12·100) / 1000
+        let close = vec![10.0, 20.0, 12.0];
+        let volume = vec![100.0, 800.0, 100.0];
+        let v = vwma(&close, &volume, 3);
+        assert!(v[0].is_nan() && v[1].is_nan());
+        assert_approx(v[2], 18.2, 1e-12, "vwma[2]");
+        assert!(v[2] > sma(&close, 3)[2]);
+
+        // Zero-volume window degrades to the simple average
+        let v = vwma(&close, &[0.0; 3], 3);
+        assert_approx(v[2], 14.0, 1e-12, "zero-volume vwma");
+    }
+
+    #[test]
+    fn test_zlema_lags_less_than_ema_on_ramp() {
+        let close: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+        let z = zlema(&close, 9);
+        let e = ema(&close, 9);
+        // lag = 4 → first value at 4 + 9 - 1
+        assert!(z[11].is_nan());
+        assert!(z[12].is_finite());
+        for i in 30..60 {
+            assert!((close[i] - z[i]).abs() < (close[i] - e[i]).abs() * 0.1, "bar {i}: zlema {} ema {}", z[i], e[i]);
+        }
+    }
+
+    #[test]
+    fn test_elder_impulse_trend_and_reversal() {
+        // Accelerating rally, then an accelerating sell-off
+        let mut close: Vec<f64> = (0..60).map(|i| 100.0 + 0.02 * (i * i) as f64).collect();
+        let top = close[59];
+        close.extend((1..=40).map(|i| top - 0.05 * (i * i) as f64));
+        let imp = elder_impulse(&close, 13, 12, 26, 9);
+
+        assert!(imp[..34].iter().all(|v| v.is_nan()));
+        assert!(imp[40..60].iter().all(|&v| v == 1.0), "rally: {:?}", &imp[40..60]);
+        assert!(imp[70..85].iter().all(|&v| v == -1.0), "sell-off: {:?}", &imp[70..85]);
+        assert!(imp.iter().all(|v| v.is_nan() || [-1.0, 0.0, 1.0].contains(v)));
+    }
+
+    #[test]
+    fn test_mass_index_reversal_bulge() {
+        // Steady 1.0 ranges, a 10-bar expansion to 3.0, then back to 1.0
+        let ranges: Vec<f64> = (0..120)
+            .map(|i| if (60..70).contains(&i) { 3.0 } else { 1.0 })
+            .collect();
+        let high: Vec<f64> = ranges.iter().map(|r| 100.0 + r / 2.0).collect();
+        let low: Vec<f64> = ranges.iter().map(|r| 100.0 - r / 2.0).collect();
+        let mi = mass_index(&high, &low, 25, 9);
+
+        assert!(mi[39].is_nan());
+        assert!(mi[40].is_finite());
+        assert_approx(mi[59], 25.0, 1e-9, "steady-range mass index");
+        let peak = mi[60..90].iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!(peak > 27.0, "expansion should bulge above 27, got {peak}");
+        // After the bulge the index falls back under 26.5 — the reversal signal
+        let peak_bar = 60 + mi[60..90].iter().position(|&v| v == peak).unwrap();
+        assert!(mi[peak_bar..].iter().any(|&v| v < 26.5));
+    }
+
+    #[test]
+    fn test_schaff_trend_cycle_leads_macd_zero_cross() {
+        // 150-bar decline, then a steady rally from bar 150
+        let close: Vec<f64> = (0..300)
+            .map(|i| if i < 150 { 200.0 - 0.5 * i as f64 } else { 125.0 + 0.5 * (i - 150) as f64 })
+            .collect();
+        let stc = schaff_trend_cycle(&close, 10, 20, 50, 3);
+        let (macd_line, _, _) = macd(&close, 20, 50, 9, EmaSeed::SmaSeed);
+
+        assert!(stc.iter().filter(|v| v.is_finite()).all(|&v| (0.0..=100.0).contains(&v)));
+        // Deep in the decline STC is pinned at the bottom of its range
+        assert!(stc[140] < 1.0, "stc during decline {}", stc[140]);
+        let stc_top = (150..300).find(|&i| stc[i] > 99.0).expect("STC should reach the top");
+        let macd_cross = (150..300).find(|&i| macd_line[i] > 0.0).expect("MACD should cross zero");
+        assert!(stc_top < macd_cross, "STC top at {stc_top}, MACD zero-cross at {macd_cross}");
+    }
+
+    #[test]
+    fn test_klinger_volume_surge() {
+        // Steady uptrend on flat volume, then a 5-bar 10× volume surge at bar 80
+        let len = 140;
+        let close: Vec<f64> = (0..len).map(|i| 100.0 + 0.1 * i as f64).collect();
+        let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+        let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+        let volume: Vec<f64> = (0..len).map(|i| if (80..85).contains(&i) { 10_000.0 } else { 1_000.0 }).collect();
+        let (kvo, signal) = klinger(&high, &low, &close, &volume, 34, 55, 13);
+
+        // Force starts at bar 1, so the slow EMA (and KVO) first appears at bar 55
+        assert!(kvo[54].is_nan());
+        assert!(kvo[55].is_finite());
+        assert!(signal[66].is_nan() && signal[67].is_finite());
+        // cm keeps growing in an unbroken trend, so force converges and KVO flattens
+        assert!(kvo[79].abs() < 0.05 * 200.0 * 1_000.0, "pre-surge kvo {}", kvo[79]);
+        // The surge pushes the fast EMA well above the slow one and KVO over its signal
+        let peak = kvo[80..90].iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!(peak > 100_000.0, "surge kvo peak {peak}");
+        assert!(kvo[84] > signal[84]);
+    }
+
+    #[test]
+    fn test_ease_of_movement_hand_computed() {
+        // EMV × 10000: bar1 midpoint 9→11 over range 2 on 2000 volume = 2·2·10000/2000 = 20,
+        // bar2 zero range → 0, bar3 zero volume → 0, bar4 midpoint 12→13 over range 4 on 4000 = 10
+        let high = vec![10.0, 12.0, 12.0, 14.0, 15.0];
+        let low = vec![8.0, 10.0, 12.0, 10.0, 11.0];
+        let volume = vec![1000.0, 2000.0, 500.0, 0.0, 4000.0];
+        let eom = ease_of_movement(&high, &low, &volume, 2, 10_000.0);
+        assert!(eom[0].is_nan() && eom[1].is_nan());
+        assert_approx(eom[2], 10.0, 1e-12, "eom[2]");
+        assert_approx(eom[3], 0.0, 1e-12, "eom[3]");
+        assert_approx(eom[4], 5.0, 1e-12, "eom[4]");
+
+        let candles: Vec<Candle> = (0..5)
+            .map(|i| Candle { high: high[i], low: low[i], close: high[i], volume: volume[i], ..Default::default() })
+            .collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::EaseOfMovement,
+            params: crate::models::strategy::IndicatorParams { period: Some(2), multiplier: Some(10_000.0), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        let out = compute_indicator(&config, &candles).unwrap();
+        assert_approx(out.primary[4], 5.0, 1e-12, "dispatched eom");
+    }
+
+    #[test]
+    fn test_dpo_detrends() {
+        // Linear trend: the displaced SMA lags by a fixed amount, so DPO is flat and
+        // small next to the price level (slope × (period/2 + 1 + (period-1)/2)).
+        let trend: Vec<f64> = (0..100).map(|i| 100.0 + 0.01 * i as f64).collect();
+        let d = dpo(&trend, 20);
+        assert!(d[..30].iter().all(|v| v.is_nan()));
+        assert!(d[30].is_finite());
+        for v in &d[30..] {
+            assert_approx(*v, 0.01 * 20.5, 1e-9, "linear-trend dpo");
+        }
+
+        // Sinusoid riding the same trend: DPO swings both sides of zero with the cycle
+        let wave: Vec<f64> = (0..200)
+            .map(|i| 100.0 + 0.01 * i as f64 + 5.0 * (2.0 * std::f64::consts::PI * i as f64 / 40.0).sin())
+            .collect();
+        let d = dpo(&wave, 20);
+        let valid = &d[30..];
+        let max = valid.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = valid.iter().copied().fold(f64::INFINITY, f64::min);
+        assert!(max > 3.0 && min < -3.0, "dpo should oscillate: min {min}, max {max}");
+        let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+        assert!(mean.abs() < 0.5, "dpo should be centred near zero, mean {mean}");
+
+        let candles: Vec<Candle> = trend[..30].iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::DPO,
+            params: crate::models::strategy::IndicatorParams { period: Some(20), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert!(matches!(compute_indicator(&config, &candles), Err(AppError::InsufficientData { needed: 31, .. })));
+    }
+
+    #[test]
+    fn test_coppock_bullish_turn() {
+        // 40-bar decline into a bottom at bar 40, then a 40-bar rally
+        let close: Vec<f64> = (0..80)
+            .map(|i| if i <= 40 { 140.0 - i as f64 } else { 100.0 + (i - 40) as f64 })
+            .collect();
+        let c = coppock(&close, 10, 11, 14);
+        assert!(c[..23].iter().all(|v| v.is_nan()));
+        assert!(c[23].is_finite());
+        assert!(c[23..41].iter().all(|v| *v < 0.0), "decline should give negative Coppock");
+
+        let cross = (42..80).find(|&i| c[i - 1] < 0.0 && c[i] >= 0.0);
+        let cross = cross.expect("Coppock should cross above zero after the bottom");
+        assert!(c[41..=cross].windows(2).all(|w| w[1] > w[0]), "Coppock should rise into the cross");
+
+        // A NaN close poisons the ROC sum at bars 50, 61 (short ROC) and 64 (long ROC),
+        // and every WMA window that covers one of them
+        let mut gapped = close.clone();
+        gapped[50] = f64::NAN;
+        let c = coppock(&gapped, 10, 11, 14);
+        assert!(c[49].is_finite());
+        assert!(c[50..60].iter().all(|v| v.is_nan()));
+        assert!(c[61..=73].iter().all(|v| v.is_nan()));
+        assert!(c[74].is_finite());
+    }
+
+    #[test]
+    fn test_donchian_channel() {
+        let high = [10.0, 12.0, 11.0, 15.0, 13.0];
+        let low = [8.0, 9.0, 7.0, 12.0, 11.0];
+        let candles: Vec<Candle> = (0..5)
+            .map(|i| Candle { high: high[i], low: low[i], close: low[i], ..Default::default() })
+            .collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::DonchianChannel,
+            params: crate::models::strategy::IndicatorParams { period: Some(3), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        let out = compute_indicator(&config, &candles).unwrap();
+        let (upper, lower) = (out.secondary.unwrap(), out.tertiary.unwrap());
+        assert!(out.primary[1].is_nan() && upper[1].is_nan());
+        assert_eq!(&upper[2..], &[12.0, 15.0, 15.0]);
+        assert_eq!(&lower[2..], &[7.0, 7.0, 7.0]);
+        assert_eq!(&out.primary[2..], &[9.5, 11.0, 11.0]);
+    }
+
+    #[test]
+    fn test_super_smoother_lags_less_than_sma() {
+        // Ramp with a 3-bar noise cycle: the smoother must trail the trend by less
+        // than SMA's (n-1)/2 bars while flattening the noise at least as well.
+        let slope = 0.1;
+        let noisy: Vec<f64> = (0..300)
+            .map(|i| 100.0 + slope * i as f64 + (2.0 * std::f64::consts::PI * i as f64 / 3.0).sin())
+            .collect();
+        let ss = super_smoother(&noisy, 10);
+        let ma = sma(&noisy, 10);
+        assert!(ss[0].is_nan() && ss[1].is_nan());
+        assert!(ss[2].is_finite());
+
+        let i = 299;
+        let trend = 100.0 + slope * i as f64;
+        let ss_lag = (trend - ss[i]) / slope;
+        let ma_lag = (trend - ma[i]) / slope;
+        assert!(ss_lag > 0.0 && ss_lag < ma_lag, "super smoother lag {ss_lag} should be below sma lag {ma_lag}");
+
+        // Largest bar-to-bar deviation from the trend slope
+        let roughness = |v: &[f64]| v[200..].windows(2).map(|w| (w[1] - w[0] - slope).abs()).fold(0.0, f64::max);
+        let (ss_rough, ma_rough, raw_rough) = (roughness(&ss), roughness(&ma), roughness(&noisy));
+        assert!(ss_rough < ma_rough, "super smoother {ss_rough} should be smoother than sma {ma_rough}");
+        assert!(ss_rough < raw_rough * 0.1, "super smoother should remove most noise: {ss_rough} vs {raw_rough}");
+    }
+
+    #[test]
+    fn test_roofing_filter_removes_trend() {
+        // Pure ramp: the high-pass stage removes the trend so the output settles at zero
+        let ramp: Vec<f64> = (0..300).map(|i| 100.0 + 0.5 * i as f64).collect();
+        let rf = roofing_filter(&ramp, 48, 10);
+        assert!(rf[0].is_nan() && rf[1].is_nan());
+        assert!(rf[250..].iter().all(|v| v.abs() < 1e-3), "ramp should be filtered out");
+
+        // A 20-bar cycle on the same ramp passes through and oscillates around zero
+        let wave: Vec<f64> = (0..300)
+            .map(|i| 100.0 + 0.5 * i as f64 + 3.0 * (2.0 * std::f64::consts::PI * i as f64 / 20.0).sin())
+            .collect();
+        let rf = roofing_filter(&wave, 48, 10);
+        let tail = &rf[200..];
+        let max = tail.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = tail.iter().copied().fold(f64::INFINITY, f64::min);
+        assert!(max > 1.0 && min < -1.0, "cycle should survive: min {min}, max {max}");
+        let mean = tail.iter().sum::<f64>() / tail.len() as f64;
+        assert!(mean.abs() < 0.2, "roofing filter should be centred near zero, mean {mean}");
+
+        let candles: Vec<Candle> = ramp[..2].iter().map(|&c| Candle { close: c, ..Default::default() }).collect();
+        let config = IndicatorConfig {
+            indicator_type: IndicatorType::RoofingFilter,
+            params: crate::models::strategy::IndicatorParams { period: Some(10), slow_period: Some(48), ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        };
+        assert!(matches!(compute_indicator(&config, &candles), Err(AppError::InsufficientData { needed: 3, .. })));
+    }
+
+    #[test]
+    fn test_connors_rsi_oversold_after_down_streak() {
+        // Sideways chop, then seven straight lower closes, each a bigger drop than the last
+        let mut close: Vec<f64> = (0..120).map(|i| 100.0 + if i % 2 == 0 { 0.5 } else { -0.5 }).collect();
+        for k in 1..=7 {
+            let last = *close.last().unwrap();
+            close.push(last - 0.5 * k as f64);
+        }
+        let crsi = connors_rsi(&close, 3, 2, 100);
+        assert!(crsi[..101].iter().all(|v| v.is_nan()));
+        assert!(crsi[101..].iter().all(|v| (0.0..=100.0).contains(v)));
+
+        let chop = &crsi[101..120];
+        assert!(chop.iter().all(|v| *v > 20.0 && *v < 80.0), "chop should stay mid-range: {chop:?}");
+        let last = *crsi.last().unwrap();
+        assert!(last < 5.0, "sustained down-streak should be extremely oversold, got {last}");
+        assert!(crsi[122..].windows(2).all(|w| w[1] <= w[0]), "reading should deepen as the streak extends");
+
+        assert_eq!(streak(&[1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 2.0]), vec![0.0, 1.0, 2.0, 0.0, -1.0, -2.0, 1.0]);
+        let r = percent_rank(&[1.0, 2.0, 3.0, 4.0, 2.5], 4);
+        assert_approx(r[4], 50.0, 1e-12, "percent rank");
+    }
+
+    /// Prior day H=110, L=100, C=105 (range 10), then one bar of the next day.
+    fn pivot_candles() -> Vec<Candle> {
+        let bar = |dt: &str, high: f64, low: f64, close: f64| Candle {
+            datetime: dt.to_string(), open: close, high, low, close, ..Default::default()
+        };
+        vec![
+            bar("2024-01-02 00:00", 108.0, 100.0, 102.0),
+            bar("2024-01-02 12:00", 110.0, 103.0, 105.0),
+            bar("2024-01-03 00:00", 106.0, 104.0, 105.5),
+        ]
+    }
+
+    fn pivot_config(pivot_type: Option<PivotType>) -> IndicatorConfig {
+        IndicatorConfig {
+            indicator_type: IndicatorType::Pivots,
+            params: crate::models::strategy::IndicatorParams { pivot_type, ..Default::default() },
+            output_field: None,
+            cached_hash: 0,
+        }
+    }
+
+    #[test]
+    fn test_pivots_camarilla_levels() {
+        let out = compute_indicator(&pivot_config(Some(PivotType::Camarilla)), &pivot_candles()).unwrap();
+        let extra = out.extra.unwrap();
+        assert!(extra["r1"][1].is_nan(), "no levels before the first completed day");
+        let level = |k: &str| extra[k][2];
+        assert_approx(out.primary[2], 105.0, 1e-12, "pp");
+        assert_approx(level("r1"), 105.0 + 11.0 / 12.0, 1e-12, "r1");
+        assert_approx(level("r2"), 105.0 + 11.0 / 6.0, 1e-12, "r2");
+        assert_approx(level("r3"), 107.75, 1e-12, "r3");
+        assert_approx(level("s1"), 105.0 - 11.0 / 12.0, 1e-12, "s1");
+        assert_approx(level("s2"), 105.0 - 11.0 / 6.0, 1e-12, "s2");
+        assert_approx(level("s3"), 102.25, 1e-12, "s3");
+    }
+
+    #[test]
+    fn test_pivots_fibonacci_levels() {
+        let out = compute_indicator(&pivot_config(Some(PivotType::Fibonacci)), &pivot_candles()).unwrap();
+        let extra = out.extra.unwrap();
+        let level = |k: &str| extra[k][2];
+        assert_approx(level("pp"), 105.0, 1e-12, "pp");
+        assert_approx(level("r1"), 108.82, 1e-12, "r1");
+        assert_approx(level("r2"), 111.18, 1e-12, "r2");
+        assert_approx(level("r3"), 115.0, 1e-12, "r3");
+        assert_approx(level("s1"), 101.18, 1e-12, "s1");
+        assert_approx(level("s2"), 98.82, 1e-12, "s2");
+        assert_approx(level("s3"), 95.0, 1e-12, "s3");
+
+        // Classic stays the default and differs from Fibonacci at R1 (2PP - L)
+        let classic = compute_indicator(&pivot_config(None), &pivot_candles()).unwrap().extra.unwrap();
+        assert_approx(classic["r1"][2], 110.0, 1e-12, "classic r1");
+        assert_ne!(pivot_config(None).cache_key(), pivot_config(Some(PivotType::Fibonacci)).cache_key());
+    }
+
+    #[test]
+    fn test_rolling_sum_recovers_after_nan_gap() {
+        let mut data: Vec<f64> = (0..20).map(|i| 1.0 + i as f64 * 0.5).collect();
+        data[5] = f64::NAN;
+        data[6] = f64::INFINITY;
+        let sums = rolling_sum(&data, 3);
+
+        // Windows touching bars 5 or 6 are NaN; every later window is exact again
+        for (i, s) in sums.iter().enumerate() {
+            if i < 2 || (5..=8).contains(&i) {
+                assert!(s.is_nan(), "sum[{}] = {}", i, s);
+            } else {
+                let expected: f64 = data[i - 2..=i].iter().sum();
+                assert_approx(*s, expected, 1e-12, &format!("sum[{}]", i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rolling_mean_m2_recovers_after_nan_gap() {
+        let mut data: Vec<f64> = (0..20).map(|i| 1.0 + (i as f64 * 0.7).sin()).collect();
+        data[0] = f64::NAN;
+        data[9] = f64::NAN;
+        let (mean, m2) = rolling_mean_m2(&data, 4);
+
+        // Windows touching bars 0 or 9 are NaN; every other window is exact
+        for i in 0..data.len() {
+            if i < 4 || (9..=12).contains(&i) {
+                assert!(mean[i].is_nan() && m2[i].is_nan(), "window ending at {}", i);
+            } else {
+                let window = &data[i - 3..=i];
+                let expected_mean = window.iter().sum::<f64>() / 4.0;
+                let expected_m2: f64 = window.iter().map(|v| (v - expected_mean).powi(2)).sum();
+                assert_approx(mean[i], expected_mean, 1e-12, &format!("mean[{}]", i));
+                assert_approx(m2[i], expected_m2, 1e-12, &format!("m2[{}]", i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pivots_roll_at_session_start_hour() {
+        // Session opening Jan 2 17:00 spans midnight; the next one opens Jan 3 17:00
+        let bar = |dt: &str, high: f64, low: f64, close: f64| Candle {
+            datetime: dt.to_string(), open: close, high, low, close, ..Default::default()
+        };
+        let candles = vec![
+            bar("2024-01-02 17:00", 108.0, 100.0, 102.0),
+            bar("2024-01-03 02:00", 110.0, 103.0, 105.0),
+            bar("2024-01-03 16:00", 109.0, 104.0, 105.0),
+            bar("2024-01-03 17:00", 106.0, 104.0, 105.5),
+        ];
+        let slices = CandleSlices::from_candles(&candles).with_session_start_hour(17);
+        let out = compute_indicator_with_slices(&pivot_config(None), &slices, &candles).unwrap();
+        assert!(out.primary[2].is_nan(), "midnight must not start a new day");
+        assert_approx(out.primary[3], (110.0 + 100.0 + 105.0) / 3.0, 1e-12, "pp from the 17:00 session");
+
+        // Calendar days: midnight rolls over, using only the 17:00 bar as the prior day
+        let out = compute_indicator(&pivot_config(None), &candles).unwrap();
+        assert_approx(out.primary[1], (108.0 + 100.0 + 102.0) / 3.0, 1e-12, "calendar pp");
+        assert!(out.primary[3].is_finite());
+    }
+
     #[test]
     fn test_compute_indicator_dispatches() {
         let candles: Vec<Candle> = (0..30)
@@ -1,8 +1,14 @@
-use chrono::NaiveDate;
+use std::collections::{BTreeMap, VecDeque};
 
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+use crate::models::candle::Candle;
 use crate::models::config::Timeframe;
-use crate::models::result::{BacktestMetrics, EquityPoint, MonthlyReturn};
-use crate::models::trade::TradeResult;
+use crate::models::result::{
+    BacktestMetrics, EquityPoint, MonthlyReturn, RollingRatioPoint, SeasonalityBucket, SeasonalityReport,
+};
+use crate::models::strategy::{TradeDirection, DEFAULT_OVERTRADING_THRESHOLD};
+use crate::models::trade::{MarketRegime, TradeResult};
 
 #[inline]
 fn parse_4digits(b: &[u8], offset: usize) -> u32 {
@@ -29,6 +35,7 @@ fn bars_per_day(tf: Timeframe) -> f64 {
         Timeframe::H1 => 24.0,
         Timeframe::H4 => 6.0,
         Timeframe::D1 => 1.0,
+        Timeframe::Custom { minutes } => 1440.0 / minutes.max(1) as f64,
     }
 }
 
@@ -38,6 +45,28 @@ pub fn calculate_metrics(
     equity_curve: &[EquityPoint],
     initial_capital: f64,
     timeframe: Timeframe,
+) -> BacktestMetrics {
+    if trades.is_empty() {
+        return empty_metrics(initial_capital);
+    }
+    let stats = EquityStats::from_curve(equity_curve);
+    let bar_of = |ts: &str| {
+        equity_curve
+            .partition_point(|p| p.timestamp.as_str() <= ts)
+            .saturating_sub(1)
+    };
+    calculate_metrics_from_stats(trades, &stats, bar_of, initial_capital, timeframe)
+}
+
+/// Calculate all backtest metrics from trades and equity statistics accumulated bar by bar.
+///
+/// `bar_of` maps a trade entry/exit timestamp to the index of the equity point containing it.
+pub fn calculate_metrics_from_stats(
+    trades: &[TradeResult],
+    stats: &EquityStats,
+    bar_of: impl Fn(&str) -> usize,
+    initial_capital: f64,
+    timeframe: Timeframe,
 ) -> BacktestMetrics {
     let total_trades = trades.len();
 
@@ -57,6 +86,8 @@ pub fn calculate_metrics(
     let mut largest_loss = 0.0f64;
     let mut total_commission = 0.0f64;
     let mut total_swap = 0.0f64;
+    let mut total_spread = 0.0f64;
+    let mut total_slippage = 0.0f64;
     let mut sum_pnl = 0.0f64;
     let mut winner_bars_sum = 0usize;
     let mut loser_bars_sum = 0usize;
@@ -70,6 +101,8 @@ pub fn calculate_metrics(
         sum_pnl += t.pnl;
         total_commission += t.commission;
         total_swap += t.swap;
+        total_spread += t.spread_cost;
+        total_slippage += t.slippage_cost;
         total_bars_sum += t.duration_bars;
         mae_sum += t.mae;
         mfe_sum += t.mfe;
@@ -93,6 +126,7 @@ pub fn calculate_metrics(
 
     let win_rate_pct = winning_trades as f64 / total_trades as f64 * 100.0;
     let net_profit = sum_pnl - total_commission + total_swap;
+    let total_costs = total_spread + total_slippage + total_commission - total_swap;
     let avg_trade = sum_pnl / total_trades as f64;
 
     // Cap at 999 when there are no losing trades — serde_json cannot serialize f64::INFINITY.
@@ -121,9 +155,9 @@ pub fn calculate_metrics(
     let total_return_pct = net_profit / initial_capital * 100.0;
 
     // Annualized return: estimate trading days from equity curve using actual timeframe
-    let trading_bars = equity_curve.len().max(1);
+    let trading_bars = stats.count.max(1);
     let bpd = bars_per_day(timeframe);
-    let annualized_return_pct = annualize_return(total_return_pct, trading_bars, bpd, equity_curve);
+    let annualized_return_pct = annualize_return(total_return_pct, trading_bars, bpd, stats.calendar_years());
     let bars_per_month = bpd * 21.0; // ~21 trading days per month
     let monthly_return_avg_pct = if trading_bars > 0 {
         total_return_pct / (trading_bars as f64 / bars_per_month).max(1.0)
@@ -132,26 +166,10 @@ pub fn calculate_metrics(
     };
 
     // ── Drawdown ──
-    let (max_drawdown_pct, max_dd_duration_bars, avg_drawdown_pct) =
-        calculate_drawdown_stats(equity_curve);
+    let (max_drawdown_pct, max_dd_duration_bars, avg_drawdown_pct) = stats.drawdown_stats();
     // Recovery Factor = Net Profit / Max Absolute Drawdown
     // Computed directly from the equity curve to avoid dependence on initial_capital.
-    let max_dd_absolute = if !equity_curve.is_empty() {
-        let mut peak = equity_curve[0].equity;
-        let mut max_abs = 0.0f64;
-        for point in equity_curve {
-            if point.equity > peak {
-                peak = point.equity;
-            }
-            let abs_dd = peak - point.equity;
-            if abs_dd > max_abs {
-                max_abs = abs_dd;
-            }
-        }
-        max_abs
-    } else {
-        0.0
-    };
+    let max_dd_absolute = stats.max_dd_abs;
     let recovery_factor = if max_dd_absolute > 0.0 {
         net_profit / max_dd_absolute
     } else {
@@ -162,12 +180,12 @@ pub fn calculate_metrics(
     // Prefer daily equity returns for Sharpe/Sortino/Omega — this gives a methodology-consistent
     // result regardless of trade frequency. Fall back to per-trade returns only when the
     // equity curve spans fewer than 2 calendar days (very short tests).
-    let daily_returns_cache = equity_to_daily_returns(equity_curve);
+    let daily_returns_cache = stats.daily_returns();
     let (sharpe_ratio, sortino_ratio) =
         if let Some((ref daily_returns, n_days)) = daily_returns_cache {
             // Trading days per year = observed days / calendar years.
             // This adapts automatically: ~252 for stocks, ~260 for forex, ~365 for crypto.
-            let cal_years = calendar_years_from_equity(equity_curve).unwrap_or(1.0);
+            let cal_years = stats.calendar_years().unwrap_or(1.0);
             let trading_days_per_year = (n_days as f64 / cal_years).max(1.0);
             (
                 calculate_sharpe(daily_returns, trading_days_per_year),
@@ -221,21 +239,26 @@ pub fn calculate_metrics(
     let mfe_max = mfe_max_acc;
 
     // ── Stagnation (longest period without new equity high) ──
-    let stagnation_bars = calculate_stagnation_bars(equity_curve);
+    let stagnation_bars = stats.stagnation_bars();
     let stagnation_time = format_bars(stagnation_bars, mpb);
 
     // ── Ulcer Index % ──
-    let ulcer_index_pct = calculate_ulcer_index(equity_curve);
+    let ulcer_index_pct = stats.ulcer_index();
 
     // ── Additional metrics ──
-    let k_ratio = calculate_k_ratio(equity_curve);
+    let k_ratio = stats.k_ratio();
     let omega_ratio = if let Some((ref daily_returns, _)) = daily_returns_cache {
         calculate_omega_ratio(daily_returns, 0.0)
     } else {
         0.0
     };
-    let monthly_returns = compute_monthly_returns(equity_curve);
+    let monthly_returns = stats.monthly_returns();
     let temporal_consistency = calculate_temporal_consistency(trades);
+    let exposure = calculate_exposure(trades, stats.count, bar_of);
+    let episodes = stats.drawdown_episodes();
+    let clustering = calculate_trade_clustering(trades);
+    let trending = calculate_regime_stats(trades, MarketRegime::Trending);
+    let ranging = calculate_regime_stats(trades, MarketRegime::Ranging);
 
     BacktestMetrics {
         final_capital,
@@ -292,8 +315,37 @@ pub fn calculate_metrics(
         omega_ratio,
         monthly_returns,
         temporal_consistency,
+        benchmark_return_pct: 0.0,
+        alpha: 0.0,
+        beta: 0.0,
+        correlation: 0.0,
+        exposure_pct: exposure.time_in_market_pct,
+        avg_simultaneous_positions: exposure.avg_simultaneous,
+        long_exposure_pct: exposure.long_pct,
+        short_exposure_pct: exposure.short_pct,
+        drawdown_episodes: episodes.count,
+        longest_drawdown_bars: episodes.longest_bars,
+        longest_drawdown_time: episodes.longest_time,
+        max_drawdown_recovery_bars: episodes.max_dd_recovery_bars,
+        max_drawdown_recovery_time: episodes.max_dd_recovery_time,
+        avg_drawdown_depth_pct: episodes.avg_depth_pct,
+        avg_drawdown_duration_bars: episodes.avg_duration_bars,
+        avg_minutes_between_trades: clustering.avg_gap_minutes,
+        clustered_trades: clustering.clustered,
+        max_trades_per_hour: clustering.max_per_hour,
+        overtrading: clustering.max_per_hour > DEFAULT_OVERTRADING_THRESHOLD,
+        trending_trades: trending.trades,
+        trending_win_rate_pct: trending.win_rate_pct,
+        trending_net_profit: trending.net_profit,
+        ranging_trades: ranging.trades,
+        ranging_win_rate_pct: ranging.win_rate_pct,
+        ranging_net_profit: ranging.net_profit,
         total_swap_charged: total_swap,
         total_commission_charged: total_commission,
+        total_spread_cost: total_spread,
+        total_slippage_cost: total_slippage,
+        total_costs,
+        gross_pnl_before_costs: net_profit + total_costs,
     }
 }
 
@@ -348,22 +400,45 @@ fn empty_metrics(initial_capital: f64) -> BacktestMetrics {
         omega_ratio: 0.0,
         monthly_returns: vec![],
         temporal_consistency: 0.0,
+        benchmark_return_pct: 0.0,
+        alpha: 0.0,
+        beta: 0.0,
+        correlation: 0.0,
+        exposure_pct: 0.0,
+        avg_simultaneous_positions: 0.0,
+        long_exposure_pct: 0.0,
+        short_exposure_pct: 0.0,
+        drawdown_episodes: 0,
+        longest_drawdown_bars: 0,
+        longest_drawdown_time: "0m".to_string(),
+        max_drawdown_recovery_bars: 0,
+        max_drawdown_recovery_time: "0m".to_string(),
+        avg_drawdown_depth_pct: 0.0,
+        avg_drawdown_duration_bars: 0.0,
+        avg_minutes_between_trades: 0.0,
+        clustered_trades: 0,
+        max_trades_per_hour: 0,
+        overtrading: false,
+        trending_trades: 0,
+        trending_win_rate_pct: 0.0,
+        trending_net_profit: 0.0,
+        ranging_trades: 0,
+        ranging_win_rate_pct: 0.0,
+        ranging_net_profit: 0.0,
         total_swap_charged: 0.0,
         total_commission_charged: 0.0,
+        total_spread_cost: 0.0,
+        total_slippage_cost: 0.0,
+        total_costs: 0.0,
+        gross_pnl_before_costs: 0.0,
     }
 }
 
-/// Extract the number of calendar years spanned by an equity curve using actual timestamps.
+/// Number of calendar years between two timestamps, from their `YYYY-MM-DD` prefixes.
 ///
-/// Parses the first 10 characters of each endpoint timestamp as `YYYY-MM-DD`.
-/// Returns `None` if parsing fails or the span is zero.
-fn calendar_years_from_equity(equity_curve: &[EquityPoint]) -> Option<f64> {
-    if equity_curve.len() < 2 {
-        return None;
-    }
-    let first_ts = &equity_curve.first()?.timestamp;
-    let last_ts = &equity_curve.last()?.timestamp;
-    // Accept both "YYYY-MM-DD" and "YYYY-MM-DD HH:MM:SS" by slicing the date prefix.
+/// Accepts both "YYYY-MM-DD" and "YYYY-MM-DD HH:MM:SS". Returns `None` if parsing
+/// fails or the span is zero.
+fn calendar_years(first_ts: &str, last_ts: &str) -> Option<f64> {
     let parse_date = |s: &str| -> Option<NaiveDate> {
         let date_part = s.get(..10)?;
         NaiveDate::parse_from_str(date_part, "%Y-%m-%d").ok()
@@ -379,10 +454,10 @@ fn calendar_years_from_equity(equity_curve: &[EquityPoint]) -> Option<f64> {
 
 /// Annualize a return percentage.
 ///
-/// Uses actual calendar days from the equity curve when available (preferred), falling back
-/// to bar-count estimation based on the timeframe's bars-per-day.
-fn annualize_return(total_return_pct: f64, bars: usize, bpd: f64, equity_curve: &[EquityPoint]) -> f64 {
-    let years = if let Some(cal_years) = calendar_years_from_equity(equity_curve) {
+/// Uses actual calendar years spanned by the equity curve when available (preferred), falling
+/// back to bar-count estimation based on the timeframe's bars-per-day.
+fn annualize_return(total_return_pct: f64, bars: usize, bpd: f64, calendar_years: Option<f64>) -> f64 {
+    let years = if let Some(cal_years) = calendar_years {
         cal_years
     } else {
         if bars == 0 {
@@ -401,63 +476,386 @@ fn annualize_return(total_return_pct: f64, bars: usize, bpd: f64, equity_curve:
     (total_factor.powf(1.0 / years) - 1.0) * 100.0
 }
 
-/// Calculate drawdown statistics from the equity curve.
-/// Returns (max_dd_pct, max_dd_duration_bars, avg_dd_pct).
-/// `max_dd_duration_bars` is measured peak-to-recovery (industry standard):
-/// the number of bars from the peak of the largest drawdown until equity returns
-/// to that peak level. If equity never recovers, the duration extends to the end
-/// of the data.
-fn calculate_drawdown_stats(equity_curve: &[EquityPoint]) -> (f64, usize, f64) {
-    if equity_curve.is_empty() {
-        return (0.0, 0, 0.0);
+/// Overwrite `buf` with `s`, reusing its allocation.
+fn set_str(buf: &mut String, s: &str) {
+    buf.clear();
+    buf.push_str(s);
+}
+
+/// Running inputs for every equity-curve metric, fed one point at a time.
+///
+/// Holds peaks, running sums and per-day/per-month closes instead of the curve itself,
+/// so a backtest that stores only a sample of its curve still gets metrics over every bar.
+#[derive(Debug, Clone, Default)]
+pub struct EquityStats {
+    count: usize,
+    first_timestamp: String,
+    last_timestamp: String,
+    first_equity: f64,
+    // Running peak, raised only by a strictly higher equity
+    peak: f64,
+    peak_idx: usize,
+    /// First point after `peak_idx` back at the peak level.
+    peak_regained: Option<usize>,
+    max_dd_pct: f64,
+    max_dd_abs: f64,
+    max_dd_peak_idx: usize,
+    max_dd_peak_equity: f64,
+    max_dd_recovered: Option<usize>,
+    dd_sum: f64,
+    dd_count: usize,
+    dd_sq_sum: f64,
+    stagnation: usize,
+    max_stagnation: usize,
+    // Online regression of ln(equity / first equity) on the point index (K-Ratio)
+    reg_n: usize,
+    reg_mean_x: f64,
+    reg_mean_y: f64,
+    reg_cxx: f64,
+    reg_cxy: f64,
+    reg_cyy: f64,
+    /// Last equity per calendar day, keyed YYYYMMDD.
+    by_day: BTreeMap<u32, f64>,
+    /// (first, last) equity per calendar month.
+    by_month: BTreeMap<(i32, u32), (f64, f64)>,
+    episodes: EpisodeTracker,
+}
+
+impl EquityStats {
+    pub fn from_curve(equity_curve: &[EquityPoint]) -> Self {
+        let mut stats = Self::default();
+        for point in equity_curve {
+            stats.push(&point.timestamp, point.equity);
+        }
+        stats
     }
 
-    let mut peak = equity_curve[0].equity;
-    let mut max_dd_pct = 0.0f64;
-    let mut current_dd_peak_idx = 0usize;
-    let mut max_dd_peak_idx = 0usize; // index where the max-DD peak occurred
-    let mut dd_sum = 0.0f64;
-    let mut dd_count = 0usize;
+    /// Add the next equity point.
+    pub fn push(&mut self, timestamp: &str, equity: f64) {
+        let i = self.count;
+        if i == 0 {
+            self.first_timestamp = timestamp.to_string();
+            self.first_equity = equity;
+            self.peak = equity;
+            self.max_dd_peak_equity = equity;
+        }
+        self.count += 1;
+        set_str(&mut self.last_timestamp, timestamp);
 
-    for (i, point) in equity_curve.iter().enumerate() {
-        if point.equity > peak {
-            peak = point.equity;
-            current_dd_peak_idx = i;
+        // Peak-to-recovery of the largest drawdown: first point back at its peak
+        if i > self.max_dd_peak_idx && self.max_dd_recovered.is_none() && equity >= self.max_dd_peak_equity {
+            self.max_dd_recovered = Some(i);
+        }
+        if equity > self.peak {
+            self.peak = equity;
+            self.peak_idx = i;
+            self.peak_regained = None;
+            self.stagnation = 0;
+        } else {
+            if i > self.peak_idx && self.peak_regained.is_none() && equity >= self.peak {
+                self.peak_regained = Some(i);
+            }
+            if i > 0 {
+                self.stagnation += 1;
+                self.max_stagnation = self.max_stagnation.max(self.stagnation);
+            }
+        }
+        let dd_pct = if self.peak > 0.0 {
+            (self.peak - equity) / self.peak * 100.0
+        } else {
+            0.0
+        };
+        if dd_pct > self.max_dd_pct {
+            self.max_dd_pct = dd_pct;
+            self.max_dd_peak_idx = self.peak_idx;
+            self.max_dd_peak_equity = self.peak;
+            self.max_dd_recovered = self.peak_regained;
+        }
+        if dd_pct > 0.0 {
+            self.dd_sum += dd_pct;
+            self.dd_count += 1;
+        }
+        self.dd_sq_sum += dd_pct * dd_pct;
+        if self.peak - equity > self.max_dd_abs {
+            self.max_dd_abs = self.peak - equity;
         }
 
-        let dd_pct = if peak > 0.0 {
-            (peak - point.equity) / peak * 100.0
+        // y = log(equity / first equity), x = point index; non-positive equity is skipped
+        if self.first_equity > 0.0 && equity > 0.0 {
+            let (x, y) = (i as f64, (equity / self.first_equity).ln());
+            self.reg_n += 1;
+            let n = self.reg_n as f64;
+            let dx = x - self.reg_mean_x;
+            let dy = y - self.reg_mean_y;
+            self.reg_mean_x += dx / n;
+            self.reg_mean_y += dy / n;
+            self.reg_cxx += dx * (x - self.reg_mean_x);
+            self.reg_cxy += dx * (y - self.reg_mean_y);
+            self.reg_cyy += dy * (y - self.reg_mean_y);
+        }
+
+        let ts = timestamp.as_bytes();
+        let date_key: u32 = if ts.len() >= 10 {
+            parse_4digits(ts, 0) * 10000 + parse_2digits(ts, 5) * 100 + parse_2digits(ts, 8)
+        } else {
+            0
+        };
+        self.by_day.insert(date_key, equity);
+
+        // Parse YYYY-MM from timestamp prefix
+        if timestamp.len() >= 7 {
+            let year: i32 = timestamp[0..4].parse().unwrap_or(0);
+            let month: u32 = timestamp[5..7].parse().unwrap_or(0);
+            if year != 0 && month != 0 {
+                self.by_month.entry((year, month)).or_insert((equity, equity)).1 = equity;
+            }
+        }
+
+        self.episodes.push(i, timestamp, equity);
+    }
+
+    /// Calendar years between the first and last point.
+    fn calendar_years(&self) -> Option<f64> {
+        if self.count < 2 {
+            return None;
+        }
+        calendar_years(&self.first_timestamp, &self.last_timestamp)
+    }
+
+    /// Drawdown statistics: (max_dd_pct, max_dd_duration_bars, avg_dd_pct).
+    ///
+    /// `max_dd_duration_bars` is measured peak-to-recovery (industry standard):
+    /// the number of bars from the peak of the largest drawdown until equity returns
+    /// to that peak level. If equity never recovers, the duration extends to the end
+    /// of the data.
+    fn drawdown_stats(&self) -> (f64, usize, f64) {
+        if self.count == 0 {
+            return (0.0, 0, 0.0);
+        }
+        let recovered_idx = self.max_dd_recovered.unwrap_or(self.count);
+        let avg_dd = if self.dd_count > 0 {
+            self.dd_sum / self.dd_count as f64
         } else {
             0.0
         };
+        (self.max_dd_pct, recovered_idx - self.max_dd_peak_idx, avg_dd)
+    }
+
+    /// Longest period (in bars) without making a new equity high.
+    fn stagnation_bars(&self) -> usize {
+        if self.count < 2 { 0 } else { self.max_stagnation }
+    }
 
-        if dd_pct > max_dd_pct {
-            max_dd_pct = dd_pct;
-            max_dd_peak_idx = current_dd_peak_idx;
+    /// Ulcer Index percentage: sqrt(mean(drawdown_pct²)) from the running peak.
+    fn ulcer_index(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
         }
+        (self.dd_sq_sum / self.count as f64).sqrt()
+    }
 
-        if dd_pct > 0.0 {
-            dd_sum += dd_pct;
-            dd_count += 1;
+    /// K-Ratio: measures the consistency of the equity curve growth.
+    ///
+    /// Fits a linear regression on `log(equity[i] / equity[0])` vs bar index i.
+    /// Returns `slope / std_error_of_slope * sqrt(n)`, normalized to be comparable across
+    /// strategies. Higher is better; values > 1.0 indicate consistent growth.
+    fn k_ratio(&self) -> f64 {
+        if self.count < 4 || self.first_equity <= 0.0 || self.reg_n < 4 || self.reg_cxx <= 0.0 {
+            return 0.0;
+        }
+        let nf = self.reg_n as f64;
+        let slope = self.reg_cxy / self.reg_cxx;
+        let ss_res = self.reg_cyy - self.reg_cxy * slope;
+        // Running co-moments leave rounding residue; treat a near-perfect fit as no error
+        if ss_res <= self.reg_cyy * 1e-12 {
+            return 0.0;
+        }
+        let std_err = (ss_res / (nf - 2.0) / self.reg_cxx).sqrt();
+        if std_err < f64::EPSILON {
+            return 0.0;
         }
+        (slope / std_err) * nf.sqrt()
     }
 
-    // Peak-to-recovery: find first bar after max_dd_peak_idx where equity >= peak equity
-    let max_dd_peak_equity = equity_curve[max_dd_peak_idx].equity;
-    let recovered_idx = equity_curve[(max_dd_peak_idx + 1)..]
-        .iter()
-        .position(|p| p.equity >= max_dd_peak_equity)
-        .map(|pos| max_dd_peak_idx + 1 + pos)
-        .unwrap_or(equity_curve.len()); // never recovered — use end of data
-    let max_dd_duration = recovered_idx - max_dd_peak_idx;
+    /// Daily percentage returns and observed trading-day count.
+    ///
+    /// Takes the last equity value of each calendar day (first 10 chars of the timestamp,
+    /// assumed to be `YYYY-MM-DD`) and computes day-over-day returns. Returns `None` when
+    /// fewer than 2 trading days are present.
+    ///
+    /// The second element of the tuple is the count of unique trading days, which callers
+    /// use to derive `trading_days_per_year` for the annualization factor.
+    fn daily_returns(&self) -> Option<(Vec<f64>, usize)> {
+        if self.count < 2 {
+            return None;
+        }
+        let daily: Vec<f64> = self.by_day.values().copied().collect();
+        let n_days = daily.len();
+        if n_days < 2 {
+            return None;
+        }
 
-    let avg_dd = if dd_count > 0 {
-        dd_sum / dd_count as f64
-    } else {
-        0.0
-    };
+        let returns: Vec<f64> = daily
+            .windows(2)
+            .filter_map(|w| {
+                if w[0] > 0.0 {
+                    Some((w[1] - w[0]) / w[0])
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if returns.len() < 2 {
+            return None;
+        }
+
+        Some((returns, n_days))
+    }
+
+    /// Percentage return from the first to the last equity value of each calendar month.
+    fn monthly_returns(&self) -> Vec<MonthlyReturn> {
+        if self.count < 2 {
+            return vec![];
+        }
+        self.by_month
+            .iter()
+            .filter_map(|(&(year, month), &(first, last))| {
+                if first > 0.0 {
+                    Some(MonthlyReturn {
+                        year,
+                        month,
+                        return_pct: (last - first) / first * 100.0,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Drawdown episodes, with an episode still open at the last point run to the end of data.
+    fn drawdown_episodes(&self) -> DrawdownEpisodes {
+        self.episodes.finish(self.count, &self.last_timestamp)
+    }
+}
+
+/// Per-episode drawdown statistics.
+#[derive(Debug, Clone)]
+struct DrawdownEpisodes {
+    count: usize,
+    longest_bars: usize,
+    longest_time: String,
+    max_dd_recovery_bars: usize,
+    max_dd_recovery_time: String,
+    avg_depth_pct: f64,
+    avg_duration_bars: f64,
+}
+
+impl Default for DrawdownEpisodes {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            longest_bars: 0,
+            longest_time: "0m".to_string(),
+            max_dd_recovery_bars: 0,
+            max_dd_recovery_time: "0m".to_string(),
+            avg_depth_pct: 0.0,
+            avg_duration_bars: 0.0,
+        }
+    }
+}
+
+/// Minutes between two equity-curve timestamps, or `None` if either fails to parse.
+fn minutes_between(from: &str, to: &str) -> Option<i64> {
+    Some((parse_timestamp(to)? - parse_timestamp(from)?).num_minutes().max(0))
+}
+
+/// Parse `YYYY-MM-DD HH:MM[:SS[.f]]`, or a bare date as midnight.
+fn parse_timestamp(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M"))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+}
+
+/// Wall-clock span between two timestamps, formatted like a duration; empty if unparseable.
+fn wall_clock(from: &str, to: &str) -> String {
+    minutes_between(from, to).map_or_else(String::new, |m| format_bars(m as usize, 1))
+}
+
+/// Splits the equity curve into drawdown episodes as points arrive.
+///
+/// An episode opens when equity falls below the running peak and closes on the
+/// first point that regains it; its duration is measured from the peak point, matching
+/// `max_drawdown_duration_bars`.
+#[derive(Debug, Clone, Default)]
+struct EpisodeTracker {
+    peak: f64,
+    peak_idx: usize,
+    peak_timestamp: String,
+    /// (deepest %, trough index) of the episode in progress
+    open: Option<(f64, usize)>,
+    trough_timestamp: String,
+    depth_sum: f64,
+    duration_sum: usize,
+    max_depth: f64,
+    out: DrawdownEpisodes,
+}
+
+impl EpisodeTracker {
+    fn push(&mut self, i: usize, timestamp: &str, equity: f64) {
+        if i == 0 {
+            self.peak = equity;
+        }
+        if equity >= self.peak {
+            if let Some((depth, trough)) = self.open.take() {
+                self.close(i, timestamp, depth, trough);
+            }
+            self.peak = equity;
+            self.peak_idx = i;
+            set_str(&mut self.peak_timestamp, timestamp);
+            return;
+        }
+        let dd_pct = if self.peak > 0.0 { (self.peak - equity) / self.peak * 100.0 } else { 0.0 };
+        match self.open {
+            Some((depth, _)) if dd_pct <= depth => {}
+            _ => {
+                self.open = Some((dd_pct, i));
+                set_str(&mut self.trough_timestamp, timestamp);
+            }
+        }
+    }
+
+    fn close(&mut self, end: usize, end_timestamp: &str, depth: f64, trough: usize) {
+        let bars = end - self.peak_idx;
+        self.out.count += 1;
+        self.depth_sum += depth;
+        self.duration_sum += bars;
+        if bars > self.out.longest_bars {
+            self.out.longest_bars = bars;
+            self.out.longest_time = wall_clock(&self.peak_timestamp, end_timestamp);
+        }
+        if depth > self.max_depth {
+            self.max_depth = depth;
+            self.out.max_dd_recovery_bars = end - trough;
+            self.out.max_dd_recovery_time = wall_clock(&self.trough_timestamp, end_timestamp);
+        }
+    }
 
-    (max_dd_pct, max_dd_duration, avg_dd)
+    /// Episode figures after `count` points, closing an unrecovered episode at the end.
+    fn finish(&self, count: usize, last_timestamp: &str) -> DrawdownEpisodes {
+        let mut tracker = self.clone();
+        if let Some((depth, trough)) = tracker.open.take() {
+            tracker.close(count, last_timestamp, depth, trough);
+        }
+        let mut out = tracker.out;
+        if out.count > 0 {
+            out.avg_depth_pct = tracker.depth_sum / out.count as f64;
+            out.avg_duration_bars = tracker.duration_sum as f64 / out.count as f64;
+        }
+        out
+    }
 }
 
 /// Sharpe Ratio: mean(returns) / std(returns) * sqrt(annualization_factor).
@@ -475,60 +873,6 @@ fn calculate_sharpe(returns: &[f64], annualization_factor: f64) -> f64 {
     (mean / std_dev) * annualization_factor.sqrt()
 }
 
-/// Convert an equity curve to daily percentage returns and observed trading-day count.
-///
-/// Groups equity curve points by their calendar date (first 10 chars of timestamp,
-/// assumed to be `YYYY-MM-DD`). Takes the last equity value of each day and computes
-/// day-over-day returns. Returns `None` when fewer than 2 trading days are present.
-///
-/// The second element of the tuple is the count of unique trading days, which callers
-/// use to derive `trading_days_per_year` for the annualization factor.
-fn equity_to_daily_returns(equity_curve: &[EquityPoint]) -> Option<(Vec<f64>, usize)> {
-    use std::collections::BTreeMap;
-
-    if equity_curve.len() < 2 {
-        return None;
-    }
-
-    // Last equity value per calendar day (BTreeMap keeps u32 YYYYMMDD keys in order)
-    let mut by_day: BTreeMap<u32, f64> = BTreeMap::new();
-    for pt in equity_curve {
-        let ts = pt.timestamp.as_bytes();
-        let date_key: u32 = if ts.len() >= 10 {
-            let y = parse_4digits(ts, 0);
-            let m = parse_2digits(ts, 5);
-            let d = parse_2digits(ts, 8);
-            y * 10000 + m * 100 + d
-        } else {
-            0
-        };
-        by_day.insert(date_key, pt.equity);
-    }
-
-    let daily: Vec<f64> = by_day.values().copied().collect();
-    let n_days = daily.len();
-    if n_days < 2 {
-        return None;
-    }
-
-    let returns: Vec<f64> = daily
-        .windows(2)
-        .filter_map(|w| {
-            if w[0] > 0.0 {
-                Some((w[1] - w[0]) / w[0])
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    if returns.len() < 2 {
-        return None;
-    }
-
-    Some((returns, n_days))
-}
-
 /// Sortino Ratio: mean(returns) / downside_deviation * sqrt(annualization_factor).
 fn calculate_sortino(returns: &[f64], annualization_factor: f64) -> f64 {
     let n = returns.len();
@@ -632,130 +976,6 @@ fn calculate_consecutive(trades: &[TradeResult]) -> (usize, usize, f64, f64) {
     (max_wins, max_losses, avg_wins, avg_losses)
 }
 
-/// Calculate stagnation: longest period (in bars) without making a new equity high.
-fn calculate_stagnation_bars(equity_curve: &[EquityPoint]) -> usize {
-    if equity_curve.len() < 2 {
-        return 0;
-    }
-    let mut peak = equity_curve[0].equity;
-    let mut current_stag = 0usize;
-    let mut max_stag = 0usize;
-
-    for point in equity_curve.iter().skip(1) {
-        if point.equity > peak {
-            peak = point.equity;
-            current_stag = 0;
-        } else {
-            current_stag += 1;
-            if current_stag > max_stag {
-                max_stag = current_stag;
-            }
-        }
-    }
-    max_stag
-}
-
-/// Calculate Ulcer Index percentage from the equity curve.
-/// UI = sqrt(mean(drawdown_pct²)) where drawdown_pct is measured from the running peak.
-fn calculate_ulcer_index(equity_curve: &[EquityPoint]) -> f64 {
-    if equity_curve.len() < 2 {
-        return 0.0;
-    }
-    let mut peak = equity_curve[0].equity;
-    let mut sum_sq = 0.0f64;
-    let n = equity_curve.len();
-
-    for point in equity_curve.iter() {
-        if point.equity > peak {
-            peak = point.equity;
-        }
-        let dd_pct = if peak > 0.0 {
-            (peak - point.equity) / peak * 100.0
-        } else {
-            0.0
-        };
-        sum_sq += dd_pct * dd_pct;
-    }
-    (sum_sq / n as f64).sqrt()
-}
-
-/// K-Ratio: measures the consistency of the equity curve growth.
-///
-/// Fits a linear regression on `log(equity[i] / equity[0])` vs bar index i.
-/// Returns `slope / std_error_of_slope * sqrt(n)`, normalized to be comparable across
-/// strategies. Higher is better; values > 1.0 indicate consistent growth.
-fn calculate_k_ratio(equity_curve: &[EquityPoint]) -> f64 {
-    let n = equity_curve.len();
-    if n < 4 {
-        return 0.0;
-    }
-    let base = equity_curve[0].equity;
-    if base <= 0.0 {
-        return 0.0;
-    }
-
-    // y[i] = log(equity[i] / base), x[i] = i
-    let mut sum_x = 0.0f64;
-    let mut sum_y = 0.0f64;
-    let mut sum_xx = 0.0f64;
-    let mut sum_xy = 0.0f64;
-    let mut valid = 0usize;
-
-    for (i, pt) in equity_curve.iter().enumerate() {
-        if pt.equity <= 0.0 {
-            continue;
-        }
-        let x = i as f64;
-        let y = (pt.equity / base).ln();
-        sum_x += x;
-        sum_y += y;
-        sum_xx += x * x;
-        sum_xy += x * y;
-        valid += 1;
-    }
-
-    if valid < 4 {
-        return 0.0;
-    }
-    let nf = valid as f64;
-    let denom = nf * sum_xx - sum_x * sum_x;
-    if denom.abs() < f64::EPSILON {
-        return 0.0;
-    }
-
-    let slope = (nf * sum_xy - sum_x * sum_y) / denom;
-    let intercept = (sum_y - slope * sum_x) / nf;
-
-    // Residual sum of squares
-    let mut ss_res = 0.0f64;
-    for (i, pt) in equity_curve.iter().enumerate() {
-        if pt.equity <= 0.0 {
-            continue;
-        }
-        let y = (pt.equity / base).ln();
-        let y_hat = intercept + slope * i as f64;
-        ss_res += (y - y_hat).powi(2);
-    }
-
-    let var_slope = if valid > 2 {
-        let mean_x = sum_x / nf;
-        let ss_xx = sum_xx - nf * mean_x * mean_x;
-        if ss_xx > 0.0 {
-            (ss_res / (nf - 2.0)) / ss_xx
-        } else {
-            return 0.0;
-        }
-    } else {
-        return 0.0;
-    };
-
-    let std_err = var_slope.sqrt();
-    if std_err < f64::EPSILON {
-        return 0.0;
-    }
-    (slope / std_err) * (nf.sqrt())
-}
-
 /// Omega Ratio: probability-weighted ratio of gains to losses above/below threshold.
 ///
 /// `omega = sum(max(r - threshold, 0)) / sum(max(threshold - r, 0))` over daily returns.
@@ -770,49 +990,49 @@ fn calculate_omega_ratio(returns: &[f64], threshold: f64) -> f64 {
     }
 }
 
-/// Compute monthly returns from the equity curve.
+/// Bucket realized trade PnL by entry hour and entry weekday.
 ///
-/// Groups equity-curve points by YYYY-MM calendar month and returns the
-/// percentage return from the first to the last equity value in each month.
-fn compute_monthly_returns(equity_curve: &[EquityPoint]) -> Vec<MonthlyReturn> {
-    use std::collections::BTreeMap;
-
-    if equity_curve.len() < 2 {
-        return vec![];
-    }
-
-    // (year, month) → (first_equity, last_equity)
-    let mut by_month: BTreeMap<(i32, u32), (f64, f64)> = BTreeMap::new();
+/// Trades whose `entry_time` doesn't start with `YYYY-MM-DD HH` are skipped.
+pub fn seasonality_report(trades: &[TradeResult]) -> SeasonalityReport {
+    // (count, wins, total_pnl) per bucket
+    let mut hours = [(0usize, 0usize, 0.0f64); 24];
+    let mut weekdays = [(0usize, 0usize, 0.0f64); 7];
 
-    for pt in equity_curve {
-        let ts = &pt.timestamp;
-        if ts.len() < 7 {
+    for trade in trades {
+        let b = trade.entry_time.as_bytes();
+        if b.len() < 13 || !b[..13].iter().enumerate().all(|(i, c)| matches!(i, 4 | 7 | 10) || c.is_ascii_digit()) {
             continue;
         }
-        // Parse YYYY-MM from timestamp prefix
-        let year: i32 = ts[0..4].parse().unwrap_or(0);
-        let month: u32 = ts[5..7].parse().unwrap_or(0);
-        if year == 0 || month == 0 {
+        let hour = parse_2digits(b, 11);
+        let Some(date) = NaiveDate::from_ymd_opt(parse_4digits(b, 0) as i32, parse_2digits(b, 5), parse_2digits(b, 8)) else {
+            continue;
+        };
+        if hour > 23 {
             continue;
         }
-        let entry = by_month.entry((year, month)).or_insert((pt.equity, pt.equity));
-        entry.1 = pt.equity; // keep updating last
+        let weekday = date.weekday().number_from_monday();
+        for bucket in [&mut hours[hour as usize], &mut weekdays[weekday as usize - 1]] {
+            bucket.0 += 1;
+            bucket.1 += usize::from(trade.pnl > 0.0);
+            bucket.2 += trade.pnl;
+        }
     }
 
-    by_month
-        .into_iter()
-        .filter_map(|((year, month), (first, last))| {
-            if first > 0.0 {
-                Some(MonthlyReturn {
-                    year,
-                    month,
-                    return_pct: (last - first) / first * 100.0,
-                })
-            } else {
-                None
-            }
-        })
-        .collect()
+    let to_buckets = |stats: &[(usize, usize, f64)], first: u32| -> Vec<SeasonalityBucket> {
+        stats
+            .iter()
+            .zip(first..)
+            .map(|(&(count, wins, total), bucket)| SeasonalityBucket {
+                bucket,
+                trade_count: count,
+                total_pnl: total,
+                avg_pnl: if count > 0 { total / count as f64 } else { 0.0 },
+                win_rate_pct: if count > 0 { wins as f64 / count as f64 * 100.0 } else { 0.0 },
+            })
+            .collect()
+    };
+
+    SeasonalityReport { by_hour: to_buckets(&hours, 0), by_weekday: to_buckets(&weekdays, 1) }
 }
 
 /// Temporal consistency: splits trades into 3 chronological thirds, computes
@@ -855,6 +1075,131 @@ fn calculate_temporal_consistency(trades: &[crate::models::trade::TradeResult])
     (mean / (std + 1.0)).clamp(-5.0, 5.0)
 }
 
+/// How tightly trade entries bunch together in time.
+struct TradeClustering {
+    avg_gap_minutes: f64,
+    clustered: usize,
+    max_per_hour: usize,
+}
+
+/// Entry-time clustering: the mean gap between consecutive entries, how many entries
+/// follow the previous one within an hour, and the most entries in any rolling hour
+/// (window `[t, t + 60m)`). Trades with an unparseable `entry_time` are ignored.
+fn calculate_trade_clustering(trades: &[TradeResult]) -> TradeClustering {
+    let mut entries: Vec<NaiveDateTime> = trades.iter().filter_map(|t| parse_timestamp(&t.entry_time)).collect();
+    entries.sort_unstable();
+
+    let hour = chrono::Duration::hours(1);
+    let gaps: Vec<chrono::Duration> = entries.windows(2).map(|w| w[1] - w[0]).collect();
+    let avg_gap_minutes = if gaps.is_empty() {
+        0.0
+    } else {
+        gaps.iter().map(|g| g.num_seconds() as f64 / 60.0).sum::<f64>() / gaps.len() as f64
+    };
+
+    // Two-pointer sweep: `start` is the earliest entry still within an hour of `end`
+    let mut max_per_hour = 0usize;
+    let mut start = 0usize;
+    for end in 0..entries.len() {
+        while entries[end] - entries[start] >= hour {
+            start += 1;
+        }
+        max_per_hour = max_per_hour.max(end - start + 1);
+    }
+
+    TradeClustering {
+        avg_gap_minutes,
+        clustered: gaps.iter().filter(|&&g| g < hour).count(),
+        max_per_hour,
+    }
+}
+
+/// Re-flag `metrics.overtrading` against a run-specific threshold
+/// (`BacktestConfig::overtrading_threshold`).
+pub fn apply_overtrading_threshold(metrics: &mut BacktestMetrics, threshold: usize) {
+    metrics.overtrading = metrics.max_trades_per_hour > threshold;
+}
+
+/// Trade count, win rate and net P&L of the trades entered in one market regime.
+struct RegimeStats {
+    trades: usize,
+    win_rate_pct: f64,
+    net_profit: f64,
+}
+
+/// Stats over the trades tagged with `regime`. Wins use the same `pnl >= 1e-6`
+/// threshold as the overall win rate; untagged trades are ignored.
+fn calculate_regime_stats(trades: &[TradeResult], regime: MarketRegime) -> RegimeStats {
+    let mut count = 0usize;
+    let mut wins = 0usize;
+    let mut net_profit = 0.0;
+    for t in trades.iter().filter(|t| t.regime == Some(regime)) {
+        count += 1;
+        if t.pnl >= 1e-6 {
+            wins += 1;
+        }
+        net_profit += t.pnl - t.commission + t.swap;
+    }
+    RegimeStats {
+        trades: count,
+        win_rate_pct: if count > 0 { wins as f64 / count as f64 * 100.0 } else { 0.0 },
+        net_profit,
+    }
+}
+
+/// Time-in-market figures derived from the position timeline.
+struct Exposure {
+    time_in_market_pct: f64,
+    avg_simultaneous: f64,
+    long_pct: f64,
+    short_pct: f64,
+}
+
+/// Walk the equity-curve bars and count how many positions are open on each.
+///
+/// A trade occupies the bars `[entry_bar, exit_bar)` — the same span as
+/// `duration_bars` — located by timestamp so overlapping trades (pyramiding)
+/// stack. Sub-bar entry/exit times map to the bar that contains them.
+fn calculate_exposure(trades: &[TradeResult], n: usize, bar_of: impl Fn(&str) -> usize) -> Exposure {
+    if n == 0 {
+        return Exposure { time_in_market_pct: 0.0, avg_simultaneous: 0.0, long_pct: 0.0, short_pct: 0.0 };
+    }
+
+    // Difference arrays: +1 at the entry bar, -1 at the exit bar.
+    let mut long_delta = vec![0i64; n + 1];
+    let mut short_delta = vec![0i64; n + 1];
+    for t in trades {
+        let start = bar_of(&t.entry_time);
+        let end = bar_of(&t.exit_time).max(start);
+        let delta = match t.direction {
+            TradeDirection::Short => &mut short_delta,
+            TradeDirection::Long | TradeDirection::Both => &mut long_delta,
+        };
+        delta[start] += 1;
+        delta[end] -= 1;
+    }
+
+    let (mut long_open, mut short_open) = (0i64, 0i64);
+    let (mut bars_in_market, mut long_bars, mut short_bars, mut position_bars) = (0usize, 0usize, 0usize, 0i64);
+    for i in 0..n {
+        long_open += long_delta[i];
+        short_open += short_delta[i];
+        if long_open > 0 { long_bars += 1; }
+        if short_open > 0 { short_bars += 1; }
+        if long_open + short_open > 0 {
+            bars_in_market += 1;
+            position_bars += long_open + short_open;
+        }
+    }
+
+    Exposure {
+        time_in_market_pct: bars_in_market as f64 / n as f64 * 100.0,
+        avg_simultaneous: if bars_in_market > 0 { position_bars as f64 / bars_in_market as f64 } else { 0.0 },
+        long_pct: long_bars as f64 / n as f64 * 100.0,
+        short_pct: short_bars as f64 / n as f64 * 100.0,
+    }
+}
+
 /// Compute metrics for a subset of trades (e.g. long-only or short-only).
 ///
 /// Builds a synthetic equity curve from the filtered trades starting at `initial_capital`.
@@ -878,6 +1223,201 @@ pub fn calculate_direction_metrics(
     Some(calculate_metrics(trades, &curve, initial_capital, timeframe))
 }
 
+/// Buy-and-hold benchmark: `initial_capital` bought at the first close and marked
+/// to every following close. One point per candle, so passing the bars the strategy
+/// ran over yields a curve aligned with its equity curve.
+pub fn buy_and_hold_curve(candles: &[Candle], initial_capital: f64) -> Vec<EquityPoint> {
+    let first_close = match candles.first() {
+        Some(c) if c.close > 0.0 => c.close,
+        _ => return Vec::new(),
+    };
+    candles
+        .iter()
+        .map(|c| EquityPoint {
+            timestamp: c.datetime.clone(),
+            equity: initial_capital * c.close / first_close,
+        })
+        .collect()
+}
+
+/// Fill the benchmark fields of `metrics` from per-bar returns of the strategy
+/// equity curve against a buy-and-hold curve over the same bars.
+pub fn apply_benchmark_metrics(
+    metrics: &mut BacktestMetrics,
+    equity_curve: &[EquityPoint],
+    benchmark: &[EquityPoint],
+) {
+    let mut stats = BenchmarkStats::default();
+    for (point, bench) in equity_curve.iter().zip(benchmark) {
+        stats.push(point.equity, bench.equity);
+    }
+    stats.apply(metrics);
+}
+
+/// Running co-moments of per-bar strategy and buy-and-hold returns, fed one bar at a time.
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkStats {
+    first: f64,
+    last: f64,
+    prev: Option<(f64, f64)>,
+    n: usize,
+    mean_s: f64,
+    mean_b: f64,
+    cov: f64,
+    var_s: f64,
+    var_b: f64,
+}
+
+impl BenchmarkStats {
+    /// Add the strategy and benchmark equity of the next bar.
+    pub fn push(&mut self, equity: f64, benchmark: f64) {
+        match self.prev {
+            None => self.first = benchmark,
+            Some((prev_s, prev_b)) => {
+                let bar_return = |prev: f64, cur: f64| if prev != 0.0 { cur / prev - 1.0 } else { 0.0 };
+                let (s, b) = (bar_return(prev_s, equity), bar_return(prev_b, benchmark));
+                self.n += 1;
+                let n = self.n as f64;
+                let ds = s - self.mean_s;
+                let db = b - self.mean_b;
+                self.mean_s += ds / n;
+                self.mean_b += db / n;
+                self.cov += ds * (b - self.mean_b);
+                self.var_s += ds * (s - self.mean_s);
+                self.var_b += db * (b - self.mean_b);
+            }
+        }
+        self.last = benchmark;
+        self.prev = Some((equity, benchmark));
+    }
+
+    /// Fill the benchmark fields of `metrics`.
+    ///
+    /// `beta = cov(s, b) / var(b)`, `correlation = cov(s, b) / (σs · σb)` and
+    /// `alpha = total_return_pct − beta × benchmark_return_pct` (zero risk-free rate).
+    pub fn apply(&self, metrics: &mut BacktestMetrics) {
+        if self.prev.is_none() || self.first <= 0.0 {
+            return;
+        }
+        metrics.benchmark_return_pct = (self.last / self.first - 1.0) * 100.0;
+        if self.n < 2 {
+            return;
+        }
+        metrics.beta = if self.var_b > 0.0 { self.cov / self.var_b } else { 0.0 };
+        metrics.correlation = if self.var_s > 0.0 && self.var_b > 0.0 {
+            self.cov / (self.var_s * self.var_b).sqrt()
+        } else {
+            0.0
+        };
+        metrics.alpha = metrics.total_return_pct - metrics.beta * metrics.benchmark_return_pct;
+    }
+}
+
+/// Rolling Sharpe and Sortino over the last `window` per-point equity returns, one
+/// point per equity point so the series lines up with `equity_curve`.
+///
+/// Same formulas as [`calculate_sharpe`] / [`calculate_sortino`], annualized by the
+/// observed points per calendar year (see [`rolling_periods_per_year`]). Points before
+/// the first full window are `None`.
+pub fn rolling_risk_ratios(
+    equity_curve: &[EquityPoint],
+    window: usize,
+    timeframe: Timeframe,
+) -> Vec<RollingRatioPoint> {
+    let (Some(first), Some(last)) = (equity_curve.first(), equity_curve.last()) else {
+        return Vec::new();
+    };
+    let periods_per_year = rolling_periods_per_year(&first.timestamp, &last.timestamp, equity_curve.len(), timeframe);
+    let mut rolling = RollingRatios::new(window, periods_per_year);
+    equity_curve
+        .iter()
+        .map(|pt| {
+            let (sharpe, sortino) = rolling.push(pt.equity);
+            RollingRatioPoint { timestamp: pt.timestamp.clone(), sharpe, sortino }
+        })
+        .collect()
+}
+
+/// Equity points per calendar year for a curve of `points` points between two timestamps,
+/// or bars per year of `timeframe` when it spans under a day.
+pub fn rolling_periods_per_year(first_ts: &str, last_ts: &str, points: usize, timeframe: Timeframe) -> f64 {
+    (points >= 2)
+        .then(|| calendar_years(first_ts, last_ts))
+        .flatten()
+        .map(|years| (points - 1) as f64 / years)
+        .unwrap_or(252.0 * bars_per_day(timeframe))
+}
+
+/// Annualisation factor [`RollingRatios`] applies to Sharpe/Sortino.
+pub fn rolling_ratio_scale(periods_per_year: f64) -> f64 {
+    periods_per_year.max(1.0).sqrt()
+}
+
+/// Rolling Sharpe/Sortino fed one equity point at a time; see [`rolling_risk_ratios`].
+#[derive(Debug, Clone)]
+pub struct RollingRatios {
+    window: usize,
+    scale: f64,
+    prev: Option<f64>,
+    returns: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+    neg_sq: f64,
+    neg_count: usize,
+}
+
+impl RollingRatios {
+    pub fn new(window: usize, periods_per_year: f64) -> Self {
+        let window = window.max(2);
+        Self {
+            window,
+            scale: rolling_ratio_scale(periods_per_year),
+            prev: None,
+            returns: VecDeque::with_capacity(window + 1),
+            sum: 0.0,
+            sum_sq: 0.0,
+            neg_sq: 0.0,
+            neg_count: 0,
+        }
+    }
+
+    /// Add the next equity point and return its (Sharpe, Sortino).
+    pub fn push(&mut self, equity: f64) -> (Option<f64>, Option<f64>) {
+        // The return ending at this point enters the window; the oldest one leaves
+        if let Some(prev) = self.prev {
+            let r = if prev > 0.0 { equity / prev - 1.0 } else { 0.0 };
+            self.sum += r;
+            self.sum_sq += r * r;
+            if r < 0.0 {
+                self.neg_sq += r * r;
+                self.neg_count += 1;
+            }
+            self.returns.push_back(r);
+            if self.returns.len() > self.window {
+                let r = self.returns.pop_front().unwrap_or_default();
+                self.sum -= r;
+                self.sum_sq -= r * r;
+                if r < 0.0 {
+                    self.neg_sq -= r * r;
+                    self.neg_count -= 1;
+                }
+            }
+        }
+        self.prev = Some(equity);
+        if self.returns.len() < self.window {
+            return (None, None);
+        }
+        let w = self.window as f64;
+        let mean = self.sum / w;
+        // Running sums leave rounding residue; treat near-zero spread as none
+        let std_dev = ((self.sum_sq - self.sum * self.sum / w) / (w - 1.0)).max(0.0).sqrt();
+        let sharpe = if std_dev > 1e-12 { mean / std_dev * self.scale } else { 0.0 };
+        let downside_dev = (self.neg_sq.max(0.0) / w).sqrt();
+        let sortino = if self.neg_count > 0 && downside_dev > 1e-12 { mean / downside_dev * self.scale } else { 0.0 };
+        (Some(sharpe), Some(sortino))
+    }
+}
+
 /// Format a number of bars to a human-readable duration, given minutes per bar.
 fn format_bars(bars: usize, minutes_per_bar: u32) -> String {
     let total_minutes = bars as u64 * minutes_per_bar as u64;
@@ -914,7 +1454,13 @@ mod tests {
             duration_time: format_bars(duration_bars, 1),
             mae: 5.0,
             mfe: 10.0,
+            initial_risk_pips: None,
             swap: 0.0,
+            spread_cost: 0.0,
+            slippage_cost: 0.0,
+            entry_reason: None,
+            exit_reason: None,
+            regime: None,
         }
     }
 
@@ -983,8 +1529,231 @@ mod tests {
             EquityPoint { timestamp: "4".to_string(), equity: 9500.0 },
             EquityPoint { timestamp: "5".to_string(), equity: 10200.0 },
         ];
-        let (max_dd, _, _) = calculate_drawdown_stats(&curve);
+        let (max_dd, _, _) = EquityStats::from_curve(&curve).drawdown_stats();
         // Peak was 10500, trough was 9500 → DD = 1000/10500 * 100 ≈ 9.52%
         assert!((max_dd - 9.52).abs() < 0.1);
     }
+
+    /// Uptrending closes with a small zig-zag so per-bar returns vary.
+    fn trending_candles(n: usize) -> Vec<Candle> {
+        (0..n)
+            .map(|i| Candle {
+                datetime: format!("2024-01-01 {:02}:00", i),
+                close: 100.0 + i as f64 + if i % 2 == 0 { 0.5 } else { -0.5 },
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Strategy equity = buy-and-hold plus a steady `drift` per bar.
+    fn drifted(curve: &[EquityPoint], drift: f64) -> (Vec<EquityPoint>, BacktestMetrics) {
+        let eq: Vec<EquityPoint> = curve
+            .iter()
+            .enumerate()
+            .map(|(i, p)| EquityPoint { timestamp: p.timestamp.clone(), equity: p.equity + drift * i as f64 })
+            .collect();
+        let metrics = BacktestMetrics {
+            total_return_pct: (eq[eq.len() - 1].equity / eq[0].equity - 1.0) * 100.0,
+            ..Default::default()
+        };
+        (eq, metrics)
+    }
+
+    #[test]
+    fn test_buy_and_hold_benchmark() {
+        let candles = trending_candles(40);
+        let hold = buy_and_hold_curve(&candles, 10_000.0);
+        assert_eq!(hold.len(), candles.len());
+        assert!((hold[0].equity - 10_000.0).abs() < 1e-9);
+        let expected_last = 10_000.0 * candles[39].close / candles[0].close;
+        assert!((hold[39].equity - expected_last).abs() < 1e-9);
+
+        // Outperformer: tracks the trend and adds a steady edge on top
+        let (eq, mut m) = drifted(&hold, 20.0);
+        apply_benchmark_metrics(&mut m, &eq, &hold);
+        assert!(m.total_return_pct > m.benchmark_return_pct);
+        assert!(m.alpha > 0.0, "alpha {}", m.alpha);
+        assert!(m.correlation > 0.9, "corr {}", m.correlation);
+        assert!((m.beta - 1.0).abs() < 0.2, "beta {}", m.beta);
+
+        // Underperformer: same exposure, steady bleed
+        let (eq, mut m) = drifted(&hold, -20.0);
+        apply_benchmark_metrics(&mut m, &eq, &hold);
+        assert!(m.total_return_pct < m.benchmark_return_pct);
+        assert!(m.alpha < 0.0, "alpha {}", m.alpha);
+        assert!(m.correlation > 0.9, "corr {}", m.correlation);
+
+        // Holding the instrument itself: beta = correlation = 1, alpha = 0
+        let (eq, mut m) = drifted(&hold, 0.0);
+        apply_benchmark_metrics(&mut m, &eq, &hold);
+        assert!((m.beta - 1.0).abs() < 1e-9);
+        assert!((m.correlation - 1.0).abs() < 1e-9);
+        assert!(m.alpha.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exposure_half_the_bars() {
+        let curve: Vec<EquityPoint> = (0..20)
+            .map(|i| EquityPoint { timestamp: format!("2024-01-01 {:02}:00", i), equity: 10_000.0 })
+            .collect();
+        // Long from bar 4 to bar 14 → open on 10 of 20 bars
+        let mut trade = make_trade(100.0, 10);
+        trade.entry_time = "2024-01-01 04:00".to_string();
+        trade.exit_time = "2024-01-01 14:00".to_string();
+
+        let m = calculate_metrics(&[trade.clone()], &curve, 10_000.0, Timeframe::H1);
+        assert!((m.exposure_pct - 50.0).abs() < 1e-9, "exposure {}", m.exposure_pct);
+        assert!((m.long_exposure_pct - 50.0).abs() < 1e-9);
+        assert_eq!(m.short_exposure_pct, 0.0);
+        assert!((m.avg_simultaneous_positions - 1.0).abs() < 1e-9);
+
+        // An overlapping short on bars 9..14 stacks without extending time in market
+        let mut short = trade.clone();
+        short.direction = TradeDirection::Short;
+        short.entry_time = "2024-01-01 09:00".to_string();
+        let m = calculate_metrics(&[trade, short], &curve, 10_000.0, Timeframe::H1);
+        assert!((m.exposure_pct - 50.0).abs() < 1e-9);
+        assert!((m.short_exposure_pct - 25.0).abs() < 1e-9);
+        assert!((m.avg_simultaneous_positions - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drawdown_episodes() {
+        // Episode 1: peak 110 (bar 1) → trough 100 → regained at bar 4
+        // Episode 2: peak 120 (bar 5) → trough 80 (bar 7) → regained at bar 9
+        let equities = [100.0, 110.0, 100.0, 105.0, 111.0, 120.0, 90.0, 80.0, 100.0, 121.0, 125.0];
+        let curve: Vec<EquityPoint> = equities
+            .iter()
+            .enumerate()
+            .map(|(i, &equity)| EquityPoint { timestamp: format!("2024-01-01 {:02}:00", i), equity })
+            .collect();
+
+        let ep = EquityStats::from_curve(&curve).drawdown_episodes();
+        assert_eq!(ep.count, 2);
+        assert_eq!(ep.longest_bars, 4);
+        assert_eq!(ep.longest_time, "4h 0m");
+        // Max DD trough at bar 7, back above 120 at bar 9
+        assert_eq!(ep.max_dd_recovery_bars, 2);
+        assert_eq!(ep.max_dd_recovery_time, "2h 0m");
+        let expected_depth = (10.0 / 110.0 * 100.0 + 40.0 / 120.0 * 100.0) / 2.0;
+        assert!((ep.avg_depth_pct - expected_depth).abs() < 1e-9);
+        assert!((ep.avg_duration_bars - 3.5).abs() < 1e-9);
+
+        // Unrecovered tail runs to the end of data
+        let mut tail = curve.clone();
+        tail.push(EquityPoint { timestamp: "2024-01-01 11:00".to_string(), equity: 100.0 });
+        let ep = EquityStats::from_curve(&tail).drawdown_episodes();
+        assert_eq!(ep.count, 3);
+        assert_eq!(ep.longest_bars, 4);
+    }
+
+    #[test]
+    fn test_rolling_ratios_track_deteriorating_second_half() {
+        // 200 daily points: steady gains for 100 days, then mostly losses
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut equity = 10_000.0;
+        let curve: Vec<EquityPoint> = (0..200)
+            .map(|i| {
+                if i > 0 {
+                    let r = match (i < 100, i % 2 == 0) {
+                        (true, true) => 0.010,
+                        (true, false) => -0.002,
+                        (false, true) => 0.002,
+                        (false, false) => -0.010,
+                    };
+                    equity *= 1.0 + r;
+                }
+                EquityPoint {
+                    timestamp: (start + chrono::Duration::days(i)).format("%Y-%m-%d").to_string(),
+                    equity,
+                }
+            })
+            .collect();
+
+        let rolling = rolling_risk_ratios(&curve, 20, Timeframe::D1);
+        assert_eq!(rolling.len(), curve.len());
+        assert!(rolling[..20].iter().all(|p| p.sharpe.is_none() && p.sortino.is_none()));
+        assert_eq!(rolling[20].timestamp, curve[20].timestamp);
+
+        let (early, late) = (&rolling[90], &rolling[190]);
+        assert!(early.sharpe.unwrap() > 1.0 && early.sortino.unwrap() > 1.0);
+        assert!(late.sharpe.unwrap() < -1.0 && late.sortino.unwrap() < -1.0);
+
+        // Each point matches the aggregate formulas over its own window
+        let returns: Vec<f64> = curve.windows(2).map(|w| w[1].equity / w[0].equity - 1.0).collect();
+        let ppy = 199.0 / calendar_years(&curve[0].timestamp, &curve[199].timestamp).unwrap();
+        let window = &returns[170..190];
+        assert!((late.sharpe.unwrap() - calculate_sharpe(window, ppy)).abs() < 1e-9);
+        assert!((late.sortino.unwrap() - calculate_sortino(window, ppy)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_seasonality_profitable_hour_dominates_its_bucket() {
+        let at = |entry_time: &str, pnl: f64| TradeResult { entry_time: entry_time.into(), ..make_trade(pnl, 1) };
+        // London-open winners on Tuesdays, afternoon losers on Fridays (2024-01-02 is a Tuesday)
+        let mut trades: Vec<TradeResult> = (0..4)
+            .map(|w| at(&format!("2024-01-{:02} 09:{:02}", 2 + 7 * w, 15 + w), 120.0))
+            .collect();
+        trades.push(at("2024-01-30 09:45", -40.0));
+        trades.extend((0..3).map(|w| at(&format!("2024-01-{:02} 15:30", 5 + 7 * w), -60.0)));
+        trades.push(at("not a date", 1_000.0));
+
+        let report = seasonality_report(&trades);
+        assert_eq!(report.by_hour.len(), 24);
+        assert_eq!(report.by_weekday.len(), 7);
+
+        let h9 = &report.by_hour[9];
+        assert_eq!(h9.bucket, 9);
+        assert_eq!(h9.trade_count, 5);
+        assert!((h9.total_pnl - 440.0).abs() < 1e-9);
+        assert!((h9.avg_pnl - 88.0).abs() < 1e-9);
+        assert!((h9.win_rate_pct - 80.0).abs() < 1e-9);
+        let best = report.by_hour.iter().max_by(|a, b| a.total_pnl.total_cmp(&b.total_pnl)).unwrap();
+        assert_eq!(best.bucket, 9);
+        assert_eq!(report.by_hour[15].trade_count, 3);
+        assert_eq!(report.by_hour[15].win_rate_pct, 0.0);
+        assert_eq!(report.by_hour[3].trade_count, 0);
+
+        let tuesday = &report.by_weekday[1];
+        assert_eq!((tuesday.bucket, tuesday.trade_count), (2, 5));
+        assert!((tuesday.total_pnl - 440.0).abs() < 1e-9);
+        let friday = &report.by_weekday[4];
+        assert_eq!((friday.bucket, friday.trade_count), (5, 3));
+        assert!((friday.avg_pnl + 60.0).abs() < 1e-9);
+        // The unparseable entry is left out entirely
+        assert_eq!(report.by_weekday.iter().map(|b| b.trade_count).sum::<usize>(), 8);
+    }
+
+    #[test]
+    fn test_trade_clustering_flags_bursts() {
+        let at = |entry: &str| TradeResult { entry_time: entry.into(), exit_time: entry.into(), ..make_trade(10.0, 1) };
+        let curve = vec![
+            EquityPoint { timestamp: "2024-01-01 00:00".into(), equity: 10_000.0 },
+            EquityPoint { timestamp: "2024-01-02 00:00".into(), equity: 10_080.0 },
+        ];
+
+        // Eight entries three hours apart
+        let even: Vec<_> = (0..8).map(|i| at(&format!("2024-01-01 {:02}:00", i * 3))).collect();
+        let m = calculate_metrics(&even, &curve, 10_000.0, Timeframe::M5);
+        assert!((m.avg_minutes_between_trades - 180.0).abs() < 1e-9);
+        assert_eq!((m.clustered_trades, m.max_trades_per_hour, m.overtrading), (0, 1, false));
+
+        // Six entries in 25 minutes, then two spread out; 10:05 is exactly an hour after
+        // 09:05 and so falls outside its window
+        let mut clustered: Vec<_> =
+            ["09:00", "09:05", "09:10", "09:15", "09:20", "09:25", "10:05", "15:00"]
+                .iter()
+                .map(|t| at(&format!("2024-01-01 {t}")))
+                .collect();
+        clustered.reverse(); // order of the trade list doesn't matter
+        let m = calculate_metrics(&clustered, &curve, 10_000.0, Timeframe::M5);
+        assert!((m.avg_minutes_between_trades - 360.0 / 7.0).abs() < 1e-9);
+        assert_eq!(m.clustered_trades, 6);
+        assert_eq!(m.max_trades_per_hour, 6);
+        assert!(m.overtrading);
+
+        let mut relaxed = m.clone();
+        apply_overtrading_threshold(&mut relaxed, 6);
+        assert!(!relaxed.overtrading);
+    }
 }
@@ -6,7 +6,9 @@ pub mod monte_carlo;
 pub mod optimizer;
 pub mod orders;
 pub mod position;
+pub mod sensitivity;
 pub mod sr;
 pub mod strategy;
 pub mod streaming;
+pub mod validation;
 pub mod walk_forward;
@@ -1,31 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::errors::AppError;
 use crate::models::candle::Candle;
 use crate::models::config::InstrumentConfig;
 use crate::models::result::{
-    BacktestMetrics, EquityPoint, GeneticAlgorithmConfig, ObjectiveFunction, OptimizationResult,
-    ParameterRange,
+    BacktestMetrics, CrossoverType, EquityPoint, GeneticAlgorithmConfig, MutationType,
+    ObjectiveFunction, OosPeriod, OptimizationResult, ParameterRange, RandomSearchConfig,
+    WeightedObjective,
 };
 use crate::models::strategy::{
     BacktestConfig, CloseTradesAt, IndicatorParams, Strategy, TradingHours,
 };
 
 use super::executor::{self, SubBarData};
+use super::sr::cmaes::standard_normal;
 use super::strategy::IndicatorCache;
+use super::validation;
 
 /// Maximum allowed combinations for Grid Search.
 const MAX_COMBINATIONS: usize = 500_000;
 
 /// Maximum results to return from optimization.
-const MAX_RESULTS: usize = 50;
+pub const MAX_RESULTS: usize = 50;
 
 // ══════════════════════════════════════════════════════════════
 // Shared helpers
@@ -246,9 +251,99 @@ fn extract_objective(metrics: &BacktestMetrics, objective: &ObjectiveFunction) -
         ObjectiveFunction::ReturnDdRatio => metrics.return_dd_ratio,
         ObjectiveFunction::MinStagnation => -(metrics.stagnation_bars as f64),
         ObjectiveFunction::MinUlcerIndex => -metrics.ulcer_index_pct,
+        ObjectiveFunction::SortinoRatio => metrics.sortino_ratio,
+        ObjectiveFunction::CalmarRatio => metrics.calmar_ratio,
+        ObjectiveFunction::Expectancy => metrics.expectancy,
+        ObjectiveFunction::RecoveryFactor => metrics.recovery_factor,
     }
 }
 
+/// Flip an objective value (already oriented by its default direction) when the
+/// user overrides the direction, so that higher = better still holds.
+fn orient(value: f64, objective: &WeightedObjective) -> f64 {
+    if objective.direction() == objective.objective.default_direction() {
+        value
+    } else {
+        -value
+    }
+}
+
+/// Reject weights that would make the composite score meaningless.
+fn validate_objectives(objectives: &[WeightedObjective]) -> Result<(), AppError> {
+    if let Some(o) = objectives.iter().find(|o| !o.weight.is_finite() || o.weight < 0.0) {
+        return Err(AppError::OptimizationError(format!(
+            "Invalid weight {} for objective {:?}",
+            o.weight, o.objective
+        )));
+    }
+    if objectives.len() > 1 && objectives.iter().all(|o| o.weight == 0.0) {
+        return Err(AppError::OptimizationError(
+            "At least one objective must have a positive weight".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a config date bound into microseconds since epoch, mirroring
+/// `loader::build_date_filter`: an empty bound is open, and a date-only end
+/// covers the whole day.
+fn date_bound_us(date: &str, is_end: bool) -> Result<Option<i64>, AppError> {
+    let s = date.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| {
+                if is_end {
+                    d.and_hms_micro_opt(23, 59, 59, 999_999).unwrap()
+                } else {
+                    d.and_hms_opt(0, 0, 0).unwrap()
+                }
+            })
+        })
+        .map(|ndt| Some(ndt.and_utc().timestamp_micros()))
+        .map_err(|_| AppError::OptimizationError(format!("Invalid date '{}'", s)))
+}
+
+/// Check that every OOS period is a valid range disjoint from the in-sample
+/// range. An overlap leaks in-sample data into the OOS metrics, so it is
+/// rejected unless `allow_overlap` is set, in which case it is only logged.
+pub fn validate_oos_periods(
+    is_start: &str,
+    is_end: &str,
+    periods: &[OosPeriod],
+    allow_overlap: bool,
+) -> Result<(), AppError> {
+    let is_lo = date_bound_us(is_start, false)?.unwrap_or(i64::MIN);
+    let is_hi = date_bound_us(is_end, true)?.unwrap_or(i64::MAX);
+
+    for p in periods {
+        let lo = date_bound_us(&p.start_date, false)?.unwrap_or(i64::MIN);
+        let hi = date_bound_us(&p.end_date, true)?.unwrap_or(i64::MAX);
+        if lo > hi {
+            return Err(AppError::OptimizationError(format!(
+                "OOS period '{}' starts after it ends",
+                p.label
+            )));
+        }
+        if lo <= is_hi && is_lo <= hi {
+            if !allow_overlap {
+                return Err(AppError::OptimizationError(format!(
+                    "OOS period '{}' ({} – {}) overlaps the in-sample range ({} – {})",
+                    p.label, p.start_date, p.end_date, is_start, is_end
+                )));
+            }
+            warn!(
+                "OOS period '{}' overlaps the in-sample range; its metrics include in-sample data",
+                p.label
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Downsample an equity curve to at most `max_points` for sparkline display.
 fn downsample_equity(curve: &[EquityPoint], max_points: usize) -> Vec<EquityPoint> {
     if curve.len() <= max_points {
@@ -278,17 +373,17 @@ fn build_result(
     ranges: &[ParameterRange],
     values: &[f64],
     metrics: &BacktestMetrics,
-    objectives: &[ObjectiveFunction],
+    objectives: &[WeightedObjective],
     equity_curve: &[EquityPoint],
 ) -> OptimizationResult {
     let mut params = HashMap::new();
     for (range, &val) in ranges.iter().zip(values.iter()) {
         params.insert(range.display_name.clone(), val);
     }
-    let primary = objectives.first().copied().unwrap_or(ObjectiveFunction::SharpeRatio);
+    let primary = objectives.first().copied().unwrap_or_else(|| ObjectiveFunction::SharpeRatio.into());
     OptimizationResult {
         params,
-        objective_value: extract_objective(metrics, &primary),
+        objective_value: orient(extract_objective(metrics, &primary.objective), &primary),
         composite_score: 0.0, // computed after all results are collected
         total_return_pct: metrics.total_return_pct,
         sharpe_ratio: metrics.sharpe_ratio,
@@ -299,8 +394,14 @@ fn build_result(
         return_dd_ratio: metrics.return_dd_ratio,
         stagnation_bars: metrics.stagnation_bars,
         ulcer_index_pct: metrics.ulcer_index_pct,
+        net_profit: metrics.net_profit,
+        sortino_ratio: metrics.sortino_ratio,
+        calmar_ratio: metrics.calmar_ratio,
+        expectancy: metrics.expectancy,
+        recovery_factor: metrics.recovery_factor,
         oos_results: Vec::new(),
         equity_curve: downsample_equity(equity_curve, SPARKLINE_MAX_POINTS),
+        failure_reason: None,
     }
 }
 
@@ -380,6 +481,7 @@ fn index_to_params(mut idx: usize, per_range: &[Vec<f64>]) -> Vec<f64> {
 ///
 /// Evaluates all parameter combinations in parallel using rayon.
 /// The `progress_callback` receives `(percent, current, total, best_so_far)`.
+/// Cancelling returns the best results evaluated up to that point.
 pub fn run_grid_search(
     candles: &[Candle],
     sub_bars: &SubBarData,
@@ -387,16 +489,92 @@ pub fn run_grid_search(
     config: &BacktestConfig,
     instrument: &InstrumentConfig,
     ranges: &[ParameterRange],
-    objectives: &[ObjectiveFunction],
+    objectives: &[WeightedObjective],
     cancel_flag: &AtomicBool,
     progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
 ) -> Result<Vec<OptimizationResult>, AppError> {
+    let mut valid = run_grid_search_all(
+        candles,
+        sub_bars,
+        strategy,
+        config,
+        instrument,
+        ranges,
+        objectives,
+        cancel_flag,
+        progress_callback,
+    )?;
+    valid.truncate(MAX_RESULTS);
+    Ok(valid)
+}
+
+/// Run Grid Search and keep every successfully evaluated combination, ranked best first.
+///
+/// Used for parameter sensitivity analysis. Only the top `MAX_RESULTS` keep their
+/// equity sparkline to bound memory on large grids.
+pub fn run_grid_search_all(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    ranges: &[ParameterRange],
+    objectives: &[WeightedObjective],
+    cancel_flag: &AtomicBool,
+    progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
+) -> Result<Vec<OptimizationResult>, AppError> {
+    validate_objectives(objectives)?;
     let (per_range, total) = build_grid_ranges(ranges)?;
     info!("Grid search: {} combinations", total);
 
+    let start = Instant::now();
+    let mut valid = evaluate_combinations(
+        candles,
+        sub_bars,
+        strategy,
+        config,
+        instrument,
+        ranges,
+        objectives,
+        total,
+        |combo_idx| index_to_params(combo_idx, &per_range),
+        cancel_flag,
+        progress_callback,
+    )?;
+    for r in valid.iter_mut().skip(MAX_RESULTS) {
+        r.equity_curve = Vec::new();
+    }
+
+    info!(
+        "Grid search complete: {} valid results in {:.1}s",
+        valid.len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(valid)
+}
+
+/// Backtest `total` parameter combinations in parallel and return all results, best first.
+///
+/// `combo_at(i)` yields the parameter values of combination `i`. Failed backtests
+/// (e.g. insufficient data for large periods) and combinations rejected by
+/// [`validation::check_indicator_params`] are kept with a `failure_reason`. When
+/// `cancel_flag` is raised, the combinations evaluated so far are returned.
+fn evaluate_combinations(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    ranges: &[ParameterRange],
+    objectives: &[WeightedObjective],
+    total: usize,
+    combo_at: impl Fn(usize) -> Vec<f64> + Send + Sync,
+    cancel_flag: &AtomicBool,
+    progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
+) -> Result<Vec<OptimizationResult>, AppError> {
     let counter = AtomicUsize::new(0);
     let best_so_far = Arc::new(AtomicU64::new(f64::NEG_INFINITY.to_bits()));
-    let start = Instant::now();
 
     // Shared indicator cache: indicators unchanged across combinations are computed once.
     let shared_cache = Arc::new(IndicatorCache::new());
@@ -409,24 +587,26 @@ pub fn run_grid_search(
                 return None;
             }
 
-            let values = index_to_params(combo_idx, &per_range);
+            let values = combo_at(combo_idx);
             let modified = apply_params(strategy, ranges, &values);
 
-            // Run backtest using shared indicator cache
-            let result = executor::run_backtest_with_cache(
-                candles,
-                sub_bars,
-                &modified,
-                config,
-                instrument,
-                cancel_flag,
-                |_, _, _| {},
-                Arc::clone(&shared_cache),
-            );
-
-            let current = counter.fetch_add(1, Ordering::Relaxed) + 1;
+            // Invalid indicator params (e.g. MACD fast >= slow) are recorded as failed
+            // without running a backtest
+            let result = validation::check_indicator_params(&modified, candles.len()).and_then(|()| {
+                // Run backtest using shared indicator cache
+                executor::run_backtest_with_cache(
+                    candles,
+                    sub_bars,
+                    &modified,
+                    config,
+                    instrument,
+                    cancel_flag,
+                    |_, _, _| {},
+                    Arc::clone(&shared_cache),
+                )
+            });
 
-            match result {
+            let opt_result = match result {
                 Ok(bt) => {
                     let opt_result = build_result(ranges, &values, &bt.metrics, objectives, &bt.equity_curve);
 
@@ -450,51 +630,177 @@ pub fn run_grid_search(
                         }
                     }
 
-                    // Report progress periodically (every 1% or every 10 iterations)
-                    let report_interval = (total / 100).max(1);
-                    if current % report_interval == 0 || current == total {
-                        let pct = ((current as f64 / total as f64) * 100.0) as u8;
-                        let best_val = f64::from_bits(best_so_far.load(Ordering::Relaxed));
-                        progress_callback(pct, current, total, best_val);
-                    }
-
-                    Some(opt_result)
-                }
-                Err(_) => {
-                    // Skip failed backtests (e.g. insufficient data for large periods)
-                    None
+                    opt_result
                 }
+                Err(AppError::BacktestCancelled) => return None,
+                // Keep going (e.g. insufficient data for large periods); the combination
+                // is recorded as failed instead of aborting the whole search
+                Err(e) => build_failed_result(ranges, &values, e.to_string()),
+            };
+
+            let current = counter.fetch_add(1, Ordering::Relaxed) + 1;
+
+            // Report progress periodically (every 1% or every 10 iterations)
+            let report_interval = (total / 100).max(1);
+            if current % report_interval == 0 || current == total {
+                let pct = ((current as f64 / total as f64) * 100.0) as u8;
+                let best_val = f64::from_bits(best_so_far.load(Ordering::Relaxed));
+                progress_callback(pct, current, total, best_val);
             }
+
+            Some(opt_result)
         })
         .collect();
 
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Err(AppError::OptimizationCancelled);
-    }
+    // On cancellation the combinations finished so far are still ranked and returned;
+    // callers inspect `cancel_flag` to tell a partial result set from a complete one.
+    Ok(rank_results(results.into_iter().flatten().collect(), objectives))
+}
 
-    let elapsed = start.elapsed();
-    let mut valid: Vec<OptimizationResult> = results.into_iter().flatten().collect();
+// ══════════════════════════════════════════════════════════════
+// Random Search (Latin hypercube)
+// ══════════════════════════════════════════════════════════════
 
-    // Compute composite scores for multi-objective
-    compute_composite_scores(&mut valid, objectives);
+/// Run Random Search optimization.
+///
+/// Draws `rs_config.samples` parameter sets by Latin-hypercube sampling over the
+/// ranges, evaluates them in parallel and ranks them like grid search. Samples that
+/// collapse onto the same grid point after step snapping are evaluated once.
+pub fn run_random_search(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    ranges: &[ParameterRange],
+    objectives: &[WeightedObjective],
+    rs_config: &RandomSearchConfig,
+    cancel_flag: &AtomicBool,
+    progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
+) -> Result<Vec<OptimizationResult>, AppError> {
+    let mut valid = run_random_search_all(
+        candles,
+        sub_bars,
+        strategy,
+        config,
+        instrument,
+        ranges,
+        objectives,
+        rs_config,
+        cancel_flag,
+        progress_callback,
+    )?;
+    valid.truncate(MAX_RESULTS);
+    Ok(valid)
+}
 
-    // Sort by composite_score if multi-objective, otherwise by objective_value
-    if objectives.len() > 1 {
-        valid.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap_or(std::cmp::Ordering::Equal));
-    } else {
-        valid.sort_by(|a, b| b.objective_value.partial_cmp(&a.objective_value).unwrap_or(std::cmp::Ordering::Equal));
+/// Run Random Search and keep every successfully evaluated sample, ranked best first.
+pub fn run_random_search_all(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    ranges: &[ParameterRange],
+    objectives: &[WeightedObjective],
+    rs_config: &RandomSearchConfig,
+    cancel_flag: &AtomicBool,
+    progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
+) -> Result<Vec<OptimizationResult>, AppError> {
+    validate_objectives(objectives)?;
+    validate_sampling_ranges(ranges)?;
+    if rs_config.samples == 0 {
+        return Err(AppError::OptimizationError(
+            "Random search needs at least one sample".into(),
+        ));
     }
-    valid.truncate(MAX_RESULTS);
+    if rs_config.samples > MAX_COMBINATIONS {
+        return Err(AppError::TooManyCombinations {
+            count: rs_config.samples,
+            limit: MAX_COMBINATIONS,
+        });
+    }
+
+    let mut rng = match rs_config.seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
+    let mut samples = latin_hypercube_samples(ranges, rs_config.samples, &mut rng);
+    let mut seen = HashSet::new();
+    samples.retain(|s| seen.insert(s.iter().map(|v| v.to_bits()).collect::<Vec<u64>>()));
+    info!(
+        "Random search: {} samples ({} unique after snapping)",
+        rs_config.samples,
+        samples.len()
+    );
+
+    let start = Instant::now();
+    let valid = evaluate_combinations(
+        candles,
+        sub_bars,
+        strategy,
+        config,
+        instrument,
+        ranges,
+        objectives,
+        samples.len(),
+        |i| samples[i].clone(),
+        cancel_flag,
+        progress_callback,
+    )?;
 
     info!(
-        "Grid search complete: {} valid results in {:.1}s",
+        "Random search complete: {} valid results in {:.1}s",
         valid.len(),
-        elapsed.as_secs_f64()
+        start.elapsed().as_secs_f64()
     );
 
     Ok(valid)
 }
 
+/// Reject ranges that cannot be sampled (empty list, inverted bounds, non-positive step).
+fn validate_sampling_ranges(ranges: &[ParameterRange]) -> Result<(), AppError> {
+    if ranges.is_empty() {
+        return Err(AppError::OptimizationError(
+            "No parameter ranges specified".into(),
+        ));
+    }
+    for r in ranges {
+        if r.step <= 0.0 {
+            return Err(AppError::OptimizationError(format!(
+                "Step must be positive for parameter '{}'",
+                r.display_name
+            )));
+        }
+        if r.max < r.min {
+            return Err(AppError::OptimizationError(format!(
+                "Max must not be below min for parameter '{}'",
+                r.display_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Draw `n` Latin-hypercube samples over `ranges`, snapped to each range's step.
+///
+/// Every parameter's range is split into `n` equal strata; each stratum receives
+/// exactly one sample (at a random point inside it), and strata are paired across
+/// parameters by independent random permutations.
+fn latin_hypercube_samples(ranges: &[ParameterRange], n: usize, rng: &mut impl Rng) -> Vec<Vec<f64>> {
+    let mut samples = vec![Vec::with_capacity(ranges.len()); n];
+    for range in ranges {
+        let mut strata: Vec<usize> = (0..n).collect();
+        strata.shuffle(rng);
+        let width = range.max - range.min;
+        for (sample, stratum) in samples.iter_mut().zip(strata) {
+            let u = (stratum as f64 + rng.gen::<f64>()) / n as f64;
+            sample.push(snap_to_step(range.min + u * width, range));
+        }
+    }
+    samples
+}
+
 // ══════════════════════════════════════════════════════════════
 // Genetic Algorithm
 // ══════════════════════════════════════════════════════════════
@@ -506,10 +812,21 @@ struct Individual {
     fitness: f64,
 }
 
+/// Slippage seed for one GA evaluation: the GA seed mixed with the genes' bit patterns,
+/// so the same individual always sees the same slippage draws.
+fn evaluation_seed(seed: u64, genes: &[f64]) -> u64 {
+    genes
+        .iter()
+        .fold(seed, |acc, g| (acc ^ g.to_bits()).wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(31))
+}
+
 /// Run Genetic Algorithm optimization.
 ///
 /// Uses tournament selection, single-point crossover, and mutation.
-/// Evaluates each generation in parallel with rayon.
+/// Evaluates each generation in parallel with rayon. All random draws come from a
+/// single RNG seeded with `ga_config.seed` (OS entropy when unset), and each backtest
+/// gets a slippage seed derived from it and the individual's genes, so a fixed seed
+/// reproduces the same search even with random slippage.
 pub fn run_genetic_algorithm(
     candles: &[Candle],
     sub_bars: &SubBarData,
@@ -517,15 +834,44 @@ pub fn run_genetic_algorithm(
     config: &BacktestConfig,
     instrument: &InstrumentConfig,
     ranges: &[ParameterRange],
-    objectives: &[ObjectiveFunction],
+    objectives: &[WeightedObjective],
+    ga_config: &GeneticAlgorithmConfig,
+    cancel_flag: &AtomicBool,
+    progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
+) -> Result<Vec<OptimizationResult>, AppError> {
+    let mut results = run_genetic_algorithm_all(
+        candles,
+        sub_bars,
+        strategy,
+        config,
+        instrument,
+        ranges,
+        objectives,
+        ga_config,
+        cancel_flag,
+        progress_callback,
+    )?;
+    results.truncate(MAX_RESULTS);
+    Ok(results)
+}
+
+/// Run the Genetic Algorithm and keep every distinct individual evaluated across all
+/// generations, ranked best first. Cancelling stops breeding and returns the
+/// individuals evaluated so far.
+pub fn run_genetic_algorithm_all(
+    candles: &[Candle],
+    sub_bars: &SubBarData,
+    strategy: &Strategy,
+    config: &BacktestConfig,
+    instrument: &InstrumentConfig,
+    ranges: &[ParameterRange],
+    objectives: &[WeightedObjective],
     ga_config: &GeneticAlgorithmConfig,
     cancel_flag: &AtomicBool,
     progress_callback: impl Fn(u8, usize, usize, f64) + Send + Sync,
 ) -> Result<Vec<OptimizationResult>, AppError> {
     let pop_size = ga_config.population_size;
     let generations = ga_config.generations;
-    let mutation_rate = ga_config.mutation_rate;
-    let crossover_rate = ga_config.crossover_rate;
     let patience = ga_config.patience;
     let num_params = ranges.len();
 
@@ -534,10 +880,12 @@ pub fn run_genetic_algorithm(
             "No parameter ranges specified".into(),
         ));
     }
+    validate_objectives(objectives)?;
 
     info!(
-        "GA: pop={}, gens={}, mut_rate={:.2}, cross_rate={:.2}, params={}",
-        pop_size, generations, mutation_rate, crossover_rate, num_params
+        "GA: pop={}, gens={}, mut_rate={:.2} ({:?}), cross_rate={:.2} ({:?}), params={}",
+        pop_size, generations, ga_config.mutation_rate, ga_config.mutation_type,
+        ga_config.crossover_rate, ga_config.crossover_type, num_params
     );
 
     let start = Instant::now();
@@ -548,10 +896,14 @@ pub fn run_genetic_algorithm(
     // Shared indicator cache for reuse across generations / individuals
     let shared_cache = Arc::new(IndicatorCache::new());
 
+    let mut rng = match ga_config.seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
+
     // Initialize random population
     let mut population: Vec<Individual> = (0..pop_size)
         .map(|_| {
-            let mut rng = rand::thread_rng();
             let genes: Vec<f64> = ranges
                 .iter()
                 .map(|r| snap_to_step(rng.gen_range(r.min..=r.max), r))
@@ -568,7 +920,7 @@ pub fn run_genetic_algorithm(
 
     for gen in 0..generations {
         if cancel_flag.load(Ordering::Relaxed) {
-            return Err(AppError::OptimizationCancelled);
+            break;
         }
 
         // Evaluate population in parallel
@@ -580,16 +932,26 @@ pub fn run_genetic_algorithm(
                 }
 
                 let modified = apply_params(strategy, ranges, &ind.genes);
-                let result = executor::run_backtest_with_cache(
-                    candles,
-                    sub_bars,
-                    &modified,
-                    config,
-                    instrument,
-                    cancel_flag,
-                    |_, _, _| {},
-                    Arc::clone(&shared_cache),
-                );
+                let seeded;
+                let config = match ga_config.seed {
+                    Some(seed) => {
+                        seeded = BacktestConfig { slippage_seed: Some(evaluation_seed(seed, &ind.genes)), ..config.clone() };
+                        &seeded
+                    }
+                    None => config,
+                };
+                let result = validation::check_indicator_params(&modified, candles.len()).and_then(|()| {
+                    executor::run_backtest_with_cache(
+                        candles,
+                        sub_bars,
+                        &modified,
+                        config,
+                        instrument,
+                        cancel_flag,
+                        |_, _, _| {},
+                        Arc::clone(&shared_cache),
+                    )
+                });
 
                 match result {
                     Ok(bt) => {
@@ -597,13 +959,31 @@ pub fn run_genetic_algorithm(
                         let fitness = opt_result.objective_value;
                         Some((fitness, opt_result))
                     }
-                    Err(_) => Some((f64::NEG_INFINITY, build_failed_result(ranges, &ind.genes))),
+                    Err(AppError::BacktestCancelled) => None,
+                    Err(e) => Some((f64::NEG_INFINITY, build_failed_result(ranges, &ind.genes, e.to_string()))),
                 }
             })
             .collect();
-
-        if cancel_flag.load(Ordering::Relaxed) {
-            return Err(AppError::OptimizationCancelled);
+        let cancelled = cancel_flag.load(Ordering::Relaxed);
+
+        // With several objectives, selection uses the weighted composite score
+        // normalized across this generation; progress and early stopping keep
+        // tracking the primary objective so they stay comparable between generations.
+        let mut fitnesses = fitnesses;
+        if objectives.len() > 1 {
+            let mut generation: Vec<OptimizationResult> = fitnesses
+                .iter()
+                .flatten()
+                .filter(|(f, _)| *f > f64::NEG_INFINITY)
+                .map(|(_, r)| r.clone())
+                .collect();
+            compute_composite_scores(&mut generation, objectives);
+            let mut scores = generation.iter().map(|r| r.composite_score);
+            for (fitness, _) in fitnesses.iter_mut().flatten() {
+                if *fitness > f64::NEG_INFINITY {
+                    *fitness = scores.next().unwrap_or(f64::NEG_INFINITY);
+                }
+            }
         }
 
         // Update fitness values and collect results
@@ -612,14 +992,22 @@ pub fn run_genetic_algorithm(
             if let Some((fitness, opt_result)) = eval {
                 ind.fitness = fitness;
                 if fitness > f64::NEG_INFINITY {
+                    if opt_result.objective_value > global_best {
+                        global_best = opt_result.objective_value;
+                    }
+                    all_results.lock().unwrap().push(opt_result);
+                } else if opt_result.failure_reason.is_some() {
                     all_results.lock().unwrap().push(opt_result);
-                }
-                if fitness > global_best {
-                    global_best = fitness;
                 }
             }
         }
 
+        // Keep the individuals finished before cancellation, but don't breed further
+        if cancelled {
+            info!("GA cancelled during generation {}", gen + 1);
+            break;
+        }
+
         // Early stopping via patience — require at least 0.01% relative improvement
         let improvement_threshold = prev_best.abs() * 1e-4;
         if global_best > prev_best + improvement_threshold.max(1e-10) {
@@ -662,23 +1050,12 @@ pub fn run_genetic_algorithm(
         let mut next_pop: Vec<Individual> = Vec::with_capacity(pop_size);
         next_pop.push(elite); // Elitism
 
-        let mut rng = rand::thread_rng();
-
         while next_pop.len() < pop_size {
             // Tournament selection
             let parent1 = tournament_select(&population, &mut rng);
             let parent2 = tournament_select(&population, &mut rng);
 
-            // Crossover
-            let (mut child1, mut child2) = if rng.gen::<f64>() < crossover_rate && num_params > 1 {
-                crossover(&parent1.genes, &parent2.genes, &mut rng)
-            } else {
-                (parent1.genes.clone(), parent2.genes.clone())
-            };
-
-            // Mutation
-            mutate(&mut child1, ranges, mutation_rate, &mut rng);
-            mutate(&mut child2, ranges, mutation_rate, &mut rng);
+            let (child1, child2) = breed(&parent1.genes, &parent2.genes, ranges, ga_config, &mut rng);
 
             next_pop.push(Individual {
                 genes: child1,
@@ -698,21 +1075,12 @@ pub fn run_genetic_algorithm(
     let elapsed = start.elapsed();
 
     // Deduplicate and sort results
-    let mut results = match Arc::try_unwrap(all_results) {
+    let results = match Arc::try_unwrap(all_results) {
         Ok(mutex) => mutex.into_inner().unwrap(),
         Err(arc) => arc.lock().unwrap().clone(),
     };
-
-    // Compute composite scores for multi-objective
-    compute_composite_scores(&mut results, objectives);
-
-    if objectives.len() > 1 {
-        results.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap_or(std::cmp::Ordering::Equal));
-    } else {
-        results.sort_by(|a, b| b.objective_value.partial_cmp(&a.objective_value).unwrap_or(std::cmp::Ordering::Equal));
-    }
+    let mut results = rank_results(results, objectives);
     results.dedup_by(|a, b| a.params == b.params);
-    results.truncate(MAX_RESULTS);
 
     info!(
         "GA complete: {} unique results in {:.1}s",
@@ -736,21 +1104,99 @@ fn tournament_select<'a>(population: &'a [Individual], rng: &mut impl Rng) -> &'
     &population[best_idx]
 }
 
-/// Single-point crossover.
-fn crossover(parent1: &[f64], parent2: &[f64], rng: &mut impl Rng) -> (Vec<f64>, Vec<f64>) {
-    let point = rng.gen_range(1..parent1.len());
-    let mut child1 = parent1[..point].to_vec();
-    child1.extend_from_slice(&parent2[point..]);
-    let mut child2 = parent2[..point].to_vec();
-    child2.extend_from_slice(&parent1[point..]);
+/// Interval extension factor of blend (BLX-α) crossover.
+const BLEND_ALPHA: f64 = 0.5;
+/// Standard deviation of gaussian mutation, as a fraction of the parameter range.
+const GAUSSIAN_MUTATION_SIGMA: f64 = 0.1;
+
+/// Produce two children: crossover with probability `crossover_rate`, then per-gene
+/// mutation with probability `mutation_rate`, using the configured operators.
+fn breed(
+    parent1: &[f64],
+    parent2: &[f64],
+    ranges: &[ParameterRange],
+    ga_config: &GeneticAlgorithmConfig,
+    rng: &mut impl Rng,
+) -> (Vec<f64>, Vec<f64>) {
+    // One-point crossover needs a cut between two genes.
+    let can_cross = parent1.len() > 1 || ga_config.crossover_type != CrossoverType::OnePoint;
+    let (mut child1, mut child2) = if rng.gen::<f64>() < ga_config.crossover_rate && can_cross {
+        crossover(parent1, parent2, ranges, ga_config.crossover_type, rng)
+    } else {
+        (parent1.to_vec(), parent2.to_vec())
+    };
+
+    mutate(&mut child1, ranges, ga_config.mutation_rate, ga_config.mutation_type, rng);
+    mutate(&mut child2, ranges, ga_config.mutation_rate, ga_config.mutation_type, rng);
     (child1, child2)
 }
 
+/// Recombine two parents. Blended genes are clamped to the range and snapped to its step.
+fn crossover(
+    parent1: &[f64],
+    parent2: &[f64],
+    ranges: &[ParameterRange],
+    crossover_type: CrossoverType,
+    rng: &mut impl Rng,
+) -> (Vec<f64>, Vec<f64>) {
+    match crossover_type {
+        CrossoverType::OnePoint => {
+            let point = rng.gen_range(1..parent1.len());
+            let mut child1 = parent1[..point].to_vec();
+            child1.extend_from_slice(&parent2[point..]);
+            let mut child2 = parent2[..point].to_vec();
+            child2.extend_from_slice(&parent1[point..]);
+            (child1, child2)
+        }
+        CrossoverType::Uniform => {
+            let mut child1 = parent1.to_vec();
+            let mut child2 = parent2.to_vec();
+            for (a, b) in child1.iter_mut().zip(child2.iter_mut()) {
+                if rng.gen::<bool>() {
+                    std::mem::swap(a, b);
+                }
+            }
+            (child1, child2)
+        }
+        CrossoverType::Blend => {
+            let mut blend = || -> Vec<f64> {
+                parent1
+                    .iter()
+                    .zip(parent2)
+                    .zip(ranges)
+                    .map(|((&a, &b), range)| {
+                        let (lo, hi) = (a.min(b), a.max(b));
+                        let ext = BLEND_ALPHA * (hi - lo);
+                        let value = rng.gen_range(lo - ext..=hi + ext);
+                        snap_to_step(value.clamp(range.min, range.max), range)
+                    })
+                    .collect()
+            };
+            let child1 = blend();
+            let child2 = blend();
+            (child1, child2)
+        }
+    }
+}
+
 /// Mutate genes with given probability, keeping values within ranges.
-fn mutate(genes: &mut [f64], ranges: &[ParameterRange], mutation_rate: f64, rng: &mut impl Rng) {
+fn mutate(
+    genes: &mut [f64],
+    ranges: &[ParameterRange],
+    mutation_rate: f64,
+    mutation_type: MutationType,
+    rng: &mut impl Rng,
+) {
     for (gene, range) in genes.iter_mut().zip(ranges.iter()) {
         if rng.gen::<f64>() < mutation_rate {
-            *gene = snap_to_step(rng.gen_range(range.min..=range.max), range);
+            *gene = match mutation_type {
+                MutationType::UniformReset => snap_to_step(rng.gen_range(range.min..=range.max), range),
+                MutationType::Gaussian => {
+                    let sigma = GAUSSIAN_MUTATION_SIGMA * (range.max - range.min);
+                    let value = *gene + standard_normal(rng) * sigma;
+                    snap_to_step(value.clamp(range.min, range.max), range)
+                }
+            };
         }
     }
 }
@@ -766,7 +1212,7 @@ fn snap_to_step(value: f64, range: &ParameterRange) -> f64 {
 }
 
 /// Build a result placeholder for failed backtests.
-fn build_failed_result(ranges: &[ParameterRange], values: &[f64]) -> OptimizationResult {
+fn build_failed_result(ranges: &[ParameterRange], values: &[f64], reason: String) -> OptimizationResult {
     let mut params = HashMap::new();
     for (range, &val) in ranges.iter().zip(values.iter()) {
         params.insert(range.display_name.clone(), val);
@@ -784,15 +1230,41 @@ fn build_failed_result(ranges: &[ParameterRange], values: &[f64]) -> Optimizatio
         return_dd_ratio: 0.0,
         stagnation_bars: 0,
         ulcer_index_pct: 0.0,
+        net_profit: 0.0,
+        sortino_ratio: 0.0,
+        calmar_ratio: 0.0,
+        expectancy: 0.0,
+        recovery_factor: 0.0,
         oos_results: Vec::new(),
         equity_curve: Vec::new(),
+        failure_reason: Some(reason),
+    }
+}
+
+/// Score and sort results best-first. Failed combinations are kept out of the
+/// composite normalization and ranked after every evaluated one.
+fn rank_results(results: Vec<OptimizationResult>, objectives: &[WeightedObjective]) -> Vec<OptimizationResult> {
+    let (mut ranked, failed): (Vec<_>, Vec<_>) =
+        results.into_iter().partition(|r| r.failure_reason.is_none());
+
+    // Compute composite scores for multi-objective
+    compute_composite_scores(&mut ranked, objectives);
+
+    // Sort by composite_score if multi-objective, otherwise by objective_value
+    if objectives.len() > 1 {
+        ranked.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap_or(std::cmp::Ordering::Equal));
+    } else {
+        ranked.sort_by(|a, b| b.objective_value.partial_cmp(&a.objective_value).unwrap_or(std::cmp::Ordering::Equal));
     }
+
+    ranked.extend(failed);
+    ranked
 }
 
 /// Compute composite scores for multi-objective optimization.
-/// Normalizes each objective to [0, 1] using min-max across all results, then averages.
-/// For single-objective, composite_score == objective_value (normalized to [0, 1]).
-fn compute_composite_scores(results: &mut [OptimizationResult], objectives: &[ObjectiveFunction]) {
+/// Normalizes each objective to [0, 1] using min-max across all results, then takes the
+/// weighted average. For single-objective, composite_score == objective_value.
+fn compute_composite_scores(results: &mut [OptimizationResult], objectives: &[WeightedObjective]) {
     if results.is_empty() || objectives.is_empty() {
         return;
     }
@@ -805,57 +1277,64 @@ fn compute_composite_scores(results: &mut [OptimizationResult], objectives: &[Ob
         return;
     }
 
-    // Extract raw objective values for each objective
-    let num_objectives = objectives.len();
-    let num_results = results.len();
-
-    // Collect raw values: raw_values[obj_idx][result_idx]
-    let mut raw_values: Vec<Vec<f64>> = Vec::with_capacity(num_objectives);
-    for obj in objectives {
-        let vals: Vec<f64> = results.iter().map(|r| {
-            extract_objective_from_result(r, obj)
-        }).collect();
-        raw_values.push(vals);
+    let total_weight: f64 = objectives.iter().map(|o| o.weight.max(0.0)).sum();
+    if total_weight <= 0.0 {
+        return;
     }
 
-    // Normalize each objective to [0, 1] and compute average
-    for i in 0..num_results {
+    // Min-max bounds per objective over all results
+    let bounds: Vec<(f64, f64)> = objectives
+        .iter()
+        .map(|obj| {
+            results.iter().map(|r| extract_objective_from_result(r, obj)).fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(lo, hi), v| (lo.min(v), hi.max(v)),
+            )
+        })
+        .collect();
+
+    for r in results.iter_mut() {
         let mut score_sum = 0.0;
-        for (j, _obj) in objectives.iter().enumerate() {
-            let vals = &raw_values[j];
-            let min = vals.iter().copied().fold(f64::INFINITY, f64::min);
-            let max = vals.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        for (obj, &(min, max)) in objectives.iter().zip(bounds.iter()) {
             let range = max - min;
             // Use f64::EPSILON as guard: very small ranges (caused by floating-point noise)
             // would produce Inf/NaN when dividing. Treat them as "all results equal" → 0.5.
             let normalized = if range > f64::EPSILON {
-                (vals[i] - min) / range
+                (extract_objective_from_result(r, obj) - min) / range
             } else {
                 0.5
             };
-            score_sum += normalized;
+            score_sum += obj.weight.max(0.0) * normalized;
         }
-        results[i].composite_score = score_sum / num_objectives as f64;
+        r.composite_score = score_sum / total_weight;
     }
 }
 
-/// Extract an objective value directly from an OptimizationResult (without BacktestMetrics).
-/// For "minimize" objectives, values are negated so higher = better.
-fn extract_objective_from_result(r: &OptimizationResult, obj: &ObjectiveFunction) -> f64 {
-    match obj {
-        ObjectiveFunction::TotalProfit => r.total_return_pct * 100.0, // use return %
+/// Extract an objective value directly from an OptimizationResult (without BacktestMetrics),
+/// oriented so that higher = better for the objective's effective direction.
+fn extract_objective_from_result(r: &OptimizationResult, obj: &WeightedObjective) -> f64 {
+    let value = match obj.objective {
+        ObjectiveFunction::TotalProfit => r.net_profit,
         ObjectiveFunction::SharpeRatio => r.sharpe_ratio,
         ObjectiveFunction::ProfitFactor => r.profit_factor,
         ObjectiveFunction::WinRate => r.win_rate_pct,
         ObjectiveFunction::ReturnDdRatio => r.return_dd_ratio,
         ObjectiveFunction::MinStagnation => -(r.stagnation_bars as f64),
         ObjectiveFunction::MinUlcerIndex => -r.ulcer_index_pct,
-    }
+        ObjectiveFunction::SortinoRatio => r.sortino_ratio,
+        ObjectiveFunction::CalmarRatio => r.calmar_ratio,
+        ObjectiveFunction::Expectancy => r.expectancy,
+        ObjectiveFunction::RecoveryFactor => r.recovery_factor,
+    };
+    orient(value, obj)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::config::Timeframe;
+    use crate::models::result::ObjectiveDirection;
+    use crate::models::strategy::*;
 
     #[test]
     fn test_generate_grid_simple() {
@@ -960,6 +1439,123 @@ mod tests {
         assert_eq!(snap_to_step(25.0, &range), 25.0);
     }
 
+    fn operator_ranges() -> Vec<ParameterRange> {
+        let range = |name: &str, min: f64, max: f64, step: f64| ParameterRange {
+            rule_index: 0,
+            param_name: name.into(),
+            display_name: name.into(),
+            min,
+            max,
+            step,
+            operand_side: "left".into(),
+            param_source: "indicator".into(),
+        };
+        // Integer period, stepped float, and an unstepped continuous parameter.
+        vec![range("period", 5.0, 50.0, 1.0), range("std_dev", 0.5, 3.0, 0.25), range("level", 10.0, 90.0, 0.0)]
+    }
+
+    fn operator_config(crossover_type: CrossoverType, mutation_type: MutationType, rate: f64) -> GeneticAlgorithmConfig {
+        GeneticAlgorithmConfig {
+            population_size: 10,
+            generations: 1,
+            mutation_rate: rate,
+            crossover_rate: rate,
+            crossover_type,
+            mutation_type,
+            patience: None,
+            seed: None,
+        }
+    }
+
+    const ALL_OPERATORS: [(CrossoverType, MutationType); 6] = [
+        (CrossoverType::OnePoint, MutationType::UniformReset),
+        (CrossoverType::OnePoint, MutationType::Gaussian),
+        (CrossoverType::Uniform, MutationType::UniformReset),
+        (CrossoverType::Uniform, MutationType::Gaussian),
+        (CrossoverType::Blend, MutationType::UniformReset),
+        (CrossoverType::Blend, MutationType::Gaussian),
+    ];
+
+    #[test]
+    fn test_ga_operators_produce_children_within_ranges() {
+        let ranges = operator_ranges();
+        let mut rng = SmallRng::seed_from_u64(7);
+        for (crossover_type, mutation_type) in ALL_OPERATORS {
+            let config = operator_config(crossover_type, mutation_type, 1.0);
+            let mut changed = false;
+            for _ in 0..500 {
+                // Parents sit at the range edges so blend and gaussian noise push past them.
+                let parent1: Vec<f64> = ranges.iter().map(|r| r.min).collect();
+                let parent2: Vec<f64> = ranges.iter().map(|r| r.max).collect();
+                let (child1, child2) = breed(&parent1, &parent2, &ranges, &config, &mut rng);
+                for child in [&child1, &child2] {
+                    assert_eq!(child.len(), ranges.len());
+                    for (gene, r) in child.iter().zip(&ranges) {
+                        assert!((r.min..=r.max).contains(gene), "{:?}/{:?}: {} outside {}", crossover_type, mutation_type, gene, r.param_name);
+                        if r.step > 0.0 {
+                            let steps = (gene - r.min) / r.step;
+                            assert!((steps - steps.round()).abs() < 1e-9, "{} off the {} step grid", gene, r.param_name);
+                        }
+                    }
+                }
+                changed |= child1 != parent1 && child1 != parent2;
+            }
+            assert!(changed, "{:?}/{:?} never produced a new child", crossover_type, mutation_type);
+        }
+    }
+
+    #[test]
+    fn test_ga_zero_rates_leave_genes_unchanged() {
+        let ranges = operator_ranges();
+        let parent1 = vec![12.0, 1.75, 33.3];
+        let parent2 = vec![40.0, 2.5, 71.9];
+        let mut rng = SmallRng::seed_from_u64(7);
+        for (crossover_type, mutation_type) in ALL_OPERATORS {
+            let config = operator_config(crossover_type, mutation_type, 0.0);
+            for _ in 0..100 {
+                let (child1, child2) = breed(&parent1, &parent2, &ranges, &config, &mut rng);
+                assert_eq!(child1, parent1);
+                assert_eq!(child2, parent2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_latin_hypercube_covers_ranges_uniformly() {
+        let range = |name: &str, min: f64, max: f64, step: f64| ParameterRange {
+            rule_index: 0,
+            param_name: name.into(),
+            display_name: name.into(),
+            min,
+            max,
+            step,
+            operand_side: "left".into(),
+            param_source: "long_entry".into(),
+        };
+        let ranges = vec![range("period", 5.0, 205.0, 1.0), range("std_dev", 0.5, 3.0, 0.01)];
+        let n = 200;
+        let samples = latin_hypercube_samples(&ranges, n, &mut SmallRng::seed_from_u64(1));
+        assert_eq!(samples.len(), n);
+
+        for (i, r) in ranges.iter().enumerate() {
+            let bins = 10;
+            let mut counts = vec![0usize; bins];
+            for s in &samples {
+                let v = s[i];
+                assert!(v >= r.min && v <= r.max);
+                // Snapped to the step grid
+                let steps = (v - r.min) / r.step;
+                assert!((steps - steps.round()).abs() < 1e-6);
+                let bin = (((v - r.min) / (r.max - r.min)) * bins as f64) as usize;
+                counts[bin.min(bins - 1)] += 1;
+            }
+            // One sample per stratum → 20 per decile, give or take snapping at the edges
+            for c in counts {
+                assert!((18..=22).contains(&c), "{}: uneven decile count {}", r.param_name, c);
+            }
+        }
+    }
+
     #[test]
     fn test_extract_objective() {
         let metrics = BacktestMetrics {
@@ -1009,9 +1605,39 @@ mod tests {
             return_dd_ratio: 2.5,
             total_swap_charged: 0.0,
             total_commission_charged: 0.0,
+            total_spread_cost: 0.0,
+            total_slippage_cost: 0.0,
+            total_costs: 0.0,
+            gross_pnl_before_costs: 0.0,
             k_ratio: 0.0,
             omega_ratio: 0.0,
             monthly_returns: vec![],
+            temporal_consistency: 0.0,
+            benchmark_return_pct: 0.0,
+            alpha: 0.0,
+            beta: 0.0,
+            correlation: 0.0,
+            exposure_pct: 0.0,
+            avg_simultaneous_positions: 0.0,
+            long_exposure_pct: 0.0,
+            short_exposure_pct: 0.0,
+            drawdown_episodes: 0,
+            longest_drawdown_bars: 0,
+            longest_drawdown_time: String::new(),
+            max_drawdown_recovery_bars: 0,
+            max_drawdown_recovery_time: String::new(),
+            avg_drawdown_depth_pct: 0.0,
+            avg_drawdown_duration_bars: 0.0,
+            avg_minutes_between_trades: 0.0,
+            clustered_trades: 0,
+            max_trades_per_hour: 0,
+            overtrading: false,
+            trending_trades: 0,
+            trending_win_rate_pct: 0.0,
+            trending_net_profit: 0.0,
+            ranging_trades: 0,
+            ranging_win_rate_pct: 0.0,
+            ranging_net_profit: 0.0,
         };
 
         assert_eq!(extract_objective(&metrics, &ObjectiveFunction::TotalProfit), 1000.0);
@@ -1022,4 +1648,489 @@ mod tests {
         assert_eq!(extract_objective(&metrics, &ObjectiveFunction::MinStagnation), -100.0);
         assert_eq!(extract_objective(&metrics, &ObjectiveFunction::MinUlcerIndex), -3.5);
     }
+
+    fn scored_result(name: &str, sharpe: f64, net_profit: f64, ulcer: f64) -> OptimizationResult {
+        let mut r = build_failed_result(&[], &[], String::new());
+        r.failure_reason = None;
+        r.params.insert(name.into(), 0.0);
+        r.sharpe_ratio = sharpe;
+        r.net_profit = net_profit;
+        r.ulcer_index_pct = ulcer;
+        r
+    }
+
+    fn ranking(results: &mut [OptimizationResult], objectives: &[WeightedObjective]) -> Vec<String> {
+        compute_composite_scores(results, objectives);
+        results.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap());
+        results.iter().map(|r| r.params.keys().next().unwrap().clone()).collect()
+    }
+
+    #[test]
+    fn test_objective_weights_reorder_results() {
+        // A: steady (high Sharpe, low profit), B: aggressive (low Sharpe, high profit)
+        let mut results = vec![
+            scored_result("A", 2.0, 1_000.0, 1.0),
+            scored_result("B", 0.5, 5_000.0, 8.0),
+            scored_result("C", 1.2, 3_000.0, 4.0),
+        ];
+        let weighted = |sharpe: f64, profit: f64| {
+            vec![
+                WeightedObjective { objective: ObjectiveFunction::SharpeRatio, weight: sharpe, direction: None },
+                WeightedObjective { objective: ObjectiveFunction::TotalProfit, weight: profit, direction: None },
+            ]
+        };
+
+        assert_eq!(ranking(&mut results, &weighted(3.0, 1.0)), ["A", "C", "B"]);
+        assert_eq!(ranking(&mut results, &weighted(1.0, 3.0)), ["B", "C", "A"]);
+        // Equal weights: A and B tie at 0.5, C scores (0.7 / 1.5 + 0.5) / 2
+        compute_composite_scores(&mut results, &weighted(1.0, 1.0));
+        for r in &results {
+            assert!((r.composite_score - 0.5).abs() < 1e-12 || r.params.contains_key("C"));
+        }
+
+        // Minimized objectives prefer the lowest raw value; overriding the direction flips that
+        let ulcer = |direction| {
+            vec![
+                WeightedObjective { objective: ObjectiveFunction::MinUlcerIndex, weight: 1.0, direction },
+                WeightedObjective { objective: ObjectiveFunction::SharpeRatio, weight: 0.0, direction: None },
+            ]
+        };
+        assert_eq!(ranking(&mut results, &ulcer(None)), ["A", "C", "B"]);
+        assert_eq!(ranking(&mut results, &ulcer(Some(ObjectiveDirection::Maximize))), ["B", "C", "A"]);
+    }
+
+    #[test]
+    fn test_weighted_objective_deserializes_bare_names() {
+        let parsed: Vec<WeightedObjective> = serde_json::from_str(
+            r#"["SharpeRatio", {"objective": "CalmarRatio", "weight": 2.5, "direction": "Minimize"}, {"objective": "Expectancy"}]"#,
+        )
+        .unwrap();
+        assert_eq!(parsed[0], ObjectiveFunction::SharpeRatio.into());
+        assert_eq!(parsed[1].weight, 2.5);
+        assert_eq!(parsed[1].direction(), ObjectiveDirection::Minimize);
+        assert_eq!(parsed[2].weight, 1.0);
+        assert_eq!(parsed[2].direction(), ObjectiveDirection::Maximize);
+
+        assert!(validate_objectives(&parsed).is_ok());
+        let negative = [WeightedObjective { weight: -1.0, ..parsed[0] }];
+        assert!(validate_objectives(&negative).is_err());
+    }
+
+    /// Oscillating EUR/USD-like series: a slow swing plus a faster wobble, so
+    /// different thresholds and SL/TP distances produce different trade sets.
+    fn wave_candles(n: usize) -> Vec<Candle> {
+        (0..n)
+            .map(|i| {
+                let t = i as f64;
+                let mid = 1.1000 + 0.0040 * (t / 37.0).sin() + 0.0012 * (t / 5.0).sin();
+                Candle {
+                    datetime: format!("2024-01-{:02} {:02}:00", 1 + i / 24, i % 24),
+                    open: mid - 0.0002,
+                    high: mid + 0.0008,
+                    low: mid - 0.0008,
+                    close: mid + 0.0002,
+                    volume: 1000.0,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn price_operand(field: PriceField) -> Operand {
+        Operand {
+            operand_type: OperandType::Price,
+            indicator: None,
+            price_field: Some(field),
+            constant_value: None,
+            time_field: None,
+            candle_pattern: None,
+            offset: None,
+            compound_left: None,
+            compound_op: None,
+            compound_right: None,
+            percent_of: None,
+            transform: None,
+        }
+    }
+
+    fn constant_operand(value: f64) -> Operand {
+        Operand {
+            operand_type: OperandType::Constant,
+            constant_value: Some(value),
+            price_field: None,
+            ..price_operand(PriceField::Close)
+        }
+    }
+
+    /// Long when close crosses a constant level, exits via pip SL/TP.
+    fn threshold_strategy() -> Strategy {
+        Strategy {
+            id: "s1".to_string(),
+            name: "threshold".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+            long_entry_rules: vec![Rule {
+                id: "r1".to_string(),
+                left_operand: price_operand(PriceField::Close),
+                comparator: Comparator::GreaterThan,
+                right_operand: constant_operand(1.1000),
+                logical_operator: None,
+                open_parens: 0,
+                close_parens: 0,
+                tolerance: None,
+            }],
+            short_entry_rules: vec![],
+            long_exit_rules: vec![],
+            short_exit_rules: vec![],
+            long_entry_groups: vec![],
+            short_entry_groups: vec![],
+            long_exit_groups: vec![],
+            short_exit_groups: vec![],
+            position_sizing: PositionSizing {
+                sizing_type: PositionSizingType::FixedLots,
+                value: 1.0,
+                decrease_factor: 0.9,
+            },
+            stop_loss: Some(StopLoss { sl_type: StopLossType::Pips, value: 30.0, atr_period: None }),
+            take_profit: Some(TakeProfit { tp_type: TakeProfitType::Pips, value: 30.0, atr_period: None }),
+            trailing_stop: None,
+            trading_costs: TradingCosts {
+                spread_pips: 1.0,
+                commission_type: CommissionType::FixedPerLot,
+                commission_value: 0.0,
+                slippage_pips: 0.0,
+                slippage_random: false,
+                max_spread_pips: None,
+            },
+            trade_direction: TradeDirection::Long,
+            trading_hours: None,
+            max_daily_trades: None,
+            close_trades_at: None,
+            entry_order: OrderType::Market,
+            entry_order_offset_pips: 0.0,
+            close_after_bars: None,
+            min_hold_bars: None,
+            max_hold_bars: None,
+            move_sl_to_be: false,
+            close_on_opposite_signal: false,
+            reverse_on_opposite_signal: false,
+            session_exit: None,
+            entry_order_indicator: None,
+        }
+    }
+
+    fn threshold_ranges() -> Vec<ParameterRange> {
+        let range = |source: &str, name: &str, display: &str, min: f64, max: f64, step: f64| ParameterRange {
+            rule_index: 0,
+            param_name: name.into(),
+            display_name: display.into(),
+            min,
+            max,
+            step,
+            operand_side: "right".into(),
+            param_source: source.into(),
+        };
+        vec![
+            range("long_entry", "constant_value", "Level", 1.0960, 1.1040, 0.0005),
+            range("stop_loss", "value", "SL", 10.0, 80.0, 5.0),
+            range("take_profit", "value", "TP", 10.0, 80.0, 5.0),
+        ]
+    }
+
+    fn threshold_config() -> BacktestConfig {
+        BacktestConfig {
+            symbol_id: "sym".to_string(),
+            timeframe: Timeframe::H1,
+            start_date: "2024-01-01".to_string(),
+            end_date: "2024-12-31".to_string(),
+            initial_capital: 10_000.0,
+            leverage: 1.0,
+            precision: BacktestPrecision::SelectedTfOnly,
+            early_stop_no_trades_pct: None,
+            pending_order_expiry_bars: None,
+            use_heikin_ashi: false,
+            equity_sampling: None,
+            tick_chunk_bars: None,
+            compounding: true,
+            stop_out_equity: None,
+            magnifier_timeframe: None,
+            account_currency: None,
+            quote_conversion: None,
+            warmup_bars: None,
+            exit_priority: ExitPriority::default(),
+            fallback_on_missing_raw: false,
+            drawdown_basis: DrawdownBasis::default(),
+            overtrading_threshold: None,
+            regime: None,
+            slippage_seed: None,
+        }
+    }
+
+    fn run_seeded_ga(seed: u64, strategy: &Strategy) -> Vec<OptimizationResult> {
+        let ga_config = GeneticAlgorithmConfig {
+            population_size: 16,
+            generations: 4,
+            mutation_rate: 0.2,
+            crossover_rate: 0.7,
+            crossover_type: CrossoverType::OnePoint,
+            mutation_type: MutationType::UniformReset,
+            patience: None,
+            seed: Some(seed),
+        };
+        run_genetic_algorithm(
+            &wave_candles(600),
+            &SubBarData::None,
+            strategy,
+            &threshold_config(),
+            &InstrumentConfig::default(),
+            &threshold_ranges(),
+            &[ObjectiveFunction::TotalProfit.into()],
+            &ga_config,
+            &AtomicBool::new(false),
+            |_, _, _, _| {},
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cancelled_grid_search_returns_partial_results() {
+        let ranges = &threshold_ranges()[..2];
+        let (_, total) = build_grid_ranges(ranges).unwrap();
+        let cancel = AtomicBool::new(false);
+        let results = run_grid_search_all(
+            &wave_candles(600),
+            &SubBarData::None,
+            &threshold_strategy(),
+            &threshold_config(),
+            &InstrumentConfig::default(),
+            ranges,
+            &[ObjectiveFunction::TotalProfit.into()],
+            &cancel,
+            |_, current, _, _| {
+                if current >= 20 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            },
+        )
+        .unwrap();
+
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(!results.is_empty() && results.len() < total, "{} of {}", results.len(), total);
+        assert!(results.windows(2).all(|w| w[0].objective_value >= w[1].objective_value));
+    }
+
+    #[test]
+    fn test_grid_search_skips_macd_fast_not_below_slow() {
+        // MACD line above zero; fast periods 8/12/16 against a fixed slow period of 12
+        let mut strategy = threshold_strategy();
+        strategy.long_entry_rules[0].left_operand = Operand {
+            operand_type: OperandType::Indicator,
+            indicator: Some(IndicatorConfig {
+                indicator_type: IndicatorType::MACD,
+                params: IndicatorParams {
+                    fast_period: Some(8),
+                    slow_period: Some(12),
+                    signal_period: Some(9),
+                    ..Default::default()
+                },
+                output_field: None,
+                cached_hash: 0,
+            }),
+            price_field: None,
+            ..price_operand(PriceField::Close)
+        };
+        strategy.long_entry_rules[0].right_operand = constant_operand(0.0);
+        let range = |param: &str, min: f64, max: f64| ParameterRange {
+            rule_index: 0,
+            param_name: param.into(),
+            display_name: param.into(),
+            min,
+            max,
+            step: 4.0,
+            operand_side: "left".into(),
+            param_source: "long_entry".into(),
+        };
+        let ranges = vec![range("fast_period", 8.0, 16.0), range("slow_period", 12.0, 12.0)];
+
+        let results = run_grid_search_all(
+            &wave_candles(600),
+            &SubBarData::None,
+            &strategy,
+            &threshold_config(),
+            &InstrumentConfig::default(),
+            &ranges,
+            &[ObjectiveFunction::TotalProfit.into()],
+            &AtomicBool::new(false),
+            |_, _, _, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 3);
+        // The valid combination runs and ranks first
+        assert!(results[0].failure_reason.is_none());
+        assert_eq!(results[0].params["fast_period"], 8.0);
+        assert!(results[0].total_trades > 0);
+        // fast >= slow is recorded as skipped without a backtest
+        let mut skipped: Vec<f64> = results[1..]
+            .iter()
+            .map(|r| {
+                let reason = r.failure_reason.as_deref().unwrap();
+                assert!(reason.contains("not shorter than slow period"), "{}", reason);
+                r.params["fast_period"]
+            })
+            .collect();
+        skipped.sort_by(f64::total_cmp);
+        assert_eq!(skipped, vec![12.0, 16.0]);
+    }
+
+    #[test]
+    fn test_grid_search_marks_periods_longer_than_data_failed() {
+        // Close above SMA(period); periods of 700 and 900 exceed the 600 candles
+        let mut strategy = threshold_strategy();
+        strategy.long_entry_rules[0].right_operand = Operand {
+            operand_type: OperandType::Indicator,
+            indicator: Some(IndicatorConfig {
+                indicator_type: IndicatorType::SMA,
+                params: IndicatorParams { period: Some(100), ..Default::default() },
+                output_field: None,
+                cached_hash: 0,
+            }),
+            price_field: None,
+            ..price_operand(PriceField::Close)
+        };
+        let ranges = vec![ParameterRange {
+            rule_index: 0,
+            param_name: "period".into(),
+            display_name: "SMA period".into(),
+            min: 100.0,
+            max: 900.0,
+            step: 200.0,
+            operand_side: "right".into(),
+            param_source: "long_entry".into(),
+        }];
+
+        let results = run_grid_search_all(
+            &wave_candles(600),
+            &SubBarData::None,
+            &strategy,
+            &threshold_config(),
+            &InstrumentConfig::default(),
+            &ranges,
+            &[ObjectiveFunction::TotalProfit.into()],
+            &AtomicBool::new(false),
+            |_, _, _, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 5);
+        let failed: Vec<f64> = results
+            .iter()
+            .filter(|r| r.failure_reason.is_some())
+            .map(|r| r.params["SMA period"])
+            .collect();
+        assert_eq!(failed.len(), 2);
+        assert!(failed.contains(&700.0) && failed.contains(&900.0));
+        // Failed combinations carry a disqualifying score and rank last
+        assert!(results[..3].iter().all(|r| r.failure_reason.is_none() && r.objective_value.is_finite()));
+        assert!(results[3..].iter().all(|r| r.objective_value == f64::NEG_INFINITY));
+        assert!(results[3].failure_reason.as_deref().unwrap().contains("Insufficient data"));
+    }
+
+    #[test]
+    fn test_cancelled_ga_returns_evaluated_generations() {
+        let ga_config = GeneticAlgorithmConfig {
+            population_size: 16,
+            generations: 20,
+            mutation_rate: 0.2,
+            crossover_rate: 0.7,
+            crossover_type: CrossoverType::OnePoint,
+            mutation_type: MutationType::UniformReset,
+            patience: None,
+            seed: Some(42),
+        };
+        let cancel = AtomicBool::new(false);
+        let last_gen = AtomicUsize::new(0);
+        let results = run_genetic_algorithm_all(
+            &wave_candles(600),
+            &SubBarData::None,
+            &threshold_strategy(),
+            &threshold_config(),
+            &InstrumentConfig::default(),
+            &threshold_ranges(),
+            &[ObjectiveFunction::TotalProfit.into()],
+            &ga_config,
+            &cancel,
+            |_, gen, _, _| {
+                last_gen.store(gen, Ordering::Relaxed);
+                if gen == 2 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            },
+        )
+        .unwrap();
+
+        // Stopped after the second generation, keeping what it had evaluated
+        assert_eq!(last_gen.load(Ordering::Relaxed), 2);
+        assert!(!results.is_empty() && results.len() <= 2 * 16);
+    }
+
+    #[test]
+    fn test_seeded_ga_is_reproducible() {
+        // Compare via JSON so params (a HashMap) compare order-independently
+        let as_json = |r: &[OptimizationResult]| serde_json::to_value(r).unwrap();
+        let first = run_seeded_ga(42, &threshold_strategy());
+        assert!(!first.is_empty() && first[0].total_trades > 0);
+        assert_eq!(as_json(&first), as_json(&run_seeded_ga(42, &threshold_strategy())));
+
+        let other = run_seeded_ga(7, &threshold_strategy());
+        assert_ne!(as_json(&first), as_json(&other));
+    }
+
+    #[test]
+    fn test_seeded_ga_is_reproducible_with_random_slippage() {
+        let as_json = |r: &[OptimizationResult]| serde_json::to_value(r).unwrap();
+        let mut strategy = threshold_strategy();
+        strategy.trading_costs.slippage_pips = 3.0;
+        strategy.trading_costs.slippage_random = true;
+        let first = run_seeded_ga(42, &strategy);
+        assert!(!first.is_empty() && first[0].total_trades > 0);
+        assert_eq!(as_json(&first), as_json(&run_seeded_ga(42, &strategy)));
+    }
+
+    fn oos(label: &str, start: &str, end: &str) -> OosPeriod {
+        OosPeriod { label: label.into(), start_date: start.into(), end_date: end.into() }
+    }
+
+    #[test]
+    fn test_oos_overlapping_in_sample_rejected() {
+        let periods = vec![oos("Q4", "2023-12-31", "2024-03-31")];
+        let err = validate_oos_periods("2023-01-01", "2023-12-31", &periods, false).unwrap_err();
+        assert!(matches!(err, AppError::OptimizationError(msg) if msg.contains("Q4")));
+
+        // An open-ended OOS start reaches back into the in-sample range.
+        let open = vec![oos("Before", "", "2023-06-01")];
+        assert!(validate_oos_periods("2023-01-01", "2023-12-31", &open, false).is_err());
+
+        // Explicitly allowed overlaps pass.
+        assert!(validate_oos_periods("2023-01-01", "2023-12-31", &periods, true).is_ok());
+    }
+
+    #[test]
+    fn test_oos_disjoint_from_in_sample_accepted() {
+        let periods = vec![
+            oos("Before", "2022-01-01", "2022-12-31"),
+            oos("After", "2024-01-01", "2024-06-30"),
+        ];
+        assert!(validate_oos_periods("2023-01-01", "2023-12-31", &periods, false).is_ok());
+
+        // Datetime bounds: the OOS window starts right after the in-sample end.
+        let tight = vec![oos("Next", "2023-12-31 12:00:00", "2024-01-31")];
+        assert!(validate_oos_periods("2023-01-01", "2023-12-31 11:59:59", &tight, false).is_ok());
+    }
+
+    #[test]
+    fn test_oos_inverted_period_rejected() {
+        let periods = vec![oos("Bad", "2024-06-30", "2024-01-01")];
+        assert!(validate_oos_periods("2023-01-01", "2023-12-31", &periods, true).is_err());
+    }
 }
@@ -1,8 +1,9 @@
 use rand::Rng;
 
+use crate::errors::AppError;
 use crate::models::candle::Candle;
 use crate::models::config::InstrumentConfig;
-use crate::models::strategy::{CommissionType, TradeDirection, TradingCosts};
+use crate::models::strategy::{BacktestConfig, CommissionType, QuoteConversion, TradeDirection, TradingCosts};
 
 // ── Bid/Ask split ──────────────────────────────────────────────────────────
 
@@ -39,11 +40,25 @@ impl BidAskOhlc {
 
 /// Compute the full spread in price units from costs + instrument config.
 pub fn spread_price(costs: &TradingCosts, instrument: &InstrumentConfig) -> f64 {
-    costs.spread_pips * instrument.pip_size
+    instrument.pips_to_price(costs.spread_pips)
 }
 
-/// Apply trading costs (spread + slippage) to the entry price.
-/// For long: buy at ask (price + spread), for short: sell at bid (price - spread).
+/// Slippage for one fill in price units: the configured pips, or a uniform draw
+/// in `[0, slippage_pips)` when random slippage is enabled.
+fn slippage_price<R: Rng>(costs: &TradingCosts, instrument: &InstrumentConfig, rng: &mut R) -> f64 {
+    if costs.slippage_random {
+        // Random slippage between 0 and max — uses caller-provided RNG for reproducibility
+        instrument.pips_to_price(costs.slippage_pips) * rng.gen::<f64>()
+    } else {
+        instrument.pips_to_price(costs.slippage_pips)
+    }
+}
+
+/// Apply trading costs (spread + slippage) to a bid-quoted entry price.
+///
+/// Bar prices are bid prices, so the spread only moves the side that trades at ask:
+/// long buys at ask (price + spread), short sells at bid (price).
+/// Slippage always moves the fill against the trader.
 pub fn apply_entry_costs<R: Rng>(
     price: f64,
     direction: TradeDirection,
@@ -51,17 +66,12 @@ pub fn apply_entry_costs<R: Rng>(
     instrument: &InstrumentConfig,
     rng: &mut R,
 ) -> f64 {
-    let spread = costs.spread_pips * instrument.pip_size;
-    let slippage = if costs.slippage_random {
-        // Random slippage between 0 and max — uses caller-provided RNG for reproducibility
-        costs.slippage_pips * instrument.pip_size * rng.gen::<f64>()
-    } else {
-        costs.slippage_pips * instrument.pip_size
-    };
+    let spread = spread_price(costs, instrument);
+    let slippage = slippage_price(costs, instrument, rng);
 
     match direction {
         TradeDirection::Long | TradeDirection::Both => price + spread + slippage,
-        TradeDirection::Short => price - spread - slippage,
+        TradeDirection::Short => price - slippage,
     }
 }
 
@@ -74,11 +84,7 @@ pub fn apply_slippage_only<R: Rng>(
     instrument: &InstrumentConfig,
     rng: &mut R,
 ) -> f64 {
-    let slippage = if costs.slippage_random {
-        costs.slippage_pips * instrument.pip_size * rng.gen::<f64>()
-    } else {
-        costs.slippage_pips * instrument.pip_size
-    };
+    let slippage = slippage_price(costs, instrument, rng);
 
     match direction {
         TradeDirection::Long | TradeDirection::Both => price + slippage,
@@ -86,9 +92,11 @@ pub fn apply_slippage_only<R: Rng>(
     }
 }
 
-/// Apply trading costs (slippage) to the exit price.
-/// For long: sell at bid (price - slippage), for short: buy at ask (price + slippage).
-/// Note: spread is already paid on entry, only slippage affects exit.
+/// Apply trading costs (spread + slippage) to a bid-quoted exit price.
+///
+/// Mirror of [`apply_entry_costs`]: long sells at bid (price), short buys back at
+/// ask (price + spread). A round trip therefore pays the spread exactly once in
+/// either direction.
 pub fn apply_exit_costs<R: Rng>(
     price: f64,
     direction: TradeDirection,
@@ -96,30 +104,80 @@ pub fn apply_exit_costs<R: Rng>(
     instrument: &InstrumentConfig,
     rng: &mut R,
 ) -> f64 {
-    let slippage = if costs.slippage_random {
-        costs.slippage_pips * instrument.pip_size * rng.gen::<f64>()
-    } else {
-        costs.slippage_pips * instrument.pip_size
-    };
+    let spread = spread_price(costs, instrument);
+    let slippage = slippage_price(costs, instrument, rng);
+
+    match direction {
+        // Long exit = selling at bid → slippage moves us lower
+        TradeDirection::Long | TradeDirection::Both => price - slippage,
+        // Short exit = buying at ask → spread + slippage move us higher
+        TradeDirection::Short => price + spread + slippage,
+    }
+}
+
+/// Apply only slippage to an exit price that is already on the executable side.
+/// Used for SL/TP fills resolved against [`BidAskOhlc`] and for real tick bid/ask.
+pub fn apply_exit_slippage_only<R: Rng>(
+    price: f64,
+    direction: TradeDirection,
+    costs: &TradingCosts,
+    instrument: &InstrumentConfig,
+    rng: &mut R,
+) -> f64 {
+    let slippage = slippage_price(costs, instrument, rng);
 
     match direction {
-        // Long exit = selling → price moves against us (lower)
         TradeDirection::Long | TradeDirection::Both => price - slippage,
-        // Short exit = buying → price moves against us (higher)
         TradeDirection::Short => price + slippage,
     }
 }
 
-/// Calculate monetary P&L for a closed position.
+/// Calculate monetary P&L for a closed position, in account currency.
+///
+/// `quote_to_account` converts `pip_value` money into the account currency
+/// (see [`quote_to_account_rate`]); 1.0 when no conversion applies.
 pub fn calculate_pnl(
     direction: TradeDirection,
     entry_price: f64,
     exit_price: f64,
     lots: f64,
     instrument: &InstrumentConfig,
+    quote_to_account: f64,
 ) -> f64 {
     let pnl_pips = calculate_pnl_pips(direction, entry_price, exit_price, instrument);
-    pnl_pips * instrument.pip_value * lots
+    pnl_pips * instrument.pip_value * lots * quote_to_account
+}
+
+/// Money value of a price distance over `lots`, in account currency.
+pub fn price_distance_value(distance: f64, lots: f64, instrument: &InstrumentConfig, quote_to_account: f64) -> f64 {
+    instrument.price_to_pips(distance) * instrument.pip_value * lots * quote_to_account
+}
+
+/// Quote→account currency rate in effect at `datetime` under `config.quote_conversion`.
+/// 1.0 without a conversion. A symbol source errors when none of its bars has closed by
+/// `datetime` rather than guessing a rate.
+pub fn quote_to_account_rate(config: &BacktestConfig, datetime: &str) -> Result<f64, AppError> {
+    match &config.quote_conversion {
+        None => Ok(1.0),
+        Some(QuoteConversion::Constant { rate }) => Ok(*rate),
+        Some(QuoteConversion::Symbol { symbol_id, rates, .. }) => datetime_to_micros(datetime)
+            .and_then(|t| rates.rate_at(t))
+            .ok_or_else(|| {
+                AppError::InvalidConfig(format!(
+                    "No quote conversion rate from {} before {}: its data must start at least one bar before the backtest",
+                    symbol_id, datetime
+                ))
+            }),
+    }
+}
+
+/// Parse a candle/tick datetime ("YYYY-MM-DD HH:MM[:SS[.ffffff]]") as UTC microseconds.
+fn datetime_to_micros(datetime: &str) -> Option<i64> {
+    use chrono::NaiveDateTime;
+    NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M"))
+        .ok()
+        .map(|dt| dt.and_utc().timestamp_micros())
 }
 
 /// Calculate P&L in pips.
@@ -129,29 +187,30 @@ pub fn calculate_pnl_pips(
     exit_price: f64,
     instrument: &InstrumentConfig,
 ) -> f64 {
-    if instrument.pip_size == 0.0 {
-        return 0.0;
-    }
     match direction {
         TradeDirection::Long | TradeDirection::Both => {
-            (exit_price - entry_price) / instrument.pip_size
+            instrument.price_to_pips(exit_price - entry_price)
         }
-        TradeDirection::Short => (entry_price - exit_price) / instrument.pip_size,
+        TradeDirection::Short => instrument.price_to_pips(entry_price - exit_price),
     }
 }
 
-/// Calculate commission for a trade.
+/// Calculate commission for a trade, in account currency.
+///
+/// `FixedPerLot` is already quoted in account currency; `Percentage` applies to the
+/// position value in quote currency and is converted with `quote_to_account`.
 pub fn calculate_commission(
     costs: &TradingCosts,
     lots: f64,
     entry_price: f64,
     instrument: &InstrumentConfig,
+    quote_to_account: f64,
 ) -> f64 {
     match costs.commission_type {
         CommissionType::FixedPerLot => costs.commission_value * lots,
         CommissionType::Percentage => {
             let position_value = entry_price * lots * instrument.lot_size;
-            position_value * costs.commission_value / 100.0
+            position_value * costs.commission_value / 100.0 * quote_to_account
         }
     }
 }
@@ -169,7 +228,7 @@ mod tests {
         let inst = forex_instrument();
         let pnl_pips = calculate_pnl_pips(TradeDirection::Long, 1.1000, 1.1050, &inst);
         assert!((pnl_pips - 50.0).abs() < 0.01);
-        let pnl = calculate_pnl(TradeDirection::Long, 1.1000, 1.1050, 1.0, &inst);
+        let pnl = calculate_pnl(TradeDirection::Long, 1.1000, 1.1050, 1.0, &inst, 1.0);
         assert!((pnl - 500.0).abs() < 0.01); // 50 pips * $10/pip * 1 lot
     }
 
@@ -180,6 +239,144 @@ mod tests {
         assert!((pnl_pips - 50.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_cross_pair_pnl_converted_to_account_currency() {
+        // EUR/GBP on a USD account: pip value is £10 per lot, GBP→USD at 1.25
+        let inst = forex_instrument();
+        let pnl = calculate_pnl(TradeDirection::Long, 0.8500, 0.8550, 2.0, &inst, 1.25);
+        assert!((pnl - 1250.0).abs() < 1e-9); // 50 pips * £10 * 2 lots = £1000 → $1250
+
+        // Percentage commission is charged on the GBP position value; per-lot is already USD
+        let mut costs = spread_only_costs(0.0);
+        costs.commission_type = CommissionType::Percentage;
+        costs.commission_value = 0.01;
+        let comm = calculate_commission(&costs, 1.0, 0.8500, &inst, 1.25);
+        assert!((comm - 8.5 * 1.25).abs() < 1e-9);
+        costs.commission_type = CommissionType::FixedPerLot;
+        costs.commission_value = 7.0;
+        assert!((calculate_commission(&costs, 1.0, 0.8500, &inst, 1.25) - 7.0).abs() < 1e-12);
+    }
+
+    fn usd_account_config() -> BacktestConfig {
+        BacktestConfig {
+            symbol_id: "eurgbp".into(),
+            timeframe: crate::models::config::Timeframe::H1,
+            start_date: String::new(),
+            end_date: String::new(),
+            initial_capital: 10_000.0,
+            leverage: 1.0,
+            precision: Default::default(),
+            early_stop_no_trades_pct: None,
+            pending_order_expiry_bars: None,
+            use_heikin_ashi: false,
+            equity_sampling: None,
+            tick_chunk_bars: None,
+            compounding: true,
+            stop_out_equity: None,
+            magnifier_timeframe: None,
+            account_currency: Some("USD".into()),
+            quote_conversion: None,
+            warmup_bars: None,
+            exit_priority: Default::default(),
+            fallback_on_missing_raw: false,
+            drawdown_basis: Default::default(),
+            overtrading_threshold: None,
+            regime: None,
+            slippage_seed: None,
+        }
+    }
+
+    #[test]
+    fn test_quote_to_account_rate_sources() {
+        let mut config = usd_account_config();
+        let rate = |config: &BacktestConfig, dt: &str| quote_to_account_rate(config, dt).unwrap();
+        assert_eq!(rate(&config, "2024-01-02 10:00"), 1.0);
+
+        config.quote_conversion = Some(QuoteConversion::Constant { rate: 1.27 });
+        assert_eq!(rate(&config, "2024-01-02 10:00"), 1.27);
+
+        // H1 GBPUSD bars opening at 10:00 and 11:00: a fill uses the close of the last
+        // bar that has completed, never the bar still forming around it
+        let hour = |h: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap().and_hms_opt(h, 0, 0).unwrap().and_utc().timestamp_micros()
+        };
+        let rates = crate::models::strategy::ConversionRates {
+            timestamps: vec![hour(10), hour(11)],
+            rates: vec![1.25, 1.30],
+            bar_micros: 3_600_000_000,
+        };
+        config.quote_conversion = Some(QuoteConversion::Symbol {
+            symbol_id: "gbpusd".into(),
+            invert: false,
+            rates: std::sync::Arc::new(rates),
+        });
+        assert_eq!(rate(&config, "2024-01-02 11:00:00"), 1.25);
+        assert_eq!(rate(&config, "2024-01-02 11:59:59.500000"), 1.25);
+        assert_eq!(rate(&config, "2024-01-02 12:00"), 1.30);
+        assert_eq!(rate(&config, "2024-01-03 00:00"), 1.30);
+    }
+
+    #[test]
+    fn test_quote_to_account_rate_missing_series_errors() {
+        let hour = |h: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap().and_hms_opt(h, 0, 0).unwrap().and_utc().timestamp_micros()
+        };
+        let with_rates = |timestamps: Vec<i64>, rates: Vec<f64>| BacktestConfig {
+            quote_conversion: Some(QuoteConversion::Symbol {
+                symbol_id: "gbpusd".into(),
+                invert: false,
+                rates: std::sync::Arc::new(crate::models::strategy::ConversionRates {
+                    timestamps,
+                    rates,
+                    bar_micros: 3_600_000_000,
+                }),
+            }),
+            ..usd_account_config()
+        };
+
+        // Before the first conversion bar closes, and with no series loaded at all
+        let config = with_rates(vec![hour(10)], vec![1.25]);
+        for dt in ["2024-01-02 09:00", "2024-01-02 10:00", "2024-01-02 10:30"] {
+            assert!(matches!(quote_to_account_rate(&config, dt), Err(AppError::InvalidConfig(_))), "{}", dt);
+        }
+        assert_eq!(quote_to_account_rate(&config, "2024-01-02 11:00").unwrap(), 1.25);
+        let empty = with_rates(Vec::new(), Vec::new());
+        assert!(quote_to_account_rate(&empty, "2024-01-02 11:00").is_err());
+    }
+
+    #[test]
+    fn test_pip_point_conversion_jpy_and_index() {
+        // USD/JPY on a 3-digit feed: pip 0.01 = 10 points, like EUR/USD at 5 digits
+        let jpy3 = InstrumentConfig { pip_size: 0.01, pip_value: 6.7, tick_size: 0.001, digits: 3, ..Default::default() };
+        assert!((jpy3.point() - 0.001).abs() < 1e-15);
+        assert!((jpy3.pip_multiplier() - 10.0).abs() < 1e-9);
+        assert_eq!(InstrumentConfig::conventional_pip_multiplier(jpy3.digits), 10.0);
+        let pips = calculate_pnl_pips(TradeDirection::Long, 150.000, 150.255, &jpy3);
+        assert!((pips - 25.5).abs() < 1e-9);
+
+        // 2-digit JPY feed: the point IS the pip, so `_Point * 10` would be 10 pips off
+        let jpy2 = InstrumentConfig { digits: 2, tick_size: 0.01, ..jpy3.clone() };
+        assert!((jpy2.pip_multiplier() - 1.0).abs() < 1e-9);
+        assert_eq!(InstrumentConfig::conventional_pip_multiplier(jpy2.digits), 1.0);
+        assert!((jpy2.pips_to_price(30.0) - 0.30).abs() < 1e-12);
+        let pips = calculate_pnl_pips(TradeDirection::Short, 150.30, 150.00, &jpy2);
+        assert!((pips - 30.0).abs() < 1e-9);
+
+        // Index CFD quoted to 2 digits with a 1.0 pip: 100 points per pip, which no
+        // digits-based rule can infer — the EA needs InpPipPoints for this one
+        let index = InstrumentConfig { pip_size: 1.0, pip_value: 1.0, lot_size: 1.0, tick_size: 0.01, digits: 2, ..Default::default() };
+        assert!((index.pip_multiplier() - 100.0).abs() < 1e-9);
+        assert_ne!(InstrumentConfig::conventional_pip_multiplier(index.digits), index.pip_multiplier());
+        assert!((index.pips_to_price(50.0) - 50.0).abs() < 1e-12);
+        let pnl = calculate_pnl(TradeDirection::Long, 39_000.0, 39_012.5, 2.0, &index, 1.0);
+        assert!((pnl - 25.0).abs() < 1e-9); // 12.5 pips * $1/pip * 2 lots
+        assert!((index.points_to_pips(250.0) - 2.5).abs() < 1e-12);
+
+        // Unset pip size never divides by zero
+        let unset = InstrumentConfig { pip_size: 0.0, ..Default::default() };
+        assert_eq!(calculate_pnl_pips(TradeDirection::Long, 1.0, 2.0, &unset), 0.0);
+    }
+
     #[test]
     fn test_apply_entry_costs_long() {
         let inst = forex_instrument();
@@ -189,12 +386,111 @@ mod tests {
             commission_value: 7.0,
             slippage_pips: 0.0,
             slippage_random: false,
+            max_spread_pips: None,
         };
         let adjusted = apply_entry_costs(1.1000, TradeDirection::Long, &costs, &inst, &mut rand::thread_rng());
         // Long: price + spread = 1.1000 + 2*0.0001 = 1.1002
         assert!((adjusted - 1.1002).abs() < 1e-10);
     }
 
+    fn spread_only_costs(spread_pips: f64) -> TradingCosts {
+        TradingCosts {
+            spread_pips,
+            commission_type: CommissionType::FixedPerLot,
+            commission_value: 0.0,
+            slippage_pips: 0.0,
+            slippage_random: false,
+            max_spread_pips: None,
+        }
+    }
+
+    fn flat_candle(price: f64) -> Candle {
+        Candle {
+            timestamp: 0,
+            datetime: "2024-01-02 10:00".into(),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn open_position(direction: TradeDirection, entry_price: f64, sl: f64, tp: f64) -> crate::engine::position::OpenPosition {
+        crate::engine::position::OpenPosition {
+            direction,
+            entry_price,
+            entry_bar: 0,
+            entry_time: String::new(),
+            lots: 1.0,
+            stop_loss: Some(sl),
+            initial_stop_loss: Some(sl),
+            take_profit: Some(tp),
+            trailing_stop_distance: None,
+            highest_since_entry: entry_price,
+            lowest_since_entry: entry_price,
+            mae_pips: 0.0,
+            mfe_pips: 0.0,
+            trailing_stop_activated: false,
+            last_swap_date: String::new(),
+            accumulated_swap: 0.0,
+            entry_spread: 0.0,
+            entry_slippage: 0.0,
+            sl_moved_to_be: false,
+            trailing_activation_dist: None,
+            trailing_step: None,
+            entry_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_flat_round_trip_loses_exactly_the_spread() {
+        let inst = forex_instrument();
+        let costs = spread_only_costs(1.5);
+        let mut rng = rand::thread_rng();
+        let bid = 1.1000;
+
+        for dir in [TradeDirection::Long, TradeDirection::Short] {
+            let entry = apply_entry_costs(bid, dir, &costs, &inst, &mut rng);
+            let exit = apply_exit_costs(bid, dir, &costs, &inst, &mut rng);
+            let pips = calculate_pnl_pips(dir, entry, exit, &inst);
+            assert!((pips + 1.5).abs() < 1e-9, "{:?} round trip lost {} pips", dir, -pips);
+        }
+
+        // Long buys at ask and sells at bid; short sells at bid and buys at ask
+        assert!((apply_entry_costs(bid, TradeDirection::Long, &costs, &inst, &mut rng) - 1.10015).abs() < 1e-12);
+        assert!((apply_entry_costs(bid, TradeDirection::Short, &costs, &inst, &mut rng) - bid).abs() < 1e-12);
+        assert!((apply_exit_costs(bid, TradeDirection::Long, &costs, &inst, &mut rng) - bid).abs() < 1e-12);
+        assert!((apply_exit_costs(bid, TradeDirection::Short, &costs, &inst, &mut rng) - 1.10015).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_flat_round_trip_via_sl_tp_loses_exactly_the_spread() {
+        // Bar-mode SL/TP: a flat bar touches a level sitting exactly on the
+        // executable side, so the only cost left is the spread paid once.
+        let inst = forex_instrument();
+        let costs = spread_only_costs(2.0);
+        let spread = spread_price(&costs, &inst);
+        let mut rng = rand::thread_rng();
+        let bid = 1.1000;
+        let ba = BidAskOhlc::from_candle(&flat_candle(bid), spread);
+
+        // Long: entered at ask, SL sits at the bid → stopped out at bid
+        let entry = apply_entry_costs(bid, TradeDirection::Long, &costs, &inst, &mut rng);
+        let pos = open_position(TradeDirection::Long, entry, bid, bid + 0.0050);
+        let (fill, _) = crate::engine::position::check_sl_tp_hit(&pos, &ba).unwrap();
+        let exit = apply_exit_slippage_only(fill, pos.direction, &costs, &inst, &mut rng);
+        assert!((calculate_pnl_pips(pos.direction, entry, exit, &inst) + 2.0).abs() < 1e-9);
+
+        // Short: entered at bid, SL sits at the ask → stopped out at ask
+        let entry = apply_entry_costs(bid, TradeDirection::Short, &costs, &inst, &mut rng);
+        let pos = open_position(TradeDirection::Short, entry, bid + spread, bid - 0.0050);
+        let (fill, _) = crate::engine::position::check_sl_tp_hit(&pos, &ba).unwrap();
+        let exit = apply_exit_slippage_only(fill, pos.direction, &costs, &inst, &mut rng);
+        assert!((calculate_pnl_pips(pos.direction, entry, exit, &inst) + 2.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_commission_fixed_per_lot() {
         let inst = forex_instrument();
@@ -204,8 +500,9 @@ mod tests {
             commission_value: 7.0,
             slippage_pips: 0.0,
             slippage_random: false,
+            max_spread_pips: None,
         };
-        let comm = calculate_commission(&costs, 2.0, 1.1000, &inst);
+        let comm = calculate_commission(&costs, 2.0, 1.1000, &inst, 1.0);
         assert!((comm - 14.0).abs() < 1e-10); // $7 * 2 lots
     }
 }
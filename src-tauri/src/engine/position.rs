@@ -9,6 +9,10 @@ use crate::models::strategy::{
 };
 use crate::models::trade::CloseReason;
 
+/// Risk, in pips, that R:R take profits and trailing stops scale when the strategy
+/// has no stop loss. The exported MQL5/PineScript code uses the same value.
+pub const RR_FALLBACK_RISK_PIPS: f64 = 10.0;
+
 /// An open position being tracked during backtest execution.
 #[derive(Debug, Clone)]
 pub struct OpenPosition {
@@ -18,6 +22,8 @@ pub struct OpenPosition {
     pub entry_time: String,
     pub lots: f64,
     pub stop_loss: Option<f64>,
+    /// Stop loss level at entry, before any trailing or breakeven move (R-multiple risk).
+    pub initial_stop_loss: Option<f64>,
     pub take_profit: Option<f64>,
     pub trailing_stop_distance: Option<f64>,
     /// Highest price since entry (for long positions / trailing stop).
@@ -35,12 +41,29 @@ pub struct OpenPosition {
     pub last_swap_date: String,
     /// Cumulative swap charged so far (negative = cost to the trader).
     pub accumulated_swap: f64,
+    /// Spread paid on the entry fill, in price units.
+    pub entry_spread: f64,
+    /// Slippage paid on the entry fill, in price units.
+    pub entry_slippage: f64,
     /// True once the stop loss has been moved to breakeven (avoids double-trigger).
     pub sl_moved_to_be: bool,
     /// Minimum MFE (in price distance) before trailing stop is allowed to move.
     /// Computed from `TrailingStop::activation_pips * pip_size` at position open.
     /// `None` means no activation threshold — trailing starts immediately.
     pub trailing_activation_dist: Option<f64>,
+    /// Step trailing: the stop only moves in whole multiples of this price distance
+    /// of profit. `None` means continuous trailing.
+    pub trailing_step: Option<f64>,
+    /// Description of the entry rule set that opened this position (copied to `TradeResult`).
+    pub entry_reason: Option<String>,
+}
+
+impl OpenPosition {
+    /// Distance from entry to the initial stop loss in pips (`None` without a stop loss).
+    pub fn initial_risk_pips(&self, instrument: &InstrumentConfig) -> Option<f64> {
+        self.initial_stop_loss
+            .map(|sl| instrument.price_to_pips((self.entry_price - sl).abs()))
+    }
 }
 
 /// A pending limit or stop entry order waiting to be filled.
@@ -56,11 +79,15 @@ pub struct PendingOrder {
     pub atr_for_sl: Option<f64>,
     pub atr_for_tp: Option<f64>,
     pub atr_for_ts: Option<f64>,
+    /// Entry rule set that created the order — carried over to the position on fill.
+    pub entry_reason: Option<String>,
 }
 
 /// Calculate position size in lots.
 ///
 /// `consecutive_losses` is used only by `AntiMartingale` mode — pass 0 for all other modes.
+/// `quote_to_account` converts the quote-currency pip value into account currency
+/// (1.0 when they match); callers pass the rate known before the entry bar.
 pub fn calculate_lots(
     sizing: &PositionSizing,
     equity: f64,
@@ -68,40 +95,42 @@ pub fn calculate_lots(
     sl_price: Option<f64>,
     instrument: &InstrumentConfig,
     consecutive_losses: u32,
+    quote_to_account: f64,
 ) -> f64 {
+    let pip_value = instrument.pip_value * quote_to_account;
     let raw = match sizing.sizing_type {
         PositionSizingType::FixedLots => sizing.value,
         PositionSizingType::FixedAmount => {
             if let Some(sl) = sl_price {
-                let sl_distance_pips = (entry_price - sl).abs() / instrument.pip_size;
-                if sl_distance_pips < 1e-6 || instrument.pip_value == 0.0 {
+                let sl_distance_pips = instrument.price_to_pips((entry_price - sl).abs());
+                if sl_distance_pips < 1e-6 || pip_value == 0.0 {
                     return instrument.min_lot;
                 }
-                sizing.value / (sl_distance_pips * instrument.pip_value)
+                sizing.value / (sl_distance_pips * pip_value)
             } else {
                 instrument.min_lot
             }
         }
         PositionSizingType::PercentEquity => {
             if let Some(sl) = sl_price {
-                let sl_distance_pips = (entry_price - sl).abs() / instrument.pip_size;
-                if sl_distance_pips < 1e-6 || instrument.pip_value == 0.0 {
+                let sl_distance_pips = instrument.price_to_pips((entry_price - sl).abs());
+                if sl_distance_pips < 1e-6 || pip_value == 0.0 {
                     return instrument.min_lot;
                 }
                 let risk_amount = equity * sizing.value / 100.0;
-                risk_amount / (sl_distance_pips * instrument.pip_value)
+                risk_amount / (sl_distance_pips * pip_value)
             } else {
                 instrument.min_lot
             }
         }
         PositionSizingType::RiskBased => {
             if let Some(sl) = sl_price {
-                let sl_distance_pips = (entry_price - sl).abs() / instrument.pip_size;
-                if sl_distance_pips < 1e-6 || instrument.pip_value == 0.0 {
+                let sl_distance_pips = instrument.price_to_pips((entry_price - sl).abs());
+                if sl_distance_pips < 1e-6 || pip_value == 0.0 {
                     return instrument.min_lot;
                 }
                 let risk_amount = equity * sizing.value / 100.0;
-                risk_amount / (sl_distance_pips * instrument.pip_value)
+                risk_amount / (sl_distance_pips * pip_value)
             } else {
                 instrument.min_lot
             }
@@ -110,12 +139,12 @@ pub fn calculate_lots(
             // Base size uses risk-based sizing, then apply decay: decrease_factor^n_losses.
             // decrease_factor is in (0, 1]: 0.9 = −10% per loss, 1.0 = no decay.
             if let Some(sl) = sl_price {
-                let sl_distance_pips = (entry_price - sl).abs() / instrument.pip_size;
-                if sl_distance_pips < 1e-6 || instrument.pip_value == 0.0 {
+                let sl_distance_pips = instrument.price_to_pips((entry_price - sl).abs());
+                if sl_distance_pips < 1e-6 || pip_value == 0.0 {
                     return instrument.min_lot;
                 }
                 let risk_amount = equity * sizing.value / 100.0;
-                let base_lots = risk_amount / (sl_distance_pips * instrument.pip_value);
+                let base_lots = risk_amount / (sl_distance_pips * pip_value);
                 let decay = sizing.decrease_factor.max(0.0).powi(consecutive_losses as i32);
                 base_lots * decay
             } else {
@@ -124,7 +153,7 @@ pub fn calculate_lots(
         }
     };
 
-    // Clamp to min_lot and round to min_lot increments
+    // Round down to lot_step within [min_lot, max_lot]
     if raw <= 0.0 || raw.is_nan() {
         tracing::warn!(
             "Position sizing produced invalid raw lots ({:.6}); clamping to min_lot={}. \
@@ -133,8 +162,7 @@ pub fn calculate_lots(
         );
         return instrument.min_lot;
     }
-    let lots = (raw / instrument.min_lot).floor() * instrument.min_lot;
-    lots.max(instrument.min_lot)
+    instrument.normalize_lots(raw)
 }
 
 /// Calculate stop loss price.
@@ -146,9 +174,10 @@ pub fn calculate_stop_loss(
     instrument: &InstrumentConfig,
 ) -> f64 {
     let distance = match config.sl_type {
-        StopLossType::Pips => config.value * instrument.pip_size,
+        StopLossType::Pips => instrument.pips_to_price(config.value),
         StopLossType::Percentage => entry_price * config.value / 100.0,
-        StopLossType::ATR => {
+        // Chande Kroll starts like an ATR stop; `update_chande_kroll_stop` trails it
+        StopLossType::ATR | StopLossType::ChandeKroll => {
             let atr = atr_value.unwrap_or(0.0);
             atr * config.value
         }
@@ -194,13 +223,13 @@ pub fn calculate_take_profit(
     instrument: &InstrumentConfig,
 ) -> f64 {
     let distance = match config.tp_type {
-        TakeProfitType::Pips => config.value * instrument.pip_size,
+        TakeProfitType::Pips => instrument.pips_to_price(config.value),
         TakeProfitType::RiskReward => {
             // TP distance = SL distance * R:R ratio
             if let Some(sl) = sl_price {
                 (entry_price - sl).abs() * config.value
             } else {
-                config.value * instrument.pip_size * 10.0
+                instrument.pips_to_price(config.value * RR_FALLBACK_RISK_PIPS)
             }
         }
         TakeProfitType::ATR => {
@@ -232,9 +261,39 @@ pub fn calculate_trailing_stop_distance(
             if let Some(sl) = sl_price {
                 (entry_price - sl).abs() * config.value
             } else {
-                config.value * instrument.pip_size * 10.0
+                instrument.pips_to_price(config.value * RR_FALLBACK_RISK_PIPS)
             }
         }
+        TrailingStopType::Step => instrument.pips_to_price(config.value),
+    }
+}
+
+/// Price increment of a Step trailing stop (`None` for continuous types).
+pub fn trailing_step_price(config: &TrailingStop, instrument: &InstrumentConfig) -> Option<f64> {
+    match config.ts_type {
+        TrailingStopType::Step => {
+            Some(instrument.pips_to_price(config.step_pips.unwrap_or(config.value))).filter(|&s| s > 0.0)
+        }
+        _ => None,
+    }
+}
+
+/// Trailing stop level for a position whose best price so far is `extreme`
+/// (highest bid for longs, lowest ask for shorts). With a step, the level is
+/// `distance` from entry, moved by whole steps of profit.
+pub(crate) fn trailing_stop_level(position: &OpenPosition, extreme: f64, distance: f64) -> f64 {
+    let profit = match position.direction {
+        TradeDirection::Long | TradeDirection::Both => extreme - position.entry_price,
+        TradeDirection::Short => position.entry_price - extreme,
+    };
+    let offset = match position.trailing_step {
+        // Small epsilon so an exact multiple isn't floored one step short
+        Some(step) => ((profit / step) + 1e-9).floor().max(0.0) * step,
+        None => profit,
+    };
+    match position.direction {
+        TradeDirection::Long | TradeDirection::Both => position.entry_price + offset - distance,
+        TradeDirection::Short => position.entry_price - offset + distance,
     }
 }
 
@@ -260,7 +319,7 @@ pub fn update_trailing_stop(position: &mut OpenPosition, ba: &BidAskOhlc) {
                 // Track highest bid
                 if ba.bid_high > position.highest_since_entry {
                     position.highest_since_entry = ba.bid_high;
-                    let new_sl = position.highest_since_entry - distance;
+                    let new_sl = trailing_stop_level(position, position.highest_since_entry, distance);
                     let moved = if let Some(ref mut sl) = position.stop_loss {
                         if new_sl > *sl { *sl = new_sl; true } else { false }
                     } else {
@@ -274,7 +333,7 @@ pub fn update_trailing_stop(position: &mut OpenPosition, ba: &BidAskOhlc) {
                 // Track lowest ask
                 if ba.ask_low < position.lowest_since_entry {
                     position.lowest_since_entry = ba.ask_low;
-                    let new_sl = position.lowest_since_entry + distance;
+                    let new_sl = trailing_stop_level(position, position.lowest_since_entry, distance);
                     let moved = if let Some(ref mut sl) = position.stop_loss {
                         if new_sl < *sl { *sl = new_sl; true } else { false }
                     } else {
@@ -288,6 +347,60 @@ pub fn update_trailing_stop(position: &mut OpenPosition, ba: &BidAskOhlc) {
     }
 }
 
+/// Per-bar Chande Kroll stop levels (NaN until `period` bars and the ATR are available).
+#[derive(Debug, Clone)]
+pub struct ChandeKrollLevels {
+    /// Highest high of the last `period` bars minus `multiplier` × ATR.
+    pub long: Vec<f64>,
+    /// Lowest low of the last `period` bars plus `multiplier` × ATR.
+    pub short: Vec<f64>,
+}
+
+/// Compute Chande Kroll stop levels from the candles and an ATR series of the same length.
+pub fn chande_kroll_levels(candles: &[Candle], atr: &[f64], period: usize, multiplier: f64) -> ChandeKrollLevels {
+    let n = candles.len();
+    let mut long = vec![f64::NAN; n];
+    let mut short = vec![f64::NAN; n];
+    if period == 0 {
+        return ChandeKrollLevels { long, short };
+    }
+    for i in (period - 1)..n.min(atr.len()) {
+        if atr[i].is_nan() {
+            continue;
+        }
+        let window = &candles[i + 1 - period..=i];
+        let highest = window.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
+        let lowest = window.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+        long[i] = highest - multiplier * atr[i];
+        short[i] = lowest + multiplier * atr[i];
+    }
+    ChandeKrollLevels { long, short }
+}
+
+/// Move the stop loss to the Chande Kroll level of a completed bar when that tightens it.
+///
+/// A move counts as trailing (`trailing_stop_activated`), so the exit is reported as
+/// `CloseReason::TrailingStop`. NaN levels (warmup) are ignored.
+pub fn update_chande_kroll_stop(position: &mut OpenPosition, levels: &ChandeKrollLevels, bar: usize) {
+    let series = match position.direction {
+        TradeDirection::Long | TradeDirection::Both => &levels.long,
+        TradeDirection::Short => &levels.short,
+    };
+    let level = series.get(bar).copied().unwrap_or(f64::NAN);
+    if level.is_nan() {
+        return;
+    }
+    let moved = match (position.stop_loss, position.direction) {
+        (None, _) => true,
+        (Some(sl), TradeDirection::Short) => level < sl,
+        (Some(sl), _) => level > sl,
+    };
+    if moved {
+        position.stop_loss = Some(level);
+        position.trailing_stop_activated = true;
+    }
+}
+
 /// Check if SL or TP was hit on the current bar using bid/ask OHLC.
 ///
 /// Bid/Ask convention (MT5-matching):
@@ -469,6 +582,8 @@ fn check_take_profit(pos: &OpenPosition, ba: &BidAskOhlc) -> Option<(f64, CloseR
 /// Calculate daily swap charge for an open position.
 ///
 /// `multiplier` = 1.0 for normal days, 3.0 on `triple_swap_day` (covers weekend).
+/// `quote_to_account` converts quote-currency amounts (pip, point and percent modes)
+/// into account currency; `InMoney` rates are already in account currency.
 /// Returns a signed amount: negative = cost to the trader, positive = credit.
 pub fn calculate_swap_charge(
     direction: TradeDirection,
@@ -476,27 +591,21 @@ pub fn calculate_swap_charge(
     entry_price: f64,
     instrument: &InstrumentConfig,
     multiplier: f64,
+    quote_to_account: f64,
 ) -> f64 {
     let rate = match direction {
         TradeDirection::Long | TradeDirection::Both => instrument.swap_long,
         TradeDirection::Short => instrument.swap_short,
     };
     let daily_swap = match instrument.swap_mode {
-        SwapMode::InPips => rate * instrument.pip_value * lots,
-        SwapMode::InPoints => {
-            // 1 point = tick_size; convert to pip-equivalent value
-            if instrument.tick_size > 0.0 {
-                rate * (instrument.tick_size / instrument.pip_size) * instrument.pip_value * lots
-            } else {
-                rate * instrument.pip_value * lots
-            }
-        }
+        SwapMode::InPips => rate * instrument.pip_value * lots * quote_to_account,
+        SwapMode::InPoints => instrument.points_to_pips(rate) * instrument.pip_value * lots * quote_to_account,
         SwapMode::InMoney => rate * lots,
         SwapMode::AsPercent => {
             // Annual rate (%) divided by configured annual days (default 365; use 252 for equities)
             let pos_value = entry_price * lots * instrument.lot_size;
             let annual_days = instrument.swap_annual_days.max(1) as f64;
-            pos_value * rate / 100.0 / annual_days
+            pos_value * rate / 100.0 / annual_days * quote_to_account
         }
     };
     daily_swap * multiplier
@@ -549,7 +658,7 @@ pub fn enforce_stops_level_sl(
     if instrument.min_stop_distance_pips <= 0.0 {
         return sl_price;
     }
-    let min_dist = instrument.min_stop_distance_pips * instrument.pip_size;
+    let min_dist = instrument.pips_to_price(instrument.min_stop_distance_pips);
     match direction {
         TradeDirection::Long | TradeDirection::Both => sl_price.min(entry_price - min_dist),
         TradeDirection::Short => sl_price.max(entry_price + min_dist),
@@ -566,7 +675,7 @@ pub fn enforce_stops_level_tp(
     if instrument.min_stop_distance_pips <= 0.0 {
         return tp_price;
     }
-    let min_dist = instrument.min_stop_distance_pips * instrument.pip_size;
+    let min_dist = instrument.pips_to_price(instrument.min_stop_distance_pips);
     match direction {
         TradeDirection::Long | TradeDirection::Both => tp_price.max(entry_price + min_dist),
         TradeDirection::Short => tp_price.min(entry_price - min_dist),
@@ -587,14 +696,14 @@ pub fn update_mae_mfe_ba(
 ) {
     match position.direction {
         TradeDirection::Long | TradeDirection::Both => {
-            let adverse = (position.entry_price - ba.bid_low) / instrument.pip_size;
-            let favorable = (ba.bid_high - position.entry_price) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(position.entry_price - ba.bid_low);
+            let favorable = instrument.price_to_pips(ba.bid_high - position.entry_price);
             if adverse > position.mae_pips { position.mae_pips = adverse; }
             if favorable > position.mfe_pips { position.mfe_pips = favorable; }
         }
         TradeDirection::Short => {
-            let adverse = (ba.ask_high - position.entry_price) / instrument.pip_size;
-            let favorable = (position.entry_price - ba.ask_low) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(ba.ask_high - position.entry_price);
+            let favorable = instrument.price_to_pips(position.entry_price - ba.ask_low);
             if adverse > position.mae_pips { position.mae_pips = adverse; }
             if favorable > position.mfe_pips { position.mfe_pips = favorable; }
         }
@@ -696,7 +805,7 @@ pub fn update_trailing_stop_tick(pos: &mut OpenPosition, bid: f64, ask: f64) {
                 // Track highest bid (selling price)
                 if bid > pos.highest_since_entry {
                     pos.highest_since_entry = bid;
-                    let new_sl = bid - distance;
+                    let new_sl = trailing_stop_level(pos, bid, distance);
                     if let Some(ref mut sl) = pos.stop_loss {
                         if new_sl > *sl {
                             *sl = new_sl;
@@ -712,7 +821,7 @@ pub fn update_trailing_stop_tick(pos: &mut OpenPosition, bid: f64, ask: f64) {
                 // Track lowest ask (buying price)
                 if ask < pos.lowest_since_entry {
                     pos.lowest_since_entry = ask;
-                    let new_sl = ask + distance;
+                    let new_sl = trailing_stop_level(pos, ask, distance);
                     if let Some(ref mut sl) = pos.stop_loss {
                         if new_sl < *sl {
                             *sl = new_sl;
@@ -738,8 +847,8 @@ pub fn update_mae_mfe_tick(
 ) {
     match pos.direction {
         TradeDirection::Long | TradeDirection::Both => {
-            let adverse = (pos.entry_price - bid) / instrument.pip_size;
-            let favorable = (bid - pos.entry_price) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(pos.entry_price - bid);
+            let favorable = instrument.price_to_pips(bid - pos.entry_price);
             if adverse > pos.mae_pips {
                 pos.mae_pips = adverse;
             }
@@ -748,8 +857,8 @@ pub fn update_mae_mfe_tick(
             }
         }
         TradeDirection::Short => {
-            let adverse = (ask - pos.entry_price) / instrument.pip_size;
-            let favorable = (pos.entry_price - ask) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(ask - pos.entry_price);
+            let favorable = instrument.price_to_pips(pos.entry_price - ask);
             if adverse > pos.mae_pips {
                 pos.mae_pips = adverse;
             }
@@ -772,8 +881,8 @@ pub fn update_mae_mfe(
 ) {
     match position.direction {
         TradeDirection::Long | TradeDirection::Both => {
-            let adverse = (position.entry_price - candle.low) / instrument.pip_size;
-            let favorable = (candle.high - position.entry_price) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(position.entry_price - candle.low);
+            let favorable = instrument.price_to_pips(candle.high - position.entry_price);
             if adverse > position.mae_pips {
                 position.mae_pips = adverse;
             }
@@ -782,8 +891,8 @@ pub fn update_mae_mfe(
             }
         }
         TradeDirection::Short => {
-            let adverse = (candle.high - position.entry_price) / instrument.pip_size;
-            let favorable = (position.entry_price - candle.low) / instrument.pip_size;
+            let adverse = instrument.price_to_pips(candle.high - position.entry_price);
+            let favorable = instrument.price_to_pips(position.entry_price - candle.low);
             if adverse > position.mae_pips {
                 position.mae_pips = adverse;
             }
@@ -793,3 +902,73 @@ pub fn update_mae_mfe(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizing(sizing_type: PositionSizingType, value: f64) -> PositionSizing {
+        PositionSizing { sizing_type, value, decrease_factor: 1.0 }
+    }
+
+    #[test]
+    fn test_lots_snap_down_to_lot_step() {
+        let instrument = InstrumentConfig::default(); // min 0.01, step 0.01, pip value 10
+        // Risk 1% of 10 000 over a 137-pip stop: 100 / 1370 = 0.0729… → 0.07
+        let lots = calculate_lots(
+            &sizing(PositionSizingType::RiskBased, 1.0),
+            10_000.0,
+            1.1000,
+            Some(1.1000 - 0.0137),
+            &instrument,
+            0,
+            1.0,
+        );
+        assert_eq!(lots, 0.07);
+
+        let coarse = InstrumentConfig { min_lot: 0.1, lot_step: 0.1, ..Default::default() };
+        assert_eq!(coarse.normalize_lots(0.73), 0.7);
+        // Exact multiples survive floating-point division
+        assert_eq!(coarse.normalize_lots(0.1 + 0.2), 0.3);
+        // Step finer than the minimum
+        let fine = InstrumentConfig { min_lot: 0.1, lot_step: 0.01, ..Default::default() };
+        assert_eq!(fine.normalize_lots(0.256), 0.25);
+    }
+
+    #[test]
+    fn test_lots_respect_min_and_max() {
+        let instrument = InstrumentConfig { min_lot: 0.1, lot_step: 0.1, max_lot: 5.0, ..Default::default() };
+        assert_eq!(instrument.normalize_lots(0.04), 0.1);
+        assert_eq!(instrument.normalize_lots(12.34), 5.0);
+        assert_eq!(
+            calculate_lots(&sizing(PositionSizingType::FixedLots, 50.0), 10_000.0, 1.1, None, &instrument, 0, 1.0),
+            5.0
+        );
+
+        // max_lot = 0 leaves the size uncapped; lot_step = 0 steps by min_lot
+        let legacy = InstrumentConfig { lot_step: 0.0, max_lot: 0.0, ..Default::default() };
+        assert_eq!(legacy.normalize_lots(123.456), 123.45);
+    }
+
+    #[test]
+    fn test_quote_conversion_scales_lots_and_swap() {
+        let instrument = InstrumentConfig { swap_long: -2.0, ..Default::default() };
+        let risk = sizing(PositionSizingType::RiskBased, 1.0);
+        // Quote currency worth 2 account units doubles the pip value: 100 / (100 pips × 20) = 0.05
+        let native = calculate_lots(&risk, 10_000.0, 1.1, Some(1.09), &instrument, 0, 1.0);
+        let converted = calculate_lots(&risk, 10_000.0, 1.1, Some(1.09), &instrument, 0, 2.0);
+        assert_eq!(native, 0.1);
+        assert_eq!(converted, 0.05);
+
+        let swap = calculate_swap_charge(TradeDirection::Long, 1.0, 1.1, &instrument, 1.0, 1.0);
+        let swap_converted = calculate_swap_charge(TradeDirection::Long, 1.0, 1.1, &instrument, 1.0, 2.0);
+        assert!((swap - -20.0).abs() < 1e-9);
+        assert!((swap_converted - -40.0).abs() < 1e-9);
+
+        let in_money = InstrumentConfig { swap_mode: SwapMode::InMoney, ..instrument };
+        assert_eq!(
+            calculate_swap_charge(TradeDirection::Long, 1.0, 1.1, &in_money, 1.0, 2.0),
+            calculate_swap_charge(TradeDirection::Long, 1.0, 1.1, &in_money, 1.0, 1.0)
+        );
+    }
+}
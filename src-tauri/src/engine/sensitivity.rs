@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use crate::models::result::{
+    ObjectiveGrid, OptimizationResult, ParameterMarginal, ParameterRange, ParameterSensitivity,
+};
+
+/// Compute marginal objective curves for every parameter and an objective grid for
+/// every parameter pair.
+///
+/// `results` should be the full set of evaluated combinations (see
+/// [`super::optimizer::run_grid_search_all`]); with only the top N the marginals are
+/// biased toward the best region. Results with a non-finite score are ignored.
+pub fn compute_sensitivity(
+    results: &[OptimizationResult],
+    ranges: &[ParameterRange],
+) -> ParameterSensitivity {
+    let marginals = ranges
+        .iter()
+        .map(|r| parameter_marginal(results, &r.display_name))
+        .collect();
+
+    let mut grids = Vec::new();
+    for (i, x) in ranges.iter().enumerate() {
+        for y in &ranges[i + 1..] {
+            grids.push(objective_grid(results, &x.display_name, &y.display_name));
+        }
+    }
+
+    ParameterSensitivity { marginals, grids }
+}
+
+/// Mean and best objective for each distinct value of `param`.
+pub fn parameter_marginal(results: &[OptimizationResult], param: &str) -> ParameterMarginal {
+    let values = distinct_values(results, param);
+    let mut sums = vec![0.0; values.len()];
+    let mut counts = vec![0usize; values.len()];
+    let mut best = vec![f64::NEG_INFINITY; values.len()];
+
+    for (v, score) in scored(results, param) {
+        let i = value_index(&values, v);
+        sums[i] += score;
+        counts[i] += 1;
+        best[i] = best[i].max(score);
+    }
+
+    let mean_objective: Vec<f64> = sums
+        .iter()
+        .zip(&counts)
+        .map(|(&s, &c)| s / c as f64)
+        .collect();
+    let (lo, hi) = mean_objective
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &m| (lo.min(m), hi.max(m)));
+
+    ParameterMarginal {
+        param: param.to_string(),
+        values,
+        mean_objective,
+        best_objective: best,
+        importance: if hi >= lo { hi - lo } else { 0.0 },
+    }
+}
+
+/// Mean objective over every `(param_x, param_y)` value pair, marginalized over the
+/// remaining parameters.
+pub fn objective_grid(results: &[OptimizationResult], param_x: &str, param_y: &str) -> ObjectiveGrid {
+    let x_values = distinct_values(results, param_x);
+    let y_values = distinct_values(results, param_y);
+    let mut acc: HashMap<(usize, usize), (f64, usize)> = HashMap::new();
+
+    for r in results.iter().filter(|r| r.composite_score.is_finite()) {
+        let (Some(&x), Some(&y)) = (r.params.get(param_x), r.params.get(param_y)) else {
+            continue;
+        };
+        let cell = acc
+            .entry((value_index(&y_values, y), value_index(&x_values, x)))
+            .or_insert((0.0, 0));
+        cell.0 += r.composite_score;
+        cell.1 += 1;
+    }
+
+    let cells = (0..y_values.len())
+        .map(|row| {
+            (0..x_values.len())
+                .map(|col| acc.get(&(row, col)).map(|&(sum, n)| sum / n as f64))
+                .collect()
+        })
+        .collect();
+
+    ObjectiveGrid {
+        param_x: param_x.to_string(),
+        param_y: param_y.to_string(),
+        x_values,
+        y_values,
+        cells,
+    }
+}
+
+/// `(param value, score)` for every result that has `param` and a finite score.
+fn scored<'a>(
+    results: &'a [OptimizationResult],
+    param: &'a str,
+) -> impl Iterator<Item = (f64, f64)> + 'a {
+    results
+        .iter()
+        .filter(|r| r.composite_score.is_finite())
+        .filter_map(move |r| r.params.get(param).map(|&v| (v, r.composite_score)))
+}
+
+/// Distinct values of `param` across scored results, ascending.
+fn distinct_values(results: &[OptimizationResult], param: &str) -> Vec<f64> {
+    let mut values: Vec<f64> = scored(results, param).map(|(v, _)| v).collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.dedup();
+    values
+}
+
+/// Position of `v` in a sorted list produced by [`distinct_values`].
+fn value_index(values: &[f64], v: f64) -> usize {
+    values.binary_search_by(|p| p.total_cmp(&v)).unwrap_or_else(|i| i.min(values.len() - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(name: &str) -> ParameterRange {
+        ParameterRange {
+            rule_index: 0,
+            param_name: "period".into(),
+            display_name: name.into(),
+            min: 0.0,
+            max: 0.0,
+            step: 1.0,
+            operand_side: "left".into(),
+            param_source: "long_entry".into(),
+        }
+    }
+
+    fn result(params: &[(&str, f64)], score: f64) -> OptimizationResult {
+        OptimizationResult {
+            params: params.iter().map(|&(k, v)| (k.to_string(), v)).collect(),
+            objective_value: score,
+            composite_score: score,
+            total_return_pct: 0.0,
+            sharpe_ratio: 0.0,
+            max_drawdown_pct: 0.0,
+            total_trades: 0,
+            profit_factor: 0.0,
+            return_dd_ratio: 0.0,
+            win_rate_pct: 0.0,
+            stagnation_bars: 0,
+            ulcer_index_pct: 0.0,
+            net_profit: 0.0,
+            sortino_ratio: 0.0,
+            calmar_ratio: 0.0,
+            expectancy: 0.0,
+            recovery_factor: 0.0,
+            oos_results: Vec::new(),
+            equity_curve: Vec::new(),
+            failure_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_dominant_and_irrelevant_parameters() {
+        // Objective peaks at period = 20 and ignores `noise` entirely
+        let mut results = Vec::new();
+        for period in [10.0, 15.0, 20.0, 25.0, 30.0] {
+            for noise in [1.0, 2.0, 3.0, 4.0] {
+                let score = 100.0 - (period - 20.0_f64).powi(2);
+                results.push(result(&[("period", period), ("noise", noise)], score));
+            }
+        }
+        results.push(result(&[("period", 20.0), ("noise", 1.0)], f64::NEG_INFINITY));
+
+        let s = compute_sensitivity(&results, &[range("period"), range("noise")]);
+
+        let period = &s.marginals[0];
+        assert_eq!(period.values, vec![10.0, 15.0, 20.0, 25.0, 30.0]);
+        assert_eq!(period.mean_objective, vec![0.0, 75.0, 100.0, 75.0, 0.0]);
+        assert_eq!(period.best_objective[2], 100.0);
+        assert_eq!(period.importance, 100.0);
+
+        let noise = &s.marginals[1];
+        assert_eq!(noise.values.len(), 4);
+        assert!(noise.mean_objective.iter().all(|&m| (m - 50.0).abs() < 1e-12));
+        assert!(noise.importance < 1e-12);
+
+        assert_eq!(s.grids.len(), 1);
+        let grid = &s.grids[0];
+        assert_eq!((grid.param_x.as_str(), grid.param_y.as_str()), ("period", "noise"));
+        assert_eq!(grid.cells.len(), 4);
+        for row in &grid.cells {
+            assert_eq!(row, &vec![Some(0.0), Some(75.0), Some(100.0), Some(75.0), Some(0.0)]);
+        }
+    }
+
+    #[test]
+    fn test_grid_marks_unevaluated_cells() {
+        let results = vec![
+            result(&[("a", 1.0), ("b", 1.0)], 1.0),
+            result(&[("a", 2.0), ("b", 2.0)], 3.0),
+            result(&[("a", 2.0), ("b", 2.0)], 5.0),
+        ];
+        let grid = objective_grid(&results, "a", "b");
+        assert_eq!(grid.cells, vec![vec![Some(1.0), None], vec![None, Some(4.0)]]);
+    }
+}
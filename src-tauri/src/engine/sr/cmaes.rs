@@ -212,7 +212,7 @@ pub fn optimize_constants(
 /// Uses rejection sampling for u1 to avoid `.max(1e-10)` which biases the uniform
 /// distribution and compresses the tails of the resulting normal distribution.
 #[inline]
-fn standard_normal(rng: &mut impl rand::Rng) -> f64 {
+pub(crate) fn standard_normal(rng: &mut impl rand::Rng) -> f64 {
     let u1: f64 = loop {
         let v = rng.gen::<f64>();
         if v > 1e-10 { break v; }
@@ -15,10 +15,10 @@ use crate::engine::indicators::{
     compute_indicator_with_slices, CandleSlices, IndicatorOutput,
 };
 use crate::engine::metrics::calculate_metrics;
-use crate::engine::orders::BidAskOhlc;
+use crate::engine::orders::{self, price_distance_value, BidAskOhlc};
 use crate::engine::position::{
     calculate_lots, calculate_stop_loss, calculate_take_profit, calculate_trailing_stop_distance,
-    check_sl_tp_hit, update_trailing_stop, OpenPosition,
+    check_sl_tp_hit, trailing_step_price, update_trailing_stop, OpenPosition,
 };
 use crate::errors::AppError;
 use crate::models::candle::Candle;
@@ -29,7 +29,7 @@ use crate::models::sr_result::{
 };
 use crate::models::strategy::{
     BacktestConfig, CloseTradesAt, IndicatorConfig, IndicatorParams, IndicatorType, TradeDirection,
-    TradingHours,
+    TradingCosts, TradingHours,
 };
 use crate::models::trade::{CloseReason, TradeResult};
 
@@ -74,11 +74,13 @@ fn expand_pool(pool: Vec<PoolLeaf>) -> Vec<PoolLeaf> {
 
 /// Build the shared indicator cache from the SR pool.
 /// One entry per unique (indicator_type, params) combination, regardless of buffer_index.
+/// Daily indicators roll over at `session_start_hour` (see `InstrumentConfig::session_start_hour`).
 pub fn build_sr_cache_pub(
     pool: &[PoolLeaf],
     candles: &[Candle],
+    session_start_hour: u8,
 ) -> Result<SrCache, AppError> {
-    build_sr_cache(pool, candles)
+    build_sr_cache(pool, candles, session_start_hour)
 }
 
 /// Pre-compute ATR series for SL/TP (public for external callers).
@@ -90,8 +92,9 @@ pub fn build_atr_series_pub(candles: &[Candle]) -> Vec<f64> {
 fn build_sr_cache(
     pool: &[PoolLeaf],
     candles: &[Candle],
+    session_start_hour: u8,
 ) -> Result<SrCache, AppError> {
-    let slices = CandleSlices::from_candles(candles);
+    let slices = CandleSlices::from_candles(candles).with_session_start_hour(session_start_hour);
     let mut map: HashMap<u64, Arc<IndicatorOutput>> = HashMap::new();
 
     for leaf in pool {
@@ -190,13 +193,7 @@ fn compute_swap(
     let days_held = duration_bars as f64 * minutes / (24.0 * 60.0);
     match instrument.swap_mode {
         SwapMode::InPips => rate * instrument.pip_value * lots * days_held,
-        // InPoints: 1 point ≈ 1 tick = tick_size; monetary value = tick_size/pip_size * pip_value
-        SwapMode::InPoints => {
-            let point_value = if instrument.pip_size > 0.0 {
-                instrument.tick_size / instrument.pip_size * instrument.pip_value
-            } else { 0.0 };
-            rate * point_value * lots * days_held
-        }
+        SwapMode::InPoints => instrument.points_to_pips(rate) * instrument.pip_value * lots * days_held,
         SwapMode::InMoney => rate * lots * days_held,
         SwapMode::AsPercent => {
             let position_value = entry_price * instrument.lot_size * lots;
@@ -207,6 +204,25 @@ fn compute_swap(
 
 // ── Lightweight SR Backtest ───────────────────────────────────────────────────
 
+/// Spread and slippage cost of one SR trade, converted with `quote_to_account` like the
+/// executor's trade costs. Spread is crossed once per round trip (ask entry, bid exit)
+/// and slippage only hits the entry fill. The metric-only runners have no conversion
+/// config and keep P&L and costs in quote currency (rate 1.0).
+fn trade_costs(costs: &TradingCosts, lots: f64, instrument: &InstrumentConfig, quote_to_account: f64) -> (f64, f64) {
+    (
+        price_distance_value(instrument.pips_to_price(costs.spread_pips), lots, instrument, quote_to_account),
+        price_distance_value(instrument.pips_to_price(costs.slippage_pips), lots, instrument, quote_to_account),
+    )
+}
+
+/// `orders::quote_to_account_rate` for an SR trade closing at `datetime`, logging why
+/// the run is abandoned when no rate exists.
+fn quote_to_account_rate(config: &BacktestConfig, datetime: &str) -> Option<f64> {
+    orders::quote_to_account_rate(config, datetime)
+        .map_err(|e| tracing::warn!("SR backtest: {e}"))
+        .ok()
+}
+
 /// Evaluate an SR strategy on historical candles.
 ///
 /// When `sub_bars` contains M1 candles or tick data, SL/TP resolution uses sub-bar
@@ -233,12 +249,14 @@ pub fn sr_backtest(
     let mut equity_curve: Vec<EquityPoint> = Vec::with_capacity(n);
     let mut open: Option<OpenPosition> = None;
     let mut prev_exit_signal: f64 = 0.0;
-    let spread_price = strategy.trading_costs.spread_pips * instrument.pip_size;
-    let slippage_price = strategy.trading_costs.slippage_pips * instrument.pip_size;
+    let spread_price = instrument.pips_to_price(strategy.trading_costs.spread_pips);
+    let slippage_price = instrument.pips_to_price(strategy.trading_costs.slippage_pips);
     // Trailing stop activation: convert pips to price distance once
     let ts_activation_dist = strategy.trailing_stop.as_ref()
         .and_then(|ts| ts.activation_pips)
-        .map(|pips| pips * instrument.pip_size);
+        .map(|pips| instrument.pips_to_price(pips));
+    let ts_step = strategy.trailing_stop.as_ref()
+        .and_then(|ts| trailing_step_price(ts, instrument));
 
     // Precompute commission helper
     let commission_per_lot = |lots: f64| -> f64 {
@@ -270,7 +288,7 @@ pub fn sr_backtest(
         let next_ts = if i + 1 < n { candles[i + 1].timestamp } else { i64::MAX };
         let (sub_start, sub_end) = find_subbar_range(
             sub_bars, &mut sub_cursor, &candle.datetime, next_dt,
-            candle.timestamp, next_ts,
+            candle.timestamp, next_ts, instrument.subbar_boundary,
         );
         // Evaluate signals using the PREVIOUS bar's completed data (i-1),
         // then act at the OPEN of the current bar (i). This matches MT5's
@@ -286,8 +304,8 @@ pub fn sr_backtest(
             // Check SL/TP using sub-bar data when available, otherwise bar-level OHLC.
             match sub_bars {
                 SubBarData::Candles(ref subs) if sub_start < sub_end => {
-                    if let Some((fill, time, reason)) = process_subbars_candle(
-                        pos, subs, sub_start, sub_end, instrument, spread_price,
+                    if let Ok(Some((fill, time, reason))) = process_subbars_candle(
+                        pos, subs, sub_start, sub_end, instrument, spread_price, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
@@ -295,8 +313,8 @@ pub fn sr_backtest(
                     }
                 }
                 SubBarData::Ticks(ref ticks) if sub_start < sub_end => {
-                    if let Some((fill, time, reason)) = process_subbars_tick_columnar(
-                        pos, ticks, sub_start, sub_end, instrument,
+                    if let Ok(Some((fill, time, reason))) = process_subbars_tick_columnar(
+                        pos, ticks, sub_start, sub_end, instrument, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
@@ -360,16 +378,17 @@ pub fn sr_backtest(
                 let pos = open.take().unwrap();
                 let pnl_pips = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => {
-                        (exit_price - pos.entry_price) / instrument.pip_size
+                        instrument.price_to_pips(exit_price - pos.entry_price)
                     }
                     TradeDirection::Short => {
-                        (pos.entry_price - exit_price) / instrument.pip_size
+                        instrument.price_to_pips(pos.entry_price - exit_price)
                     }
                 };
                 let commission = commission_per_lot(pos.lots);
                 let duration_bars = i.saturating_sub(pos.entry_bar);
                 let swap = compute_swap(pos.direction, pos.lots, duration_bars, timeframe, pos.entry_price, instrument);
                 let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+                let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, 1.0);
                 equity += pnl;
                 if pnl >= 0.0 { consecutive_losses = 0; } else { consecutive_losses += 1; }
                 last_exit_bar = Some(i);
@@ -386,11 +405,17 @@ pub fn sr_backtest(
                     pnl_pips,
                     commission,
                     swap,
+                    spread_cost,
+                    slippage_cost,
                     close_reason,
                     duration_bars,
                     duration_time: format!("{}b", duration_bars),
                     mae: pos.mae_pips,
                     mfe: pos.mfe_pips,
+                    initial_risk_pips: pos.initial_risk_pips(instrument),
+                    entry_reason: None,
+                    exit_reason: None,
+                    regime: None,
                 });
             }
         }
@@ -401,12 +426,12 @@ pub fn sr_backtest(
             if matches!(sub_bars, SubBarData::None) || sub_start >= sub_end {
                 let (excursion_positive, excursion_negative) = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => (
-                        (ba.bid_high - pos.entry_price) / instrument.pip_size,
-                        (pos.entry_price - ba.bid_low) / instrument.pip_size,
+                        instrument.price_to_pips(ba.bid_high - pos.entry_price),
+                        instrument.price_to_pips(pos.entry_price - ba.bid_low),
                     ),
                     TradeDirection::Short => (
-                        (pos.entry_price - ba.ask_low) / instrument.pip_size,
-                        (ba.ask_high - pos.entry_price) / instrument.pip_size,
+                        instrument.price_to_pips(pos.entry_price - ba.ask_low),
+                        instrument.price_to_pips(ba.ask_high - pos.entry_price),
                     ),
                 };
                 pos.mfe_pips = pos.mfe_pips.max(excursion_positive);
@@ -477,6 +502,7 @@ pub fn sr_backtest(
                             sl_price,
                             instrument,
                             consecutive_losses,
+                            1.0,
                         );
 
                         open = Some(OpenPosition {
@@ -486,6 +512,7 @@ pub fn sr_backtest(
                             entry_time: candle.datetime.clone(),
                             lots,
                             stop_loss: sl_price,
+                            initial_stop_loss: sl_price,
                             take_profit: tp_price,
                             trailing_stop_distance: ts_distance,
                             highest_since_entry: entry_price,
@@ -495,8 +522,12 @@ pub fn sr_backtest(
                             trailing_stop_activated: false,
                             last_swap_date: String::new(),
                             accumulated_swap: 0.0,
+                            entry_spread: 0.0,
+                            entry_slippage: 0.0,
                             sl_moved_to_be: false,
                             trailing_activation_dist: ts_activation_dist,
+                            trailing_step: ts_step,
+                        entry_reason: None,
                         });
                         trades_today += 1;
                     }
@@ -542,24 +573,26 @@ pub fn sr_backtest(
     }
 
     // Close any remaining open position at end of data.
-    // Apply spread consistently: longs close at bid (close - spread), shorts at ask (close + spread).
+    // Bars are bid-quoted: longs close at bid (close), shorts at ask (close + spread).
     if let Some(pos) = open.take() {
         let last = candles.last().unwrap();
         let exit_price = match pos.direction {
             TradeDirection::Short => last.close + spread_price,
-            _ => last.close - spread_price,  // Longs pay spread on exit too
+            _ => last.close,
         };
         let pnl_pips = match pos.direction {
             TradeDirection::Long | TradeDirection::Both => {
-                (exit_price - pos.entry_price) / instrument.pip_size
+                instrument.price_to_pips(exit_price - pos.entry_price)
             }
-            TradeDirection::Short => (pos.entry_price - exit_price) / instrument.pip_size,
+            TradeDirection::Short => instrument.price_to_pips(pos.entry_price - exit_price),
         };
         let commission = commission_per_lot(pos.lots);
         let dur = n.saturating_sub(pos.entry_bar);
         let swap = compute_swap(pos.direction, pos.lots, dur, timeframe, pos.entry_price, instrument);
         let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+        let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, 1.0);
         equity += pnl;
+        let initial_risk_pips = pos.initial_risk_pips(instrument);
         trades.push(TradeResult {
             id: trades.len().to_string(),
             direction: pos.direction,
@@ -572,11 +605,17 @@ pub fn sr_backtest(
             pnl_pips,
             commission,
             swap,
+            spread_cost,
+            slippage_cost,
             close_reason: CloseReason::EndOfData,
             duration_bars: dur,
             duration_time: format!("{}b", dur),
             mae: pos.mae_pips,
             mfe: pos.mfe_pips,
+            initial_risk_pips,
+            entry_reason: None,
+            exit_reason: None,
+            regime: None,
         });
         equity_curve.push(EquityPoint { timestamp: last.datetime.clone(), equity });
     }
@@ -614,11 +653,13 @@ pub fn sr_backtest_with_offset(
     let mut equity_curve: Vec<EquityPoint> = Vec::with_capacity(n);
     let mut open: Option<OpenPosition> = None;
     let mut prev_exit_signal: f64 = 0.0;
-    let spread_price = strategy.trading_costs.spread_pips * instrument.pip_size;
-    let slippage_price = strategy.trading_costs.slippage_pips * instrument.pip_size;
+    let spread_price = instrument.pips_to_price(strategy.trading_costs.spread_pips);
+    let slippage_price = instrument.pips_to_price(strategy.trading_costs.slippage_pips);
     let ts_activation_dist = strategy.trailing_stop.as_ref()
         .and_then(|ts| ts.activation_pips)
-        .map(|pips| pips * instrument.pip_size);
+        .map(|pips| instrument.pips_to_price(pips));
+    let ts_step = strategy.trailing_stop.as_ref()
+        .and_then(|ts| trailing_step_price(ts, instrument));
 
     let commission_per_lot = |lots: f64| -> f64 {
         use crate::models::strategy::CommissionType;
@@ -643,7 +684,7 @@ pub fn sr_backtest_with_offset(
         let next_ts = if i + 1 < n { candles[i + 1].timestamp } else { i64::MAX };
         let (sub_start, sub_end) = find_subbar_range(
             sub_bars, &mut sub_cursor, &candle.datetime, next_dt,
-            candle.timestamp, next_ts,
+            candle.timestamp, next_ts, instrument.subbar_boundary,
         );
         // Use global (offset) index for cache/ATR lookups so the correct pre-computed values
         // are used regardless of where this slice starts in the full candle array.
@@ -656,16 +697,16 @@ pub fn sr_backtest_with_offset(
 
             match sub_bars {
                 SubBarData::Candles(ref subs) if sub_start < sub_end => {
-                    if let Some((fill, _time, reason)) = process_subbars_candle(
-                        pos, subs, sub_start, sub_end, instrument, spread_price,
+                    if let Ok(Some((fill, _time, reason))) = process_subbars_candle(
+                        pos, subs, sub_start, sub_end, instrument, spread_price, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
                     }
                 }
                 SubBarData::Ticks(ref ticks) if sub_start < sub_end => {
-                    if let Some((fill, _time, reason)) = process_subbars_tick_columnar(
-                        pos, ticks, sub_start, sub_end, instrument,
+                    if let Ok(Some((fill, _time, reason))) = process_subbars_tick_columnar(
+                        pos, ticks, sub_start, sub_end, instrument, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
@@ -722,14 +763,15 @@ pub fn sr_backtest_with_offset(
                 let pos = open.take().unwrap();
                 let pnl_pips = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => {
-                        (exit_price - pos.entry_price) / instrument.pip_size
+                        instrument.price_to_pips(exit_price - pos.entry_price)
                     }
-                    TradeDirection::Short => (pos.entry_price - exit_price) / instrument.pip_size,
+                    TradeDirection::Short => instrument.price_to_pips(pos.entry_price - exit_price),
                 };
                 let commission = commission_per_lot(pos.lots);
                 let duration_bars = i.saturating_sub(pos.entry_bar);
                 let swap = compute_swap(pos.direction, pos.lots, duration_bars, timeframe, pos.entry_price, instrument);
                 let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+                let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, 1.0);
                 equity += pnl;
                 if pnl >= 0.0 { consecutive_losses = 0; } else { consecutive_losses += 1; }
                 last_exit_bar = Some(i);
@@ -742,9 +784,12 @@ pub fn sr_backtest_with_offset(
                     exit_price,
                     lots: pos.lots,
                     pnl, pnl_pips, commission, swap,
+                    spread_cost, slippage_cost,
                     close_reason, duration_bars,
                     duration_time: format!("{}b", duration_bars),
                     mae: pos.mae_pips, mfe: pos.mfe_pips,
+                    initial_risk_pips: pos.initial_risk_pips(instrument),
+                    entry_reason: None, exit_reason: None, regime: None,
                 });
             }
         }
@@ -753,12 +798,12 @@ pub fn sr_backtest_with_offset(
             if matches!(sub_bars, SubBarData::None) || sub_start >= sub_end {
                 let (excursion_positive, excursion_negative) = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => (
-                        (ba.bid_high - pos.entry_price) / instrument.pip_size,
-                        (pos.entry_price - ba.bid_low) / instrument.pip_size,
+                        instrument.price_to_pips(ba.bid_high - pos.entry_price),
+                        instrument.price_to_pips(pos.entry_price - ba.bid_low),
                     ),
                     TradeDirection::Short => (
-                        (pos.entry_price - ba.ask_low) / instrument.pip_size,
-                        (ba.ask_high - pos.entry_price) / instrument.pip_size,
+                        instrument.price_to_pips(pos.entry_price - ba.ask_low),
+                        instrument.price_to_pips(ba.ask_high - pos.entry_price),
                     ),
                 };
                 pos.mfe_pips = pos.mfe_pips.max(excursion_positive);
@@ -806,18 +851,20 @@ pub fn sr_backtest_with_offset(
                         let ts_distance = strategy.trailing_stop.as_ref().map(|ts| {
                             calculate_trailing_stop_distance(ts, entry_price, sl_price, atr_val, instrument)
                         });
-                        let lots = calculate_lots(&strategy.position_sizing, equity, entry_price, sl_price, instrument, consecutive_losses);
+                        let lots = calculate_lots(&strategy.position_sizing, equity, entry_price, sl_price, instrument, consecutive_losses, 1.0);
                         open = Some(OpenPosition {
                             direction, entry_price, entry_bar: i,
                             entry_time: candle.datetime.clone(), lots,
-                            stop_loss: sl_price, take_profit: tp_price,
+                            stop_loss: sl_price, initial_stop_loss: sl_price, take_profit: tp_price,
                             trailing_stop_distance: ts_distance,
                             highest_since_entry: entry_price,
                             lowest_since_entry: entry_price,
                             mae_pips: 0.0, mfe_pips: 0.0,
                             trailing_stop_activated: false,
-                            last_swap_date: String::new(), accumulated_swap: 0.0, sl_moved_to_be: false,
+                            last_swap_date: String::new(), accumulated_swap: 0.0, entry_spread: 0.0, entry_slippage: 0.0, sl_moved_to_be: false,
                             trailing_activation_dist: ts_activation_dist,
+                            trailing_step: ts_step,
+                        entry_reason: None,
                         });
                         trades_today += 1;
                     }
@@ -862,24 +909,28 @@ pub fn sr_backtest_with_offset(
         let last = candles.last().unwrap();
         let exit_price = match pos.direction {
             TradeDirection::Short => last.close + spread_price,
-            _ => last.close - spread_price,
+            _ => last.close,
         };
         let pnl_pips = match pos.direction {
-            TradeDirection::Long | TradeDirection::Both => (exit_price - pos.entry_price) / instrument.pip_size,
-            TradeDirection::Short => (pos.entry_price - exit_price) / instrument.pip_size,
+            TradeDirection::Long | TradeDirection::Both => instrument.price_to_pips(exit_price - pos.entry_price),
+            TradeDirection::Short => instrument.price_to_pips(pos.entry_price - exit_price),
         };
         let commission = commission_per_lot(pos.lots);
         let dur = n.saturating_sub(pos.entry_bar);
         let swap = compute_swap(pos.direction, pos.lots, dur, timeframe, pos.entry_price, instrument);
         let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+        let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, 1.0);
         equity += pnl;
+        let initial_risk_pips = pos.initial_risk_pips(instrument);
         trades.push(TradeResult {
             id: trades.len().to_string(),
             direction: pos.direction, entry_time: pos.entry_time,
             entry_price: pos.entry_price, exit_time: last.datetime.clone(),
             exit_price, lots: pos.lots, pnl, pnl_pips, commission, swap,
+            spread_cost, slippage_cost,
             close_reason: CloseReason::EndOfData, duration_bars: dur,
-            duration_time: format!("{}b", dur), mae: pos.mae_pips, mfe: pos.mfe_pips,
+            duration_time: format!("{}b", dur), mae: pos.mae_pips, mfe: pos.mfe_pips, initial_risk_pips,
+            entry_reason: None, exit_reason: None, regime: None,
         });
         equity_curve.push(EquityPoint { timestamp: last.datetime.clone(), equity });
     }
@@ -913,11 +964,13 @@ pub fn sr_backtest_full(
     let mut drawdown_curve: Vec<DrawdownPoint> = Vec::with_capacity(n);
     let mut open: Option<OpenPosition> = None;
     let mut prev_exit_signal: f64 = 0.0;
-    let spread_price = strategy.trading_costs.spread_pips * instrument.pip_size;
-    let slippage_price = strategy.trading_costs.slippage_pips * instrument.pip_size;
+    let spread_price = instrument.pips_to_price(strategy.trading_costs.spread_pips);
+    let slippage_price = instrument.pips_to_price(strategy.trading_costs.slippage_pips);
     let ts_activation_dist = strategy.trailing_stop.as_ref()
         .and_then(|ts| ts.activation_pips)
-        .map(|pips| pips * instrument.pip_size);
+        .map(|pips| instrument.pips_to_price(pips));
+    let ts_step = strategy.trailing_stop.as_ref()
+        .and_then(|ts| trailing_step_price(ts, instrument));
 
     let commission_per_lot = |lots: f64| -> f64 {
         use crate::models::strategy::CommissionType;
@@ -942,7 +995,7 @@ pub fn sr_backtest_full(
         let next_ts = if i + 1 < n { candles[i + 1].timestamp } else { i64::MAX };
         let (sub_start, sub_end) = find_subbar_range(
             sub_bars, &mut sub_cursor, &candle.datetime, next_dt,
-            candle.timestamp, next_ts,
+            candle.timestamp, next_ts, instrument.subbar_boundary,
         );
         // Use previous bar's (i-1) completed indicator values to decide actions
         // at bar i's open — matches MT5 CopyBuffer(shift=1) behaviour.
@@ -954,16 +1007,16 @@ pub fn sr_backtest_full(
 
             match sub_bars {
                 SubBarData::Candles(ref subs) if sub_start < sub_end => {
-                    if let Some((fill, _time, reason)) = process_subbars_candle(
-                        pos, subs, sub_start, sub_end, instrument, spread_price,
+                    if let Ok(Some((fill, _time, reason))) = process_subbars_candle(
+                        pos, subs, sub_start, sub_end, instrument, spread_price, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
                     }
                 }
                 SubBarData::Ticks(ref ticks) if sub_start < sub_end => {
-                    if let Some((fill, _time, reason)) = process_subbars_tick_columnar(
-                        pos, ticks, sub_start, sub_end, instrument,
+                    if let Ok(Some((fill, _time, reason))) = process_subbars_tick_columnar(
+                        pos, ticks, sub_start, sub_end, instrument, None,
                     ) {
                         closed_price = Some(fill);
                         close_reason = reason;
@@ -1021,16 +1074,18 @@ pub fn sr_backtest_full(
                 let pos = open.take().unwrap();
                 let pnl_pips = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => {
-                        (exit_price - pos.entry_price) / instrument.pip_size
+                        instrument.price_to_pips(exit_price - pos.entry_price)
                     }
                     TradeDirection::Short => {
-                        (pos.entry_price - exit_price) / instrument.pip_size
+                        instrument.price_to_pips(pos.entry_price - exit_price)
                     }
                 };
                 let commission = commission_per_lot(pos.lots);
                 let dur = i.saturating_sub(pos.entry_bar);
                 let swap = compute_swap(pos.direction, pos.lots, dur, timeframe, pos.entry_price, instrument);
-                let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+                let quote_to_account = quote_to_account_rate(&bt_config, &candle.datetime)?;
+                let pnl = pnl_pips * instrument.pip_value * pos.lots * quote_to_account - commission + swap;
+                let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, quote_to_account);
                 equity += pnl;
                 if pnl >= 0.0 { consecutive_losses = 0; } else { consecutive_losses += 1; }
                 last_exit_bar_full = Some(i);
@@ -1046,11 +1101,17 @@ pub fn sr_backtest_full(
                     pnl_pips,
                     commission,
                     swap,
+                    spread_cost,
+                    slippage_cost,
                     close_reason,
                     duration_bars: dur,
                     duration_time: format!("{}b", dur),
                     mae: pos.mae_pips,
                     mfe: pos.mfe_pips,
+                    initial_risk_pips: pos.initial_risk_pips(instrument),
+                    entry_reason: None,
+                    exit_reason: None,
+                    regime: None,
                 });
             }
         }
@@ -1059,12 +1120,12 @@ pub fn sr_backtest_full(
             if matches!(sub_bars, SubBarData::None) || sub_start >= sub_end {
                 let (excursion_positive, excursion_negative) = match pos.direction {
                     TradeDirection::Long | TradeDirection::Both => (
-                        (ba.bid_high - pos.entry_price) / instrument.pip_size,
-                        (pos.entry_price - ba.bid_low) / instrument.pip_size,
+                        instrument.price_to_pips(ba.bid_high - pos.entry_price),
+                        instrument.price_to_pips(pos.entry_price - ba.bid_low),
                     ),
                     TradeDirection::Short => (
-                        (pos.entry_price - ba.ask_low) / instrument.pip_size,
-                        (ba.ask_high - pos.entry_price) / instrument.pip_size,
+                        instrument.price_to_pips(pos.entry_price - ba.ask_low),
+                        instrument.price_to_pips(ba.ask_high - pos.entry_price),
                     ),
                 };
                 pos.mfe_pips = pos.mfe_pips.max(excursion_positive);
@@ -1114,7 +1175,7 @@ pub fn sr_backtest_full(
                     let ts_distance = strategy.trailing_stop.as_ref().map(|ts| {
                         calculate_trailing_stop_distance(ts, entry_price, sl_price, atr_val, instrument)
                     });
-                    let lots = calculate_lots(&strategy.position_sizing, equity, entry_price, sl_price, instrument, consecutive_losses);
+                    let lots = calculate_lots(&strategy.position_sizing, equity, entry_price, sl_price, instrument, consecutive_losses, 1.0);
                     open = Some(OpenPosition {
                         direction,
                         entry_price,
@@ -1122,6 +1183,7 @@ pub fn sr_backtest_full(
                         entry_time: candle.datetime.clone(),
                         lots,
                         stop_loss: sl_price,
+                        initial_stop_loss: sl_price,
                         take_profit: tp_price,
                         trailing_stop_distance: ts_distance,
                         highest_since_entry: entry_price,
@@ -1131,8 +1193,12 @@ pub fn sr_backtest_full(
                         trailing_stop_activated: false,
                         last_swap_date: String::new(),
                         accumulated_swap: 0.0,
+                        entry_spread: 0.0,
+                        entry_slippage: 0.0,
                         sl_moved_to_be: false,
                         trailing_activation_dist: ts_activation_dist,
+                        trailing_step: ts_step,
+                        entry_reason: None,
                     });
                     trades_today_full += 1;
                 }
@@ -1176,9 +1242,9 @@ pub fn sr_backtest_full(
             };
             let pnl_pips = match pos.direction {
                 TradeDirection::Long | TradeDirection::Both => {
-                    (current_price - pos.entry_price) / instrument.pip_size
+                    instrument.price_to_pips(current_price - pos.entry_price)
                 }
-                TradeDirection::Short => (pos.entry_price - current_price) / instrument.pip_size,
+                TradeDirection::Short => instrument.price_to_pips(pos.entry_price - current_price),
             };
             pnl_pips * instrument.pip_value * pos.lots
         } else {
@@ -1194,20 +1260,23 @@ pub fn sr_backtest_full(
 
     if let Some(pos) = open.take() {
         let last = candles.last().unwrap();
-        // Apply spread consistently: longs close at bid (close - spread), shorts at ask (close + spread).
+        // Bars are bid-quoted: longs close at bid (close), shorts at ask (close + spread).
         let exit_price = match pos.direction {
             TradeDirection::Short => last.close + spread_price,
-            _ => last.close - spread_price,
+            _ => last.close,
         };
         let pnl_pips = match pos.direction {
-            TradeDirection::Long | TradeDirection::Both => (exit_price - pos.entry_price) / instrument.pip_size,
-            TradeDirection::Short => (pos.entry_price - exit_price) / instrument.pip_size,
+            TradeDirection::Long | TradeDirection::Both => instrument.price_to_pips(exit_price - pos.entry_price),
+            TradeDirection::Short => instrument.price_to_pips(pos.entry_price - exit_price),
         };
         let commission = commission_per_lot(pos.lots);
         let dur = n.saturating_sub(pos.entry_bar);
         let swap = compute_swap(pos.direction, pos.lots, dur, timeframe, pos.entry_price, instrument);
-        let pnl = pnl_pips * instrument.pip_value * pos.lots - commission + swap;
+        let quote_to_account = quote_to_account_rate(&bt_config, &last.datetime)?;
+        let pnl = pnl_pips * instrument.pip_value * pos.lots * quote_to_account - commission + swap;
+        let (spread_cost, slippage_cost) = trade_costs(&strategy.trading_costs, pos.lots, instrument, quote_to_account);
         equity += pnl;
+        let initial_risk_pips = pos.initial_risk_pips(instrument);
         trades.push(TradeResult {
             id: trades.len().to_string(),
             direction: pos.direction,
@@ -1220,11 +1289,17 @@ pub fn sr_backtest_full(
             pnl_pips,
             commission,
             swap,
+            spread_cost,
+            slippage_cost,
             close_reason: CloseReason::EndOfData,
             duration_bars: dur,
             duration_time: format!("{}b", dur),
             mae: pos.mae_pips,
             mfe: pos.mfe_pips,
+            initial_risk_pips,
+            entry_reason: None,
+            exit_reason: None,
+            regime: None,
         });
         if peak_equity < equity { peak_equity = equity; }
         let dd_pct = if peak_equity > 0.0 { (peak_equity - equity) / peak_equity * 100.0 } else { 0.0 };
@@ -1239,7 +1314,7 @@ pub fn sr_backtest_full(
     let metrics = calculate_metrics(&trades, &equity_curve, initial_capital, timeframe);
     let returns: Vec<f64> = trades.iter().map(|t| t.pnl).collect();
 
-    Some(BacktestResults { trades, equity_curve, drawdown_curve, returns, metrics, backtest_config: bt_config, long_metrics: None, short_metrics: None, warnings: vec![] })
+    Some(BacktestResults { trades, equity_curve, drawdown_curve, returns, metrics, backtest_config: bt_config, long_metrics: None, short_metrics: None, warnings: vec![], benchmark_curve: vec![], stopped_out: false, rolling_ratios: vec![] })
 }
 
 /// Evaluate entry signals for the current bar.
@@ -1389,7 +1464,7 @@ pub fn run_sr_builder(
 
     // ── Pre-compute indicators (always over full candle range) ───────────────
     // The cache covers all bars so OOS evaluation can reuse it with an index offset.
-    let cache = build_sr_cache(&expanded_pool, &candles)?;
+    let cache = build_sr_cache(&expanded_pool, &candles, instrument.session_start_hour)?;
     let atr_series = build_atr_series(&candles, config.atr_period);
     let atr_arc = Arc::new(atr_series);
 
@@ -2039,3 +2114,47 @@ fn build_front_item(
         oos_metrics,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sr_cache_rolls_daily_indicators_at_session_start_hour() {
+        let bar = |dt: &str, high: f64, low: f64, close: f64| Candle {
+            datetime: dt.to_string(), open: close, high, low, close, ..Default::default()
+        };
+        let candles = vec![
+            bar("2024-01-02 17:00", 108.0, 100.0, 102.0),
+            bar("2024-01-03 02:00", 110.0, 103.0, 105.0),
+            bar("2024-01-03 16:00", 109.0, 104.0, 105.0),
+            bar("2024-01-03 17:00", 106.0, 104.0, 105.5),
+        ];
+        let leaf = PoolLeaf {
+            config: IndicatorConfig {
+                indicator_type: IndicatorType::Pivots,
+                params: IndicatorParams::default(),
+                output_field: None,
+                cached_hash: 0,
+            },
+            buffer_index: 0,
+            period_min: None,
+            period_max: None,
+            period_step: None,
+        };
+        let key = leaf.config.cache_key_hash();
+        let pivots = |session_start_hour: u8| {
+            let cache = build_sr_cache(std::slice::from_ref(&leaf), &candles, session_start_hour).unwrap();
+            cache[&key].primary.clone()
+        };
+
+        // Session days: the 02:00 and 16:00 bars still belong to the session opened at 17:00
+        let session = pivots(17);
+        assert!(session[2].is_nan());
+        assert!((session[3] - (110.0 + 100.0 + 105.0) / 3.0).abs() < 1e-12);
+
+        // Calendar days roll at midnight
+        let calendar = pivots(0);
+        assert!((calendar[1] - (108.0 + 100.0 + 102.0) / 3.0).abs() < 1e-12);
+    }
+}
@@ -5,8 +5,8 @@ use dashmap::DashMap;
 use crate::errors::AppError;
 use crate::models::candle::Candle;
 use crate::models::strategy::{
-    ArithmeticOp, CandlePatternType, Comparator, IndicatorConfig, LogicalOperator, Operand,
-    OperandType, PriceField, Rule, RuleGroup, Strategy, TimeField,
+    ArithmeticOp, CandlePatternType, Comparator, IndicatorConfig, IndicatorType, LogicalOperator, Operand,
+    OperandTransform, OperandType, PriceField, Rule, RuleGroup, Strategy, TimeField,
 };
 
 use super::indicators::{CandleSlices, compute_indicator_with_slices, IndicatorOutput};
@@ -31,7 +31,8 @@ pub struct DailyOhlcCache {
 }
 
 /// Pre-compute daily OHLC boundaries from candle data.
-pub fn compute_daily_ohlc(candles: &[Candle]) -> DailyOhlcCache {
+/// Days roll over at `session_start_hour` (see [`Candle::session_day`]).
+pub fn compute_daily_ohlc(candles: &[Candle], session_start_hour: u8) -> DailyOhlcCache {
     let len = candles.len();
     let mut daily_open = vec![f64::NAN; len];
     let mut daily_high = vec![f64::NAN; len];
@@ -45,13 +46,9 @@ pub fn compute_daily_ohlc(candles: &[Candle]) -> DailyOhlcCache {
     let mut prev_day_close = f64::NAN;
 
     for i in 0..len {
-        let current_date = if candles[i].datetime.len() >= 10 {
-            &candles[i].datetime[..10]
-        } else {
-            &candles[i].datetime
-        };
+        let current_date = candles[i].session_day(session_start_hour);
 
-        if current_date != prev_date {
+        if *current_date != *prev_date {
             // New day — save previous day's close
             if i > 0 {
                 prev_day_close = candles[i - 1].close;
@@ -313,12 +310,13 @@ pub fn strategy_uses_candle_patterns(strategy: &Strategy) -> bool {
 pub fn pre_compute_indicators(
     strategy: &Strategy,
     candles: &[Candle],
+    session_start_hour: u8,
 ) -> Result<IndicatorCache, AppError> {
     let cache = IndicatorCache::new();
     let mut seen = std::collections::HashSet::new();
 
     // Extract OHLCV once for all indicator computations.
-    let slices = CandleSlices::from_candles(candles);
+    let slices = CandleSlices::from_candles(candles).with_session_start_hour(session_start_hour);
 
     // Collect all indicator configs from flat rules and group rules
     let group_rules = strategy.long_entry_groups.iter()
@@ -351,6 +349,7 @@ pub fn pre_compute_indicators(
 pub fn pre_compute_indicators_with_shared_cache(
     strategy: &Strategy,
     candles: &[Candle],
+    session_start_hour: u8,
     shared: &Arc<IndicatorCache>,
 ) -> Result<IndicatorCache, AppError> {
     let local_cache = IndicatorCache::new();
@@ -376,7 +375,7 @@ pub fn pre_compute_indicators_with_shared_cache(
 
     for rule in all_rules_vec {
         for operand in [&rule.left_operand, &rule.right_operand] {
-            pre_compute_operand_shared(operand, &mut seen, shared, &local_cache, &mut slices, candles)?;
+            pre_compute_operand_shared(operand, &mut seen, shared, &local_cache, &mut slices, candles, session_start_hour)?;
         }
     }
 
@@ -393,6 +392,7 @@ fn pre_compute_operand_shared(
     local_cache: &IndicatorCache,
     slices: &mut Option<CandleSlices>,
     candles: &[Candle],
+    session_start_hour: u8,
 ) -> Result<(), AppError> {
     match operand.operand_type {
         OperandType::Indicator => {
@@ -406,7 +406,9 @@ fn pre_compute_operand_shared(
                         cached
                     } else {
                         // Cache miss: allocate CandleSlices lazily (only on first miss)
-                        let s = slices.get_or_insert_with(|| CandleSlices::from_candles(candles));
+                        let s = slices.get_or_insert_with(|| {
+                            CandleSlices::from_candles(candles).with_session_start_hour(session_start_hour)
+                        });
                         let computed = Arc::new(compute_indicator_with_slices(config, s, candles)?);
                         // Store Arc in shared DashMap cache (no data copy, just pointer)
                         shared.insert(key, Arc::clone(&computed));
@@ -419,10 +421,10 @@ fn pre_compute_operand_shared(
         // Recurse into compound sub-operands so their indicators are also pre-computed.
         OperandType::Compound => {
             if let Some(ref left) = operand.compound_left {
-                pre_compute_operand_shared(left, seen, shared, local_cache, slices, candles)?;
+                pre_compute_operand_shared(left, seen, shared, local_cache, slices, candles, session_start_hour)?;
             }
             if let Some(ref right) = operand.compound_right {
-                pre_compute_operand_shared(right, seen, shared, local_cache, slices, candles)?;
+                pre_compute_operand_shared(right, seen, shared, local_cache, slices, candles, session_start_hour)?;
             }
         }
         _ => {}
@@ -478,33 +480,78 @@ pub fn evaluate_rules(
     pattern_cache: Option<&CandlePatternCache>,
     time_offset: usize,
 ) -> bool {
+    combine_rules(rules, |i| {
+        evaluate_single_rule(&rules[i], bar_index, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset)
+    })
+}
+
+/// Combine per-rule results according to each rule's `logical_operator` and parentheses.
+///
+/// `eval(i)` evaluates `rules[i]`. Without parentheses the list folds left to right
+/// with short-circuiting (the original flat behaviour). With parentheses each group
+/// is folded left to right on its own and its result feeds the enclosing level, so
+/// `(A OR B) AND C` and `A OR (B AND C)` differ. Unbalanced parentheses are tolerated:
+/// surplus closers are ignored and unclosed groups are closed at the end.
+/// An empty slice returns `false`.
+pub(crate) fn combine_rules(rules: &[Rule], mut eval: impl FnMut(usize) -> bool) -> bool {
     if rules.is_empty() {
         return false;
     }
 
-    let mut result = evaluate_single_rule(&rules[0], bar_index, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset);
+    if !Rule::any_parens(rules) {
+        let mut result = eval(0);
+        for i in 1..rules.len() {
+            let prev_operator = rules[i - 1]
+                .logical_operator
+                .unwrap_or(LogicalOperator::And);
+
+            // Short-circuit: skip evaluation when result is already determined
+            match prev_operator {
+                LogicalOperator::And if !result => continue,
+                LogicalOperator::Or if result => continue,
+                _ => {}
+            }
 
-    for i in 1..rules.len() {
-        let prev_operator = rules[i - 1]
-            .logical_operator
-            .unwrap_or(LogicalOperator::And);
+            let current = eval(i);
 
-        // Short-circuit: skip evaluation when result is already determined
-        match prev_operator {
-            LogicalOperator::And if !result => continue,
-            LogicalOperator::Or if result => continue,
-            _ => {}
+            match prev_operator {
+                LogicalOperator::And => result = result && current,
+                LogicalOperator::Or => result = result || current,
+            }
         }
+        return result;
+    }
 
-        let current = evaluate_single_rule(&rules[i], bar_index, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset);
+    // One frame per open group: (accumulated value, operator joining the next value).
+    fn push_value(frame: &mut (Option<bool>, LogicalOperator), value: bool) {
+        frame.0 = Some(match (frame.0, frame.1) {
+            (None, _) => value,
+            (Some(acc), LogicalOperator::And) => acc && value,
+            (Some(acc), LogicalOperator::Or) => acc || value,
+        });
+    }
 
-        match prev_operator {
-            LogicalOperator::And => result = result && current,
-            LogicalOperator::Or => result = result || current,
+    let mut stack: Vec<(Option<bool>, LogicalOperator)> = vec![(None, LogicalOperator::And)];
+    for (i, rule) in rules.iter().enumerate() {
+        for _ in 0..rule.open_parens {
+            stack.push((None, LogicalOperator::And));
+        }
+        let value = eval(i);
+        push_value(stack.last_mut().unwrap(), value);
+        for _ in 0..rule.close_parens {
+            if stack.len() == 1 {
+                break;
+            }
+            let group = stack.pop().unwrap().0.unwrap_or(false);
+            push_value(stack.last_mut().unwrap(), group);
         }
+        stack.last_mut().unwrap().1 = rule.logical_operator.unwrap_or(LogicalOperator::And);
     }
-
-    result
+    while stack.len() > 1 {
+        let group = stack.pop().unwrap().0.unwrap_or(false);
+        push_value(stack.last_mut().unwrap(), group);
+    }
+    stack[0].0.unwrap_or(false)
 }
 
 /// Evaluate a single rule group: all rules combined via `g.internal` (AND = all pass, OR = any pass).
@@ -580,6 +627,28 @@ pub fn evaluate_rule_groups(
     result
 }
 
+/// Indices of the groups that individually evaluate to `true` at a bar.
+///
+/// Used only to annotate trades with the group(s) responsible for a signal,
+/// so it is called once per entry/exit rather than on every bar.
+pub fn fired_group_indices(
+    groups: &[RuleGroup],
+    bar_index: usize,
+    cache: &IndicatorCache,
+    candles: &[Candle],
+    daily_ohlc: Option<&DailyOhlcCache>,
+    time_cache: Option<&TimeCache>,
+    pattern_cache: Option<&CandlePatternCache>,
+    time_offset: usize,
+) -> Vec<usize> {
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| evaluate_group(g, bar_index, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
 /// Evaluate a single rule at a bar index.
 fn evaluate_single_rule(
     rule: &Rule,
@@ -604,7 +673,7 @@ fn evaluate_single_rule(
         Comparator::LessThan => left < right,
         Comparator::GreaterOrEqual => left >= right,
         Comparator::LessOrEqual => left <= right,
-        Comparator::Equal => (left - right).abs() < f64::EPSILON,
+        Comparator::Equal => Rule::values_equal(rule.tolerance, left, right),
         Comparator::CrossAbove => {
             if bar_index == 0 {
                 return false;
@@ -630,7 +699,7 @@ fn evaluate_single_rule(
     }
 }
 
-/// Resolve an operand's value at a specific bar index.
+/// Resolve an operand's value at a specific bar index, applying its `transform`.
 ///
 /// `time_offset` is added to `bar_index` for BarTime operands so that
 /// time-based rules reference the execution bar while indicators use
@@ -644,6 +713,62 @@ fn resolve_operand(
     time_cache: Option<&TimeCache>,
     pattern_cache: Option<&CandlePatternCache>,
     time_offset: usize,
+) -> f64 {
+    match operand.transform {
+        None => resolve_operand_value(operand, bar_index, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset),
+        Some(transform) => apply_transform(transform, bar_index, |k| {
+            resolve_operand_value(operand, bar_index - k, cache, candles, daily_ohlc, time_cache, pattern_cache, time_offset)
+        }),
+    }
+}
+
+/// `transform` of an operand series at `bar_index`; `value_at(k)` is the raw value
+/// `k` bars back. NaN until the window is full or when any value in it is NaN.
+fn apply_transform(transform: OperandTransform, bar_index: usize, value_at: impl Fn(usize) -> f64) -> f64 {
+    match transform {
+        OperandTransform::PercentRank { period } => {
+            if period == 0 || bar_index < period {
+                return f64::NAN;
+            }
+            let current = value_at(0);
+            let mut at_or_below = 0usize;
+            for k in 1..=period {
+                let v = value_at(k);
+                if v.is_nan() {
+                    return f64::NAN;
+                }
+                if v <= current {
+                    at_or_below += 1;
+                }
+            }
+            if current.is_nan() { f64::NAN } else { 100.0 * at_or_below as f64 / period as f64 }
+        }
+        OperandTransform::ZScore { period } => {
+            if period < 2 || bar_index + 1 < period {
+                return f64::NAN;
+            }
+            // Two passes: mean first, then variance — avoids cancellation on price-scale values
+            let mean = (0..period).map(&value_at).sum::<f64>() / period as f64;
+            if mean.is_nan() {
+                return f64::NAN;
+            }
+            let var = (0..period).map(|k| (value_at(k) - mean).powi(2)).sum::<f64>() / period as f64;
+            let sd = var.sqrt();
+            if sd < f64::EPSILON { f64::NAN } else { (value_at(0) - mean) / sd }
+        }
+    }
+}
+
+/// Raw operand value at `bar_index`, ignoring `transform`.
+fn resolve_operand_value(
+    operand: &Operand,
+    bar_index: usize,
+    cache: &IndicatorCache,
+    candles: &[Candle],
+    daily_ohlc: Option<&DailyOhlcCache>,
+    time_cache: Option<&TimeCache>,
+    pattern_cache: Option<&CandlePatternCache>,
+    time_offset: usize,
 ) -> f64 {
     // BarTime operands use bar_index + time_offset so they resolve to
     // the execution bar's time, not the data/signal bar's time.
@@ -681,29 +806,14 @@ fn resolve_operand(
                 f64::NAN
             }
         }
-        OperandType::Price => {
-            let candle = &candles[effective_index];
-            match operand.price_field {
-                Some(PriceField::Open) => candle.open,
-                Some(PriceField::High) => candle.high,
-                Some(PriceField::Low) => candle.low,
-                Some(PriceField::Close) => candle.close,
-                Some(PriceField::DailyOpen) => daily_ohlc
-                    .map(|d| d.daily_open[effective_index])
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyHigh) => daily_ohlc
-                    .map(|d| d.daily_high[effective_index])
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyLow) => daily_ohlc
-                    .map(|d| d.daily_low[effective_index])
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyClose) => daily_ohlc
-                    .map(|d| d.daily_close[effective_index])
-                    .unwrap_or(f64::NAN),
-                None => candle.close,
+        OperandType::Price => price_value(operand.price_field, candles, effective_index, daily_ohlc),
+        OperandType::Constant => {
+            let value = operand.constant_value.unwrap_or(0.0);
+            match operand.percent_of {
+                Some(field) => percent_of_price(price_value(Some(field), candles, effective_index, daily_ohlc), value),
+                None => value,
             }
         }
-        OperandType::Constant => operand.constant_value.unwrap_or(0.0),
         OperandType::BarTime => {
             if let Some(tc) = time_cache {
                 let idx = effective_index;
@@ -764,6 +874,15 @@ fn resolve_operand(
     }
 }
 
+/// Whether `config.output_field` names an `IndicatorOutput::extra` series that the
+/// streaming state doesn't carry.
+fn is_extra_output(config: &IndicatorConfig) -> bool {
+    matches!(
+        (config.indicator_type, config.output_field.as_deref()),
+        (IndicatorType::LinearRegression, Some("slope"))
+    )
+}
+
 /// Get the appropriate value from an indicator output based on the output_field.
 fn get_indicator_value(
     output: &IndicatorOutput,
@@ -858,33 +977,12 @@ pub fn evaluate_rules_streaming(
     time_cache: Option<&TimeCache>,
     pattern_cache: Option<&CandlePatternCache>,
 ) -> bool {
-    if rules.is_empty() {
-        return false;
-    }
-
-    let mut result = evaluate_single_rule_streaming(
-        &rules[0], 0, bar_index, cache, streaming_state, streaming_vals, cross_prev,
-        candles, running_candle, daily_ohlc, time_cache, pattern_cache,
-    );
-
-    for i in 1..rules.len() {
-        let prev_op = rules[i - 1].logical_operator.unwrap_or(LogicalOperator::And);
-        match prev_op {
-            LogicalOperator::And if !result => continue,
-            LogicalOperator::Or if result => continue,
-            _ => {}
-        }
-        let current = evaluate_single_rule_streaming(
+    combine_rules(rules, |i| {
+        evaluate_single_rule_streaming(
             &rules[i], i, bar_index, cache, streaming_state, streaming_vals, cross_prev,
             candles, running_candle, daily_ohlc, time_cache, pattern_cache,
-        );
-        match prev_op {
-            LogicalOperator::And => result = result && current,
-            LogicalOperator::Or => result = result || current,
-        }
-    }
-
-    result
+        )
+    })
 }
 
 fn evaluate_single_rule_streaming(
@@ -919,7 +1017,7 @@ fn evaluate_single_rule_streaming(
         Comparator::LessThan => left < right,
         Comparator::GreaterOrEqual => left >= right,
         Comparator::LessOrEqual => left <= right,
-        Comparator::Equal => (left - right).abs() < f64::EPSILON,
+        Comparator::Equal => Rule::values_equal(rule.tolerance, left, right),
         Comparator::CrossAbove => {
             if bar_index == 0 {
                 return false;
@@ -942,6 +1040,64 @@ fn evaluate_single_rule_streaming(
     }
 }
 
+/// Price field value of the completed bar at `index` (`None` = close).
+fn price_value(field: Option<PriceField>, candles: &[Candle], index: usize, daily_ohlc: Option<&DailyOhlcCache>) -> f64 {
+    let candle = &candles[index];
+    match field {
+        Some(PriceField::Open) => candle.open,
+        Some(PriceField::High) => candle.high,
+        Some(PriceField::Low) => candle.low,
+        Some(PriceField::Close) => candle.close,
+        Some(PriceField::DailyOpen) => daily_ohlc
+            .map(|d| d.daily_open[index])
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyHigh) => daily_ohlc
+            .map(|d| d.daily_high[index])
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyLow) => daily_ohlc
+            .map(|d| d.daily_low[index])
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyClose) => daily_ohlc
+            .map(|d| d.daily_close[index])
+            .unwrap_or(f64::NAN),
+        Some(PriceField::OpenInterest) => candle.open_interest.unwrap_or(f64::NAN),
+        Some(PriceField::AvgSpread) => candle.avg_spread.unwrap_or(f64::NAN),
+        None => candle.close,
+    }
+}
+
+/// Price field value of the in-progress bar: uses the running candle (current tick's OHLCV).
+fn running_price_value(field: Option<PriceField>, running_candle: &Candle, index: usize, daily_ohlc: Option<&DailyOhlcCache>) -> f64 {
+    match field {
+        Some(PriceField::Open) => running_candle.open,
+        Some(PriceField::High) => running_candle.high,
+        Some(PriceField::Low) => running_candle.low,
+        Some(PriceField::Close) | None => running_candle.close,
+        // Daily fields are not meaningful for the in-progress bar; use prior day
+        Some(PriceField::DailyOpen) => daily_ohlc
+            .and_then(|d| d.daily_open.get(index).copied())
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyHigh) => daily_ohlc
+            .and_then(|d| d.daily_high.get(index).copied())
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyLow) => daily_ohlc
+            .and_then(|d| d.daily_low.get(index).copied())
+            .unwrap_or(f64::NAN),
+        Some(PriceField::DailyClose) => daily_ohlc
+            .and_then(|d| d.daily_close.get(index).copied())
+            .unwrap_or(f64::NAN),
+        // Bar-level fields carry the bar's own aggregate value
+        Some(PriceField::OpenInterest) => running_candle.open_interest.unwrap_or(f64::NAN),
+        Some(PriceField::AvgSpread) => running_candle.avg_spread.unwrap_or(f64::NAN),
+    }
+}
+
+/// `price` shifted by `pct` percent: `price * (1 + pct / 100)`.
+#[inline]
+fn percent_of_price(price: f64, pct: f64) -> f64 {
+    price * (1.0 + pct / 100.0)
+}
+
 /// Resolve an operand at `bar_index`, using streaming overrides when available.
 ///
 /// For `effective_index == bar_index`:
@@ -960,6 +1116,35 @@ fn resolve_operand_streaming(
     daily_ohlc: Option<&DailyOhlcCache>,
     time_cache: Option<&TimeCache>,
     pattern_cache: Option<&CandlePatternCache>,
+) -> f64 {
+    match operand.transform {
+        None => resolve_operand_streaming_value(
+            operand, bar_index, cache, streaming_state, streaming_vals,
+            candles, running_candle, daily_ohlc, time_cache, pattern_cache,
+        ),
+        // Only the current value is still forming; the history comes from completed bars
+        Some(transform) => apply_transform(transform, bar_index, |k| match k {
+            0 => resolve_operand_streaming_value(
+                operand, bar_index, cache, streaming_state, streaming_vals,
+                candles, running_candle, daily_ohlc, time_cache, pattern_cache,
+            ),
+            _ => resolve_operand_value(operand, bar_index - k, cache, candles, daily_ohlc, time_cache, pattern_cache, 0),
+        }),
+    }
+}
+
+/// Raw streaming operand value at `bar_index`, ignoring `transform`.
+fn resolve_operand_streaming_value(
+    operand: &Operand,
+    bar_index: usize,
+    cache: &IndicatorCache,
+    streaming_state: &StreamingStateMap,
+    streaming_vals: &StreamingVals,
+    candles: &[Candle],
+    running_candle: &Candle,
+    daily_ohlc: Option<&DailyOhlcCache>,
+    time_cache: Option<&TimeCache>,
+    pattern_cache: Option<&CandlePatternCache>,
 ) -> f64 {
     // BarTime uses time_offset=0 in streaming context (no bar shift needed)
     let base_index = bar_index;
@@ -975,7 +1160,7 @@ fn resolve_operand_streaming(
 
     // For indices other than bar_index, fall back to the regular (completed-bar) resolver
     if effective_index != bar_index {
-        return resolve_operand(
+        return resolve_operand_value(
             operand, effective_index, cache, candles,
             daily_ohlc, time_cache, pattern_cache, 0,
         );
@@ -986,8 +1171,10 @@ fn resolve_operand_streaming(
         OperandType::Indicator => {
             if let Some(ref config) = operand.indicator {
                 let str_key = config.cache_key();
-                // Vec lookup via key_index — no String hashing in hot path
-                if let Some(&idx) = streaming_state.key_index.get(&str_key) {
+                // Vec lookup via key_index — no String hashing in hot path.
+                // Extra outputs (e.g. the regression slope) aren't streamed; they read the cache.
+                let streamed = !is_extra_output(config);
+                if let Some(&idx) = streaming_state.key_index.get(&str_key).filter(|_| streamed) {
                     if let Some(sv) = streaming_vals.get(idx) {
                         // Pick the right output based on output_field (mirrors get_indicator_value)
                         return match config.output_field.as_deref() {
@@ -1011,27 +1198,12 @@ fn resolve_operand_streaming(
             }
         }
         OperandType::Price => {
-            // Use the running candle (current tick's OHLCV) instead of candles[bar_index]
-            match operand.price_field {
-                Some(PriceField::Open) => running_candle.open,
-                Some(PriceField::High) => running_candle.high,
-                Some(PriceField::Low) => running_candle.low,
-                Some(PriceField::Close) | None => running_candle.close,
-                // Daily fields are not meaningful for the in-progress bar; use prior day
-                Some(PriceField::DailyOpen) => daily_ohlc
-                    .and_then(|d| d.daily_open.get(effective_index).copied())
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyHigh) => daily_ohlc
-                    .and_then(|d| d.daily_high.get(effective_index).copied())
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyLow) => daily_ohlc
-                    .and_then(|d| d.daily_low.get(effective_index).copied())
-                    .unwrap_or(f64::NAN),
-                Some(PriceField::DailyClose) => daily_ohlc
-                    .and_then(|d| d.daily_close.get(effective_index).copied())
-                    .unwrap_or(f64::NAN),
-            }
+            running_price_value(operand.price_field, running_candle, effective_index, daily_ohlc)
         }
+        OperandType::Constant if operand.percent_of.is_some() => percent_of_price(
+            running_price_value(operand.percent_of, running_candle, effective_index, daily_ohlc),
+            operand.constant_value.unwrap_or(0.0),
+        ),
         OperandType::Compound => {
             let left_op = match operand.compound_left.as_deref() {
                 Some(l) => l,
@@ -1059,7 +1231,7 @@ fn resolve_operand_streaming(
             }
         }
         // Constants, BarTime, and CandlePattern are not tick-sensitive — use regular resolver
-        _ => resolve_operand(
+        _ => resolve_operand_value(
             operand, bar_index, cache, candles,
             daily_ohlc, time_cache, pattern_cache, 0,
         ),
@@ -1104,7 +1276,12 @@ pub fn precompute_cross_prev_vals(
     }).collect()
 }
 
+/// Largest `Operand::offset` a strategy may use (the rule editor's limit).
+pub const MAX_OPERAND_OFFSET: usize = 500;
+
 /// Calculate the maximum lookback period needed for a strategy's indicators.
+/// Includes operand offsets, so the bar loop starts late enough that every offset
+/// resolves to a real past bar.
 pub fn max_lookback(strategy: &Strategy) -> usize {
     let mut max = 0usize;
     let all_rules = strategy.long_entry_rules.iter()
@@ -1176,7 +1353,12 @@ fn operand_lookback(operand: &Operand) -> usize {
         }
         _ => 0,
     };
-    base + operand.offset.unwrap_or(0)
+    let window = match operand.transform {
+        Some(OperandTransform::PercentRank { period }) => period,
+        Some(OperandTransform::ZScore { period }) => period.saturating_sub(1),
+        None => 0,
+    };
+    base + window + operand.offset.unwrap_or(0)
 }
 
 fn indicator_lookback(config: &IndicatorConfig) -> usize {
@@ -1185,7 +1367,13 @@ fn indicator_lookback(config: &IndicatorConfig) -> usize {
         SMA | EMA | CCI | WilliamsR => config.params.period.unwrap_or(14),
         RSI | ATR => config.params.period.unwrap_or(14) + 1,
         ROC => config.params.period.unwrap_or(14) + 1,
-        MACD => {
+        ElderImpulse => {
+            let period = config.params.period.unwrap_or(13);
+            let slow = config.params.slow_period.unwrap_or(26);
+            let signal = config.params.signal_period.unwrap_or(9);
+            period.max(slow + signal) + 1
+        }
+        MACD | PPO => {
             let slow = config.params.slow_period.unwrap_or(26);
             let signal = config.params.signal_period.unwrap_or(9);
             slow + signal
@@ -1201,8 +1389,8 @@ fn indicator_lookback(config: &IndicatorConfig) -> usize {
         // New indicators
         Aroon | Momentum | Vortex => config.params.period.unwrap_or(14) + 1,
         AwesomeOscillator => 34,
-        BarRange | TrueRange | HeikenAshi | LaguerreRSI | Pivots => 1,
-        BiggestRange | HighestInRange | LowestInRange | SmallestRange
+        BarRange | TrueRange | HeikenAshi | LaguerreRSI | Pivots | AccumulationDistribution => 1,
+        BiggestRange | HighestInRange | LowestInRange | SmallestRange | DonchianChannel
         | BearsPower | BullsPower | Fibonacci | GannHiLo | HullMA
         | LinearRegression | StdDev | UlcerIndex => config.params.period.unwrap_or(14),
         DeMarker => config.params.period.unwrap_or(14) + 1,
@@ -1214,10 +1402,50 @@ fn indicator_lookback(config: &IndicatorConfig) -> usize {
         }
         KeltnerChannel | SuperTrend => config.params.period.unwrap_or(14) + 1,
         Reflex => config.params.period.unwrap_or(14) + 2,
+        SuperSmoother => config.params.period.unwrap_or(10) + 2,
+        RoofingFilter => config.params.slow_period.unwrap_or(48).max(config.params.period.unwrap_or(10)) + 2,
+        ConnorsRSI => {
+            let rsi_p = config.params.period.unwrap_or(3);
+            let streak_p = config.params.fast_period.unwrap_or(2);
+            let rank_p = config.params.slow_period.unwrap_or(100);
+            rsi_p.max(streak_p).max(rank_p + 1) + 1
+        }
         // SQX new indicators
-        AvgVolume | BBWidthRatio | EfficiencyRatio | HighestIndex | LowestIndex =>
+        AvgVolume | BBWidthRatio | EfficiencyRatio | HighestIndex | LowestIndex | ChaikinMoneyFlow =>
             config.params.period.unwrap_or(14),
+        ForceIndex | EaseOfMovement => config.params.period.unwrap_or(14) + 1,
+        MassIndex => {
+            let ema_period = config.params.fast_period.unwrap_or(9);
+            2 * ema_period.saturating_sub(1) + config.params.period.unwrap_or(25)
+        }
+        Klinger => {
+            let fast = config.params.fast_period.unwrap_or(34);
+            let slow = config.params.slow_period.unwrap_or(55);
+            fast.max(slow) + config.params.signal_period.unwrap_or(13)
+        }
+        VWMA | WMA => config.params.period.unwrap_or(20),
+        ZLEMA => {
+            let period = config.params.period.unwrap_or(20);
+            period + period.saturating_sub(1) / 2
+        }
         KAMA => config.params.period.unwrap_or(10) + 1,
+        Choppiness => config.params.period.unwrap_or(14) + 1,
+        Coppock => {
+            let fast = config.params.fast_period.unwrap_or(11);
+            let slow = config.params.slow_period.unwrap_or(14);
+            fast.max(slow) + config.params.period.unwrap_or(10)
+        }
+        DPO => {
+            let period = config.params.period.unwrap_or(20);
+            period + period / 2 + 1
+        }
+        TRIX => config.params.period.unwrap_or(15) * 3 + config.params.signal_period.unwrap_or(0),
+        StochasticRSI => {
+            config.params.period.unwrap_or(14)
+                + config.params.k_period.unwrap_or(14)
+                + config.params.slowing.unwrap_or(3)
+                + config.params.d_period.unwrap_or(3)
+        }
         QQE => {
             let rsi_p = config.params.period.unwrap_or(14);
             rsi_p * 2 + config.params.signal_period.unwrap_or(5)
@@ -1256,6 +1484,7 @@ pub struct FastRule {
     pub left: FastOp,
     pub right: FastOp,
     pub comparator: Comparator,
+    pub tolerance: Option<f64>,
     pub logical_op: Option<LogicalOperator>,
 }
 
@@ -1268,13 +1497,15 @@ pub fn compile_rules_streaming(rules: &[Rule], streaming_state: &StreamingStateM
         left: fast_op_for(&rule.left_operand, streaming_state),
         right: fast_op_for(&rule.right_operand, streaming_state),
         comparator: rule.comparator,
+        tolerance: rule.tolerance,
         logical_op: rule.logical_operator,
     }).collect()
 }
 
 fn fast_op_for(operand: &Operand, streaming_state: &StreamingStateMap) -> FastOp {
-    // Operands with offsets need full per-tick resolution (bar_index - offset != bar_index)
-    if operand.offset.is_some() {
+    // Operands with offsets need full per-tick resolution (bar_index - offset != bar_index),
+    // transformed ones their history window
+    if operand.offset.is_some() || operand.transform.is_some() {
         return FastOp::Fallback;
     }
     match operand.operand_type {
@@ -1282,7 +1513,7 @@ fn fast_op_for(operand: &Operand, streaming_state: &StreamingStateMap) -> FastOp
             if let Some(ref config) = operand.indicator {
                 // One-time cache_key() per operand per bar — not per tick
                 let key = config.cache_key();
-                if let Some(&idx) = streaming_state.key_index.get(&key) {
+                if let Some(&idx) = streaming_state.key_index.get(&key).filter(|_| !is_extra_output(config)) {
                     let field: u8 = match config.output_field.as_deref() {
                         Some("signal") | Some("d") | Some("upper") => 1,
                         Some("histogram") | Some("lower") => 2,
@@ -1299,8 +1530,10 @@ fn fast_op_for(operand: &Operand, streaming_state: &StreamingStateMap) -> FastOp
             | Some(pf @ PriceField::Low)
             | Some(pf @ PriceField::Close) => FastOp::Price(pf),
             None => FastOp::Price(PriceField::Close),
-            _ => FastOp::Fallback, // Daily fields handled by full fallback
+            _ => FastOp::Fallback, // Daily and bar-aggregate fields handled by full fallback
         },
+        // Percent-of-price constants depend on the running candle
+        OperandType::Constant if operand.percent_of.is_some() => FastOp::Fallback,
         OperandType::Constant => FastOp::Const(operand.constant_value.unwrap_or(0.0)),
         _ => FastOp::Fallback, // BarTime, CandlePattern
     }
@@ -1328,27 +1561,13 @@ pub fn evaluate_rules_fast(
     if fast_rules.is_empty() {
         return false;
     }
-    let mut result = eval_fast_single(
-        &fast_rules[0], &rules[0], 0, bar_index, cache, streaming_state, streaming_vals,
-        cross_prev, candles, running_candle, daily_ohlc, time_cache, pattern_cache,
-    );
-    for i in 1..fast_rules.len() {
-        let prev_op = fast_rules[i - 1].logical_op.unwrap_or(LogicalOperator::And);
-        match prev_op {
-            LogicalOperator::And if !result => continue,
-            LogicalOperator::Or if result => continue,
-            _ => {}
-        }
-        let current = eval_fast_single(
+    // `fast_rules[i]` is compiled from `rules[i]`, so operators and parentheses come from `rules`.
+    combine_rules(rules, |i| {
+        eval_fast_single(
             &fast_rules[i], &rules[i], i, bar_index, cache, streaming_state, streaming_vals,
             cross_prev, candles, running_candle, daily_ohlc, time_cache, pattern_cache,
-        );
-        match prev_op {
-            LogicalOperator::And => result = result && current,
-            LogicalOperator::Or => result = result || current,
-        }
-    }
-    result
+        )
+    })
 }
 
 #[inline(always)]
@@ -1428,7 +1647,7 @@ fn eval_fast_single(
         Comparator::LessThan      => left < right,
         Comparator::GreaterOrEqual => left >= right,
         Comparator::LessOrEqual   => left <= right,
-        Comparator::Equal         => (left - right).abs() < f64::EPSILON,
+        Comparator::Equal         => Rule::values_equal(fast.tolerance, left, right),
         Comparator::CrossAbove => {
             if bar_index == 0 { return false; }
             let Some((pl, pr)) = cross_prev.get(rule_index).and_then(|v| *v) else { return false; };
@@ -1483,6 +1702,8 @@ mod tests {
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     }
 
@@ -1498,6 +1719,8 @@ mod tests {
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     }
 
@@ -1513,9 +1736,30 @@ mod tests {
             compound_left: None,
             compound_op: None,
             compound_right: None,
+            percent_of: None,
+            transform: None,
         }
     }
 
+    #[test]
+    fn test_daily_ohlc_rolls_at_session_start_hour() {
+        let candles: Vec<Candle> = [("2024-01-02 16:00", 1.0), ("2024-01-02 17:00", 2.0), ("2024-01-03 09:00", 3.0)]
+            .iter()
+            .map(|&(dt, price)| Candle {
+                datetime: dt.to_string(), open: price, high: price, low: price, close: price, ..Default::default()
+            })
+            .collect();
+
+        let calendar = compute_daily_ohlc(&candles, 0);
+        assert_eq!(calendar.daily_open, vec![1.0, 1.0, 3.0]);
+        assert_eq!(calendar.daily_close[2], 2.0);
+
+        let session = compute_daily_ohlc(&candles, 17);
+        assert_eq!(session.daily_open, vec![1.0, 2.0, 2.0]);
+        assert_eq!(session.daily_high[2], 3.0);
+        assert_eq!(session.daily_close[2], 1.0);
+    }
+
     #[test]
     fn test_evaluate_greater_than() {
         let candles = make_candles(&[10.0, 20.0, 30.0]);
@@ -1525,6 +1769,9 @@ mod tests {
             comparator: Comparator::GreaterThan,
             right_operand: constant_operand(15.0),
             logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         }];
         let cache = IndicatorCache::new();
         assert!(!evaluate_rules(&rules, 0, &cache, &candles, None, None, None, 0)); // 10 > 15 = false
@@ -1532,22 +1779,39 @@ mod tests {
     }
 
     #[test]
-    fn test_evaluate_cross_above() {
-        // Create prices where SMA(3) crosses above a constant
-        // Prices: 10, 12, 14, 16, 18 → SMA(3) at idx 2=12, 3=14, 4=16
-        let candles = make_candles(&[10.0, 12.0, 14.0, 16.0, 18.0]);
-        let strategy = Strategy {
+    fn test_equal_matches_near_equal_indicator_value() {
+        // SMA(3) of 0.1, 0.2, 0.3 lands a few ulps away from 0.2
+        let candles = make_candles(&[0.1, 0.2, 0.3, 0.5]);
+        let equal_rule = |right: f64, tolerance: Option<f64>| Rule {
+            id: "r1".to_string(),
+            left_operand: indicator_operand(IndicatorType::SMA, 3),
+            comparator: Comparator::Equal,
+            right_operand: constant_operand(right),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance,
+        };
+        let rules = vec![equal_rule(0.2, None)];
+        let cache = cache_for(&rules, &candles);
+        let sma = resolve_operand(&rules[0].left_operand, 2, &cache, &candles, None, None, None, 0);
+        assert_ne!(sma, 0.2);
+        assert!(evaluate_rules(&rules, 2, &cache, &candles, None, None, None, 0));
+        assert!(!evaluate_rules(&rules, 3, &cache, &candles, None, None, None, 0));
+
+        // An explicit tolerance widens the neutral zone
+        assert!(!evaluate_rules(&[equal_rule(0.21, None)], 2, &cache, &candles, None, None, None, 0));
+        assert!(evaluate_rules(&[equal_rule(0.21, Some(0.02))], 2, &cache, &candles, None, None, None, 0));
+    }
+
+    /// Minimal strategy trading both directions on `long_entry_rules`, without costs or exits.
+    fn make_strategy(long_entry_rules: Vec<Rule>) -> Strategy {
+        Strategy {
             id: "s1".to_string(),
             name: "test".to_string(),
             created_at: String::new(),
             updated_at: String::new(),
-            long_entry_rules: vec![Rule {
-                id: "r1".to_string(),
-                left_operand: indicator_operand(IndicatorType::SMA, 3),
-                comparator: Comparator::CrossAbove,
-                right_operand: constant_operand(13.0),
-                logical_operator: None,
-            }],
+            long_entry_rules,
             short_entry_rules: vec![],
             long_exit_rules: vec![],
             short_exit_rules: vec![],
@@ -1569,6 +1833,7 @@ mod tests {
                 commission_value: 0.0,
                 slippage_pips: 0.0,
                 slippage_random: false,
+                max_spread_pips: None,
             },
             trade_direction: TradeDirection::Both,
             trading_hours: None,
@@ -1577,11 +1842,37 @@ mod tests {
             entry_order: OrderType::Market,
             entry_order_offset_pips: 0.0,
             close_after_bars: None,
+            min_hold_bars: None,
+            max_hold_bars: None,
             move_sl_to_be: false,
+            close_on_opposite_signal: false,
+            reverse_on_opposite_signal: false,
+            session_exit: None,
             entry_order_indicator: None,
-        };
+        }
+    }
+
+    fn cross_above_rule(left_operand: Operand, right_operand: Operand) -> Rule {
+        Rule {
+            id: "r1".to_string(),
+            left_operand,
+            comparator: Comparator::CrossAbove,
+            right_operand,
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }
+    }
 
-        let cache = pre_compute_indicators(&strategy, &candles).unwrap();
+    #[test]
+    fn test_evaluate_cross_above() {
+        // Create prices where SMA(3) crosses above a constant
+        // Prices: 10, 12, 14, 16, 18 → SMA(3) at idx 2=12, 3=14, 4=16
+        let candles = make_candles(&[10.0, 12.0, 14.0, 16.0, 18.0]);
+        let strategy = make_strategy(vec![cross_above_rule(indicator_operand(IndicatorType::SMA, 3), constant_operand(13.0))]);
+
+        let cache = pre_compute_indicators(&strategy, &candles, 0).unwrap();
         // SMA(3): NaN, NaN, 12.0, 14.0, 16.0
         // CrossAbove 13.0: at idx 3 → prev=12.0 <= 13.0 AND curr=14.0 > 13.0 → true
         assert!(evaluate_rules(&strategy.long_entry_rules, 3, &cache, &candles, None, None, None, 0));
@@ -1589,6 +1880,30 @@ mod tests {
         assert!(!evaluate_rules(&strategy.long_entry_rules, 4, &cache, &candles, None, None, None, 0));
     }
 
+    #[test]
+    fn test_volume_indicator_on_zero_volume_data_errors() {
+        // Bars imported without a volume column: a money-flow indicator must fail loudly
+        // instead of producing a flat series.
+        let candles: Vec<Candle> = make_candles(&[10.0, 12.0, 14.0, 16.0, 18.0])
+            .into_iter()
+            .map(|c| Candle { volume: 0.0, ..c })
+            .collect();
+        let strategy = make_strategy(vec![cross_above_rule(
+            indicator_operand(IndicatorType::ChaikinMoneyFlow, 3),
+            constant_operand(0.0),
+        )]);
+
+        let err = pre_compute_indicators(&strategy, &candles, 0).unwrap_err();
+        assert!(matches!(err, AppError::MissingVolumeData(_)));
+        assert!(err.to_string().contains("ChaikinMoneyFlow"));
+
+        // Price-only indicators are unaffected by missing volume.
+        assert!(crate::engine::indicators::compute_indicator(
+            &indicator_operand(IndicatorType::SMA, 3).indicator.unwrap(),
+            &candles,
+        ).is_ok());
+    }
+
     #[test]
     fn test_evaluate_and_logic() {
         let candles = make_candles(&[50.0]);
@@ -1599,6 +1914,9 @@ mod tests {
                 comparator: Comparator::GreaterThan,
                 right_operand: constant_operand(40.0),
                 logical_operator: Some(LogicalOperator::And),
+                open_parens: 0,
+                close_parens: 0,
+                tolerance: None,
             },
             Rule {
                 id: "r2".to_string(),
@@ -1606,6 +1924,9 @@ mod tests {
                 comparator: Comparator::LessThan,
                 right_operand: constant_operand(60.0),
                 logical_operator: None,
+                open_parens: 0,
+                close_parens: 0,
+                tolerance: None,
             },
         ];
         let cache = IndicatorCache::new();
@@ -1623,6 +1944,9 @@ mod tests {
                 comparator: Comparator::GreaterThan,
                 right_operand: constant_operand(100.0),
                 logical_operator: Some(LogicalOperator::Or),
+                open_parens: 0,
+                close_parens: 0,
+                tolerance: None,
             },
             Rule {
                 id: "r2".to_string(),
@@ -1630,6 +1954,9 @@ mod tests {
                 comparator: Comparator::LessThan,
                 right_operand: constant_operand(60.0),
                 logical_operator: None,
+                open_parens: 0,
+                close_parens: 0,
+                tolerance: None,
             },
         ];
         let cache = IndicatorCache::new();
@@ -1637,6 +1964,140 @@ mod tests {
         assert!(evaluate_rules(&rules, 0, &cache, &candles, None, None, None, 0));
     }
 
+    /// Close > `threshold`, joined to the next rule by `op`.
+    fn close_above(threshold: f64, op: Option<LogicalOperator>, open_parens: u8, close_parens: u8) -> Rule {
+        Rule {
+            id: format!("r{}", threshold),
+            left_operand: price_operand(PriceField::Close),
+            comparator: Comparator::GreaterThan,
+            right_operand: constant_operand(threshold),
+            logical_operator: op,
+            open_parens,
+            close_parens,
+            tolerance: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_parenthesized_groups() {
+        let candles = make_candles(&[50.0]);
+        let cache = IndicatorCache::new();
+        // A = 50 > 10 (true), B = 50 > 100 (false), C = 50 > 200 (false)
+        let or = Some(LogicalOperator::Or);
+        let and = Some(LogicalOperator::And);
+
+        // (A OR B) AND C → (true OR false) AND false → false
+        let grouped_left = vec![
+            close_above(10.0, or, 1, 0),
+            close_above(100.0, and, 0, 1),
+            close_above(200.0, None, 0, 0),
+        ];
+        assert!(!evaluate_rules(&grouped_left, 0, &cache, &candles, None, None, None, 0));
+
+        // A OR (B AND C) → true OR (false AND false) → true
+        let grouped_right = vec![
+            close_above(10.0, or, 0, 0),
+            close_above(100.0, and, 1, 0),
+            close_above(200.0, None, 0, 1),
+        ];
+        assert!(evaluate_rules(&grouped_right, 0, &cache, &candles, None, None, None, 0));
+
+        // Flat list keeps left-to-right folding: (A OR B) AND C
+        let flat = vec![
+            close_above(10.0, or, 0, 0),
+            close_above(100.0, and, 0, 0),
+            close_above(200.0, None, 0, 0),
+        ];
+        assert!(!evaluate_rules(&flat, 0, &cache, &candles, None, None, None, 0));
+    }
+
+    #[test]
+    fn test_percent_of_price_constant() {
+        // close > open * 1.005
+        let candles: Vec<Candle> = [(100.0, 100.4), (100.0, 100.6), (200.0, 200.9)]
+            .iter()
+            .map(|&(open, close)| Candle {
+                open,
+                high: close + 1.0,
+                low: open - 1.0,
+                close,
+                ..Default::default()
+            })
+            .collect();
+        let cache = IndicatorCache::new();
+        let mut threshold = constant_operand(0.5);
+        threshold.percent_of = Some(PriceField::Open);
+        let rules = vec![Rule {
+            id: "pct".into(),
+            left_operand: price_operand(PriceField::Close),
+            comparator: Comparator::GreaterThan,
+            right_operand: threshold.clone(),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        assert!(!evaluate_rules(&rules, 0, &cache, &candles, None, None, None, 0), "+0.4% is below the threshold");
+        assert!(evaluate_rules(&rules, 1, &cache, &candles, None, None, None, 0), "+0.6% is above the threshold");
+        // Scales with the bar's own open: 200 * 1.005 = 201
+        assert!(!evaluate_rules(&rules, 2, &cache, &candles, None, None, None, 0));
+
+        // Negative percentages sit below the price
+        threshold.constant_value = Some(-1.0);
+        let v = resolve_operand(&threshold, 2, &cache, &candles, None, None, None, 0);
+        assert!((v - 198.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_avg_spread_operand() {
+        // Skip entries while the bar's average spread is wider than 2 pips
+        let candles: Vec<Candle> = [Some(0.00012), Some(0.00031), None]
+            .iter()
+            .map(|&avg_spread| Candle { close: 1.1, avg_spread, ..Default::default() })
+            .collect();
+        let cache = IndicatorCache::new();
+        let rules = vec![Rule {
+            id: "spread".into(),
+            left_operand: price_operand(PriceField::AvgSpread),
+            comparator: Comparator::LessThan,
+            right_operand: constant_operand(0.0002),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        assert!(evaluate_rules(&rules, 0, &cache, &candles, None, None, None, 0));
+        assert!(!evaluate_rules(&rules, 1, &cache, &candles, None, None, None, 0));
+        // Bars without spread data never satisfy the rule
+        assert!(resolve_operand(&rules[0].left_operand, 2, &cache, &candles, None, None, None, 0).is_nan());
+        assert!(!evaluate_rules(&rules, 2, &cache, &candles, None, None, None, 0));
+    }
+
+    #[test]
+    fn test_evaluate_nested_and_unbalanced_parens() {
+        let candles = make_candles(&[50.0]);
+        let cache = IndicatorCache::new();
+        let or = Some(LogicalOperator::Or);
+        let and = Some(LogicalOperator::And);
+
+        // A AND ((B OR C) OR D) with D true → true
+        let nested = vec![
+            close_above(10.0, and, 0, 0),
+            close_above(100.0, or, 2, 0),
+            close_above(200.0, or, 0, 1),
+            close_above(20.0, None, 0, 1),
+        ];
+        assert!(evaluate_rules(&nested, 0, &cache, &candles, None, None, None, 0));
+
+        // Unclosed group is closed at the end: A OR (B AND C → true
+        let unclosed = vec![
+            close_above(10.0, or, 0, 0),
+            close_above(100.0, and, 1, 0),
+            close_above(200.0, None, 0, 0),
+        ];
+        assert!(evaluate_rules(&unclosed, 0, &cache, &candles, None, None, None, 0));
+    }
+
     #[test]
     fn test_operand_with_offset() {
         let candles = make_candles(&[10.0, 20.0, 30.0]);
@@ -1655,8 +2116,13 @@ mod tests {
                 compound_left: None,
                 compound_op: None,
                 compound_right: None,
+                percent_of: None,
+                transform: None,
             },
             logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         }];
         let cache = IndicatorCache::new();
         // Bar 2: close=30 > close[1]=20 → true
@@ -1665,6 +2131,166 @@ mod tests {
         assert!(!evaluate_rules(&rules, 0, &cache, &candles, None, None, None, 0));
     }
 
+    #[test]
+    fn test_offset_reads_exact_past_bar() {
+        let candles = make_candles(&[10.0, 20.0, 30.0, 40.0, 50.0]);
+        let close_back_above = |offset: usize, level: f64| Rule {
+            id: "r1".to_string(),
+            left_operand: Operand { offset: Some(offset), ..price_operand(PriceField::Close) },
+            comparator: Comparator::GreaterThan,
+            right_operand: constant_operand(level),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        };
+        let cache = IndicatorCache::new();
+        // Bar 4 with offset 2 reads bar 2 (close 30), never a later bar
+        assert!(evaluate_rules(&[close_back_above(2, 29.0)], 4, &cache, &candles, None, None, None, 0));
+        assert!(!evaluate_rules(&[close_back_above(2, 31.0)], 4, &cache, &candles, None, None, None, 0));
+        // BarTime shift does not move price operands
+        assert!(!evaluate_rules(&[close_back_above(2, 31.0)], 4, &cache, &candles, None, None, None, 1));
+    }
+
+    #[test]
+    fn test_percent_rank_transform() {
+        let candles = make_candles(&[10.0, 12.0, 11.0, 13.0, 9.0, 14.0]);
+        let ranked = Operand {
+            transform: Some(OperandTransform::PercentRank { period: 4 }),
+            ..price_operand(PriceField::Close)
+        };
+        let cache = IndicatorCache::new();
+        let at = |i: usize| resolve_operand(&ranked, i, &cache, &candles, None, None, None, 0);
+        // Needs `period` previous bars
+        assert!(at(3).is_nan());
+        // Bar 4: 9 vs [10, 12, 11, 13] → none at or below
+        assert_eq!(at(4), 0.0);
+        // Bar 5: 14 vs [12, 11, 13, 9] → all at or below
+        assert_eq!(at(5), 100.0);
+
+        let rules = vec![Rule {
+            id: "r1".to_string(),
+            left_operand: ranked,
+            comparator: Comparator::GreaterThan,
+            right_operand: constant_operand(80.0),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        assert!(!evaluate_rules(&rules, 4, &cache, &candles, None, None, None, 0));
+        assert!(evaluate_rules(&rules, 5, &cache, &candles, None, None, None, 0));
+    }
+
+    #[test]
+    fn test_zscore_transform() {
+        // Window [2, 4, 4, 4, 5, 5, 7, 9]: mean 5, population sd 2
+        let candles = make_candles(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, 5.0]);
+        let z = Operand {
+            transform: Some(OperandTransform::ZScore { period: 8 }),
+            ..price_operand(PriceField::Close)
+        };
+        let cache = IndicatorCache::new();
+        assert!(resolve_operand(&z, 6, &cache, &candles, None, None, None, 0).is_nan());
+        assert!((resolve_operand(&z, 7, &cache, &candles, None, None, None, 0) - 2.0).abs() < 1e-12);
+
+        // Flat window has no dispersion → NaN rather than ±inf
+        let flat = make_candles(&[3.0; 5]);
+        let z3 = Operand { transform: Some(OperandTransform::ZScore { period: 3 }), ..price_operand(PriceField::Close) };
+        assert!(resolve_operand(&z3, 4, &cache, &flat, None, None, None, 0).is_nan());
+
+        // Offset shifts the whole window
+        let shifted = Operand { offset: Some(1), ..z };
+        assert!((resolve_operand(&shifted, 8, &cache, &candles, None, None, None, 0) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_transform_extends_lookback() {
+        let op = Operand {
+            transform: Some(OperandTransform::PercentRank { period: 20 }),
+            ..indicator_operand(IndicatorType::SMA, 10)
+        };
+        assert_eq!(operand_lookback(&op), 30);
+    }
+
+    /// Ichimoku (tenkan 2, kijun 4, senkou B 6) reading one of its outputs.
+    fn ichimoku_operand(field: &str) -> Operand {
+        let mut op = indicator_operand(IndicatorType::Ichimoku, 0);
+        let ind = op.indicator.as_mut().unwrap();
+        ind.params = IndicatorParams { fast_period: Some(2), slow_period: Some(4), signal_period: Some(6), ..Default::default() };
+        ind.output_field = Some(field.to_string());
+        op
+    }
+
+    /// V-shaped closes: 60 down to 41 (bar 19), then back up to 61.
+    fn v_shaped_candles() -> Vec<Candle> {
+        let prices: Vec<f64> = (0..40).map(|k| if k <= 19 { 60.0 - k as f64 } else { 41.0 + (k - 19) as f64 }).collect();
+        make_candles(&prices)
+    }
+
+    fn cache_for(rules: &[Rule], candles: &[Candle]) -> IndicatorCache {
+        let cache = IndicatorCache::new();
+        let slices = CandleSlices::from_candles(candles);
+        let mut seen = std::collections::HashSet::new();
+        for rule in rules {
+            collect_indicator_from_operand(&rule.left_operand, &mut seen, &cache, &slices, candles).unwrap();
+            collect_indicator_from_operand(&rule.right_operand, &mut seen, &cache, &slices, candles).unwrap();
+        }
+        cache
+    }
+
+    #[test]
+    fn test_ichimoku_tenkan_crosses_kijun() {
+        let candles = v_shaped_candles();
+        let rules = vec![Rule {
+            id: "r1".to_string(),
+            left_operand: ichimoku_operand("tenkan"),
+            comparator: Comparator::CrossAbove,
+            right_operand: ichimoku_operand("kijun"),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        let cache = cache_for(&rules, &candles);
+        let at = |field: &str, i: usize| resolve_operand(&ichimoku_operand(field), i, &cache, &candles, None, None, None, 0);
+        // Bar 20: tenkan (43 + 40) / 2 = 41.5 ≤ kijun (44 + 40) / 2 = 42; bar 21: 42.5 > 42
+        assert_eq!((at("tenkan", 20), at("kijun", 20)), (41.5, 42.0));
+        assert_eq!((at("tenkan", 21), at("kijun", 21)), (42.5, 42.0));
+
+        let crosses: Vec<usize> = (1..candles.len())
+            .filter(|&i| evaluate_rules(&rules, i, &cache, &candles, None, None, None, 0))
+            .collect();
+        assert_eq!(crosses, vec![21]);
+    }
+
+    #[test]
+    fn test_ichimoku_price_above_cloud() {
+        let candles = v_shaped_candles();
+        let rules = vec![Rule {
+            id: "r1".to_string(),
+            left_operand: price_operand(PriceField::Close),
+            comparator: Comparator::GreaterThan,
+            right_operand: ichimoku_operand("cloud_top"),
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        let cache = cache_for(&rules, &candles);
+        let at = |field: &str, i: usize| resolve_operand(&ichimoku_operand(field), i, &cache, &candles, None, None, None, 0);
+        // Bar 39 reads the spans projected from bar 35: A = (56.5 + 55.5) / 2, B = (58 + 51) / 2
+        assert_eq!(at("senkou_a", 39), 56.0);
+        assert_eq!(at("senkou_b", 39), 54.5);
+        assert_eq!((at("cloud_top", 39), at("cloud_bottom", 39)), (56.0, 54.5));
+        // Spans need senkou B + kijun bars of history
+        assert!(at("cloud_top", 8).is_nan());
+
+        assert!(!evaluate_rules(&rules, 8, &cache, &candles, None, None, None, 0));
+        assert!(!evaluate_rules(&rules, 15, &cache, &candles, None, None, None, 0));
+        assert!(evaluate_rules(&rules, 39, &cache, &candles, None, None, None, 0));
+    }
+
     #[test]
     fn test_candle_pattern_doji() {
         // Create candles where bar 1 is a Doji (body <= 10% of range)
@@ -1701,11 +2327,14 @@ mod tests {
                 candle_pattern: Some(CandlePatternType::Doji),
                 indicator: None, price_field: None, constant_value: None,
                 time_field: None, offset: None,
-                compound_left: None, compound_op: None, compound_right: None,
+                compound_left: None, compound_op: None, compound_right: None, percent_of: None, transform: None,
             },
             comparator: Comparator::Equal,
             right_operand: constant_operand(1.0),
             logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         }];
         let cache = IndicatorCache::new();
         // Bar 1 is Doji → should match
@@ -1743,11 +2372,14 @@ mod tests {
                 candle_pattern: Some(CandlePatternType::BullishEngulfing),
                 indicator: None, price_field: None, constant_value: None,
                 time_field: None, offset: None,
-                compound_left: None, compound_op: None, compound_right: None,
+                compound_left: None, compound_op: None, compound_right: None, percent_of: None, transform: None,
             },
             comparator: Comparator::Equal,
             right_operand: constant_operand(1.0),
             logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         }];
         let cache = IndicatorCache::new();
         assert!(evaluate_rules(&rules, 1, &cache, &candles, None, None, Some(&pc), 0));
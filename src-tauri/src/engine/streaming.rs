@@ -16,7 +16,7 @@
 use std::collections::HashMap;
 
 use crate::models::candle::Candle;
-use crate::models::strategy::{IndicatorType, OperandType, Strategy};
+use crate::models::strategy::{AtrSmoothing, EmaSeed, IndicatorType, OperandType, RsiSmoothing, Strategy};
 
 use super::strategy::IndicatorCache;
 
@@ -96,11 +96,26 @@ pub enum IndicatorStreamState {
         window_sum: f64,
         /// Sum of squares of the last `period` closes (constant during tick sub-loop).
         window_sum_sq: f64,
+        /// Divide the variance by N-1 instead of N.
+        sample: bool,
     },
 
-    /// ATR(period): Wilder smoothed ATR + prev bar OHLC — O(1) update.
+    /// Linear Regression(period, std_dev): sums over the previous `period - 1` closes at
+    /// x = 0..period-2; the running close completes the window at x = period-1.
+    /// Residual variance comes from `SSR = Σy² - a·Σy - b·Σxy`, so each tick is O(1).
+    LinearRegression {
+        period: usize,
+        std_dev_mult: f64,
+        window_sum: f64,
+        window_sum_xy: f64,
+        window_sum_sq: f64,
+    },
+
+    /// ATR(period): Wilder or EMA smoothed ATR + prev bar OHLC — O(1) update.
+    /// SMA-smoothed ATR needs the TR window and uses `LastValue` instead.
     Atr {
         period: usize,
+        smoothing: AtrSmoothing,
         prev_atr: f64,
         prev_close: f64,
     },
@@ -171,9 +186,21 @@ pub fn build_streaming_state(
                         IndicatorStreamState::Sma { period, window_sum }
                     }
 
+                    IndicatorType::LinearRegression => {
+                        let period = config.params.period.unwrap_or(14);
+                        let (window_sum, window_sum_xy, window_sum_sq) =
+                            extract_linreg_sums(candles, bar_index, period);
+                        IndicatorStreamState::LinearRegression {
+                            period,
+                            std_dev_mult: config.params.std_dev.unwrap_or(2.0),
+                            window_sum,
+                            window_sum_xy,
+                            window_sum_sq,
+                        }
+                    }
+
                     IndicatorType::EMA
                     | IndicatorType::HullMA
-                    | IndicatorType::LinearRegression
                     | IndicatorType::LaguerreRSI => {
                         let period = config.params.period.unwrap_or(14);
                         let prev_ema = cache
@@ -183,7 +210,9 @@ pub fn build_streaming_state(
                         IndicatorStreamState::Ema { period, prev_ema }
                     }
 
-                    IndicatorType::RSI => {
+                    IndicatorType::RSI
+                        if config.params.rsi_smoothing.unwrap_or_default() == RsiSmoothing::Wilder =>
+                    {
                         let period = config.params.period.unwrap_or(14);
                         let (avg_gain, avg_loss) =
                             extract_rsi_tail(candles, period, bar_index);
@@ -203,8 +232,9 @@ pub fn build_streaming_state(
                         let fast = config.params.fast_period.unwrap_or(12);
                         let slow = config.params.slow_period.unwrap_or(26);
                         let signal = config.params.signal_period.unwrap_or(9);
-                        let prev_fast_ema = extract_ema_tail(candles, fast, bar_index);
-                        let prev_slow_ema = extract_ema_tail(candles, slow, bar_index);
+                        let seed = config.params.ema_seed.unwrap_or_default();
+                        let prev_fast_ema = extract_ema_tail(candles, fast, bar_index, seed);
+                        let prev_slow_ema = extract_ema_tail(candles, slow, bar_index, seed);
                         let prev_signal_ema = cache
                             .get(&cache_hash_key)
                             .and_then(|o| o.secondary.as_ref().and_then(|s| s.get(bar_index).copied()))
@@ -228,10 +258,13 @@ pub fn build_streaming_state(
                             std_dev_mult,
                             window_sum,
                             window_sum_sq,
+                            sample: config.params.sample_stddev.unwrap_or(false),
                         }
                     }
 
-                    IndicatorType::ATR | IndicatorType::TrueRange => {
+                    IndicatorType::ATR | IndicatorType::TrueRange
+                        if config.params.atr_smoothing.unwrap_or_default() != AtrSmoothing::Sma =>
+                    {
                         let period = config.params.period.unwrap_or(14);
                         let prev_atr = cache
                             .get(&cache_hash_key)
@@ -243,6 +276,7 @@ pub fn build_streaming_state(
                             .unwrap_or(f64::NAN);
                         IndicatorStreamState::Atr {
                             period,
+                            smoothing: config.params.atr_smoothing.unwrap_or_default(),
                             prev_atr,
                             prev_close,
                         }
@@ -409,8 +443,10 @@ pub fn compute_streaming_value(
             std_dev_mult,
             window_sum,
             window_sum_sq,
+            sample,
         } => {
-            if window_sum.is_nan() || *period == 0 {
+            // A single-bar sample window has no N-1 estimate (rejected by validation)
+            if window_sum.is_nan() || *period == 0 || (*sample && *period < 2) {
                 return nan_sv();
             }
             let n = *period as f64;
@@ -419,7 +455,11 @@ pub fn compute_streaming_value(
             let sum_sq = window_sum_sq + running_close * running_close;
             let mean = sum / n;
             // abs() for floating-point numerical stability (tiny negatives near zero)
-            let variance = (sum_sq / n - mean * mean).abs();
+            let mut variance = (sum_sq / n - mean * mean).abs();
+            if *sample {
+                // Rescale population variance to the N-1 estimator
+                variance *= n / (n - 1.0);
+            }
             let std_dev = variance.sqrt();
             // primary=middle, secondary=upper, tertiary=lower
             StreamingSingleValue {
@@ -429,8 +469,38 @@ pub fn compute_streaming_value(
             }
         }
 
+        IndicatorStreamState::LinearRegression {
+            period,
+            std_dev_mult,
+            window_sum,
+            window_sum_xy,
+            window_sum_sq,
+        } => {
+            if window_sum.is_nan() || *period < 2 {
+                return nan_sv();
+            }
+            let n = *period as f64;
+            let sum_x = n * (n - 1.0) / 2.0;
+            let sum_x2 = (n - 1.0) * n * (2.0 * n - 1.0) / 6.0;
+            let sum_y = window_sum + running_close;
+            let sum_xy = window_sum_xy + (n - 1.0) * running_close;
+            let sum_sq = window_sum_sq + running_close * running_close;
+            let b = (n * sum_xy - sum_x * sum_y) / (n * sum_x2 - sum_x * sum_x);
+            let a = (sum_y - b * sum_x) / n;
+            let fitted = a + b * (n - 1.0);
+            // abs() for floating-point numerical stability (tiny negatives near zero)
+            let band = std_dev_mult * ((sum_sq - a * sum_y - b * sum_xy).abs() / n).sqrt();
+            // primary=fitted, secondary=upper, tertiary=lower (slope is read from the cache)
+            StreamingSingleValue {
+                primary: fitted,
+                secondary: Some(fitted + band),
+                tertiary: Some(fitted - band),
+            }
+        }
+
         IndicatorStreamState::Atr {
             period,
+            smoothing,
             prev_atr,
             prev_close,
         } => {
@@ -441,8 +511,12 @@ pub fn compute_streaming_value(
                 .max((running_high - prev_close).abs())
                 .max((running_low - prev_close).abs());
             let pf = *period as f64;
+            let primary = match smoothing {
+                AtrSmoothing::Ema => prev_atr + (tr - prev_atr) * 2.0 / (pf + 1.0),
+                _ => (prev_atr * (pf - 1.0) + tr) / pf,
+            };
             StreamingSingleValue {
-                primary: (prev_atr * (pf - 1.0) + tr) / pf,
+                primary,
                 secondary: None,
                 tertiary: None,
             }
@@ -505,6 +579,22 @@ fn extract_window_sums(candles: &[Candle], bar_index: usize, period: usize) -> (
     (sum, sum_sq)
 }
 
+/// Extract `(Σy, Σxy, Σy²)` over the `period - 1` completed closes ending at `bar_index`,
+/// with x = 0 at the oldest. NaN when there are too few bars.
+fn extract_linreg_sums(candles: &[Candle], bar_index: usize, period: usize) -> (f64, f64, f64) {
+    if period < 2 || bar_index + 2 < period {
+        return (f64::NAN, f64::NAN, f64::NAN);
+    }
+    let (mut sum, mut sum_xy, mut sum_sq) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, idx) in ((bar_index + 2 - period)..=bar_index).enumerate() {
+        let c = candles[idx].close;
+        sum += c;
+        sum_xy += x as f64 * c;
+        sum_sq += c * c;
+    }
+    (sum, sum_xy, sum_sq)
+}
+
 /// Replay RSI Wilder smoothing up to `bar_index` to extract (avg_gain, avg_loss).
 /// O(n) but called only once per bar.
 fn extract_rsi_tail(candles: &[Candle], period: usize, bar_index: usize) -> (f64, f64) {
@@ -547,15 +637,18 @@ fn extract_rsi_tail(candles: &[Candle], period: usize, bar_index: usize) -> (f64
 
 /// Compute EMA at `bar_index` by replaying from scratch. O(n).
 /// Used to extract fast/slow EMA tail state for MACD streaming.
-fn extract_ema_tail(candles: &[Candle], period: usize, bar_index: usize) -> f64 {
-    if period == 0 || bar_index < period - 1 || candles.len() <= bar_index {
+fn extract_ema_tail(candles: &[Candle], period: usize, bar_index: usize, seed: EmaSeed) -> f64 {
+    if period == 0 || candles.len() <= bar_index {
         return f64::NAN;
     }
     let mult = 2.0 / (period as f64 + 1.0);
-    let mut ema_val: f64 =
-        candles[..period].iter().map(|c| c.close).sum::<f64>() / period as f64;
-    for i in period..=bar_index {
-        ema_val = (candles[i].close - ema_val) * mult + ema_val;
+    let (mut ema_val, first) = match seed {
+        EmaSeed::FirstValue => (candles[0].close, 1),
+        EmaSeed::SmaSeed if bar_index < period - 1 => return f64::NAN,
+        EmaSeed::SmaSeed => (candles[..period].iter().map(|c| c.close).sum::<f64>() / period as f64, period),
+    };
+    for candle in candles.iter().take(bar_index + 1).skip(first) {
+        ema_val = (candle.close - ema_val) * mult + ema_val;
     }
     ema_val
 }
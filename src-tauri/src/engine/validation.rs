@@ -0,0 +1,526 @@
+use serde::Serialize;
+
+use crate::engine::position::RR_FALLBACK_RISK_PIPS;
+use crate::engine::strategy::MAX_OPERAND_OFFSET;
+use crate::errors::AppError;
+use crate::models::strategy::{
+    IndicatorConfig, IndicatorType, Operand, OperandType, Rule, RuleGroup,
+    Strategy, TakeProfitType, TradeDirection, TrailingStopType,
+};
+
+/// How serious a [`ValidationIssue`] is.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The strategy cannot run as configured.
+    Error,
+    /// The strategy runs, but likely not as intended.
+    Warning,
+}
+
+/// A single problem found by [`validate_strategy`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    /// Path to the offending field, e.g. `long_entry_rules[0].left_operand.indicator.params.period`.
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { severity: IssueSeverity::Error, field: field.into(), message: message.into() }
+    }
+
+    fn warning(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { severity: IssueSeverity::Warning, field: field.into(), message: message.into() }
+    }
+}
+
+/// Check a strategy for configurations that would fail or silently never trade.
+pub fn validate_strategy(strategy: &Strategy) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    check_entries(strategy, &mut issues);
+    check_exits(strategy, &mut issues);
+
+    for_each_operand(strategy, &mut |field, operand| check_operand(field, operand, &mut issues));
+    for_each_rule(strategy, &mut |field, rule| {
+        // A negative or NaN tolerance makes `Equal` silently never fire
+        if let Some(tol) = rule.tolerance.filter(|t| !t.is_finite() || *t < 0.0) {
+            issues.push(ValidationIssue::error(
+                format!("{field}.tolerance"),
+                format!("Tolerance must be a finite number of at least 0 (got {tol})"),
+            ));
+        }
+    });
+
+    if let Some(ref sl) = strategy.stop_loss {
+        check_distance("stop_loss", sl.value, sl.sl_type.uses_atr(), sl.atr_period, &mut issues);
+    }
+    if let Some(ref tp) = strategy.take_profit {
+        check_distance("take_profit", tp.value, tp.tp_type == TakeProfitType::ATR, tp.atr_period, &mut issues);
+        if tp.tp_type == TakeProfitType::RiskReward && strategy.stop_loss.is_none() {
+            issues.push(ValidationIssue::warning(
+                "take_profit.tp_type",
+                format!(
+                    "Risk:Reward take profit without a stop loss assumes a {} pip risk",
+                    RR_FALLBACK_RISK_PIPS
+                ),
+            ));
+        }
+    }
+    if let Some(ref ts) = strategy.trailing_stop {
+        check_distance("trailing_stop", ts.value, ts.ts_type == TrailingStopType::ATR, ts.atr_period, &mut issues);
+        if ts.ts_type == TrailingStopType::Step && ts.step_pips.is_some_and(|s| s.is_nan() || s <= 0.0) {
+            issues.push(ValidationIssue::error("trailing_stop.step_pips", "Trailing step must be greater than 0"));
+        }
+    }
+
+    issues
+}
+
+/// Run [`validate_strategy`] and fail on any error. Returns the warnings as
+/// `"field: message"` strings for inclusion in a result.
+pub fn ensure_valid(strategy: &Strategy) -> Result<Vec<String>, AppError> {
+    let (errors, warnings): (Vec<_>, Vec<_>) = validate_strategy(strategy)
+        .into_iter()
+        .partition(|i| i.severity == IssueSeverity::Error);
+    let describe = |i: &ValidationIssue| format!("{}: {}", i.field, i.message);
+    if !errors.is_empty() {
+        return Err(AppError::InvalidConfig(
+            errors.iter().map(describe).collect::<Vec<_>>().join("; "),
+        ));
+    }
+    Ok(warnings.iter().map(describe).collect())
+}
+
+/// Reject indicator parameters that make an optimizer combination pointless to
+/// backtest: anything [`check_indicator`] reports (zero periods, non-positive std_dev,
+/// MACD/PPO fast period not below slow) or a lookback longer than the `bars` of data.
+pub fn check_indicator_params(strategy: &Strategy, bars: usize) -> Result<(), AppError> {
+    // Every check_indicator issue sits under `.params.`, including the fast/slow warning
+    if let Some(issue) = validate_strategy(strategy).into_iter().find(|i| i.field.contains(".params.")) {
+        return Err(AppError::InvalidIndicatorParams(format!("{}: {}", issue.field, issue.message)));
+    }
+    let mut lookback = 0;
+    for_each_operand(strategy, &mut |_, operand| lookback = lookback.max(operand_lookback(operand)));
+    if lookback > bars {
+        return Err(AppError::InsufficientData { needed: lookback, available: bars });
+    }
+    Ok(())
+}
+
+/// Call `f` with the field path of every rule in the strategy, flat rules first,
+/// then rules nested in groups.
+fn for_each_rule(strategy: &Strategy, f: &mut impl FnMut(&str, &Rule)) {
+    for (field, rules) in [
+        ("long_entry_rules", &strategy.long_entry_rules),
+        ("short_entry_rules", &strategy.short_entry_rules),
+        ("long_exit_rules", &strategy.long_exit_rules),
+        ("short_exit_rules", &strategy.short_exit_rules),
+    ] {
+        for (r, rule) in rules.iter().enumerate() {
+            f(&format!("{field}[{r}]"), rule);
+        }
+    }
+    for (field, groups) in [
+        ("long_entry_groups", &strategy.long_entry_groups),
+        ("short_entry_groups", &strategy.short_entry_groups),
+        ("long_exit_groups", &strategy.long_exit_groups),
+        ("short_exit_groups", &strategy.short_exit_groups),
+    ] {
+        for (g, group) in groups.iter().enumerate() {
+            for (r, rule) in group.rules.iter().enumerate() {
+                f(&format!("{field}[{g}].rules[{r}]"), rule);
+            }
+        }
+    }
+}
+
+/// Call `f` with the field path of every rule operand in the strategy, in
+/// [`for_each_rule`] order. Compound sub-operands are left to `f`.
+fn for_each_operand(strategy: &Strategy, f: &mut impl FnMut(&str, &Operand)) {
+    for_each_rule(strategy, &mut |field, rule| {
+        f(&format!("{field}.left_operand"), &rule.left_operand);
+        f(&format!("{field}.right_operand"), &rule.right_operand);
+    });
+}
+
+/// Longest indicator lookback (period, slow, %K or %D) used by `operand`, 0 if none.
+fn operand_lookback(operand: &Operand) -> usize {
+    match operand.operand_type {
+        OperandType::Indicator => operand.indicator.as_ref().map_or(0, |config| {
+            let p = &config.params;
+            [p.period, p.slow_period, p.k_period, p.d_period].into_iter().flatten().max().unwrap_or(0)
+        }),
+        OperandType::Compound => [&operand.compound_left, &operand.compound_right]
+            .into_iter()
+            .flatten()
+            .map(|o| operand_lookback(o))
+            .max()
+            .unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn has_entries(rules: &[Rule], groups: &[RuleGroup]) -> bool {
+    !rules.is_empty() || groups.iter().any(|g| !g.rules.is_empty())
+}
+
+fn check_entries(strategy: &Strategy, issues: &mut Vec<ValidationIssue>) {
+    let long = has_entries(&strategy.long_entry_rules, &strategy.long_entry_groups);
+    let short = has_entries(&strategy.short_entry_rules, &strategy.short_entry_groups);
+    match strategy.trade_direction {
+        TradeDirection::Long if !long => issues.push(ValidationIssue::error(
+            "long_entry_rules",
+            "No long entry rules: the strategy can never open a trade",
+        )),
+        TradeDirection::Short if !short => issues.push(ValidationIssue::error(
+            "short_entry_rules",
+            "No short entry rules: the strategy can never open a trade",
+        )),
+        TradeDirection::Both if !long && !short => issues.push(ValidationIssue::error(
+            "long_entry_rules",
+            "No entry rules: the strategy can never open a trade",
+        )),
+        TradeDirection::Both if !long => issues.push(ValidationIssue::warning(
+            "long_entry_rules",
+            "No long entry rules: only short trades will be opened",
+        )),
+        TradeDirection::Both if !short => issues.push(ValidationIssue::warning(
+            "short_entry_rules",
+            "No short entry rules: only long trades will be opened",
+        )),
+        _ => {}
+    }
+}
+
+fn check_exits(strategy: &Strategy, issues: &mut Vec<ValidationIssue>) {
+    let has_rules = has_entries(&strategy.long_exit_rules, &strategy.long_exit_groups)
+        || has_entries(&strategy.short_exit_rules, &strategy.short_exit_groups);
+    let has_other_exit = strategy.stop_loss.is_some()
+        || strategy.take_profit.is_some()
+        || strategy.trailing_stop.is_some()
+        || strategy.close_after_bars.is_some()
+        || strategy.close_trades_at.is_some()
+        || strategy.session_exit.is_some();
+    if !has_rules && !has_other_exit {
+        issues.push(ValidationIssue::warning(
+            "long_exit_rules",
+            "No exit rules, stops or time exits: positions only close at the end of data",
+        ));
+    }
+}
+
+fn check_distance(
+    field: &str,
+    value: f64,
+    is_atr: bool,
+    atr_period: Option<usize>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    if value.is_nan() || value <= 0.0 {
+        issues.push(ValidationIssue::error(
+            format!("{field}.value"),
+            format!("Must be greater than 0 (got {value})"),
+        ));
+    }
+    if is_atr {
+        match atr_period {
+            None => issues.push(ValidationIssue::error(
+                format!("{field}.atr_period"),
+                "ATR type requires an ATR period",
+            )),
+            Some(0) => issues.push(ValidationIssue::error(
+                format!("{field}.atr_period"),
+                "ATR period must be greater than 0",
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+fn check_operand(field: &str, operand: &Operand, issues: &mut Vec<ValidationIssue>) {
+    if let Some(offset) = operand.offset.filter(|&o| o > MAX_OPERAND_OFFSET) {
+        issues.push(ValidationIssue::error(
+            format!("{field}.offset"),
+            format!("Offset of {} bars exceeds the maximum of {}", offset, MAX_OPERAND_OFFSET),
+        ));
+    }
+    match operand.operand_type {
+        OperandType::Indicator => match operand.indicator {
+            Some(ref config) => check_indicator(&format!("{field}.indicator"), config, issues),
+            None => issues.push(ValidationIssue::error(field, "Indicator operand has no indicator")),
+        },
+        OperandType::Price if operand.price_field.is_none() => {
+            issues.push(ValidationIssue::error(field, "Price operand has no price field"))
+        }
+        OperandType::Constant if operand.constant_value.is_none() => {
+            issues.push(ValidationIssue::error(field, "Constant operand has no value"))
+        }
+        OperandType::Compound => {
+            if let Some(ref left) = operand.compound_left {
+                check_operand(&format!("{field}.compound_left"), left, issues);
+            }
+            if let Some(ref right) = operand.compound_right {
+                check_operand(&format!("{field}.compound_right"), right, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_indicator(field: &str, config: &IndicatorConfig, issues: &mut Vec<ValidationIssue>) {
+    let p = &config.params;
+    for (name, value) in [
+        ("period", p.period),
+        ("fast_period", p.fast_period),
+        ("slow_period", p.slow_period),
+        ("signal_period", p.signal_period),
+        ("k_period", p.k_period),
+        ("d_period", p.d_period),
+        ("slowing", p.slowing),
+    ] {
+        if value == Some(0) {
+            issues.push(ValidationIssue::error(
+                format!("{field}.params.{name}"),
+                format!("{:?} {} must be greater than 0", config.indicator_type, name),
+            ));
+        }
+    }
+    if let Some(sd) = p.std_dev {
+        if sd.is_nan() || sd <= 0.0 {
+            issues.push(ValidationIssue::error(
+                format!("{field}.params.std_dev"),
+                format!("{:?} std_dev must be greater than 0", config.indicator_type),
+            ));
+        }
+    }
+    // The N-1 estimator is undefined on one bar (a zero period is reported above)
+    if p.sample_stddev == Some(true) && p.period == Some(1) {
+        issues.push(ValidationIssue::error(
+            format!("{field}.params.sample_stddev"),
+            format!("{:?} sample standard deviation requires a period of at least 2", config.indicator_type),
+        ));
+    }
+    if matches!(config.indicator_type, IndicatorType::MACD | IndicatorType::PPO) {
+        if let (Some(fast), Some(slow)) = (p.fast_period, p.slow_period) {
+            if fast >= slow {
+                issues.push(ValidationIssue::warning(
+                    format!("{field}.params.fast_period"),
+                    format!(
+                        "{:?} fast period ({}) is not shorter than slow period ({})",
+                        config.indicator_type, fast, slow
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(patch: serde_json::Value) -> Strategy {
+        let mut value = serde_json::json!({
+            "id": "s1",
+            "name": "Validation test",
+            "created_at": "",
+            "updated_at": "",
+            "long_entry_rules": [{
+                "id": "r1",
+                "left_operand": {
+                    "operand_type": "Indicator",
+                    "indicator": { "indicator_type": "RSI", "params": { "period": 14 } }
+                },
+                "comparator": "LessThan",
+                "right_operand": { "operand_type": "Constant", "constant_value": 30.0 }
+            }],
+            "long_exit_rules": [],
+            "position_sizing": { "sizing_type": "FixedLots", "value": 1.0 },
+            "stop_loss": { "sl_type": "Pips", "value": 50.0 },
+            "trading_costs": {
+                "spread_pips": 1.0,
+                "commission_type": "FixedPerLot",
+                "commission_value": 0.0,
+                "slippage_pips": 0.0,
+                "slippage_random": false
+            },
+            "trade_direction": "Long"
+        });
+        for (k, v) in patch.as_object().unwrap() {
+            value[k] = v.clone();
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn errors(strategy: &Strategy) -> Vec<ValidationIssue> {
+        validate_strategy(strategy)
+            .into_iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_strategy_has_no_issues() {
+        assert!(validate_strategy(&strategy(serde_json::json!({}))).is_empty());
+    }
+
+    #[test]
+    fn test_empty_entry_rules() {
+        let s = strategy(serde_json::json!({ "long_entry_rules": [] }));
+        let errs = errors(&s);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].field, "long_entry_rules");
+        assert!(matches!(ensure_valid(&s), Err(AppError::InvalidConfig(_))));
+
+        // With both directions, one empty side is only a warning
+        let both = strategy(serde_json::json!({ "trade_direction": "Both" }));
+        let issues = validate_strategy(&both);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert_eq!(issues[0].field, "short_entry_rules");
+        assert_eq!(ensure_valid(&both).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_missing_atr_period() {
+        let s = strategy(serde_json::json!({
+            "stop_loss": { "sl_type": "ATR", "value": 2.0 },
+            "trailing_stop": { "ts_type": "ATR", "value": 1.5, "atr_period": 0 }
+        }));
+        let fields: Vec<_> = errors(&s).into_iter().map(|i| i.field).collect();
+        assert_eq!(fields, vec!["stop_loss.atr_period", "trailing_stop.atr_period"]);
+    }
+
+    #[test]
+    fn test_risk_reward_tp_without_sl_warns() {
+        let s = strategy(serde_json::json!({
+            "stop_loss": null,
+            "take_profit": { "tp_type": "RiskReward", "value": 2.0 }
+        }));
+        let issues = validate_strategy(&s);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert_eq!(issues[0].field, "take_profit.tp_type");
+    }
+
+    #[test]
+    fn test_operand_offsets() {
+        let rule = |offset: i64| serde_json::json!([{
+            "id": "r1",
+            "left_operand": { "operand_type": "Price", "price_field": "Close", "offset": offset },
+            "comparator": "GreaterThan",
+            "right_operand": { "operand_type": "Price", "price_field": "Open" }
+        }]);
+
+        assert!(validate_strategy(&strategy(serde_json::json!({ "long_entry_rules": rule(3) }))).is_empty());
+
+        let s = strategy(serde_json::json!({ "long_entry_rules": rule(MAX_OPERAND_OFFSET as i64 + 1) }));
+        let errs = errors(&s);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].field, "long_entry_rules[0].left_operand.offset");
+
+        // Negative offsets would read future bars and never deserialize
+        let mut value = serde_json::to_value(strategy(serde_json::json!({}))).unwrap();
+        value["long_entry_rules"] = rule(-1);
+        let err = serde_json::from_value::<Strategy>(value).unwrap_err().to_string();
+        assert!(err.contains("operand offset -1 would read a future bar"), "{err}");
+    }
+
+    #[test]
+    fn test_invalid_equal_tolerance() {
+        let rule = |tolerance: f64| serde_json::json!([{
+            "id": "r1",
+            "left_operand": { "operand_type": "Price", "price_field": "Close" },
+            "comparator": "Equal",
+            "right_operand": { "operand_type": "Price", "price_field": "Open" },
+            "tolerance": tolerance
+        }]);
+        assert!(validate_strategy(&strategy(serde_json::json!({ "long_entry_rules": rule(0.0) }))).is_empty());
+
+        let s = strategy(serde_json::json!({ "long_entry_rules": rule(-0.5) }));
+        let errs = errors(&s);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].field, "long_entry_rules[0].tolerance");
+
+        let mut s = strategy(serde_json::json!({}));
+        s.long_entry_rules[0].tolerance = Some(f64::NAN);
+        let msg = ensure_valid(&s).unwrap_err().to_string();
+        assert!(msg.contains("long_entry_rules[0].tolerance"), "{msg}");
+    }
+
+    #[test]
+    fn test_zero_indicator_period() {
+        let s = strategy(serde_json::json!({
+            "long_exit_groups": [{
+                "id": "g1",
+                "internal": "AND",
+                "rules": [{
+                    "id": "x1",
+                    "left_operand": { "operand_type": "Price", "price_field": "Close" },
+                    "comparator": "CrossBelow",
+                    "right_operand": {
+                        "operand_type": "Indicator",
+                        "indicator": { "indicator_type": "SMA", "params": { "period": 0 } }
+                    }
+                }]
+            }]
+        }));
+        let errs = errors(&s);
+        assert_eq!(errs.len(), 1);
+        assert_eq!(
+            errs[0].field,
+            "long_exit_groups[0].rules[0].right_operand.indicator.params.period"
+        );
+        let msg = ensure_valid(&s).unwrap_err().to_string();
+        assert!(msg.contains("SMA period must be greater than 0"), "{msg}");
+    }
+
+    #[test]
+    fn test_sample_stddev_needs_two_bars() {
+        let bands = |period: usize| strategy(serde_json::json!({
+            "long_entry_rules": [{
+                "id": "r1",
+                "left_operand": { "operand_type": "Price", "price_field": "Close" },
+                "comparator": "GreaterThan",
+                "right_operand": {
+                    "operand_type": "Indicator",
+                    "indicator": { "indicator_type": "BollingerBands", "params": { "period": period, "std_dev": 2.0, "sample_stddev": true } }
+                }
+            }]
+        }));
+        assert!(errors(&bands(2)).is_empty());
+        let errs = errors(&bands(1));
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].field, "long_entry_rules[0].right_operand.indicator.params.sample_stddev");
+    }
+
+    #[test]
+    fn test_check_indicator_params_for_optimizer_combos() {
+        let stochastic = |d_period: usize| strategy(serde_json::json!({
+            "long_entry_rules": [{
+                "id": "r1",
+                "left_operand": {
+                    "operand_type": "Indicator",
+                    "indicator": {
+                        "indicator_type": "Stochastic",
+                        "params": { "k_period": 14, "d_period": d_period, "slowing": 3 }
+                    }
+                },
+                "comparator": "LessThan",
+                "right_operand": { "operand_type": "Constant", "constant_value": 20.0 }
+            }]
+        }));
+
+        assert!(check_indicator_params(&stochastic(3), 100).is_ok());
+        assert!(matches!(
+            check_indicator_params(&stochastic(150), 100),
+            Err(AppError::InsufficientData { needed: 150, available: 100 })
+        ));
+        let Err(AppError::InvalidIndicatorParams(msg)) = check_indicator_params(&stochastic(0), 100) else {
+            panic!("zero d_period should be rejected");
+        };
+        assert!(msg.starts_with("long_entry_rules[0].left_operand.indicator.params.d_period"), "{msg}");
+    }
+}
@@ -11,7 +11,7 @@ use crate::models::result::{
 };
 
 use super::executor::{run_backtest, SubBarData};
-use super::optimizer::{apply_params, run_genetic_algorithm, run_grid_search};
+use super::optimizer::{apply_params, run_genetic_algorithm, run_grid_search, run_random_search};
 
 /// Run a Walk-Forward Analysis.
 ///
@@ -131,10 +131,36 @@ pub fn run_walk_forward(
                     |_, _, _, _| {},
                 )?
             }
+            OptimizationMethod::RandomSearch => {
+                let rs_cfg = opt_config.random_search_config.as_ref().ok_or_else(|| {
+                    AppError::InvalidConfig(
+                        "RandomSearchConfig required for walk-forward random search mode".into(),
+                    )
+                })?;
+                run_random_search(
+                    in_sample,
+                    &sub_bars,
+                    strategy,
+                    &opt_config.backtest_config,
+                    instrument,
+                    &opt_config.parameter_ranges,
+                    &opt_config.objectives,
+                    rs_cfg,
+                    cancel_flag,
+                    |_, _, _, _| {},
+                )?
+            }
         };
 
+        // The optimizers return partial results on cancel; a walk-forward window
+        // optimized on a partial search isn't meaningful, so abort the whole run.
+        if cancel_flag.load(Ordering::Relaxed) {
+            return Err(AppError::OptimizationCancelled);
+        }
+
         let best = opt_results
-            .first()
+            .iter()
+            .find(|r| r.failure_reason.is_none())
             .ok_or_else(|| AppError::OptimizationError(format!("No results for window {}", window_idx)))?;
 
         // Reconstruct parameter vector in range order (HashMap → Vec<f64>)
@@ -255,6 +281,19 @@ fn combine_oos_metrics(windows: &[WalkForwardWindowResult]) -> BacktestMetrics {
     } else {
         0.0
     };
+
+    // Per-regime win rates are pooled by weighting each window's rate by its trade count.
+    let trending_trades: usize = windows.iter().map(|w| w.out_of_sample_metrics.trending_trades).sum();
+    let ranging_trades: usize = windows.iter().map(|w| w.out_of_sample_metrics.ranging_trades).sum();
+    let trade_weighted = |total: usize, field: fn(&BacktestMetrics) -> (usize, f64)| {
+        if total == 0 {
+            return 0.0;
+        }
+        windows.iter().map(|w| {
+            let (trades, rate) = field(&w.out_of_sample_metrics);
+            trades as f64 * rate
+        }).sum::<f64>() / total as f64
+    };
     let profit_factor = if gross_loss.abs() > f64::EPSILON {
         gross_profit / gross_loss.abs()
     } else if gross_profit > 0.0 {
@@ -353,7 +392,36 @@ fn combine_oos_metrics(windows: &[WalkForwardWindowResult]) -> BacktestMetrics {
         monthly_returns: vec![],
         total_swap_charged: windows.iter().map(|w| w.out_of_sample_metrics.total_swap_charged).sum(),
         total_commission_charged: windows.iter().map(|w| w.out_of_sample_metrics.total_commission_charged).sum(),
+        total_spread_cost: windows.iter().map(|w| w.out_of_sample_metrics.total_spread_cost).sum(),
+        total_slippage_cost: windows.iter().map(|w| w.out_of_sample_metrics.total_slippage_cost).sum(),
+        total_costs: windows.iter().map(|w| w.out_of_sample_metrics.total_costs).sum(),
+        gross_pnl_before_costs: windows.iter().map(|w| w.out_of_sample_metrics.gross_pnl_before_costs).sum(),
         temporal_consistency: 0.0,
+        benchmark_return_pct: 0.0,
+        alpha: 0.0,
+        beta: 0.0,
+        correlation: 0.0,
+        exposure_pct: windows.iter().map(|w| w.out_of_sample_metrics.exposure_pct).sum::<f64>() / n,
+        avg_simultaneous_positions: windows.iter().map(|w| w.out_of_sample_metrics.avg_simultaneous_positions).sum::<f64>() / n,
+        long_exposure_pct: windows.iter().map(|w| w.out_of_sample_metrics.long_exposure_pct).sum::<f64>() / n,
+        short_exposure_pct: windows.iter().map(|w| w.out_of_sample_metrics.short_exposure_pct).sum::<f64>() / n,
+        drawdown_episodes: 0,
+        longest_drawdown_bars: 0,
+        longest_drawdown_time: String::new(),
+        max_drawdown_recovery_bars: 0,
+        max_drawdown_recovery_time: String::new(),
+        avg_drawdown_depth_pct: 0.0,
+        avg_drawdown_duration_bars: 0.0,
+        avg_minutes_between_trades: windows.iter().map(|w| w.out_of_sample_metrics.avg_minutes_between_trades).sum::<f64>() / n,
+        clustered_trades: windows.iter().map(|w| w.out_of_sample_metrics.clustered_trades).sum(),
+        max_trades_per_hour: windows.iter().map(|w| w.out_of_sample_metrics.max_trades_per_hour).max().unwrap_or(0),
+        overtrading: windows.iter().any(|w| w.out_of_sample_metrics.overtrading),
+        trending_trades,
+        trending_win_rate_pct: trade_weighted(trending_trades, |m| (m.trending_trades, m.trending_win_rate_pct)),
+        trending_net_profit: windows.iter().map(|w| w.out_of_sample_metrics.trending_net_profit).sum(),
+        ranging_trades,
+        ranging_win_rate_pct: trade_weighted(ranging_trades, |m| (m.ranging_trades, m.ranging_win_rate_pct)),
+        ranging_net_profit: windows.iter().map(|w| w.out_of_sample_metrics.ranging_net_profit).sum(),
     }
 }
 
@@ -408,6 +476,35 @@ fn default_metrics() -> BacktestMetrics {
         monthly_returns: vec![],
         total_swap_charged: 0.0,
         total_commission_charged: 0.0,
+        total_spread_cost: 0.0,
+        total_slippage_cost: 0.0,
+        total_costs: 0.0,
+        gross_pnl_before_costs: 0.0,
         temporal_consistency: 0.0,
+        benchmark_return_pct: 0.0,
+        alpha: 0.0,
+        beta: 0.0,
+        correlation: 0.0,
+        exposure_pct: 0.0,
+        avg_simultaneous_positions: 0.0,
+        long_exposure_pct: 0.0,
+        short_exposure_pct: 0.0,
+        drawdown_episodes: 0,
+        longest_drawdown_bars: 0,
+        longest_drawdown_time: String::new(),
+        max_drawdown_recovery_bars: 0,
+        max_drawdown_recovery_time: String::new(),
+        avg_drawdown_depth_pct: 0.0,
+        avg_drawdown_duration_bars: 0.0,
+        avg_minutes_between_trades: 0.0,
+        clustered_trades: 0,
+        max_trades_per_hour: 0,
+        overtrading: false,
+        trending_trades: 0,
+        trending_win_rate_pct: 0.0,
+        trending_net_profit: 0.0,
+        ranging_trades: 0,
+        ranging_win_rate_pct: 0.0,
+        ranging_net_profit: 0.0,
     }
 }
@@ -65,6 +65,9 @@ pub enum AppError {
     #[error("Invalid indicator parameters: {0}")]
     InvalidIndicatorParams(String),
 
+    #[error("Volume data required: {0}")]
+    MissingVolumeData(String),
+
     // ── Optimization ──
     #[error("Optimization error: {0}")]
     OptimizationError(String),
@@ -131,6 +134,7 @@ impl From<&AppError> for ErrorResponse {
             AppError::NoDataInRange => "NO_DATA_IN_RANGE",
             AppError::InsufficientData { .. } => "INSUFFICIENT_DATA",
             AppError::InvalidIndicatorParams(_) => "INVALID_INDICATOR_PARAMS",
+            AppError::MissingVolumeData(_) => "MISSING_VOLUME_DATA",
             AppError::OptimizationError(_) => "OPTIMIZATION_ERROR",
             AppError::OptimizationCancelled => "OPTIMIZATION_CANCELLED",
             AppError::TooManyCombinations { .. } => "TOO_MANY_COMBINATIONS",
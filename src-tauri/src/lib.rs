@@ -37,6 +37,8 @@ pub struct AppState {
     pub builder_pause_flag: Arc<AtomicBool>,
     /// Cancellation flag for SR (Symbolic Regression) builder operations.
     pub sr_cancel_flag: Arc<AtomicBool>,
+    /// Full result set of the last optimization run with `keep_all_results` set.
+    pub last_optimization: Mutex<Option<models::result::RetainedOptimization>>,
 }
 
 /// Resolve the application data directory and ensure it exists.
@@ -96,6 +98,7 @@ pub fn run() {
         builder_cancel_flag: Arc::new(AtomicBool::new(false)),
         builder_pause_flag: Arc::new(AtomicBool::new(false)),
         sr_cancel_flag: Arc::new(AtomicBool::new(false)),
+        last_optimization: Mutex::new(None),
     };
 
     tauri::Builder::default()
@@ -109,18 +112,30 @@ pub fn run() {
             commands::get_symbols,
             commands::delete_symbol,
             commands::preview_data,
+            commands::count_rows,
             commands::run_backtest,
+            commands::run_backtest_stepped,
+            commands::run_backtest_batch,
             commands::cancel_backtest,
+            commands::save_backtest_result,
+            commands::list_backtest_results,
+            commands::load_backtest_result,
+            commands::delete_backtest_result,
             commands::save_strategy,
             commands::load_strategies,
             commands::delete_strategy,
+            commands::export_strategy_bundle,
+            commands::import_strategy_bundle,
             commands::run_optimization,
             commands::cancel_optimization,
             commands::export_trades_csv,
             commands::export_metrics_csv,
+            commands::export_optimization_csv,
             commands::export_report_html,
+            commands::export_equity_png,
             commands::export_tick_data_mt5,
             commands::generate_strategy_code,
+            commands::save_generated_code,
             commands::download_dukascopy,
             commands::cancel_download,
             commands::validate_license,
@@ -129,6 +144,7 @@ pub fn run() {
             commands::start_license_monitor,
             commands::run_walk_forward,
             commands::run_monte_carlo,
+            commands::seasonality_report,
             commands::transform_symbol_timezone,
             commands::start_builder,
             commands::stop_builder,
@@ -1,5 +1,6 @@
 use std::path::Path;
 
+use ring::hmac;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -13,11 +14,24 @@ pub enum LicenseTier {
     Pro,
 }
 
+/// How a validation attempt ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStatus {
+    /// The server confirmed the key.
+    Valid,
+    /// The server answered and rejected the key (or the input was empty).
+    Invalid,
+    /// The server could not be reached or returned a server-side error.
+    Unreachable,
+}
+
 /// Response returned to the frontend after validation.
 #[derive(Debug, Serialize)]
 pub struct LicenseResponse {
     pub valid: bool,
     pub tier: LicenseTier,
+    pub status: ValidationStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
@@ -27,10 +41,82 @@ pub struct LicenseResponse {
 pub struct SavedCredentials {
     pub username: String,
     pub license_key: String,
+    /// Unix timestamp (seconds) of the last successful online validation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_validated_at: Option<i64>,
+    /// Tier confirmed by the last successful online validation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_tier: Option<LicenseTier>,
+    /// Hex HMAC-SHA256 over username, key, `last_tier` and `last_validated_at`, so an edited
+    /// file can't grant a tier offline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl SavedCredentials {
+    fn new(username: &str, license_key: &str, tier: LicenseTier, validated_at: i64) -> Self {
+        Self {
+            username: username.to_string(),
+            license_key: license_key.to_string(),
+            last_validated_at: Some(validated_at),
+            last_tier: Some(tier),
+            signature: sign_validation(username, license_key, tier, validated_at),
+        }
+    }
+
+    /// The cached `(tier, validated_at)`, if present and its signature checks out.
+    fn verified_validation(&self) -> Option<(LicenseTier, i64)> {
+        let (tier, validated_at) = (self.last_tier?, self.last_validated_at?);
+        let tag = decode_hex(self.signature.as_deref()?)?;
+        let key = hmac::Key::new(hmac::HMAC_SHA256, CACHE_SIGNING_KEY?);
+        let message = validation_message(&self.username, &self.license_key, tier, validated_at);
+        hmac::verify(&key, message.as_bytes(), &tag).ok()?;
+        Some((tier, validated_at))
+    }
 }
 
 const VALIDATION_API: &str = "https://lb-quant.com/api/license/validate";
 
+/// How long the last-known tier is kept while the validation server is unreachable.
+pub const OFFLINE_GRACE_DAYS: i64 = 7;
+
+/// Key for the HMAC over cached validations, taken from `LBQ_LICENSE_CACHE_KEY` at build time.
+/// Builds without it never sign or trust a cached validation, so there is no offline grace;
+/// `build.rs` refuses to produce a release build without it.
+#[cfg(not(test))]
+const CACHE_SIGNING_KEY: Option<&[u8]> = match option_env!("LBQ_LICENSE_CACHE_KEY") {
+    Some(key) if !key.is_empty() => Some(key.as_bytes()),
+    _ => None,
+};
+#[cfg(test)]
+const CACHE_SIGNING_KEY: Option<&[u8]> = Some(b"license-cache-test-key");
+
+fn validation_message(username: &str, license_key: &str, tier: LicenseTier, validated_at: i64) -> String {
+    format!("{}\n{}\n{:?}\n{}", username, license_key, tier, validated_at)
+}
+
+fn sign_validation(username: &str, license_key: &str, tier: LicenseTier, validated_at: i64) -> Option<String> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, CACHE_SIGNING_KEY?);
+    let message = validation_message(username, license_key, tier, validated_at);
+    Some(
+        hmac::sign(&key, message.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 /// API response from the validation endpoint.
 #[derive(Debug, Deserialize)]
 struct ApiValidationResponse {
@@ -43,8 +129,13 @@ struct ApiValidationResponse {
 ///
 /// All users (free and pro) must provide a valid license key.
 /// Calls the remote validation API for every request.
-/// On network error → falls back to Free tier with error message.
+/// On network error → Free tier with `ValidationStatus::Unreachable`; callers decide
+/// whether the offline grace period applies (see [`apply_offline_grace`]).
 pub async fn validate_license(username: &str, license_key: &str) -> LicenseResponse {
+    validate_license_at(VALIDATION_API, username, license_key).await
+}
+
+async fn validate_license_at(api_url: &str, username: &str, license_key: &str) -> LicenseResponse {
     let username = username.trim();
     let key = license_key.trim();
 
@@ -52,6 +143,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
         return LicenseResponse {
             valid: false,
             tier: LicenseTier::Free,
+            status: ValidationStatus::Invalid,
             message: Some("Username is required".to_string()),
         };
     }
@@ -60,6 +152,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
         return LicenseResponse {
             valid: false,
             tier: LicenseTier::Free,
+            status: ValidationStatus::Invalid,
             message: Some("License key is required. Create a free account at lb-quant.com/register".to_string()),
         };
     }
@@ -68,7 +161,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
     info!("Validating license key for user '{}'", username);
     let client = reqwest::Client::new();
     let result = client
-        .post(VALIDATION_API)
+        .post(api_url)
         .json(&serde_json::json!({
             "username": username,
             "license_key": key,
@@ -93,6 +186,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
                     LicenseResponse {
                         valid: api_resp.valid,
                         tier,
+                        status: if api_resp.valid { ValidationStatus::Valid } else { ValidationStatus::Invalid },
                         message: api_resp.message,
                     }
                 }
@@ -101,6 +195,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
                     LicenseResponse {
                         valid: false,
                         tier: LicenseTier::Free,
+                        status: ValidationStatus::Unreachable,
                         message: Some("Invalid server response".to_string()),
                     }
                 }
@@ -108,9 +203,16 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
         }
         Ok(resp) => {
             tracing::error!("Validation API returned status {}", resp.status());
+            // 5xx means the server is down, not that the key was rejected
+            let status = if resp.status().is_server_error() {
+                ValidationStatus::Unreachable
+            } else {
+                ValidationStatus::Invalid
+            };
             LicenseResponse {
                 valid: false,
                 tier: LicenseTier::Free,
+                status,
                 message: Some("License validation failed".to_string()),
             }
         }
@@ -119,6 +221,7 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
             LicenseResponse {
                 valid: false,
                 tier: LicenseTier::Free,
+                status: ValidationStatus::Unreachable,
                 message: Some(
                     "Could not validate license. Check your internet connection.".to_string(),
                 ),
@@ -127,12 +230,76 @@ pub async fn validate_license(username: &str, license_key: &str) -> LicenseRespo
     }
 }
 
-/// Save credentials to `data/license.json`.
-pub fn save_credentials(data_dir: &Path, username: &str, license_key: &str) -> Result<(), AppError> {
-    let creds = SavedCredentials {
-        username: username.to_string(),
-        license_key: license_key.to_string(),
+/// Keep the last-known tier when the validation server is unreachable.
+///
+/// Only applies to `ValidationStatus::Unreachable` responses, in builds with a cache
+/// signing key, and only if `creds`
+/// belong to the same username/key, carry a valid signature and were validated online
+/// within `OFFLINE_GRACE_DAYS` before `now` (Unix seconds). Otherwise `response` is
+/// returned unchanged (Free tier).
+/// The returned response keeps `status: Unreachable` so callers can tell it is cached.
+pub fn apply_offline_grace(
+    response: LicenseResponse,
+    creds: Option<&SavedCredentials>,
+    username: &str,
+    license_key: &str,
+    now: i64,
+) -> LicenseResponse {
+    if response.status != ValidationStatus::Unreachable {
+        return response;
+    }
+    let Some(creds) = creds else { return response };
+    if creds.username != username.trim() || creds.license_key != license_key.trim() {
+        return response;
+    }
+    if creds.last_validated_at.is_none() || creds.last_tier.is_none() {
+        return response;
+    }
+    if CACHE_SIGNING_KEY.is_none() {
+        tracing::warn!("Built without LBQ_LICENSE_CACHE_KEY — offline grace is disabled");
+        return response;
+    }
+    let Some((tier, validated_at)) = creds.verified_validation() else {
+        tracing::warn!("Saved license for user '{}' failed its signature check", creds.username);
+        return response;
     };
+    if validated_at > now {
+        tracing::warn!("Saved license for user '{}' was validated in the future", creds.username);
+        return response;
+    }
+
+    let grace_secs = OFFLINE_GRACE_DAYS * 86_400;
+    let elapsed = now - validated_at;
+    if elapsed > grace_secs {
+        info!("Offline grace period expired for user '{}'", creds.username);
+        return response;
+    }
+
+    let days_left = (grace_secs - elapsed) / 86_400;
+    info!(
+        "Validation server unreachable — keeping cached tier {:?} for user '{}' ({} days left)",
+        tier, creds.username, days_left
+    );
+    LicenseResponse {
+        valid: true,
+        tier,
+        status: ValidationStatus::Unreachable,
+        message: Some(format!(
+            "Offline mode: using cached license ({} days left before re-validation is required).",
+            days_left
+        )),
+    }
+}
+
+/// Save credentials to `data/license.json`, stamped with the tier just validated online.
+pub fn save_credentials(
+    data_dir: &Path,
+    username: &str,
+    license_key: &str,
+    tier: LicenseTier,
+    validated_at: i64,
+) -> Result<(), AppError> {
+    let creds = SavedCredentials::new(username, license_key, tier, validated_at);
     let json = serde_json::to_string_pretty(&creds)?;
     let path = data_dir.join("license.json");
     std::fs::write(&path, json)?;
@@ -140,6 +307,14 @@ pub fn save_credentials(data_dir: &Path, username: &str, license_key: &str) -> R
     Ok(())
 }
 
+/// Refresh the last successful validation stamp on the saved credentials, if any.
+pub fn record_validation(data_dir: &Path, tier: LicenseTier, validated_at: i64) -> Result<(), AppError> {
+    match load_credentials(data_dir) {
+        Some(creds) => save_credentials(data_dir, &creds.username, &creds.license_key, tier, validated_at),
+        None => Ok(()),
+    }
+}
+
 /// Load saved credentials from `data/license.json`.
 pub fn load_credentials(data_dir: &Path) -> Option<SavedCredentials> {
     let path = data_dir.join("license.json");
@@ -156,3 +331,128 @@ pub fn clear_credentials(data_dir: &Path) -> Result<(), AppError> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    /// Nothing listens on the discard port, so the request fails like an offline machine.
+    const UNREACHABLE_API: &str = "http://127.0.0.1:9/api/license/validate";
+
+    fn saved_pro(validated_at: i64) -> SavedCredentials {
+        SavedCredentials::new("trader", "LBQ-TEST-KEY", LicenseTier::Pro, validated_at)
+    }
+
+    fn unreachable() -> LicenseResponse {
+        LicenseResponse {
+            valid: false,
+            tier: LicenseTier::Free,
+            status: ValidationStatus::Unreachable,
+            message: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_network_failure_is_unreachable() {
+        let resp = validate_license_at(UNREACHABLE_API, "trader", "LBQ-TEST-KEY").await;
+        assert!(!resp.valid);
+        assert_eq!(resp.status, ValidationStatus::Unreachable);
+        assert_eq!(resp.tier, LicenseTier::Free);
+    }
+
+    #[tokio::test]
+    async fn test_offline_grace_keeps_tier_within_window() {
+        let now = 1_700_000_000;
+        let creds = saved_pro(now - 3 * DAY);
+        let resp = validate_license_at(UNREACHABLE_API, "trader", "LBQ-TEST-KEY").await;
+        let resp = apply_offline_grace(resp, Some(&creds), "trader", "LBQ-TEST-KEY", now);
+        assert!(resp.valid);
+        assert_eq!(resp.tier, LicenseTier::Pro);
+        assert_eq!(resp.status, ValidationStatus::Unreachable);
+    }
+
+    #[tokio::test]
+    async fn test_offline_grace_downgrades_after_window() {
+        let now = 1_700_000_000;
+        let creds = saved_pro(now - (OFFLINE_GRACE_DAYS + 1) * DAY);
+        let resp = validate_license_at(UNREACHABLE_API, "trader", "LBQ-TEST-KEY").await;
+        let resp = apply_offline_grace(resp, Some(&creds), "trader", "LBQ-TEST-KEY", now);
+        assert!(!resp.valid);
+        assert_eq!(resp.tier, LicenseTier::Free);
+    }
+
+    #[test]
+    fn test_offline_grace_ignores_invalid_and_mismatched() {
+        let now = 1_700_000_000;
+        let creds = saved_pro(now - DAY);
+
+        // A rejected key never falls back to the cached tier
+        let invalid = LicenseResponse {
+            valid: false,
+            tier: LicenseTier::Free,
+            status: ValidationStatus::Invalid,
+            message: None,
+        };
+        let resp = apply_offline_grace(invalid, Some(&creds), "trader", "LBQ-TEST-KEY", now);
+        assert_eq!(resp.tier, LicenseTier::Free);
+
+        // Cached credentials for a different key don't apply
+        let resp = apply_offline_grace(unreachable(), Some(&creds), "trader", "OTHER-KEY", now);
+        assert!(!resp.valid);
+
+        // Nor does a validation stamped after `now`
+        let future = saved_pro(now + DAY);
+        let resp = apply_offline_grace(unreachable(), Some(&future), "trader", "LBQ-TEST-KEY", now);
+        assert!(!resp.valid);
+        assert_eq!(resp.tier, LicenseTier::Free);
+    }
+
+    #[test]
+    fn test_tampered_credentials_fall_back_to_free() {
+        let now = 1_700_000_000;
+        let dir = tempfile::tempdir().unwrap();
+        save_credentials(dir.path(), "trader", "LBQ-TEST-KEY", LicenseTier::Free, now - DAY).unwrap();
+
+        let saved = load_credentials(dir.path()).unwrap();
+        let resp = apply_offline_grace(unreachable(), Some(&saved), "trader", "LBQ-TEST-KEY", now);
+        assert!(resp.valid);
+        assert_eq!(resp.tier, LicenseTier::Free);
+
+        // Upgrading the cached tier by hand breaks the signature
+        let path = dir.path().join("license.json");
+        let edited = std::fs::read_to_string(&path).unwrap().replace("\"free\"", "\"pro\"");
+        std::fs::write(&path, edited).unwrap();
+        let tampered = load_credentials(dir.path()).unwrap();
+        assert_eq!(tampered.last_tier, Some(LicenseTier::Pro));
+        let resp = apply_offline_grace(unreachable(), Some(&tampered), "trader", "LBQ-TEST-KEY", now);
+        assert!(!resp.valid);
+        assert_eq!(resp.tier, LicenseTier::Free);
+
+        // So does pushing the validation stamp forward, or dropping the signature
+        let mut stale = saved_pro(now - 30 * DAY);
+        stale.last_validated_at = Some(now);
+        let resp = apply_offline_grace(unreachable(), Some(&stale), "trader", "LBQ-TEST-KEY", now);
+        assert!(!resp.valid);
+        let mut unsigned = saved_pro(now - DAY);
+        unsigned.signature = None;
+        let resp = apply_offline_grace(unreachable(), Some(&unsigned), "trader", "LBQ-TEST-KEY", now);
+        assert!(!resp.valid);
+    }
+
+    #[test]
+    fn test_record_validation_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+
+        save_credentials(dir.path(), "trader", "LBQ-TEST-KEY", LicenseTier::Free, 100).unwrap();
+        record_validation(dir.path(), LicenseTier::Pro, 200).unwrap();
+        let creds = load_credentials(dir.path()).unwrap();
+        assert_eq!(creds.last_validated_at, Some(200));
+        assert_eq!(creds.last_tier, Some(LicenseTier::Pro));
+
+        // Credentials saved by older versions have no validation stamp
+        std::fs::write(dir.path().join("license.json"), r#"{"username":"u","license_key":"k"}"#).unwrap();
+        assert_eq!(load_credentials(dir.path()).unwrap().last_validated_at, None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 /// A single OHLCV candle/bar.
@@ -13,6 +15,32 @@ pub struct Candle {
     pub low: f64,
     pub close: f64,
     pub volume: f64,
+    /// Open interest at bar close, when the source data carries it (futures).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_interest: Option<f64>,
+    /// Mean ask−bid spread over the bar in price units, for tick-derived bars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_spread: Option<f64>,
+}
+
+impl Candle {
+    /// Trading day of this bar as `YYYY-MM-DD` when days start at `session_start_hour`
+    /// instead of midnight. Bars before that hour belong to the session that opened on
+    /// the previous calendar date. With hour 0 this is simply the calendar date.
+    pub fn session_day(&self, session_start_hour: u8) -> Cow<'_, str> {
+        let date = &self.datetime[..10.min(self.datetime.len())];
+        if session_start_hour == 0 {
+            return Cow::Borrowed(date);
+        }
+        let hour = self.datetime.get(11..13).and_then(|h| h.parse::<u8>().ok());
+        if hour.map_or(true, |h| h >= session_start_hour) {
+            return Cow::Borrowed(date);
+        }
+        match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().and_then(|d| d.pred_opt()) {
+            Some(prev) => Cow::Owned(prev.format("%Y-%m-%d").to_string()),
+            None => Cow::Borrowed(date),
+        }
+    }
 }
 
 /// Columnar tick data for maximum cache efficiency in tick-level backtesting.
@@ -34,4 +62,17 @@ impl TickColumns {
     pub fn len(&self) -> usize {
         self.timestamps.len()
     }
+
+    /// Keep only ticks with `from_us <= timestamp < to_us`. Assumes time order.
+    pub fn retain_range(mut self, from_us: i64, to_us: i64) -> TickColumns {
+        let start = self.timestamps.partition_point(|&t| t < from_us);
+        let end = self.timestamps.partition_point(|&t| t < to_us);
+        for col in [&mut self.bids, &mut self.asks] {
+            col.truncate(end);
+            col.drain(..start);
+        }
+        self.timestamps.truncate(end);
+        self.timestamps.drain(..start);
+        self
+    }
 }
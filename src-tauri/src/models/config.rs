@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 /// How swap rates are denominated.
@@ -14,6 +16,30 @@ pub enum SwapMode {
     AsPercent,
 }
 
+/// Which side of a TF candle's interval owns a sub-bar or tick stamped exactly on a
+/// candle boundary. Matters when the data source rounds timestamps onto the bar open.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SubBarBoundary {
+    /// `[candle_ts, next_ts)` — a tick at the next candle's open belongs to the next candle
+    /// (MT5 convention).
+    #[default]
+    StartInclusive,
+    /// `(candle_ts, next_ts]` — a tick at the next candle's open closes the current candle,
+    /// for feeds that stamp ticks with the end of their aggregation window.
+    EndInclusive,
+}
+
+/// Which edge of its interval a bar's timestamp marks in the source data.
+/// Stored data always uses bar-open time; close-stamped imports are shifted at load.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum BarTimestamp {
+    /// Timestamp is the bar's open time (MT4/MT5, Dukascopy).
+    #[default]
+    BarOpen,
+    /// Timestamp is the bar's close time (e.g. some vendor and exchange exports).
+    BarClose,
+}
+
 fn default_triple_swap_day() -> u8 {
     3 // Wednesday (ISO weekday: Mon=1 … Sun=7)
 }
@@ -33,6 +59,12 @@ pub struct InstrumentConfig {
     pub lot_size: f64,
     /// Minimum lot size (e.g. 0.01)
     pub min_lot: f64,
+    /// Lot size increment (SYMBOL_VOLUME_STEP). 0 = step by `min_lot`.
+    #[serde(default)]
+    pub lot_step: f64,
+    /// Maximum lot size per position (SYMBOL_VOLUME_MAX). 0 = no cap.
+    #[serde(default)]
+    pub max_lot: f64,
     /// Minimum price movement
     pub tick_size: f64,
     /// Number of decimal places
@@ -74,6 +106,25 @@ pub struct InstrumentConfig {
     /// Fractional values are supported (e.g. 5.5 for UTC+5:30, -3.5 for UTC-3:30).
     #[serde(default)]
     pub tz_offset_hours: f64,
+
+    // ── Session ──
+
+    /// Hour of day (0-23, in the stored timestamps) at which the trading day starts.
+    /// Daily resets — pivots, Daily price fields and the max-daily-trades counter — roll
+    /// over here instead of at midnight (e.g. 17 for forex data stored in New York time).
+    /// Default 0 = calendar day.
+    #[serde(default)]
+    pub session_start_hour: u8,
+
+    /// Interval convention used to bucket sub-bars/ticks into TF candles.
+    /// The last candle always takes every remaining sub-bar.
+    #[serde(default)]
+    pub subbar_boundary: SubBarBoundary,
+
+    /// Whether imported bar timestamps mark the bar open or close. Close-stamped bars
+    /// are moved back one bar width at import so every timeframe is open-stamped.
+    #[serde(default)]
+    pub timestamp_is: BarTimestamp,
 }
 
 impl Default for InstrumentConfig {
@@ -84,6 +135,8 @@ impl Default for InstrumentConfig {
             pip_value: 10.0,
             lot_size: 100_000.0,
             min_lot: 0.01,
+            lot_step: 0.01,
+            max_lot: 0.0,
             tick_size: 0.00001,
             digits: 5,
             swap_long: 0.0,
@@ -93,13 +146,81 @@ impl Default for InstrumentConfig {
             swap_annual_days: 365,
             min_stop_distance_pips: 0.0,
             tz_offset_hours: 0.0,
+            session_start_hour: 0,
+            subbar_boundary: SubBarBoundary::StartInclusive,
+            timestamp_is: BarTimestamp::BarOpen,
         }
     }
 }
 
+impl InstrumentConfig {
+    /// Round a lot size down to the lot step and clamp it to `[min_lot, max_lot]`,
+    /// like an EA's `MathFloor(lots / step) * step` + `NormalizeDouble` before `OrderSend`.
+    pub fn normalize_lots(&self, lots: f64) -> f64 {
+        let step = if self.lot_step > 0.0 { self.lot_step } else { self.min_lot };
+        let mut lots = if step > 0.0 {
+            // Tolerance so 0.3 / 0.1 = 2.9999999999999996 doesn't lose a whole step
+            let steps = (lots / step + 1e-9).floor();
+            let decimals = (-step.log10()).ceil().clamp(0.0, 8.0) as i32;
+            let scale = 10f64.powi(decimals);
+            (steps * step * scale).round() / scale
+        } else {
+            lots
+        };
+        lots = lots.max(self.min_lot);
+        if self.max_lot > 0.0 {
+            lots = lots.min(self.max_lot);
+        }
+        lots
+    }
+
+    /// Points per pip implied by quote precision alone — the convention generated EAs
+    /// fall back on: fractional-pip quotes (3 or 5 digits) have 10 points per pip,
+    /// everything else 1.
+    pub fn conventional_pip_multiplier(digits: usize) -> f64 {
+        if digits == 3 || digits == 5 {
+            10.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Smallest quoted price increment, `10^-digits` (MT5 `_Point`).
+    pub fn point(&self) -> f64 {
+        10f64.powi(-(self.digits as i32))
+    }
+
+    /// Points per pip for this instrument: 10 for EUR/USD at 5 digits or USD/JPY at 3,
+    /// 1 for USD/JPY at 2, 100 for an index quoted to 2 digits with a 1.0 pip.
+    pub fn pip_multiplier(&self) -> f64 {
+        self.pip_size / self.point()
+    }
+
+    /// Price distance of `pips`.
+    pub fn pips_to_price(&self, pips: f64) -> f64 {
+        pips * self.pip_size
+    }
+
+    /// Pips in a price distance. 0 when `pip_size` is unset.
+    pub fn price_to_pips(&self, distance: f64) -> f64 {
+        if self.pip_size == 0.0 {
+            return 0.0;
+        }
+        distance / self.pip_size
+    }
+
+    /// Pips in a number of points. 0 when `pip_size` is unset.
+    pub fn points_to_pips(&self, points: f64) -> f64 {
+        self.price_to_pips(points * self.point())
+    }
+}
+
 /// Supported timeframes for OHLCV data.
+///
+/// Serialized as its lowercase key (`"m1"`, `"h4"`, …). Custom timeframes use the
+/// same scheme with the largest whole unit (`"m3"`, `"h2"`, `"d2"`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(try_from = "String", into = "String")]
 pub enum Timeframe {
     Tick,
     M1,
@@ -109,9 +230,35 @@ pub enum Timeframe {
     H1,
     H4,
     D1,
+    /// Arbitrary bar length in minutes (e.g. 3, 120, 480), resampled on demand
+    /// from stored data. Never holds a value that matches a standard variant.
+    Custom { minutes: u32 },
 }
 
 impl Timeframe {
+    /// Build the timeframe for a bar length in minutes, preferring the standard
+    /// variants. Returns `None` for zero.
+    pub fn from_minutes(minutes: u32) -> Option<Timeframe> {
+        let standard = [
+            Timeframe::M1,
+            Timeframe::M5,
+            Timeframe::M15,
+            Timeframe::M30,
+            Timeframe::H1,
+            Timeframe::H4,
+            Timeframe::D1,
+        ];
+        match minutes {
+            0 => None,
+            m => Some(
+                standard
+                    .into_iter()
+                    .find(|tf| tf.minutes() == m)
+                    .unwrap_or(Timeframe::Custom { minutes: m }),
+            ),
+        }
+    }
+
     /// Returns the duration in minutes (0 for tick).
     pub fn minutes(&self) -> u32 {
         match self {
@@ -123,25 +270,24 @@ impl Timeframe {
             Timeframe::H1 => 60,
             Timeframe::H4 => 240,
             Timeframe::D1 => 1440,
+            Timeframe::Custom { minutes } => *minutes,
         }
     }
 
     /// Returns the Polars duration string for `group_by_dynamic`.
-    pub fn polars_duration(&self) -> &'static str {
+    pub fn polars_duration(&self) -> String {
         match self {
-            Timeframe::Tick => "1s", // not really used for grouping
-            Timeframe::M1 => "1m",
-            Timeframe::M5 => "5m",
-            Timeframe::M15 => "15m",
-            Timeframe::M30 => "30m",
-            Timeframe::H1 => "1h",
-            Timeframe::H4 => "4h",
-            Timeframe::D1 => "1d",
+            Timeframe::Tick => "1s".to_string(), // not really used for grouping
+            Timeframe::D1 => "1d".to_string(),
+            tf => format!("{}m", tf.minutes()),
         }
     }
 
     /// Returns all timeframes that should be generated from this base timeframe.
     /// E.g. from M1 -> [M5, M15, M30, H1, H4, D1]
+    ///
+    /// Only whole multiples of the base are returned, so a custom base (e.g. 3m)
+    /// never produces bars that straddle its own bar boundaries.
     pub fn higher_timeframes(&self) -> Vec<Timeframe> {
         let all = [
             Timeframe::M1,
@@ -154,44 +300,88 @@ impl Timeframe {
         ];
         all.into_iter()
             .filter(|tf| tf.minutes() > self.minutes())
+            .filter(|tf| self.minutes() == 0 || tf.minutes() % self.minutes() == 0)
             .collect()
     }
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> Cow<'static, str> {
         match self {
-            Timeframe::Tick => "tick",
-            Timeframe::M1 => "m1",
-            Timeframe::M5 => "m5",
-            Timeframe::M15 => "m15",
-            Timeframe::M30 => "m30",
-            Timeframe::H1 => "h1",
-            Timeframe::H4 => "h4",
-            Timeframe::D1 => "d1",
+            Timeframe::Tick => Cow::Borrowed("tick"),
+            Timeframe::M1 => Cow::Borrowed("m1"),
+            Timeframe::M5 => Cow::Borrowed("m5"),
+            Timeframe::M15 => Cow::Borrowed("m15"),
+            Timeframe::M30 => Cow::Borrowed("m30"),
+            Timeframe::H1 => Cow::Borrowed("h1"),
+            Timeframe::H4 => Cow::Borrowed("h4"),
+            Timeframe::D1 => Cow::Borrowed("d1"),
+            Timeframe::Custom { minutes } => Cow::Owned(match *minutes {
+                m if m % 1440 == 0 => format!("d{}", m / 1440),
+                m if m % 60 == 0 => format!("h{}", m / 60),
+                m => format!("m{}", m),
+            }),
         }
     }
 }
 
 impl std::fmt::Display for Timeframe {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.as_str())
+        f.write_str(&self.as_str())
     }
 }
 
 impl std::str::FromStr for Timeframe {
     type Err = String;
 
+    /// Accepts the standard keys plus custom lengths written either unit-first
+    /// (`"m3"`, `"h2"`, `"d2"`) or unit-last (`"3m"`, `"2h"`, `"8h"`).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "tick" => Ok(Timeframe::Tick),
-            "m1" => Ok(Timeframe::M1),
-            "m5" => Ok(Timeframe::M5),
-            "m15" => Ok(Timeframe::M15),
-            "m30" => Ok(Timeframe::M30),
-            "h1" => Ok(Timeframe::H1),
-            "h4" => Ok(Timeframe::H4),
-            "d1" => Ok(Timeframe::D1),
-            _ => Err(format!("Unknown timeframe: {}", s)),
+        let lower = s.trim().to_lowercase();
+        match lower.as_str() {
+            "tick" => return Ok(Timeframe::Tick),
+            "m1" => return Ok(Timeframe::M1),
+            "m5" => return Ok(Timeframe::M5),
+            "m15" => return Ok(Timeframe::M15),
+            "m30" => return Ok(Timeframe::M30),
+            "h1" => return Ok(Timeframe::H1),
+            "h4" => return Ok(Timeframe::H4),
+            "d1" => return Ok(Timeframe::D1),
+            _ => {}
         }
+
+        let (unit, count) = if lower.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            lower.split_at(1)
+        } else {
+            // Split before the last char, not the last byte: the unit may be multibyte
+            let last = lower.char_indices().last().map_or(0, |(i, _)| i);
+            let (count, unit) = lower.split_at(last);
+            (unit, count)
+        };
+        let unit_minutes = match unit {
+            "m" => 1,
+            "h" => 60,
+            "d" => 1440,
+            _ => return Err(format!("Unknown timeframe: {}", s)),
+        };
+        count
+            .parse::<u32>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit_minutes))
+            .and_then(Timeframe::from_minutes)
+            .ok_or_else(|| format!("Unknown timeframe: {}", s))
+    }
+}
+
+impl TryFrom<String> for Timeframe {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Timeframe> for String {
+    fn from(tf: Timeframe) -> Self {
+        tf.as_str().into_owned()
     }
 }
 
@@ -221,6 +411,60 @@ pub enum TickPipeline {
     ViaCsv,
 }
 
+/// Origin of an uploaded CSV file.
+///
+/// `Generic` files go through format auto-detection; vendor exports with a fixed
+/// layout are parsed by a dedicated importer in `data::importers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    #[default]
+    Generic,
+    /// HistData.com Generic ASCII M1 bars (EST timestamps, `;` separated).
+    HistData,
+    /// OANDA v20 candle export with RFC 3339 or UNIX timestamps.
+    Oanda,
+}
+
+/// User-supplied mapping from target fields to 0-based source CSV column indices.
+///
+/// When provided on import it replaces header-name and positional detection, so
+/// files with shuffled, renamed or extra columns can be loaded. Bar imports
+/// require `open`/`high`/`low`/`close`; tick imports require `bid`/`ask`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    /// Column holding the timestamp (or only the date part when `time` is set).
+    pub datetime: usize,
+    /// Optional separate time-of-day column, joined to `datetime` before parsing.
+    #[serde(default)]
+    pub time: Option<usize>,
+    #[serde(default)]
+    pub open: Option<usize>,
+    #[serde(default)]
+    pub high: Option<usize>,
+    #[serde(default)]
+    pub low: Option<usize>,
+    #[serde(default)]
+    pub close: Option<usize>,
+    #[serde(default)]
+    pub volume: Option<usize>,
+    #[serde(default)]
+    pub bid: Option<usize>,
+    #[serde(default)]
+    pub ask: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Tick format when both bid and ask are mapped, bar format otherwise.
+    pub fn data_format(&self) -> DataFormat {
+        if self.bid.is_some() && self.ask.is_some() {
+            DataFormat::Tick
+        } else {
+            DataFormat::Bar
+        }
+    }
+}
+
 /// Detected CSV data format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataFormat {
@@ -12,6 +12,15 @@ pub struct EquityPoint {
     pub equity: f64,
 }
 
+/// Rolling risk-adjusted ratios at one equity-curve point.
+/// Both are `None` until the first full window of returns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollingRatioPoint {
+    pub timestamp: String,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+}
+
 /// A point on the drawdown curve.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrawdownPoint {
@@ -27,6 +36,28 @@ pub struct MonthlyReturn {
     pub return_pct: f64,
 }
 
+/// Realized trade PnL of one entry-hour or entry-weekday bucket.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SeasonalityBucket {
+    /// Entry hour (0–23) or ISO weekday (Mon=1 … Sun=7).
+    pub bucket: u32,
+    pub trade_count: usize,
+    pub total_pnl: f64,
+    /// 0 for an empty bucket.
+    pub avg_pnl: f64,
+    pub win_rate_pct: f64,
+}
+
+/// Trade performance bucketed by entry hour and weekday. Every bucket is present,
+/// empty ones with zero trades, so the rows line up across runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalityReport {
+    /// 24 buckets, hour 0 … 23.
+    pub by_hour: Vec<SeasonalityBucket>,
+    /// 7 buckets, Monday … Sunday.
+    pub by_weekday: Vec<SeasonalityBucket>,
+}
+
 /// Configuration for a Monte Carlo simulation run.
 ///
 /// When both `use_resampling` and `use_skip_trades` are enabled, each simulation
@@ -164,6 +195,18 @@ pub struct BacktestMetrics {
     // Costs breakdown
     pub total_swap_charged: f64,
     pub total_commission_charged: f64,
+    /// Spread paid across all trades (see `TradeResult::spread_cost`).
+    #[serde(default)]
+    pub total_spread_cost: f64,
+    #[serde(default)]
+    pub total_slippage_cost: f64,
+    /// Spread + slippage + commission, less swap (swap credits reduce costs).
+    #[serde(default)]
+    pub total_costs: f64,
+    /// Net profit the trades would have made without any trading costs:
+    /// `gross_pnl_before_costs - total_costs == net_profit`.
+    #[serde(default)]
+    pub gross_pnl_before_costs: f64,
 
     // Stagnation & Ulcer
     pub stagnation_bars: usize,
@@ -183,6 +226,112 @@ pub struct BacktestMetrics {
     /// High value → strategy is profitable and stable across different time windows.
     #[serde(default)]
     pub temporal_consistency: f64,
+
+    // Benchmark (buy-and-hold over the same bars)
+    /// Buy-and-hold return of the instrument over the backtest range, in percent.
+    #[serde(default)]
+    pub benchmark_return_pct: f64,
+    /// Total return in excess of `beta × benchmark_return_pct` (percentage points).
+    #[serde(default)]
+    pub alpha: f64,
+    /// Sensitivity of per-bar strategy returns to per-bar buy-and-hold returns.
+    #[serde(default)]
+    pub beta: f64,
+    /// Pearson correlation of per-bar strategy and buy-and-hold returns.
+    #[serde(default)]
+    pub correlation: f64,
+
+    // Exposure
+    /// Percentage of equity-curve bars with at least one open position (time in market).
+    #[serde(default)]
+    pub exposure_pct: f64,
+    /// Average number of open positions over the bars that had any position open.
+    #[serde(default)]
+    pub avg_simultaneous_positions: f64,
+    /// Percentage of bars with a long position open.
+    #[serde(default)]
+    pub long_exposure_pct: f64,
+    /// Percentage of bars with a short position open.
+    #[serde(default)]
+    pub short_exposure_pct: f64,
+
+    // Drawdown episodes (peak → recovery to the prior peak)
+    /// Number of distinct drawdown episodes.
+    #[serde(default)]
+    pub drawdown_episodes: usize,
+    /// Longest episode of any depth, in bars.
+    #[serde(default)]
+    pub longest_drawdown_bars: usize,
+    /// Wall-clock length of the longest episode, from equity-curve timestamps.
+    #[serde(default)]
+    pub longest_drawdown_time: String,
+    /// Bars from the max-drawdown trough back to the prior peak (end of data if never recovered).
+    #[serde(default)]
+    pub max_drawdown_recovery_bars: usize,
+    /// Wall-clock time from the max-drawdown trough back to the prior peak.
+    #[serde(default)]
+    pub max_drawdown_recovery_time: String,
+    /// Mean of each episode's deepest point, in percent.
+    #[serde(default)]
+    pub avg_drawdown_depth_pct: f64,
+    /// Mean episode length, in bars.
+    #[serde(default)]
+    pub avg_drawdown_duration_bars: f64,
+
+    // Trade clustering (from entry times)
+    /// Mean time between consecutive trade entries, in minutes.
+    #[serde(default)]
+    pub avg_minutes_between_trades: f64,
+    /// Trades entered within one hour of the previous entry.
+    #[serde(default)]
+    pub clustered_trades: usize,
+    /// Most entries inside any rolling one-hour window (the largest burst).
+    #[serde(default)]
+    pub max_trades_per_hour: usize,
+    /// `max_trades_per_hour` exceeds the overtrading threshold
+    /// (`BacktestConfig::overtrading_threshold`).
+    #[serde(default)]
+    pub overtrading: bool,
+
+    // Market regime breakdown (trades tagged via `BacktestConfig::regime`)
+    #[serde(default)]
+    pub trending_trades: usize,
+    #[serde(default)]
+    pub trending_win_rate_pct: f64,
+    /// Net P&L (after commission and swap) of trades entered in a trending regime.
+    #[serde(default)]
+    pub trending_net_profit: f64,
+    #[serde(default)]
+    pub ranging_trades: usize,
+    #[serde(default)]
+    pub ranging_win_rate_pct: f64,
+    /// Net P&L (after commission and swap) of trades entered in a ranging regime.
+    #[serde(default)]
+    pub ranging_net_profit: f64,
+}
+
+/// Incremental snapshot of a stepped (replay) backtest: everything produced since the
+/// previous step. Trades are not yet tagged with `regime`; the final results are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestStep {
+    /// Last bar processed (index into the loaded candles).
+    pub bar_index: usize,
+    pub total_bars: usize,
+    pub equity_points: Vec<EquityPoint>,
+    /// Empty when the run does not record a drawdown curve.
+    pub drawdown_points: Vec<DrawdownPoint>,
+    pub new_trades: Vec<TradeResult>,
+    pub in_position: bool,
+}
+
+/// One row of a batch backtest: a strategy's metrics over the shared symbol and range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchBacktestResult {
+    pub strategy_id: String,
+    pub strategy_name: String,
+    pub metrics: BacktestMetrics,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Complete results of a backtest run.
@@ -204,6 +353,17 @@ pub struct BacktestResults {
     /// Warnings about backtest configuration that may affect accuracy.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub warnings: Vec<String>,
+    /// Buy-and-hold equity over the same bars as `equity_curve`, for overlaying.
+    /// Empty in optimizer/builder runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub benchmark_curve: Vec<EquityPoint>,
+    /// True when the run ended early at `BacktestConfig::stop_out_equity`.
+    #[serde(default)]
+    pub stopped_out: bool,
+    /// Rolling Sharpe/Sortino, one point per `equity_curve` point.
+    /// Empty in optimizer/builder runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rolling_ratios: Vec<RollingRatioPoint>,
 }
 
 // ══════════════════════════════════════════════════════════════
@@ -215,6 +375,8 @@ pub struct BacktestResults {
 pub enum OptimizationMethod {
     GridSearch,
     GeneticAlgorithm,
+    /// Latin-hypercube sampling of a fixed number of parameter sets.
+    RandomSearch,
 }
 
 /// Objective function for optimization.
@@ -222,13 +384,95 @@ pub enum OptimizationMethod {
 /// "Minimize" objectives (MinStagnation, MinUlcerIndex): lower is better — internally negated.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ObjectiveFunction {
+    /// Net profit in account currency.
     TotalProfit,
     SharpeRatio,
     ProfitFactor,
     WinRate,
+    /// Total return % divided by maximum drawdown %.
     ReturnDdRatio,
     MinStagnation,
     MinUlcerIndex,
+    SortinoRatio,
+    CalmarRatio,
+    /// Average P&L per trade.
+    Expectancy,
+    RecoveryFactor,
+}
+
+impl ObjectiveFunction {
+    /// Direction the objective is optimized in when no explicit direction is given.
+    pub fn default_direction(self) -> ObjectiveDirection {
+        match self {
+            ObjectiveFunction::MinStagnation | ObjectiveFunction::MinUlcerIndex => {
+                ObjectiveDirection::Minimize
+            }
+            _ => ObjectiveDirection::Maximize,
+        }
+    }
+}
+
+/// Whether higher or lower values of an objective are preferred.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ObjectiveDirection {
+    Maximize,
+    Minimize,
+}
+
+/// An objective with its weight in the composite score and an optional direction override.
+///
+/// Deserializes from either a bare objective name (`"SharpeRatio"`, weight 1, natural
+/// direction) or an object `{ "objective": "SharpeRatio", "weight": 2.0, "direction": "Maximize" }`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(from = "WeightedObjectiveRepr")]
+pub struct WeightedObjective {
+    pub objective: ObjectiveFunction,
+    /// Relative weight in the composite score (only used with multiple objectives).
+    pub weight: f64,
+    /// `None` uses [`ObjectiveFunction::default_direction`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<ObjectiveDirection>,
+}
+
+impl WeightedObjective {
+    /// Effective optimization direction.
+    pub fn direction(&self) -> ObjectiveDirection {
+        self.direction.unwrap_or_else(|| self.objective.default_direction())
+    }
+}
+
+impl From<ObjectiveFunction> for WeightedObjective {
+    fn from(objective: ObjectiveFunction) -> Self {
+        WeightedObjective { objective, weight: 1.0, direction: None }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WeightedObjectiveRepr {
+    Bare(ObjectiveFunction),
+    Weighted {
+        objective: ObjectiveFunction,
+        #[serde(default = "default_objective_weight")]
+        weight: f64,
+        #[serde(default)]
+        direction: Option<ObjectiveDirection>,
+    },
+}
+
+fn default_objective_weight() -> f64 {
+    1.0
+}
+
+impl From<WeightedObjectiveRepr> for WeightedObjective {
+    fn from(repr: WeightedObjectiveRepr) -> Self {
+        match repr {
+            WeightedObjectiveRepr::Bare(objective) => objective.into(),
+            WeightedObjectiveRepr::Weighted { objective, weight, direction } => {
+                WeightedObjective { objective, weight, direction }
+            }
+        }
+    }
 }
 
 /// A parameter range to optimize over.
@@ -251,6 +495,29 @@ fn default_param_source() -> String {
     "indicator".into()
 }
 
+/// How the genetic algorithm recombines two parents (applied with `crossover_rate`).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CrossoverType {
+    /// Swap the gene tails after a random cut point.
+    #[default]
+    OnePoint,
+    /// Swap each gene independently with probability 0.5. Suits categorical parameters.
+    Uniform,
+    /// BLX-0.5: draw each child gene from the parents' interval widened by half its
+    /// length on each side. Suits continuous parameters.
+    Blend,
+}
+
+/// How the genetic algorithm changes a gene picked for mutation (per gene, `mutation_rate`).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MutationType {
+    /// Replace the gene with a uniformly drawn value from its range.
+    #[default]
+    UniformReset,
+    /// Add normal noise with a standard deviation of 10% of the range.
+    Gaussian,
+}
+
 /// Configuration for the genetic algorithm.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneticAlgorithmConfig {
@@ -258,10 +525,28 @@ pub struct GeneticAlgorithmConfig {
     pub generations: usize,
     pub mutation_rate: f64,
     pub crossover_rate: f64,
+    #[serde(default)]
+    pub crossover_type: CrossoverType,
+    #[serde(default)]
+    pub mutation_type: MutationType,
     /// Stop early if the best fitness has not improved for this many consecutive generations.
     /// `None` means no early stopping (run all generations).
     #[serde(default)]
     pub patience: Option<usize>,
+    /// Seed for population init, selection, crossover and mutation.
+    /// `None` seeds from OS entropy (non-reproducible runs).
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Configuration for Latin-hypercube random search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomSearchConfig {
+    /// Number of parameter sets to draw (before de-duplication of snapped samples).
+    pub samples: usize,
+    /// Seed for the sampler. `None` seeds from OS entropy (non-reproducible runs).
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 /// A date range for Out-of-Sample testing.
@@ -288,14 +573,25 @@ pub struct OosResult {
 pub struct OptimizationConfig {
     pub method: OptimizationMethod,
     pub parameter_ranges: Vec<ParameterRange>,
-    /// One or more objectives. First is primary (used for GA fitness).
-    pub objectives: Vec<ObjectiveFunction>,
+    /// One or more weighted objectives. The first is primary (`objective_value`);
+    /// with several, results are ranked by their weighted composite score.
+    pub objectives: Vec<WeightedObjective>,
     pub backtest_config: BacktestConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ga_config: Option<GeneticAlgorithmConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_search_config: Option<RandomSearchConfig>,
     /// Out-of-Sample periods for validation (optional).
     #[serde(default)]
     pub oos_periods: Vec<OosPeriod>,
+    /// Accept OOS periods that overlap the in-sample range (logged as a warning)
+    /// instead of rejecting the run.
+    #[serde(default)]
+    pub allow_oos_overlap: bool,
+    /// Keep every evaluated combination on the backend (not just the top results)
+    /// so it can be written out with `export_optimization_csv`.
+    #[serde(default)]
+    pub keep_all_results: bool,
 }
 
 /// A single result from an optimization run.
@@ -304,7 +600,7 @@ pub struct OptimizationResult {
     pub params: HashMap<String, f64>,
     /// Primary objective value (first objective).
     pub objective_value: f64,
-    /// Composite score when multiple objectives are used (normalized average).
+    /// Composite score when multiple objectives are used (weighted normalized average).
     pub composite_score: f64,
     pub total_return_pct: f64,
     pub sharpe_ratio: f64,
@@ -315,12 +611,97 @@ pub struct OptimizationResult {
     pub win_rate_pct: f64,
     pub stagnation_bars: usize,
     pub ulcer_index_pct: f64,
+    #[serde(default)]
+    pub net_profit: f64,
+    #[serde(default)]
+    pub sortino_ratio: f64,
+    #[serde(default)]
+    pub calmar_ratio: f64,
+    #[serde(default)]
+    pub expectancy: f64,
+    #[serde(default)]
+    pub recovery_factor: f64,
     /// Out-of-Sample results for each OOS period (empty if no OOS configured).
     #[serde(default)]
     pub oos_results: Vec<OosResult>,
     /// Downsampled equity curve for sparkline visualization (max ~60 points).
     #[serde(default)]
     pub equity_curve: Vec<EquityPoint>,
+    /// Why the backtest for these parameters failed (e.g. an indicator period longer than
+    /// the data). Failed results carry a `-inf` score and rank after every evaluated one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+}
+
+/// Objective averaged over all evaluated combinations sharing one value of a parameter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterMarginal {
+    /// Parameter display name (key in `OptimizationResult::params`).
+    pub param: String,
+    /// Distinct parameter values, ascending.
+    pub values: Vec<f64>,
+    /// Mean objective per value (marginalized over every other parameter).
+    pub mean_objective: Vec<f64>,
+    /// Best objective per value.
+    pub best_objective: Vec<f64>,
+    /// Spread of `mean_objective` (max − min). Larger means the parameter matters more.
+    pub importance: f64,
+}
+
+/// Mean objective over a pair of parameters, for heatmap rendering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectiveGrid {
+    pub param_x: String,
+    pub param_y: String,
+    /// Distinct values of `param_x`, ascending (columns).
+    pub x_values: Vec<f64>,
+    /// Distinct values of `param_y`, ascending (rows).
+    pub y_values: Vec<f64>,
+    /// `cells[row][col]` = mean objective at (`x_values[col]`, `y_values[row]`);
+    /// `None` where no combination was evaluated.
+    pub cells: Vec<Vec<Option<f64>>>,
+}
+
+/// Parameter sensitivity computed from the full grid-search result set.
+/// The objective is each result's `composite_score` (equal to `objective_value`
+/// for a single objective).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSensitivity {
+    /// One marginal curve per parameter range, in range order.
+    pub marginals: Vec<ParameterMarginal>,
+    /// One grid per parameter pair `(i, j)` with `i < j`, in range order.
+    pub grids: Vec<ObjectiveGrid>,
+}
+
+/// Output of an optimization run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationOutput {
+    /// Top results, best first.
+    pub results: Vec<OptimizationResult>,
+    /// Parameter sensitivity over every evaluated combination (Grid Search only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<ParameterSensitivity>,
+    /// Number of combinations retained for CSV export (`keep_all_results` runs only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retained_count: Option<usize>,
+    /// The run was cancelled; `results` holds the best of what was evaluated before that.
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Warnings about the run configuration (e.g. a precision fallback) that apply to every result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Every combination evaluated by an optimization run, kept in `AppState` for export.
+/// Only the top results carry OOS metrics and equity curves.
+#[derive(Debug, Clone, Default)]
+pub struct RetainedOptimization {
+    /// Parameter display names, in `OptimizationConfig::parameter_ranges` order.
+    pub param_names: Vec<String>,
+    /// OOS period labels, in `OptimizationConfig::oos_periods` order.
+    pub oos_labels: Vec<String>,
+    /// Ranked best first.
+    pub results: Vec<OptimizationResult>,
 }
 
 // ══════════════════════════════════════════════════════════════
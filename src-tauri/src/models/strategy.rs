@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
 use super::config::Timeframe;
@@ -56,6 +58,43 @@ pub enum IndicatorType {
     LowestIndex,
     QQE,
     SchaffTrendCycle,
+    Choppiness,
+    StochasticRSI,
+    AccumulationDistribution,
+    ChaikinMoneyFlow,
+    TRIX,
+    DPO,
+    Coppock,
+    PPO,
+    DonchianChannel,
+    SuperSmoother,
+    RoofingFilter,
+    ConnorsRSI,
+    ForceIndex,
+    EaseOfMovement,
+    VWMA,
+    ZLEMA,
+    ElderImpulse,
+    MassIndex,
+    Klinger,
+    WMA,
+}
+
+impl IndicatorType {
+    /// Whether the indicator's output is derived from bar volume.
+    /// These produce flat or meaningless values on data imported without volume.
+    pub fn uses_volume(self) -> bool {
+        matches!(
+            self,
+            IndicatorType::AvgVolume
+                | IndicatorType::AccumulationDistribution
+                | IndicatorType::ChaikinMoneyFlow
+                | IndicatorType::ForceIndex
+                | IndicatorType::EaseOfMovement
+                | IndicatorType::VWMA
+                | IndicatorType::Klinger
+        )
+    }
 }
 
 /// Parameters for indicator calculation. Each indicator uses the fields relevant to it.
@@ -86,6 +125,71 @@ pub struct IndicatorParams {
     pub gamma: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub multiplier: Option<f64>,
+    /// True Range smoothing for ATR and ATR-based indicators (SuperTrend). Default: Wilder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atr_smoothing: Option<AtrSmoothing>,
+    /// StdDev / Bollinger Bands: divide the variance by N-1 (sample) instead of N (population).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_stddev: Option<bool>,
+    /// RSI averaging of gains/losses. Default: Wilder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsi_smoothing: Option<RsiSmoothing>,
+    /// Pivot point formula used for the PP/R1-3/S1-3 levels. Default: Classic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot_type: Option<PivotType>,
+    /// EMA / MACD seeding. Default: SMA of the first `period` values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ema_seed: Option<EmaSeed>,
+}
+
+/// How ATR averages True Range.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AtrSmoothing {
+    /// Wilder's RMA: seeded with an SMA, then `(prev * (n - 1) + tr) / n`.
+    #[default]
+    Wilder,
+    /// Simple moving average of the last `n` True Ranges.
+    Sma,
+    /// EMA with alpha `2 / (n + 1)`, seeded with an SMA.
+    Ema,
+}
+
+/// How RSI averages gains and losses.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RsiSmoothing {
+    /// Wilder's RMA: seeded with an SMA, then `(prev * (n - 1) + x) / n`.
+    #[default]
+    Wilder,
+    /// Cutler's RSI: simple average of the last `n` gains and losses.
+    Sma,
+}
+
+/// How EMA (and MACD's EMAs) start the recursion.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmaSeed {
+    /// SMA of the first `period` values; the first `period - 1` bars are NaN.
+    #[default]
+    SmaSeed,
+    /// The first value itself, defined from bar 0 (TradingView's `ta.ema`).
+    FirstValue,
+}
+
+/// Formula set used by the Pivots indicator, all from the prior day's HLC.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PivotType {
+    /// Floor pivots: `PP = (H + L + C) / 3`, levels at `2PP - L`, `PP + range`, ...
+    #[default]
+    Classic,
+    /// Classic PP with levels at 0.382 / 0.618 / 1.0 of the range.
+    Fibonacci,
+    /// Levels around the close at 1.1/12, 1.1/6 and 1.1/4 of the range.
+    Camarilla,
+    /// Close-weighted `PP = (H + L + 2C) / 4` with the classic level formulas.
+    Woodie,
 }
 
 /// Configuration for a single indicator instance.
@@ -123,6 +227,11 @@ impl IndicatorConfig {
         self.params.maximum_factor.map(|v| (v * 10000.0).round() as i64).hash(&mut h);
         self.params.gamma.map(|v| (v * 10000.0).round() as i64).hash(&mut h);
         self.params.multiplier.map(|v| (v * 100.0).round() as i64).hash(&mut h);
+        self.params.atr_smoothing.unwrap_or_default().hash(&mut h);
+        self.params.sample_stddev.unwrap_or(false).hash(&mut h);
+        self.params.rsi_smoothing.unwrap_or_default().hash(&mut h);
+        self.params.pivot_type.unwrap_or_default().hash(&mut h);
+        self.params.ema_seed.unwrap_or_default().hash(&mut h);
         h.finish()
     }
 
@@ -166,6 +275,27 @@ impl IndicatorConfig {
         if let Some(m) = self.params.multiplier {
             key.push_str(&format!("_mul{:.2}", m));
         }
+        // Wilder is the default — omitted so existing keys are unchanged
+        match self.params.atr_smoothing.unwrap_or_default() {
+            AtrSmoothing::Wilder => {}
+            AtrSmoothing::Sma => key.push_str("_atrsma"),
+            AtrSmoothing::Ema => key.push_str("_atrema"),
+        }
+        if self.params.sample_stddev.unwrap_or(false) {
+            key.push_str("_ssd");
+        }
+        if self.params.rsi_smoothing.unwrap_or_default() == RsiSmoothing::Sma {
+            key.push_str("_rsisma");
+        }
+        match self.params.pivot_type.unwrap_or_default() {
+            PivotType::Classic => {}
+            PivotType::Fibonacci => key.push_str("_pvfib"),
+            PivotType::Camarilla => key.push_str("_pvcam"),
+            PivotType::Woodie => key.push_str("_pvwoo"),
+        }
+        if self.params.ema_seed.unwrap_or_default() == EmaSeed::FirstValue {
+            key.push_str("_emafv");
+        }
         key
     }
 }
@@ -240,6 +370,10 @@ pub enum PriceField {
     DailyHigh,
     DailyLow,
     DailyClose,
+    /// Bar open interest; NaN when the data has none.
+    OpenInterest,
+    /// Bar average spread in price units; NaN when the data has none.
+    AvgSpread,
 }
 
 /// Candle pattern types for the CandlePattern operand.
@@ -268,8 +402,9 @@ pub struct Operand {
     pub time_field: Option<TimeField>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub candle_pattern: Option<CandlePatternType>,
-    /// Look back N bars for the operand value.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Look back N bars for the operand value (0 = the signal bar). Past bars only:
+    /// negative values are rejected when the strategy is deserialized.
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_offset")]
     pub offset: Option<usize>,
     // ── Compound operand fields (only used when operand_type == Compound) ──
     /// Left sub-operand. Must be a non-Compound operand (depth = 1).
@@ -281,6 +416,27 @@ pub struct Operand {
     /// Right sub-operand. Must be a non-Compound operand (depth = 1).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub compound_right: Option<Box<Operand>>,
+    /// Constant operands only: treat `constant_value` as a percentage offset from this
+    /// price field on the same bar, i.e. `price * (1 + constant_value / 100)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_of: Option<PriceField>,
+    /// Replace the operand's value with a rolling statistic of its own recent values,
+    /// for thresholds relative to the operand's distribution (e.g. RSI percent rank).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<OperandTransform>,
+}
+
+/// Rolling normalization applied to an operand's series before the comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OperandTransform {
+    /// Percent (0-100) of the previous `period` values at or below the current one
+    /// (PineScript `ta.percentrank`).
+    PercentRank { period: usize },
+    /// Distance from the mean of the last `period` values (current one included),
+    /// in population standard deviations.
+    #[serde(rename = "zscore")]
+    ZScore { period: usize },
 }
 
 /// A group of rules combined with a shared logical operator.
@@ -308,6 +464,50 @@ pub struct Rule {
     /// Logical connector to the next rule in the list.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logical_operator: Option<LogicalOperator>,
+    /// Opening parentheses placed before this rule, e.g. 1 for `(A OR B) AND C` on A.
+    /// When every rule has 0 open/close parens the list is evaluated left to right.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub open_parens: u8,
+    /// Closing parentheses placed after this rule, e.g. 1 for `(A OR B) AND C` on B.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub close_parens: u8,
+    /// Absolute tolerance for `Comparator::Equal`. `None` = relative default
+    /// (`EQUAL_RELATIVE_TOLERANCE` of the larger magnitude).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+}
+
+/// Default relative tolerance for `Comparator::Equal`, scaled by `max(1, |left|, |right|)`.
+pub const EQUAL_RELATIVE_TOLERANCE: f64 = 1e-9;
+
+fn is_zero(v: &u8) -> bool {
+    *v == 0
+}
+
+/// Reads `Operand::offset` as a signed number so a negative (future-bar) offset
+/// gets a clear message instead of serde's generic "expected usize".
+fn deserialize_offset<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    match Option::<i64>::deserialize(deserializer)? {
+        Some(n) if n < 0 => Err(serde::de::Error::custom(format!(
+            "operand offset {} would read a future bar: offsets count bars back and must be 0 or more",
+            n
+        ))),
+        n => Ok(n.map(|n| n as usize)),
+    }
+}
+
+impl Rule {
+    /// True if any rule in the slice opens or closes a parenthesised group.
+    pub fn any_parens(rules: &[Rule]) -> bool {
+        rules.iter().any(|r| r.open_parens > 0 || r.close_parens > 0)
+    }
+
+    /// `Comparator::Equal` test: within `tolerance` when set, otherwise within
+    /// `EQUAL_RELATIVE_TOLERANCE` relative to the larger magnitude (floored at 1).
+    pub fn values_equal(tolerance: Option<f64>, left: f64, right: f64) -> bool {
+        let tol = tolerance.unwrap_or_else(|| EQUAL_RELATIVE_TOLERANCE * left.abs().max(right.abs()).max(1.0));
+        (left - right).abs() <= tol
+    }
 }
 
 // ── Position Sizing ──
@@ -365,13 +565,24 @@ pub enum StopLossType {
     Pips,
     Percentage,
     ATR,
+    /// Chande Kroll volatility stop: starts `value` × ATR from entry, then trails each
+    /// bar to the highest high (longs) / lowest low (shorts) of the last `atr_period`
+    /// bars, less / plus `value` × ATR. Only ever tightens.
+    ChandeKroll,
+}
+
+impl StopLossType {
+    /// True for the types whose distance is a multiple of ATR(`atr_period`).
+    pub fn uses_atr(self) -> bool {
+        matches!(self, StopLossType::ATR | StopLossType::ChandeKroll)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StopLoss {
     pub sl_type: StopLossType,
     pub value: f64,
-    /// ATR period used when sl_type is ATR.
+    /// ATR period used when sl_type is ATR; also the high/low lookback for ChandeKroll.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub atr_period: Option<usize>,
 }
@@ -400,6 +611,9 @@ pub struct TakeProfit {
 pub enum TrailingStopType {
     ATR,
     RiskReward,
+    /// Fixed distance of `value` pips that only tightens in whole `step_pips`
+    /// increments of profit.
+    Step,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -413,6 +627,9 @@ pub struct TrailingStop {
     /// `None` = trailing starts immediately (previous behaviour).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub activation_pips: Option<f64>,
+    /// Step trailing: profit increment, in pips, per stop move. Defaults to `value`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_pips: Option<f64>,
 }
 
 // ── Trading Costs ──
@@ -432,6 +649,7 @@ pub struct TradingCosts {
     pub slippage_random: bool,
     /// Skip entry if current spread exceeds this value (pips). None = no filter.
     /// In bar-mode uses fixed spread; in tick-mode uses real bid-ask spread.
+    /// Suppressed entries are counted in the backtest warnings.
     #[serde(default)]
     pub max_spread_pips: Option<f64>,
 }
@@ -466,6 +684,15 @@ pub struct CloseTradesAt {
     pub minute: u8,
 }
 
+// ── Session Exit ──
+
+/// Deterministic exit at the end of each trading session (see `InstrumentConfig::session_start_hour`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SessionExit {
+    /// Close at the close price of the session's final bar with `CloseReason::SessionClose`.
+    MarketOnClose,
+}
+
 // ── Order Type ──
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -536,9 +763,27 @@ pub struct Strategy {
     /// Close position after this many bars regardless of SL/TP or rules.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub close_after_bars: Option<u32>,
+    /// Ignore exit rules until the position has been open this many bars.
+    /// SL/TP, trailing stops and time-based closes still apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_hold_bars: Option<u32>,
+    /// Force-close the position with `CloseReason::MaxHold` once it has been open
+    /// this many bars.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_hold_bars: Option<u32>,
     /// If true, move stop loss to entry price (breakeven) once profit ≥ SL distance.
     #[serde(default)]
     pub move_sl_to_be: bool,
+    /// Close the open position when the opposite entry signal fires (no new entry that bar).
+    #[serde(default)]
+    pub close_on_opposite_signal: bool,
+    /// Close on the opposite entry signal and open the opposite position at the same bar
+    /// open. Implies `close_on_opposite_signal`.
+    #[serde(default)]
+    pub reverse_on_opposite_signal: bool,
+    /// Optional end-of-session exit, applied after intrabar SL/TP resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_exit: Option<SessionExit>,
     /// If set, use this indicator-based offset for Stop/Limit order target price.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub entry_order_indicator: Option<OrderPriceConfig>,
@@ -570,6 +815,34 @@ impl Default for BacktestPrecision {
     }
 }
 
+/// Which exit wins when a bar-open exit and an SL/TP hit both qualify on the same bar.
+///
+/// Within a bar the engine runs: exit signals, then exit-after-bars / max hold at the
+/// open; SL/TP (and trailing stop) across the bar; time close at the bar close.
+/// Time close can only fire if the position survived SL/TP, so it always ranks last.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ExitPriority {
+    /// Bar-open exits (signal, exit-after-bars, max hold) fill at the open before
+    /// the bar's range is checked against SL/TP.
+    #[default]
+    SignalFirst,
+    /// If SL/TP would trigger anywhere in the bar, it takes the exit and bar-open
+    /// exits are skipped — the conservative reading when the open is the only timing
+    /// information.
+    StopsFirst,
+}
+
+/// Equity sampled for the drawdown curve at each bar.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DrawdownBasis {
+    /// Equity at the bar close, marking the open position at the close.
+    #[default]
+    BarClose,
+    /// Worst equity within the bar: the open position marked at the sub-bar low
+    /// (longs) or high (shorts), or the bar's own low/high without sub-bar data.
+    Intrabar,
+}
+
 // ── Backtest Config ──
 
 /// Configuration for a single backtest run.
@@ -592,4 +865,132 @@ pub struct BacktestConfig {
     /// Defaults to 20 when not set.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pending_order_expiry_bars: Option<usize>,
+    /// Evaluate indicators and entry/exit rules on Heikin-Ashi candles.
+    /// Fills, SL/TP, costs and equity still use the raw OHLC series.
+    #[serde(default)]
+    pub use_heikin_ashi: bool,
+    /// Cap on the number of equity/drawdown points stored in the results.
+    /// Points are picked during the run: every Nth bar (N fixed from the run length)
+    /// for up to half the cap, trade entry/exit bars for the rest, plus the last bar.
+    /// Metrics are always computed over every bar. `None` stores one point per bar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equity_sampling: Option<usize>,
+    /// Real-tick modes only: load ticks in windows of this many TF candles instead
+    /// of holding the whole range in memory. Lowers peak RAM on multi-year tick sets
+    /// at the cost of re-reading partition files per window. `None` loads everything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tick_chunk_bars: Option<usize>,
+    /// Size percent/risk-based positions from running equity (`true`, the default) or
+    /// from `initial_capital` on every trade. The equity curve compounds either way.
+    #[serde(default = "default_true")]
+    pub compounding: bool,
+    /// End the run once equity (including open P&L) falls to this level: any open
+    /// position is closed at the bar close with `CloseReason::StopOut`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_out_equity: Option<f64>,
+    /// `M1TickSimulation` only: sub-bar resolution used to resolve SL/TP inside each
+    /// bar (M1, M5 or M15). Coarser sub-bars run faster but order fewer intra-bar
+    /// moves. `None` uses M1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub magnifier_timeframe: Option<Timeframe>,
+    /// Currency the account — capital, P&L and metrics — is denominated in (e.g. "USD").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_currency: Option<String>,
+    /// Converts P&L and percentage commission from the instrument's quote currency into
+    /// `account_currency` (e.g. GBP→USD for EUR/GBP on a USD account). When set,
+    /// `pip_value` is read as quote currency. `None` = already in account currency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_conversion: Option<QuoteConversion>,
+    /// Minimum number of bars to skip before the first signal is evaluated. The run
+    /// always skips at least the strategy's lookback; raise this for EMA-seeded chains
+    /// that need longer to converge. `None` = lookback only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup_bars: Option<usize>,
+    /// Resolution order when a bar-open exit and SL/TP both qualify on one bar.
+    #[serde(default)]
+    pub exit_priority: ExitPriority,
+    /// When `RealTickRealSpread` is requested but the symbol has no raw bid/ask ticks,
+    /// run as `RealTickCustomSpread` (with a warning) instead of failing.
+    #[serde(default)]
+    pub fallback_on_missing_raw: bool,
+    /// Equity the drawdown curve is measured on.
+    #[serde(default)]
+    pub drawdown_basis: DrawdownBasis,
+    /// Flag the run as overtrading when more than this many trades are entered within
+    /// any rolling hour. `None` uses `DEFAULT_OVERTRADING_THRESHOLD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtrading_threshold: Option<usize>,
+    /// Classify bars as trending or ranging and tag each trade with the regime at entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regime: Option<RegimeConfig>,
+    /// Seed for random slippage draws, so a run with `slippage_random` can be repeated
+    /// exactly. `None` seeds from OS entropy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slippage_seed: Option<u64>,
+}
+
+/// Indicator a regime classification is read from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RegimeIndicator {
+    /// Trending when ADX is at or above the threshold.
+    ADX,
+    /// Trending when the Choppiness Index is below the threshold.
+    Choppiness,
+}
+
+/// Per-bar trending/ranging classification used to tag trades.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeConfig {
+    pub indicator: RegimeIndicator,
+    pub period: usize,
+    pub threshold: f64,
+}
+
+/// Entries per rolling hour above which a run is flagged as overtrading.
+pub const DEFAULT_OVERTRADING_THRESHOLD: usize = 5;
+
+fn default_true() -> bool { true }
+
+/// Where the quote→account currency rate comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuoteConversion {
+    /// One fixed rate for the whole run.
+    Constant { rate: f64 },
+    /// Close of another symbol's last completed bar at each fill, e.g. GBPUSD for EUR/GBP
+    /// on a USD account. `invert` uses `1 / close` for pairs quoted the other way round
+    /// (USDJPY for a JPY quote).
+    Symbol {
+        symbol_id: String,
+        #[serde(default)]
+        invert: bool,
+        /// Rates loaded from `symbol_id` by the command layer before the run.
+        #[serde(skip)]
+        rates: Arc<ConversionRates>,
+    },
+}
+
+/// Quote→account rates by bar open time (microseconds since epoch, ascending), each the
+/// close of a bar lasting `bar_micros`.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionRates {
+    pub timestamps: Vec<i64>,
+    pub rates: Vec<f64>,
+    pub bar_micros: i64,
+}
+
+impl ConversionRates {
+    /// Close of the last bar completed by `micros`. A bar stamped at or before `micros`
+    /// but still open has no close yet, so it isn't used. `None` before the first bar closes.
+    pub fn rate_at(&self, micros: i64) -> Option<f64> {
+        let idx = self.timestamps.partition_point(|&t| t + self.bar_micros <= micros);
+        idx.checked_sub(1).and_then(|i| self.rates.get(i)).copied()
+    }
+}
+
+impl BacktestConfig {
+    /// Equity that position sizing should use given the current running equity.
+    pub fn sizing_equity(&self, equity: f64) -> f64 {
+        if self.compounding { equity } else { self.initial_capital }
+    }
 }
@@ -12,6 +12,19 @@ pub enum CloseReason {
     EndOfData,
     TimeClose,
     ExitAfterBars,
+    /// Force-closed after `Strategy::max_hold_bars` bars.
+    MaxHold,
+    /// Force-closed because equity fell to `BacktestConfig::stop_out_equity`.
+    StopOut,
+    /// Market-on-close exit on the final bar of a session (`Strategy::session_exit`).
+    SessionClose,
+}
+
+/// Market regime at a trade's entry (`BacktestConfig::regime`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MarketRegime {
+    Trending,
+    Ranging,
 }
 
 /// A completed trade with all its details.
@@ -30,9 +43,32 @@ pub struct TradeResult {
     /// Total swap charged over the life of this trade.
     /// Negative = cost (reduces profit), positive = credit (adds to profit).
     pub swap: f64,
+    /// Spread paid on the entry and exit fills, in account currency. Spread already
+    /// embedded in real tick bid/ask quotes is part of `pnl` and not counted here.
+    #[serde(default)]
+    pub spread_cost: f64,
+    /// Slippage paid on the entry and exit fills, in account currency.
+    #[serde(default)]
+    pub slippage_cost: f64,
     pub close_reason: CloseReason,
     pub duration_bars: usize,
     pub duration_time: String,
+    /// Maximum adverse excursion in pips.
     pub mae: f64,
+    /// Maximum favorable excursion in pips.
     pub mfe: f64,
+    /// Distance from entry to the initial stop loss in pips. `None` without a stop loss.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_risk_pips: Option<f64>,
+    /// Which entry rule set fired (e.g. "Long entry rules (3)", "Short entry group 2").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_reason: Option<String>,
+    /// The specific exit trigger: the exit rule set that fired, or the SL/TP/trailing
+    /// level that was hit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_reason: Option<String>,
+    /// Regime of the last closed bar before entry. `None` without `BacktestConfig::regime`
+    /// or while the regime indicator is still warming up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regime: Option<MarketRegime>,
 }
@@ -0,0 +1,371 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::indicators::compute_indicator;
+use crate::errors::AppError;
+use crate::models::candle::Candle;
+use crate::models::config::{InstrumentConfig, Timeframe};
+use crate::models::strategy::{BacktestConfig, IndicatorConfig, QuoteConversion, Strategy};
+use crate::models::symbol::Symbol;
+
+/// Current bundle format version. Bundles written by a newer version are rejected.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Bars of synthetic data used to check that bundled indicator params compute.
+const VALIDATION_BARS: usize = 500;
+
+/// A portable strategy + backtest setup. Carries the symbol's metadata so the
+/// importer can match it against local data, but never the data itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyBundle {
+    pub version: u32,
+    pub exported_at: String,
+    pub strategy: Strategy,
+    pub backtest_config: BacktestConfig,
+    pub symbol: BundleSymbol,
+    /// Name of the quote-conversion symbol, when `backtest_config` converts through one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion_symbol: Option<String>,
+}
+
+/// Symbol metadata stored in a bundle (no file paths or row counts).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSymbol {
+    pub name: String,
+    pub base_timeframe: Timeframe,
+    pub start_date: String,
+    pub end_date: String,
+    pub instrument_config: InstrumentConfig,
+}
+
+impl From<&Symbol> for BundleSymbol {
+    fn from(symbol: &Symbol) -> Self {
+        Self {
+            name: symbol.name.clone(),
+            base_timeframe: symbol.base_timeframe,
+            start_date: symbol.start_date.clone(),
+            end_date: symbol.end_date.clone(),
+            instrument_config: symbol.instrument_config.clone(),
+        }
+    }
+}
+
+impl StrategyBundle {
+    pub fn new(
+        strategy: Strategy,
+        backtest_config: BacktestConfig,
+        symbol: &Symbol,
+        conversion_symbol: Option<&Symbol>,
+    ) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            strategy,
+            backtest_config,
+            symbol: BundleSymbol::from(symbol),
+            conversion_symbol: conversion_symbol.map(|s| s.name.clone()),
+        }
+    }
+
+    /// Point the exported symbol ids — which belong to the exporting machine — at the
+    /// local symbols with the same names. Ids with no local match are cleared so the
+    /// user has to pick a symbol before running. Returns warnings for the user.
+    pub fn remap_symbol_ids(&mut self, symbols: &[Symbol]) -> Vec<String> {
+        let find = |name: &str| symbols.iter().find(|s| s.name.eq_ignore_ascii_case(name));
+        let mut warnings = Vec::new();
+
+        match find(&self.symbol.name) {
+            Some(symbol) => {
+                self.backtest_config.symbol_id = symbol.id.clone();
+                let (ours, theirs) = (&symbol.instrument_config, &self.symbol.instrument_config);
+                if ours.pip_size != theirs.pip_size
+                    || ours.pip_value != theirs.pip_value
+                    || ours.lot_size != theirs.lot_size
+                {
+                    warnings.push(format!(
+                        "Instrument settings of local symbol '{}' differ from the exported ones",
+                        symbol.name
+                    ));
+                }
+            }
+            None => {
+                self.backtest_config.symbol_id = String::new();
+                warnings.push(format!(
+                    "Symbol '{}' is not available locally; select a symbol before running",
+                    self.symbol.name
+                ));
+            }
+        }
+
+        if let Some(QuoteConversion::Symbol { symbol_id, .. }) = &mut self.backtest_config.quote_conversion {
+            let name = self.conversion_symbol.as_deref().unwrap_or_default();
+            match find(name) {
+                Some(symbol) => *symbol_id = symbol.id.clone(),
+                None => {
+                    symbol_id.clear();
+                    warnings.push(format!(
+                        "Conversion symbol '{}' is not available locally; select one before running",
+                        name
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+/// Write a bundle as pretty-printed JSON.
+pub fn write_bundle(bundle: &StrategyBundle, path: &Path) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(bundle)
+        .map_err(|e| AppError::Serialization(format!("serialize bundle: {e}")))?;
+    std::fs::write(path, json)
+        .map_err(|e| AppError::FileWrite(format!("{}: {e}", path.display())))
+}
+
+/// Read a bundle and check it can run on this engine.
+pub fn read_bundle(path: &Path) -> Result<StrategyBundle, AppError> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| AppError::FileRead(format!("{}: {e}", path.display())))?;
+    parse_bundle(&json)
+}
+
+/// Parse bundle JSON, rejecting newer format versions, indicator types this build
+/// does not know, and indicator params the engine refuses to compute.
+pub fn parse_bundle(json: &str) -> Result<StrategyBundle, AppError> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| AppError::Serialization(format!("bundle is not valid JSON: {e}")))?;
+
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| AppError::InvalidConfig("Bundle has no version".into()))?;
+    if version > BUNDLE_VERSION as u64 {
+        return Err(AppError::InvalidConfig(format!(
+            "Bundle version {} is newer than supported version {}",
+            version, BUNDLE_VERSION
+        )));
+    }
+
+    // Check indicators one by one first so an unknown type is reported by name
+    // rather than as an opaque enum error deep inside the strategy.
+    let mut indicators = Vec::new();
+    collect_indicator_values(&value, &mut indicators);
+    let indicators = indicators
+        .into_iter()
+        .map(|v| {
+            let name = v.get("indicator_type").cloned().unwrap_or(Value::Null);
+            serde_json::from_value::<IndicatorConfig>(v.clone()).map_err(|e| {
+                AppError::InvalidConfig(format!("Unsupported indicator {}: {}", name, e))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let bundle: StrategyBundle = serde_json::from_value(value)
+        .map_err(|e| AppError::Serialization(format!("invalid bundle: {e}")))?;
+
+    let candles = validation_candles();
+    for config in &indicators {
+        match compute_indicator(config, &candles) {
+            Ok(_) | Err(AppError::InsufficientData { .. }) => {}
+            Err(e) => {
+                return Err(AppError::InvalidConfig(format!(
+                    "{:?} parameters not supported: {}",
+                    config.indicator_type, e
+                )))
+            }
+        }
+    }
+
+    Ok(bundle)
+}
+
+/// Every JSON object carrying an `indicator_type` key, at any depth.
+fn collect_indicator_values<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("indicator_type") {
+                out.push(value);
+            }
+            map.values().for_each(|v| collect_indicator_values(v, out));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_indicator_values(v, out)),
+        _ => {}
+    }
+}
+
+/// Deterministic oscillating series with non-zero volume, long enough for typical periods.
+fn validation_candles() -> Vec<Candle> {
+    (0..VALIDATION_BARS)
+        .map(|i| {
+            let x = i as f64;
+            let close = 100.0 + 5.0 * (x / 15.0).sin() + 0.01 * x;
+            let open = close - 0.3 * (x / 7.0).cos();
+            Candle {
+                datetime: format!("2024-01-01 00:{:02}:00", i % 60),
+                open,
+                high: open.max(close) + 0.5,
+                low: open.min(close) - 0.5,
+                close,
+                volume: 1000.0 + 100.0 * (x / 5.0).sin().abs(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_strategy_json(indicator: &str) -> Value {
+        serde_json::json!({
+            "id": "s1",
+            "name": "Bundle test",
+            "created_at": "2024-01-01 00:00:00",
+            "updated_at": "2024-01-01 00:00:00",
+            "long_entry_rules": [{
+                "id": "r1",
+                "left_operand": {
+                    "operand_type": "Indicator",
+                    "indicator": { "indicator_type": indicator, "params": { "period": 14 } }
+                },
+                "comparator": "GreaterThan",
+                "right_operand": { "operand_type": "Constant", "constant_value": 50.0 }
+            }],
+            "long_exit_rules": [],
+            "position_sizing": { "sizing_type": "FixedLots", "value": 1.0 },
+            "trading_costs": {
+                "spread_pips": 1.0,
+                "commission_type": "FixedPerLot",
+                "commission_value": 0.0,
+                "slippage_pips": 0.0,
+                "slippage_random": false
+            },
+            "trade_direction": "Long"
+        })
+    }
+
+    fn sample_bundle() -> StrategyBundle {
+        let strategy: Strategy = serde_json::from_value(sample_strategy_json("RSI")).unwrap();
+        let backtest_config: BacktestConfig = serde_json::from_value(serde_json::json!({
+            "symbol_id": "local-id",
+            "timeframe": "h1",
+            "start_date": "2024-01-01",
+            "end_date": "2024-06-30",
+            "initial_capital": 10000.0,
+            "leverage": 1.0
+        }))
+        .unwrap();
+        StrategyBundle {
+            version: BUNDLE_VERSION,
+            exported_at: "2024-07-01 00:00:00".into(),
+            strategy,
+            backtest_config,
+            symbol: BundleSymbol {
+                name: "EURUSD".into(),
+                base_timeframe: Timeframe::M1,
+                start_date: "2020-01-01".into(),
+                end_date: "2024-12-31".into(),
+                instrument_config: InstrumentConfig::default(),
+            },
+            conversion_symbol: None,
+        }
+    }
+
+    fn local_symbol(id: &str, name: &str) -> Symbol {
+        Symbol {
+            id: id.into(),
+            name: name.into(),
+            base_timeframe: Timeframe::M1,
+            upload_date: "2024-07-01".into(),
+            total_rows: 0,
+            start_date: "2020-01-01".into(),
+            end_date: "2024-12-31".into(),
+            timeframe_paths: Default::default(),
+            instrument_config: InstrumentConfig::default(),
+            status: "complete".into(),
+            download_params: None,
+        }
+    }
+
+    fn with_conversion(mut bundle: StrategyBundle, name: &str) -> StrategyBundle {
+        bundle.backtest_config.quote_conversion = Some(QuoteConversion::Symbol {
+            symbol_id: "exporter-gbpusd".into(),
+            invert: false,
+            rates: Default::default(),
+        });
+        bundle.conversion_symbol = Some(name.into());
+        bundle
+    }
+
+    fn conversion_id(bundle: &StrategyBundle) -> &str {
+        match &bundle.backtest_config.quote_conversion {
+            Some(QuoteConversion::Symbol { symbol_id, .. }) => symbol_id,
+            other => panic!("expected symbol conversion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_remap_symbol_ids_by_name() {
+        let mut bundle = with_conversion(sample_bundle(), "GBPUSD");
+        let symbols = [local_symbol("mine-eurusd", "eurusd"), local_symbol("mine-gbpusd", "GBPUSD")];
+        let warnings = bundle.remap_symbol_ids(&symbols);
+
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(bundle.backtest_config.symbol_id, "mine-eurusd");
+        assert_eq!(conversion_id(&bundle), "mine-gbpusd");
+    }
+
+    #[test]
+    fn test_remap_symbol_ids_clears_unknown_symbols() {
+        let mut bundle = with_conversion(sample_bundle(), "GBPUSD");
+        let warnings = bundle.remap_symbol_ids(&[local_symbol("mine-usdjpy", "USDJPY")]);
+
+        assert_eq!(bundle.backtest_config.symbol_id, "");
+        assert_eq!(conversion_id(&bundle), "");
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("GBPUSD"), "{warnings:?}");
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let path = std::env::temp_dir().join(format!("bundle_{}.json", uuid::Uuid::new_v4()));
+        let bundle = sample_bundle();
+        write_bundle(&bundle, &path).unwrap();
+        let loaded = read_bundle(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.version, BUNDLE_VERSION);
+        assert_eq!(loaded.symbol.name, "EURUSD");
+        assert_eq!(loaded.backtest_config.symbol_id, "local-id");
+        assert_eq!(loaded.backtest_config.initial_capital, 10000.0);
+        assert_eq!(
+            serde_json::to_value(&loaded.strategy).unwrap(),
+            serde_json::to_value(&bundle.strategy).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bundle_rejects_unknown_indicator() {
+        let mut value = serde_json::to_value(sample_bundle()).unwrap();
+        value["strategy"] = sample_strategy_json("Bogus");
+        let err = parse_bundle(&value.to_string()).unwrap_err();
+        match err {
+            AppError::InvalidConfig(msg) => assert!(msg.contains("Bogus"), "{msg}"),
+            other => panic!("expected InvalidConfig, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_bundle_rejects_newer_version() {
+        let mut value = serde_json::to_value(sample_bundle()).unwrap();
+        value["version"] = (BUNDLE_VERSION + 1).into();
+        assert!(matches!(
+            parse_bundle(&value.to_string()),
+            Err(AppError::InvalidConfig(_))
+        ));
+    }
+}
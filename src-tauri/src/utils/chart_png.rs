@@ -0,0 +1,301 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use serde::Deserialize;
+
+use crate::errors::AppError;
+use crate::models::result::{BacktestResults, DrawdownPoint, EquityPoint};
+
+/// Smallest and largest accepted image side, in pixels.
+const MIN_WIDTH: u32 = 320;
+const MIN_HEIGHT: u32 = 200;
+const MAX_SIDE: u32 = 8000;
+
+/// Family the bundled font is registered under. Bundling it keeps rendering independent
+/// of the fonts (and fontconfig) installed on the user's machine.
+const FONT: &str = "DejaVu Sans";
+static FONT_DATA: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+
+// Same palette as the HTML report.
+const BG: RGBColor = RGBColor(9, 9, 11);
+const GRID: RGBColor = RGBColor(39, 39, 42);
+const FG: RGBColor = RGBColor(250, 250, 250);
+const MUTED: RGBColor = RGBColor(161, 161, 170);
+const BLUE: RGBColor = RGBColor(59, 130, 246);
+const RED: RGBColor = RGBColor(239, 68, 68);
+
+/// Size and content of an equity curve image.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct EquityPngOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Draw the drawdown curve in a panel under the equity curve.
+    pub include_drawdown: bool,
+}
+
+impl Default for EquityPngOptions {
+    fn default() -> Self {
+        Self { width: 1200, height: 600, include_drawdown: true }
+    }
+}
+
+/// Render the equity curve (and optionally the drawdown curve) to a PNG file.
+pub fn write_equity_png(
+    results: &BacktestResults,
+    options: &EquityPngOptions,
+    path: &Path,
+) -> Result<(), AppError> {
+    let bytes = render_equity_png(
+        &results.equity_curve,
+        &results.drawdown_curve,
+        results.metrics.total_return_pct,
+        results.backtest_config.account_currency.as_deref(),
+        options,
+    )?;
+    std::fs::write(path, bytes).map_err(|e| AppError::FileWrite(format!("{}: {}", path.display(), e)))
+}
+
+/// Render an equity curve (and optionally its drawdown curve) to PNG bytes.
+///
+/// The title carries the final return; the y axes are labelled in `account_currency` (left
+/// unitless when it isn't configured) and percent, the x axis with timestamps taken from
+/// the curve.
+pub fn render_equity_png(
+    equity: &[EquityPoint],
+    drawdown: &[DrawdownPoint],
+    total_return_pct: f64,
+    account_currency: Option<&str>,
+    options: &EquityPngOptions,
+) -> Result<Vec<u8>, AppError> {
+    if !(MIN_WIDTH..=MAX_SIDE).contains(&options.width) || !(MIN_HEIGHT..=MAX_SIDE).contains(&options.height) {
+        return Err(AppError::InvalidConfig(format!(
+            "image size must be between {}x{} and {}x{} pixels, got {}x{}",
+            MIN_WIDTH, MIN_HEIGHT, MAX_SIDE, MAX_SIDE, options.width, options.height
+        )));
+    }
+    if equity.is_empty() {
+        return Err(AppError::InvalidConfig("equity curve is empty".into()));
+    }
+
+    register_font()?;
+    let (width, height) = (options.width, options.height);
+    let scale = (height / 300).clamp(1, 4);
+    let mut pixels = vec![0u8; width as usize * height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (width, height)).into_drawing_area();
+        root.fill(&BG).map_err(plot_err)?;
+        let title = format!("Equity curve - return {:+.2}%", total_return_pct);
+        let root = root
+            .titled(&title, (FONT, 14 * scale).into_font().color(&FG))
+            .map_err(plot_err)?;
+
+        let show_drawdown = options.include_drawdown && !drawdown.is_empty();
+        if show_drawdown {
+            let (upper, lower) = root.split_vertically(root.dim_in_pixel().1 * 2 / 3);
+            draw_equity(&upper, equity, account_currency, scale)?;
+            draw_drawdown(&lower, drawdown, scale)?;
+        } else {
+            draw_equity(&root, equity, account_currency, scale)?;
+        }
+        root.present().map_err(plot_err)?;
+    }
+    encode(&pixels, width, height)
+}
+
+type Area<'a> = DrawingArea<BitMapBackend<'a>, Shift>;
+
+fn plot_err<E: std::error::Error + Send + Sync>(e: DrawingAreaErrorKind<E>) -> AppError {
+    AppError::Internal(format!("chart rendering failed: {}", e))
+}
+
+/// Register the bundled font with plotters, once per process. A failed registration
+/// keeps failing on later calls instead of rendering without the font.
+fn register_font() -> Result<(), AppError> {
+    static REGISTERED: OnceLock<bool> = OnceLock::new();
+    let ok = *REGISTERED.get_or_init(|| plotters::style::register_font(FONT, FontStyle::Normal, FONT_DATA).is_ok());
+    if ok {
+        Ok(())
+    } else {
+        Err(AppError::Internal("bundled chart font is invalid".into()))
+    }
+}
+
+fn label_style(scale: u32) -> TextStyle<'static> {
+    (FONT, 11 * scale).into_font().color(&MUTED)
+}
+
+/// Equity axis label and tick formatter, in `currency` when one is configured.
+fn equity_labels(currency: Option<&str>) -> (String, impl Fn(&f64) -> String + '_) {
+    let desc = match currency {
+        Some(c) => format!("Equity ({})", c),
+        None => "Equity".to_string(),
+    };
+    let tick = move |v: &f64| match currency {
+        Some(c) => format!("{:.0} {}", v, c),
+        None => format!("{:.0}", v),
+    };
+    (desc, tick)
+}
+
+/// Equity in account currency against point index, labelled with the points' timestamps.
+fn draw_equity(area: &Area, data: &[EquityPoint], currency: Option<&str>, scale: u32) -> Result<(), AppError> {
+    let min = data.iter().map(|p| p.equity).fold(f64::INFINITY, f64::min);
+    let max = data.iter().map(|p| p.equity).fold(f64::NEG_INFINITY, f64::max);
+    let pad = ((max - min) * 0.05).max(0.5);
+    let last = (data.len() - 1).max(1) as f64;
+
+    let mut chart = ChartBuilder::on(area)
+        .margin(8 * scale)
+        .x_label_area_size(24 * scale)
+        .y_label_area_size(60 * scale)
+        .build_cartesian_2d(0.0..last, (min - pad)..(max + pad))
+        .map_err(plot_err)?;
+    let (equity_desc, equity_tick) = equity_labels(currency);
+    let timestamp = |x: &f64| {
+        let i = (x.round().max(0.0) as usize).min(data.len() - 1);
+        data[i].timestamp.clone()
+    };
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .bold_line_style(GRID)
+        .light_line_style(BG)
+        .axis_style(GRID)
+        .x_labels(2)
+        .y_labels(5)
+        .x_label_formatter(&timestamp)
+        .y_label_formatter(&equity_tick)
+        .y_desc(equity_desc)
+        .label_style(label_style(scale))
+        .axis_desc_style(label_style(scale))
+        .draw()
+        .map_err(plot_err)?;
+    chart
+        .draw_series(LineSeries::new(
+            data.iter().enumerate().map(|(i, p)| (i as f64, p.equity)),
+            BLUE.stroke_width(scale),
+        ))
+        .map_err(plot_err)?;
+    Ok(())
+}
+
+/// Drawdown (a positive percentage below the running peak) hanging down from a zero line.
+fn draw_drawdown(area: &Area, data: &[DrawdownPoint], scale: u32) -> Result<(), AppError> {
+    let values: Vec<f64> = data.iter().map(|p| p.drawdown_pct.max(0.0)).collect();
+    let deepest = values.iter().copied().fold(0.0_f64, f64::max).max(0.01);
+    let last = (data.len() - 1).max(1) as f64;
+
+    let mut chart = ChartBuilder::on(area)
+        .margin(8 * scale)
+        .y_label_area_size(60 * scale)
+        .build_cartesian_2d(0.0..last, deepest * 1.05..0.0)
+        .map_err(plot_err)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .bold_line_style(GRID)
+        .light_line_style(BG)
+        .axis_style(GRID)
+        .x_labels(0)
+        .y_labels(4)
+        .y_label_formatter(&|v| format!("{:.1}%", v))
+        .y_desc("Drawdown (%)")
+        .label_style(label_style(scale))
+        .axis_desc_style(label_style(scale))
+        .draw()
+        .map_err(plot_err)?;
+    let points = || values.iter().enumerate().map(|(i, v)| (i as f64, *v));
+    chart
+        .draw_series(AreaSeries::new(points(), 0.0, RED.mix(0.3).filled()))
+        .map_err(plot_err)?;
+    chart
+        .draw_series(LineSeries::new(points(), RED.stroke_width(scale)))
+        .map_err(plot_err)?;
+    Ok(())
+}
+
+/// PNG-encode the RGB buffer plotters drew into.
+fn encode(pixels: &[u8], width: u32, height: u32) -> Result<Vec<u8>, AppError> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| AppError::Internal(format!("PNG encoding failed: {}", e)))?;
+        writer
+            .write_image_data(pixels)
+            .map_err(|e| AppError::Internal(format!("PNG encoding failed: {}", e)))?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_curves() -> (Vec<EquityPoint>, Vec<DrawdownPoint>) {
+        let mut peak = f64::NEG_INFINITY;
+        (0..200)
+            .map(|i| {
+                let equity = 10_000.0 + (i as f64 * 0.1).sin() * 300.0 + i as f64 * 5.0;
+                peak = peak.max(equity);
+                let timestamp = format!("2024-01-01 {:02}:{:02}", i / 60, i % 60);
+                (
+                    EquityPoint { timestamp: timestamp.clone(), equity },
+                    DrawdownPoint { timestamp, drawdown_pct: (peak - equity) / peak * 100.0 },
+                )
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn test_equity_png_is_valid_image_of_requested_size() {
+        let (equity, drawdown) = sample_curves();
+        let options = EquityPngOptions { width: 640, height: 360, include_drawdown: true };
+        let bytes = render_equity_png(&equity, &drawdown, 9.87, Some("USD"), &options).unwrap();
+
+        assert!(bytes.starts_with(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']));
+        let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (640, 360));
+
+        // The curves are actually drawn, not just the background.
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+        let has = |c: RGBColor| buf.chunks(3).any(|px| px == [c.0, c.1, c.2]);
+        assert!(has(BLUE));
+        assert!(has(RED));
+        // Title and axis labels are rendered with the bundled font.
+        assert!(has(FG));
+        assert!(has(MUTED));
+        // Drawdowns are positive, so the red curve must leave the zero line.
+        let red_rows = buf.chunks(640 * 3).filter(|row| row.chunks(3).any(|px| px == [RED.0, RED.1, RED.2])).count();
+        assert!(red_rows > 10, "drawdown curve spans only {} rows", red_rows);
+
+        let equity_only = EquityPngOptions { include_drawdown: false, ..options };
+        assert!(!render_equity_png(&equity, &drawdown, 9.87, None, &equity_only).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_equity_png_rejects_bad_size_and_empty_curve() {
+        let (equity, drawdown) = sample_curves();
+        let tiny = EquityPngOptions { width: 10, height: 10, include_drawdown: false };
+        assert!(matches!(render_equity_png(&equity, &drawdown, 0.0, None, &tiny), Err(AppError::InvalidConfig(_))));
+        assert!(render_equity_png(&[], &[], 0.0, None, &EquityPngOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_equity_axis_uses_account_currency() {
+        let (desc, tick) = equity_labels(Some("EUR"));
+        assert_eq!(desc, "Equity (EUR)");
+        assert_eq!(tick(&10_250.4), "10250 EUR");
+
+        let (desc, tick) = equity_labels(None);
+        assert_eq!(desc, "Equity");
+        assert_eq!(tick(&10_250.4), "10250");
+    }
+}
@@ -1,9 +1,13 @@
 ﻿use std::collections::HashSet;
 use std::fmt::Write as FmtWrite;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+use crate::engine::indicators::compute_indicator;
+use crate::engine::position::RR_FALLBACK_RISK_PIPS;
 use crate::errors::AppError;
+use crate::models::candle::Candle;
+use crate::models::config::InstrumentConfig;
 use crate::models::strategy::*;
 
 // ══════════════════════════════════════════════════════════════
@@ -11,7 +15,7 @@ use crate::models::strategy::*;
 // ══════════════════════════════════════════════════════════════
 
 /// A single generated code file.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeFile {
     pub filename: String,
     pub code: String,
@@ -19,10 +23,57 @@ pub struct CodeFile {
     pub is_main: bool,
 }
 
+/// Backtest account settings carried into the generated code so it starts from the
+/// same capital and margin as the backtest.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AccountSettings {
+    pub initial_capital: f64,
+    /// Account leverage (1 = no leverage).
+    pub leverage: f64,
+}
+
+impl Default for AccountSettings {
+    fn default() -> Self {
+        AccountSettings { initial_capital: 10_000.0, leverage: 1.0 }
+    }
+}
+
+impl From<&BacktestConfig> for AccountSettings {
+    fn from(config: &BacktestConfig) -> Self {
+        AccountSettings { initial_capital: config.initial_capital, leverage: config.leverage }
+    }
+}
+
+impl AccountSettings {
+    /// Margin required per position as a percentage of its value (100 / leverage),
+    /// rounded to 2 decimals.
+    fn margin_pct(&self) -> f64 {
+        (10_000.0 / self.leverage.max(1.0)).round() / 100.0
+    }
+}
+
 /// Result of code generation — may contain multiple files.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeGenerationResult {
     pub files: Vec<CodeFile>,
+    /// Strategy validation warnings (see `engine::validation`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Replace every character that is not alphanumeric, `_` or `-` with `_` (EA names).
+pub fn sanitize_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_")
+}
+
+/// Sanitize a generated filename like the EA name, keeping its extension and dropping
+/// any directory components.
+pub fn sanitize_filename(filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    match base.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}.{}", sanitize_name(stem), sanitize_name(ext)),
+        _ => sanitize_name(base),
+    }
 }
 
 // ══════════════════════════════════════════════════════════════
@@ -30,12 +81,19 @@ pub struct CodeGenerationResult {
 // ══════════════════════════════════════════════════════════════
 
 /// Generate MQL5 EA + custom indicator files from a strategy.
-pub fn generate_mql5(strategy: &Strategy) -> Result<CodeGenerationResult, AppError> {
+///
+/// `instrument` is the backtested symbol's config; its points per pip become the
+/// `InpPipPoints` default so pip distances match the engine. `None` leaves it on auto.
+pub fn generate_mql5(
+    strategy: &Strategy,
+    account: &AccountSettings,
+    instrument: Option<&InstrumentConfig>,
+) -> Result<CodeGenerationResult, AppError> {
     let mut out = String::with_capacity(8192);
     let indicators = collect_unique_indicators(strategy);
 
     mql5_header(&mut out, strategy);
-    mql5_inputs(&mut out, strategy, &indicators);
+    mql5_inputs(&mut out, strategy, &indicators, account, instrument);
     mql5_globals(&mut out, strategy, &indicators);
     mql5_on_init(&mut out, &indicators);
     mql5_on_deinit(&mut out, &indicators);
@@ -52,12 +110,16 @@ pub fn generate_mql5(strategy: &Strategy) -> Result<CodeGenerationResult, AppErr
     mql5_trailing_stop(&mut out, strategy);
     mql5_time_helpers(&mut out, strategy);
 
-    let ea_name = strategy.name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let ea_name = sanitize_name(&strategy.name);
 
     // Collect unique indicator types used
     let mut indicator_types_used = HashSet::new();
     for ind in &indicators {
         indicator_types_used.insert(ind.config.indicator_type);
+        // BT_SuperTrend loads BT_ATR for non-Wilder smoothing
+        if ind.config.indicator_type == IndicatorType::SuperTrend && mql5_atr_smoothing(&ind.config.params) != 0 {
+            indicator_types_used.insert(IndicatorType::ATR);
+        }
     }
 
     // Generate custom indicator files
@@ -74,33 +136,206 @@ pub fn generate_mql5(strategy: &Strategy) -> Result<CodeGenerationResult, AppErr
         is_main: true,
     });
 
-    Ok(CodeGenerationResult { files })
+    Ok(CodeGenerationResult { files, warnings: mql5_warnings(strategy) })
+}
+
+/// Strategy features the MQL5 EA cannot reproduce, surfaced as generation warnings.
+fn mql5_warnings(strategy: &Strategy) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let rules = strategy.long_entry_rules.iter()
+        .chain(&strategy.short_entry_rules)
+        .chain(&strategy.long_exit_rules)
+        .chain(&strategy.short_exit_rules)
+        .chain(strategy.long_entry_groups.iter()
+            .chain(&strategy.short_entry_groups)
+            .chain(&strategy.long_exit_groups)
+            .chain(&strategy.short_exit_groups)
+            .flat_map(|g| g.rules.iter()));
+    let transformed = rules
+        .flat_map(|r| [&r.left_operand, &r.right_operand])
+        .filter(|o| o.transform.is_some())
+        .count();
+    if transformed > 0 {
+        warnings.push(format!(
+            "{} rule operand(s) use a percent-rank or z-score transform, which the MQL5 export \
+             does not support; they are emitted as 0 and the EA will not match the backtest",
+            transformed
+        ));
+    }
+    warnings
 }
 
 /// Generate a PineScript v6 strategy from a strategy.
-pub fn generate_pinescript(strategy: &Strategy) -> Result<CodeGenerationResult, AppError> {
+///
+/// `instrument` sets the `i_pip_points` default like in [`generate_mql5`]. With
+/// `emit_alerts`, entry and signal exits also fire `alert()` calls carrying a JSON
+/// payload (symbol, direction, action, price) for webhook-driven execution.
+pub fn generate_pinescript(
+    strategy: &Strategy,
+    account: &AccountSettings,
+    instrument: Option<&InstrumentConfig>,
+    emit_alerts: bool,
+) -> Result<CodeGenerationResult, AppError> {
     let mut out = String::with_capacity(4096);
     let indicators = collect_unique_indicators(strategy);
 
-    pine_header(&mut out, strategy);
-    pine_inputs(&mut out, strategy, &indicators);
+    pine_header(&mut out, strategy, account);
+    pine_inputs(&mut out, strategy, &indicators, instrument);
     pine_indicators(&mut out, &indicators);
     pine_trading_hours(&mut out, strategy);
     pine_conditions(&mut out, strategy);
-    pine_execution(&mut out, strategy);
+    pine_execution(&mut out, strategy, emit_alerts);
     pine_sl_tp(&mut out, strategy);
     pine_plots(&mut out, &indicators, strategy);
 
-    let name = strategy.name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let name = sanitize_name(&strategy.name);
     Ok(CodeGenerationResult {
         files: vec![CodeFile {
             filename: format!("{}.pine", name),
             code: out,
             is_main: true,
         }],
+        warnings: Vec::new(),
     })
 }
 
+/// Trailing bars of the verification series whose indicator values are embedded.
+pub const VERIFICATION_SAMPLE_BARS: usize = 5;
+
+/// One engine indicator value embedded by [`embed_verification_samples`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationSample {
+    pub datetime: String,
+    /// Variable the value is read from in the generated code (e.g. `macd_12_26_9_signal`).
+    pub variable: String,
+    pub value: f64,
+}
+
+/// Engine values to embed, plus the indicators that could not warm up on the series.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationSamples {
+    pub samples: Vec<VerificationSample>,
+    /// `(indicator variable, bars needed, bars available)` for indicators the engine
+    /// rejected with `InsufficientData`.
+    pub insufficient: Vec<(String, usize, usize)>,
+}
+
+/// Compute the engine's value of every strategy indicator output on the last
+/// [`VERIFICATION_SAMPLE_BARS`] bars of `candles`, labelled with the MQL5 (`mql5`)
+/// or PineScript variable names. Warm-up (NaN) values are skipped.
+pub fn verification_samples(
+    strategy: &Strategy,
+    candles: &[Candle],
+    mql5: bool,
+) -> Result<VerificationSamples, AppError> {
+    let first = candles.len().saturating_sub(VERIFICATION_SAMPLE_BARS);
+    let mut result = VerificationSamples::default();
+    for ind in collect_unique_indicators(strategy) {
+        let output = match compute_indicator(&ind.config, candles) {
+            Ok(output) => output,
+            Err(AppError::InsufficientData { needed, available }) => {
+                result.insufficient.push((ind.var_name.clone(), needed, available));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let mut series: Vec<(Option<&str>, &Vec<f64>)> = vec![(None, &output.primary)];
+        for (slot, values) in [(1, &output.secondary), (2, &output.tertiary)] {
+            if let (Some(field), Some(values)) = (slot_output_field(ind.config.indicator_type, slot), values) {
+                series.push((Some(field), values));
+            }
+        }
+        if let Some(extra) = &output.extra {
+            let mut names: Vec<&String> = extra.keys().collect();
+            names.sort();
+            series.extend(names.into_iter().map(|name| (Some(name.as_str()), &extra[name])));
+        }
+        // Extra maps repeat the slot series (Fibonacci levels, Ichimoku tenkan, pivot PP)
+        let mut labelled: Vec<(String, &Vec<f64>)> = Vec::new();
+        for (field, values) in series {
+            let variable = format!("{}{}", ind.var_name, output_variable_suffix(&ind.config, field, mql5));
+            if !labelled.iter().any(|(v, _)| *v == variable) {
+                labelled.push((variable, values));
+            }
+        }
+        for (bar, candle) in candles.iter().enumerate().skip(first) {
+            for (variable, values) in &labelled {
+                if let Some(&value) = values.get(bar).filter(|v| v.is_finite()) {
+                    result.samples.push(VerificationSample {
+                        datetime: candle.datetime.clone(),
+                        variable: variable.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Output field that selects an engine indicator's secondary (`slot` 1) or tertiary
+/// (`slot` 2) series, mirroring `engine::strategy::get_indicator_value`.
+fn slot_output_field(ind_type: IndicatorType, slot: usize) -> Option<&'static str> {
+    match (ind_type, slot) {
+        (IndicatorType::MACD | IndicatorType::PPO | IndicatorType::TRIX | IndicatorType::Klinger, 1) => Some("signal"),
+        (IndicatorType::MACD | IndicatorType::PPO, 2) => Some("histogram"),
+        (IndicatorType::BollingerBands | IndicatorType::KeltnerChannel | IndicatorType::DonchianChannel
+            | IndicatorType::LinearRegression, 1) => Some("upper"),
+        (IndicatorType::BollingerBands | IndicatorType::KeltnerChannel | IndicatorType::DonchianChannel
+            | IndicatorType::LinearRegression, 2) => Some("lower"),
+        (IndicatorType::Stochastic | IndicatorType::StochasticRSI, 1) => Some("d"),
+        (IndicatorType::ADX, 1) => Some("plus_di"),
+        (IndicatorType::ADX, 2) => Some("minus_di"),
+        (IndicatorType::Aroon, 1) => Some("aroon_down"),
+        (IndicatorType::Fractal, 1) => Some("fractal_down"),
+        (IndicatorType::HeikenAshi, 1) => Some("ha_open"),
+        (IndicatorType::Vortex, 1) => Some("vi_minus"),
+        (IndicatorType::QQE, 1) => Some("tr_level"),
+        (IndicatorType::Fibonacci, 1) => Some("level_382"),
+        (IndicatorType::Fibonacci, 2) => Some("level_500"),
+        _ => None,
+    }
+}
+
+/// Variable suffix the generated code uses for `field` of `config` (`None` = default output).
+fn output_variable_suffix(config: &IndicatorConfig, field: Option<&str>, mql5: bool) -> String {
+    let selected = IndicatorConfig { output_field: field.map(str::to_string), ..config.clone() };
+    if mql5 {
+        buffer_suffix(selected.indicator_type, mql5_buffer_index(&selected)).to_string()
+    } else {
+        pine_output_suffix(&selected).to_string()
+    }
+}
+
+/// Append the engine's indicator values at known bars (see [`verification_samples`])
+/// to the main generated file as a comment block, so parity can be eyeballed against
+/// the platform's data window. MQL5 and PineScript share the `//` comment syntax.
+pub fn embed_verification_samples(
+    result: &mut CodeGenerationResult,
+    strategy: &Strategy,
+    candles: &[Candle],
+) -> Result<(), AppError> {
+    let Some(main) = result.files.iter_mut().find(|f| f.is_main) else {
+        return Ok(());
+    };
+    let verification = verification_samples(strategy, candles, main.filename.ends_with(".mq5"))?;
+    let out = &mut main.code;
+    writeln!(out).ok();
+    writeln!(out, "// ═══════════════ VERIFICATION SAMPLES ═══════════════").ok();
+    writeln!(out, "// Backtester indicator values at bar open times; compare with the platform").ok();
+    writeln!(out, "// on the same symbol and timeframe.").ok();
+    for (variable, needed, available) in &verification.insufficient {
+        writeln!(out, "// {}: not enough bars to compute (need {}, have {})", variable, needed, available).ok();
+    }
+    if verification.samples.is_empty() && verification.insufficient.is_empty() {
+        writeln!(out, "// (no indicator values: not enough bars to warm up)").ok();
+    }
+    for s in &verification.samples {
+        writeln!(out, "// {} {} = {:.8}", s.datetime, s.variable, s.value).ok();
+    }
+    Ok(())
+}
+
 // ══════════════════════════════════════════════════════════════
 // Shared helpers
 // ══════════════════════════════════════════════════════════════
@@ -150,7 +385,7 @@ fn collect_unique_indicators(strategy: &Strategy) -> Vec<UniqueIndicator> {
 
     // Also collect ATR if used by SL/TP/TS
     if let Some(sl) = &strategy.stop_loss {
-        if sl.sl_type == StopLossType::ATR {
+        if sl.sl_type.uses_atr() {
             let period = sl.atr_period.unwrap_or(14);
             add_atr_if_missing(&mut seen, &mut result, period);
         }
@@ -244,6 +479,26 @@ fn indicator_var_name(ind: &IndicatorConfig) -> String {
         IndicatorType::LowestIndex => "lo_idx",
         IndicatorType::QQE => "qqe",
         IndicatorType::SchaffTrendCycle => "stc",
+        IndicatorType::Choppiness => "chop",
+        IndicatorType::StochasticRSI => "stochrsi",
+        IndicatorType::AccumulationDistribution => "ad",
+        IndicatorType::ChaikinMoneyFlow => "cmf",
+        IndicatorType::DPO => "dpo",
+        IndicatorType::Coppock => "coppock",
+        IndicatorType::PPO => "ppo",
+        IndicatorType::DonchianChannel => "dc",
+        IndicatorType::TRIX => "trix",
+        IndicatorType::SuperSmoother => "ssmooth",
+        IndicatorType::RoofingFilter => "roof",
+        IndicatorType::ConnorsRSI => "crsi",
+        IndicatorType::ForceIndex => "force",
+        IndicatorType::EaseOfMovement => "eom",
+        IndicatorType::VWMA => "vwma",
+        IndicatorType::ZLEMA => "zlema",
+        IndicatorType::ElderImpulse => "impulse",
+        IndicatorType::MassIndex => "mass",
+        IndicatorType::Klinger => "kvo",
+        IndicatorType::WMA => "wma",
     };
 
     let mut s = String::from(name);
@@ -254,8 +509,16 @@ fn indicator_var_name(ind: &IndicatorConfig) -> String {
         IndicatorType::BarRange | IndicatorType::TrueRange |
         IndicatorType::AwesomeOscillator |
         IndicatorType::Fractal | IndicatorType::HeikenAshi |
-        IndicatorType::Pivots
+        IndicatorType::Pivots | IndicatorType::AccumulationDistribution
     );
+    if ind.indicator_type == IndicatorType::Pivots {
+        match ind.params.pivot_type.unwrap_or_default() {
+            PivotType::Classic => {}
+            PivotType::Fibonacci => s.push_str("_fib"),
+            PivotType::Camarilla => s.push_str("_cam"),
+            PivotType::Woodie => s.push_str("_woo"),
+        }
+    }
     if no_params { return s; }
 
     if let Some(p) = ind.params.period { write!(s, "_{}", p).ok(); }
@@ -269,14 +532,61 @@ fn indicator_var_name(ind: &IndicatorConfig) -> String {
     if let Some(v) = ind.params.maximum_factor { write!(s, "_mf{}", float_to_var(v)).ok(); }
     if let Some(v) = ind.params.gamma { write!(s, "_g{}", float_to_var(v)).ok(); }
     if let Some(v) = ind.params.multiplier { write!(s, "_m{}", float_to_var(v)).ok(); }
+    match ind.params.atr_smoothing.unwrap_or_default() {
+        AtrSmoothing::Wilder => {}
+        AtrSmoothing::Sma => s.push_str("_sma"),
+        AtrSmoothing::Ema => s.push_str("_ema"),
+    }
+    if ind.params.sample_stddev.unwrap_or(false) { s.push_str("_ssd"); }
+    if ind.params.rsi_smoothing.unwrap_or_default() == RsiSmoothing::Sma { s.push_str("_sma"); }
+    if ind.params.ema_seed.unwrap_or_default() == EmaSeed::FirstValue { s.push_str("_fv"); }
     s
 }
 
+/// `InpSmoothing` value understood by BT_ATR.mq5 (0 = Wilder, 1 = SMA, 2 = EMA).
+fn mql5_atr_smoothing(params: &IndicatorParams) -> u8 {
+    match params.atr_smoothing.unwrap_or_default() {
+        AtrSmoothing::Wilder => 0,
+        AtrSmoothing::Sma => 1,
+        AtrSmoothing::Ema => 2,
+    }
+}
+
+/// `InpSmoothing` value understood by BT_RSI.mq5 (0 = Wilder, 1 = SMA / Cutler).
+fn mql5_rsi_smoothing(params: &IndicatorParams) -> u8 {
+    match params.rsi_smoothing.unwrap_or_default() {
+        RsiSmoothing::Wilder => 0,
+        RsiSmoothing::Sma => 1,
+    }
+}
+
+/// `InpSeed` value understood by BT_EMA.mq5 and BT_MACD.mq5 (0 = SMA seed, 1 = first value).
+fn mql5_ema_seed(params: &IndicatorParams) -> u8 {
+    match params.ema_seed.unwrap_or_default() {
+        EmaSeed::SmaSeed => 0,
+        EmaSeed::FirstValue => 1,
+    }
+}
+
+/// `InpPivotType` value understood by BT_Pivots.mq5 (0 = Classic, 1 = Fibonacci, 2 = Camarilla, 3 = Woodie).
+fn mql5_pivot_type(params: &IndicatorParams) -> u8 {
+    match params.pivot_type.unwrap_or_default() {
+        PivotType::Classic => 0,
+        PivotType::Fibonacci => 1,
+        PivotType::Camarilla => 2,
+        PivotType::Woodie => 3,
+    }
+}
+
 /// Get the MQL5 buffer index for a given output_field.
 fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
     let field = ind.output_field.as_deref().unwrap_or("");
     match ind.indicator_type {
-        IndicatorType::MACD => match field {
+        IndicatorType::TRIX | IndicatorType::Klinger => match field {
+            "signal" => 1,
+            _ => 0, // TRIX/KVO line or default
+        },
+        IndicatorType::MACD | IndicatorType::PPO => match field {
             "signal" => 1,
             "histogram" => 2,
             _ => 0, // "macd" or default
@@ -286,7 +596,7 @@ fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
             "lower" => 2,
             _ => 0, // "middle" or default
         },
-        IndicatorType::Stochastic => match field {
+        IndicatorType::Stochastic | IndicatorType::StochasticRSI => match field {
             "D" | "d" => 1,
             _ => 0, // "K" or default
         },
@@ -316,6 +626,8 @@ fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
             "senkou_a" => 2,
             "senkou_b" => 3,
             "chikou" => 4,
+            "cloud_top" => 5,
+            "cloud_bottom" => 6,
             _ => 0, // "tenkan" or default
         },
         IndicatorType::Fibonacci => match field {
@@ -332,7 +644,7 @@ fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
             "s2" => 4,
             _ => 0, // "pp" or default
         },
-        IndicatorType::KeltnerChannel => match field {
+        IndicatorType::KeltnerChannel | IndicatorType::DonchianChannel => match field {
             "upper" => 1,
             "lower" => 2,
             _ => 0, // "middle" or default
@@ -341,6 +653,12 @@ fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
             "tr_level" | "trend_level" => 1,
             _ => 0, // "rsi_ma" or default
         },
+        IndicatorType::LinearRegression => match field {
+            "upper" => 1,
+            "lower" => 2,
+            "slope" => 3,
+            _ => 0, // fitted value or default
+        },
         _ => 0,
     }
 }
@@ -349,17 +667,21 @@ fn mql5_buffer_index(ind: &IndicatorConfig) -> usize {
 fn pine_output_suffix(ind: &IndicatorConfig) -> &str {
     let field = ind.output_field.as_deref().unwrap_or("");
     match ind.indicator_type {
-        IndicatorType::MACD => match field {
+        IndicatorType::TRIX | IndicatorType::Klinger => match field {
+            "signal" => "_signal",
+            _ => "_line",
+        },
+        IndicatorType::MACD | IndicatorType::PPO => match field {
             "signal" => "_signal",
             "histogram" => "_hist",
             _ => "_line",
         },
-        IndicatorType::BollingerBands | IndicatorType::KeltnerChannel => match field {
+        IndicatorType::BollingerBands | IndicatorType::KeltnerChannel | IndicatorType::DonchianChannel => match field {
             "upper" => "_upper",
             "lower" => "_lower",
             _ => "_middle",
         },
-        IndicatorType::Stochastic => match field {
+        IndicatorType::Stochastic | IndicatorType::StochasticRSI => match field {
             "D" | "d" => "_d",
             _ => "_k",
         },
@@ -376,7 +698,7 @@ fn pine_output_suffix(ind: &IndicatorConfig) -> &str {
             _ => "_close",
         },
         IndicatorType::Vortex => match field {
-            "vi_minus" => "_minus",
+            "vi_minus" | "vortex_minus" => "_minus",
             _ => "_plus",
         },
         IndicatorType::Ichimoku => match field {
@@ -384,6 +706,8 @@ fn pine_output_suffix(ind: &IndicatorConfig) -> &str {
             "senkou_a" => "_senkou_a",
             "senkou_b" => "_senkou_b",
             "chikou" => "_chikou",
+            "cloud_top" => "_cloud_top",
+            "cloud_bottom" => "_cloud_bottom",
             _ => "_tenkan",
         },
         IndicatorType::Fibonacci => match field {
@@ -407,6 +731,12 @@ fn pine_output_suffix(ind: &IndicatorConfig) -> &str {
             "-DI" | "minus_di" => "_mdi",
             _ => "",
         },
+        IndicatorType::LinearRegression => match field {
+            "upper" => "_upper",
+            "lower" => "_lower",
+            "slope" => "_slope",
+            _ => "",
+        },
         _ => "",
     }
 }
@@ -416,7 +746,9 @@ fn is_multi_output(ind_type: IndicatorType) -> bool {
         IndicatorType::MACD | IndicatorType::BollingerBands | IndicatorType::Stochastic |
         IndicatorType::Aroon | IndicatorType::Fractal | IndicatorType::HeikenAshi |
         IndicatorType::Vortex | IndicatorType::KeltnerChannel | IndicatorType::Ichimoku |
-        IndicatorType::Fibonacci | IndicatorType::Pivots
+        IndicatorType::Fibonacci | IndicatorType::Pivots | IndicatorType::StochasticRSI |
+        IndicatorType::TRIX | IndicatorType::PPO | IndicatorType::DonchianChannel |
+        IndicatorType::Klinger | IndicatorType::LinearRegression
     )
 }
 
@@ -445,6 +777,10 @@ fn mql5_header(out: &mut String, strategy: &Strategy) {
     writeln!(out, "// Data        : Use same symbol / timeframe / date range as Backtester.").ok();
     writeln!(out, "//               MT5 history server downloads bid prices ✓").ok();
     writeln!(out, "//               CSV from Yahoo/mid-price sources → spreads will differ.").ok();
+    if let Some(note) = hold_limits_note(strategy) {
+        writeln!(out, "//").ok();
+        writeln!(out, "{}", note).ok();
+    }
     writeln!(out, "// ═══════════════════════════════════════════════════════════════════").ok();
     writeln!(out, "#property copyright \"Generated by Backtester Rust\"").ok();
     writeln!(out, "#property version   \"1.00\"").ok();
@@ -453,8 +789,33 @@ fn mql5_header(out: &mut String, strategy: &Strategy) {
     writeln!(out).ok();
 }
 
-fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndicator]) {
+/// Comment flagging `min_hold_bars` / `max_hold_bars`, which the generated code does not
+/// enforce. `None` when neither is set.
+fn hold_limits_note(strategy: &Strategy) -> Option<String> {
+    let mut limits = Vec::new();
+    if let Some(n) = strategy.min_hold_bars {
+        limits.push(format!("exit rules ignored for the first {} bars", n));
+    }
+    if let Some(n) = strategy.max_hold_bars {
+        limits.push(format!("positions force-closed after {} bars", n));
+    }
+    if limits.is_empty() {
+        return None;
+    }
+    Some(format!("// NOTE: Backtester hold limits ({}) are not replicated here", limits.join(", ")))
+}
+
+fn mql5_inputs(
+    out: &mut String,
+    strategy: &Strategy,
+    indicators: &[UniqueIndicator],
+    account: &AccountSettings,
+    instrument: Option<&InstrumentConfig>,
+) {
     writeln!(out, "// ═══════════════ INPUT PARAMETERS ═══════════════").ok();
+    // The deposit and leverage are Strategy Tester settings, not EA inputs
+    writeln!(out, "// Backtest initial deposit : {} (set as Tester \"Deposit\")", account.initial_capital).ok();
+    writeln!(out, "// Backtest leverage        : 1:{} (set as Tester \"Leverage\")", account.leverage).ok();
     writeln!(out, "input int    InpMagicNumber = 123456;   // Magic Number").ok();
 
     // Position sizing
@@ -476,8 +837,9 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
         match sl.sl_type {
             StopLossType::Pips => writeln!(out, "input double InpSLPips = {:.1};       // Stop Loss (pips)", sl.value).ok(),
             StopLossType::Percentage => writeln!(out, "input double InpSLPct = {:.2};       // Stop Loss (%)", sl.value).ok(),
-            StopLossType::ATR => writeln!(out, "input double InpSLAtrMult = {:.1};    // Stop Loss (ATR multiplier)", sl.value).ok(),
+            StopLossType::ATR | StopLossType::ChandeKroll => writeln!(out, "input double InpSLAtrMult = {:.1};    // Stop Loss (ATR multiplier)", sl.value).ok(),
         };
+        chande_kroll_note(out, sl);
     }
     if let Some(tp) = &strategy.take_profit {
         match tp.tp_type {
@@ -486,12 +848,22 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
             TakeProfitType::ATR => writeln!(out, "input double InpTPAtrMult = {:.1};    // Take Profit (ATR multiplier)", tp.value).ok(),
         };
     }
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        writeln!(out, "input double InpPipPoints = {};         // Points per pip (0 = auto from _Digits)", pip_points_default(instrument)).ok();
+    }
     if let Some(ts) = &strategy.trailing_stop {
         match ts.ts_type {
             TrailingStopType::ATR => writeln!(out, "input double InpTSAtrMult = {:.1};    // Trailing Stop (ATR mult)", ts.value).ok(),
             TrailingStopType::RiskReward => writeln!(out, "input double InpTSRR = {:.1};        // Trailing Stop (R:R)", ts.value).ok(),
+            TrailingStopType::Step => {
+                writeln!(out, "input double InpTSPips = {:.1};        // Trailing Stop (pips)", ts.value).ok();
+                writeln!(out, "input double InpTSStepPips = {:.1};    // Trailing Stop step (pips)", ts.step_pips.unwrap_or(ts.value)).ok()
+            }
         };
     }
+    if let Some(pips) = ts_activation_pips(strategy.trailing_stop.as_ref()) {
+        writeln!(out, "input double InpTSActivationPips = {:.1}; // Trailing Stop activation (pips in profit)", pips).ok();
+    }
 
     // Indicator params as inputs
     for ind in indicators {
@@ -506,7 +878,22 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                     writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, period).ok();
                 }
             }
-            IndicatorType::MACD => {
+            IndicatorType::MACD | IndicatorType::PPO => {
+                writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(12)).ok();
+                writeln!(out, "input int    Inp_{}_slow = {};", ind.var_name, p.slow_period.unwrap_or(26)).ok();
+                writeln!(out, "input int    Inp_{}_signal = {};", ind.var_name, p.signal_period.unwrap_or(9)).ok();
+            }
+            IndicatorType::Klinger => {
+                writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(34)).ok();
+                writeln!(out, "input int    Inp_{}_slow = {};", ind.var_name, p.slow_period.unwrap_or(55)).ok();
+                writeln!(out, "input int    Inp_{}_signal = {};", ind.var_name, p.signal_period.unwrap_or(13)).ok();
+            }
+            IndicatorType::MassIndex => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(25)).ok();
+                writeln!(out, "input int    Inp_{}_ema = {};", ind.var_name, p.fast_period.unwrap_or(9)).ok();
+            }
+            IndicatorType::ElderImpulse => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(13)).ok();
                 writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(12)).ok();
                 writeln!(out, "input int    Inp_{}_slow = {};", ind.var_name, p.slow_period.unwrap_or(26)).ok();
                 writeln!(out, "input int    Inp_{}_signal = {};", ind.var_name, p.signal_period.unwrap_or(9)).ok();
@@ -519,6 +906,18 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                 writeln!(out, "input int    Inp_{}_k = {};", ind.var_name, p.k_period.unwrap_or(14)).ok();
                 writeln!(out, "input int    Inp_{}_d = {};", ind.var_name, p.d_period.unwrap_or(3)).ok();
             }
+            IndicatorType::AccumulationDistribution => {} // no params
+            IndicatorType::TRIX => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(15)).ok();
+                // 0 = no signal line, as in the engine when signal_period is unset
+                writeln!(out, "input int    Inp_{}_signal = {};", ind.var_name, p.signal_period.unwrap_or(0)).ok();
+            }
+            IndicatorType::StochasticRSI => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "input int    Inp_{}_k = {};", ind.var_name, p.k_period.unwrap_or(14)).ok();
+                writeln!(out, "input int    Inp_{}_d = {};", ind.var_name, p.d_period.unwrap_or(3)).ok();
+                writeln!(out, "input int    Inp_{}_slowing = {};", ind.var_name, p.slowing.unwrap_or(3)).ok();
+            }
             IndicatorType::ParabolicSAR => {
                 writeln!(out, "input double Inp_{}_af = {:.2};", ind.var_name, p.acceleration_factor.unwrap_or(0.02)).ok();
                 writeln!(out, "input double Inp_{}_max = {:.2};", ind.var_name, p.maximum_factor.unwrap_or(0.20)).ok();
@@ -534,7 +933,14 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                 writeln!(out, "input int    Inp_{}_kijun = {};", ind.var_name, p.slow_period.unwrap_or(26)).ok();
                 writeln!(out, "input int    Inp_{}_senkou = {};", ind.var_name, p.signal_period.unwrap_or(52)).ok();
             }
-            IndicatorType::KeltnerChannel | IndicatorType::SuperTrend => {
+            IndicatorType::DonchianChannel => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(20)).ok();
+            }
+            IndicatorType::KeltnerChannel => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(20)).ok();
+                writeln!(out, "input double Inp_{}_mult = {:.2};", ind.var_name, p.multiplier.unwrap_or(1.5)).ok();
+            }
+            IndicatorType::SuperTrend => {
                 if let Some(period) = p.period {
                     writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, period).ok();
                 }
@@ -551,6 +957,10 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                 writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(20)).ok();
                 writeln!(out, "input double Inp_{}_stddev = {:.1};", ind.var_name, p.std_dev.unwrap_or(2.0)).ok();
             }
+            IndicatorType::LinearRegression => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "input double Inp_{}_stddev = {:.1};", ind.var_name, p.std_dev.unwrap_or(2.0)).ok();
+            }
             IndicatorType::KAMA => {
                 writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(10)).ok();
                 writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(2)).ok();
@@ -565,6 +975,25 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                 writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(10)).ok();
                 writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(20)).ok();
                 writeln!(out, "input int    Inp_{}_slow = {};", ind.var_name, p.slow_period.unwrap_or(50)).ok();
+                writeln!(out, "input int    Inp_{}_smooth = {};", ind.var_name, p.slowing.unwrap_or(3)).ok();
+            }
+            IndicatorType::Coppock => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "input int    Inp_{}_fast = {};", ind.var_name, p.fast_period.unwrap_or(11)).ok();
+                writeln!(out, "input int    Inp_{}_slow = {};", ind.var_name, p.slow_period.unwrap_or(14)).ok();
+            }
+            IndicatorType::RoofingFilter => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "input int    Inp_{}_hp = {};", ind.var_name, p.slow_period.unwrap_or(48)).ok();
+            }
+            IndicatorType::ConnorsRSI => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(3)).ok();
+                writeln!(out, "input int    Inp_{}_streak = {};", ind.var_name, p.fast_period.unwrap_or(2)).ok();
+                writeln!(out, "input int    Inp_{}_rank = {};", ind.var_name, p.slow_period.unwrap_or(100)).ok();
+            }
+            IndicatorType::EaseOfMovement => {
+                writeln!(out, "input int    Inp_{}_period = {};", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "input double Inp_{}_scale = {:.1};", ind.var_name, p.multiplier.unwrap_or(10_000.0)).ok();
             }
             _ => {
                 // Period-only indicators (Aroon, BiggestRange, HighestInRange, etc.)
@@ -589,6 +1018,9 @@ fn mql5_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
         writeln!(out, "input int    InpCloseHour   = {};       // Force-close hour (0-23)", ct.hour).ok();
         writeln!(out, "input int    InpCloseMinute = {};       // Force-close minute (0-59)", ct.minute).ok();
     }
+    if strategy.session_exit.is_some() {
+        writeln!(out, "input int    InpSessionStartHour = 0;  // Session day start hour (match the instrument setting)").ok();
+    }
     if let Some(n) = strategy.close_after_bars {
         writeln!(out, "input int    InpCloseAfterBars = {};    // Close position after N bars", n).ok();
     }
@@ -624,25 +1056,25 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 ind.var_name
             ),
             IndicatorType::EMA => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_EMA\", Inp_{}_period)",
-                ind.var_name
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_EMA\", Inp_{}_period, {})",
+                ind.var_name, mql5_ema_seed(&ind.config.params)
             ),
             IndicatorType::RSI => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_RSI\", Inp_{}_period)",
-                ind.var_name
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_RSI\", Inp_{}_period, {})",
+                ind.var_name, mql5_rsi_smoothing(&ind.config.params)
             ),
             IndicatorType::MACD => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_MACD\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)",
-                ind.var_name
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_MACD\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal, {1})",
+                ind.var_name, mql5_ema_seed(&ind.config.params)
             ),
             IndicatorType::BollingerBands => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_BollingerBands\", Inp_{0}_period, Inp_{0}_stddev)",
-                ind.var_name
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_BollingerBands\", Inp_{0}_period, Inp_{0}_stddev, {1})",
+                ind.var_name, ind.config.params.sample_stddev.unwrap_or(false)
             ),
             // ── SQX indicators (use BT_* names — self-contained files generated alongside EA) ──
             IndicatorType::ATR => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ATR\", Inp_{}_period)",
-                ind.var_name
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ATR\", Inp_{}_period, {})",
+                ind.var_name, mql5_atr_smoothing(&ind.config.params)
             ),
             IndicatorType::Stochastic => format!(
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Stochastic\", Inp_{0}_k, Inp_{0}_d, 3, MODE_SMA, STO_LOWHIGH)",
@@ -676,10 +1108,14 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_KeltnerChannel\", Inp_{0}_period, Inp_{0}_mult)",
                 ind.var_name
             ),
-            IndicatorType::SuperTrend => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_SuperTrend\", 1, Inp_{0}_period, Inp_{0}_mult)",
+            IndicatorType::DonchianChannel => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_DonchianChannel\", Inp_{}_period)",
                 ind.var_name
             ),
+            IndicatorType::SuperTrend => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_SuperTrend\", 1, Inp_{0}_period, Inp_{0}_mult, {1})",
+                ind.var_name, mql5_atr_smoothing(&ind.config.params)
+            ),
             IndicatorType::LaguerreRSI => format!(
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_LaguerreRSI\", Inp_{}_gamma)",
                 ind.var_name
@@ -696,12 +1132,16 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_TrueRange\")"
             ),
             IndicatorType::LinearRegression => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_LinearRegression\", Inp_{}_period, PRICE_CLOSE)",
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_LinearRegression\", Inp_{0}_period, PRICE_CLOSE, Inp_{0}_stddev)",
                 ind.var_name
             ),
             IndicatorType::Fractal => format!(
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Fractal\", 3)"
             ),
+            IndicatorType::Pivots => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Pivots\", {})",
+                mql5_pivot_type(&ind.config.params)
+            ),
             IndicatorType::HeikenAshi => format!(
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_HeikenAshi\")"
             ),
@@ -710,7 +1150,7 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 ind.var_name
             ),
             IndicatorType::HullMA => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_HullMA\", Inp_{}_period, 2.0, PRICE_CLOSE)",
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_HullMA\", Inp_{}_period)",
                 ind.var_name
             ),
             IndicatorType::UlcerIndex => format!(
@@ -737,6 +1177,38 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Reflex\", Inp_{}_period)",
                 ind.var_name
             ),
+            IndicatorType::SuperSmoother => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_SuperSmoother\", Inp_{}_period)",
+                ind.var_name
+            ),
+            IndicatorType::RoofingFilter => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_RoofingFilter\", Inp_{0}_hp, Inp_{0}_period)",
+                ind.var_name
+            ),
+            IndicatorType::ConnorsRSI => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ConnorsRSI\", Inp_{0}_period, Inp_{0}_streak, Inp_{0}_rank)",
+                ind.var_name
+            ),
+            IndicatorType::ForceIndex => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ForceIndex\", Inp_{}_period)",
+                ind.var_name
+            ),
+            IndicatorType::EaseOfMovement => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_EaseOfMovement\", Inp_{0}_period, Inp_{0}_scale)",
+                ind.var_name
+            ),
+            IndicatorType::VWMA => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_VWMA\", Inp_{}_period)",
+                ind.var_name
+            ),
+            IndicatorType::WMA => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_WMA\", Inp_{}_period)",
+                ind.var_name
+            ),
+            IndicatorType::ZLEMA => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ZLEMA\", Inp_{}_period)",
+                ind.var_name
+            ),
             // ── New SQX indicators ──
             IndicatorType::AvgVolume => format!(
                 "iCustom(_Symbol, PERIOD_CURRENT, \"BT_AvgVolume\", Inp_{}_period)",
@@ -755,7 +1227,7 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 ind.var_name
             ),
             IndicatorType::KAMA => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_KAMA\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, 0)",
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_KAMA\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, PRICE_CLOSE)",
                 ind.var_name
             ),
             IndicatorType::LowestIndex => format!(
@@ -767,7 +1239,7 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 ind.var_name
             ),
             IndicatorType::SchaffTrendCycle => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_SchaffTrendCycle\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, 3.0)",
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_SchaffTrendCycle\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_smooth)",
                 ind.var_name
             ),
             // ── Non-SQX indicators (keep BT_* custom files) ──
@@ -786,7 +1258,35 @@ fn mql5_on_init(out: &mut String, indicators: &[UniqueIndicator]) {
                 ind.var_name
             ),
             IndicatorType::StdDev => format!(
-                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_StdDev\", Inp_{}_period)",
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_StdDev\", Inp_{}_period, {})",
+                ind.var_name, ind.config.params.sample_stddev.unwrap_or(false)
+            ),
+            IndicatorType::TRIX => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_TRIX\", Inp_{0}_period, Inp_{0}_signal)",
+                ind.var_name
+            ),
+            IndicatorType::StochasticRSI => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_StochasticRSI\", Inp_{0}_period, Inp_{0}_k, Inp_{0}_d, Inp_{0}_slowing)",
+                ind.var_name
+            ),
+            IndicatorType::PPO => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_PPO\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal, {1})",
+                ind.var_name, mql5_ema_seed(&ind.config.params)
+            ),
+            IndicatorType::MassIndex => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_MassIndex\", Inp_{0}_period, Inp_{0}_ema)",
+                ind.var_name
+            ),
+            IndicatorType::Klinger => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Klinger\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)",
+                ind.var_name
+            ),
+            IndicatorType::ElderImpulse => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_ElderImpulse\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)",
+                ind.var_name
+            ),
+            IndicatorType::Coppock => format!(
+                "iCustom(_Symbol, PERIOD_CURRENT, \"BT_Coppock\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow)",
                 ind.var_name
             ),
             // --- Fallback for any remaining types ---
@@ -905,6 +1405,15 @@ fn mql5_on_tick(out: &mut String, strategy: &Strategy) {
         writeln!(out).ok();
     }
 
+    // ── Market-on-close at the end of each session ─────────────────────────────
+    if strategy.session_exit.is_some() {
+        writeln!(out, "   // Market-on-close: the previous bar was the last of its session day").ok();
+        writeln!(out, "   if((long)(iTime(_Symbol, PERIOD_CURRENT, 1) - InpSessionStartHour * 3600) / 86400 !=").ok();
+        writeln!(out, "      (long)(currentBarTime - InpSessionStartHour * 3600) / 86400)").ok();
+        writeln!(out, "      CloseAllPositions();").ok();
+        writeln!(out).ok();
+    }
+
     // ── Check if this EA has an open position (magic-number filtered) ──────────
     writeln!(out, "   bool hasPosition = false;").ok();
     writeln!(out, "   for(int _pi = 0; _pi < PositionsTotal(); _pi++)").ok();
@@ -992,6 +1501,33 @@ fn mql5_on_tick(out: &mut String, strategy: &Strategy) {
         writeln!(out).ok();
     }
 
+    // ── Close (or reverse) on the opposite entry signal ────────────────────
+    if strategy.close_on_opposite_signal || strategy.reverse_on_opposite_signal {
+        let reverse = strategy.reverse_on_opposite_signal;
+        let guard = guard_parts.join(" && ");
+        for (pos_type, check, open, can_open, has_entry) in [
+            ("POSITION_TYPE_BUY", "CheckShortEntry", "OpenShort", can_short, has_short_entry),
+            ("POSITION_TYPE_SELL", "CheckLongEntry", "OpenLong", can_long, has_long_entry),
+        ] {
+            if !has_entry {
+                continue;
+            }
+            writeln!(out, "      if(posType == {} && {}())", pos_type, check).ok();
+            writeln!(out, "      {{").ok();
+            writeln!(out, "         ClosePosition();").ok();
+            if reverse && can_open {
+                if guard.is_empty() {
+                    writeln!(out, "         {}();   // reverse on opposite signal", open).ok();
+                } else {
+                    writeln!(out, "         if({}) {}();   // reverse on opposite signal", guard, open).ok();
+                }
+            }
+            writeln!(out, "         return;").ok();
+            writeln!(out, "      }}").ok();
+        }
+        writeln!(out).ok();
+    }
+
     let mut first_exit = true;
     if can_long && has_long_exit {
         writeln!(out, "      if(posType == POSITION_TYPE_BUY && CheckLongExit())").ok();
@@ -1016,6 +1552,71 @@ fn all_rules_from_groups(groups: &[RuleGroup]) -> Vec<Rule> {
     groups.iter().flat_map(|g| g.rules.iter().cloned()).collect()
 }
 
+/// Join per-rule expressions honouring each rule's parentheses (shared by MQL5 and Pine).
+///
+/// Mirrors `engine::strategy::combine_rules`: every group folds left to right, so when a
+/// level mixes AND and OR the left-hand side is wrapped in extra parentheses to stop the
+/// target language's AND-over-OR precedence from changing the result.
+fn grouped_rules_expression(rules: &[Rule], exprs: &[String], and: &str, or: &str) -> String {
+    // (accumulated expression, last operator used at this level, operator joining the next value)
+    struct Frame<'a> {
+        acc: Option<String>,
+        last_op: Option<&'a str>,
+        pending: &'a str,
+    }
+    fn push_value<'a>(frame: &mut Frame<'a>, value: String) {
+        frame.acc = Some(match frame.acc.take() {
+            None => value,
+            Some(acc) => {
+                let acc = match frame.last_op {
+                    Some(op) if op != frame.pending => format!("({})", acc),
+                    _ => acc,
+                };
+                frame.last_op = Some(frame.pending);
+                format!("{} {} {}", acc, frame.pending, value)
+            }
+        });
+    }
+    fn close_group(stack: &mut Vec<Frame>) {
+        let group = stack.pop().and_then(|f| f.acc).unwrap_or_else(|| "false".into());
+        if let Some(top) = stack.last_mut() {
+            push_value(top, format!("({})", group));
+        }
+    }
+
+    let mut stack = vec![Frame { acc: None, last_op: None, pending: and }];
+    for (rule, expr) in rules.iter().zip(exprs) {
+        for _ in 0..rule.open_parens {
+            stack.push(Frame { acc: None, last_op: None, pending: and });
+        }
+        push_value(stack.last_mut().unwrap(), expr.clone());
+        for _ in 0..rule.close_parens {
+            if stack.len() == 1 {
+                break;
+            }
+            close_group(&mut stack);
+        }
+        stack.last_mut().unwrap().pending =
+            if let Some(LogicalOperator::Or) = rule.logical_operator { or } else { and };
+    }
+    while stack.len() > 1 {
+        close_group(&mut stack);
+    }
+    stack.pop().and_then(|f| f.acc).unwrap_or_else(|| "false".into())
+}
+
+/// `Comparator::Equal` as a tolerance test mirroring `Rule::values_equal`, given the
+/// target language's abs and two-argument max functions.
+fn approx_equal_expr(tolerance: Option<f64>, left: &str, right: &str, abs: &str, max: &str) -> String {
+    match tolerance {
+        Some(tol) => format!("({abs}({left} - {right}) <= {})", float_literal(tol)),
+        None => format!(
+            "({abs}({left} - {right}) <= {} * {max}(1.0, {max}({abs}({left}), {abs}({right}))))",
+            float_literal(EQUAL_RELATIVE_TOLERANCE)
+        ),
+    }
+}
+
 /// Emit one rule expression (shared between flat and group paths).
 /// extra_shift=1 for "current" so that buf[1] = shift=1 = previous CLOSED bar,
 /// matching the Rust engine which evaluates rules on bar[i-1].
@@ -1027,7 +1628,7 @@ fn mql5_rule_expr(rule: &Rule, indicators: &[UniqueIndicator]) -> String {
         Comparator::LessThan      => format!("{} < {}",  left_curr, right_curr),
         Comparator::GreaterOrEqual => format!("{} >= {}", left_curr, right_curr),
         Comparator::LessOrEqual   => format!("{} <= {}", left_curr, right_curr),
-        Comparator::Equal         => format!("{} == {}", left_curr, right_curr),
+        Comparator::Equal         => approx_equal_expr(rule.tolerance, &left_curr, &right_curr, "MathAbs", "MathMax"),
         Comparator::CrossAbove => {
             // "previous" = 2 bars back (shift=2) to match Rust bar[i-2] at eval time
             let lp = mql5_operand_expr(&rule.left_operand,  2, indicators);
@@ -1126,11 +1727,17 @@ fn mql5_check_rules_fn(out: &mut String, rules: &[Rule], groups: &[RuleGroup], f
     }
     writeln!(out).ok();
 
-    let mut combined = "rule1".to_string();
-    for (i, _rule) in rules.iter().enumerate().skip(1) {
-        let op = if let Some(LogicalOperator::Or) = rules[i - 1].logical_operator { "||" } else { "&&" };
-        write!(combined, " {} rule{}", op, i + 1).ok();
-    }
+    let combined = if Rule::any_parens(rules) {
+        let names: Vec<String> = (1..=rules.len()).map(|i| format!("rule{}", i)).collect();
+        grouped_rules_expression(rules, &names, "&&", "||")
+    } else {
+        let mut combined = "rule1".to_string();
+        for (i, _rule) in rules.iter().enumerate().skip(1) {
+            let op = if let Some(LogicalOperator::Or) = rules[i - 1].logical_operator { "||" } else { "&&" };
+            write!(combined, " {} rule{}", op, i + 1).ok();
+        }
+        combined
+    };
 
     writeln!(out, "   return {};", combined).ok();
     writeln!(out, "}}").ok();
@@ -1174,19 +1781,66 @@ fn collect_buffers_used(rules: &[Rule], ind: &UniqueIndicator) -> Vec<usize> {
 
 fn buffer_suffix(ind_type: IndicatorType, buf_idx: usize) -> &'static str {
     match ind_type {
-        IndicatorType::MACD => match buf_idx { 1 => "_signal", 2 => "_hist", _ => "_line" },
-        IndicatorType::BollingerBands | IndicatorType::KeltnerChannel => match buf_idx { 1 => "_upper", 2 => "_lower", _ => "_middle" },
-        IndicatorType::Stochastic => match buf_idx { 1 => "_d", _ => "_k" },
+        IndicatorType::MACD | IndicatorType::PPO => match buf_idx { 1 => "_signal", 2 => "_hist", _ => "_line" },
+        IndicatorType::TRIX | IndicatorType::Klinger => match buf_idx { 1 => "_signal", _ => "_line" },
+        IndicatorType::BollingerBands | IndicatorType::KeltnerChannel | IndicatorType::DonchianChannel => match buf_idx { 1 => "_upper", 2 => "_lower", _ => "_middle" },
+        IndicatorType::Stochastic | IndicatorType::StochasticRSI => match buf_idx { 1 => "_d", _ => "_k" },
         IndicatorType::ADX => match buf_idx { 1 => "_pdi", 2 => "_mdi", _ => "_val" },
         IndicatorType::Aroon => match buf_idx { 1 => "_down", _ => "_up" },
         IndicatorType::Fractal => match buf_idx { 1 => "_down", _ => "_up" },
         IndicatorType::HeikenAshi => match buf_idx { 1 => "_open", _ => "_close" },
         IndicatorType::Vortex => match buf_idx { 1 => "_minus", _ => "_plus" },
+        IndicatorType::Ichimoku => match buf_idx {
+            1 => "_kijun", 2 => "_senkou_a", 3 => "_senkou_b", 4 => "_chikou", 5 => "_cloud_top", 6 => "_cloud_bottom",
+            _ => "_tenkan",
+        },
+        IndicatorType::LinearRegression => match buf_idx { 1 => "_upper", 2 => "_lower", 3 => "_slope", _ => "_buf" },
         _ => "_buf",
     }
 }
 
+/// The price operand a percent-of-price constant is measured against (same bar and offset).
+fn percent_base_operand(operand: &Operand) -> Operand {
+    Operand {
+        operand_type: OperandType::Price,
+        price_field: operand.percent_of,
+        constant_value: None,
+        percent_of: None,
+        transform: None,
+        ..operand.clone()
+    }
+}
+
+/// Multiplier for a percent-of-price constant, e.g. 0.5 → "1.005". Printed unrounded,
+/// so it parses back to the same double as the engine's `1.0 + pct / 100.0`.
+fn percent_factor(operand: &Operand) -> String {
+    float_literal(1.0 + operand.constant_value.unwrap_or(0.0) / 100.0)
+}
+
+/// Format a constant as an MQL5/Pine float literal that parses back to exactly `v`.
+/// `{}` prints the shortest round-trip digits but drops the decimal point on whole
+/// numbers, and spells out long runs of zeros, so those cases get ".0" or exponent form.
+fn float_literal(v: f64) -> String {
+    if !v.is_finite() {
+        return "0.0".to_string();
+    }
+    let abs = v.abs();
+    if abs != 0.0 && !(1e-5..1e15).contains(&abs) {
+        return format!("{:e}", v);
+    }
+    let s = format!("{}", v);
+    if s.contains('.') {
+        s
+    } else {
+        format!("{}.0", s)
+    }
+}
+
 fn mql5_operand_expr(operand: &Operand, extra_shift: usize, indicators: &[UniqueIndicator]) -> String {
+    if operand.transform.is_some() {
+        // Would need the operand re-evaluated at every shift of the window
+        return "0 /* operand transform not supported in MQL5 */".into();
+    }
     let offset = operand.offset.unwrap_or(0) + extra_shift;
 
     match operand.operand_type {
@@ -1200,27 +1854,40 @@ fn mql5_operand_expr(operand: &Operand, extra_shift: usize, indicators: &[Unique
                 PriceField::DailyHigh => "iHigh(_Symbol, PERIOD_D1, ",
                 PriceField::DailyLow => "iLow(_Symbol, PERIOD_D1, ",
                 PriceField::DailyClose => "iClose(_Symbol, PERIOD_D1, ",
+                PriceField::OpenInterest | PriceField::AvgSpread => "",
             };
             match operand.price_field.unwrap_or(PriceField::Close) {
-                PriceField::DailyOpen | PriceField::DailyHigh |
-                PriceField::DailyLow | PriceField::DailyClose => {
+                field @ (PriceField::DailyOpen | PriceField::DailyHigh |
+                PriceField::DailyLow | PriceField::DailyClose) => {
                     // PERIOD_D1 uses daily-bar indices, not intraday bar indices.
-                    // extra_shift (1 or 2) must NOT be added here — it is an intraday
-                    // concept and would shift into the wrong day.
-                    let daily_shift = operand.offset.unwrap_or(0);
-                    format!("{}{})", func, daily_shift)
+                    // Without an offset extra_shift (1 or 2) must NOT be added here — it
+                    // is an intraday concept and would shift into the wrong day. With one,
+                    // the engine counts chart bars back and reads that bar's day, so map
+                    // the shifted chart bar to its D1 index.
+                    let day = if operand.offset.unwrap_or(0) > 0 {
+                        format!("iBarShift(_Symbol, PERIOD_D1, iTime(_Symbol, PERIOD_CURRENT, {}))", offset)
+                    } else {
+                        "0".to_string()
+                    };
+                    // The engine's daily close is the previous day's close
+                    match (field, day.as_str()) {
+                        (PriceField::DailyClose, "0") => format!("{}1)", func),
+                        (PriceField::DailyClose, _) => format!("{}{} + 1)", func, day),
+                        _ => format!("{}{})", func, day),
+                    }
                 }
+                // MT5 keeps per-bar spread in points; convert to price units
+                PriceField::AvgSpread => format!("(iSpread(_Symbol, PERIOD_CURRENT, {}) * _Point)", offset),
+                // MT5 has no open-interest history, only the current session value
+                PriceField::OpenInterest => "SymbolInfoDouble(_Symbol, SYMBOL_SESSION_INTEREST)".to_string(),
                 _ => format!("{}(_Symbol, PERIOD_CURRENT, {})", func, offset),
             }
         }
-        OperandType::Constant => {
-            let v = operand.constant_value.unwrap_or(0.0);
-            if v == v.floor() && v.abs() < 1_000_000.0 {
-                format!("{:.1}", v)
-            } else {
-                format!("{}", v)
-            }
+        OperandType::Constant if operand.percent_of.is_some() => {
+            let price = mql5_operand_expr(&percent_base_operand(operand), extra_shift, indicators);
+            format!("{} * {}", price, percent_factor(operand))
         }
+        OperandType::Constant => float_literal(operand.constant_value.unwrap_or(0.0)),
         OperandType::Indicator => {
             if let Some(ind) = &operand.indicator {
                 let key = ind.cache_key();
@@ -1430,7 +2097,64 @@ fn mql5_lot_size(out: &mut String, strategy: &Strategy) {
     writeln!(out).ok();
 }
 
+/// Describe the per-bar Chande Kroll update in comments. The exports place only its
+/// initial `value` × ATR stop, since neither platform has a built-in for it.
+fn chande_kroll_note(out: &mut String, sl: &StopLoss) {
+    if sl.sl_type != StopLossType::ChandeKroll {
+        return;
+    }
+    let p = sl.atr_period.unwrap_or(14);
+    let m = sl.value;
+    writeln!(out, "// Chande Kroll stop: after entry the backtester moves the SL on every closed bar,").ok();
+    writeln!(out, "// only when it tightens, to").ok();
+    writeln!(out, "//   long:  highest(high, {p}) - {m:.2} * ATR({p})").ok();
+    writeln!(out, "//   short: lowest(low, {p}) + {m:.2} * ATR({p})").ok();
+    writeln!(out, "// Only the initial ATR stop is placed here; add that update to match the backtest.").ok();
+}
+
+/// Default for the points-per-pip input: the instrument's `pip_multiplier` (rounded to
+/// drop float noise like 10.000000000000002), or 0 to infer it from the quote digits.
+fn pip_points_default(instrument: Option<&InstrumentConfig>) -> String {
+    match instrument.map(InstrumentConfig::pip_multiplier) {
+        Some(points) if points.is_finite() && points > 0.0 => format!("{}", (points * 1e6).round() / 1e6),
+        _ => "0".to_string(),
+    }
+}
+
+/// True when SL or TP is given in pips, so the EA needs a pip size.
+fn uses_pip_distances(sl: Option<&StopLoss>, tp: Option<&TakeProfit>, ts: Option<&TrailingStop>) -> bool {
+    sl.is_some_and(|sl| sl.sl_type == StopLossType::Pips)
+        || tp.is_some_and(|tp| tp.tp_type == TakeProfitType::Pips)
+        || (sl.is_none() && tp.is_some_and(|tp| tp.tp_type == TakeProfitType::RiskReward))
+        || ts_activation_pips(ts).is_some()
+        || ts.is_some_and(|ts| ts.ts_type == TrailingStopType::Step)
+}
+
+/// Trailing-stop activation threshold in pips, if one is configured.
+fn ts_activation_pips(ts: Option<&TrailingStop>) -> Option<f64> {
+    ts.and_then(|ts| ts.activation_pips).filter(|&pips| pips > 0.0)
+}
+
+/// MQL5 `PipSize()` helper. The auto rule matches
+/// `InstrumentConfig::conventional_pip_multiplier`; `InpPipPoints` overrides it for
+/// symbols where it doesn't hold (2-digit JPY brokers are covered, index CFDs usually not).
+fn mql5_pip_size_fn(out: &mut String) {
+    writeln!(out, "//+------------------------------------------------------------------+").ok();
+    writeln!(out, "// Pip size in price units: 10 points on 3/5-digit quotes, 1 point otherwise,").ok();
+    writeln!(out, "// unless InpPipPoints sets the points per pip explicitly (e.g. index CFDs).").ok();
+    writeln!(out, "double PipSize()").ok();
+    writeln!(out, "{{").ok();
+    writeln!(out, "   if(InpPipPoints > 0) return InpPipPoints * _Point;").ok();
+    writeln!(out, "   return (_Digits == 3 || _Digits == 5) ? _Point * 10.0 : _Point;").ok();
+    writeln!(out, "}}").ok();
+    writeln!(out).ok();
+}
+
 fn mql5_sl_tp_helpers(out: &mut String, strategy: &Strategy) {
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        mql5_pip_size_fn(out);
+    }
+
     // SL helper
     writeln!(out, "//+------------------------------------------------------------------+").ok();
     writeln!(out, "double CalculateSL(ENUM_ORDER_TYPE orderType, double price)").ok();
@@ -1439,14 +2163,14 @@ fn mql5_sl_tp_helpers(out: &mut String, strategy: &Strategy) {
     if let Some(sl) = &strategy.stop_loss {
         match sl.sl_type {
             StopLossType::Pips => {
-                writeln!(out, "   double dist = InpSLPips * _Point * 10;").ok();
+                writeln!(out, "   double dist = InpSLPips * PipSize();").ok();
                 writeln!(out, "   return (orderType == ORDER_TYPE_BUY) ? price - dist : price + dist;").ok();
             }
             StopLossType::Percentage => {
                 writeln!(out, "   double dist = price * InpSLPct / 100.0;").ok();
                 writeln!(out, "   return (orderType == ORDER_TYPE_BUY) ? price - dist : price + dist;").ok();
             }
-            StopLossType::ATR => {
+            StopLossType::ATR | StopLossType::ChandeKroll => {
                 let var = format!("atr_{}", sl.atr_period.unwrap_or(14));
                 writeln!(out, "   double atrBuf[];").ok();
                 writeln!(out, "   ArraySetAsSeries(atrBuf, true);").ok();
@@ -1470,9 +2194,13 @@ fn mql5_sl_tp_helpers(out: &mut String, strategy: &Strategy) {
     if let Some(tp) = &strategy.take_profit {
         match tp.tp_type {
             TakeProfitType::Pips => {
-                writeln!(out, "   double dist = InpTPPips * _Point * 10;").ok();
+                writeln!(out, "   double dist = InpTPPips * PipSize();").ok();
                 writeln!(out, "   return (orderType == ORDER_TYPE_BUY) ? price + dist : price - dist;").ok();
             }
+            TakeProfitType::RiskReward if strategy.stop_loss.is_none() => {
+                writeln!(out, "   double tpDist = InpTPRR * {:.1} * PipSize(); // No SL: R:R of a fixed-pip risk", RR_FALLBACK_RISK_PIPS).ok();
+                writeln!(out, "   return (orderType == ORDER_TYPE_BUY) ? price + tpDist : price - tpDist;").ok();
+            }
             TakeProfitType::RiskReward => {
                 writeln!(out, "   double slDist = MathAbs(price - sl);").ok();
                 writeln!(out, "   double tpDist = slDist * InpTPRR;").ok();
@@ -1523,12 +2251,32 @@ fn mql5_trailing_stop(out: &mut String, strategy: &Strategy) {
             writeln!(out, "   if(initialSlDist <= 0) return; // no SL set yet").ok();
             writeln!(out, "   double trailDist = initialSlDist * InpTSRR;").ok();
         }
+        TrailingStopType::Step => {
+            writeln!(out, "   // Step trailing: SL sits trailDist from entry, moved by whole steps of profit").ok();
+            writeln!(out, "   double trailDist = InpTSPips * PipSize();").ok();
+            writeln!(out, "   double trailStep = InpTSStepPips * PipSize();").ok();
+            writeln!(out, "   if(trailStep <= 0) return;").ok();
+        }
+    }
+
+    if ts_activation_pips(Some(ts)).is_some() {
+        writeln!(out).ok();
+        writeln!(out, "   // Trail only once price has moved InpTSActivationPips in profit").ok();
+        writeln!(out, "   double profitDist = (posType == POSITION_TYPE_BUY)").ok();
+        writeln!(out, "      ? SymbolInfoDouble(_Symbol, SYMBOL_BID) - entryPrice").ok();
+        writeln!(out, "      : entryPrice - SymbolInfoDouble(_Symbol, SYMBOL_ASK);").ok();
+        writeln!(out, "   if(profitDist < InpTSActivationPips * PipSize()) return;").ok();
     }
 
     writeln!(out).ok();
     writeln!(out, "   if(posType == POSITION_TYPE_BUY)").ok();
     writeln!(out, "   {{").ok();
-    writeln!(out, "      double newSL = SymbolInfoDouble(_Symbol, SYMBOL_BID) - trailDist;").ok();
+    if ts.ts_type == TrailingStopType::Step {
+        writeln!(out, "      double steps = MathMax(0.0, MathFloor((SymbolInfoDouble(_Symbol, SYMBOL_BID) - entryPrice) / trailStep));").ok();
+        writeln!(out, "      double newSL = entryPrice - trailDist + steps * trailStep;").ok();
+    } else {
+        writeln!(out, "      double newSL = SymbolInfoDouble(_Symbol, SYMBOL_BID) - trailDist;").ok();
+    }
     writeln!(out, "      newSL = NormalizeDouble(newSL, _Digits);").ok();
     writeln!(out, "      // Only tighten — never move SL further from current price").ok();
     writeln!(out, "      if(newSL > currentSL)").ok();
@@ -1536,7 +2284,12 @@ fn mql5_trailing_stop(out: &mut String, strategy: &Strategy) {
     writeln!(out, "   }}").ok();
     writeln!(out, "   else if(posType == POSITION_TYPE_SELL)").ok();
     writeln!(out, "   {{").ok();
-    writeln!(out, "      double newSL = SymbolInfoDouble(_Symbol, SYMBOL_ASK) + trailDist;").ok();
+    if ts.ts_type == TrailingStopType::Step {
+        writeln!(out, "      double steps = MathMax(0.0, MathFloor((entryPrice - SymbolInfoDouble(_Symbol, SYMBOL_ASK)) / trailStep));").ok();
+        writeln!(out, "      double newSL = entryPrice + trailDist - steps * trailStep;").ok();
+    } else {
+        writeln!(out, "      double newSL = SymbolInfoDouble(_Symbol, SYMBOL_ASK) + trailDist;").ok();
+    }
     writeln!(out, "      newSL = NormalizeDouble(newSL, _Digits);").ok();
     writeln!(out, "      // Only tighten — never move SL further from current price").ok();
     writeln!(out, "      if(currentSL == 0 || newSL < currentSL)").ok();
@@ -1581,7 +2334,7 @@ fn mql5_time_helpers(out: &mut String, strategy: &Strategy) {
 // PineScript Generation
 // ══════════════════════════════════════════════════════════════
 
-fn pine_header(out: &mut String, strategy: &Strategy) {
+fn pine_header(out: &mut String, strategy: &Strategy, account: &AccountSettings) {
     writeln!(out, "//@version=6").ok();
 
     // Build strategy() declaration
@@ -1590,12 +2343,10 @@ fn pine_header(out: &mut String, strategy: &Strategy) {
         "overlay=true".into(),
     ];
 
-    // Initial capital (not in strategy model, use a sensible default)
-    params.push("initial_capital=10000".into());
-
-    // PineScript v6: explicit margin defaults
-    params.push("margin_long=100".into());
-    params.push("margin_short=100".into());
+    // Initial capital and margin from the backtest account
+    params.push(format!("initial_capital={}", account.initial_capital));
+    params.push(format!("margin_long={}", account.margin_pct()));
+    params.push(format!("margin_short={}", account.margin_pct()));
 
     // Position sizing
     match strategy.position_sizing.sizing_type {
@@ -1628,11 +2379,15 @@ fn pine_header(out: &mut String, strategy: &Strategy) {
         params.push(format!("slippage={}", (strategy.trading_costs.slippage_pips * 10.0) as i64));
     }
 
+    if let Some(note) = hold_limits_note(strategy) {
+        writeln!(out, "{}", note).ok();
+    }
+
     writeln!(out, "strategy({})", params.join(", ")).ok();
     writeln!(out).ok();
 }
 
-fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndicator]) {
+fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndicator], instrument: Option<&InstrumentConfig>) {
     writeln!(out, "// ═══════════════ INPUTS ═══════════════").ok();
 
     for ind in indicators {
@@ -1659,6 +2414,62 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                 writeln!(out, "i_{}_k = input.int({}, \"Stoch K\")", ind.var_name, p.k_period.unwrap_or(14)).ok();
                 writeln!(out, "i_{}_d = input.int({}, \"Stoch D\")", ind.var_name, p.d_period.unwrap_or(3)).ok();
             }
+            IndicatorType::TRIX => {
+                writeln!(out, "i_{}_period = input.int({}, \"TRIX Period\")", ind.var_name, p.period.unwrap_or(15)).ok();
+                if let Some(signal) = p.signal_period {
+                    writeln!(out, "i_{}_signal = input.int({}, \"TRIX Signal\")", ind.var_name, signal).ok();
+                }
+            }
+            IndicatorType::StochasticRSI => {
+                writeln!(out, "i_{}_period = input.int({}, \"StochRSI RSI Period\")", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "i_{}_k = input.int({}, \"StochRSI K\")", ind.var_name, p.k_period.unwrap_or(14)).ok();
+                writeln!(out, "i_{}_d = input.int({}, \"StochRSI D\")", ind.var_name, p.d_period.unwrap_or(3)).ok();
+                writeln!(out, "i_{}_slowing = input.int({}, \"StochRSI Slowing\")", ind.var_name, p.slowing.unwrap_or(3)).ok();
+            }
+            IndicatorType::PPO => {
+                writeln!(out, "i_{}_fast = input.int({}, \"PPO Fast\")", ind.var_name, p.fast_period.unwrap_or(12)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"PPO Slow\")", ind.var_name, p.slow_period.unwrap_or(26)).ok();
+                writeln!(out, "i_{}_signal = input.int({}, \"PPO Signal\")", ind.var_name, p.signal_period.unwrap_or(9)).ok();
+            }
+            IndicatorType::Klinger => {
+                writeln!(out, "i_{}_fast = input.int({}, \"Klinger Fast\")", ind.var_name, p.fast_period.unwrap_or(34)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"Klinger Slow\")", ind.var_name, p.slow_period.unwrap_or(55)).ok();
+                writeln!(out, "i_{}_signal = input.int({}, \"Klinger Signal\")", ind.var_name, p.signal_period.unwrap_or(13)).ok();
+            }
+            IndicatorType::MassIndex => {
+                writeln!(out, "i_{}_period = input.int({}, \"Mass Index Sum Period\")", ind.var_name, p.period.unwrap_or(25)).ok();
+                writeln!(out, "i_{}_ema = input.int({}, \"Mass Index EMA Period\")", ind.var_name, p.fast_period.unwrap_or(9)).ok();
+            }
+            IndicatorType::ElderImpulse => {
+                writeln!(out, "i_{}_period = input.int({}, \"Impulse EMA Period\")", ind.var_name, p.period.unwrap_or(13)).ok();
+                writeln!(out, "i_{}_fast = input.int({}, \"Impulse MACD Fast\")", ind.var_name, p.fast_period.unwrap_or(12)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"Impulse MACD Slow\")", ind.var_name, p.slow_period.unwrap_or(26)).ok();
+                writeln!(out, "i_{}_signal = input.int({}, \"Impulse MACD Signal\")", ind.var_name, p.signal_period.unwrap_or(9)).ok();
+            }
+            IndicatorType::SchaffTrendCycle => {
+                writeln!(out, "i_{}_period = input.int({}, \"STC Cycle Length\")", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "i_{}_fast = input.int({}, \"STC MACD Fast\")", ind.var_name, p.fast_period.unwrap_or(20)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"STC MACD Slow\")", ind.var_name, p.slow_period.unwrap_or(50)).ok();
+                writeln!(out, "i_{}_smooth = input.int({}, \"STC Smoothing\")", ind.var_name, p.slowing.unwrap_or(3)).ok();
+            }
+            IndicatorType::Coppock => {
+                writeln!(out, "i_{}_period = input.int({}, \"Coppock WMA Period\")", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "i_{}_fast = input.int({}, \"Coppock Short ROC\")", ind.var_name, p.fast_period.unwrap_or(11)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"Coppock Long ROC\")", ind.var_name, p.slow_period.unwrap_or(14)).ok();
+            }
+            IndicatorType::RoofingFilter => {
+                writeln!(out, "i_{}_period = input.int({}, \"Roofing Smoother Period\")", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "i_{}_hp = input.int({}, \"Roofing High-Pass Period\")", ind.var_name, p.slow_period.unwrap_or(48)).ok();
+            }
+            IndicatorType::ConnorsRSI => {
+                writeln!(out, "i_{}_period = input.int({}, \"CRSI RSI Period\")", ind.var_name, p.period.unwrap_or(3)).ok();
+                writeln!(out, "i_{}_streak = input.int({}, \"CRSI Streak Period\")", ind.var_name, p.fast_period.unwrap_or(2)).ok();
+                writeln!(out, "i_{}_rank = input.int({}, \"CRSI Rank Lookback\")", ind.var_name, p.slow_period.unwrap_or(100)).ok();
+            }
+            IndicatorType::EaseOfMovement => {
+                writeln!(out, "i_{}_period = input.int({}, \"EOM Period\")", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "i_{}_scale = input.float({:.1}, \"EOM Volume Scale\")", ind.var_name, p.multiplier.unwrap_or(10_000.0)).ok();
+            }
             IndicatorType::ParabolicSAR => {
                 writeln!(out, "i_{}_af = input.float({:.2}, \"SAR Accel\")", ind.var_name, p.acceleration_factor.unwrap_or(0.02)).ok();
                 writeln!(out, "i_{}_max = input.float({:.2}, \"SAR Max\")", ind.var_name, p.maximum_factor.unwrap_or(0.20)).ok();
@@ -1666,13 +2477,20 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
             IndicatorType::AwesomeOscillator |
             IndicatorType::BarRange | IndicatorType::Fractal |
             IndicatorType::HeikenAshi | IndicatorType::TrueRange |
-            IndicatorType::Pivots => {} // no params
+            IndicatorType::Pivots | IndicatorType::AccumulationDistribution => {} // no params
             IndicatorType::Ichimoku => {
                 writeln!(out, "i_{}_tenkan = input.int({}, \"Ichimoku Tenkan\")", ind.var_name, p.fast_period.unwrap_or(9)).ok();
                 writeln!(out, "i_{}_kijun = input.int({}, \"Ichimoku Kijun\")", ind.var_name, p.slow_period.unwrap_or(26)).ok();
                 writeln!(out, "i_{}_senkou = input.int({}, \"Ichimoku Senkou B\")", ind.var_name, p.signal_period.unwrap_or(52)).ok();
             }
-            IndicatorType::KeltnerChannel | IndicatorType::SuperTrend => {
+            IndicatorType::DonchianChannel => {
+                writeln!(out, "i_{}_period = input.int({}, \"Donchian Period\")", ind.var_name, p.period.unwrap_or(20)).ok();
+            }
+            IndicatorType::KeltnerChannel => {
+                writeln!(out, "i_{}_period = input.int({}, \"Keltner Period\")", ind.var_name, p.period.unwrap_or(20)).ok();
+                writeln!(out, "i_{}_mult = input.float({:.2}, \"Keltner Mult\")", ind.var_name, p.multiplier.unwrap_or(1.5)).ok();
+            }
+            IndicatorType::SuperTrend => {
                 if let Some(period) = p.period {
                     writeln!(out, "i_{}_period = input.int({}, \"{:?} Period\")", ind.var_name, period, ind.config.indicator_type).ok();
                 }
@@ -1685,6 +2503,15 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
                     writeln!(out, "i_{}_gamma = input.float({:.2}, \"Laguerre Gamma\")", ind.var_name, gamma).ok();
                 }
             }
+            IndicatorType::LinearRegression => {
+                writeln!(out, "i_{}_period = input.int({}, \"LinReg Period\")", ind.var_name, p.period.unwrap_or(14)).ok();
+                writeln!(out, "i_{}_stddev = input.float({:.1}, \"LinReg Channel StdDev\")", ind.var_name, p.std_dev.unwrap_or(2.0)).ok();
+            }
+            IndicatorType::KAMA => {
+                writeln!(out, "i_{}_period = input.int({}, \"KAMA Period\")", ind.var_name, p.period.unwrap_or(10)).ok();
+                writeln!(out, "i_{}_fast = input.int({}, \"KAMA Fast\")", ind.var_name, p.fast_period.unwrap_or(2)).ok();
+                writeln!(out, "i_{}_slow = input.int({}, \"KAMA Slow\")", ind.var_name, p.slow_period.unwrap_or(30)).ok();
+            }
             _ => {
                 // Period-only indicators
                 if let Some(period) = p.period {
@@ -1699,8 +2526,9 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
         match sl.sl_type {
             StopLossType::Pips => writeln!(out, "i_sl_pips = input.float({:.1}, \"Stop Loss (pips)\")", sl.value).ok(),
             StopLossType::Percentage => writeln!(out, "i_sl_pct = input.float({:.2}, \"Stop Loss (%)\")", sl.value).ok(),
-            StopLossType::ATR => writeln!(out, "i_sl_atr_mult = input.float({:.1}, \"SL ATR Multiplier\")", sl.value).ok(),
+            StopLossType::ATR | StopLossType::ChandeKroll => writeln!(out, "i_sl_atr_mult = input.float({:.1}, \"SL ATR Multiplier\")", sl.value).ok(),
         };
+        chande_kroll_note(out, sl);
     }
     if let Some(tp) = &strategy.take_profit {
         match tp.tp_type {
@@ -1709,14 +2537,24 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
             TakeProfitType::ATR => writeln!(out, "i_tp_atr_mult = input.float({:.1}, \"TP ATR Multiplier\")", tp.value).ok(),
         };
     }
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        writeln!(out, "i_pip_points = input.float({}, \"Points per Pip (0 = auto)\")", pip_points_default(instrument)).ok();
+    }
 
     // Trailing stop inputs
     if let Some(ts) = &strategy.trailing_stop {
         match ts.ts_type {
             TrailingStopType::ATR => writeln!(out, "i_ts_atr_mult = input.float({:.1}, \"Trailing Stop ATR Multiplier\")", ts.value).ok(),
             TrailingStopType::RiskReward => writeln!(out, "i_ts_rr = input.float({:.1}, \"Trailing Stop R:R\")", ts.value).ok(),
+            TrailingStopType::Step => {
+                writeln!(out, "i_ts_pips = input.float({:.1}, \"Trailing Stop (pips)\")", ts.value).ok();
+                writeln!(out, "i_ts_step_pips = input.float({:.1}, \"Trailing Step (pips)\")", ts.step_pips.unwrap_or(ts.value)).ok()
+            }
         };
     }
+    if let Some(pips) = ts_activation_pips(strategy.trailing_stop.as_ref()) {
+        writeln!(out, "i_ts_activation_pips = input.float({:.1}, \"Trailing Activation (pips)\")", pips).ok();
+    }
 
     if let Some(th) = &strategy.trading_hours {
         writeln!(out, "i_start_hour = input.int({}, \"Start Hour\")", th.start_hour).ok();
@@ -1724,6 +2562,9 @@ fn pine_inputs(out: &mut String, strategy: &Strategy, indicators: &[UniqueIndica
         writeln!(out, "i_end_hour = input.int({}, \"End Hour\")", th.end_hour).ok();
         writeln!(out, "i_end_minute = input.int({}, \"End Minute\")", th.end_minute).ok();
     }
+    if strategy.session_exit.is_some() {
+        writeln!(out, "i_session_start_hour = input.int(0, \"Session Start Hour\")").ok();
+    }
 
     writeln!(out).ok();
 }
@@ -1737,20 +2578,38 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
                 writeln!(out, "{} = ta.sma(close, i_{}_period)", ind.var_name, ind.var_name).ok();
             }
             IndicatorType::EMA => {
+                // ta.ema seeds from the first value: exact for EmaSeed::FirstValue, while the
+                // default SMA seed only converges after the first bars
                 writeln!(out, "{} = ta.ema(close, i_{}_period)", ind.var_name, ind.var_name).ok();
             }
-            IndicatorType::RSI => {
-                writeln!(out, "{} = ta.rsi(close, i_{}_period)", ind.var_name, ind.var_name).ok();
-            }
+            IndicatorType::RSI => match ind.config.params.rsi_smoothing.unwrap_or_default() {
+                RsiSmoothing::Wilder => { writeln!(out, "{} = ta.rsi(close, i_{}_period)", ind.var_name, ind.var_name).ok(); }
+                RsiSmoothing::Sma => {
+                    // Cutler's RSI — ta.rsi is Wilder-smoothed
+                    writeln!(out, "{0}_gain = ta.sma(math.max(ta.change(close), 0), i_{0}_period)", ind.var_name).ok();
+                    writeln!(out, "{0}_loss = ta.sma(math.max(-ta.change(close), 0), i_{0}_period)", ind.var_name).ok();
+                    writeln!(out, "{0} = {0}_loss == 0 ? 100 : 100 - 100 / (1 + {0}_gain / {0}_loss)", ind.var_name).ok();
+                }
+            },
             IndicatorType::MACD => {
                 writeln!(out, "[{0}_line, {0}_signal, {0}_hist] = ta.macd(close, i_{0}_fast, i_{0}_slow, i_{0}_signal)", ind.var_name).ok();
             }
             IndicatorType::BollingerBands => {
-                writeln!(out, "[{0}_basis, {0}_upper, {0}_lower] = ta.bb(close, i_{0}_period, i_{0}_stddev)", ind.var_name).ok();
-            }
-            IndicatorType::ATR => {
-                writeln!(out, "{} = ta.atr(i_{}_period)", ind.var_name, ind.var_name).ok();
+                if ind.config.params.sample_stddev.unwrap_or(false) {
+                    // ta.bb uses population stdev — build the bands from the unbiased (N-1) estimate
+                    writeln!(out, "{0}_basis = ta.sma(close, i_{0}_period)", ind.var_name).ok();
+                    writeln!(out, "{0}_dev = i_{0}_stddev * ta.stdev(close, i_{0}_period, false)", ind.var_name).ok();
+                    writeln!(out, "{0}_upper = {0}_basis + {0}_dev", ind.var_name).ok();
+                    writeln!(out, "{0}_lower = {0}_basis - {0}_dev", ind.var_name).ok();
+                } else {
+                    writeln!(out, "[{0}_basis, {0}_upper, {0}_lower] = ta.bb(close, i_{0}_period, i_{0}_stddev)", ind.var_name).ok();
+                }
             }
+            IndicatorType::ATR => match ind.config.params.atr_smoothing.unwrap_or_default() {
+                AtrSmoothing::Wilder => { writeln!(out, "{} = ta.atr(i_{}_period)", ind.var_name, ind.var_name).ok(); }
+                AtrSmoothing::Sma => { writeln!(out, "{0} = ta.sma(ta.tr(true), i_{0}_period)", ind.var_name).ok(); }
+                AtrSmoothing::Ema => { writeln!(out, "{0} = ta.ema(ta.tr(true), i_{0}_period)", ind.var_name).ok(); }
+            },
             IndicatorType::Stochastic => {
                 writeln!(out, "{0}_k = ta.stoch(close, high, low, i_{0}_k)", ind.var_name).ok();
                 writeln!(out, "{0}_d = ta.sma({0}_k, i_{0}_d)", ind.var_name).ok();
@@ -1835,22 +2694,40 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
                 writeln!(out, "{0}_open := na({0}_open[1]) ? open : ({0}_open[1] + {0}_close[1]) / 2", ind.var_name).ok();
             }
             IndicatorType::HullMA => {
+                // ta.hma = WMA(2·WMA(n/2) − WMA(n), floor(sqrt(n))), same integer periods as the engine
                 writeln!(out, "{0} = ta.hma(close, i_{0}_period)", ind.var_name).ok();
             }
+            IndicatorType::WMA => {
+                writeln!(out, "{0} = ta.wma(close, i_{0}_period)", ind.var_name).ok();
+            }
             IndicatorType::Ichimoku => {
                 writeln!(out, "// Ichimoku").ok();
                 writeln!(out, "{0}_tenkan = (ta.highest(high, i_{0}_tenkan) + ta.lowest(low, i_{0}_tenkan)) / 2", ind.var_name).ok();
                 writeln!(out, "{0}_kijun = (ta.highest(high, i_{0}_kijun) + ta.lowest(low, i_{0}_kijun)) / 2", ind.var_name).ok();
-                writeln!(out, "{0}_senkou_a = ({0}_tenkan + {0}_kijun) / 2", ind.var_name).ok();
-                writeln!(out, "{0}_senkou_b = (ta.highest(high, i_{0}_senkou) + ta.lowest(low, i_{0}_senkou)) / 2", ind.var_name).ok();
+                // Spans are projected forward by kijun bars, as in the engine: the value
+                // on this bar is the one computed kijun bars ago
+                writeln!(out, "{0}_span_a_raw = ({0}_tenkan + {0}_kijun) / 2", ind.var_name).ok();
+                writeln!(out, "{0}_span_b_raw = (ta.highest(high, i_{0}_senkou) + ta.lowest(low, i_{0}_senkou)) / 2", ind.var_name).ok();
+                writeln!(out, "{0}_senkou_a = {0}_span_a_raw[i_{0}_kijun]", ind.var_name).ok();
+                writeln!(out, "{0}_senkou_b = {0}_span_b_raw[i_{0}_kijun]", ind.var_name).ok();
+                writeln!(out, "{0}_cloud_top = math.max({0}_senkou_a, {0}_senkou_b)", ind.var_name).ok();
+                writeln!(out, "{0}_cloud_bottom = math.min({0}_senkou_a, {0}_senkou_b)", ind.var_name).ok();
+                // No look-ahead: the engine's chikou is the current close
                 writeln!(out, "{0}_chikou = close", ind.var_name).ok();
             }
             IndicatorType::KeltnerChannel => {
+                // ta.kc is EMA-based; the engine uses SMA(typical price) ± mult × SMA(high - low)
                 writeln!(out, "// Keltner Channel").ok();
-                writeln!(out, "{0}_middle = ta.ema(close, i_{0}_period)", ind.var_name).ok();
-                writeln!(out, "{0}_atr = ta.atr(i_{0}_period)", ind.var_name).ok();
-                writeln!(out, "{0}_upper = {0}_middle + i_{0}_mult * {0}_atr", ind.var_name).ok();
-                writeln!(out, "{0}_lower = {0}_middle - i_{0}_mult * {0}_atr", ind.var_name).ok();
+                writeln!(out, "{0}_middle = ta.sma(hlc3, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_range = ta.sma(high - low, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_upper = {0}_middle + i_{0}_mult * {0}_range", ind.var_name).ok();
+                writeln!(out, "{0}_lower = {0}_middle - i_{0}_mult * {0}_range", ind.var_name).ok();
+            }
+            IndicatorType::DonchianChannel => {
+                writeln!(out, "// Donchian Channel").ok();
+                writeln!(out, "{0}_upper = ta.highest(high, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_lower = ta.lowest(low, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_middle = ({0}_upper + {0}_lower) / 2", ind.var_name).ok();
             }
             IndicatorType::LaguerreRSI => {
                 writeln!(out, "// Laguerre RSI (custom calculation)").ok();
@@ -1864,27 +2741,89 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
                 writeln!(out, "{0} = {0}_cu + {0}_cd != 0 ? {0}_cu / ({0}_cu + {0}_cd) : 0", ind.var_name).ok();
             }
             IndicatorType::LinearRegression => {
+                writeln!(out, "// Linear Regression channel: bands at ± stddev × residual std dev around the fit").ok();
                 writeln!(out, "{0} = ta.linreg(close, i_{0}_period, 0)", ind.var_name).ok();
+                writeln!(out, "{0}_slope = {0} - ta.linreg(close, i_{0}_period, 1)", ind.var_name).ok();
+                writeln!(out, "float {0}_ss = 0.0", ind.var_name).ok();
+                writeln!(out, "for j = 0 to i_{0}_period - 1", ind.var_name).ok();
+                writeln!(out, "    {0}_ss += math.pow(close[j] - ({0} - {0}_slope * j), 2)", ind.var_name).ok();
+                writeln!(out, "{0}_dev = math.sqrt({0}_ss / i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_upper = {0} + i_{0}_stddev * {0}_dev", ind.var_name).ok();
+                writeln!(out, "{0}_lower = {0} - i_{0}_stddev * {0}_dev", ind.var_name).ok();
             }
             IndicatorType::Momentum => {
-                writeln!(out, "{0} = ta.mom(close, i_{0}_period)", ind.var_name).ok();
-            }
-            IndicatorType::SuperTrend => {
-                writeln!(out, "[{0}, {0}_dir] = ta.supertrend(i_{0}_mult, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0} = close - close[i_{0}_period]", ind.var_name).ok();
             }
+            IndicatorType::SuperTrend => match ind.config.params.atr_smoothing.unwrap_or_default() {
+                AtrSmoothing::Wilder => {
+                    writeln!(out, "[{0}, {0}_dir] = ta.supertrend(i_{0}_mult, i_{0}_period)", ind.var_name).ok();
+                }
+                smoothing => {
+                    // ta.supertrend is hard-wired to Wilder ATR — rebuild it on the configured smoothing
+                    let ma = if smoothing == AtrSmoothing::Sma { "ta.sma" } else { "ta.ema" };
+                    writeln!(out, "{0}_atr = {1}(ta.tr(true), i_{0}_period)", ind.var_name, ma).ok();
+                    writeln!(out, "{0}_up = hl2 + i_{0}_mult * {0}_atr", ind.var_name).ok();
+                    writeln!(out, "{0}_dn = hl2 - i_{0}_mult * {0}_atr", ind.var_name).ok();
+                    writeln!(out, "{0}_prev_up = nz({0}_up[1])", ind.var_name).ok();
+                    writeln!(out, "{0}_prev_dn = nz({0}_dn[1])", ind.var_name).ok();
+                    writeln!(out, "{0}_up := {0}_up < {0}_prev_up or close[1] > {0}_prev_up ? {0}_up : {0}_prev_up", ind.var_name).ok();
+                    writeln!(out, "{0}_dn := {0}_dn > {0}_prev_dn or close[1] < {0}_prev_dn ? {0}_dn : {0}_prev_dn", ind.var_name).ok();
+                    writeln!(out, "float {0} = na", ind.var_name).ok();
+                    writeln!(out, "int {0}_dir = na", ind.var_name).ok();
+                    writeln!(out, "if na({0}_atr[1])", ind.var_name).ok();
+                    writeln!(out, "    {0}_dir := 1", ind.var_name).ok();
+                    writeln!(out, "else if {0}[1] == {0}_prev_up", ind.var_name).ok();
+                    writeln!(out, "    {0}_dir := close > {0}_up ? -1 : 1", ind.var_name).ok();
+                    writeln!(out, "else").ok();
+                    writeln!(out, "    {0}_dir := close < {0}_dn ? 1 : -1", ind.var_name).ok();
+                    writeln!(out, "{0} := {0}_dir == -1 ? {0}_dn : {0}_up", ind.var_name).ok();
+                }
+            },
             IndicatorType::TrueRange => {
                 writeln!(out, "{} = ta.tr(true)", ind.var_name).ok();
             }
             IndicatorType::StdDev => {
-                writeln!(out, "{0} = ta.stdev(close, i_{0}_period)", ind.var_name).ok();
+                let biased = !ind.config.params.sample_stddev.unwrap_or(false);
+                writeln!(out, "{0} = ta.stdev(close, i_{0}_period, {1})", ind.var_name, biased).ok();
             }
             IndicatorType::Reflex => {
                 writeln!(out, "// Reflex (Ehlers) — approximated with custom calc").ok();
                 writeln!(out, "{0} = 2 * ta.sma(close, i_{0}_period) - ta.sma(ta.sma(close, i_{0}_period), i_{0}_period)", ind.var_name).ok();
             }
             IndicatorType::Pivots => {
-                writeln!(out, "// Classic Pivots from prior daily bar").ok();
-                writeln!(out, "[{0}_pp, {0}_r1, {0}_s1, {0}_r2, {0}_s2, {0}_r3, {0}_s3] = request.security(syminfo.tickerid, \"D\", [(high[1]+low[1]+close[1])/3, 2*(high[1]+low[1]+close[1])/3 - low[1], 2*(high[1]+low[1]+close[1])/3 - high[1], (high[1]+low[1]+close[1])/3 + high[1] - low[1], (high[1]+low[1]+close[1])/3 - high[1] + low[1], 2*((high[1]+low[1]+close[1])/3 - low[1]) + (high[1]+low[1]+close[1])/3, 2*((high[1]+low[1]+close[1])/3 - high[1]) + (high[1]+low[1]+close[1])/3])", ind.var_name).ok();
+                let pivot_type = ind.config.params.pivot_type.unwrap_or_default();
+                writeln!(out, "// {:?} Pivots from prior daily bar", pivot_type).ok();
+                writeln!(out, "[{0}_ph, {0}_pl, {0}_pc] = request.security(syminfo.tickerid, \"D\", [high[1], low[1], close[1]])", ind.var_name).ok();
+                writeln!(out, "{0}_rng = {0}_ph - {0}_pl", ind.var_name).ok();
+                match pivot_type {
+                    PivotType::Classic | PivotType::Woodie => {
+                        if pivot_type == PivotType::Woodie {
+                            writeln!(out, "{0}_pp = ({0}_ph + {0}_pl + 2 * {0}_pc) / 4", ind.var_name).ok();
+                        } else {
+                            writeln!(out, "{0}_pp = ({0}_ph + {0}_pl + {0}_pc) / 3", ind.var_name).ok();
+                        }
+                        writeln!(out, "{0}_r1 = 2 * {0}_pp - {0}_pl", ind.var_name).ok();
+                        writeln!(out, "{0}_s1 = 2 * {0}_pp - {0}_ph", ind.var_name).ok();
+                        writeln!(out, "{0}_r2 = {0}_pp + {0}_rng", ind.var_name).ok();
+                        writeln!(out, "{0}_s2 = {0}_pp - {0}_rng", ind.var_name).ok();
+                        writeln!(out, "{0}_r3 = {0}_ph + 2 * ({0}_pp - {0}_pl)", ind.var_name).ok();
+                        writeln!(out, "{0}_s3 = {0}_pl - 2 * ({0}_ph - {0}_pp)", ind.var_name).ok();
+                    }
+                    PivotType::Fibonacci => {
+                        writeln!(out, "{0}_pp = ({0}_ph + {0}_pl + {0}_pc) / 3", ind.var_name).ok();
+                        for (n, ratio) in [(1, "0.382"), (2, "0.618"), (3, "1.0")] {
+                            writeln!(out, "{0}_r{1} = {0}_pp + {2} * {0}_rng", ind.var_name, n, ratio).ok();
+                            writeln!(out, "{0}_s{1} = {0}_pp - {2} * {0}_rng", ind.var_name, n, ratio).ok();
+                        }
+                    }
+                    PivotType::Camarilla => {
+                        writeln!(out, "{0}_pp = ({0}_ph + {0}_pl + {0}_pc) / 3", ind.var_name).ok();
+                        for (n, div) in [(1, 12), (2, 6), (3, 4)] {
+                            writeln!(out, "{0}_r{1} = {0}_pc + {0}_rng * 1.1 / {2}", ind.var_name, n, div).ok();
+                            writeln!(out, "{0}_s{1} = {0}_pc - {0}_rng * 1.1 / {2}", ind.var_name, n, div).ok();
+                        }
+                    }
+                }
             }
             IndicatorType::UlcerIndex => {
                 writeln!(out, "// Ulcer Index").ok();
@@ -1920,8 +2859,14 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
                 writeln!(out, "{0} = ta.highestbars(high, i_{0}_period)", ind.var_name).ok();
             }
             IndicatorType::KAMA => {
-                writeln!(out, "// Kaufman Adaptive MA (approximated as EMA — use SqKAMA in MT5 for exact calc)").ok();
-                writeln!(out, "{0} = ta.ema(close, i_{0}_period)", ind.var_name).ok();
+                // Kaufman Adaptive MA, seeded with the close at bar period-1 like the engine
+                writeln!(out, "{0}_fast_sc = 2.0 / (i_{0}_fast + 1)", ind.var_name).ok();
+                writeln!(out, "{0}_slow_sc = 2.0 / (i_{0}_slow + 1)", ind.var_name).ok();
+                writeln!(out, "{0}_noise = math.sum(math.abs(close - close[1]), i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_er = {0}_noise > 1e-10 ? math.abs(close - close[i_{0}_period]) / {0}_noise : 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_ssc = math.pow({0}_er * ({0}_fast_sc - {0}_slow_sc) + {0}_slow_sc, 2)", ind.var_name).ok();
+                writeln!(out, "var float {0} = na", ind.var_name).ok();
+                writeln!(out, "{0} := bar_index == i_{0}_period - 1 ? close : bar_index >= i_{0}_period ? {0}[1] + {0}_ssc * (close - {0}[1]) : na", ind.var_name).ok();
             }
             IndicatorType::LowestIndex => {
                 writeln!(out, "{0} = ta.lowestbars(low, i_{0}_period)", ind.var_name).ok();
@@ -1933,10 +2878,125 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
             }
             IndicatorType::SchaffTrendCycle => {
                 writeln!(out, "// Schaff Trend Cycle (use SqSchaffTrendCycle in MT5 for exact calc)").ok();
+                writeln!(out, "{0}_alpha = 2.0 / (1.0 + i_{0}_smooth)", ind.var_name).ok();
                 writeln!(out, "{0}_macd = ta.ema(close, i_{0}_fast) - ta.ema(close, i_{0}_slow)", ind.var_name).ok();
                 writeln!(out, "{0}_ll = ta.lowest({0}_macd, i_{0}_period)", ind.var_name).ok();
                 writeln!(out, "{0}_hh = ta.highest({0}_macd, i_{0}_period) - {0}_ll", ind.var_name).ok();
-                writeln!(out, "{0} = {0}_hh != 0 ? 100 * ({0}_macd - {0}_ll) / {0}_hh : na", ind.var_name).ok();
+                writeln!(out, "{0}_k1 = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_k1 := {0}_hh > 0 ? 100 * ({0}_macd - {0}_ll) / {0}_hh : nz({0}_k1[1])", ind.var_name).ok();
+                writeln!(out, "{0}_d1 = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_d1 := na({0}_d1[1]) ? {0}_k1 : {0}_d1[1] + {0}_alpha * ({0}_k1 - {0}_d1[1])", ind.var_name).ok();
+                writeln!(out, "{0}_ll2 = ta.lowest({0}_d1, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_hh2 = ta.highest({0}_d1, i_{0}_period) - {0}_ll2", ind.var_name).ok();
+                writeln!(out, "{0}_k2 = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_k2 := {0}_hh2 > 0 ? 100 * ({0}_d1 - {0}_ll2) / {0}_hh2 : nz({0}_k2[1])", ind.var_name).ok();
+                writeln!(out, "{0} = 0.0", ind.var_name).ok();
+                writeln!(out, "{0} := na({0}[1]) ? {0}_k2 : {0}[1] + {0}_alpha * ({0}_k2 - {0}[1])", ind.var_name).ok();
+            }
+            IndicatorType::Choppiness => {
+                writeln!(out, "// Choppiness Index").ok();
+                writeln!(out, "{0}_range = ta.highest(high, i_{0}_period) - ta.lowest(low, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0} = {0}_range > 0 ? 100 * math.log10(math.sum(ta.tr(true), i_{0}_period) / {0}_range) / math.log10(i_{0}_period) : na", ind.var_name).ok();
+            }
+            IndicatorType::StochasticRSI => {
+                writeln!(out, "{0}_rsi = ta.rsi(close, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_k = ta.sma(ta.stoch({0}_rsi, {0}_rsi, {0}_rsi, i_{0}_k), i_{0}_slowing)", ind.var_name).ok();
+                writeln!(out, "{0}_d = ta.sma({0}_k, i_{0}_d)", ind.var_name).ok();
+            }
+            IndicatorType::TRIX => {
+                writeln!(out, "{0}_e3 = ta.ema(ta.ema(ta.ema(close, i_{0}_period), i_{0}_period), i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_line = 100 * ({0}_e3 - {0}_e3[1]) / {0}_e3[1]", ind.var_name).ok();
+                if ind.config.params.signal_period.is_some() {
+                    writeln!(out, "{0}_signal = ta.ema({0}_line, i_{0}_signal)", ind.var_name).ok();
+                } else {
+                    writeln!(out, "float {0}_signal = na", ind.var_name).ok();
+                }
+            }
+            IndicatorType::AccumulationDistribution => {
+                writeln!(out, "{0}_clv = high - low > 0 ? ((close - low) - (high - close)) / (high - low) : 0.0", ind.var_name).ok();
+                writeln!(out, "{0} = ta.cum({0}_clv * volume)", ind.var_name).ok();
+            }
+            IndicatorType::ChaikinMoneyFlow => {
+                writeln!(out, "{0}_clv = high - low > 0 ? ((close - low) - (high - close)) / (high - low) : 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_vol = math.sum(volume, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0} = {0}_vol > 0 ? math.sum({0}_clv * volume, i_{0}_period) / {0}_vol : na", ind.var_name).ok();
+            }
+            IndicatorType::DPO => {
+                writeln!(out, "{0} = close - ta.sma(close, i_{0}_period)[math.floor(i_{0}_period / 2) + 1]", ind.var_name).ok();
+            }
+            IndicatorType::PPO => {
+                writeln!(out, "{0}_slow_ema = ta.ema(close, i_{0}_slow)", ind.var_name).ok();
+                writeln!(out, "{0}_line = 100 * (ta.ema(close, i_{0}_fast) - {0}_slow_ema) / {0}_slow_ema", ind.var_name).ok();
+                writeln!(out, "{0}_signal = ta.ema({0}_line, i_{0}_signal)", ind.var_name).ok();
+                writeln!(out, "{0}_hist = {0}_line - {0}_signal", ind.var_name).ok();
+            }
+            IndicatorType::Coppock => {
+                writeln!(out, "{0} = ta.wma(ta.roc(close, i_{0}_slow) + ta.roc(close, i_{0}_fast), i_{0}_period)", ind.var_name).ok();
+            }
+            IndicatorType::SuperSmoother => {
+                writeln!(out, "{0}_a1 = math.exp(-1.414 * math.pi / i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_c2 = 2 * {0}_a1 * math.cos(1.414 * math.pi / i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_c3 = -{0}_a1 * {0}_a1", ind.var_name).ok();
+                writeln!(out, "var float {0}_filt = na", ind.var_name).ok();
+                writeln!(out, "{0}_filt := bar_index < 2 ? close : (1 - {0}_c2 - {0}_c3) * (close + close[1]) / 2 + {0}_c2 * {0}_filt[1] + {0}_c3 * {0}_filt[2]", ind.var_name).ok();
+                writeln!(out, "{0} = bar_index < 2 ? na : {0}_filt", ind.var_name).ok();
+            }
+            IndicatorType::RoofingFilter => {
+                writeln!(out, "{0}_w = 0.707 * 2 * math.pi / i_{0}_hp", ind.var_name).ok();
+                writeln!(out, "{0}_alpha = (math.cos({0}_w) + math.sin({0}_w) - 1) / math.cos({0}_w)", ind.var_name).ok();
+                writeln!(out, "var float {0}_hpf = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_hpf := bar_index < 2 ? 0.0 : math.pow(1 - {0}_alpha / 2, 2) * (close - 2 * close[1] + close[2]) + 2 * (1 - {0}_alpha) * {0}_hpf[1] - math.pow(1 - {0}_alpha, 2) * {0}_hpf[2]", ind.var_name).ok();
+                writeln!(out, "{0}_a1 = math.exp(-1.414 * math.pi / i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_c2 = 2 * {0}_a1 * math.cos(1.414 * math.pi / i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0}_c3 = -{0}_a1 * {0}_a1", ind.var_name).ok();
+                writeln!(out, "var float {0}_filt = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_filt := bar_index < 2 ? 0.0 : (1 - {0}_c2 - {0}_c3) * ({0}_hpf + {0}_hpf[1]) / 2 + {0}_c2 * {0}_filt[1] + {0}_c3 * {0}_filt[2]", ind.var_name).ok();
+                writeln!(out, "{0} = bar_index < 2 ? na : {0}_filt", ind.var_name).ok();
+            }
+            IndicatorType::ConnorsRSI => {
+                writeln!(out, "var float {0}_streak = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_streak := close > close[1] ? math.max({0}_streak[1], 0) + 1 : close < close[1] ? math.min({0}_streak[1], 0) - 1 : 0", ind.var_name).ok();
+                writeln!(out, "{0}_roc1 = ta.roc(close, 1)", ind.var_name).ok();
+                writeln!(out, "{0}_below = 0", ind.var_name).ok();
+                writeln!(out, "for j = 1 to i_{0}_rank", ind.var_name).ok();
+                writeln!(out, "    {0}_below += {0}_roc1[j] < {0}_roc1 ? 1 : 0", ind.var_name).ok();
+                writeln!(out, "{0} = (ta.rsi(close, i_{0}_period) + ta.rsi({0}_streak, i_{0}_streak) + 100.0 * {0}_below / i_{0}_rank) / 3", ind.var_name).ok();
+            }
+            IndicatorType::ForceIndex => {
+                writeln!(out, "{0} = ta.ema(ta.change(close) * volume, i_{0}_period)", ind.var_name).ok();
+            }
+            IndicatorType::EaseOfMovement => {
+                writeln!(out, "{0}_emv = high - low > 0 and volume > 0 ? ta.change(hl2) * (high - low) * i_{0}_scale / volume : 0.0", ind.var_name).ok();
+                writeln!(out, "{0} = ta.sma({0}_emv, i_{0}_period)", ind.var_name).ok();
+            }
+            IndicatorType::VWMA => {
+                writeln!(out, "{0}_vs = math.sum(volume, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "{0} = {0}_vs > 0 ? math.sum(close * volume, i_{0}_period) / {0}_vs : ta.sma(close, i_{0}_period)", ind.var_name).ok();
+            }
+            IndicatorType::MassIndex => {
+                writeln!(out, "{0}_single = ta.ema(high - low, i_{0}_ema)", ind.var_name).ok();
+                writeln!(out, "{0}_double = ta.ema({0}_single, i_{0}_ema)", ind.var_name).ok();
+                writeln!(out, "{0} = math.sum({0}_double != 0 ? {0}_single / {0}_double : 1.0, i_{0}_period)", ind.var_name).ok();
+            }
+            IndicatorType::Klinger => {
+                writeln!(out, "{0}_dm = high - low", ind.var_name).ok();
+                writeln!(out, "var float {0}_trend = 0.0", ind.var_name).ok();
+                writeln!(out, "var float {0}_cm = 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_bar_trend = hlc3 > hlc3[1] ? 1.0 : hlc3 < hlc3[1] ? -1.0 : {0}_trend == 0.0 ? 1.0 : {0}_trend", ind.var_name).ok();
+                writeln!(out, "{0}_cm := {0}_trend == 0.0 ? {0}_dm : {0}_bar_trend == {0}_trend ? {0}_cm + {0}_dm : {0}_dm[1] + {0}_dm", ind.var_name).ok();
+                writeln!(out, "{0}_trend := {0}_bar_trend", ind.var_name).ok();
+                writeln!(out, "{0}_vf = {0}_cm > 0 ? volume * math.abs(2 * ({0}_dm / {0}_cm - 1)) * {0}_trend * 100 : 0.0", ind.var_name).ok();
+                writeln!(out, "{0}_line = ta.ema({0}_vf, i_{0}_fast) - ta.ema({0}_vf, i_{0}_slow)", ind.var_name).ok();
+                writeln!(out, "{0}_signal = ta.ema({0}_line, i_{0}_signal)", ind.var_name).ok();
+            }
+            IndicatorType::ElderImpulse => {
+                writeln!(out, "{0}_ema = ta.ema(close, i_{0}_period)", ind.var_name).ok();
+                writeln!(out, "[{0}_line, {0}_signal, {0}_hist] = ta.macd(close, i_{0}_fast, i_{0}_slow, i_{0}_signal)", ind.var_name).ok();
+                writeln!(out, "{0} = {0}_ema > {0}_ema[1] and {0}_hist > {0}_hist[1] ? 1 : {0}_ema < {0}_ema[1] and {0}_hist < {0}_hist[1] ? -1 : 0", ind.var_name).ok();
+            }
+            IndicatorType::ZLEMA => {
+                writeln!(out, "{0}_lag = math.floor((i_{0}_period - 1) / 2)", ind.var_name).ok();
+                writeln!(out, "{0} = ta.ema(close + (close - close[{0}_lag]), i_{0}_period)", ind.var_name).ok();
             }
         }
     }
@@ -1945,7 +3005,7 @@ fn pine_indicators(out: &mut String, indicators: &[UniqueIndicator]) {
 }
 
 fn pine_trading_hours(out: &mut String, strategy: &Strategy) {
-    if strategy.trading_hours.is_none() && strategy.close_trades_at.is_none() {
+    if strategy.trading_hours.is_none() && strategy.close_trades_at.is_none() && strategy.session_exit.is_none() {
         return;
     }
 
@@ -1964,6 +3024,15 @@ fn pine_trading_hours(out: &mut String, strategy: &Strategy) {
         writeln!(out, "    strategy.close_all(comment=\"Time Close\")").ok();
     }
 
+    if strategy.session_exit.is_some() {
+        // Exit when the bar closes into the next session day. A bar before a weekend or
+        // holiday gap still closes on its own day, so the gap check exits one bar late.
+        writeln!(out, "sessionDayOf(t) => math.floor((t - i_session_start_hour * 3600000) / 86400000)").ok();
+        writeln!(out, "sessionEnd = sessionDayOf(time_close) != sessionDayOf(time) or sessionDayOf(time) != sessionDayOf(nz(time_close[1], time))").ok();
+        writeln!(out, "if sessionEnd and strategy.position_size != 0").ok();
+        writeln!(out, "    strategy.close_all(comment=\"Session Close\")").ok();
+    }
+
     writeln!(out).ok();
 }
 
@@ -2038,13 +3107,17 @@ fn pine_rules_expression(rules: &[Rule]) -> String {
             Comparator::LessThan => format!("{} < {}", left, right),
             Comparator::GreaterOrEqual => format!("{} >= {}", left, right),
             Comparator::LessOrEqual => format!("{} <= {}", left, right),
-            Comparator::Equal => format!("{} == {}", left, right),
+            Comparator::Equal => approx_equal_expr(rule.tolerance, &left, &right, "math.abs", "math.max"),
             Comparator::CrossAbove => format!("ta.crossover({}, {})", left, right),
             Comparator::CrossBelow => format!("ta.crossunder({}, {})", left, right),
         };
         parts.push(expr);
     }
 
+    if Rule::any_parens(rules) {
+        return grouped_rules_expression(rules, &parts, "and", "or");
+    }
+
     // Chain with logical operators
     let mut result = parts[0].clone();
     for (i, part) in parts.iter().enumerate().skip(1) {
@@ -2060,6 +3133,16 @@ fn pine_rules_expression(rules: &[Rule]) -> String {
 }
 
 fn pine_operand_expr(operand: &Operand, extra_offset: usize) -> String {
+    if let Some(transform) = operand.transform {
+        // Offsetting the inner series shifts the whole window, as in the engine
+        let raw = pine_operand_expr(&Operand { transform: None, ..operand.clone() }, extra_offset);
+        return match transform {
+            OperandTransform::PercentRank { period } => format!("ta.percentrank({}, {})", raw, period),
+            // ta.stdev defaults to the population deviation, matching the engine
+            OperandTransform::ZScore { period } =>
+                format!("(({r}) - ta.sma({r}, {p})) / ta.stdev({r}, {p})", r = raw, p = period),
+        };
+    }
     let offset = operand.offset.unwrap_or(0) + extra_offset;
     let offset_str = if offset > 0 { format!("[{}]", offset) } else { String::new() };
 
@@ -2074,17 +3157,17 @@ fn pine_operand_expr(operand: &Operand, extra_offset: usize) -> String {
                 PriceField::DailyHigh => "request.security(syminfo.tickerid, \"D\", high)",
                 PriceField::DailyLow => "request.security(syminfo.tickerid, \"D\", low)",
                 PriceField::DailyClose => "request.security(syminfo.tickerid, \"D\", close[1])",
+                PriceField::OpenInterest => "request.security(syminfo.tickerid + \"_OI\", timeframe.period, close)",
+                // Pine exposes no bar spread history
+                PriceField::AvgSpread => return "na".to_string(),
             };
             format!("{}{}", field, offset_str)
         }
-        OperandType::Constant => {
-            let v = operand.constant_value.unwrap_or(0.0);
-            if v == v.floor() && v.abs() < 1_000_000.0 {
-                format!("{:.1}", v)
-            } else {
-                format!("{}", v)
-            }
+        OperandType::Constant if operand.percent_of.is_some() => {
+            let price = pine_operand_expr(&percent_base_operand(operand), extra_offset);
+            format!("{} * {}", price, percent_factor(operand))
         }
+        OperandType::Constant => float_literal(operand.constant_value.unwrap_or(0.0)),
         OperandType::Indicator => {
             if let Some(ind) = &operand.indicator {
                 let var = indicator_var_name(ind);
@@ -2124,19 +3207,19 @@ fn pine_operand_expr(operand: &Operand, extra_offset: usize) -> String {
                 Some(CandlePatternType::ShootingStar) =>
                     format!("(high{os}-math.max(open{os},close{os}) >= 2.0*math.abs(close{os}-open{os}) and math.min(open{os},close{os})-low{os} <= math.abs(close{os}-open{os}) ? 1.0 : 0.0)"),
                 Some(CandlePatternType::BullishEngulfing) => {
-                    let p = if offset_str.is_empty() { "[1]".to_string() } else { format!("[{}]", operand.offset.unwrap_or(0) + 1) };
+                    let p = format!("[{}]", offset + 1);
                     format!("(close{p}<open{p} and close{os}>open{os} and open{os}<=close{p} and close{os}>=open{p} ? 1.0 : 0.0)")
                 }
                 Some(CandlePatternType::BearishEngulfing) => {
-                    let p = if offset_str.is_empty() { "[1]".to_string() } else { format!("[{}]", operand.offset.unwrap_or(0) + 1) };
+                    let p = format!("[{}]", offset + 1);
                     format!("(close{p}>open{p} and close{os}<open{os} and open{os}>=close{p} and close{os}<=open{p} ? 1.0 : 0.0)")
                 }
                 Some(CandlePatternType::DarkCloud) => {
-                    let p = if offset_str.is_empty() { "[1]".to_string() } else { format!("[{}]", operand.offset.unwrap_or(0) + 1) };
+                    let p = format!("[{}]", offset + 1);
                     format!("(close{p}>open{p} and close{os}<open{os} and open{os}>high{p} and close{os}<(open{p}+close{p})/2.0 ? 1.0 : 0.0)")
                 }
                 Some(CandlePatternType::PiercingLine) => {
-                    let p = if offset_str.is_empty() { "[1]".to_string() } else { format!("[{}]", operand.offset.unwrap_or(0) + 1) };
+                    let p = format!("[{}]", offset + 1);
                     format!("(close{p}<open{p} and close{os}>open{os} and open{os}<low{p} and close{os}>(open{p}+close{p})/2.0 ? 1.0 : 0.0)")
                 }
                 None => "na".into(),
@@ -2161,30 +3244,77 @@ fn pine_operand_expr(operand: &Operand, extra_offset: usize) -> String {
     }
 }
 
-fn pine_execution(out: &mut String, strategy: &Strategy) {
+/// Webhook payload template; `{{...}}` placeholders are filled in by `sendAlert`.
+const PINE_ALERT_TEMPLATE: &str =
+    r#"'{"symbol": "{{ticker}}", "direction": "{{direction}}", "action": "{{action}}", "price": {{price}}}'"#;
+
+fn pine_execution(out: &mut String, strategy: &Strategy, emit_alerts: bool) {
     writeln!(out, "// ═══════════════ EXECUTION ═══════════════").ok();
 
     let can_long = strategy.trade_direction != TradeDirection::Short;
     let can_short = strategy.trade_direction != TradeDirection::Long;
 
+    // Alerts fire once per bar close, i.e. when the order is placed (fill is next bar's open)
+    if emit_alerts {
+        writeln!(out, "alertTemplate = {}", PINE_ALERT_TEMPLATE).ok();
+        writeln!(out, "sendAlert(string direction, string action) =>").ok();
+        writeln!(out, "    msg = str.replace_all(alertTemplate, \"{{{{ticker}}}}\", syminfo.ticker)").ok();
+        writeln!(out, "    msg := str.replace_all(msg, \"{{{{direction}}}}\", direction)").ok();
+        writeln!(out, "    msg := str.replace_all(msg, \"{{{{action}}}}\", action)").ok();
+        writeln!(out, "    msg := str.replace_all(msg, \"{{{{price}}}}\", str.tostring(close, format.mintick))").ok();
+        writeln!(out, "    alert(msg, alert.freq_once_per_bar_close)").ok();
+        writeln!(out).ok();
+    }
+    let alert_call = |out: &mut String, direction: &str, action: &str| {
+        if emit_alerts {
+            writeln!(out, "    sendAlert(\"{}\", \"{}\")", direction, action).ok();
+        }
+    };
+
     if can_long {
         writeln!(out, "if longEntry and strategy.position_size == 0").ok();
         writeln!(out, "    strategy.entry(\"Long\", strategy.long)").ok();
+        alert_call(out, "long", "entry");
         writeln!(out).ok();
     }
     if can_short {
         writeln!(out, "if shortEntry and strategy.position_size == 0").ok();
         writeln!(out, "    strategy.entry(\"Short\", strategy.short)").ok();
+        alert_call(out, "short", "entry");
+        writeln!(out).ok();
+    }
+    // Opposite entry signal: strategy.entry reverses an open position, strategy.close flattens it.
+    // Both entry conditions only exist when the strategy trades both directions.
+    if (strategy.close_on_opposite_signal || strategy.reverse_on_opposite_signal) && can_long && can_short {
+        if strategy.reverse_on_opposite_signal {
+            writeln!(out, "if strategy.position_size > 0 and shortEntry").ok();
+            writeln!(out, "    strategy.entry(\"Short\", strategy.short, comment=\"Reverse\")").ok();
+            alert_call(out, "short", "entry");
+            writeln!(out).ok();
+            writeln!(out, "if strategy.position_size < 0 and longEntry").ok();
+            writeln!(out, "    strategy.entry(\"Long\", strategy.long, comment=\"Reverse\")").ok();
+            alert_call(out, "long", "entry");
+        } else {
+            writeln!(out, "if strategy.position_size > 0 and shortEntry").ok();
+            writeln!(out, "    strategy.close(\"Long\", comment=\"Opposite Signal\")").ok();
+            alert_call(out, "long", "exit");
+            writeln!(out).ok();
+            writeln!(out, "if strategy.position_size < 0 and longEntry").ok();
+            writeln!(out, "    strategy.close(\"Short\", comment=\"Opposite Signal\")").ok();
+            alert_call(out, "short", "exit");
+        }
         writeln!(out).ok();
     }
     if can_long {
         writeln!(out, "if strategy.position_size > 0 and longExit").ok();
         writeln!(out, "    strategy.close(\"Long\", comment=\"Exit Signal\")").ok();
+        alert_call(out, "long", "exit");
         writeln!(out).ok();
     }
     if can_short {
         writeln!(out, "if strategy.position_size < 0 and shortExit").ok();
         writeln!(out, "    strategy.close(\"Short\", comment=\"Exit Signal\")").ok();
+        alert_call(out, "short", "exit");
         writeln!(out).ok();
     }
 }
@@ -2199,16 +3329,22 @@ fn pine_sl_tp(out: &mut String, strategy: &Strategy) {
     let can_long = strategy.trade_direction != TradeDirection::Short;
     let can_short = strategy.trade_direction != TradeDirection::Long;
 
+    // Pip size: 10 ticks on 3/5-decimal quotes, 1 tick otherwise (see PipSize() in the MQL5 export)
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        writeln!(out, "pipDecimals = math.round(-math.log10(syminfo.mintick))").ok();
+        writeln!(out, "pipSize = i_pip_points > 0 ? i_pip_points * syminfo.mintick : (pipDecimals == 3 or pipDecimals == 5) ? syminfo.mintick * 10 : syminfo.mintick").ok();
+    }
+
     // Calculate SL distance
     if let Some(sl) = &strategy.stop_loss {
         match sl.sl_type {
             StopLossType::Pips => {
-                writeln!(out, "slDist = i_sl_pips * syminfo.mintick * 10").ok();
+                writeln!(out, "slDist = i_sl_pips * pipSize").ok();
             }
             StopLossType::Percentage => {
                 writeln!(out, "slDist = close * i_sl_pct / 100.0").ok();
             }
-            StopLossType::ATR => {
+            StopLossType::ATR | StopLossType::ChandeKroll => {
                 let var = format!("atr_{}", sl.atr_period.unwrap_or(14));
                 writeln!(out, "slDist = {} * i_sl_atr_mult", var).ok();
             }
@@ -2219,13 +3355,13 @@ fn pine_sl_tp(out: &mut String, strategy: &Strategy) {
     if let Some(tp) = &strategy.take_profit {
         match tp.tp_type {
             TakeProfitType::Pips => {
-                writeln!(out, "tpDist = i_tp_pips * syminfo.mintick * 10").ok();
+                writeln!(out, "tpDist = i_tp_pips * pipSize").ok();
             }
             TakeProfitType::RiskReward => {
                 if strategy.stop_loss.is_some() {
                     writeln!(out, "tpDist = slDist * i_tp_rr").ok();
                 } else {
-                    writeln!(out, "tpDist = close * 0.02 // NOTE: No SL defined for R:R calculation").ok();
+                    writeln!(out, "tpDist = i_tp_rr * {:.1} * pipSize // No SL: R:R of a fixed-pip risk", RR_FALLBACK_RISK_PIPS).ok();
                 }
             }
             TakeProfitType::ATR => {
@@ -2235,64 +3371,80 @@ fn pine_sl_tp(out: &mut String, strategy: &Strategy) {
         }
     }
 
+    // Step trailing has no strategy.exit equivalent: track the best price since entry
+    // and fold the stepped level into the stop price instead
+    let step_trailing = strategy.trailing_stop.as_ref().is_some_and(|ts| ts.ts_type == TrailingStopType::Step);
+    if step_trailing {
+        writeln!(out, "var float tsHigh = na").ok();
+        writeln!(out, "var float tsLow = na").ok();
+        writeln!(out, "tsHigh := strategy.position_size > 0 ? math.max(nz(tsHigh, high), high) : na").ok();
+        writeln!(out, "tsLow := strategy.position_size < 0 ? math.min(nz(tsLow, low), low) : na").ok();
+        writeln!(out, "tsStep = i_ts_step_pips * pipSize").ok();
+        writeln!(out, "longStepStop = strategy.position_avg_price - i_ts_pips * pipSize + math.max(0, math.floor((tsHigh - strategy.position_avg_price) / tsStep)) * tsStep").ok();
+        writeln!(out, "shortStepStop = strategy.position_avg_price + i_ts_pips * pipSize - math.max(0, math.floor((strategy.position_avg_price - tsLow) / tsStep)) * tsStep").ok();
+        if ts_activation_pips(strategy.trailing_stop.as_ref()).is_some() {
+            writeln!(out, "longStepStop := tsHigh - strategy.position_avg_price >= i_ts_activation_pips * pipSize ? longStepStop : na").ok();
+            writeln!(out, "shortStepStop := strategy.position_avg_price - tsLow >= i_ts_activation_pips * pipSize ? shortStepStop : na").ok();
+        }
+    }
+
     writeln!(out).ok();
 
     // strategy.exit calls
     if can_long {
         let mut exit_params = vec!["\"Long\"".to_string(), "from_entry=\"Long\"".to_string()];
-        if strategy.stop_loss.is_some() {
-            exit_params.push("stop=strategy.position_avg_price - slDist".into());
+        match (strategy.stop_loss.is_some(), step_trailing) {
+            (true, false) => exit_params.push("stop=strategy.position_avg_price - slDist".into()),
+            (true, true) => exit_params.push("stop=math.max(strategy.position_avg_price - slDist, nz(longStepStop, strategy.position_avg_price - slDist))".into()),
+            (false, true) => exit_params.push("stop=longStepStop".into()),
+            (false, false) => {}
         }
         if strategy.take_profit.is_some() {
             exit_params.push("limit=strategy.position_avg_price + tpDist".into());
         }
-        if let Some(ts) = &strategy.trailing_stop {
-            match ts.ts_type {
-                TrailingStopType::ATR => {
-                    let var = format!("atr_{}", ts.atr_period.unwrap_or(14));
-                    exit_params.push(format!("trail_points={} * i_ts_atr_mult / syminfo.mintick", var));
-                    exit_params.push(format!("trail_offset={} * i_ts_atr_mult / syminfo.mintick", var));
-                }
-                TrailingStopType::RiskReward => {
-                    if strategy.stop_loss.is_some() {
-                        exit_params.push("trail_points=slDist / syminfo.mintick".into());
-                        exit_params.push("trail_offset=slDist * i_ts_rr / syminfo.mintick".into());
-                    }
-                }
-            }
-        }
+        exit_params.extend(pine_trailing_params(strategy));
         writeln!(out, "strategy.exit({})", exit_params.join(", ")).ok();
     }
 
     if can_short {
         let mut exit_params = vec!["\"Short\"".to_string(), "from_entry=\"Short\"".to_string()];
-        if strategy.stop_loss.is_some() {
-            exit_params.push("stop=strategy.position_avg_price + slDist".into());
+        match (strategy.stop_loss.is_some(), step_trailing) {
+            (true, false) => exit_params.push("stop=strategy.position_avg_price + slDist".into()),
+            (true, true) => exit_params.push("stop=math.min(strategy.position_avg_price + slDist, nz(shortStepStop, strategy.position_avg_price + slDist))".into()),
+            (false, true) => exit_params.push("stop=shortStepStop".into()),
+            (false, false) => {}
         }
         if strategy.take_profit.is_some() {
             exit_params.push("limit=strategy.position_avg_price - tpDist".into());
         }
-        if let Some(ts) = &strategy.trailing_stop {
-            match ts.ts_type {
-                TrailingStopType::ATR => {
-                    let var = format!("atr_{}", ts.atr_period.unwrap_or(14));
-                    exit_params.push(format!("trail_points={} * i_ts_atr_mult / syminfo.mintick", var));
-                    exit_params.push(format!("trail_offset={} * i_ts_atr_mult / syminfo.mintick", var));
-                }
-                TrailingStopType::RiskReward => {
-                    if strategy.stop_loss.is_some() {
-                        exit_params.push("trail_points=slDist / syminfo.mintick".into());
-                        exit_params.push("trail_offset=slDist * i_ts_rr / syminfo.mintick".into());
-                    }
-                }
-            }
-        }
+        exit_params.extend(pine_trailing_params(strategy));
         writeln!(out, "strategy.exit({})", exit_params.join(", ")).ok();
     }
 
     writeln!(out).ok();
 }
 
+/// `trail_points`/`trail_offset` for `strategy.exit`. Pine starts trailing once profit
+/// reaches `trail_points` ticks, so it carries the activation threshold (0 = trail
+/// from entry, as the backtester does) and `trail_offset` the trailing distance.
+fn pine_trailing_params(strategy: &Strategy) -> Vec<String> {
+    let Some(ts) = &strategy.trailing_stop else { return Vec::new() };
+    let distance = match ts.ts_type {
+        TrailingStopType::ATR => format!("atr_{} * i_ts_atr_mult", ts.atr_period.unwrap_or(14)),
+        TrailingStopType::RiskReward if strategy.stop_loss.is_some() => "slDist * i_ts_rr".to_string(),
+        // No SL to scale, or Step (handled through the stop price)
+        TrailingStopType::RiskReward | TrailingStopType::Step => return Vec::new(),
+    };
+    let activation = match ts_activation_pips(Some(ts)) {
+        Some(_) => "i_ts_activation_pips * pipSize / syminfo.mintick".to_string(),
+        None => "0".to_string(),
+    };
+    vec![
+        format!("trail_points={}", activation),
+        format!("trail_offset={} / syminfo.mintick", distance),
+    ]
+}
+
 fn pine_plots(out: &mut String, indicators: &[UniqueIndicator], strategy: &Strategy) {
     writeln!(out, "// ═══════════════ VISUALIZATION ═══════════════").ok();
 
@@ -2312,6 +3464,21 @@ fn pine_plots(out: &mut String, indicators: &[UniqueIndicator], strategy: &Strat
             IndicatorType::ParabolicSAR => {
                 writeln!(out, "plot({}, \"SAR\", style=plot.style_circles, color=color.purple, linewidth=1)", ind.var_name).ok();
             }
+            IndicatorType::VWMA => {
+                writeln!(out, "plot({}, \"VWMA\", color=color.teal, linewidth=1)", ind.var_name).ok();
+            }
+            IndicatorType::ZLEMA => {
+                writeln!(out, "plot({}, \"ZLEMA\", color=color.fuchsia, linewidth=1)", ind.var_name).ok();
+            }
+            IndicatorType::WMA => {
+                writeln!(out, "plot({}, \"WMA\", color=color.purple, linewidth=1)", ind.var_name).ok();
+            }
+            IndicatorType::HullMA => {
+                writeln!(out, "plot({}, \"HMA\", color=color.yellow, linewidth=1)", ind.var_name).ok();
+            }
+            IndicatorType::KAMA => {
+                writeln!(out, "plot({}, \"KAMA\", color=color.red, linewidth=1)", ind.var_name).ok();
+            }
             _ => {} // Non-overlay indicators (RSI, MACD, etc.) would need separate pane
         }
     }
@@ -2360,6 +3527,7 @@ fn generate_custom_indicator(ind_type: IndicatorType) -> Option<(String, String)
         IndicatorType::ROC              => ("BT_ROC.mq5".into(),              gen_mql5_roc()),
         IndicatorType::Ichimoku         => ("BT_Ichimoku.mq5".into(),         gen_mql5_ichimoku()),
         IndicatorType::KeltnerChannel   => ("BT_KeltnerChannel.mq5".into(),   gen_mql5_keltner_channel()),
+        IndicatorType::DonchianChannel  => ("BT_DonchianChannel.mq5".into(),  gen_mql5_donchian_channel()),
         IndicatorType::SuperTrend       => ("BT_SuperTrend.mq5".into(),       gen_mql5_supertrend()),
         IndicatorType::LaguerreRSI      => ("BT_LaguerreRSI.mq5".into(),      gen_mql5_laguerre_rsi()),
         IndicatorType::BearsPower       => ("BT_BearsPower.mq5".into(),       gen_mql5_bears_power()),
@@ -2384,12 +3552,53 @@ fn generate_custom_indicator(ind_type: IndicatorType) -> Option<(String, String)
         IndicatorType::LowestIndex      => ("BT_LowestIndex.mq5".into(),      gen_mql5_lowest_index()),
         IndicatorType::QQE              => ("BT_QQE.mq5".into(),              gen_mql5_qqe()),
         IndicatorType::SchaffTrendCycle => ("BT_SchaffTrendCycle.mq5".into(), gen_mql5_schaff_trend_cycle()),
+        IndicatorType::Choppiness       => ("BT_Choppiness.mq5".into(),       gen_mql5_choppiness()),
+        IndicatorType::StochasticRSI    => ("BT_StochasticRSI.mq5".into(),    gen_mql5_stochastic_rsi()),
+        IndicatorType::TRIX             => ("BT_TRIX.mq5".into(),             gen_mql5_trix()),
+        IndicatorType::AccumulationDistribution => ("BT_AccumulationDistribution.mq5".into(), gen_mql5_accumulation_distribution()),
+        IndicatorType::ChaikinMoneyFlow => ("BT_ChaikinMoneyFlow.mq5".into(), gen_mql5_chaikin_money_flow()),
+        IndicatorType::DPO              => ("BT_DPO.mq5".into(),              gen_mql5_dpo()),
+        IndicatorType::Coppock          => ("BT_Coppock.mq5".into(),          gen_mql5_coppock()),
+        IndicatorType::PPO              => ("BT_PPO.mq5".into(),              gen_mql5_ppo()),
+        IndicatorType::SuperSmoother    => ("BT_SuperSmoother.mq5".into(),    gen_mql5_super_smoother()),
+        IndicatorType::RoofingFilter    => ("BT_RoofingFilter.mq5".into(),    gen_mql5_roofing_filter()),
+        IndicatorType::ConnorsRSI       => ("BT_ConnorsRSI.mq5".into(),       gen_mql5_connors_rsi()),
+        IndicatorType::ForceIndex       => ("BT_ForceIndex.mq5".into(),       gen_mql5_force_index()),
+        IndicatorType::EaseOfMovement   => ("BT_EaseOfMovement.mq5".into(),   gen_mql5_ease_of_movement()),
+        IndicatorType::VWMA             => ("BT_VWMA.mq5".into(),             gen_mql5_vwma()),
+        IndicatorType::WMA              => ("BT_WMA.mq5".into(),              gen_mql5_wma()),
+        IndicatorType::ZLEMA            => ("BT_ZLEMA.mq5".into(),            gen_mql5_zlema()),
+        IndicatorType::ElderImpulse     => ("BT_ElderImpulse.mq5".into(),     gen_mql5_elder_impulse()),
+        IndicatorType::MassIndex        => ("BT_MassIndex.mq5".into(),        gen_mql5_mass_index()),
+        IndicatorType::Klinger          => ("BT_Klinger.mq5".into(),          gen_mql5_klinger()),
+        IndicatorType::AwesomeOscillator => ("BT_AwesomeOscillator.mq5".into(), gen_mql5_awesome_oscillator()),
+        IndicatorType::Momentum         => ("BT_Momentum.mq5".into(),         gen_mql5_momentum()),
         // Native handles or no file needed
         _ => return None,
     };
     Some((filename, code))
 }
 
+/// `SeededEma` helper shared by the indicators that chain EMAs (TRIX, PPO, Elder
+/// Impulse, Klinger). `firstValue` mirrors `EmaSeed::FirstValue`.
+const MQL5_SEEDED_EMA: &str = r#"// EMA of src seeded with the SMA of its first `period` values starting at `first`,
+// or with src[first] itself when firstValue
+void SeededEma(const double &src[], double &dst[], int first, int period, int total, bool firstValue = false) {
+   double k = 2.0 / (period + 1.0);
+   for(int i = 0; i < total; i++) dst[i] = EMPTY_VALUE;
+   if(first + period > total) return;
+   if(firstValue) {
+      dst[first] = src[first];
+      for(int i = first + 1; i < total; i++) dst[i] = (src[i] - dst[i - 1]) * k + dst[i - 1];
+      return;
+   }
+   double seed = 0.0;
+   for(int i = first; i < first + period; i++) seed += src[i];
+   dst[first + period - 1] = seed / period;
+   for(int i = first + period; i < total; i++) dst[i] = (src[i] - dst[i - 1]) * k + dst[i - 1];
+}
+"#;
+
 fn mql5_indicator_header(name: &str) -> String {
     format!(
 r#"//+------------------------------------------------------------------+
@@ -2484,13 +3693,14 @@ fn gen_mql5_ema() -> String {
 #property indicator_width1  1
 
 input int InpPeriod = 14; // Period
+input int InpSeed = 0; // 0 = SMA of first 'period' values, 1 = first value
 
 double EmaBuffer[];
 
 int OnInit()
 {
    SetIndexBuffer(0, EmaBuffer, INDICATOR_DATA);
-   PlotIndexSetInteger(0, PLOT_DRAW_BEGIN, InpPeriod);
+   PlotIndexSetInteger(0, PLOT_DRAW_BEGIN, InpSeed == 1 ? 0 : InpPeriod);
    PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
    IndicatorSetString(INDICATOR_SHORTNAME, "BT_EMA(" + IntegerToString(InpPeriod) + ")");
    return INIT_SUCCEEDED;
@@ -2512,7 +3722,13 @@ int OnCalculate(const int rates_total,
    double multiplier = 2.0 / (InpPeriod + 1.0);
 
    int start;
-   if(prev_calculated == 0)
+   if(prev_calculated == 0 && InpSeed == 1)
+   {
+      // Seed with the first value (TradingView ta.ema)
+      EmaBuffer[0] = close[0];
+      start = 1;
+   }
+   else if(prev_calculated == 0)
    {
       for(int i = 0; i < InpPeriod - 1; i++)
          EmaBuffer[i] = EMPTY_VALUE;
@@ -2557,6 +3773,7 @@ fn gen_mql5_rsi() -> String {
 #property indicator_maximum 100
 
 input int InpPeriod = 14; // Period
+input int InpSmoothing = 0; // 0 = Wilder, 1 = SMA (Cutler)
 
 double RsiBuffer[];
 
@@ -2587,6 +3804,29 @@ int OnCalculate(const int rates_total,
 {
    if(rates_total < InpPeriod + 1) return 0;
 
+   // Cutler's RSI: plain average of the last 'period' gains and losses
+   if(InpSmoothing == 1)
+   {
+      int first = (prev_calculated == 0) ? 0 : prev_calculated - 1;
+      for(int i = first; i < rates_total; i++)
+      {
+         RsiBuffer[i] = EMPTY_VALUE;
+         if(i < InpPeriod) continue;
+         double sumGain = 0, sumLoss = 0;
+         for(int j = i - InpPeriod + 1; j <= i; j++)
+         {
+            double change = close[j] - close[j - 1];
+            if(change > 0) sumGain += change;
+            else           sumLoss += (-change);
+         }
+         if(sumLoss == 0)
+            RsiBuffer[i] = 100.0;
+         else
+            RsiBuffer[i] = 100.0 - 100.0 / (1.0 + sumGain / sumLoss);
+      }
+      return rates_total;
+   }
+
    int start;
    if(prev_calculated == 0)
    {
@@ -2662,6 +3902,7 @@ fn gen_mql5_macd() -> String {
 input int InpFastPeriod   = 12; // Fast EMA Period
 input int InpSlowPeriod   = 26; // Slow EMA Period
 input int InpSignalPeriod = 9;  // Signal EMA Period
+input int InpSeed         = 0;  // EMA seed: 0 = SMA of first 'period' values, 1 = first value
 
 double MacdBuffer[];
 double SignalBuffer[];
@@ -2686,12 +3927,17 @@ int OnInit()
    return INIT_SUCCEEDED;
 }
 
-// Helper: compute EMA buffer (SMA-seeded, same as Rust engine)
+// Helper: compute EMA buffer (seeded per InpSeed, same as Rust engine)
 void ComputeEMA(const double &src[], double &dst[], int period, int rates_total, int prev_calculated)
 {
    double mult = 2.0 / (period + 1.0);
    int start;
-   if(prev_calculated == 0)
+   if(prev_calculated == 0 && InpSeed == 1)
+   {
+      dst[0] = src[0];
+      start = 1;
+   }
+   else if(prev_calculated == 0)
    {
       for(int i = 0; i < period - 1; i++)
          dst[i] = EMPTY_VALUE;
@@ -2728,15 +3974,30 @@ void ComputeEMAOnSlice(const double &src[], double &dst[], int period, int rates
       }
       if(seedStart < 0) { ArrayInitialize(dst, EMPTY_VALUE); return; }
 
-      for(int i = 0; i < seedStart + period - 1; i++)
-         dst[i] = EMPTY_VALUE;
-
-      double sum = 0;
-      for(int i = seedStart; i < seedStart + period; i++)
-         sum += src[i];
-      dst[seedStart + period - 1] = sum / period;
+      int first;
+      if(InpSeed == 1)
+      {
+         // Seed with the first valid value
+         seedStart = 0;
+         while(src[seedStart] == EMPTY_VALUE) seedStart++;
+         for(int i = 0; i < seedStart; i++)
+            dst[i] = EMPTY_VALUE;
+         dst[seedStart] = src[seedStart];
+         first = seedStart + 1;
+      }
+      else
+      {
+         for(int i = 0; i < seedStart + period - 1; i++)
+            dst[i] = EMPTY_VALUE;
+
+         double sum = 0;
+         for(int i = seedStart; i < seedStart + period; i++)
+            sum += src[i];
+         dst[seedStart + period - 1] = sum / period;
+         first = seedStart + period;
+      }
 
-      for(int i = seedStart + period; i < rates_total; i++)
+      for(int i = first; i < rates_total; i++)
       {
          if(src[i] == EMPTY_VALUE) { dst[i] = dst[i - 1]; continue; }
          dst[i] = (src[i] - dst[i - 1]) * mult + dst[i - 1];
@@ -2820,6 +4081,7 @@ fn gen_mql5_bollinger() -> String {
 
 input int    InpPeriod = 20;  // Period
 input double InpStdDev = 2.0; // Std Dev Multiplier
+input bool   InpSampleStdDev = false; // Divide by N-1 instead of N
 
 double MiddleBuffer[];
 double UpperBuffer[];
@@ -2827,6 +4089,9 @@ double LowerBuffer[];
 
 int OnInit()
 {
+   // Sample variance of a single bar is undefined; the Rust engine rejects it too
+   if(InpSampleStdDev && InpPeriod < 2)
+      return INIT_PARAMETERS_INCORRECT;
    // Buffer order matches Rust: primary=middle(0), secondary=upper(1), tertiary=lower(2)
    SetIndexBuffer(0, MiddleBuffer, INDICATOR_DATA);
    SetIndexBuffer(1, UpperBuffer, INDICATOR_DATA);
@@ -2874,14 +4139,14 @@ int OnCalculate(const int rates_total,
       double mean = sum / InpPeriod;
       MiddleBuffer[i] = mean;
 
-      // Population standard deviation (matching Rust: divide by N, not N-1)
+      // Population (N) or sample (N-1) standard deviation, matching the Rust engine
       double variance = 0;
       for(int j = i - InpPeriod + 1; j <= i; j++)
       {
          double diff = close[j] - mean;
          variance += diff * diff;
       }
-      variance /= InpPeriod;
+      variance /= (InpSampleStdDev ? InpPeriod - 1 : InpPeriod);
       double sd = MathSqrt(variance);
 
       UpperBuffer[i] = mean + InpStdDev * sd;
@@ -2916,6 +4181,60 @@ int OnCalculate(const int rates_total, const int prev_calculated, const datetime
     out
 }
 
+// ── BT_AwesomeOscillator ──
+
+fn gen_mql5_awesome_oscillator() -> String {
+    let mut out = mql5_indicator_header("BT_AwesomeOscillator");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "AO"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); IndicatorSetString(INDICATOR_SHORTNAME, "BT_AwesomeOscillator"); return INIT_SUCCEEDED; }
+// SMA(5) - SMA(34) of the bar midpoint (high + low) / 2
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int start = (prev_calculated > 34 ? prev_calculated - 1 : 33);
+   for(int i = start; i < rates_total; i++) {
+      double sum5 = 0, sum34 = 0;
+      for(int j = 0; j < 34; j++) {
+         double mid = (high[i-j] + low[i-j]) / 2.0;
+         if(j < 5) sum5 += mid;
+         sum34 += mid;
+      }
+      ExtBuffer[i] = sum5 / 5.0 - sum34 / 34.0;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_Momentum ──
+
+fn gen_mql5_momentum() -> String {
+    let mut out = mql5_indicator_header("BT_Momentum");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "Momentum"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 14;
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); IndicatorSetString(INDICATOR_SHORTNAME, "BT_Momentum"); return INIT_SUCCEEDED; }
+// Close - Close[period] (difference, not the MT5 ratio * 100)
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int start = (prev_calculated > InpPeriod ? prev_calculated - 1 : InpPeriod);
+   for(int i = start; i < rates_total; i++)
+      ExtBuffer[i] = close[i] - close[i - InpPeriod];
+   return rates_total;
+}
+"#);
+    out
+}
+
 // ── BT_BiggestRange ──
 
 fn gen_mql5_biggest_range() -> String {
@@ -3252,6 +4571,7 @@ r##"//+------------------------------------------------------------------+
 #property indicator_color7 Red
 
 //---- input parameters
+input int       InpPivotType=0;   // 0 = Classic, 1 = Fibonacci, 2 = Camarilla, 3 = Woodie
 input int       StartHour=8;
 input int       StartMinute=20;
 input int       DaysToPlot=0;
@@ -3386,13 +4706,40 @@ int OnCalculate(const int rates_total,
             }
          }
 
-         P =  (PreviousHigh + PreviousLow + PreviousClose) / 3;
-         R1 = (2 * P) - PreviousLow;
-         S1 = (2 * P) - PreviousHigh;
-         R2 =  P + (PreviousHigh - PreviousLow);
-         S2 =  P - (PreviousHigh - PreviousLow);
-         R3 =  P + 2 * (PreviousHigh - PreviousLow);
-         S3 =  P - 2 * (PreviousHigh - PreviousLow);
+         double Range = PreviousHigh - PreviousLow;
+         if(InpPivotType == 1) {
+            P =  (PreviousHigh + PreviousLow + PreviousClose) / 3;
+            R1 = P + 0.382 * Range;
+            S1 = P - 0.382 * Range;
+            R2 = P + 0.618 * Range;
+            S2 = P - 0.618 * Range;
+            R3 = P + Range;
+            S3 = P - Range;
+         } else if(InpPivotType == 2) {
+            P =  (PreviousHigh + PreviousLow + PreviousClose) / 3;
+            R1 = PreviousClose + Range * 1.1 / 12;
+            S1 = PreviousClose - Range * 1.1 / 12;
+            R2 = PreviousClose + Range * 1.1 / 6;
+            S2 = PreviousClose - Range * 1.1 / 6;
+            R3 = PreviousClose + Range * 1.1 / 4;
+            S3 = PreviousClose - Range * 1.1 / 4;
+         } else if(InpPivotType == 3) {
+            P =  (PreviousHigh + PreviousLow + 2 * PreviousClose) / 4;
+            R1 = (2 * P) - PreviousLow;
+            S1 = (2 * P) - PreviousHigh;
+            R2 =  P + Range;
+            S2 =  P - Range;
+            R3 =  PreviousHigh + 2 * (P - PreviousLow);
+            S3 =  PreviousLow - 2 * (PreviousHigh - P);
+         } else {
+            P =  (PreviousHigh + PreviousLow + PreviousClose) / 3;
+            R1 = (2 * P) - PreviousLow;
+            S1 = (2 * P) - PreviousHigh;
+            R2 =  P + (PreviousHigh - PreviousLow);
+            S2 =  P - (PreviousHigh - PreviousLow);
+            R3 =  P + 2 * (PreviousHigh - PreviousLow);
+            S3 =  P - 2 * (PreviousHigh - PreviousLow);
+         }
 
          LabelShiftTime = time[LabelShift];
 
@@ -3539,6 +4886,7 @@ r#"//+------------------------------------------------------------------+
 #property indicator_label1  "ATR"
 //--- input parameters
 input int InpAtrPeriod=14;  // ATR period
+input int InpSmoothing=0;   // 0 = Wilder, 1 = SMA, 2 = EMA
 //--- indicator buffers
 double    ExtATRBuffer[];
 double    ExtTRBuffer[];
@@ -3587,6 +4935,7 @@ int OnCalculate(const int rates_total,
 
    if(prev_calculated == 0){
       ExtATRBuffer[0] = high[0] - low[0];
+      ExtTRBuffer[0] = ExtATRBuffer[0];
       limit=1;
    }
    else limit=prev_calculated-1;
@@ -3595,8 +4944,20 @@ int OnCalculate(const int rates_total,
       double trueRange = high[i] - low[i];
       double prevClose = close[i-1];
       trueRange = MathMax(MathAbs(low[i] - prevClose), MathMax(trueRange, MathAbs(high[i] - prevClose)));
+      ExtTRBuffer[i] = trueRange;
       double multiplier = MathIsValidNumber(ExtATRBuffer[i-1]) ? ExtATRBuffer[i-1] : 0;
-      ExtATRBuffer[i] = (((MathMin(i + 1, ExtPeriodATR) - 1 ) * multiplier) + trueRange) / MathMin(i + 1, ExtPeriodATR);
+      int n = MathMin(i + 1, ExtPeriodATR);
+      if(InpSmoothing == 1){
+         //--- SMA of the last n true ranges
+         double sum = 0;
+         for(int j = i - n + 1; j <= i; j++) sum += ExtTRBuffer[j];
+         ExtATRBuffer[i] = sum / n;
+      }
+      else if(InpSmoothing == 2 && i >= ExtPeriodATR){
+         //--- EMA once the SMA seed is complete
+         ExtATRBuffer[i] = multiplier + (trueRange - multiplier) * 2.0 / (ExtPeriodATR + 1.0);
+      }
+      else ExtATRBuffer[i] = (((n - 1) * multiplier) + trueRange) / n;
    }
 //--- return value of prev_calculated for next call
    return(rates_total);
@@ -4525,293 +5886,171 @@ int OnCalculate(const int rates_total,
 // ── BT_Ichimoku ──
 
 fn gen_mql5_ichimoku() -> String {
-r#"//+------------------------------------------------------------------+
-//|                                                   BT_Ichimoku.mq5 |
-//|                           Copyright © 2017, StrategyQuant s.r.o. |
-//|                                     http://www.strategyquant.com |
-//+------------------------------------------------------------------+
-#property copyright   "Copyright © 2017, StrategyQuant s.r.o."
-#property link        "http://www.strategyquant.com"
-#property description "Ichimoku Kinko Hyo"
-//--- indicator settings
-#property indicator_chart_window
-#property indicator_buffers 5
+    let mut out = mql5_indicator_header("BT_Ichimoku");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 7
 #property indicator_plots   4
-#property indicator_type1   DRAW_LINE
-#property indicator_type2   DRAW_LINE
-#property indicator_type3   DRAW_FILLING
-#property indicator_type4   DRAW_LINE
-#property indicator_color1  Red
-#property indicator_color2  Blue
-#property indicator_color3  SandyBrown,Thistle
-#property indicator_color4  Lime
 #property indicator_label1  "Tenkan-sen"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrRed
 #property indicator_label2  "Kijun-sen"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrBlue
 #property indicator_label3  "Senkou Span A;Senkou Span B"
+#property indicator_type3   DRAW_FILLING
+#property indicator_color3  clrSandyBrown,clrThistle
 #property indicator_label4  "Chikou Span"
-//--- input parameters
-input int InpTenkan=9;     // Tenkan-sen
-input int InpKijun=26;     // Kijun-sen
-input int InpSenkou=52;    // Senkou Span B
-//--- indicator buffers
-double    ExtTenkanBuffer[];
-double    ExtKijunBuffer[];
-double    ExtSpanABuffer[];
-double    ExtSpanBBuffer[];
-double    ExtChikouBuffer[];
-//+------------------------------------------------------------------+
-//| Custom indicator initialization function                         |
-//+------------------------------------------------------------------+
-void OnInit()
-  {
-//--- indicator buffers mapping
-   SetIndexBuffer(0,ExtTenkanBuffer,INDICATOR_DATA);
-   SetIndexBuffer(1,ExtKijunBuffer,INDICATOR_DATA);
-   SetIndexBuffer(2,ExtSpanABuffer,INDICATOR_DATA);
-   SetIndexBuffer(3,ExtSpanBBuffer,INDICATOR_DATA);
-   SetIndexBuffer(4,ExtChikouBuffer,INDICATOR_DATA);
-//---
-   IndicatorSetInteger(INDICATOR_DIGITS,_Digits+1);
-//--- sets first bar from what index will be drawn
-   PlotIndexSetInteger(0,PLOT_DRAW_BEGIN,InpTenkan);
-   PlotIndexSetInteger(1,PLOT_DRAW_BEGIN,InpKijun);
-   PlotIndexSetInteger(2,PLOT_DRAW_BEGIN,InpSenkou-1);
-//--- lines shifts when drawing
-   PlotIndexSetInteger(2,PLOT_SHIFT,InpKijun);
-//--- change labels for DataWindow
-   PlotIndexSetString(0,PLOT_LABEL,"Tenkan-sen("+string(InpTenkan)+")");
-   PlotIndexSetString(1,PLOT_LABEL,"Kijun-sen("+string(InpKijun)+")");
-   PlotIndexSetString(2,PLOT_LABEL,"Senkou Span A;Senkou Span B("+string(InpSenkou)+")");
-//--- initialization done
-  }
-//+------------------------------------------------------------------+
-//| get highest value for range                                      |
-//+------------------------------------------------------------------+
-double Highest(const double&array[],int range,int fromIndex)
-  {
-   double res=0;
-//---
-   res=array[fromIndex];
-   for(int i=fromIndex;i>fromIndex-range && i>=0;i--)
-     {
-      if(res<array[i]) res=array[i];
-     }
-//---
-   return(res);
-  }
-//+------------------------------------------------------------------+
-//| get lowest value for range                                       |
-//+------------------------------------------------------------------+
-double Lowest(const double&array[],int range,int fromIndex)
-  {
-   double res=0;
-//---
-   res=array[fromIndex];
-   for(int i=fromIndex;i>fromIndex-range && i>=0;i--)
-     {
-      if(res>array[i]) res=array[i];
-     }
-//---
-   return(res);
-  }
-//+------------------------------------------------------------------+
-//| Ichimoku Kinko Hyo                                               |
-//+------------------------------------------------------------------+
-int OnCalculate(const int rates_total,
-                const int prev_calculated,
-                const datetime &time[],
-                const double &open[],
-                const double &high[],
-                const double &low[],
-                const double &close[],
-                const long &tick_volume[],
-                const long &volume[],
-                const int &spread[])
-  {
-   int limit;
-//---
-   if(prev_calculated==0) limit=0;
-   else                   limit=prev_calculated-1;
-//---
-   int chikouStart = rates_total > InpKijun ? rates_total - InpKijun : 0;
-
-   for(int a=chikouStart; a<rates_total; a++){
-      ExtChikouBuffer[a]=close[rates_total-1];
+#property indicator_type4   DRAW_LINE
+#property indicator_color4  clrLime
+input int InpTenkan = 9;
+input int InpKijun  = 26;
+input int InpSenkou = 52;
+// Buffer order matches the engine output fields: 0 = tenkan, 1 = kijun, 2 = senkou_a,
+// 3 = senkou_b, 4 = chikou, 5 = cloud_top, 6 = cloud_bottom
+double ExtTenkan[], ExtKijun[], ExtSpanA[], ExtSpanB[], ExtChikou[], ExtCloudTop[], ExtCloudBottom[];
+int OnInit() {
+   SetIndexBuffer(0, ExtTenkan, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtKijun, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtSpanA, INDICATOR_DATA);
+   SetIndexBuffer(3, ExtSpanB, INDICATOR_DATA);
+   SetIndexBuffer(4, ExtChikou, INDICATOR_DATA);
+   SetIndexBuffer(5, ExtCloudTop, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(6, ExtCloudBottom, INDICATOR_CALCULATIONS);
+   for(int b = 0; b < 4; b++) PlotIndexSetDouble(b, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_Ichimoku(" + IntegerToString(InpTenkan) + "," + IntegerToString(InpKijun) + "," + IntegerToString(InpSenkou) + ")");
+   return INIT_SUCCEEDED;
+}
+// (highest high + lowest low) / 2 over bars i-period+1..i; EMPTY_VALUE until the window is full
+double Mid(const double &high[], const double &low[], int i, int period) {
+   if(period < 1 || i < period - 1) return EMPTY_VALUE;
+   double hh = high[i], ll = low[i];
+   for(int j = i - period + 1; j < i; j++) { hh = MathMax(hh, high[j]); ll = MathMin(ll, low[j]); }
+   return (hh + ll) / 2.0;
+}
+// Spans hold the values computed InpKijun bars earlier (projected forward, as in the
+// engine), so reading shift s never looks ahead. Chikou is the current close.
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtTenkan[i] = Mid(high, low, i, InpTenkan);
+      ExtKijun[i]  = Mid(high, low, i, InpKijun);
+      ExtChikou[i] = close[i];
+      ExtSpanA[i] = EMPTY_VALUE;
+      ExtSpanB[i] = EMPTY_VALUE;
+      ExtCloudTop[i] = EMPTY_VALUE;
+      ExtCloudBottom[i] = EMPTY_VALUE;
+      int src = i - InpKijun;
+      if(src < 0) continue;
+      if(ExtTenkan[src] != EMPTY_VALUE && ExtKijun[src] != EMPTY_VALUE)
+         ExtSpanA[i] = (ExtTenkan[src] + ExtKijun[src]) / 2.0;
+      ExtSpanB[i] = Mid(high, low, src, InpSenkou);
+      if(ExtSpanA[i] == EMPTY_VALUE || ExtSpanB[i] == EMPTY_VALUE) continue;
+      ExtCloudTop[i]    = MathMax(ExtSpanA[i], ExtSpanB[i]);
+      ExtCloudBottom[i] = MathMin(ExtSpanA[i], ExtSpanB[i]);
    }
-
-   for(int i=limit;i<rates_total && !IsStopped();i++)
-     {
-
-      ExtChikouBuffer[i]=close[rates_total-1];
-      if(i >= InpKijun){
-         ExtChikouBuffer[i-InpKijun]=close[i];
-      }
-
-      //--- tenkan sen
-      double _high=Highest(high,InpTenkan,i);
-      double _low=Lowest(low,InpTenkan,i);
-      ExtTenkanBuffer[i]=(_high+_low)/2.0;
-      //--- kijun sen
-      _high=Highest(high,InpKijun,i);
-      _low=Lowest(low,InpKijun,i);
-      ExtKijunBuffer[i]=(_high+_low)/2.0;
-      //--- senkou span a
-      ExtSpanABuffer[i]=(ExtTenkanBuffer[i]+ExtKijunBuffer[i])/2.0;
-      //--- senkou span b
-      _high=Highest(high,InpSenkou,i);
-      _low=Lowest(low,InpSenkou,i);
-      ExtSpanBBuffer[i]=(_high+_low)/2.0;
-     }
-//--- done
-   return(rates_total);
-  }
-//+------------------------------------------------------------------+
-"#.to_string()
+   return rates_total;
+}
+"#);
+    out
 }
 
 // ── BT_KeltnerChannel ──
 
 fn gen_mql5_keltner_channel() -> String {
-r#"//+------------------------------------------------------------------+
-//|                                            BT_KeltnerChannel.mq5 |
-//|                           Copyright © 2017, StrategyQuant s.r.o. |
-//|                                     http://www.strategyquant.com |
-//+------------------------------------------------------------------+
-#property copyright "Copyright © 2017, StrategyQuant s.r.o."
-#property link      "http://www.strategyquant.com"
-//+------------------------------------------------------------------+
-//| Custom indicator initialization function                         |
-//+------------------------------------------------------------------+
-#property indicator_chart_window
+    let mut out = mql5_indicator_header("BT_KeltnerChannel");
+    out.push_str(r#"#property indicator_chart_window
 #property indicator_buffers 3
-#property indicator_plots 3
+#property indicator_plots   3
+#property indicator_label1  "Middle"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrSilver
+#property indicator_label2  "Upper"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrDodgerBlue
+#property indicator_label3  "Lower"
+#property indicator_type3   DRAW_LINE
+#property indicator_color3  clrOrangeRed
+input int    InpPeriod = 20;
+input double InpMultiplier = 1.5;
+// Buffer order matches the engine outputs: 0 = middle, 1 = upper, 2 = lower
+double ExtMiddle[], ExtUpper[], ExtLower[];
+int OnInit() {
+   SetIndexBuffer(0, ExtMiddle, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtUpper, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtLower, INDICATOR_DATA);
+   for(int b = 0; b < 3; b++) PlotIndexSetDouble(b, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_KeltnerChannel(" + IntegerToString(InpPeriod) + "," + DoubleToString(InpMultiplier, 2) + ")");
+   return INIT_SUCCEEDED;
+}
+// middle = SMA(typical price, period); offset = multiplier * SMA(high - low, period)
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtMiddle[i] = EMPTY_VALUE; ExtUpper[i] = EMPTY_VALUE; ExtLower[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      double tpSum = 0.0, hlSum = 0.0;
+      for(int j = i - InpPeriod + 1; j <= i; j++) {
+         tpSum += (high[j] + low[j] + close[j]) / 3.0;
+         hlSum += high[j] - low[j];
+      }
+      double offset = InpMultiplier * hlSum / InpPeriod;
+      ExtMiddle[i] = tpSum / InpPeriod;
+      ExtUpper[i] = ExtMiddle[i] + offset;
+      ExtLower[i] = ExtMiddle[i] - offset;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
 
-#property indicator_label1  "Upper"
-#property indicator_type1  DRAW_LINE
-#property indicator_color1 Blue
+// ── BT_DonchianChannel ──
 
-#property indicator_label2  "Lower"
-#property indicator_type2  DRAW_LINE
-#property indicator_color2 Red
-
-#property indicator_label3  "Middle"
-#property indicator_type3  DRAW_LINE
-#property indicator_color3 White
-
-
-double upper[], middle[], lower[];
-input int     MAPeriod = 20;
-input double  Const = 1.5;
-
-int period;
-
-void OnInit()
-  {
-//--- check for input parameters
-   if(MAPeriod <= 0){
-      printf("Incorrect value for input variable MAPeriod=%d. Indicator will use value=%d for calculations.", MAPeriod, 14);
-      period = 14;
-   }
-   else period = MAPeriod;
-
-   ArraySetAsSeries(upper, true);
-   ArraySetAsSeries(lower, true);
-   ArraySetAsSeries(middle, true);
-
-   SetIndexBuffer(0, upper);
-   SetIndexBuffer(1, lower);
-   SetIndexBuffer(2, middle);
-
-   PlotIndexSetInteger(1, PLOT_LINE_STYLE, STYLE_DOT);
-
-//--- indicator short name
-   string short_name="SqKeltnerChannel("+string(period)+","+string(Const)+")";
-   IndicatorSetString(INDICATOR_SHORTNAME,short_name);
-//---- end of initialization function
+fn gen_mql5_donchian_channel() -> String {
+    let mut out = mql5_indicator_header("BT_DonchianChannel");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 3
+#property indicator_plots   3
+#property indicator_label1  "Middle"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrSilver
+#property indicator_label2  "Upper"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrDodgerBlue
+#property indicator_label3  "Lower"
+#property indicator_type3   DRAW_LINE
+#property indicator_color3  clrOrangeRed
+input int InpPeriod = 20;
+// Buffer order matches the engine outputs: 0 = middle, 1 = upper, 2 = lower
+double ExtMiddle[], ExtUpper[], ExtLower[];
+int OnInit() {
+   SetIndexBuffer(0, ExtMiddle, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtUpper, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtLower, INDICATOR_DATA);
+   for(int b = 0; b < 3; b++) PlotIndexSetDouble(b, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_DonchianChannel(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
 }
-
-//+------------------------------------------------------------------+
-//| Custom indicator iteration function                              |
-//+------------------------------------------------------------------+
-int OnCalculate(const int rates_total,
-                const int prev_calculated,
-                const datetime &time[],
-                const double &open[],
-                const double &high[],
-                const double &low[],
-                const double &close[],
-                const long &tick_volume[],
-                const long &volume[],
-                const int &spread[])
-  {
-//--- checking for bars count
-   if(rates_total < period) return(0);
-
-   ArraySetAsSeries(time, true);
-   ArraySetAsSeries(open, true);
-   ArraySetAsSeries(high, true);
-   ArraySetAsSeries(low, true);
-   ArraySetAsSeries(close, true);
-
-   int limit;
-   double offset;
-
-   if(prev_calculated > 0) limit = rates_total - prev_calculated + 1;
-   else {
-      for(int a=0; a<rates_total; a++){
-         upper[a] = 0.0;
-         middle[a] = 0.0;
-         lower[a] = 0.0;
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtMiddle[i] = EMPTY_VALUE; ExtUpper[i] = EMPTY_VALUE; ExtLower[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      double hh = high[i], ll = low[i];
+      for(int j = i - InpPeriod + 1; j < i; j++) {
+         hh = MathMax(hh, high[j]);
+         ll = MathMin(ll, low[j]);
       }
-
-      limit = rates_total - period;
+      ExtUpper[i] = hh;
+      ExtLower[i] = ll;
+      ExtMiddle[i] = (hh + ll) / 2.0;
    }
-
-   for(int x=0; x<limit && !IsStopped(); x++) {
-      offset = avgDiff(high, low, period, x) * Const;
-
-      middle[x] = avgTrueRange(high, low, close, period, x);
-      upper[x] = middle[x] + offset;
-      lower[x] = middle[x] - offset;
-   }
-
-   return(rates_total);
-}
-
-//+------------------------------------------------------------------+
-
-double avgTrueRange(const double &high[], const double &low[], const double &close[], int atrPeriod, int shift) {
-  double sum=0;
-  for (int x=shift;x<(shift+atrPeriod);x++) {
-     sum += (high[x] + low[x] + close[x]) / 3;
-  }
-
-  sum = sum / atrPeriod;
-  return (sum);
-}
-
-double avgDiff(const double &high[], const double &low[], int atrPeriod, int shift) {
-  double sum=0;
-  for (int x=shift;x<(shift+atrPeriod);x++) {
-     sum += high[x] - low[x];
-  }
-
-  sum = sum / atrPeriod;
-  return (sum);
-}
-
-
-double getIndicatorValue(int indyHandle, int bufferIndex, int shift){
-   double buffer[];
-
-   if(CopyBuffer(indyHandle, bufferIndex, shift, 1, buffer) < 0) {
-      PrintFormat("Failed to copy data from the indicator, error code %d", GetLastError());
-      return(0);
-   }
-
-   return buffer[0];
+   return rates_total;
 }
-"#.to_string()
+"#);
+    out
 }
 
 // ── BT_SuperTrend ──
@@ -4837,6 +6076,7 @@ r#"//+------------------------------------------------------------------+
 input int    STMode=1;
 input int    ATRPeriod=24;
 input double ATRMultiplication=3;
+input int    ATRSmoothing=0;   // 0 = Wilder (native iATR), 1 = SMA, 2 = EMA (BT_ATR)
 
 //---- internal periods
 int inSTMode;
@@ -4869,7 +6109,7 @@ void OnInit()
    SetIndexBuffer(0, ind_buffer,INDICATOR_DATA);
    PlotIndexSetInteger(0,PLOT_DRAW_BEGIN,inATRPeriod);
 
-   atrHandle = iATR(NULL,0,inATRPeriod);
+   atrHandle = ATRSmoothing == 0 ? iATR(NULL,0,inATRPeriod) : iCustom(NULL,0,"BT_ATR",inATRPeriod,ATRSmoothing);
 
 //--- indicator short name
    string short_name="SqSRPercRank("+string(inATRPeriod)+","+string(inATRMultiplication)+")";
@@ -5425,19 +6665,29 @@ r#"//+------------------------------------------------------------------+
 #property  link      "http://www.strategyquant.com"
 
 #property indicator_chart_window
-#property indicator_buffers 1
-#property indicator_plots 1
+#property indicator_buffers 4
+#property indicator_plots 3
 
 #property indicator_label1  "SqLinReg"
 #property indicator_type1  DRAW_LINE
 #property indicator_color1 Blue
+#property indicator_label2  "Upper"
+#property indicator_type2  DRAW_LINE
+#property indicator_color2 Gray
+#property indicator_label3  "Lower"
+#property indicator_type3  DRAW_LINE
+#property indicator_color3 Gray
 
 input int LRPeriod=14;
 input int InpPrice=2;
+input double InpStdDev=2.0;   // Channel width (residual std devs)
 
 int period, mode;
 
 double ind_buffer[];
+double upper_buffer[];
+double lower_buffer[];
+double slope_buffer[];
 
 void OnInit()
   {
@@ -5463,8 +6713,14 @@ void OnInit()
    }
 
    ArraySetAsSeries(ind_buffer, true);
+   ArraySetAsSeries(upper_buffer, true);
+   ArraySetAsSeries(lower_buffer, true);
+   ArraySetAsSeries(slope_buffer, true);
 
    SetIndexBuffer(0, ind_buffer);
+   SetIndexBuffer(1, upper_buffer);
+   SetIndexBuffer(2, lower_buffer);
+   SetIndexBuffer(3, slope_buffer, INDICATOR_CALCULATIONS);
 
 //--- indicator short name
    string short_name="SqLinReg("+string(period)+")";
@@ -5498,13 +6754,20 @@ int OnCalculate(const int rates_total,
    else {
       for(int a=0; a<rates_total; a++){
          ind_buffer[a] = 0.0;
+         upper_buffer[a] = 0.0;
+         lower_buffer[a] = 0.0;
+         slope_buffer[a] = 0.0;
       }
 
       limit = rates_total - period;
    }
 
    for(int i=limit-1; i>=0; i--) {
-      ind_buffer[i] = linreg(open, high, low, close, mode, period, i);
+      double slope, dev;
+      ind_buffer[i] = linreg(open, high, low, close, mode, period, i, slope, dev);
+      slope_buffer[i] = slope;
+      upper_buffer[i] = ind_buffer[i] + InpStdDev * dev;
+      lower_buffer[i] = ind_buffer[i] - InpStdDev * dev;
    }
    return(rates_total);
   }
@@ -5516,7 +6779,9 @@ double linreg(const double &open[],
               const double &close[],
               int priceMode,
               int p,
-              int i){
+              int i,
+              double &slopeOut,
+              double &devOut){
 
    double SumY=0;
    double Sum1=0;
@@ -5540,6 +6805,15 @@ double linreg(const double &open[],
 
    double Intercept=(SumY-Slope*SumBars)/p;
    double linregval=Intercept+Slope*(p-1);
+
+   // Residual std dev around the line; bar i+x sits x bars before the fitted endpoint
+   double SumSqRes=0;
+   for (int x=0; x<p; x++) {
+      double res=getValue(open, high, low, close, priceMode, x+i)-(linregval-Slope*x);
+      SumSqRes+=res*res;
+   }
+   slopeOut=Slope;
+   devOut=MathSqrt(SumSqRes/p);
    return(linregval);
 }
 
@@ -5911,211 +7185,46 @@ int OnCalculate(const int rates_total,
 // ── BT_HullMA ──
 
 fn gen_mql5_hull_ma() -> String {
-r#"//+------------------------------------------------------------------+
-//|                                                     BT_HullMA.mq5 |
-//|                                    copyright "c mladen, 2019"     |
-//|                                     mladenfx@gmail.com            |
-//+------------------------------------------------------------------+
-#property copyright "c mladen, 2019"
-#property link      "mladenfx@gmail.com"
-//------------------------------------------------------------------
-#property indicator_chart_window
+    let mut out = mql5_indicator_header("BT_HullMA");
+    out.push_str(r#"#property indicator_chart_window
 #property indicator_buffers 2
 #property indicator_plots   1
-#property indicator_label1  "Hull"
-#property indicator_type1   DRAW_COLOR_LINE
-#property indicator_color1  clrGray,clrMediumSeaGreen,clrOrangeRed
-#property indicator_width1  2
-
-//
-//
-//
-//
-//
-
-input int                inpPeriod  = 20;          // Period
-input double             inpDivisor = 2.0;         // Divisor ("speed")
-input ENUM_APPLIED_PRICE inpPrice   = PRICE_CLOSE; // Price
-
-double val[],valc[];
-
-//------------------------------------------------------------------
-//
-//------------------------------------------------------------------
-//
-//
-//
-
-int OnInit()
-{
-   SetIndexBuffer(0,val,INDICATOR_DATA);
-   SetIndexBuffer(1,valc,INDICATOR_COLOR_INDEX);
-      iHull.init(inpPeriod,inpDivisor);
-         IndicatorSetString(INDICATOR_SHORTNAME,"Hull ("+(string)inpPeriod+")");
-   return (INIT_SUCCEEDED);
-}
-void OnDeinit(const int reason)
-{
+#property indicator_label1  "HMA"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrGold
+input int InpPeriod = 20;
+double ExtBuffer[], ExtDiff[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtDiff, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_HullMA(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
 }
-
-//------------------------------------------------------------------
-//
-//------------------------------------------------------------------
-//
-//
-//
-//
-//
-
-int OnCalculate(const int rates_total,const int prev_calculated,const datetime &time[],
-                const double &open[],
-                const double &high[],
-                const double &low[],
-                const double &close[],
-                const long &tick_volume[],
-                const long &volume[],
-                const int &spread[])
-{
-   int i= prev_calculated-1; if (i<0) i=0; for (; i<rates_total && !_StopFlag; i++)
-   {
-      val[i]  = iHull.calculate(getPrice(inpPrice,open,high,low,close,i),i,rates_total);
-      valc[i] = (i>0) ? (val[i]>val[i-1]) ? 1 : (val[i]<val[i-1]) ? 2 : valc[i-1] : 0;
-   }
-   return(i);
+// Linearly weighted average of src[i-period+1..i], weights 1..period (newest heaviest)
+double Wma(const double &src[], int i, int period) {
+   double sum = 0.0;
+   for(int j = 0; j < period; j++) sum += src[i - period + 1 + j] * (j + 1);
+   return sum / (period * (period + 1) / 2.0);
 }
-
-//------------------------------------------------------------------
-// Custom function(s)
-//------------------------------------------------------------------
-//
-//---
-//
-
-class CHull
-{
-   private :
-      int    m_fullPeriod;
-      int    m_halfPeriod;
-      int    m_sqrtPeriod;
-      int    m_arraySize;
-      double m_weight1;
-      double m_weight2;
-      double m_weight3;
-      struct sHullArrayStruct
-         {
-            double value;
-            double value3;
-            double wsum1;
-            double wsum2;
-            double wsum3;
-            double lsum1;
-            double lsum2;
-            double lsum3;
-         };
-      sHullArrayStruct m_array[];
-
-   public :
-      CHull() : m_fullPeriod(1), m_halfPeriod(1), m_sqrtPeriod(1), m_arraySize(-1) {                     }
-     ~CHull()                                                                      { ArrayFree(m_array); }
-
-      ///
-      ///
-      ///
-
-      bool init(int period, double divisor)
-      {
-            m_fullPeriod = (int)(period>1 ? period : 1);
-            m_halfPeriod = (int)(m_fullPeriod>1 ? m_fullPeriod/(divisor>1 ? divisor : 1) : 1);
-            m_sqrtPeriod = (int) MathSqrt(m_fullPeriod);
-            m_arraySize  = -1; m_weight1 = m_weight2 = m_weight3 = 1;
-               return(true);
-      }
-
-      //
-      //
-      //
-
-      double calculate( double value, int i, int bars)
-      {
-         if (m_arraySize<bars) { m_arraySize = ArrayResize(m_array,bars+500); if (m_arraySize<bars) return(0); }
-
-            //
-            //
-            //
-
-            m_array[i].value=value;
-            if (i>m_fullPeriod)
-            {
-               m_array[i].wsum1 = m_array[i-1].wsum1+value*m_halfPeriod-m_array[i-1].lsum1;
-               m_array[i].lsum1 = m_array[i-1].lsum1+value-m_array[i-m_halfPeriod].value;
-               m_array[i].wsum2 = m_array[i-1].wsum2+value*m_fullPeriod-m_array[i-1].lsum2;
-               m_array[i].lsum2 = m_array[i-1].lsum2+value-m_array[i-m_fullPeriod].value;
-            }
-            else
-            {
-               m_array[i].wsum1 = m_array[i].wsum2 =
-               m_array[i].lsum1 = m_array[i].lsum2 = m_weight1 = m_weight2 = 0;
-               for(int k=0, w1=m_halfPeriod, w2=m_fullPeriod; w2>0 && i>=k; k++, w1--, w2--)
-               {
-                  if (w1>0)
-                  {
-                     m_array[i].wsum1 += m_array[i-k].value*w1;
-                     m_array[i].lsum1 += m_array[i-k].value;
-                     m_weight1        += w1;
-                  }
-                  m_array[i].wsum2 += m_array[i-k].value*w2;
-                  m_array[i].lsum2 += m_array[i-k].value;
-                  m_weight2        += w2;
-               }
-            }
-            m_array[i].value3=2.0*m_array[i].wsum1/m_weight1-m_array[i].wsum2/m_weight2;
-
-            //
-            //---
-            //
-
-            if (i>m_sqrtPeriod)
-            {
-               m_array[i].wsum3 = m_array[i-1].wsum3+m_array[i].value3*m_sqrtPeriod-m_array[i-1].lsum3;
-               m_array[i].lsum3 = m_array[i-1].lsum3+m_array[i].value3-m_array[i-m_sqrtPeriod].value3;
-            }
-            else
-            {
-               m_array[i].wsum3 =
-               m_array[i].lsum3 = m_weight3 = 0;
-               for(int k=0, w3=m_sqrtPeriod; w3>0 && i>=k; k++, w3--)
-               {
-                  m_array[i].wsum3 += m_array[i-k].value3*w3;
-                  m_array[i].lsum3 += m_array[i-k].value3;
-                  m_weight3        += w3;
-               }
-            }
-         return(m_array[i].wsum3/m_weight3);
-      }
-};
-CHull iHull;
-
-//
-//---
-//
-
-template <typename T>
-double getPrice(ENUM_APPLIED_PRICE tprice, T& open[], T& high[], T& low[], T& close[], int i)
-{
-   switch(tprice)
-   {
-      case PRICE_CLOSE:     return(close[i]);
-      case PRICE_OPEN:      return(open[i]);
-      case PRICE_HIGH:      return(high[i]);
-      case PRICE_LOW:       return(low[i]);
-      case PRICE_MEDIAN:    return((high[i]+low[i])/2.0);
-      case PRICE_TYPICAL:   return((high[i]+low[i]+close[i])/3.0);
-      case PRICE_WEIGHTED:  return((high[i]+low[i]+close[i]+close[i])/4.0);
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   // Integer period/2 and floor(sqrt(period)), both at least 1 — as in the engine
+   int half = MathMax(InpPeriod / 2, 1);
+   int sqrtP = MathMax((int)MathSqrt(InpPeriod), 1);
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtDiff[i] = EMPTY_VALUE;
+      ExtBuffer[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      ExtDiff[i] = 2.0 * Wma(close, i, half) - Wma(close, i, InpPeriod);
+      if(i < InpPeriod + sqrtP - 2) continue;
+      ExtBuffer[i] = Wma(ExtDiff, i, sqrtP);
    }
-   return(0);
+   return rates_total;
 }
-//------------------------------------------------------------------
-"#.to_string()
+"#);
+    out
 }
 
 // ── BT_UlcerIndex ──
@@ -8122,7 +9231,7 @@ enum enPrices
 input int       SchaffPeriod = 10;       // Schaff period
 input int       FastEma      = 20;       // Fast EMA period
 input int       SlowEma      = 50;       // Slow EMA period
-double    SmoothPeriod = 3;        // Smoothing period
+input double    SmoothPeriod = 3;  // Smoothing period
 enPrices  Price        = pr_close; // Price
 
 double  val[],valc[],macd[],fastk1[],fastd1[],fastk2[];
@@ -8157,8 +9266,7 @@ int OnCalculate(const int rates_total,
 //
 //
 //
-   //double alpha=2.0/(1.0+SmoothPeriod);
-   double alpha=0.5;
+   double alpha=2.0/(1.0+MathMax(SmoothPeriod,1.0));
    int i=(int)MathMax(prev_calculated-1,0); for(; i<rates_total && !_StopFlag; i++)
      {
       double price=getPrice(Price,open,close,high,low,i,rates_total);
@@ -8271,27 +9379,920 @@ double getPrice(int tprice,const double &open[],const double &close[],const doub
 "#.to_string()
 }
 
-// ══════════════════════════════════════════════════════════════
-// Tests
-// ══════════════════════════════════════════════════════════════
+// ── BT_Choppiness ──
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn gen_mql5_choppiness() -> String {
+    let mut out = mql5_indicator_header("BT_Choppiness");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "Choppiness"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 14;
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE); IndicatorSetString(INDICATOR_SHORTNAME, "BT_Choppiness(" + IntegerToString(InpPeriod) + ")"); return INIT_SUCCEEDED; }
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 2 || rates_total <= InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      if(i < InpPeriod) continue;
+      double trSum = 0.0, hh = high[i], ll = low[i];
+      for(int j = i - InpPeriod + 1; j <= i; j++) {
+         trSum += MathMax(high[j] - low[j], MathMax(MathAbs(high[j] - close[j - 1]), MathAbs(low[j] - close[j - 1])));
+         hh = MathMax(hh, high[j]);
+         ll = MathMin(ll, low[j]);
+      }
+      if(hh - ll > 0.0)
+         ExtBuffer[i] = 100.0 * MathLog10(trSum / (hh - ll)) / MathLog10(InpPeriod);
+   }
+   return rates_total;
+}
+"#);
+    out
+}
 
-    fn simple_strategy() -> Strategy {
-        Strategy {
-            id: "test-1".into(),
-            name: "SMA Cross Test".into(),
-            created_at: "2024-01-01".into(),
-            updated_at: "2024-01-01".into(),
-            long_entry_rules: vec![
-                Rule {
-                    id: "r1".into(),
-                    left_operand: Operand {
-                        operand_type: OperandType::Price,
-                        price_field: Some(PriceField::Close),
-                        indicator: None,
+// ── BT_StochasticRSI ──
+
+fn gen_mql5_stochastic_rsi() -> String {
+    let mut out = mql5_indicator_header("BT_StochasticRSI");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 4
+#property indicator_plots   2
+#property indicator_minimum 0
+#property indicator_maximum 100
+#property indicator_label1  "%K"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+#property indicator_label2  "%D"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrOrangeRed
+input int InpRsiPeriod = 14;
+input int InpKPeriod   = 14;
+input int InpDPeriod   = 3;
+input int InpSlowing   = 3;
+double ExtK[], ExtD[], ExtRsi[], ExtRaw[];
+int OnInit() {
+   SetIndexBuffer(0, ExtK, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtD, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtRsi, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtRaw, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   PlotIndexSetDouble(1, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_StochasticRSI(" + IntegerToString(InpRsiPeriod) + "," + IntegerToString(InpKPeriod) + "," + IntegerToString(InpDPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+// SMA over the last n values of buf ending at i, EMPTY_VALUE if any are missing
+double WindowSma(const double &buf[], int i, int n, int first) {
+   if(n <= 1) return buf[i];
+   if(i - n + 1 < first) return EMPTY_VALUE;
+   double sum = 0.0;
+   for(int j = i - n + 1; j <= i; j++) { if(buf[j] == EMPTY_VALUE) return EMPTY_VALUE; sum += buf[j]; }
+   return sum / n;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpRsiPeriod < 1 || InpKPeriod < 1 || rates_total < InpRsiPeriod + InpKPeriod) return 0;
+   // Wilder RSI, first value at index InpRsiPeriod
+   double avgGain = 0.0, avgLoss = 0.0;
+   for(int i = 0; i < rates_total; i++) {
+      ExtRsi[i] = EMPTY_VALUE; ExtRaw[i] = EMPTY_VALUE; ExtK[i] = EMPTY_VALUE; ExtD[i] = EMPTY_VALUE;
+      if(i == 0) continue;
+      double ch = close[i] - close[i - 1];
+      double g = ch > 0.0 ? ch : 0.0, l = ch < 0.0 ? -ch : 0.0;
+      if(i <= InpRsiPeriod) {
+         avgGain += g; avgLoss += l;
+         if(i < InpRsiPeriod) continue;
+         avgGain /= InpRsiPeriod; avgLoss /= InpRsiPeriod;
+      } else {
+         avgGain = (avgGain * (InpRsiPeriod - 1) + g) / InpRsiPeriod;
+         avgLoss = (avgLoss * (InpRsiPeriod - 1) + l) / InpRsiPeriod;
+      }
+      ExtRsi[i] = avgLoss == 0.0 ? 100.0 : 100.0 - 100.0 / (1.0 + avgGain / avgLoss);
+   }
+   for(int i = InpRsiPeriod + InpKPeriod - 1; i < rates_total; i++) {
+      double hh = ExtRsi[i], ll = ExtRsi[i];
+      for(int j = i - InpKPeriod + 1; j <= i; j++) { hh = MathMax(hh, ExtRsi[j]); ll = MathMin(ll, ExtRsi[j]); }
+      ExtRaw[i] = hh - ll > 0.0 ? 100.0 * (ExtRsi[i] - ll) / (hh - ll) : 50.0;
+   }
+   int firstRaw = InpRsiPeriod + InpKPeriod - 1;
+   for(int i = firstRaw; i < rates_total; i++) ExtK[i] = WindowSma(ExtRaw, i, InpSlowing, firstRaw);
+   for(int i = firstRaw; i < rates_total; i++) ExtD[i] = WindowSma(ExtK, i, InpDPeriod, firstRaw);
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_TRIX ──
+
+fn gen_mql5_trix() -> String {
+    let mut out = mql5_indicator_header("BT_TRIX");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 5
+#property indicator_plots   2
+#property indicator_label1  "TRIX"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+#property indicator_label2  "Signal"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrOrangeRed
+input int InpPeriod = 15;
+input int InpSignal = 9;  // 0 = no signal line
+double ExtTrix[], ExtSignal[], ExtE1[], ExtE2[], ExtE3[];
+int OnInit() {
+   SetIndexBuffer(0, ExtTrix, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtSignal, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtE1, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtE2, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(4, ExtE3, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   PlotIndexSetDouble(1, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_TRIX(" + IntegerToString(InpPeriod) + "," + IntegerToString(InpSignal) + ")");
+   return INIT_SUCCEEDED;
+}
+"#);
+    out.push_str(MQL5_SEEDED_EMA);
+    out.push_str(r#"int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < 3 * (InpPeriod - 1) + 2) return 0;
+   SeededEma(close, ExtE1, 0, InpPeriod, rates_total);
+   SeededEma(ExtE1, ExtE2, InpPeriod - 1, InpPeriod, rates_total);
+   SeededEma(ExtE2, ExtE3, 2 * (InpPeriod - 1), InpPeriod, rates_total);
+   int first = 3 * (InpPeriod - 1) + 1;
+   for(int i = 0; i < rates_total; i++) {
+      ExtTrix[i] = EMPTY_VALUE;
+      if(i >= first && ExtE3[i - 1] != 0.0) ExtTrix[i] = (ExtE3[i] - ExtE3[i - 1]) / ExtE3[i - 1] * 100.0;
+   }
+   if(InpSignal > 0) SeededEma(ExtTrix, ExtSignal, first, InpSignal, rates_total);
+   else ArrayInitialize(ExtSignal, EMPTY_VALUE);
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_AccumulationDistribution ──
+
+fn gen_mql5_accumulation_distribution() -> String {
+    let mut out = mql5_indicator_header("BT_AccumulationDistribution");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "A/D"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); IndicatorSetString(INDICATOR_SHORTNAME, "BT_AccumulationDistribution"); return INIT_SUCCEEDED; }
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      double range = high[i] - low[i];
+      double clv = range > 0.0 ? ((close[i] - low[i]) - (high[i] - close[i])) / range : 0.0;
+      double vol = (double)(volume[i] ? volume[i] : tick_volume[i]);
+      ExtBuffer[i] = (i > 0 ? ExtBuffer[i - 1] : 0.0) + clv * vol;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_ChaikinMoneyFlow ──
+
+fn gen_mql5_chaikin_money_flow() -> String {
+    let mut out = mql5_indicator_header("BT_ChaikinMoneyFlow");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "CMF"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 20;
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE); IndicatorSetString(INDICATOR_SHORTNAME, "BT_ChaikinMoneyFlow(" + IntegerToString(InpPeriod) + ")"); return INIT_SUCCEEDED; }
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      double mfvSum = 0.0, volSum = 0.0;
+      for(int j = i - InpPeriod + 1; j <= i; j++) {
+         double range = high[j] - low[j];
+         double clv = range > 0.0 ? ((close[j] - low[j]) - (high[j] - close[j])) / range : 0.0;
+         double vol = (double)(volume[j] ? volume[j] : tick_volume[j]);
+         mfvSum += clv * vol;
+         volSum += vol;
+      }
+      if(volSum > 0.0) ExtBuffer[i] = mfvSum / volSum;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_DPO ──
+
+fn gen_mql5_dpo() -> String {
+    let mut out = mql5_indicator_header("BT_DPO");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "DPO"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 20;
+double ExtBuffer[];
+int OnInit() { SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA); PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE); IndicatorSetString(INDICATOR_SHORTNAME, "BT_DPO(" + IntegerToString(InpPeriod) + ")"); return INIT_SUCCEEDED; }
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int shift = InpPeriod / 2 + 1;
+   if(InpPeriod < 1 || rates_total < InpPeriod + shift) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      int j = i - shift;
+      if(j < InpPeriod - 1) continue;
+      double sum = 0.0;
+      for(int k = j - InpPeriod + 1; k <= j; k++) sum += close[k];
+      ExtBuffer[i] = close[i] - sum / InpPeriod;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_Coppock ──
+
+fn gen_mql5_coppock() -> String {
+    let mut out = mql5_indicator_header("BT_Coppock");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 2
+#property indicator_plots   1
+#property indicator_label1  "Coppock"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 10;
+input int InpFastROC = 11;
+input int InpSlowROC = 14;
+double ExtBuffer[], ExtRocSum[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtRocSum, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_Coppock(" + IntegerToString(InpPeriod) + "," + IntegerToString(InpFastROC) + "," + IntegerToString(InpSlowROC) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int rocLen = MathMax(InpFastROC, InpSlowROC);
+   if(InpPeriod < 1 || rates_total < rocLen + InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   double denom = InpPeriod * (InpPeriod + 1) / 2.0;
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtRocSum[i] = EMPTY_VALUE;
+      if(i < rocLen || close[i - InpFastROC] == 0.0 || close[i - InpSlowROC] == 0.0) continue;
+      ExtRocSum[i] = (close[i] - close[i - InpSlowROC]) / close[i - InpSlowROC] * 100.0
+                   + (close[i] - close[i - InpFastROC]) / close[i - InpFastROC] * 100.0;
+      if(i < rocLen + InpPeriod - 1) continue;
+      double sum = 0.0;
+      bool valid = true;
+      for(int j = 0; j < InpPeriod; j++) {
+         double v = ExtRocSum[i - InpPeriod + 1 + j];
+         if(v == EMPTY_VALUE) { valid = false; break; }
+         sum += v * (j + 1);
+      }
+      if(valid) ExtBuffer[i] = sum / denom;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_PPO ──
+
+fn gen_mql5_ppo() -> String {
+    let mut out = mql5_indicator_header("BT_PPO");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 5
+#property indicator_plots   3
+#property indicator_label1  "PPO"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+#property indicator_label2  "Signal"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrRed
+#property indicator_label3  "Histogram"
+#property indicator_type3   DRAW_HISTOGRAM
+#property indicator_color3  clrGray
+input int InpFastPeriod   = 12;
+input int InpSlowPeriod   = 26;
+input int InpSignalPeriod = 9;
+input int InpSeed         = 0;  // EMA seed: 0 = SMA of first 'period' values, 1 = first value
+double ExtPpo[], ExtSignal[], ExtHist[], ExtFast[], ExtSlow[];
+int OnInit() {
+   SetIndexBuffer(0, ExtPpo, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtSignal, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtHist, INDICATOR_DATA);
+   SetIndexBuffer(3, ExtFast, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(4, ExtSlow, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   PlotIndexSetDouble(1, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   PlotIndexSetDouble(2, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_PPO(" + IntegerToString(InpFastPeriod) + "," + IntegerToString(InpSlowPeriod) + "," + IntegerToString(InpSignalPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+"#);
+    out.push_str(MQL5_SEEDED_EMA);
+    out.push_str(r#"int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(rates_total < MathMax(InpFastPeriod, InpSlowPeriod)) return 0;
+   bool firstValue = InpSeed == 1;
+   SeededEma(close, ExtFast, 0, InpFastPeriod, rates_total, firstValue);
+   SeededEma(close, ExtSlow, 0, InpSlowPeriod, rates_total, firstValue);
+   // First-value EMAs are defined from bar 0, SMA-seeded ones once the slower has a full window
+   int first = firstValue ? 0 : MathMax(InpFastPeriod, InpSlowPeriod) - 1;
+   for(int i = 0; i < rates_total; i++) {
+      ExtPpo[i] = EMPTY_VALUE;
+      if(i >= first && ExtSlow[i] != 0.0) ExtPpo[i] = 100.0 * (ExtFast[i] - ExtSlow[i]) / ExtSlow[i];
+   }
+   SeededEma(ExtPpo, ExtSignal, first, InpSignalPeriod, rates_total, firstValue);
+   for(int i = 0; i < rates_total; i++)
+      ExtHist[i] = (ExtPpo[i] == EMPTY_VALUE || ExtSignal[i] == EMPTY_VALUE) ? EMPTY_VALUE : ExtPpo[i] - ExtSignal[i];
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_SuperSmoother ──
+
+fn gen_mql5_super_smoother() -> String {
+    let mut out = mql5_indicator_header("BT_SuperSmoother");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 2
+#property indicator_plots   1
+#property indicator_label1  "SuperSmoother"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 10;
+double ExtBuffer[], ExtFilt[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtFilt, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_SuperSmoother(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < 3) return 0;
+   double a1 = MathExp(-1.414 * M_PI / InpPeriod);
+   double c2 = 2.0 * a1 * MathCos(1.414 * M_PI / InpPeriod);
+   double c3 = -a1 * a1;
+   double c1 = 1.0 - c2 - c3;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   if(start < 2) { ExtFilt[0] = close[0]; ExtFilt[1] = close[1]; ExtBuffer[0] = EMPTY_VALUE; ExtBuffer[1] = EMPTY_VALUE; start = 2; }
+   for(int i = start; i < rates_total; i++) {
+      ExtFilt[i] = c1 * (close[i] + close[i - 1]) / 2.0 + c2 * ExtFilt[i - 1] + c3 * ExtFilt[i - 2];
+      ExtBuffer[i] = ExtFilt[i];
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_RoofingFilter ──
+
+fn gen_mql5_roofing_filter() -> String {
+    let mut out = mql5_indicator_header("BT_RoofingFilter");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 3
+#property indicator_plots   1
+#property indicator_label1  "RoofingFilter"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpHPPeriod = 48;
+input int InpPeriod = 10;
+double ExtBuffer[], ExtHP[], ExtFilt[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtHP, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(2, ExtFilt, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_RoofingFilter(" + IntegerToString(InpHPPeriod) + "," + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || InpHPPeriod < 1 || rates_total < 3) return 0;
+   double w = 0.707 * 2.0 * M_PI / InpHPPeriod;
+   double alpha1 = (MathCos(w) + MathSin(w) - 1.0) / MathCos(w);
+   double a1 = MathExp(-1.414 * M_PI / InpPeriod);
+   double c2 = 2.0 * a1 * MathCos(1.414 * M_PI / InpPeriod);
+   double c3 = -a1 * a1;
+   double c1 = 1.0 - c2 - c3;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   if(start < 2) {
+      for(int k = 0; k < 2; k++) { ExtHP[k] = 0.0; ExtFilt[k] = 0.0; ExtBuffer[k] = EMPTY_VALUE; }
+      start = 2;
+   }
+   for(int i = start; i < rates_total; i++) {
+      ExtHP[i] = (1.0 - alpha1 / 2.0) * (1.0 - alpha1 / 2.0) * (close[i] - 2.0 * close[i - 1] + close[i - 2])
+               + 2.0 * (1.0 - alpha1) * ExtHP[i - 1] - (1.0 - alpha1) * (1.0 - alpha1) * ExtHP[i - 2];
+      ExtFilt[i] = c1 * (ExtHP[i] + ExtHP[i - 1]) / 2.0 + c2 * ExtFilt[i - 1] + c3 * ExtFilt[i - 2];
+      ExtBuffer[i] = ExtFilt[i];
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_ConnorsRSI ──
+
+fn gen_mql5_connors_rsi() -> String {
+    let mut out = mql5_indicator_header("BT_ConnorsRSI");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 7
+#property indicator_plots   1
+#property indicator_label1  "ConnorsRSI"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrMediumPurple
+#property indicator_level1  90
+#property indicator_level2  10
+#property indicator_minimum 0
+#property indicator_maximum 100
+input int InpRsiPeriod = 3;
+input int InpStreakPeriod = 2;
+input int InpRankPeriod = 100;
+double ExtBuffer[], ExtStreak[], ExtRoc[], ExtPriceGain[], ExtPriceLoss[], ExtStreakGain[], ExtStreakLoss[];
+// Wilder averages of src[] changes at bar i, seeded with a simple average of the first 'period' changes.
+// Returns the RSI, or EMPTY_VALUE before the seed bar.
+double WilderStep(const double &src[], double &avgGain[], double &avgLoss[], int i, int period) {
+   if(i < period) return EMPTY_VALUE;
+   if(i == period) {
+      double g = 0.0, l = 0.0;
+      for(int j = 1; j <= period; j++) { double c = src[j] - src[j - 1]; if(c > 0) g += c; else l -= c; }
+      avgGain[i] = g / period;
+      avgLoss[i] = l / period;
+   } else {
+      double c = src[i] - src[i - 1];
+      avgGain[i] = (avgGain[i - 1] * (period - 1) + (c > 0 ? c : 0.0)) / period;
+      avgLoss[i] = (avgLoss[i - 1] * (period - 1) + (c < 0 ? -c : 0.0)) / period;
+   }
+   return avgLoss[i] == 0.0 ? 100.0 : 100.0 - 100.0 / (1.0 + avgGain[i] / avgLoss[i]);
+}
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtStreak, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(2, ExtRoc, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtPriceGain, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(4, ExtPriceLoss, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(5, ExtStreakGain, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(6, ExtStreakLoss, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_ConnorsRSI(" + IntegerToString(InpRsiPeriod) + "," + IntegerToString(InpStreakPeriod) + "," + IntegerToString(InpRankPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int first = MathMax(MathMax(InpRsiPeriod, InpStreakPeriod), InpRankPeriod + 1);
+   if(InpRsiPeriod < 1 || InpStreakPeriod < 1 || InpRankPeriod < 1 || rates_total <= first) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtStreak[i] = 0.0;
+      ExtRoc[i] = EMPTY_VALUE;
+      if(i > 0) {
+         if(close[i] > close[i - 1])      ExtStreak[i] = MathMax(ExtStreak[i - 1], 0.0) + 1.0;
+         else if(close[i] < close[i - 1]) ExtStreak[i] = MathMin(ExtStreak[i - 1], 0.0) - 1.0;
+         if(close[i - 1] != 0.0) ExtRoc[i] = (close[i] - close[i - 1]) / close[i - 1] * 100.0;
+      }
+      double priceRsi = WilderStep(close, ExtPriceGain, ExtPriceLoss, i, InpRsiPeriod);
+      double streakRsi = WilderStep(ExtStreak, ExtStreakGain, ExtStreakLoss, i, InpStreakPeriod);
+      if(i < first || ExtRoc[i] == EMPTY_VALUE) continue;
+      int below = 0;
+      bool valid = true;
+      for(int j = i - InpRankPeriod; j < i; j++) {
+         if(ExtRoc[j] == EMPTY_VALUE) { valid = false; break; }
+         if(ExtRoc[j] < ExtRoc[i]) below++;
+      }
+      if(valid) ExtBuffer[i] = (priceRsi + streakRsi + 100.0 * below / InpRankPeriod) / 3.0;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_ForceIndex ──
+
+fn gen_mql5_force_index() -> String {
+    let mut out = mql5_indicator_header("BT_ForceIndex");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 2
+#property indicator_plots   1
+#property indicator_label1  "Force"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod = 13;
+double ExtBuffer[], ExtRaw[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtRaw, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_ForceIndex(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod + 1) return 0;
+   double k = 2.0 / (InpPeriod + 1.0);
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtRaw[i] = 0.0;
+      if(i < 1) continue;
+      double vol = (double)(volume[i] ? volume[i] : tick_volume[i]);
+      ExtRaw[i] = (close[i] - close[i - 1]) * vol;
+      if(i < InpPeriod) continue;
+      if(i == InpPeriod) {
+         // Seed with the simple average of the first 'period' raw values
+         double sum = 0.0;
+         for(int j = 1; j <= InpPeriod; j++) sum += ExtRaw[j];
+         ExtBuffer[i] = sum / InpPeriod;
+      } else {
+         ExtBuffer[i] = (ExtRaw[i] - ExtBuffer[i - 1]) * k + ExtBuffer[i - 1];
+      }
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_EaseOfMovement ──
+
+fn gen_mql5_ease_of_movement() -> String {
+    let mut out = mql5_indicator_header("BT_EaseOfMovement");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 2
+#property indicator_plots   1
+#property indicator_label1  "EOM"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+input int    InpPeriod = 14;
+input double InpScale = 10000.0;
+double ExtBuffer[], ExtEmv[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtEmv, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_EaseOfMovement(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod + 1) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtEmv[i] = 0.0;
+      if(i < 1) continue;
+      double range = high[i] - low[i];
+      double vol = (double)(volume[i] ? volume[i] : tick_volume[i]);
+      if(range > 1e-10 && vol > 0.0)
+         ExtEmv[i] = ((high[i] + low[i]) / 2.0 - (high[i - 1] + low[i - 1]) / 2.0) * range * InpScale / vol;
+      if(i < InpPeriod) continue;
+      double sum = 0.0;
+      for(int j = i - InpPeriod + 1; j <= i; j++) sum += ExtEmv[j];
+      ExtBuffer[i] = sum / InpPeriod;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_VWMA ──
+
+fn gen_mql5_vwma() -> String {
+    let mut out = mql5_indicator_header("BT_VWMA");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "VWMA"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrTeal
+input int InpPeriod = 20;
+double ExtBuffer[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_VWMA(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      double pv = 0.0, vs = 0.0, cs = 0.0;
+      for(int j = i - InpPeriod + 1; j <= i; j++) {
+         double vol = (double)(volume[j] ? volume[j] : tick_volume[j]);
+         pv += close[j] * vol;
+         vs += vol;
+         cs += close[j];
+      }
+      // Zero-volume window: fall back to the simple average
+      ExtBuffer[i] = (vs > 0.0 ? pv / vs : cs / InpPeriod);
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_WMA ──
+
+fn gen_mql5_wma() -> String {
+    let mut out = mql5_indicator_header("BT_WMA");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 1
+#property indicator_plots   1
+#property indicator_label1  "WMA"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrOrchid
+input int InpPeriod = 20;
+double ExtBuffer[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_WMA(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(InpPeriod < 1 || rates_total < InpPeriod) return 0;
+   double denom = InpPeriod * (InpPeriod + 1) / 2.0;
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      if(i < InpPeriod - 1) continue;
+      // Weights 1..period, newest bar heaviest
+      double sum = 0.0;
+      for(int j = 0; j < InpPeriod; j++) sum += close[i - InpPeriod + 1 + j] * (j + 1);
+      ExtBuffer[i] = sum / denom;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_ZLEMA ──
+
+fn gen_mql5_zlema() -> String {
+    let mut out = mql5_indicator_header("BT_ZLEMA");
+    out.push_str(r#"#property indicator_chart_window
+#property indicator_buffers 2
+#property indicator_plots   1
+#property indicator_label1  "ZLEMA"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrMagenta
+input int InpPeriod = 20;
+double ExtBuffer[], ExtRaw[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtRaw, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_ZLEMA(" + IntegerToString(InpPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int lag = (InpPeriod - 1) / 2;
+   int first = lag + InpPeriod - 1;
+   if(InpPeriod < 1 || rates_total <= first) return 0;
+   double k = 2.0 / (InpPeriod + 1.0);
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtRaw[i] = 0.0;
+      if(i < lag) continue;
+      // De-lagged price: close plus its change over 'lag' bars
+      ExtRaw[i] = 2.0 * close[i] - close[i - lag];
+      if(i < first) continue;
+      if(i == first) {
+         double sum = 0.0;
+         for(int j = lag; j <= first; j++) sum += ExtRaw[j];
+         ExtBuffer[i] = sum / InpPeriod;
+      } else {
+         ExtBuffer[i] = (ExtRaw[i] - ExtBuffer[i - 1]) * k + ExtBuffer[i - 1];
+      }
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_ElderImpulse ──
+
+fn gen_mql5_elder_impulse() -> String {
+    let mut out = mql5_indicator_header("BT_ElderImpulse");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 6
+#property indicator_plots   1
+#property indicator_label1  "Impulse"
+#property indicator_type1   DRAW_HISTOGRAM
+#property indicator_color1  clrDodgerBlue
+input int InpPeriod       = 13;
+input int InpFastPeriod   = 12;
+input int InpSlowPeriod   = 26;
+input int InpSignalPeriod = 9;
+double ExtImpulse[], ExtEma[], ExtFast[], ExtSlow[], ExtMacd[], ExtSignal[];
+int OnInit() {
+   SetIndexBuffer(0, ExtImpulse, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtEma, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(2, ExtFast, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtSlow, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(4, ExtMacd, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(5, ExtSignal, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_ElderImpulse(" + IntegerToString(InpPeriod) + "," + IntegerToString(InpFastPeriod) + "," + IntegerToString(InpSlowPeriod) + "," + IntegerToString(InpSignalPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+"#);
+    out.push_str(MQL5_SEEDED_EMA);
+    out.push_str(r#"int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   if(rates_total < MathMax(InpPeriod, InpSlowPeriod) + 1) return 0;
+   SeededEma(close, ExtEma, 0, InpPeriod, rates_total);
+   SeededEma(close, ExtFast, 0, InpFastPeriod, rates_total);
+   SeededEma(close, ExtSlow, 0, InpSlowPeriod, rates_total);
+   int first = MathMax(InpFastPeriod, InpSlowPeriod) - 1;
+   for(int i = 0; i < rates_total; i++)
+      ExtMacd[i] = (i >= first) ? ExtFast[i] - ExtSlow[i] : EMPTY_VALUE;
+   SeededEma(ExtMacd, ExtSignal, first, InpSignalPeriod, rates_total);
+   ExtImpulse[0] = EMPTY_VALUE;
+   for(int i = 1; i < rates_total; i++) {
+      ExtImpulse[i] = EMPTY_VALUE;
+      if(ExtEma[i - 1] == EMPTY_VALUE || ExtSignal[i - 1] == EMPTY_VALUE) continue;
+      double dEma = ExtEma[i] - ExtEma[i - 1];
+      double dHist = (ExtMacd[i] - ExtSignal[i]) - (ExtMacd[i - 1] - ExtSignal[i - 1]);
+      // +1 when trend and momentum both rise, -1 when both fall, 0 otherwise
+      ExtImpulse[i] = (dEma > 0.0 && dHist > 0.0) ? 1.0 : (dEma < 0.0 && dHist < 0.0) ? -1.0 : 0.0;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_Klinger ──
+
+fn gen_mql5_klinger() -> String {
+    let mut out = mql5_indicator_header("BT_Klinger");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 5
+#property indicator_plots   2
+#property indicator_label1  "KVO"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+#property indicator_label2  "Signal"
+#property indicator_type2   DRAW_LINE
+#property indicator_color2  clrOrangeRed
+#property indicator_level1  0.0
+input int InpFast   = 34;
+input int InpSlow   = 55;
+input int InpSignal = 13;
+double ExtKvo[], ExtSignal[], ExtForce[], ExtFast[], ExtSlow[];
+int OnInit() {
+   SetIndexBuffer(0, ExtKvo, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtSignal, INDICATOR_DATA);
+   SetIndexBuffer(2, ExtForce, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtFast, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(4, ExtSlow, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   PlotIndexSetDouble(1, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_Klinger(" + IntegerToString(InpFast) + "," + IntegerToString(InpSlow) + "," + IntegerToString(InpSignal) + ")");
+   return INIT_SUCCEEDED;
+}
+"#);
+    out.push_str(MQL5_SEEDED_EMA);
+    out.push_str(r#"int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int first = MathMax(InpFast, InpSlow);
+   if(InpFast < 1 || InpSlow < 1 || InpSignal < 1 || rates_total < first + 1) return 0;
+   // Volume force: trend from the typical price; cm accumulates the range while the
+   // trend holds and restarts from the prior bar's range on a flip
+   double trend = 0.0, cm = 0.0;
+   ExtForce[0] = EMPTY_VALUE;
+   for(int i = 1; i < rates_total; i++) {
+      double tp = (high[i] + low[i] + close[i]) / 3.0;
+      double prevTp = (high[i - 1] + low[i - 1] + close[i - 1]) / 3.0;
+      double dm = high[i] - low[i];
+      double barTrend = tp > prevTp ? 1.0 : (tp < prevTp ? -1.0 : (trend == 0.0 ? 1.0 : trend));
+      if(trend == 0.0) cm = dm;
+      else if(barTrend == trend) cm += dm;
+      else cm = (high[i - 1] - low[i - 1]) + dm;
+      trend = barTrend;
+      double vol = (double)(volume[i] ? volume[i] : tick_volume[i]);
+      ExtForce[i] = cm > 0.0 ? vol * MathAbs(2.0 * (dm / cm - 1.0)) * trend * 100.0 : 0.0;
+   }
+   SeededEma(ExtForce, ExtFast, 1, InpFast, rates_total);
+   SeededEma(ExtForce, ExtSlow, 1, InpSlow, rates_total);
+   for(int i = 0; i < rates_total; i++) ExtKvo[i] = (i >= first) ? ExtFast[i] - ExtSlow[i] : EMPTY_VALUE;
+   SeededEma(ExtKvo, ExtSignal, first, InpSignal, rates_total);
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ── BT_MassIndex ──
+
+fn gen_mql5_mass_index() -> String {
+    let mut out = mql5_indicator_header("BT_MassIndex");
+    out.push_str(r#"#property indicator_separate_window
+#property indicator_buffers 4
+#property indicator_plots   1
+#property indicator_label1  "Mass Index"
+#property indicator_type1   DRAW_LINE
+#property indicator_color1  clrDodgerBlue
+#property indicator_level1  26.5
+#property indicator_level2  27.0
+input int InpPeriod    = 25;
+input int InpEmaPeriod = 9;
+double ExtBuffer[], ExtSingle[], ExtDouble[], ExtRatio[];
+int OnInit() {
+   SetIndexBuffer(0, ExtBuffer, INDICATOR_DATA);
+   SetIndexBuffer(1, ExtSingle, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(2, ExtDouble, INDICATOR_CALCULATIONS);
+   SetIndexBuffer(3, ExtRatio, INDICATOR_CALCULATIONS);
+   PlotIndexSetDouble(0, PLOT_EMPTY_VALUE, EMPTY_VALUE);
+   IndicatorSetString(INDICATOR_SHORTNAME, "BT_MassIndex(" + IntegerToString(InpPeriod) + "," + IntegerToString(InpEmaPeriod) + ")");
+   return INIT_SUCCEEDED;
+}
+int OnCalculate(const int rates_total, const int prev_calculated, const datetime &time[], const double &open[], const double &high[], const double &low[], const double &close[], const long &tick_volume[], const long &volume[], const int &spread[]) {
+   int e = InpEmaPeriod;
+   int firstSingle = e - 1;
+   int firstDouble = 2 * (e - 1);
+   if(InpPeriod < 1 || e < 1 || rates_total < firstDouble + InpPeriod) return 0;
+   double k = 2.0 / (e + 1.0);
+   int start = (prev_calculated > 0 ? prev_calculated - 1 : 0);
+   for(int i = start; i < rates_total; i++) {
+      ExtBuffer[i] = EMPTY_VALUE;
+      ExtSingle[i] = EMPTY_VALUE;
+      ExtDouble[i] = EMPTY_VALUE;
+      ExtRatio[i] = EMPTY_VALUE;
+      if(i < firstSingle) continue;
+      // Single EMA of the bar range, seeded with the SMA of the first 'e' ranges
+      if(i == firstSingle) {
+         double sum = 0.0;
+         for(int j = 0; j <= i; j++) sum += high[j] - low[j];
+         ExtSingle[i] = sum / e;
+      } else {
+         ExtSingle[i] = ((high[i] - low[i]) - ExtSingle[i - 1]) * k + ExtSingle[i - 1];
+      }
+      if(i < firstDouble) continue;
+      // Double EMA: EMA of the single EMA, seeded the same way
+      if(i == firstDouble) {
+         double sum = 0.0;
+         for(int j = firstSingle; j <= i; j++) sum += ExtSingle[j];
+         ExtDouble[i] = sum / e;
+      } else {
+         ExtDouble[i] = (ExtSingle[i] - ExtDouble[i - 1]) * k + ExtDouble[i - 1];
+      }
+      ExtRatio[i] = (MathAbs(ExtDouble[i]) < 1e-12) ? 1.0 : ExtSingle[i] / ExtDouble[i];
+      if(i < firstDouble + InpPeriod - 1) continue;
+      double mass = 0.0;
+      for(int j = i - InpPeriod + 1; j <= i; j++) mass += ExtRatio[j];
+      ExtBuffer[i] = mass;
+   }
+   return rates_total;
+}
+"#);
+    out
+}
+
+// ══════════════════════════════════════════════════════════════
+// Tests
+// ══════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple_strategy() -> Strategy {
+        Strategy {
+            id: "test-1".into(),
+            name: "SMA Cross Test".into(),
+            created_at: "2024-01-01".into(),
+            updated_at: "2024-01-01".into(),
+            long_entry_rules: vec![
+                Rule {
+                    id: "r1".into(),
+                    left_operand: Operand {
+                        operand_type: OperandType::Price,
+                        price_field: Some(PriceField::Close),
+                        indicator: None,
                         constant_value: None,
                         time_field: None,
                         candle_pattern: None,
@@ -8299,6 +10300,8 @@ mod tests {
                         compound_left: None,
                         compound_op: None,
                         compound_right: None,
+                        percent_of: None,
+                        transform: None,
                     },
                     comparator: Comparator::CrossAbove,
                     right_operand: Operand {
@@ -8317,8 +10320,13 @@ mod tests {
                         compound_left: None,
                         compound_op: None,
                         compound_right: None,
+                        percent_of: None,
+                        transform: None,
                     },
                     logical_operator: Some(LogicalOperator::And),
+                    open_parens: 0,
+                    close_parens: 0,
+                    tolerance: None,
                 },
                 Rule {
                     id: "r2".into(),
@@ -8338,6 +10346,8 @@ mod tests {
                         compound_left: None,
                         compound_op: None,
                         compound_right: None,
+                        percent_of: None,
+                        transform: None,
                     },
                     comparator: Comparator::GreaterThan,
                     right_operand: Operand {
@@ -8351,8 +10361,13 @@ mod tests {
                         compound_left: None,
                         compound_op: None,
                         compound_right: None,
+                        percent_of: None,
+                        transform: None,
                     },
                     logical_operator: None,
+                    open_parens: 0,
+                    close_parens: 0,
+                    tolerance: None,
                 },
             ],
             short_entry_rules: vec![],
@@ -8384,6 +10399,7 @@ mod tests {
                 commission_value: 0.1,
                 slippage_pips: 0.0,
                 slippage_random: false,
+                max_spread_pips: None,
             },
             trade_direction: TradeDirection::Long,
             trading_hours: None,
@@ -8392,20 +10408,270 @@ mod tests {
             entry_order: OrderType::Market,
             entry_order_offset_pips: 0.0,
             close_after_bars: None,
+            min_hold_bars: None,
+            max_hold_bars: None,
             move_sl_to_be: false,
+            close_on_opposite_signal: false,
+            reverse_on_opposite_signal: false,
+            session_exit: None,
             entry_order_indicator: None,
         }
     }
 
-    /// Helper: get main file code from a CodeGenerationResult
-    fn main_code(result: &CodeGenerationResult) -> &str {
-        &result.files.iter().find(|f| f.is_main).unwrap().code
+    /// Helper: get main file code from a CodeGenerationResult
+    fn main_code(result: &CodeGenerationResult) -> &str {
+        &result.files.iter().find(|f| f.is_main).unwrap().code
+    }
+
+    #[test]
+    fn test_pip_distances_use_digit_aware_pip_size() {
+        // A fixed `_Point * 10` is wrong on 2-digit JPY and index symbols
+        let strategy = simple_strategy();
+        let code = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(code.contains("input double InpPipPoints = 0;"));
+        assert!(code.contains("double PipSize()"));
+        assert!(code.contains("(_Digits == 3 || _Digits == 5) ? _Point * 10.0 : _Point"));
+        assert!(code.contains("double dist = InpSLPips * PipSize();"));
+        assert!(code.contains("double dist = InpTPPips * PipSize();"));
+        assert!(!code.contains("_Point * 10;"));
+
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("slDist = i_sl_pips * pipSize"));
+        assert!(pine.contains("tpDist = i_tp_pips * pipSize"));
+
+        // No pip-based SL/TP → no pip helper or input
+        let mut no_pips = simple_strategy();
+        no_pips.stop_loss = None;
+        no_pips.take_profit = None;
+        let code = main_code(&generate_mql5(&no_pips, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(!code.contains("PipSize()") && !code.contains("InpPipPoints"));
+    }
+
+    #[test]
+    fn test_pip_points_default_from_instrument() {
+        // Index CFD quoted to 2 digits with a 1.0 pip: the digits rule would give 1 point
+        // per pip, 100x smaller SL/TP distances than the engine uses
+        let strategy = simple_strategy();
+        let index = InstrumentConfig { pip_size: 1.0, pip_value: 1.0, lot_size: 1.0, tick_size: 0.01, digits: 2, ..Default::default() };
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), Some(&index)).unwrap()).to_string();
+        assert!(mql5.contains("input double InpPipPoints = 100;"));
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), Some(&index), false).unwrap()).to_string();
+        assert!(pine.contains("i_pip_points = input.float(100, "));
+
+        // EUR/USD at 5 digits: 10 points, without float noise in the literal
+        let eurusd = InstrumentConfig { pip_size: 0.0001, tick_size: 0.00001, digits: 5, ..Default::default() };
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), Some(&eurusd)).unwrap()).to_string();
+        assert!(mql5.contains("input double InpPipPoints = 10;"));
+
+        // Unset pip size keeps the digits-based auto rule
+        let unset = InstrumentConfig { pip_size: 0.0, ..Default::default() };
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), Some(&unset)).unwrap()).to_string();
+        assert!(mql5.contains("input double InpPipPoints = 0;"));
+    }
+
+    #[test]
+    fn test_trailing_stop_activation_generation() {
+        let mut strategy = simple_strategy();
+        strategy.trailing_stop = Some(TrailingStop {
+            ts_type: TrailingStopType::ATR,
+            value: 2.0,
+            atr_period: Some(14),
+            activation_pips: Some(20.0),
+            step_pips: None,
+        });
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("input double InpTSActivationPips = 20.0;"));
+        assert!(mql5.contains("if(profitDist < InpTSActivationPips * PipSize()) return;"));
+
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("i_ts_activation_pips = input.float(20.0"));
+        assert!(pine.contains("trail_points=i_ts_activation_pips * pipSize / syminfo.mintick"));
+        assert!(pine.contains("trail_offset=atr_14 * i_ts_atr_mult / syminfo.mintick"));
+
+        // Without a threshold Pine trails from entry, like the backtester
+        strategy.trailing_stop.as_mut().unwrap().activation_pips = None;
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(!mql5.contains("InpTSActivationPips"));
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("trail_points=0, trail_offset=atr_14 * i_ts_atr_mult / syminfo.mintick"));
+    }
+
+    #[test]
+    fn test_opposite_signal_close_and_reverse_emitted() {
+        let mut strategy = simple_strategy();
+        strategy.trade_direction = TradeDirection::Both;
+        strategy.short_entry_rules = strategy.long_entry_rules.clone();
+        strategy.close_on_opposite_signal = true;
+
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("if(posType == POSITION_TYPE_BUY && CheckShortEntry())"));
+        assert!(mql5.contains("if(posType == POSITION_TYPE_SELL && CheckLongEntry())"));
+        assert!(!mql5.contains("reverse on opposite signal"));
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("if strategy.position_size > 0 and shortEntry\n    strategy.close(\"Long\", comment=\"Opposite Signal\")"));
+
+        strategy.close_on_opposite_signal = false;
+        strategy.reverse_on_opposite_signal = true;
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("OpenShort();   // reverse on opposite signal"));
+        assert!(mql5.contains("OpenLong();   // reverse on opposite signal"));
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("strategy.entry(\"Short\", strategy.short, comment=\"Reverse\")"));
+        assert!(!pine.contains("Opposite Signal"));
+    }
+
+    #[test]
+    fn test_session_exit_emitted() {
+        let mut strategy = simple_strategy();
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(!mql5.contains("InpSessionStartHour"));
+
+        strategy.session_exit = Some(SessionExit::MarketOnClose);
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("input int    InpSessionStartHour = 0;"));
+        assert!(mql5.contains("(long)(currentBarTime - InpSessionStartHour * 3600) / 86400)\n      CloseAllPositions();"));
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("i_session_start_hour = input.int(0, \"Session Start Hour\")"));
+        assert!(pine.contains("if sessionEnd and strategy.position_size != 0\n    strategy.close_all(comment=\"Session Close\")"));
+    }
+
+    #[test]
+    fn test_operand_offset_matches_engine_convention() {
+        // Engine: offset N reads N bars before the signal bar. MQL5 evaluates on the
+        // new bar, so the signal bar is shift 1; Pine evaluates on the signal bar itself.
+        let mut strategy = simple_strategy();
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.comparator = Comparator::GreaterThan;
+        rule.left_operand.offset = Some(2);
+        rule.right_operand = Operand {
+            operand_type: OperandType::Price,
+            price_field: Some(PriceField::DailyOpen),
+            indicator: None,
+            offset: Some(3),
+            ..rule.left_operand.clone()
+        };
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("iClose(_Symbol, PERIOD_CURRENT, 3) > iOpen(_Symbol, PERIOD_D1, iBarShift(_Symbol, PERIOD_D1, iTime(_Symbol, PERIOD_CURRENT, 4)))"));
+
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("close[2] > request.security(syminfo.tickerid, \"D\", open)[3]"));
+
+        // Daily close is the previous day's close in the engine
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.right_operand.price_field = Some(PriceField::DailyClose);
+        rule.right_operand.offset = None;
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("iClose(_Symbol, PERIOD_D1, 1)"));
+    }
+
+    #[test]
+    fn test_step_trailing_stop_generation() {
+        let mut strategy = simple_strategy();
+        strategy.trailing_stop = Some(TrailingStop {
+            ts_type: TrailingStopType::Step,
+            value: 15.0,
+            atr_period: None,
+            activation_pips: None,
+            step_pips: Some(10.0),
+        });
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains("input double InpTSPips = 15.0;"));
+        assert!(mql5.contains("input double InpTSStepPips = 10.0;"));
+        assert!(mql5.contains("double newSL = entryPrice - trailDist + steps * trailStep;"));
+        assert!(mql5.contains("double PipSize()"));
+
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("i_ts_step_pips = input.float(10.0"));
+        assert!(pine.contains("math.floor((tsHigh - strategy.position_avg_price) / tsStep)) * tsStep"));
+        assert!(pine.contains("stop=math.max(strategy.position_avg_price - slDist, nz(longStepStop"));
+        assert!(!pine.contains("trail_offset"));
+
+        // Without an SL the stepped level is the only stop
+        strategy.stop_loss = None;
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("stop=longStepStop"));
+    }
+
+    #[test]
+    fn test_risk_reward_tp_matches_engine_with_and_without_sl() {
+        use crate::engine::position::calculate_take_profit;
+        use crate::models::config::InstrumentConfig;
+
+        let instrument = InstrumentConfig::default();
+        let entry = 1.1000;
+        let tp = TakeProfit { tp_type: TakeProfitType::RiskReward, value: 2.0, atr_period: None };
+        let mut strategy = simple_strategy();
+        strategy.take_profit = Some(tp.clone());
+
+        // With a 50-pip SL: Pine's slDist * i_tp_rr = 100 pips, as in the engine
+        let sl_pips = strategy.stop_loss.as_ref().unwrap().value;
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("slDist = i_sl_pips * pipSize"));
+        assert!(pine.contains("tpDist = slDist * i_tp_rr"));
+        let engine_tp = calculate_take_profit(&tp, entry, Some(entry - sl_pips * instrument.pip_size), TradeDirection::Long, None, &instrument);
+        assert!(((engine_tp - entry) - sl_pips * tp.value * instrument.pip_size).abs() < 1e-9);
+
+        // Without SL: both scale the fixed fallback risk
+        strategy.stop_loss = None;
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(pine.contains("pipSize = "));
+        assert!(pine.contains(&format!("tpDist = i_tp_rr * {:.1} * pipSize", RR_FALLBACK_RISK_PIPS)));
+        assert!(!pine.contains("close * 0.02"));
+        let engine_tp = calculate_take_profit(&tp, entry, None, TradeDirection::Long, None, &instrument);
+        assert!(((engine_tp - entry) - tp.value * RR_FALLBACK_RISK_PIPS * instrument.pip_size).abs() < 1e-9);
+
+        let mql5 = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).to_string();
+        assert!(mql5.contains(&format!("double tpDist = InpTPRR * {:.1} * PipSize();", RR_FALLBACK_RISK_PIPS)));
+    }
+
+    #[test]
+    fn test_codegen_uses_backtest_capital_and_leverage() {
+        let strategy = simple_strategy();
+        let config = BacktestConfig {
+            symbol_id: "sym".into(),
+            timeframe: crate::models::config::Timeframe::H1,
+            start_date: "2024-01-01".into(),
+            end_date: "2024-12-31".into(),
+            initial_capital: 25_000.0,
+            leverage: 50.0,
+            precision: BacktestPrecision::SelectedTfOnly,
+            early_stop_no_trades_pct: None,
+            pending_order_expiry_bars: None,
+            use_heikin_ashi: false,
+            equity_sampling: None,
+            tick_chunk_bars: None,
+            compounding: true,
+            stop_out_equity: None,
+            magnifier_timeframe: None,
+            account_currency: None,
+            quote_conversion: None,
+            warmup_bars: None,
+            exit_priority: ExitPriority::default(),
+            fallback_on_missing_raw: false,
+            drawdown_basis: DrawdownBasis::default(),
+            overtrading_threshold: None,
+            regime: None,
+            slippage_seed: None,
+        };
+        let account = AccountSettings::from(&config);
+
+        let pine = main_code(&generate_pinescript(&strategy, &account, None, false).unwrap()).to_string();
+        assert!(pine.contains("initial_capital=25000,"));
+        assert!(pine.contains("margin_long=2, margin_short=2"));
+        assert!(!pine.contains("initial_capital=10000"));
+
+        let mql5 = main_code(&generate_mql5(&strategy, &account, None).unwrap()).to_string();
+        assert!(mql5.contains("// Backtest initial deposit : 25000"));
+        assert!(mql5.contains("// Backtest leverage        : 1:50"));
+
+        let default_pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(default_pine.contains("initial_capital=10000,") && default_pine.contains("margin_long=100"));
     }
 
     #[test]
     fn test_mql5_generation() {
         let strategy = simple_strategy();
-        let result = generate_mql5(&strategy).unwrap();
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
         let code = main_code(&result);
 
         assert!(code.contains("SMA Cross Test.mq5"));
@@ -8428,7 +10694,7 @@ mod tests {
     #[test]
     fn test_pinescript_generation() {
         let strategy = simple_strategy();
-        let result = generate_pinescript(&strategy).unwrap();
+        let result = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
         let code = main_code(&result);
 
         assert!(code.contains("//@version=6"));
@@ -8442,18 +10708,222 @@ mod tests {
         assert_eq!(result.files.len(), 1);
     }
 
+    #[test]
+    fn test_pine_alerts_carry_direction_and_price() {
+        let mut strategy = simple_strategy();
+        strategy.trade_direction = TradeDirection::Both;
+
+        let plain = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(!plain.contains("alert("));
+
+        let pine = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, true).unwrap()).to_string();
+        assert!(pine.contains(r#""direction": "{{direction}}""#));
+        assert!(pine.contains(r#""price": {{price}}"#));
+        assert!(pine.contains(r#"str.replace_all(msg, "{{price}}", str.tostring(close, format.mintick))"#));
+        assert!(pine.contains("alert(msg, alert.freq_once_per_bar_close)"));
+        for call in [
+            r#"sendAlert("long", "entry")"#,
+            r#"sendAlert("short", "entry")"#,
+            r#"sendAlert("long", "exit")"#,
+            r#"sendAlert("short", "exit")"#,
+        ] {
+            assert!(pine.contains(call), "missing {}", call);
+        }
+        // The alert fires inside the entry block, right after the order
+        assert!(pine.contains("    strategy.entry(\"Long\", strategy.long)\n    sendAlert(\"long\", \"entry\")"));
+    }
+
     #[test]
     fn test_empty_rules() {
         let mut strategy = simple_strategy();
         strategy.long_entry_rules.clear();
 
-        let mql5 = generate_mql5(&strategy).unwrap();
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
         assert!(main_code(&mql5).contains("WARNING: No rules defined"));
 
-        let pine = generate_pinescript(&strategy).unwrap();
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
         assert!(main_code(&pine).contains("WARNING: No long entry rules defined"));
     }
 
+    #[test]
+    fn test_parenthesized_rules() {
+        let mut strategy = simple_strategy();
+        // (r1 OR r2) AND r3
+        let mut r3 = strategy.long_entry_rules[1].clone();
+        r3.id = "r3".into();
+        strategy.long_entry_rules[0].logical_operator = Some(LogicalOperator::Or);
+        strategy.long_entry_rules[0].open_parens = 1;
+        strategy.long_entry_rules[1].logical_operator = Some(LogicalOperator::And);
+        strategy.long_entry_rules[1].close_parens = 1;
+        strategy.long_entry_rules.push(r3);
+
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("return (rule1 || rule2) && rule3;"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("longEntry = (ta.crossover(close, sma_20) or rsi_14 > 50.0) and rsi_14 > 50.0"));
+
+        // A OR (B AND C)
+        let rules = &mut strategy.long_entry_rules;
+        rules[0].open_parens = 0;
+        rules[1].open_parens = 1;
+        rules[1].close_parens = 0;
+        rules[2].close_parens = 1;
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("return rule1 || (rule2 && rule3);"));
+    }
+
+    #[test]
+    fn test_percent_of_price_constant() {
+        let mut strategy = simple_strategy();
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.comparator = Comparator::GreaterThan;
+        rule.left_operand.operand_type = OperandType::Price;
+        rule.left_operand.indicator = None;
+        rule.left_operand.price_field = Some(PriceField::Close);
+        rule.right_operand = Operand {
+            operand_type: OperandType::Constant,
+            indicator: None,
+            price_field: None,
+            constant_value: Some(0.5),
+            time_field: None,
+            candle_pattern: None,
+            offset: None,
+            compound_left: None,
+            compound_op: None,
+            compound_right: None,
+            percent_of: Some(PriceField::Open),
+            transform: None,
+        };
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("close > open * 1.005"), "{}", main_code(&pine));
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("iOpen(_Symbol, PERIOD_CURRENT, 1) * 1.005"));
+
+        // The literal parses back to the engine's multiplier, float noise included
+        let mut operand = strategy.long_entry_rules[0].right_operand.clone();
+        for pct in [0.5, 1.0 / 3.0, 0.07, -2.5, 12.345] {
+            operand.constant_value = Some(pct);
+            let literal = percent_factor(&operand);
+            assert_eq!(literal.parse::<f64>().unwrap(), 1.0 + pct / 100.0, "{}", literal);
+        }
+    }
+
+    #[test]
+    fn test_constants_keep_full_precision() {
+        let mut strategy = simple_strategy();
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.comparator = Comparator::GreaterThan;
+        rule.left_operand.operand_type = OperandType::Price;
+        rule.left_operand.indicator = None;
+        rule.left_operand.price_field = Some(PriceField::Close);
+        rule.right_operand.operand_type = OperandType::Constant;
+        rule.right_operand.indicator = None;
+        rule.right_operand.constant_value = Some(0.00123);
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("close > 0.00123"), "{}", main_code(&pine));
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains(" > 0.00123"), "{}", main_code(&mql5));
+        assert!(!main_code(&mql5).contains(" > 0.0)"));
+
+        assert_eq!(float_literal(0.00123), "0.00123");
+        assert_eq!(float_literal(1.0000000000000002), "1.0000000000000002");
+        assert_eq!(float_literal(-2.0), "-2.0");
+        assert_eq!(float_literal(2_500_000.0), "2500000.0");
+        assert_eq!(float_literal(1.5e-7), "1.5e-7");
+        assert_eq!(float_literal(1.5e-7).parse::<f64>().unwrap(), 1.5e-7);
+    }
+
+    #[test]
+    fn test_ema_seed_passed_to_custom_indicators() {
+        let mut strategy = simple_strategy();
+        let ind = strategy.long_entry_rules[0].right_operand.indicator.as_mut().unwrap();
+        ind.indicator_type = IndicatorType::EMA;
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("\"BT_EMA\", Inp_ema_20_period, 0)"), "{}", main_code(&mql5));
+
+        let ind = strategy.long_entry_rules[0].right_operand.indicator.as_mut().unwrap();
+        ind.params.ema_seed = Some(EmaSeed::FirstValue);
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("\"BT_EMA\", Inp_ema_20_fv_period, 1)"), "{}", main_code(&mql5));
+        let ema_file = mql5.files.iter().find(|f| f.filename == "BT_EMA.mq5").unwrap();
+        assert!(ema_file.code.contains("input int InpSeed = 0;"));
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("ema_20_fv = ta.ema(close, i_ema_20_fv_period)"), "{}", main_code(&pine));
+    }
+
+    #[test]
+    fn test_ppo_honours_ema_seed() {
+        let params = IndicatorParams { fast_period: Some(12), slow_period: Some(26), signal_period: Some(9), ema_seed: Some(EmaSeed::FirstValue), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::PPO, params, "");
+        let var = indicator_var_name(strategy.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains(&format!("\"BT_PPO\", Inp_{var}_fast, Inp_{var}_slow, Inp_{var}_signal, 1)")), "{}", main_code(&mql5));
+
+        let file = mql5.files.iter().find(|f| f.filename == "BT_PPO.mq5").unwrap();
+        assert!(file.code.contains("input int InpSeed         = 0;"));
+        assert!(file.code.contains("SeededEma(ExtPpo, ExtSignal, first, InpSignalPeriod, rates_total, firstValue);"));
+        // The shared helper is emitted once per file
+        assert_eq!(file.code.matches("void SeededEma(").count(), 1);
+    }
+
+    #[test]
+    fn test_equal_rule_emits_tolerance_comparison() {
+        let mut strategy = simple_strategy();
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.comparator = Comparator::Equal;
+        rule.left_operand.operand_type = OperandType::Price;
+        rule.left_operand.indicator = None;
+        rule.left_operand.price_field = Some(PriceField::Close);
+        rule.right_operand.operand_type = OperandType::Constant;
+        rule.right_operand.indicator = None;
+        rule.right_operand.constant_value = Some(1.1);
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(
+            main_code(&pine).contains("(math.abs(close - 1.1) <= 1e-9 * math.max(1.0, math.max(math.abs(close), math.abs(1.1))))"),
+            "{}",
+            main_code(&pine)
+        );
+        assert!(!main_code(&pine).contains("close == 1.1"));
+
+        strategy.long_entry_rules[0].tolerance = Some(0.0005);
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("(math.abs(close - 1.1) <= 0.0005)"), "{}", main_code(&pine));
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains(" - 1.1) <= 0.0005)"), "{}", main_code(&mql5));
+        assert!(main_code(&mql5).contains("(MathAbs("));
+    }
+
+    #[test]
+    fn test_operand_transforms_in_pine() {
+        let mut strategy = simple_strategy();
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.comparator = Comparator::GreaterThan;
+        rule.left_operand.operand_type = OperandType::Price;
+        rule.left_operand.indicator = None;
+        rule.left_operand.price_field = Some(PriceField::Close);
+        rule.left_operand.offset = Some(1);
+        rule.left_operand.transform = Some(OperandTransform::PercentRank { period: 50 });
+        rule.right_operand.operand_type = OperandType::Constant;
+        rule.right_operand.indicator = None;
+        rule.right_operand.constant_value = Some(90.0);
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("ta.percentrank(close[1], 50) > 90.0"), "{}", main_code(&pine));
+
+        strategy.long_entry_rules[0].left_operand.transform = Some(OperandTransform::ZScore { period: 20 });
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("((close[1]) - ta.sma(close[1], 20)) / ta.stdev(close[1], 20)"));
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains("operand transform not supported in MQL5"));
+        assert_eq!(mql5.warnings.len(), 1);
+        assert!(mql5.warnings[0].starts_with("1 rule operand(s) use a percent-rank or z-score transform"));
+        assert!(generate_mql5(&simple_strategy(), &AccountSettings::default(), None).unwrap().warnings.is_empty());
+    }
+
     #[test]
     fn test_macd_multi_output() {
         let mut strategy = simple_strategy();
@@ -8480,6 +10950,8 @@ mod tests {
                 compound_left: None,
                 compound_op: None,
                 compound_right: None,
+                percent_of: None,
+                transform: None,
             },
             comparator: Comparator::GreaterThan,
             right_operand: Operand {
@@ -8493,26 +10965,430 @@ mod tests {
                 compound_left: None,
                 compound_op: None,
                 compound_right: None,
+                percent_of: None,
+                transform: None,
             },
             logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
         }];
 
-        let result = generate_mql5(&strategy).unwrap();
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
         let code = main_code(&result);
         assert!(code.contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_MACD\""));
         assert!(code.contains("_signal"));
         // MACD custom indicator file should be generated
         assert!(result.files.iter().any(|f| f.filename == "BT_MACD.mq5"));
 
-        let pine = generate_pinescript(&strategy).unwrap();
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
         assert!(main_code(&pine).contains("ta.macd"));
         assert!(main_code(&pine).contains("macd_f12_s26_sig9_signal"));
     }
 
+    /// `close > <channel>.<field>` long entry on a band indicator.
+    fn channel_breakout_strategy(indicator_type: IndicatorType, params: IndicatorParams, field: &str) -> Strategy {
+        let mut strategy = simple_strategy();
+        strategy.long_entry_rules = vec![Rule {
+            id: "r1".into(),
+            left_operand: Operand {
+                operand_type: OperandType::Price,
+                indicator: None,
+                price_field: Some(PriceField::Close),
+                constant_value: None,
+                time_field: None,
+                candle_pattern: None,
+                offset: None,
+                compound_left: None,
+                compound_op: None,
+                compound_right: None,
+                percent_of: None,
+                transform: None,
+            },
+            comparator: Comparator::GreaterThan,
+            right_operand: Operand {
+                operand_type: OperandType::Indicator,
+                indicator: Some(IndicatorConfig {
+                    indicator_type,
+                    params,
+                    output_field: Some(field.into()),
+                    cached_hash: 0,
+                }),
+                price_field: None,
+                constant_value: None,
+                time_field: None,
+                candle_pattern: None,
+                offset: None,
+                compound_left: None,
+                compound_op: None,
+                compound_right: None,
+                percent_of: None,
+                transform: None,
+            },
+            logical_operator: None,
+            open_parens: 0,
+            close_parens: 0,
+            tolerance: None,
+        }];
+        strategy
+    }
+
+    #[test]
+    fn test_keltner_channel_generation() {
+        let params = IndicatorParams { period: Some(20), multiplier: Some(2.0), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::KeltnerChannel, params, "upper");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("input double Inp_kc_20_m2_mult = 2.00;"));
+        assert!(code.contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_KeltnerChannel\", Inp_kc_20_m2_period, Inp_kc_20_m2_mult)"));
+        // Upper band is engine output 1 → buffer 1 of BT_KeltnerChannel
+        assert!(code.contains("CopyBuffer(handle_kc_20_m2, 1, 0, 5, kc_20_m2_upper)"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_KeltnerChannel.mq5").unwrap();
+        assert!(file.code.contains("SetIndexBuffer(1, ExtUpper, INDICATOR_DATA)"));
+        assert!(file.code.contains("(high[j] + low[j] + close[j]) / 3.0"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("kc_20_m2_middle = ta.sma(hlc3, i_kc_20_m2_period)"));
+        assert!(code.contains("kc_20_m2_range = ta.sma(high - low, i_kc_20_m2_period)"));
+        assert!(code.contains("close > kc_20_m2_upper"), "{}", code);
+    }
+
+    #[test]
+    fn test_vortex_buffer_suffixes() {
+        // VI+ crossing above VI- reads both buffers of one BT_Vortex handle
+        let params = IndicatorParams { period: Some(14), ..Default::default() };
+        let mut strategy = channel_breakout_strategy(IndicatorType::Vortex, params.clone(), "vi_minus");
+        let rule = &mut strategy.long_entry_rules[0];
+        rule.left_operand.operand_type = OperandType::Indicator;
+        rule.left_operand.price_field = None;
+        rule.left_operand.indicator = Some(IndicatorConfig {
+            indicator_type: IndicatorType::Vortex,
+            params,
+            output_field: Some("vi_plus".into()),
+            cached_hash: 0,
+        });
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert_eq!(code.matches("iCustom(_Symbol, PERIOD_CURRENT, \"BT_Vortex\", Inp_vortex_14_period)").count(), 1);
+        assert!(code.contains("CopyBuffer(handle_vortex_14, 0, 0, 5, vortex_14_plus)"), "{}", code);
+        assert!(code.contains("CopyBuffer(handle_vortex_14, 1, 0, 5, vortex_14_minus)"), "{}", code);
+        assert!(code.contains("vortex_14_plus[1] > vortex_14_minus[1]"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_Vortex.mq5").unwrap();
+        assert!(file.code.contains("fillu[i] = valp[i];") && file.code.contains("filld[i] = valm[i];"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("vortex_14_plus = ta.sum(vortex_14_vm_plus, i_vortex_14_period)"), "{}", code);
+        assert!(code.contains("vortex_14_minus = ta.sum(vortex_14_vm_minus, i_vortex_14_period)"), "{}", code);
+        assert!(code.contains("vortex_14_plus > vortex_14_minus"), "{}", code);
+    }
+
+    #[test]
+    fn test_ichimoku_cloud_rules() {
+        // Tenkan crossing above Kijun, and close above the cloud, from one Ichimoku handle
+        let params = IndicatorParams { fast_period: Some(9), slow_period: Some(26), signal_period: Some(52), ..Default::default() };
+        let ichimoku = |field: &str| Operand {
+            operand_type: OperandType::Indicator,
+            price_field: None,
+            indicator: Some(IndicatorConfig {
+                indicator_type: IndicatorType::Ichimoku,
+                params: params.clone(),
+                output_field: Some(field.into()),
+                cached_hash: 0,
+            }),
+            ..simple_strategy().long_entry_rules[0].left_operand.clone()
+        };
+        let mut strategy = channel_breakout_strategy(IndicatorType::Ichimoku, params.clone(), "cloud_top");
+        let mut cross = strategy.long_entry_rules[0].clone();
+        cross.left_operand = ichimoku("tenkan");
+        cross.comparator = Comparator::CrossAbove;
+        cross.right_operand = ichimoku("kijun");
+        strategy.long_entry_rules.push(cross);
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        let var = indicator_var_name(strategy.long_entry_rules[1].left_operand.indicator.as_ref().unwrap());
+        for (buf, suffix) in [(0, "tenkan"), (1, "kijun"), (5, "cloud_top")] {
+            let copy = format!("CopyBuffer(handle_{0}, {1}, 0, 5, {0}_{2})", var, buf, suffix);
+            assert!(code.contains(&copy), "{}\n{}", copy, code);
+        }
+        assert!(code.contains(&format!("iClose(_Symbol, PERIOD_CURRENT, 1) > {}_cloud_top[1]", var)), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_Ichimoku.mq5").unwrap();
+        assert!(file.code.contains("SetIndexBuffer(5, ExtCloudTop, INDICATOR_CALCULATIONS)"));
+        assert!(file.code.contains("ExtChikou[i] = close[i];"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains(&format!("{0}_senkou_a = {0}_span_a_raw[i_{0}_kijun]", var)), "{}", code);
+        assert!(code.contains(&format!("close > {}_cloud_top", var)), "{}", code);
+        assert!(code.contains(&format!("ta.crossover({0}_tenkan, {0}_kijun)", var)), "{}", code);
+    }
+
+    #[test]
+    fn test_klinger_generation() {
+        let params = IndicatorParams { fast_period: Some(34), slow_period: Some(55), signal_period: Some(13), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::Klinger, params, "signal");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_Klinger\", Inp_kvo_f34_s55_sig13_fast, Inp_kvo_f34_s55_sig13_slow, Inp_kvo_f34_s55_sig13_signal)"), "{}", code);
+        assert!(code.contains("CopyBuffer(handle_kvo_f34_s55_sig13, 1, 0, 5, kvo_f34_s55_sig13_signal)"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_Klinger.mq5").unwrap();
+        assert!(file.code.contains("SeededEma(ExtKvo, ExtSignal, first, InpSignal, rates_total);"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("kvo_f34_s55_sig13_signal = ta.ema(kvo_f34_s55_sig13_line, i_kvo_f34_s55_sig13_signal)"), "{}", code);
+    }
+
+    #[test]
+    fn test_awesome_oscillator_and_momentum_generation() {
+        let ao = channel_breakout_strategy(IndicatorType::AwesomeOscillator, IndicatorParams::default(), "");
+        let var = indicator_var_name(ao.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+        let pine = generate_pinescript(&ao, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains(&format!("{var} = ta.sma(hl2, 5) - ta.sma(hl2, 34)")), "{}", main_code(&pine));
+        let result = generate_mql5(&ao, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&result).contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_AwesomeOscillator\")"));
+        let file = result.files.iter().find(|f| f.filename == "BT_AwesomeOscillator.mq5").unwrap();
+        assert!(file.code.contains("ExtBuffer[i] = sum5 / 5.0 - sum34 / 34.0;"));
+
+        let params = IndicatorParams { period: Some(10), ..Default::default() };
+        let mom = channel_breakout_strategy(IndicatorType::Momentum, params, "");
+        let var = indicator_var_name(mom.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+        let pine = generate_pinescript(&mom, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains(&format!("{var} = close - close[i_{var}_period]")), "{}", main_code(&pine));
+        let result = generate_mql5(&mom, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&result).contains(&format!("\"BT_Momentum\", Inp_{var}_period)")));
+        let file = result.files.iter().find(|f| f.filename == "BT_Momentum.mq5").unwrap();
+        assert!(file.code.contains("ExtBuffer[i] = close[i] - close[i - InpPeriod];"));
+    }
+
+    #[test]
+    fn test_schaff_trend_cycle_smoothing_input() {
+        let params = IndicatorParams { period: Some(10), fast_period: Some(23), slow_period: Some(50), slowing: Some(5), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::SchaffTrendCycle, params, "");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        let var = "stc_10_f23_s50";
+        assert!(code.contains(&format!("input int    Inp_{var}_smooth = 5;")), "{}", code);
+        assert!(code.contains(&format!("Inp_{var}_slow, Inp_{var}_smooth)")), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_SchaffTrendCycle.mq5").unwrap();
+        assert!(file.code.contains("input double    SmoothPeriod = 3;"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains(&format!("i_{var}_smooth = input.int(5, \"STC Smoothing\")")), "{}", code);
+        assert!(code.contains(&format!("{var}_alpha = 2.0 / (1.0 + i_{var}_smooth)")), "{}", code);
+    }
+
+    #[test]
+    fn test_stochastic_rsi_slowing_input() {
+        let params = IndicatorParams { period: Some(14), k_period: Some(14), d_period: Some(3), slowing: Some(5), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::StochasticRSI, params, "");
+        let var = indicator_var_name(strategy.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains(&format!("input int    Inp_{var}_slowing = 5;")), "{}", code);
+        assert!(code.contains(&format!("Inp_{var}_d, Inp_{var}_slowing)")), "{}", code);
+        assert!(!code.contains(&format!("Inp_{var}_d, 3)")), "{}", code);
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains(&format!("i_{var}_slowing = input.int(5, \"StochRSI Slowing\")")), "{}", code);
+        assert!(code.contains(&format!("{var}_k = ta.sma(ta.stoch({var}_rsi, {var}_rsi, {var}_rsi, i_{var}_k), i_{var}_slowing)")), "{}", code);
+    }
+
+    #[test]
+    fn test_trix_without_signal_period_has_no_signal_line() {
+        let params = IndicatorParams { period: Some(15), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::TRIX, params, "");
+        let var = indicator_var_name(strategy.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains(&format!("input int    Inp_{var}_signal = 0;")), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_TRIX.mq5").unwrap();
+        assert!(file.code.contains("if(InpSignal > 0) SeededEma(ExtTrix, ExtSignal, first, InpSignal, rates_total);"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(!code.contains(&format!("i_{var}_signal")), "{}", code);
+        assert!(code.contains(&format!("float {var}_signal = na")), "{}", code);
+
+        let params = IndicatorParams { period: Some(15), signal_period: Some(7), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::TRIX, params, "");
+        let var = indicator_var_name(strategy.long_entry_rules[0].right_operand.indicator.as_ref().unwrap());
+        let code = main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap());
+        assert!(code.contains(&format!("input int    Inp_{var}_signal = 7;")), "{}", code);
+        let code = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap());
+        assert!(code.contains(&format!("{var}_signal = ta.ema({var}_line, i_{var}_signal)")), "{}", code);
+    }
+
+    #[test]
+    fn test_pine_supertrend_follows_atr_smoothing() {
+        let params = IndicatorParams { period: Some(10), multiplier: Some(3.0), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::SuperTrend, params.clone(), "");
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains("[st_10_m3, st_10_m3_dir] = ta.supertrend(i_st_10_m3_mult, i_st_10_m3_period)"));
+
+        let params = IndicatorParams { atr_smoothing: Some(AtrSmoothing::Sma), ..params };
+        let strategy = channel_breakout_strategy(IndicatorType::SuperTrend, params, "");
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        let var = "st_10_m3_sma";
+        assert!(!code.contains("ta.supertrend("), "{}", code);
+        assert!(code.contains(&format!("{var}_atr = ta.sma(ta.tr(true), i_{var}_period)")), "{}", code);
+        assert!(code.contains(&format!("{var}_up = hl2 + i_{var}_mult * {var}_atr")), "{}", code);
+        assert!(code.contains(&format!("{var} := {var}_dir == -1 ? {var}_dn : {var}_up")), "{}", code);
+    }
+
+    #[test]
+    fn test_mass_index_generation() {
+        let params = IndicatorParams { period: Some(25), fast_period: Some(9), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::MassIndex, params, "");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("input int    Inp_mass_25_f9_ema = 9;"), "{}", code);
+        assert!(code.contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_MassIndex\", Inp_mass_25_f9_period, Inp_mass_25_f9_ema)"));
+        assert!(result.files.iter().any(|f| f.filename == "BT_MassIndex.mq5"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("mass_25_f9 = math.sum(mass_25_f9_double != 0 ? mass_25_f9_single / mass_25_f9_double : 1.0, i_mass_25_f9_period)"), "{}", code);
+    }
+
+    #[test]
+    fn test_donchian_channel_generation() {
+        let params = IndicatorParams { period: Some(55), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::DonchianChannel, params, "lower");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("iCustom(_Symbol, PERIOD_CURRENT, \"BT_DonchianChannel\", Inp_dc_55_period)"));
+        assert!(code.contains("CopyBuffer(handle_dc_55, 2, 0, 5, dc_55_lower)"), "{}", code);
+        assert!(result.files.iter().any(|f| f.filename == "BT_DonchianChannel.mq5"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("dc_55_upper = ta.highest(high, i_dc_55_period)"));
+        assert!(code.contains("dc_55_lower = ta.lowest(low, i_dc_55_period)"));
+        assert!(code.contains("close > dc_55_lower"), "{}", code);
+    }
+
+    #[test]
+    fn test_linear_regression_channel_generation() {
+        let params = IndicatorParams { period: Some(20), std_dev: Some(1.5), ..Default::default() };
+        let mut strategy = channel_breakout_strategy(IndicatorType::LinearRegression, params.clone(), "upper");
+        // Second rule: slope > 0
+        let mut slope_rule = strategy.long_entry_rules[0].clone();
+        slope_rule.right_operand = Operand {
+            operand_type: OperandType::Constant,
+            price_field: None,
+            constant_value: Some(0.0),
+            ..slope_rule.left_operand.clone()
+        };
+        slope_rule.left_operand = strategy.long_entry_rules[0].right_operand.clone();
+        slope_rule.left_operand.indicator.as_mut().unwrap().output_field = Some("slope".into());
+        strategy.long_entry_rules[0].logical_operator = Some(LogicalOperator::And);
+        strategy.long_entry_rules.push(slope_rule);
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("input double Inp_linreg_20_sd1p5_stddev = 1.5;"), "{}", code);
+        assert!(code.contains("\"BT_LinearRegression\", Inp_linreg_20_sd1p5_period, PRICE_CLOSE, Inp_linreg_20_sd1p5_stddev)"), "{}", code);
+        assert!(code.contains("CopyBuffer(handle_linreg_20_sd1p5, 1, 0, 5, linreg_20_sd1p5_upper)"), "{}", code);
+        assert!(code.contains("CopyBuffer(handle_linreg_20_sd1p5, 3, 0, 5, linreg_20_sd1p5_slope)"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_LinearRegression.mq5").unwrap();
+        assert!(file.code.contains("SetIndexBuffer(3, slope_buffer, INDICATOR_CALCULATIONS);"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("linreg_20_sd1p5_slope = linreg_20_sd1p5 - ta.linreg(close, i_linreg_20_sd1p5_period, 1)"), "{}", code);
+        assert!(code.contains("linreg_20_sd1p5_upper = linreg_20_sd1p5 + i_linreg_20_sd1p5_stddev * linreg_20_sd1p5_dev"), "{}", code);
+        assert!(code.contains("close > linreg_20_sd1p5_upper"), "{}", code);
+        assert!(code.contains("linreg_20_sd1p5_slope > 0.0"), "{}", code);
+    }
+
+    #[test]
+    fn test_pivot_type_generation() {
+        let params = IndicatorParams { pivot_type: Some(PivotType::Camarilla), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::Pivots, params, "r3");
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("handle_pivots_cam = iCustom(_Symbol, PERIOD_CURRENT, \"BT_Pivots\", 2);"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_Pivots.mq5").unwrap();
+        assert!(file.code.contains("input int       InpPivotType=0;"));
+        assert!(file.code.contains("R3 = PreviousClose + Range * 1.1 / 4;"));
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("pivots_cam_r3 = pivots_cam_pc + pivots_cam_rng * 1.1 / 4"), "{}", code);
+        assert!(code.contains("close > pivots_cam_r3"), "{}", code);
+
+        // Classic keeps the unsuffixed name and the classic R1
+        let strategy = channel_breakout_strategy(IndicatorType::Pivots, IndicatorParams::default(), "r1");
+        let code = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(code.contains("pivots_r1 = 2 * pivots_pp - pivots_pl"), "{}", code);
+    }
+
+    #[test]
+    fn test_weighted_moving_average_generation() {
+        let params = IndicatorParams { period: Some(16), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::HullMA, params.clone(), "value");
+
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        let code = main_code(&pine);
+        assert!(code.contains("hma_16 = ta.hma(close, i_hma_16_period)"), "{}", code);
+        assert!(code.contains("i_hma_16_period = input.int(16,"), "{}", code);
+
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        let code = main_code(&result);
+        assert!(code.contains("handle_hma_16 = iCustom(_Symbol, PERIOD_CURRENT, \"BT_HullMA\", Inp_hma_16_period);"), "{}", code);
+        let file = result.files.iter().find(|f| f.filename == "BT_HullMA.mq5").unwrap();
+        assert!(file.code.contains("int half = MathMax(InpPeriod / 2, 1);"));
+        assert!(file.code.contains("int sqrtP = MathMax((int)MathSqrt(InpPeriod), 1);"));
+        assert!(file.code.contains("ExtBuffer[i] = Wma(ExtDiff, i, sqrtP);"));
+
+        let strategy = channel_breakout_strategy(IndicatorType::WMA, params, "value");
+        let code = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(code.contains("wma_16 = ta.wma(close, i_wma_16_period)"), "{}", code);
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(result.files.iter().any(|f| f.filename == "BT_WMA.mq5"));
+
+        // KAMA is computed manually rather than approximated with an EMA
+        let params = IndicatorParams { period: Some(10), fast_period: Some(2), slow_period: Some(30), ..Default::default() };
+        let strategy = channel_breakout_strategy(IndicatorType::KAMA, params, "value");
+        let code = main_code(&generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap()).to_string();
+        assert!(code.contains("i_kama_10_f2_s30_slow = input.int(30,"), "{}", code);
+        assert!(code.contains("kama_10_f2_s30 := bar_index == i_kama_10_f2_s30_period - 1 ? close"), "{}", code);
+        assert!(!code.contains("kama_10_f2_s30 = ta.ema"), "{}", code);
+    }
+
+    #[test]
+    fn test_hold_limits_noted_in_generated_code() {
+        let mut strategy = simple_strategy();
+        let note = "// NOTE: Backtester hold limits (exit rules ignored for the first 3 bars, positions force-closed after 20 bars) are not replicated here";
+        assert!(!main_code(&generate_mql5(&strategy, &AccountSettings::default(), None).unwrap()).contains("hold limits"));
+
+        strategy.min_hold_bars = Some(3);
+        strategy.max_hold_bars = Some(20);
+        let mql5 = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        assert!(main_code(&mql5).contains(note), "{}", main_code(&mql5));
+        let pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        assert!(main_code(&pine).contains(note), "{}", main_code(&pine));
+    }
+
     #[test]
     fn test_custom_indicator_files_content() {
         let strategy = simple_strategy();
-        let result = generate_mql5(&strategy).unwrap();
+        let result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
 
         // Check SMA indicator file content
         let sma_file = result.files.iter().find(|f| f.filename == "BT_SMA.mq5").unwrap();
@@ -8525,6 +11401,129 @@ mod tests {
         let rsi_file = result.files.iter().find(|f| f.filename == "BT_RSI.mq5").unwrap();
         assert!(rsi_file.code.contains("OnCalculate"));
         assert!(rsi_file.code.contains("Wilder"));
+
+        // Sample standard deviation refuses a single-bar window instead of dividing by zero
+        let bollinger = gen_mql5_bollinger();
+        assert!(bollinger.contains("if(InpSampleStdDev && InpPeriod < 2)\n      return INIT_PARAMETERS_INCORRECT;"));
+        assert!(bollinger.contains("variance /= (InpSampleStdDev ? InpPeriod - 1 : InpPeriod);"));
+    }
+
+    #[test]
+    fn test_verification_samples_match_engine_indicator() {
+        let candles: Vec<Candle> = (0..40)
+            .map(|i| Candle {
+                datetime: format!("2024-01-02 {:02}:{:02}", i / 4, (i % 4) * 15),
+                open: 1.1 + (i as f64 * 0.7).sin() * 0.01,
+                high: 1.12 + (i as f64 * 0.7).sin() * 0.01,
+                low: 1.08 + (i as f64 * 0.7).sin() * 0.01,
+                close: 1.1 + (i as f64 * 0.3).cos() * 0.01,
+                volume: 100.0,
+                ..Default::default()
+            })
+            .collect();
+        let strategy = simple_strategy();
+        let mut result = generate_mql5(&strategy, &AccountSettings::default(), None).unwrap();
+        embed_verification_samples(&mut result, &strategy, &candles).unwrap();
+        let code = main_code(&result);
+
+        let first = candles.len() - VERIFICATION_SAMPLE_BARS;
+        for ind in collect_unique_indicators(&strategy) {
+            let engine = compute_indicator(&ind.config, &candles).unwrap();
+            for (candle, value) in candles[first..].iter().zip(&engine.primary[first..]) {
+                let line = format!("// {} {}_buf = {:.8}", candle.datetime, ind.var_name, value);
+                assert!(code.contains(&line), "missing sample line: {}", line);
+            }
+        }
+        // Earlier bars are not embedded
+        assert!(!code.contains(&format!("// {} ", candles[0].datetime)));
+
+        // Multi-output indicators are labelled with the generated variable of each output
+        let mut strategy = simple_strategy();
+        let macd = IndicatorConfig {
+            indicator_type: IndicatorType::MACD,
+            params: IndicatorParams { fast_period: Some(5), slow_period: Some(10), signal_period: Some(3), ..Default::default() },
+            output_field: Some("signal".into()),
+            cached_hash: 0,
+        };
+        strategy.long_entry_rules[0].left_operand.operand_type = OperandType::Indicator;
+        strategy.long_entry_rules[0].left_operand.indicator = Some(macd.clone());
+        let mut pine = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        embed_verification_samples(&mut pine, &strategy, &candles).unwrap();
+        let code = main_code(&pine);
+        let engine = compute_indicator(&macd, &candles).unwrap();
+        let var = indicator_var_name(&macd);
+        let last = candles.len() - 1;
+        let outputs = [
+            ("_line", &engine.primary),
+            ("_signal", engine.secondary.as_ref().unwrap()),
+            ("_hist", engine.tertiary.as_ref().unwrap()),
+        ];
+        for (suffix, values) in outputs {
+            let line = format!("// {} {}{} = {:.8}", candles[last].datetime, var, suffix, values[last]);
+            assert!(code.contains(&line), "missing sample line: {}\n{}", line, code);
+        }
+        assert!(!code.contains(".primary") && !code.contains(".secondary"));
+
+        // Indicators that cannot warm up get a note instead of failing the export
+        let mut short = generate_pinescript(&strategy, &AccountSettings::default(), None, false).unwrap();
+        embed_verification_samples(&mut short, &strategy, &candles[..8]).unwrap();
+        let code = main_code(&short);
+        assert!(code.contains(&format!("// {}: not enough bars to compute (need 20, have 8)", indicator_var_name(
+            strategy.long_entry_rules[0].right_operand.indicator.as_ref().unwrap()))), "{}", code);
+    }
+
+    /// Helper: SR strategy whose long entry is a single indicator leaf
+    fn sr_strategy_with_leaf(config: IndicatorConfig) -> crate::models::sr_result::SrStrategy {
+        use crate::models::sr_result::{SrNode, SrStrategy};
+        let base = simple_strategy();
+        SrStrategy {
+            entry_long: SrNode::IndicatorLeaf { config, buffer_index: 0 },
+            long_threshold: 0.0,
+            entry_short: SrNode::Constant(0.0),
+            short_threshold: 0.0,
+            exit: SrNode::Constant(0.0),
+            stop_loss: base.stop_loss,
+            take_profit: base.take_profit,
+            trailing_stop: None,
+            position_sizing: base.position_sizing,
+            trading_costs: base.trading_costs,
+            trade_direction: TradeDirection::Long,
+            trading_hours: None,
+            close_trades_at: None,
+            max_trades_per_day: None,
+            use_exit_formula: true,
+            exit_dead_zone: 0.0,
+            max_bars_open: None,
+            min_bars_between_trades: None,
+            move_sl_to_be: false,
+        }
+    }
+
+    #[test]
+    fn test_sr_icustom_inputs_are_declared() {
+        let leaves = [
+            (IndicatorType::RoofingFilter, IndicatorParams { period: Some(10), ..Default::default() }),
+            (IndicatorType::ConnorsRSI, IndicatorParams { period: Some(3), ..Default::default() }),
+            (IndicatorType::EaseOfMovement, IndicatorParams { period: Some(14), ..Default::default() }),
+            (IndicatorType::MassIndex, IndicatorParams { period: Some(25), ..Default::default() }),
+            (IndicatorType::StochasticRSI, IndicatorParams { period: Some(14), k_period: Some(14), d_period: Some(3), ..Default::default() }),
+            (IndicatorType::TRIX, IndicatorParams { period: Some(15), ..Default::default() }),
+        ];
+        for (indicator_type, params) in leaves {
+            let config = IndicatorConfig { indicator_type, params, output_field: None, cached_hash: 0 };
+            let var = indicator_var_name(&config);
+            let call = sr_icustom_call(&config, &var);
+            let result = generate_sr_mql5(&sr_strategy_with_leaf(config), "SR Inputs", None).unwrap();
+            let code = main_code(&result);
+            let prefix = format!("Inp_{}_", var);
+            for arg in call.split(", ").filter(|a| a.starts_with(&prefix)) {
+                let arg = arg.trim_end_matches(')');
+                assert!(
+                    code.lines().any(|l| l.starts_with("input ") && l.contains(&format!(" {} ", arg))),
+                    "{:?}: {} used by iCustom but not declared", indicator_type, arg
+                );
+            }
+        }
     }
 }
 
@@ -8549,6 +11548,9 @@ fn emit_trailing_dist_init(
             TrailingStopType::RiskReward => {
                 writeln!(out, "            g_trailing_dist = (sl != 0.0) ? MathAbs(entry - sl) * InpTSRR : 0.0;").ok();
             }
+            TrailingStopType::Step => {
+                writeln!(out, "            g_trailing_dist = InpTSPips * PipSize();").ok();
+            }
         }
     }
 }
@@ -8556,9 +11558,11 @@ fn emit_trailing_dist_init(
 /// Generate an MQL5 Expert Advisor from a Symbolic Regression strategy.
 /// The EA evaluates the three formula trees at each new bar using BT_* custom
 /// indicators and applies the configured position sizing, SL, TP, and costs.
+/// `instrument` sets the `InpPipPoints` default like in [`generate_mql5`].
 pub fn generate_sr_mql5(
     strategy: &crate::models::sr_result::SrStrategy,
     name: &str,
+    instrument: Option<&InstrumentConfig>,
 ) -> Result<CodeGenerationResult, AppError> {
     use crate::engine::sr::tree::format_tree;
     use crate::models::sr_result::{BinaryOpType, SrNode, UnaryOpType};
@@ -8567,7 +11571,7 @@ pub fn generate_sr_mql5(
     };
     use std::collections::{HashMap, HashSet};
 
-    let ea_name = name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let ea_name = sanitize_name(name);
 
     // ── 1. Collect unique indicator leaves ────────────────────────────────────
     fn collect_leaves(node: &SrNode, seen: &mut HashMap<String, IndicatorConfig>) {
@@ -8605,13 +11609,7 @@ pub fn generate_sr_mql5(
         buffer_map: &HashMap<String, usize>,
     ) -> String {
         match node {
-            SrNode::Constant(v) => {
-                if v.abs() < 1e-4 && *v != 0.0 {
-                    format!("{:e}", v)
-                } else {
-                    format!("{:.6}", v)
-                }
-            }
+            SrNode::Constant(v) => float_literal(*v),
             SrNode::IndicatorLeaf { config, buffer_index } => {
                 let key = config.cache_key();
                 let var = var_map.get(&key).map(|s| s.as_str()).unwrap_or("__unknown");
@@ -8679,7 +11677,7 @@ pub fn generate_sr_mql5(
     // the field specifies a different period.
     let mut atr_periods_needed: HashSet<usize> = HashSet::new();
     if let Some(sl) = &strategy.stop_loss {
-        if sl.sl_type == StopLossType::ATR {
+        if sl.sl_type.uses_atr() {
             atr_periods_needed.insert(sl.atr_period.unwrap_or(14));
         }
     }
@@ -8776,8 +11774,9 @@ pub fn generate_sr_mql5(
         match sl.sl_type {
             StopLossType::Pips       => { writeln!(out, "input double InpSLPips    = {:.1};  // Stop Loss in pips", sl.value).ok(); }
             StopLossType::Percentage => { writeln!(out, "input double InpSLPct     = {:.4};  // Stop Loss as % of price", sl.value).ok(); }
-            StopLossType::ATR        => { writeln!(out, "input double InpSLAtrMult = {:.2};  // Stop Loss ATR multiplier", sl.value).ok(); }
+            StopLossType::ATR | StopLossType::ChandeKroll => { writeln!(out, "input double InpSLAtrMult = {:.2};  // Stop Loss ATR multiplier", sl.value).ok(); }
         }
+        chande_kroll_note(&mut out, sl);
     }
 
     // TP inputs
@@ -8788,14 +11787,24 @@ pub fn generate_sr_mql5(
             TakeProfitType::ATR         => { writeln!(out, "input double InpTPAtrMult = {:.2};  // Take Profit ATR multiplier", tp.value).ok(); }
         }
     }
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        writeln!(out, "input double InpPipPoints  = {};  // Points per pip (0 = auto from _Digits)", pip_points_default(instrument)).ok();
+    }
 
     // Trailing stop inputs
     if let Some(ts) = &strategy.trailing_stop {
         match ts.ts_type {
             TrailingStopType::ATR        => { writeln!(out, "input double InpTSAtrMult = {:.2};  // Trailing Stop ATR multiplier", ts.value).ok(); }
             TrailingStopType::RiskReward => { writeln!(out, "input double InpTSRR      = {:.2};  // Trailing Stop risk:reward ratio", ts.value).ok(); }
+            TrailingStopType::Step => {
+                writeln!(out, "input double InpTSPips     = {:.2};  // Trailing Stop distance in pips", ts.value).ok();
+                writeln!(out, "input double InpTSStepPips = {:.2};  // Trailing Stop step in pips", ts.step_pips.unwrap_or(ts.value)).ok();
+            }
         }
     }
+    if let Some(pips) = ts_activation_pips(strategy.trailing_stop.as_ref()) {
+        writeln!(out, "input double InpTSActivationPips = {:.2};  // Trailing Stop activation (pips in profit)", pips).ok();
+    }
     // Time filter inputs
     if let Some(th) = &strategy.trading_hours {
         writeln!(out, "// ── Trading hours ─────────────────────────────────────────────────").ok();
@@ -8828,17 +11837,19 @@ pub fn generate_sr_mql5(
         writeln!(out, "// ── SR indicator parameters (values fixed by SR evolution) ────────").ok();
         for (_, cfg) in &leaves {
             let v = indicator_var_name(cfg);
-            if let Some(p) = cfg.params.period          { writeln!(out, "input int    Inp_{v}_period = {p};").ok(); }
-            if let Some(p) = cfg.params.fast_period     { writeln!(out, "input int    Inp_{v}_fast   = {p};").ok(); }
-            if let Some(p) = cfg.params.slow_period     { writeln!(out, "input int    Inp_{v}_slow   = {p};").ok(); }
-            if let Some(p) = cfg.params.signal_period   { writeln!(out, "input int    Inp_{v}_signal = {p};").ok(); }
-            if let Some(p) = cfg.params.k_period        { writeln!(out, "input int    Inp_{v}_k      = {p};").ok(); }
-            if let Some(p) = cfg.params.d_period        { writeln!(out, "input int    Inp_{v}_d      = {p};").ok(); }
-            if let Some(x) = cfg.params.std_dev         { writeln!(out, "input double Inp_{v}_stddev = {x};").ok(); }
-            if let Some(x) = cfg.params.acceleration_factor { writeln!(out, "input double Inp_{v}_af  = {x};").ok(); }
-            if let Some(x) = cfg.params.maximum_factor  { writeln!(out, "input double Inp_{v}_max    = {x};").ok(); }
-            if let Some(x) = cfg.params.gamma           { writeln!(out, "input double Inp_{v}_gamma  = {x};").ok(); }
-            if let Some(x) = cfg.params.multiplier      { writeln!(out, "input double Inp_{v}_mult   = {x};").ok(); }
+            let params = sr_input_params(cfg);
+            if let Some(p) = params.period          { writeln!(out, "input int    Inp_{v}_period = {p};").ok(); }
+            if let Some(p) = params.fast_period     { writeln!(out, "input int    Inp_{v}_fast   = {p};").ok(); }
+            if let Some(p) = params.slow_period     { writeln!(out, "input int    Inp_{v}_slow   = {p};").ok(); }
+            if let Some(p) = params.signal_period   { writeln!(out, "input int    Inp_{v}_signal = {p};").ok(); }
+            if let Some(p) = params.k_period        { writeln!(out, "input int    Inp_{v}_k      = {p};").ok(); }
+            if let Some(p) = params.d_period        { writeln!(out, "input int    Inp_{v}_d      = {p};").ok(); }
+            if let Some(p) = params.slowing         { writeln!(out, "input int    Inp_{v}_slowing = {p};").ok(); }
+            if let Some(x) = params.std_dev         { writeln!(out, "input double Inp_{v}_stddev = {x};").ok(); }
+            if let Some(x) = params.acceleration_factor { writeln!(out, "input double Inp_{v}_af  = {x};").ok(); }
+            if let Some(x) = params.maximum_factor  { writeln!(out, "input double Inp_{v}_max    = {x};").ok(); }
+            if let Some(x) = params.gamma           { writeln!(out, "input double Inp_{v}_gamma  = {x};").ok(); }
+            if let Some(x) = params.multiplier      { writeln!(out, "input double Inp_{v}_mult   = {x};").ok(); }
         }
         writeln!(out).ok();
     }
@@ -9274,6 +12285,10 @@ pub fn generate_sr_mql5(
     writeln!(out, "}}").ok();
     writeln!(out).ok();
 
+    if uses_pip_distances(strategy.stop_loss.as_ref(), strategy.take_profit.as_ref(), strategy.trailing_stop.as_ref()) {
+        mql5_pip_size_fn(&mut out);
+    }
+
     // ── SR_CalcSL ─────────────────────────────────────────────────────────────
     writeln!(out, "//+------------------------------------------------------------------+").ok();
     writeln!(out, "// SR_CalcSL: mirrors position.rs calculate_stop_loss()").ok();
@@ -9284,8 +12299,7 @@ pub fn generate_sr_mql5(
         let period = sl.atr_period.unwrap_or(14);
         match sl.sl_type {
             StopLossType::Pips => {
-                // pip = _Point * 10 for 5-digit brokers; this matches pip_size = 0.0001
-                writeln!(out, "   double dist = InpSLPips * _Point * 10.0;  // pips → price distance").ok();
+                writeln!(out, "   double dist = InpSLPips * PipSize();  // pips → price distance").ok();
                 writeln!(out, "   double sl   = (dir == ORDER_TYPE_BUY) ? price - dist : price + dist;").ok();
                 writeln!(out, "   return NormalizeDouble(sl, _Digits);").ok();
             }
@@ -9294,7 +12308,7 @@ pub fn generate_sr_mql5(
                 writeln!(out, "   double sl   = (dir == ORDER_TYPE_BUY) ? price - dist : price + dist;").ok();
                 writeln!(out, "   return NormalizeDouble(sl, _Digits);").ok();
             }
-            StopLossType::ATR => {
+            StopLossType::ATR | StopLossType::ChandeKroll => {
                 writeln!(out, "   if(g_atr{period} <= 0.0) return 0.0;  // ATR not ready yet").ok();
                 writeln!(out, "   double dist = g_atr{period} * InpSLAtrMult;").ok();
                 writeln!(out, "   double sl   = (dir == ORDER_TYPE_BUY) ? price - dist : price + dist;").ok();
@@ -9317,13 +12331,17 @@ pub fn generate_sr_mql5(
         let period = tp.atr_period.unwrap_or(14);
         match tp.tp_type {
             TakeProfitType::Pips => {
-                writeln!(out, "   double dist = InpTPPips * _Point * 10.0;").ok();
+                writeln!(out, "   double dist = InpTPPips * PipSize();").ok();
                 writeln!(out, "   double tp   = (dir == ORDER_TYPE_BUY) ? price + dist : price - dist;").ok();
                 writeln!(out, "   return NormalizeDouble(tp, _Digits);").ok();
             }
             TakeProfitType::RiskReward => {
-                writeln!(out, "   if(sl == 0.0) return 0.0;  // need SL for R:R TP").ok();
-                writeln!(out, "   double slDist = MathAbs(price - sl);").ok();
+                if strategy.stop_loss.is_none() {
+                    writeln!(out, "   double slDist = {:.1} * PipSize();  // No SL: R:R of a fixed-pip risk", RR_FALLBACK_RISK_PIPS).ok();
+                } else {
+                    writeln!(out, "   if(sl == 0.0) return 0.0;  // SL not placed (e.g. ATR not ready)").ok();
+                    writeln!(out, "   double slDist = MathAbs(price - sl);").ok();
+                }
                 writeln!(out, "   double dist   = slDist * InpTPRR;").ok();
                 writeln!(out, "   double tp     = (dir == ORDER_TYPE_BUY) ? price + dist : price - dist;").ok();
                 writeln!(out, "   return NormalizeDouble(tp, _Digits);").ok();
@@ -9342,7 +12360,7 @@ pub fn generate_sr_mql5(
     writeln!(out).ok();
 
     // ── SR_ManageTrailingStop ─────────────────────────────────────────────────
-    if let Some(_ts) = &strategy.trailing_stop {
+    if let Some(ts) = &strategy.trailing_stop {
         writeln!(out, "//+------------------------------------------------------------------+").ok();
         writeln!(out, "// SR_ManageTrailingStop: mirrors position.rs update_trailing_stop()").ok();
         writeln!(out, "void SR_ManageTrailingStop()").ok();
@@ -9363,18 +12381,37 @@ pub fn generate_sr_mql5(
         writeln!(out, "      // Trailing distance fixed at entry (matches backtester)").ok();
         writeln!(out, "      double trailDist = g_trailing_dist;").ok();
         writeln!(out, "      if(trailDist <= 0.0) continue;").ok();
+        if ts_activation_pips(Some(ts)).is_some() {
+            writeln!(out, "      double entryPx    = PositionGetDouble(POSITION_PRICE_OPEN);").ok();
+            writeln!(out, "      double profitDist = (ptype == POSITION_TYPE_BUY)").ok();
+            writeln!(out, "         ? SymbolInfoDouble(_Symbol, SYMBOL_BID) - entryPx").ok();
+            writeln!(out, "         : entryPx - SymbolInfoDouble(_Symbol, SYMBOL_ASK);").ok();
+            writeln!(out, "      if(profitDist < InpTSActivationPips * PipSize()) continue;  // not activated yet").ok();
+        }
 
         writeln!(out).ok();
         writeln!(out, "      if(ptype == POSITION_TYPE_BUY)").ok();
         writeln!(out, "      {{").ok();
-        writeln!(out, "         double newSL = NormalizeDouble(SymbolInfoDouble(_Symbol, SYMBOL_BID) - trailDist, _Digits);").ok();
+        if ts.ts_type == TrailingStopType::Step {
+            writeln!(out, "         double entryOpen = PositionGetDouble(POSITION_PRICE_OPEN);").ok();
+            writeln!(out, "         double steps = MathMax(0.0, MathFloor((SymbolInfoDouble(_Symbol, SYMBOL_BID) - entryOpen) / (InpTSStepPips * PipSize())));").ok();
+            writeln!(out, "         double newSL = NormalizeDouble(entryOpen - trailDist + steps * InpTSStepPips * PipSize(), _Digits);").ok();
+        } else {
+            writeln!(out, "         double newSL = NormalizeDouble(SymbolInfoDouble(_Symbol, SYMBOL_BID) - trailDist, _Digits);").ok();
+        }
         writeln!(out, "         // Only move SL upward and only if it improves on current SL").ok();
         writeln!(out, "         if(newSL > curSL + _Point && newSL < SymbolInfoDouble(_Symbol, SYMBOL_BID))").ok();
         writeln!(out, "            g_trade.PositionModify(_Symbol, newSL, curTP);").ok();
         writeln!(out, "      }}").ok();
         writeln!(out, "      else if(ptype == POSITION_TYPE_SELL)").ok();
         writeln!(out, "      {{").ok();
-        writeln!(out, "         double newSL = NormalizeDouble(SymbolInfoDouble(_Symbol, SYMBOL_ASK) + trailDist, _Digits);").ok();
+        if ts.ts_type == TrailingStopType::Step {
+            writeln!(out, "         double entryOpen = PositionGetDouble(POSITION_PRICE_OPEN);").ok();
+            writeln!(out, "         double steps = MathMax(0.0, MathFloor((entryOpen - SymbolInfoDouble(_Symbol, SYMBOL_ASK)) / (InpTSStepPips * PipSize())));").ok();
+            writeln!(out, "         double newSL = NormalizeDouble(entryOpen + trailDist - steps * InpTSStepPips * PipSize(), _Digits);").ok();
+        } else {
+            writeln!(out, "         double newSL = NormalizeDouble(SymbolInfoDouble(_Symbol, SYMBOL_ASK) + trailDist, _Digits);").ok();
+        }
         writeln!(out, "         // Only move SL downward and only if it improves on current SL").ok();
         writeln!(out, "         if((curSL == 0.0 || newSL < curSL - _Point) && newSL > SymbolInfoDouble(_Symbol, SYMBOL_ASK))").ok();
         writeln!(out, "            g_trade.PositionModify(_Symbol, newSL, curTP);").ok();
@@ -9412,23 +12449,53 @@ pub fn generate_sr_mql5(
             code: out,
             is_main: true,
         }],
+        warnings: Vec::new(),
     })
 }
 
+/// SR indicator params with the engine's fallbacks filled in, so every input
+/// referenced by [`sr_icustom_call`] is declared even when the leaf relies on a default.
+fn sr_input_params(cfg: &IndicatorConfig) -> IndicatorParams {
+    let mut params = cfg.params.clone();
+    match cfg.indicator_type {
+        IndicatorType::RoofingFilter => {
+            params.slow_period.get_or_insert(48);
+        }
+        IndicatorType::ConnorsRSI => {
+            params.fast_period.get_or_insert(2);
+            params.slow_period.get_or_insert(100);
+        }
+        IndicatorType::EaseOfMovement => {
+            params.multiplier.get_or_insert(10_000.0);
+        }
+        IndicatorType::MassIndex => {
+            params.fast_period.get_or_insert(9);
+        }
+        IndicatorType::StochasticRSI => {
+            params.slowing.get_or_insert(3);
+        }
+        IndicatorType::TRIX => {
+            params.signal_period.get_or_insert(0);
+        }
+        _ => {}
+    }
+    params
+}
+
 /// Build the `iCustom()` call string for an SR indicator leaf.
 fn sr_icustom_call(cfg: &IndicatorConfig, var: &str) -> String {
     match cfg.indicator_type {
         // ── Non-SQX indicators (BT_* custom files) ──
         IndicatorType::SMA => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_SMA\", Inp_{}_period)", var),
-        IndicatorType::EMA => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_EMA\", Inp_{}_period)", var),
-        IndicatorType::RSI => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_RSI\", Inp_{}_period)", var),
-        IndicatorType::MACD => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_MACD\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)", var),
-        IndicatorType::BollingerBands => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_BollingerBands\", Inp_{0}_period, Inp_{0}_stddev)", var),
+        IndicatorType::EMA => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_EMA\", Inp_{}_period, {})", var, mql5_ema_seed(&cfg.params)),
+        IndicatorType::RSI => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_RSI\", Inp_{}_period, {})", var, mql5_rsi_smoothing(&cfg.params)),
+        IndicatorType::MACD => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_MACD\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal, {1})", var, mql5_ema_seed(&cfg.params)),
+        IndicatorType::BollingerBands => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_BollingerBands\", Inp_{0}_period, Inp_{0}_stddev, {1})", var, cfg.params.sample_stddev.unwrap_or(false)),
         IndicatorType::DeMarker => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_DeMarker\", Inp_{}_period)", var),
         IndicatorType::AwesomeOscillator => "iCustom(_Symbol, PERIOD_CURRENT, \"BT_AwesomeOscillator\")".to_string(),
         IndicatorType::BarRange => "iCustom(_Symbol, PERIOD_CURRENT, \"BT_BarRange\")".to_string(),
         IndicatorType::Momentum => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_Momentum\", Inp_{}_period)", var),
-        IndicatorType::StdDev => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_StdDev\", Inp_{}_period)", var),
+        IndicatorType::StdDev => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_StdDev\", Inp_{}_period, {})", var, cfg.params.sample_stddev.unwrap_or(false)),
         // ── SQX indicators ──
         IndicatorType::ATR => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqATR\", Inp_{}_period)", var),
         IndicatorType::Stochastic => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqStochastic\", Inp_{0}_k, Inp_{0}_d, 3, MODE_SMA, STO_LOWHIGH)", var),
@@ -9462,7 +12529,17 @@ fn sr_icustom_call(cfg: &IndicatorConfig, var: &str) -> String {
         IndicatorType::KAMA => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqKAMA\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, 0)", var),
         IndicatorType::LowestIndex => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqLowestIndex\", Inp_{}_period, PRICE_LOW)", var),
         IndicatorType::QQE => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqQQE\", Inp_{0}_period, Inp_{0}_sf, Inp_{0}_wf)", var),
-        IndicatorType::SchaffTrendCycle => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqSchaffTrendCycle\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, 3.0)", var),
+        IndicatorType::SchaffTrendCycle => format!("iCustom(_Symbol, PERIOD_CURRENT, \"SqSchaffTrendCycle\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, {1}.0)", var, cfg.params.slowing.unwrap_or(3)),
+        IndicatorType::Coppock => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_Coppock\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow)", var),
+        IndicatorType::RoofingFilter => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_RoofingFilter\", Inp_{0}_slow, Inp_{0}_period)", var),
+        IndicatorType::ConnorsRSI => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_ConnorsRSI\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow)", var),
+        IndicatorType::EaseOfMovement => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_EaseOfMovement\", Inp_{0}_period, Inp_{0}_mult)", var),
+        IndicatorType::PPO => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_PPO\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal, {1})", var, mql5_ema_seed(&cfg.params)),
+        IndicatorType::MassIndex => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_MassIndex\", Inp_{0}_period, Inp_{0}_fast)", var),
+        IndicatorType::Klinger => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_Klinger\", Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)", var),
+        IndicatorType::ElderImpulse => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_ElderImpulse\", Inp_{0}_period, Inp_{0}_fast, Inp_{0}_slow, Inp_{0}_signal)", var),
+        IndicatorType::TRIX => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_TRIX\", Inp_{0}_period, Inp_{0}_signal)", var),
+        IndicatorType::StochasticRSI => format!("iCustom(_Symbol, PERIOD_CURRENT, \"BT_StochasticRSI\", Inp_{0}_period, Inp_{0}_k, Inp_{0}_d, Inp_{0}_slowing)", var),
         _ => {
             let type_name = format!("{:?}", cfg.indicator_type);
             if cfg.params.period.is_some() {
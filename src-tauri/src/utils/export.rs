@@ -1,10 +1,14 @@
 use std::fmt::Write as FmtWrite;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Timelike};
+use serde::Deserialize;
 
 use crate::errors::AppError;
-use crate::models::result::{BacktestMetrics, BacktestResults, DrawdownPoint, EquityPoint};
+use crate::models::result::{BacktestMetrics, BacktestResults, DrawdownPoint, EquityPoint, OptimizationResult};
 use crate::models::trade::TradeResult;
+use crate::utils::codegen::{sanitize_filename, CodeFile};
 
 /// Write raw tick data to a CSV file in MetaTrader 5 import format.
 ///
@@ -183,7 +187,7 @@ fn days_to_ymd(z: i32) -> (i32, u32, u32) {
     (y, m as u32, d as u32)
 }
 
-/// Write a list of trades to a CSV file.
+/// Write a list of trades to a CSV file (simple format, see [`write_trade_journal_csv`]).
 pub fn write_trades_csv(trades: &[TradeResult], path: &Path) -> Result<(), AppError> {
     let mut wtr = csv::Writer::from_path(path)
         .map_err(|e| AppError::FileWrite(format!("Cannot create CSV: {}", e)))?;
@@ -200,6 +204,8 @@ pub fn write_trades_csv(trades: &[TradeResult], path: &Path) -> Result<(), AppEr
         "P&L Pips",
         "Commission",
         "Close Reason",
+        "Entry Reason",
+        "Exit Reason",
         "Duration",
         "Bars",
         "MAE",
@@ -219,10 +225,114 @@ pub fn write_trades_csv(trades: &[TradeResult], path: &Path) -> Result<(), AppEr
             &format!("{:.1}", t.pnl_pips),
             &format!("{:.2}", t.commission),
             &format!("{:?}", t.close_reason),
+            &t.entry_reason.clone().unwrap_or_default(),
+            &t.exit_reason.clone().unwrap_or_default(),
+            &t.duration_time,
+            &t.duration_bars.to_string(),
+            &format!("{:.1}", t.mae),
+            &format!("{:.1}", t.mfe),
+        ])
+        .map_err(|e| AppError::FileWrite(e.to_string()))?;
+    }
+
+    wtr.flush().map_err(|e| AppError::FileWrite(e.to_string()))?;
+    Ok(())
+}
+
+/// Account context for the trade journal's currency, equity and drawdown columns.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TradeJournalOptions {
+    pub initial_capital: f64,
+    /// Account-currency value of one pip for one lot (`InstrumentConfig::pip_value`).
+    pub pip_value: f64,
+}
+
+/// Write a detailed trade journal to a CSV file.
+///
+/// On top of the columns of [`write_trades_csv`] it adds MAE/MFE in pips and currency,
+/// initial risk and R-multiple (net P&L / currency risked at the initial stop; empty
+/// without a stop loss), and the running equity and drawdown after each trade closes.
+pub fn write_trade_journal_csv(
+    trades: &[TradeResult],
+    options: &TradeJournalOptions,
+    path: &Path,
+) -> Result<(), AppError> {
+    let mut wtr = csv::Writer::from_path(path)
+        .map_err(|e| AppError::FileWrite(format!("Cannot create CSV: {}", e)))?;
+
+    wtr.write_record([
+        "#",
+        "Direction",
+        "Entry Time",
+        "Entry Price",
+        "Exit Time",
+        "Exit Price",
+        "Lots",
+        "P&L",
+        "P&L Pips",
+        "Commission",
+        "Swap",
+        "Net P&L",
+        "Close Reason",
+        "Entry Reason",
+        "Exit Reason",
+        "Duration",
+        "Bars Held",
+        "MAE Pips",
+        "MAE",
+        "MFE Pips",
+        "MFE",
+        "Initial Risk Pips",
+        "R-Multiple",
+        "Equity",
+        "Drawdown",
+        "Drawdown %",
+    ])
+    .map_err(|e| AppError::FileWrite(e.to_string()))?;
+
+    let mut equity = options.initial_capital;
+    let mut peak = equity;
+    for (i, t) in trades.iter().enumerate() {
+        let net = t.pnl - t.commission + t.swap;
+        equity += net;
+        peak = peak.max(equity);
+        let drawdown = peak - equity;
+        let drawdown_pct = if peak > 0.0 { drawdown / peak * 100.0 } else { 0.0 };
+
+        let pip_money = options.pip_value * t.lots;
+        let r_multiple = t
+            .initial_risk_pips
+            .filter(|&risk| risk > 0.0 && pip_money > 0.0)
+            .map(|risk| format!("{:.2}", net / (risk * pip_money)))
+            .unwrap_or_default();
+
+        wtr.write_record([
+            &(i + 1).to_string(),
+            &format!("{:?}", t.direction),
+            &t.entry_time,
+            &format!("{:.6}", t.entry_price),
+            &t.exit_time,
+            &format!("{:.6}", t.exit_price),
+            &format!("{:.4}", t.lots),
+            &format!("{:.2}", t.pnl),
+            &format!("{:.1}", t.pnl_pips),
+            &format!("{:.2}", t.commission),
+            &format!("{:.2}", t.swap),
+            &format!("{:.2}", net),
+            &format!("{:?}", t.close_reason),
+            &t.entry_reason.clone().unwrap_or_default(),
+            &t.exit_reason.clone().unwrap_or_default(),
             &t.duration_time,
             &t.duration_bars.to_string(),
             &format!("{:.1}", t.mae),
+            &format!("{:.2}", t.mae * pip_money),
             &format!("{:.1}", t.mfe),
+            &format!("{:.2}", t.mfe * pip_money),
+            &t.initial_risk_pips.map(|r| format!("{:.1}", r)).unwrap_or_default(),
+            &r_multiple,
+            &format!("{:.2}", equity),
+            &format!("{:.2}", drawdown),
+            &format!("{:.2}", drawdown_pct),
         ])
         .map_err(|e| AppError::FileWrite(e.to_string()))?;
     }
@@ -303,6 +413,89 @@ pub fn write_metrics_csv(metrics: &BacktestMetrics, path: &Path) -> Result<(), A
     Ok(())
 }
 
+/// Write one row per optimization result: rank, a column per parameter, every metric,
+/// then return/Sharpe/drawdown/profit factor/trades for each OOS period. OOS cells are
+/// empty for results that were not validated out of sample.
+pub fn write_optimization_csv(
+    results: &[OptimizationResult],
+    param_names: &[String],
+    oos_labels: &[String],
+    path: &Path,
+) -> Result<(), AppError> {
+    let mut wtr = csv::Writer::from_path(path)
+        .map_err(|e| AppError::FileWrite(format!("Cannot create CSV: {}", e)))?;
+
+    const OOS_COLUMNS: [&str; 5] = ["Return %", "Sharpe", "Max DD %", "Profit Factor", "Trades"];
+    let mut header = vec!["Rank".to_string()];
+    header.extend(param_names.iter().cloned());
+    header.extend(
+        [
+            "Objective",
+            "Composite Score",
+            "Total Return %",
+            "Sharpe Ratio",
+            "Max Drawdown %",
+            "Total Trades",
+            "Profit Factor",
+            "Return/DD Ratio",
+            "Win Rate %",
+            "Stagnation (bars)",
+            "Ulcer Index %",
+            "Net Profit",
+            "Sortino Ratio",
+            "Calmar Ratio",
+            "Expectancy",
+            "Recovery Factor",
+        ]
+        .map(String::from),
+    );
+    for label in oos_labels {
+        header.extend(OOS_COLUMNS.iter().map(|c| format!("OOS {} {}", label, c)));
+    }
+    wtr.write_record(&header)
+        .map_err(|e| AppError::FileWrite(e.to_string()))?;
+
+    for (i, r) in results.iter().enumerate() {
+        let mut row = vec![(i + 1).to_string()];
+        row.extend(param_names.iter().map(|name| r.params.get(name).map(|v| v.to_string()).unwrap_or_default()));
+        row.extend([
+            format!("{:.4}", r.objective_value),
+            format!("{:.4}", r.composite_score),
+            format!("{:.2}", r.total_return_pct),
+            format!("{:.2}", r.sharpe_ratio),
+            format!("{:.2}", r.max_drawdown_pct),
+            r.total_trades.to_string(),
+            format!("{:.2}", r.profit_factor),
+            format!("{:.2}", r.return_dd_ratio),
+            format!("{:.2}", r.win_rate_pct),
+            r.stagnation_bars.to_string(),
+            format!("{:.2}", r.ulcer_index_pct),
+            format!("{:.2}", r.net_profit),
+            format!("{:.2}", r.sortino_ratio),
+            format!("{:.2}", r.calmar_ratio),
+            format!("{:.2}", r.expectancy),
+            format!("{:.2}", r.recovery_factor),
+        ]);
+        for label in oos_labels {
+            match r.oos_results.iter().find(|o| &o.label == label) {
+                Some(o) => row.extend([
+                    format!("{:.2}", o.total_return_pct),
+                    format!("{:.2}", o.sharpe_ratio),
+                    format!("{:.2}", o.max_drawdown_pct),
+                    format!("{:.2}", o.profit_factor),
+                    o.total_trades.to_string(),
+                ]),
+                None => row.extend(vec![String::new(); OOS_COLUMNS.len()]),
+            }
+        }
+        wtr.write_record(&row)
+            .map_err(|e| AppError::FileWrite(e.to_string()))?;
+    }
+
+    wtr.flush().map_err(|e| AppError::FileWrite(e.to_string()))?;
+    Ok(())
+}
+
 /// Write a full HTML backtest report with inline CSS, SVG charts, metrics, and trades table.
 pub fn write_report_html(results: &BacktestResults, path: &Path) -> Result<(), AppError> {
     let mut html = String::with_capacity(256 * 1024);
@@ -546,6 +739,60 @@ fn write_drawdown_svg(html: &mut String, data: &[DrawdownPoint]) {
     html.push_str("</svg>");
 }
 
+/// Write generated code files (EA/script + custom indicators) into `dir`, creating it
+/// if needed. Filenames are sanitized; returns the written paths in file order.
+pub fn write_code_files(files: &[CodeFile], dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| AppError::FileWrite(format!("Cannot create {}: {}", dir.display(), e)))?;
+    let mut written = Vec::with_capacity(files.len());
+    for file in files {
+        let path = dir.join(code_filename(file)?);
+        std::fs::write(&path, &file.code)
+            .map_err(|e| AppError::FileWrite(format!("Cannot write {}: {}", path.display(), e)))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Bundle generated code files into a single deflate-compressed zip archive at `path`,
+/// each entry under its sanitized filename.
+pub fn write_code_zip(files: &[CodeFile], path: &Path) -> Result<(), AppError> {
+    let zip_err = |e: zip::result::ZipError| AppError::FileWrite(format!("Cannot write {}: {}", path.display(), e));
+    let now = chrono::Local::now();
+    let modified = zip::DateTime::from_date_and_time(
+        now.year().clamp(1980, 2107) as u16,
+        now.month() as u8,
+        now.day() as u8,
+        now.hour() as u8,
+        now.minute() as u8,
+        now.second() as u8,
+    )
+    .unwrap_or_default();
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(modified);
+
+    let file = std::fs::File::create(path)
+        .map_err(|e| AppError::FileWrite(format!("Cannot create {}: {}", path.display(), e)))?;
+    let mut zip = zip::ZipWriter::new(file);
+    for file in files {
+        zip.start_file(code_filename(file)?, options).map_err(zip_err)?;
+        zip.write_all(file.code.as_bytes())
+            .map_err(|e| AppError::FileWrite(format!("Cannot write {}: {}", path.display(), e)))?;
+    }
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+/// Sanitized filename of a generated file; rejects names that sanitize to nothing.
+fn code_filename(file: &CodeFile) -> Result<String, AppError> {
+    let name = sanitize_filename(&file.filename);
+    if name.is_empty() {
+        return Err(AppError::InvalidConfig(format!("Invalid generated filename: '{}'", file.filename)));
+    }
+    Ok(name)
+}
+
 /// Format a number with thousands separator for chart labels.
 fn format_number(v: f64) -> String {
     let abs = v.abs();
@@ -558,3 +805,171 @@ fn format_number(v: f64) -> String {
         format!("{}{:.0}", sign, abs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::result::OosResult;
+    use crate::models::strategy::TradeDirection;
+    use crate::models::trade::CloseReason;
+
+    fn trade(pnl: f64, lots: f64, initial_risk_pips: Option<f64>) -> TradeResult {
+        TradeResult {
+            id: String::new(),
+            direction: TradeDirection::Long,
+            entry_time: "2024-01-02 10:00".into(),
+            entry_price: 1.1000,
+            exit_time: "2024-01-02 14:00".into(),
+            exit_price: 1.1000,
+            lots,
+            pnl,
+            pnl_pips: pnl / (10.0 * lots),
+            commission: 5.0,
+            swap: 0.0,
+            spread_cost: 0.0,
+            slippage_cost: 0.0,
+            close_reason: CloseReason::StopLoss,
+            duration_bars: 4,
+            duration_time: "4h".into(),
+            mae: 20.0,
+            mfe: 35.0,
+            initial_risk_pips,
+            entry_reason: Some("Long entry rules (1)".into()),
+            exit_reason: Some("Stop loss 1.09800".into()),
+            regime: None,
+        }
+    }
+
+    fn code_file(filename: &str, code: &str, is_main: bool) -> CodeFile {
+        CodeFile { filename: filename.into(), code: code.into(), is_main }
+    }
+
+    #[test]
+    fn test_code_files_written_with_declared_filenames() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("My_EA");
+        let files = vec![
+            code_file("My_EA.mq5", "// ea", true),
+            code_file("BT_SMA.mq5", "// sma", false),
+            code_file("BT_MACD.mq5", "// macd", false),
+        ];
+        let written = write_code_files(&files, &out).unwrap();
+        assert_eq!(written.len(), files.len());
+        for (file, path) in files.iter().zip(&written) {
+            assert_eq!(path, &out.join(&file.filename));
+            assert_eq!(std::fs::read_to_string(path).unwrap(), file.code);
+        }
+
+        // Unsafe names stay inside the target directory
+        let written = write_code_files(&[code_file("../My EA?.mq5", "// ea", true)], &out).unwrap();
+        assert_eq!(written, vec![out.join("My_EA_.mq5")]);
+
+        let zip_path = dir.path().join("My_EA.zip");
+        write_code_zip(&files, &zip_path).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+        assert_eq!(archive.len(), files.len());
+        for (i, file) in files.iter().enumerate() {
+            let mut entry = archive.by_index(i).unwrap();
+            assert_eq!(entry.name(), file.filename);
+            let mut code = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut code).unwrap();
+            assert_eq!(code, file.code);
+        }
+    }
+
+    #[test]
+    fn test_trade_journal_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.csv");
+        let trades = vec![trade(405.0, 1.0, Some(20.0)), trade(-605.0, 2.0, Some(30.0)), trade(100.0, 1.0, None)];
+        let options = TradeJournalOptions { initial_capital: 10_000.0, pip_value: 10.0 };
+        write_trade_journal_csv(&trades, &options, &path).unwrap();
+
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        let headers = rdr.headers().unwrap().clone();
+        let col = |name: &str| headers.iter().position(|h| h == name).unwrap();
+        for name in ["Entry Reason", "Exit Reason", "MAE Pips", "MAE", "MFE Pips", "MFE", "R-Multiple", "Bars Held", "Duration", "Equity", "Drawdown"] {
+            assert!(headers.iter().any(|h| h == name), "missing column {}", name);
+        }
+
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 3);
+        // Net 400 on 200 risked (20 pips × $10 × 1 lot) → 2R
+        assert_eq!(&rows[0][col("R-Multiple")], "2.00");
+        assert_eq!(&rows[0][col("Equity")], "10400.00");
+        assert_eq!(&rows[0][col("MAE")], "200.00");
+        // Net −610 on 600 risked (30 pips × $10 × 2 lots)
+        assert_eq!(&rows[1][col("R-Multiple")], "-1.02");
+        assert_eq!(&rows[1][col("MFE")], "700.00");
+        assert_eq!(&rows[1][col("Equity")], "9790.00");
+        assert_eq!(&rows[1][col("Drawdown")], "610.00");
+        // No stop loss → no R-multiple; drawdown measured from the 10 400 peak
+        assert_eq!(&rows[2][col("R-Multiple")], "");
+        assert_eq!(&rows[2][col("Drawdown")], "515.00");
+        assert_eq!(&rows[2][col("Entry Reason")], "Long entry rules (1)");
+    }
+
+    fn opt_result(period: f64, mult: f64, ret: f64) -> OptimizationResult {
+        OptimizationResult {
+            params: [("SMA period".to_string(), period), ("ATR mult".to_string(), mult)].into_iter().collect(),
+            objective_value: ret,
+            composite_score: ret,
+            total_return_pct: ret,
+            sharpe_ratio: 1.0,
+            max_drawdown_pct: 5.0,
+            total_trades: 40,
+            profit_factor: 1.5,
+            return_dd_ratio: ret / 5.0,
+            win_rate_pct: 55.0,
+            stagnation_bars: 120,
+            ulcer_index_pct: 2.0,
+            net_profit: ret * 100.0,
+            sortino_ratio: 1.2,
+            calmar_ratio: 0.8,
+            expectancy: 10.0,
+            recovery_factor: 3.0,
+            oos_results: Vec::new(),
+            equity_curve: Vec::new(),
+            failure_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_optimization_csv_rows_and_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("optimization.csv");
+        let mut results = vec![opt_result(20.0, 1.5, 12.0), opt_result(10.0, 2.0, 8.0), opt_result(30.0, 2.5, -3.0)];
+        results[0].oos_results.push(OosResult {
+            label: "2024".into(),
+            total_return_pct: 4.25,
+            sharpe_ratio: 0.9,
+            max_drawdown_pct: 3.0,
+            profit_factor: 1.3,
+            total_trades: 12,
+        });
+        let params = vec!["SMA period".to_string(), "ATR mult".to_string()];
+        write_optimization_csv(&results, &params, &["2024".to_string()], &path).unwrap();
+
+        let mut rdr = csv::Reader::from_path(&path).unwrap();
+        let headers = rdr.headers().unwrap().clone();
+        assert_eq!(&headers[0], "Rank");
+        assert_eq!(&headers[1], "SMA period");
+        assert_eq!(&headers[2], "ATR mult");
+        assert_eq!(headers.len(), 1 + 2 + 16 + 5);
+        let col = |name: &str| headers.iter().position(|h| h == name).unwrap_or_else(|| panic!("missing column {name}"));
+        for name in ["Objective", "Total Return %", "Max Drawdown %", "Recovery Factor", "OOS 2024 Return %", "OOS 2024 Trades"] {
+            col(name);
+        }
+
+        let rows: Vec<csv::StringRecord> = rdr.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), results.len());
+        assert_eq!(&rows[1][col("Rank")], "2");
+        assert_eq!(&rows[1][col("SMA period")], "10");
+        assert_eq!(&rows[1][col("ATR mult")], "2");
+        assert_eq!(&rows[2][col("Total Return %")], "-3.00");
+        assert_eq!(&rows[0][col("OOS 2024 Return %")], "4.25");
+        assert_eq!(&rows[0][col("OOS 2024 Trades")], "12");
+        // Not validated out of sample → empty OOS cells
+        assert_eq!(&rows[1][col("OOS 2024 Return %")], "");
+    }
+}
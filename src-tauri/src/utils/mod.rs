@@ -1,2 +1,4 @@
+pub mod bundle;
+pub mod chart_png;
 pub mod codegen;
 pub mod export;
@@ -2,26 +2,50 @@ import { useState } from "react";
 import { useTranslation } from "react-i18next";
 import { toast } from "sonner";
 import { useAppStore } from "@/stores/useAppStore";
-import { exportTradesCsv, exportReportHtml } from "@/lib/tauri";
+import { exportTradesCsv, exportReportHtml, exportEquityPng, saveBacktestResult } from "@/lib/tauri";
 import { save } from "@tauri-apps/plugin-dialog";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/Card";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/Tabs";
 import { Button } from "@/components/ui/Button";
-import { BarChart3, Download, FileSpreadsheet, Loader2 } from "lucide-react";
+import { BarChart3, Download, FileSpreadsheet, ImageDown, Loader2, Save } from "lucide-react";
 import { BacktestPanel } from "./BacktestPanel";
 import { MetricsGrid } from "./MetricsGrid";
 import { EquityCurve } from "./EquityCurve";
 import { DrawdownChart } from "./DrawdownChart";
+import { RollingRatiosChart } from "./RollingRatiosChart";
 import { MonthlyReturns } from "./MonthlyReturns";
 import { MonthlyReturnsGrid } from "./MonthlyReturnsGrid";
+import { SeasonalityChart } from "./SeasonalityChart";
 import { TradesList } from "./TradesList";
 
 export function BacktestPage() {
   const { t } = useTranslation("backtest");
   const { t: tc } = useTranslation("common");
-  const { backtestResults, initialCapital, equityMarkers } = useAppStore();
+  const { backtestResults, initialCapital, equityMarkers, symbols, selectedSymbolId, currentStrategy } = useAppStore();
   const [exportingTrades, setExportingTrades] = useState(false);
   const [exportingReport, setExportingReport] = useState(false);
+  const [exportingPng, setExportingPng] = useState(false);
+  const [savingRun, setSavingRun] = useState(false);
+
+  const handleSaveRun = async () => {
+    if (!backtestResults) return;
+    setSavingRun(true);
+    try {
+      const strategy = {
+        ...currentStrategy,
+        id: currentStrategy.id ?? "",
+        created_at: currentStrategy.created_at ?? "",
+        updated_at: currentStrategy.updated_at ?? "",
+      };
+      const meta = await saveBacktestResult(strategy, backtestResults);
+      toast.success(tc("toast.backtestSaved", { name: meta.name }));
+    } catch (err) {
+      const msg = err instanceof Error ? err.message : String(err);
+      toast.error(`${tc("toast.saveErr")}: ${msg}`);
+    } finally {
+      setSavingRun(false);
+    }
+  };
 
   const handleExportTrades = async () => {
     if (!backtestResults) return;
@@ -32,7 +56,12 @@ export function BacktestPage() {
     if (!path) return;
     setExportingTrades(true);
     try {
-      await exportTradesCsv(backtestResults.trades, path);
+      const pipValue = symbols.find((s) => s.id === selectedSymbolId)?.instrument_config.pip_value;
+      await exportTradesCsv(
+        backtestResults.trades,
+        path,
+        pipValue !== undefined ? { initial_capital: initialCapital, pip_value: pipValue } : undefined
+      );
       toast.success(tc("toast.exportTradesOk"));
     } catch (err) {
       const msg = err instanceof Error ? err.message : String(err);
@@ -61,6 +90,25 @@ export function BacktestPage() {
     }
   };
 
+  const handleExportPng = async () => {
+    if (!backtestResults) return;
+    const path = await save({
+      defaultPath: "equity_curve.png",
+      filters: [{ name: "PNG", extensions: ["png"] }],
+    });
+    if (!path) return;
+    setExportingPng(true);
+    try {
+      await exportEquityPng(backtestResults, path, { width: 1200, height: 600, include_drawdown: true });
+      toast.success(tc("toast.exportPngOk"));
+    } catch (err) {
+      const msg = err instanceof Error ? err.message : String(err);
+      toast.error(`${tc("toast.exportErr")}: ${msg}`);
+    } finally {
+      setExportingPng(false);
+    }
+  };
+
   return (
     <div className="mx-auto max-w-[1400px] space-y-4">
       <BacktestPanel />
@@ -80,6 +128,18 @@ export function BacktestPage() {
             <CardHeader className="flex flex-row items-center justify-between pb-3">
               <CardTitle>{t("performanceMetrics")}</CardTitle>
               <div className="flex gap-2">
+                <Button
+                  variant="outline"
+                  size="sm"
+                  className="h-8 text-sm"
+                  onClick={handleSaveRun}
+                  disabled={savingRun}
+                >
+                  {savingRun
+                    ? <Loader2 className="mr-1.5 h-3.5 w-3.5 animate-spin" />
+                    : <Save className="mr-1.5 h-3.5 w-3.5" />}
+                  {t("saveRun")}
+                </Button>
                 <Button
                   variant="outline"
                   size="sm"
@@ -104,6 +164,18 @@ export function BacktestPage() {
                     : <FileSpreadsheet className="mr-1.5 h-3.5 w-3.5" />}
                   {t("exportReport")}
                 </Button>
+                <Button
+                  variant="outline"
+                  size="sm"
+                  className="h-8 text-sm"
+                  onClick={handleExportPng}
+                  disabled={exportingPng}
+                >
+                  {exportingPng
+                    ? <Loader2 className="mr-1.5 h-3.5 w-3.5 animate-spin" />
+                    : <ImageDown className="mr-1.5 h-3.5 w-3.5" />}
+                  {t("exportPng")}
+                </Button>
               </div>
             </CardHeader>
             <CardContent>
@@ -116,7 +188,7 @@ export function BacktestPage() {
               <CardTitle>{t("equityCurve")}</CardTitle>
             </CardHeader>
             <CardContent className="pb-2">
-              <EquityCurve data={backtestResults.equity_curve} initialCapital={initialCapital} markers={equityMarkers} />
+              <EquityCurve data={backtestResults.equity_curve} initialCapital={initialCapital} markers={equityMarkers} benchmark={backtestResults.benchmark_curve} />
             </CardContent>
             <CardHeader className="pb-2 pt-0">
               <CardTitle>{t("drawdown")}</CardTitle>
@@ -124,6 +196,16 @@ export function BacktestPage() {
             <CardContent>
               <DrawdownChart data={backtestResults.drawdown_curve} />
             </CardContent>
+            {backtestResults.rolling_ratios && backtestResults.rolling_ratios.length > 0 && (
+              <>
+                <CardHeader className="pb-2 pt-0">
+                  <CardTitle>{t("rollingRatios")}</CardTitle>
+                </CardHeader>
+                <CardContent>
+                  <RollingRatiosChart data={backtestResults.rolling_ratios} />
+                </CardContent>
+              </>
+            )}
           </Card>
 
           <Card>
@@ -133,6 +215,9 @@ export function BacktestPage() {
                   <TabsTrigger value="monthly">
                     {t("monthlyPerformance")}
                   </TabsTrigger>
+                  <TabsTrigger value="seasonality">
+                    {t("seasonality")}
+                  </TabsTrigger>
                   <TabsTrigger value="trades">
                     {t("trades")} ({backtestResults.trades.length})
                   </TabsTrigger>
@@ -147,6 +232,10 @@ export function BacktestPage() {
                   )}
                 </TabsContent>
 
+                <TabsContent value="seasonality" className="pt-4">
+                  <SeasonalityChart trades={backtestResults.trades} />
+                </TabsContent>
+
                 <TabsContent value="trades" className="pt-4">
                   <TradesList trades={backtestResults.trades} />
                 </TabsContent>
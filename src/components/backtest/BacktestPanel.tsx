@@ -2,9 +2,9 @@ import { useEffect, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "@/stores/useAppStore";
-import { runBacktest, cancelBacktest } from "@/lib/tauri";
-import { sortTimeframes, PRECISION_LABELS } from "@/lib/types";
-import type { BacktestConfig, BacktestPrecision, Strategy, Timeframe } from "@/lib/types";
+import { runBacktest, runBacktestStepped, cancelBacktest } from "@/lib/tauri";
+import { sortTimeframes, PRECISION_LABELS, TIMEFRAME_ORDER } from "@/lib/types";
+import type { BacktestConfig, BacktestStep, BacktestPrecision, DrawdownBasis, ExitPriority, QuoteConversion, RegimeIndicator, Strategy, Timeframe } from "@/lib/types";
 import { useAvailablePrecisions } from "@/hooks/useAvailablePrecisions";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/Card";
 import {
@@ -18,7 +18,7 @@ import { Input } from "@/components/ui/Input";
 import { Button } from "@/components/ui/Button";
 import { Progress } from "@/components/ui/Progress";
 import { DatePicker } from "@/components/ui/DatePicker";
-import { Play, Square, AlertCircle } from "lucide-react";
+import { Play, Square, AlertCircle, StepForward } from "lucide-react";
 
 function formatEta(seconds: number): string {
   if (seconds < 1) return "<1s";
@@ -42,6 +42,8 @@ const TIMEFRAME_LABELS: Record<string, string> = {
   d1: "D1",
 };
 
+const MAGNIFIER_TIMEFRAMES: Timeframe[] = ["m1", "m5", "m15"];
+
 export function BacktestPanel() {
   const { t } = useTranslation("backtest");
   const { t: tc } = useTranslation("common");
@@ -72,7 +74,23 @@ export function BacktestPanel() {
   } = useAppStore();
 
   const [error, setError] = useState<string | null>(null);
+  const [magnifierTimeframe, setMagnifierTimeframe] = useState<Timeframe>("m1");
+  const [accountCurrency, setAccountCurrency] = useState("");
+  // Extra bars skipped before the first signal; 0 = strategy lookback only
+  const [warmupBars, setWarmupBars] = useState(0);
+  const [exitPriority, setExitPriority] = useState<ExitPriority>("SignalFirst");
+  const [drawdownBasis, setDrawdownBasis] = useState<DrawdownBasis>("BarClose");
+  const [overtradingThreshold, setOvertradingThreshold] = useState(5);
+  const [regimeIndicator, setRegimeIndicator] = useState<RegimeIndicator | "off">("off");
+  const [regimePeriod, setRegimePeriod] = useState(14);
+  const [regimeThreshold, setRegimeThreshold] = useState(25);
+  // "none", "constant" or "symbol:<id>"
+  const [conversionSource, setConversionSource] = useState("none");
+  const [conversionRate, setConversionRate] = useState(1);
+  const [conversionInvert, setConversionInvert] = useState(false);
   const [eta, setEta] = useState<string>("");
+  // Live status of a replay run: trades closed and equity at the last step
+  const [replay, setReplay] = useState<{ trades: number; equity: number; inPosition: boolean } | null>(null);
   const startTimeRef = useRef<number>(0);
   const unlistenRef = useRef<(() => void) | null>(null);
 
@@ -84,6 +102,22 @@ export function BacktestPanel() {
   // Available precision modes depend on symbol base timeframe
   const availablePrecisions = useAvailablePrecisions(selectedSymbol);
 
+  // Sub-bar resolutions stored for the symbol that are finer than the selected timeframe
+  const selectedTfIndex = TIMEFRAME_ORDER.indexOf(selectedTimeframe);
+  const availableMagnifiers = MAGNIFIER_TIMEFRAMES.filter(
+    (tf) =>
+      !!selectedSymbol?.timeframe_paths[tf] &&
+      (selectedTfIndex < 0 || TIMEFRAME_ORDER.indexOf(tf) < selectedTfIndex)
+  );
+  const magnifier = availableMagnifiers.includes(magnifierTimeframe) ? magnifierTimeframe : "m1";
+
+  const quoteConversion: QuoteConversion | undefined =
+    conversionSource === "constant"
+      ? { type: "constant", rate: conversionRate }
+      : conversionSource.startsWith("symbol:")
+        ? { type: "symbol", symbol_id: conversionSource.slice("symbol:".length), invert: conversionInvert }
+        : undefined;
+
   // Auto-fill dates when symbol changes
   useEffect(() => {
     if (selectedSymbol) {
@@ -143,7 +177,11 @@ export function BacktestPanel() {
     return null;
   };
 
-  const handleRun = async () => {
+  // Replay emits a snapshot every REPLAY_STEP_BARS bars, pausing REPLAY_STEP_DELAY_MS between them
+  const REPLAY_STEP_BARS = 50;
+  const REPLAY_STEP_DELAY_MS = 30;
+
+  const handleRun = async (stepped = false) => {
     const validationError = validate();
     if (validationError) {
       setError(validationError);
@@ -155,22 +193,35 @@ export function BacktestPanel() {
     setBacktestResults(null);
     setEquityMarkers([]);
     setEta("");
+    setReplay(null);
     startTimeRef.current = Date.now();
 
-    // Listen to progress events
-    unlistenRef.current = await listen<{
-      percent: number;
-      current_bar: number;
-      total_bars: number;
-    }>("backtest-progress", (event) => {
-      const pct = event.payload.percent;
-      setProgress(pct);
-      if (pct > 2) {
-        const elapsed = (Date.now() - startTimeRef.current) / 1000;
-        const remaining = (elapsed / pct) * (100 - pct);
-        setEta(formatEta(remaining));
-      }
-    });
+    if (stepped) {
+      let trades = 0;
+      let equity = initialCapital;
+      unlistenRef.current = await listen<BacktestStep>("backtest-step", (event) => {
+        const step = event.payload;
+        trades += step.new_trades.length;
+        if (step.equity_points.length > 0) equity = step.equity_points[step.equity_points.length - 1].equity;
+        setProgress(step.total_bars > 0 ? Math.round(((step.bar_index + 1) / step.total_bars) * 100) : 0);
+        setReplay({ trades, equity, inPosition: step.in_position });
+      });
+    } else {
+      // Listen to progress events
+      unlistenRef.current = await listen<{
+        percent: number;
+        current_bar: number;
+        total_bars: number;
+      }>("backtest-progress", (event) => {
+        const pct = event.payload.percent;
+        setProgress(pct);
+        if (pct > 2) {
+          const elapsed = (Date.now() - startTimeRef.current) / 1000;
+          const remaining = (elapsed / pct) * (100 - pct);
+          setEta(formatEta(remaining));
+        }
+      });
+    }
 
     try {
       const strategy: Strategy = {
@@ -191,6 +242,9 @@ export function BacktestPanel() {
         trading_hours: currentStrategy.trading_hours,
         max_daily_trades: currentStrategy.max_daily_trades,
         close_trades_at: currentStrategy.close_trades_at,
+        close_on_opposite_signal: currentStrategy.close_on_opposite_signal,
+        reverse_on_opposite_signal: currentStrategy.reverse_on_opposite_signal,
+        session_exit: currentStrategy.session_exit,
       };
 
       const config: BacktestConfig = {
@@ -201,9 +255,23 @@ export function BacktestPanel() {
         initial_capital: initialCapital,
         leverage,
         precision: backtestPrecision,
+        magnifier_timeframe:
+          backtestPrecision === "M1TickSimulation" && magnifier !== "m1" ? magnifier : undefined,
+        account_currency: accountCurrency.trim() || undefined,
+        quote_conversion: quoteConversion,
+        warmup_bars: warmupBars > 0 ? warmupBars : undefined,
+        exit_priority: exitPriority,
+        drawdown_basis: drawdownBasis,
+        overtrading_threshold: overtradingThreshold,
+        regime:
+          regimeIndicator === "off"
+            ? undefined
+            : { indicator: regimeIndicator, period: regimePeriod, threshold: regimeThreshold },
       };
 
-      const results = await runBacktest(strategy, config);
+      const results = stepped
+        ? await runBacktestStepped(strategy, config, REPLAY_STEP_BARS, REPLAY_STEP_DELAY_MS)
+        : await runBacktest(strategy, config);
       setBacktestResults(results);
     } catch (err) {
       const msg = typeof err === "string" ? err : err instanceof Error ? err.message : JSON.stringify(err);
@@ -218,6 +286,7 @@ export function BacktestPanel() {
         unlistenRef.current = null;
       }
       setLoading(false);
+      setReplay(null);
     }
   };
 
@@ -234,10 +303,16 @@ export function BacktestPanel() {
       {/* Actions bar */}
       <div className="flex items-center gap-3">
         {!isLoading ? (
-          <Button size="sm" onClick={handleRun} disabled={!canRun}>
-            <Play className="mr-1.5 h-4 w-4" />
-            {t("runBacktest")}
-          </Button>
+          <>
+            <Button size="sm" onClick={() => handleRun()} disabled={!canRun}>
+              <Play className="mr-1.5 h-4 w-4" />
+              {t("runBacktest")}
+            </Button>
+            <Button size="sm" variant="outline" onClick={() => handleRun(true)} disabled={!canRun}>
+              <StepForward className="mr-1.5 h-4 w-4" />
+              {t("replay")}
+            </Button>
+          </>
         ) : (
           <Button
             size="sm"
@@ -254,6 +329,13 @@ export function BacktestPanel() {
             <Progress value={progressPercent} className="flex-1" />
             <span className="whitespace-nowrap text-sm text-muted-foreground">
               {progressPercent}%{eta && <> | ETA: {eta}</>}
+              {replay && (
+                <>
+                  {" | "}
+                  {t("replayStatus", { trades: replay.trades, equity: replay.equity.toFixed(2) })}
+                  {replay.inPosition && <> | {t("replayInPosition")}</>}
+                </>
+              )}
             </span>
           </div>
         )}
@@ -331,10 +413,31 @@ export function BacktestPanel() {
                 </SelectContent>
               </Select>
             </div>
+
+            {backtestPrecision === "M1TickSimulation" && availableMagnifiers.length > 1 && (
+              <div className="space-y-1">
+                <label className="text-sm text-muted-foreground">{t("magnifierTimeframe")}</label>
+                <Select
+                  value={magnifier}
+                  onValueChange={(v) => setMagnifierTimeframe(v as Timeframe)}
+                >
+                  <SelectTrigger className="h-9 text-sm">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {availableMagnifiers.map((tf) => (
+                      <SelectItem key={tf} value={tf}>
+                        {TIMEFRAME_LABELS[tf]}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+            )}
           </div>
 
-          {/* Row 2: Start Date + End Date + Capital */}
-          <div className="grid grid-cols-2 gap-3 md:grid-cols-3">
+          {/* Row 2: Start Date + End Date + Capital + Warmup */}
+          <div className="grid grid-cols-2 gap-3 md:grid-cols-4">
             <div className="space-y-1">
               <label className="text-sm text-muted-foreground">{t("startDate")}</label>
               <DatePicker
@@ -362,6 +465,178 @@ export function BacktestPanel() {
                 onChange={(e) => setInitialCapital(Number(e.target.value))}
               />
             </div>
+
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("warmupBars")}</label>
+              <Input
+                type="number"
+                className="h-9 text-sm"
+                min={0}
+                step={1}
+                value={warmupBars}
+                onChange={(e) => setWarmupBars(Math.max(0, Math.floor(Number(e.target.value))))}
+              />
+            </div>
+          </div>
+
+          {/* Row 3: Account currency + quote conversion */}
+          <div className="grid grid-cols-2 gap-3 md:grid-cols-3">
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("accountCurrency")}</label>
+              <Input
+                className="h-9 text-sm"
+                placeholder="USD"
+                maxLength={3}
+                value={accountCurrency}
+                onChange={(e) => setAccountCurrency(e.target.value.toUpperCase())}
+              />
+            </div>
+
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("quoteConversion")}</label>
+              <Select value={conversionSource} onValueChange={setConversionSource}>
+                <SelectTrigger className="h-9 text-sm">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="none">{t("conversionNone")}</SelectItem>
+                  <SelectItem value="constant">{t("conversionConstant")}</SelectItem>
+                  {symbols
+                    .filter((s) => s.id !== selectedSymbolId)
+                    .map((s) => (
+                      <SelectItem key={s.id} value={`symbol:${s.id}`}>
+                        {s.name}
+                      </SelectItem>
+                    ))}
+                </SelectContent>
+              </Select>
+            </div>
+
+            {conversionSource === "constant" && (
+              <div className="space-y-1">
+                <label className="text-sm text-muted-foreground">{t("conversionRate")}</label>
+                <Input
+                  type="number"
+                  className="h-9 text-sm"
+                  min={0}
+                  step="any"
+                  value={conversionRate}
+                  onChange={(e) => setConversionRate(Number(e.target.value))}
+                />
+              </div>
+            )}
+
+            {conversionSource.startsWith("symbol:") && (
+              <div className="space-y-1">
+                <label className="text-sm text-muted-foreground">{t("conversionDirection")}</label>
+                <Select
+                  value={conversionInvert ? "inverted" : "direct"}
+                  onValueChange={(v) => setConversionInvert(v === "inverted")}
+                >
+                  <SelectTrigger className="h-9 text-sm">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="direct">{t("conversionDirect")}</SelectItem>
+                    <SelectItem value="inverted">{t("conversionInverted")}</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+            )}
+          </div>
+
+          {/* Row 4: Same-bar exit resolution */}
+          <div className="grid grid-cols-2 gap-3 md:grid-cols-3">
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("exitPriority")}</label>
+              <Select value={exitPriority} onValueChange={(v) => setExitPriority(v as ExitPriority)}>
+                <SelectTrigger className="h-9 text-sm" title={t("exitPriorityHint")}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="SignalFirst">{t("exitPrioritySignalFirst")}</SelectItem>
+                  <SelectItem value="StopsFirst">{t("exitPriorityStopsFirst")}</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("drawdownBasis")}</label>
+              <Select value={drawdownBasis} onValueChange={(v) => setDrawdownBasis(v as DrawdownBasis)}>
+                <SelectTrigger className="h-9 text-sm" title={t("drawdownBasisHint")}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="BarClose">{t("drawdownBasisBarClose")}</SelectItem>
+                  <SelectItem value="Intrabar">{t("drawdownBasisIntrabar")}</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("overtradingThreshold")}</label>
+              <Input
+                type="number"
+                className="h-9 text-sm"
+                min={1}
+                step={1}
+                title={t("overtradingThresholdHint")}
+                value={overtradingThreshold}
+                onChange={(e) => setOvertradingThreshold(Math.max(1, Math.floor(Number(e.target.value))))}
+              />
+            </div>
+          </div>
+
+          {/* Row 5: Market regime tagging */}
+          <div className="grid grid-cols-2 gap-3 md:grid-cols-3">
+            <div className="space-y-1">
+              <label className="text-sm text-muted-foreground">{t("regimeIndicator")}</label>
+              <Select
+                value={regimeIndicator}
+                onValueChange={(v) => {
+                  const next = v as RegimeIndicator | "off";
+                  setRegimeIndicator(next);
+                  // Conventional split points: ADX 25, Choppiness 50
+                  if (next === "ADX") setRegimeThreshold(25);
+                  if (next === "Choppiness") setRegimeThreshold(50);
+                }}
+              >
+                <SelectTrigger className="h-9 text-sm" title={t("regimeIndicatorHint")}>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="off">{t("regimeOff")}</SelectItem>
+                  <SelectItem value="ADX">ADX</SelectItem>
+                  <SelectItem value="Choppiness">Choppiness</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+            {regimeIndicator !== "off" && (
+              <>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">{t("regimePeriod")}</label>
+                  <Input
+                    type="number"
+                    className="h-9 text-sm"
+                    min={2}
+                    step={1}
+                    value={regimePeriod}
+                    onChange={(e) => setRegimePeriod(Math.max(2, Math.floor(Number(e.target.value))))}
+                  />
+                </div>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">{t("regimeThreshold")}</label>
+                  <Input
+                    type="number"
+                    className="h-9 text-sm"
+                    min={0}
+                    max={100}
+                    step={0.5}
+                    title={regimeIndicator === "ADX" ? t("regimeThresholdHintAdx") : t("regimeThresholdHintChop")}
+                    value={regimeThreshold}
+                    onChange={(e) => setRegimeThreshold(Number(e.target.value))}
+                  />
+                </div>
+              </>
+            )}
           </div>
         </CardContent>
       </Card>
@@ -24,12 +24,15 @@ interface EquityCurveProps {
   data: EquityPoint[];
   initialCapital: number;
   markers?: EquityMarker[];
+  /** Buy-and-hold curve with one point per `data` point. */
+  benchmark?: EquityPoint[];
 }
 
 interface ChartPoint {
   timestamp: string;
   equity: number;
   newHigh: number | undefined;
+  benchmark: number | undefined;
 }
 
 function extractYear(ts: string): string {
@@ -42,15 +45,19 @@ function HighDot(props: Record<string, unknown>) {
   return <circle cx={cx} cy={cy} r={3.5} fill="hsl(217 90% 60%)" stroke="hsl(217 90% 42%)" strokeWidth={1} />;
 }
 
-export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveProps) {
+export function EquityCurve({ data, initialCapital, markers = [], benchmark = [] }: EquityCurveProps) {
   const { t } = useTranslation("backtest");
   const { GRID_COLOR, GRID_DASH, AXIS_TICK, AXIS_STROKE, TOOLTIP_STYLE } = getChartTheme();
   const [showStagnation, setShowStagnation] = useState(false);
   const [showNewHighs, setShowNewHighs] = useState(false);
+  const [showBenchmark, setShowBenchmark] = useState(false);
+  const hasBenchmark = benchmark.length === data.length && data.length > 0;
 
   const maxPoints = 1000;
   const step = Math.max(1, Math.floor(data.length / maxPoints));
-  const sampled = step > 1 ? data.filter((_, i) => i % step === 0 || i === data.length - 1) : data;
+  const keep = (_: unknown, i: number) => i % step === 0 || i === data.length - 1;
+  const sampled = step > 1 ? data.filter(keep) : data;
+  const sampledBenchmark = hasBenchmark ? (step > 1 ? benchmark.filter(keep) : benchmark) : [];
 
   const { chartData, longestStag, longestStagDays } = useMemo(() => {
     let peak = -Infinity;
@@ -76,6 +83,7 @@ export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveP
         timestamp: p.timestamp,
         equity: p.equity,
         newHigh: isNew ? p.equity : undefined,
+        benchmark: sampledBenchmark[i]?.equity,
       });
     }
     if (stagnationStartIdx >= 0 && stagnationStartIdx < sampled.length - 1) {
@@ -98,7 +106,7 @@ export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveP
     }
 
     return { chartData: points, longestStag: longest, longestStagDays: days };
-  }, [sampled]);
+  }, [sampled, sampledBenchmark]);
 
   if (data.length === 0) return null;
 
@@ -164,6 +172,17 @@ export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveP
           />
           {t("newHighs")}
         </label>
+        {hasBenchmark && (
+          <label className="flex cursor-pointer items-center gap-1.5 text-sm text-muted-foreground">
+            <input
+              type="checkbox"
+              checked={showBenchmark}
+              onChange={(e) => setShowBenchmark(e.target.checked)}
+              className="h-3 w-3 rounded border-border accent-blue-500"
+            />
+            {t("buyAndHold")}
+          </label>
+        )}
       </div>
 
       <ResponsiveContainer width="100%" height={400}>
@@ -230,6 +249,9 @@ export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveP
             labelFormatter={(label: string) => label}
             formatter={(value: number, name: string) => {
               if (name === "newHigh") return [null, null];
+              if (name === "benchmark") {
+                return [`$${value.toLocaleString(undefined, { maximumFractionDigits: 2 })}`, t("buyAndHold")];
+              }
               return [`$${value.toLocaleString(undefined, { maximumFractionDigits: 2 })}`, t("equityCurve")];
             }}
           />
@@ -264,6 +286,18 @@ export function EquityCurve({ data, initialCapital, markers = [] }: EquityCurveP
             isAnimationActive={true}
           />
 
+          {showBenchmark && (
+            <Line
+              type="monotone"
+              dataKey="benchmark"
+              stroke={CHART_COLORS.blue}
+              strokeWidth={1}
+              strokeDasharray="4 3"
+              dot={false}
+              isAnimationActive={false}
+            />
+          )}
+
           {resolvedMarkers.map((m, i) => (
             <ReferenceLine
               key={`marker-${i}`}
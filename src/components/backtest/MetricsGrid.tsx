@@ -109,11 +109,16 @@ function buildGroups(m: BacktestMetrics, t: TFunction): MetricGroup[] {
     },
     {
       title: t("metrics.drawdownGroup"),
+      wide: true,
       items: [
         { label: t("metrics.maxDd"), value: fmtPct(m.max_drawdown_pct), color: "red" },
         { label: t("metrics.maxDdDuration"), value: m.max_drawdown_duration_time },
         { label: t("metrics.avgDd"), value: fmtPct(m.avg_drawdown_pct) },
         { label: t("metrics.recoveryFactor"), value: fmt(m.recovery_factor), tooltip: t("tooltips.recoveryFactor") },
+        { label: t("metrics.longestDd"), value: m.longest_drawdown_time, tooltip: t("tooltips.longestDd") },
+        { label: t("metrics.maxDdRecovery"), value: m.max_drawdown_recovery_time, tooltip: t("tooltips.maxDdRecovery") },
+        { label: t("metrics.avgDdDepth"), value: fmtPct(m.avg_drawdown_depth_pct), tooltip: t("tooltips.avgDdDepth") },
+        { label: t("metrics.avgDdDuration"), value: `${fmt(m.avg_drawdown_duration_bars, 1)} ${t("metrics.bars")}` },
       ],
     },
     {
@@ -148,6 +153,60 @@ function buildGroups(m: BacktestMetrics, t: TFunction): MetricGroup[] {
         { label: t("metrics.avgConsecLosses"), value: fmt(m.avg_consecutive_losses, 1) },
       ],
     },
+    {
+      title: t("metrics.benchmark"),
+      items: [
+        { label: t("metrics.buyAndHold"), value: fmtPct(m.benchmark_return_pct), color: colorBySign(m.benchmark_return_pct) },
+        { label: t("metrics.alpha"), value: fmtPct(m.alpha), color: colorBySign(m.alpha), tooltip: t("tooltips.alpha") },
+        { label: t("metrics.beta"), value: fmt(m.beta), tooltip: t("tooltips.beta") },
+        { label: t("metrics.correlation"), value: fmt(m.correlation), tooltip: t("tooltips.correlation") },
+      ],
+    },
+    {
+      title: t("metrics.exposureGroup"),
+      items: [
+        { label: t("metrics.timeInMarket"), value: fmtPct(m.exposure_pct), tooltip: t("tooltips.timeInMarket") },
+        { label: t("metrics.avgPositions"), value: fmt(m.avg_simultaneous_positions), tooltip: t("tooltips.avgPositions") },
+        { label: t("metrics.longExposure"), value: fmtPct(m.long_exposure_pct) },
+        { label: t("metrics.shortExposure"), value: fmtPct(m.short_exposure_pct) },
+      ],
+    },
+    {
+      title: t("metrics.clusteringGroup"),
+      items: [
+        { label: t("metrics.avgTimeBetweenTrades"), value: `${fmt(m.avg_minutes_between_trades ?? 0, 0)}m` },
+        { label: t("metrics.clusteredTrades"), value: String(m.clustered_trades ?? 0), tooltip: t("tooltips.clusteredTrades") },
+        { label: t("metrics.maxTradesPerHour"), value: String(m.max_trades_per_hour ?? 0), tooltip: t("tooltips.maxTradesPerHour") },
+        {
+          label: t("metrics.overtrading"),
+          value: m.overtrading ? t("metrics.overtradingYes") : t("metrics.overtradingNo"),
+          color: m.overtrading ? "red" : "neutral",
+          tooltip: t("tooltips.overtrading"),
+        },
+      ],
+    },
+    {
+      title: t("metrics.regimeGroup"),
+      items: [
+        { label: t("metrics.trendingTrades"), value: String(m.trending_trades ?? 0), tooltip: t("tooltips.regime") },
+        { label: t("metrics.trendingWinRate"), value: fmtPct(m.trending_win_rate_pct ?? 0) },
+        { label: t("metrics.trendingNetProfit"), value: `$${fmt(m.trending_net_profit ?? 0)}`, color: colorBySign(m.trending_net_profit ?? 0) },
+        { label: t("metrics.rangingTrades"), value: String(m.ranging_trades ?? 0), tooltip: t("tooltips.regime") },
+        { label: t("metrics.rangingWinRate"), value: fmtPct(m.ranging_win_rate_pct ?? 0) },
+        { label: t("metrics.rangingNetProfit"), value: `$${fmt(m.ranging_net_profit ?? 0)}`, color: colorBySign(m.ranging_net_profit ?? 0) },
+      ],
+    },
+    {
+      title: t("metrics.costsGroup"),
+      items: [
+        { label: t("metrics.grossBeforeCosts"), value: `$${fmt(m.gross_pnl_before_costs)}`, color: colorBySign(m.gross_pnl_before_costs), tooltip: t("tooltips.grossBeforeCosts") },
+        { label: t("metrics.totalCosts"), value: `$${fmt(m.total_costs)}`, color: "red", tooltip: t("tooltips.totalCosts") },
+        { label: t("metrics.spreadCost"), value: `$${fmt(m.total_spread_cost)}` },
+        { label: t("metrics.slippageCost"), value: `$${fmt(m.total_slippage_cost)}` },
+        { label: t("metrics.commissionCost"), value: `$${fmt(m.total_commission_charged)}` },
+        { label: t("metrics.swapTotal"), value: `$${fmt(m.total_swap_charged)}`, color: colorBySign(m.total_swap_charged), tooltip: t("tooltips.swapTotal") },
+      ],
+    },
     {
       title: t("metrics.riskAnalytics"),
       wide: true,
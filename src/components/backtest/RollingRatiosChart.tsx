@@ -0,0 +1,80 @@
+import { useTranslation } from "react-i18next";
+import {
+  ResponsiveContainer,
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  Legend,
+  ReferenceLine,
+} from "recharts";
+import type { RollingRatioPoint } from "@/lib/types";
+import { getChartTheme, CHART_COLORS } from "@/lib/chartTheme";
+
+interface RollingRatiosChartProps {
+  data: RollingRatioPoint[];
+}
+
+function formatTimestamp(ts: string): string {
+  return ts.slice(0, 10);
+}
+
+export function RollingRatiosChart({ data }: RollingRatiosChartProps) {
+  const { t } = useTranslation("backtest");
+  const { GRID_COLOR, GRID_DASH, AXIS_TICK, AXIS_STROKE, TOOLTIP_STYLE } = getChartTheme();
+
+  const filled = data.filter((p) => p.sharpe !== null);
+  if (filled.length === 0) return null;
+
+  const maxPoints = 1000;
+  const step = Math.max(1, Math.floor(filled.length / maxPoints));
+  const sampled = step > 1 ? filled.filter((_, i) => i % step === 0 || i === filled.length - 1) : filled;
+
+  return (
+    <ResponsiveContainer width="100%" height={160}>
+      <LineChart data={sampled} margin={{ top: 5, right: 20, bottom: 5, left: 10 }}>
+        <CartesianGrid strokeDasharray={GRID_DASH} stroke={GRID_COLOR} />
+        <XAxis
+          dataKey="timestamp"
+          tickFormatter={formatTimestamp}
+          tick={AXIS_TICK}
+          stroke={AXIS_STROKE}
+          interval="preserveStartEnd"
+        />
+        <YAxis
+          tickFormatter={(v: number) => v.toFixed(1)}
+          tick={AXIS_TICK}
+          stroke={AXIS_STROKE}
+          width={60}
+        />
+        <ReferenceLine y={0} stroke={AXIS_STROKE} />
+        <Tooltip
+          contentStyle={TOOLTIP_STYLE}
+          labelFormatter={(label: string) => label}
+          formatter={(value: number, name: string) => [value.toFixed(2), name]}
+        />
+        <Legend wrapperStyle={{ fontSize: 12 }} />
+        <Line
+          type="monotone"
+          dataKey="sharpe"
+          name={t("rollingSharpe")}
+          stroke={CHART_COLORS.blue}
+          dot={false}
+          strokeWidth={1.5}
+          animationDuration={500}
+        />
+        <Line
+          type="monotone"
+          dataKey="sortino"
+          name={t("rollingSortino")}
+          stroke={CHART_COLORS.purple}
+          dot={false}
+          strokeWidth={1.5}
+          animationDuration={500}
+        />
+      </LineChart>
+    </ResponsiveContainer>
+  );
+}
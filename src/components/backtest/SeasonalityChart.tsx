@@ -0,0 +1,95 @@
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import {
+  ResponsiveContainer,
+  BarChart,
+  Bar,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  Cell,
+  ReferenceLine,
+} from "recharts";
+import { seasonalityReport } from "@/lib/tauri";
+import type { SeasonalityBucket, SeasonalityReport, TradeResult } from "@/lib/types";
+import { getChartTheme, CHART_COLORS } from "@/lib/chartTheme";
+
+const WEEKDAY_KEYS = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+
+interface SeasonalityChartProps {
+  trades: TradeResult[];
+}
+
+interface BucketChartProps {
+  title: string;
+  data: (SeasonalityBucket & { label: string })[];
+}
+
+function BucketChart({ title, data }: BucketChartProps) {
+  const { t } = useTranslation("backtest");
+  const { GRID_COLOR, GRID_DASH, AXIS_TICK, AXIS_STROKE, TOOLTIP_STYLE } = getChartTheme();
+
+  return (
+    <div className="space-y-1">
+      <p className="text-sm font-medium text-muted-foreground">{title}</p>
+      <ResponsiveContainer width="100%" height={200}>
+        <BarChart data={data} margin={{ top: 5, right: 20, bottom: 5, left: 10 }}>
+          <CartesianGrid strokeDasharray={GRID_DASH} stroke={GRID_COLOR} />
+          <XAxis dataKey="label" tick={AXIS_TICK} stroke={AXIS_STROKE} interval={0} />
+          <YAxis
+            tickFormatter={(v: number) => `$${v.toFixed(0)}`}
+            tick={AXIS_TICK}
+            stroke={AXIS_STROKE}
+            width={60}
+          />
+          <ReferenceLine y={0} stroke={AXIS_STROKE} />
+          <Tooltip
+            contentStyle={TOOLTIP_STYLE}
+            formatter={(value: number, _name: string, item: { payload?: SeasonalityBucket }) => {
+              const b = item.payload;
+              return [
+                `$${value.toFixed(2)} · ${b?.trade_count ?? 0} ${t("seasonalityTrades")} · ${(b?.win_rate_pct ?? 0).toFixed(1)}% ${t("seasonalityWinRate")}`,
+                t("seasonalityAvgPnl"),
+              ];
+            }}
+          />
+          <Bar dataKey="avg_pnl" animationDuration={500}>
+            {data.map((b) => (
+              <Cell key={b.bucket} fill={b.avg_pnl >= 0 ? CHART_COLORS.green : CHART_COLORS.red} />
+            ))}
+          </Bar>
+        </BarChart>
+      </ResponsiveContainer>
+    </div>
+  );
+}
+
+export function SeasonalityChart({ trades }: SeasonalityChartProps) {
+  const { t } = useTranslation("backtest");
+  const { t: tc } = useTranslation("common");
+  const [report, setReport] = useState<SeasonalityReport | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    seasonalityReport(trades)
+      .then((r) => { if (!cancelled) setReport(r); })
+      .catch(() => { if (!cancelled) setReport(null); });
+    return () => { cancelled = true; };
+  }, [trades]);
+
+  if (!report) return null;
+
+  const byHour = report.by_hour.map((b) => ({ ...b, label: String(b.bucket).padStart(2, "0") }));
+  const byWeekday = report.by_weekday.map((b) => ({
+    ...b,
+    label: tc(`days.${WEEKDAY_KEYS[b.bucket - 1]}`).slice(0, 3),
+  }));
+
+  return (
+    <div className="space-y-4">
+      <BucketChart title={t("seasonalityByHour")} data={byHour} />
+      <BucketChart title={t("seasonalityByWeekday")} data={byWeekday} />
+    </div>
+  );
+}
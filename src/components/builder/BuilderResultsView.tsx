@@ -76,10 +76,10 @@ function CodePanel({ saved }: { saved: BuilderSavedStrategy }) {
       let res: CodeGenerationResult;
       if (saved.isSr) {
         const srStrategy = JSON.parse(saved.strategyJson) as SrStrategy;
-        res = await generateSrCode(srStrategy, saved.name);
+        res = await generateSrCode(srStrategy, saved.name, saved.symbolId ?? undefined);
       } else {
         const strategy = JSON.parse(saved.strategyJson) as Strategy;
-        res = await generateStrategyCode(lang, strategy);
+        res = await generateStrategyCode(lang, strategy, undefined, saved.symbolId ?? undefined);
       }
       setResult(res);
       const mainIdx = res.files.findIndex((f) => f.is_main);
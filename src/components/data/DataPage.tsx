@@ -12,6 +12,8 @@ import {
   RefreshCw,
   X,
   AlertCircle,
+  ChevronLeft,
+  ChevronRight,
 } from "lucide-react";
 import { toast } from "sonner";
 import { Button } from "@/components/ui/Button";
@@ -39,10 +41,11 @@ import {
   getSymbols,
   deleteSymbol,
   previewData,
+  countRows,
   cancelDownload,
   transformSymbolTimezone,
 } from "@/lib/tauri";
-import { INSTRUMENT_PRESETS, type Symbol, type InstrumentConfig, type TickStorageFormat } from "@/lib/types";
+import { INSTRUMENT_PRESETS, type Symbol, type InstrumentConfig, type TickStorageFormat, type BarTimestamp, type BarIntegrityReport } from "@/lib/types";
 import { TIMEZONE_OPTIONS, formatTzOffset } from "@/lib/timezones";
 
 // ── Import CSV dialog ──────────────────────────────────────────────────────────
@@ -62,6 +65,7 @@ function ImportDialog({ open, onOpenChange, filePath }: ImportDialogProps) {
   const [preset, setPreset] = useState("Forex Major");
   const [config, setConfig] = useState<InstrumentConfig>(INSTRUMENT_PRESETS["Forex Major"]);
   const [storageFormat, setStorageFormat] = useState<TickStorageFormat>("Parquet");
+  const [repairBars, setRepairBars] = useState(false);
   const [importing, setImporting] = useState(false);
   const [progress, setProgress] = useState(0);
   const [progressMsg, setProgressMsg] = useState("");
@@ -91,8 +95,21 @@ function ImportDialog({ open, onOpenChange, filePath }: ImportDialogProps) {
         setProgressMsg(e.payload.message);
       }
     );
-    return () => { unlisten.then((fn) => fn()); };
-  }, [open]);
+    const unlistenIntegrity = listen<BarIntegrityReport>("bar-integrity", (e) => {
+      const r = e.payload;
+      const counts = {
+        inverted: r.inverted_high_low,
+        open: r.open_out_of_range,
+        close: r.close_out_of_range,
+        nonFinite: r.non_finite,
+      };
+      toast.warning(t(r.repaired ? "import.integrityRepaired" : "import.integrityFlagged", counts));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenIntegrity.then((fn) => fn());
+    };
+  }, [open, t]);
 
   const handlePresetChange = (value: string) => {
     setPreset(value);
@@ -106,7 +123,7 @@ function ImportDialog({ open, onOpenChange, filePath }: ImportDialogProps) {
     setError("");
     setImporting(true);
     try {
-      const symbol = await uploadCsv(filePath, symbolName.trim(), config, storageFormat);
+      const symbol = await uploadCsv(filePath, symbolName.trim(), config, storageFormat, repairBars);
       addSymbol(symbol);
       onOpenChange(false);
       toast.success(`${symbolName} imported successfully`);
@@ -188,6 +205,43 @@ function ImportDialog({ open, onOpenChange, filePath }: ImportDialogProps) {
             </p>
           </div>
 
+          {/* Bar timestamp convention — only relevant for bar CSV files */}
+          <div className="space-y-1">
+            <label className="text-sm font-medium">{t("import.barTimestamp")}</label>
+            <div className="flex gap-2">
+              {(["BarOpen", "BarClose"] as BarTimestamp[]).map((ts) => (
+                <button
+                  key={ts}
+                  onClick={() => setConfig({ ...config, timestamp_is: ts })}
+                  disabled={importing}
+                  className={`flex-1 rounded-md border px-3 py-1.5 text-xs font-medium transition-colors ${
+                    (config.timestamp_is ?? "BarOpen") === ts
+                      ? "border-primary bg-primary/10 text-primary"
+                      : "border-border bg-background text-muted-foreground hover:bg-muted/50"
+                  }`}
+                >
+                  {t(`import.barTimestamp${ts}`)}
+                </button>
+              ))}
+            </div>
+            <p className="text-xs text-muted-foreground">{t("import.barTimestampDesc")}</p>
+          </div>
+
+          {/* Bar integrity repair — only relevant for bar CSV files */}
+          <div className="space-y-1">
+            <label className="flex cursor-pointer items-center gap-2 text-sm font-medium">
+              <input
+                type="checkbox"
+                checked={repairBars}
+                onChange={(e) => setRepairBars(e.target.checked)}
+                disabled={importing}
+                className="h-3.5 w-3.5 rounded border-border accent-primary"
+              />
+              {t("import.repairBars")}
+            </label>
+            <p className="text-xs text-muted-foreground">{t("import.repairBarsDesc")}</p>
+          </div>
+
           {/* Key config fields */}
           <div className="grid grid-cols-3 gap-2">
             {[
@@ -238,6 +292,8 @@ function ImportDialog({ open, onOpenChange, filePath }: ImportDialogProps) {
 
 // ── Preview dialog ─────────────────────────────────────────────────────────────
 
+const PREVIEW_PAGE_SIZE = 50;
+
 interface PreviewDialogProps {
   symbol: Symbol | null;
   open: boolean;
@@ -250,6 +306,8 @@ function PreviewDialog({ symbol, open, onOpenChange }: PreviewDialogProps) {
   const [rows, setRows] = useState<Record<string, unknown>[]>([]);
   const [loading, setLoading] = useState(false);
   const [timeframe, setTimeframe] = useState("m1");
+  const [page, setPage] = useState(0);
+  const [totalRows, setTotalRows] = useState(0);
 
   const timeframes = symbol
     ? Object.keys(symbol.timeframe_paths).filter((tf) => !tf.includes("raw"))
@@ -259,12 +317,23 @@ function PreviewDialog({ symbol, open, onOpenChange }: PreviewDialogProps) {
     if (!open || !symbol) return;
     const tf = timeframes.includes(timeframe) ? timeframe : (timeframes[0] ?? "m1");
     setTimeframe(tf);
+    setPage(0);
+    countRows(symbol.id, tf)
+      .then(setTotalRows)
+      .catch((e) => toast.error(String(e)));
+  }, [open, symbol, timeframe]); // eslint-disable-line react-hooks/exhaustive-deps
+
+  useEffect(() => {
+    if (!open || !symbol || !timeframes.includes(timeframe)) return;
     setLoading(true);
-    previewData(symbol.id, tf, 50)
+    previewData(symbol.id, timeframe, page * PREVIEW_PAGE_SIZE, PREVIEW_PAGE_SIZE)
       .then(setRows)
       .catch((e) => toast.error(String(e)))
       .finally(() => setLoading(false));
-  }, [open, symbol, timeframe]); // eslint-disable-line react-hooks/exhaustive-deps
+  }, [open, symbol, timeframe, page]); // eslint-disable-line react-hooks/exhaustive-deps
+
+  const pageCount = Math.max(1, Math.ceil(totalRows / PREVIEW_PAGE_SIZE));
+  const firstRow = page * PREVIEW_PAGE_SIZE;
 
   const columns = rows.length > 0 ? Object.keys(rows[0]) : [];
 
@@ -301,9 +370,38 @@ function PreviewDialog({ symbol, open, onOpenChange }: PreviewDialogProps) {
             <p className="py-8 text-center text-sm text-muted-foreground">{t("noDataAvailable")}</p>
           ) : (
             <>
-              <p className="mb-2 text-xs text-muted-foreground">
-                {t("showingRows", { count: rows.length })}
-              </p>
+              <div className="mb-2 flex items-center justify-between">
+                <p className="text-xs text-muted-foreground">
+                  {t("rowRange", {
+                    from: (firstRow + 1).toLocaleString(),
+                    to: (firstRow + rows.length).toLocaleString(),
+                    total: totalRows.toLocaleString(),
+                  })}
+                </p>
+                <div className="flex items-center gap-1">
+                  <Button
+                    variant="outline"
+                    size="sm"
+                    disabled={page === 0}
+                    onClick={() => setPage((p) => p - 1)}
+                    title={t("previousPage")}
+                  >
+                    <ChevronLeft className="h-3.5 w-3.5" />
+                  </Button>
+                  <span className="px-1 text-xs text-muted-foreground">
+                    {page + 1} / {pageCount}
+                  </span>
+                  <Button
+                    variant="outline"
+                    size="sm"
+                    disabled={page + 1 >= pageCount}
+                    onClick={() => setPage((p) => p + 1)}
+                    title={t("nextPage")}
+                  >
+                    <ChevronRight className="h-3.5 w-3.5" />
+                  </Button>
+                </div>
+              </div>
               <table className="w-full text-xs">
                 <thead>
                   <tr className="border-b border-border">
@@ -84,6 +84,8 @@ export function DownloadDialog({ open: isOpen, onOpenChange, resumeSymbol }: Dow
   const [ignoreFlats, setIgnoreFlats] = useState(true);
   const [retryOnEmpty, setRetryOnEmpty] = useState(true);
   const [useCache, setUseCache] = useState(true);
+  const [concurrency, setConcurrency] = useState(8);
+  const [maxRequestsPerSecond, setMaxRequestsPerSecond] = useState(20);
 
   // Date range
   const [startDate, setStartDate] = useState("2024-01-01");
@@ -114,6 +116,8 @@ export function DownloadDialog({ open: isOpen, onOpenChange, resumeSymbol }: Dow
     setIgnoreFlats(p.ignore_flats ?? true);
     setRetryOnEmpty(p.retry_on_empty ?? true);
     setUseCache(p.use_cache ?? true);
+    setConcurrency(p.concurrency ?? 8);
+    setMaxRequestsPerSecond(p.max_requests_per_second ?? 20);
     setConfig(resumeSymbol.instrument_config);
   }, [resumeSymbol, isOpen]); // eslint-disable-line react-hooks/exhaustive-deps
 
@@ -194,7 +198,9 @@ export function DownloadDialog({ open: isOpen, onOpenChange, resumeSymbol }: Dow
       modeling === "tick" && pipeline === "via_csv" ? keepCsv : undefined,
       ignoreFlats,
       retryOnEmpty,
-      useCache
+      useCache,
+      concurrency,
+      maxRequestsPerSecond
     )
       .then((symbol) => {
         // If resuming an interrupted symbol, remove the old pending entry first
@@ -465,6 +471,30 @@ export function DownloadDialog({ open: isOpen, onOpenChange, resumeSymbol }: Dow
                 {t("downloadDialog.useCache")}
               </label>
             </div>
+            <div className="grid grid-cols-2 gap-2 pt-1">
+              <div className="space-y-1">
+                <label className="text-xs text-muted-foreground">{t("downloadDialog.concurrency")}</label>
+                <Input
+                  type="number"
+                  min={1}
+                  max={32}
+                  value={concurrency}
+                  onChange={(e) => setConcurrency(Math.min(32, Math.max(1, parseInt(e.target.value) || 1)))}
+                  className="h-8 text-xs"
+                />
+              </div>
+              <div className="space-y-1">
+                <label className="text-xs text-muted-foreground">{t("downloadDialog.maxRequestsPerSecond")}</label>
+                <Input
+                  type="number"
+                  min={0}
+                  step="any"
+                  value={maxRequestsPerSecond}
+                  onChange={(e) => setMaxRequestsPerSecond(Math.max(0, parseFloat(e.target.value) || 0))}
+                  className="h-8 text-xs"
+                />
+              </div>
+            </div>
           </div>
 
           {/* Date range */}
@@ -554,6 +584,30 @@ export function DownloadDialog({ open: isOpen, onOpenChange, resumeSymbol }: Dow
                 className="h-8 text-xs"
               />
             </div>
+            <div className="space-y-1">
+              <label className="text-xs text-muted-foreground">{t("lotStep")}</label>
+              <Input
+                type="number"
+                step="any"
+                value={config.lot_step ?? 0}
+                onChange={(e) =>
+                  setConfig({ ...config, lot_step: parseFloat(e.target.value) || 0 })
+                }
+                className="h-8 text-xs"
+              />
+            </div>
+            <div className="space-y-1">
+              <label className="text-xs text-muted-foreground">{t("maxLot")}</label>
+              <Input
+                type="number"
+                step="any"
+                value={config.max_lot ?? 0}
+                onChange={(e) =>
+                  setConfig({ ...config, max_lot: parseFloat(e.target.value) || 0 })
+                }
+                className="h-8 text-xs"
+              />
+            </div>
             <div className="space-y-1">
               <label className="text-xs text-muted-foreground">{t("tickSize")}</label>
               <Input
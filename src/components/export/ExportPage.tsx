@@ -1,17 +1,35 @@
 import { useState, useEffect, useCallback } from "react";
 import { useTranslation } from "react-i18next";
-import { Copy, Check, Download, Code2, RefreshCw, FileCode2, FolderDown } from "lucide-react";
+import { Copy, Check, Download, Code2, RefreshCw, FileCode2, FolderDown, FileArchive } from "lucide-react";
 import { save } from "@tauri-apps/plugin-dialog";
-import { writeTextFile, mkdir } from "@tauri-apps/plugin-fs";
+import { writeTextFile } from "@tauri-apps/plugin-fs";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/Card";
 import { useAppStore } from "@/stores/useAppStore";
-import { generateStrategyCode } from "@/lib/tauri";
+import { generateStrategyCode, saveGeneratedCode, countRows, previewData } from "@/lib/tauri";
 import { cn } from "@/lib/utils";
-import type { Strategy, CodeFile, CodeGenerationResult } from "@/lib/types";
+import type { Strategy, CodeFile, CodeGenerationResult, Candle, Timeframe } from "@/lib/types";
 import { ProGate } from "@/components/auth/ProGate";
 
 type Language = "mql5" | "pinescript";
 
+/** Trailing bars sent for verification samples (enough to warm up typical indicators). */
+const VERIFICATION_BARS = 300;
+
+/** Load the last `VERIFICATION_BARS` bars of a symbol's timeframe as candles. */
+async function loadVerificationCandles(symbolId: string, timeframe: Timeframe): Promise<Candle[]> {
+  const total = await countRows(symbolId, timeframe);
+  const offset = Math.max(0, total - VERIFICATION_BARS);
+  const rows = await previewData(symbolId, timeframe, offset, VERIFICATION_BARS);
+  return rows.map((r) => ({
+    datetime: String(r.datetime),
+    open: Number(r.open),
+    high: Number(r.high),
+    low: Number(r.low),
+    close: Number(r.close),
+    volume: Number(r.volume ?? 0),
+  }));
+}
+
 export function ExportPage() {
   return (
     <ProGate feature="export">
@@ -23,7 +41,13 @@ export function ExportPage() {
 function ExportPageContent() {
   const { t } = useTranslation("export");
   const currentStrategy = useAppStore((s) => s.currentStrategy);
+  const initialCapital = useAppStore((s) => s.initialCapital);
+  const leverage = useAppStore((s) => s.leverage);
+  const selectedSymbolId = useAppStore((s) => s.selectedSymbolId);
+  const selectedTimeframe = useAppStore((s) => s.selectedTimeframe);
   const [language, setLanguage] = useState<Language>("mql5");
+  const [emitAlerts, setEmitAlerts] = useState(false);
+  const [embedSamples, setEmbedSamples] = useState(false);
   const [result, setResult] = useState<CodeGenerationResult | null>(null);
   const [selectedFileIdx, setSelectedFileIdx] = useState(0);
   const [isGenerating, setIsGenerating] = useState(false);
@@ -51,7 +75,13 @@ function ExportPageContent() {
         created_at: currentStrategy.created_at ?? "",
         updated_at: currentStrategy.updated_at ?? "",
       };
-      const res = await generateStrategyCode(language, strategyPayload);
+      const samples = embedSamples && selectedSymbolId
+        ? await loadVerificationCandles(selectedSymbolId, selectedTimeframe)
+        : undefined;
+      const res = await generateStrategyCode(language, strategyPayload, {
+        initial_capital: initialCapital,
+        leverage,
+      }, selectedSymbolId || undefined, emitAlerts, samples);
       setResult(res);
       // Select the main file by default
       const mainIdx = res.files.findIndex((f) => f.is_main);
@@ -63,7 +93,7 @@ function ExportPageContent() {
     } finally {
       setIsGenerating(false);
     }
-  }, [language, currentStrategy, hasRules]);
+  }, [language, currentStrategy, hasRules, initialCapital, leverage, emitAlerts, embedSamples, selectedSymbolId, selectedTimeframe]);
 
   useEffect(() => {
     generate();
@@ -99,6 +129,9 @@ function ExportPageContent() {
     }
   };
 
+  const safeStrategyName = () =>
+    (currentStrategy.name || "strategy").replace(/[^a-zA-Z0-9_-]/g, "_");
+
   const handleDownloadAll = async () => {
     if (!result || result.files.length === 0) return;
 
@@ -109,23 +142,32 @@ function ExportPageContent() {
     }
 
     // Multiple files — ask for a folder (use save dialog with folder name)
-    const name = currentStrategy.name || "strategy";
-    const safeName = name.replace(/[^a-zA-Z0-9_-]/g, "_");
     const folderPath = await save({
-      defaultPath: safeName,
+      defaultPath: safeStrategyName(),
       filters: [{ name: "Folder", extensions: [""] }],
     });
 
     if (folderPath) {
-      // Create folder and write all files
+      // The backend creates the folder and writes every file under its sanitized name
       try {
-        await mkdir(folderPath, { recursive: true });
-      } catch {
-        // Folder may already exist
+        await saveGeneratedCode(result, folderPath);
+      } catch (err: unknown) {
+        setError(err instanceof Error ? err.message : String(err));
       }
-      for (const file of result.files) {
-        const filePath = `${folderPath}/${file.filename}`;
-        await writeTextFile(filePath, file.code);
+    }
+  };
+
+  const handleDownloadZip = async () => {
+    if (!result || result.files.length === 0) return;
+    const zipPath = await save({
+      defaultPath: `${safeStrategyName()}.zip`,
+      filters: [{ name: "Zip", extensions: ["zip"] }],
+    });
+    if (zipPath) {
+      try {
+        await saveGeneratedCode(result, zipPath, true);
+      } catch (err: unknown) {
+        setError(err instanceof Error ? err.message : String(err));
       }
     }
   };
@@ -177,6 +219,33 @@ function ExportPageContent() {
           Pine Script v6 (TradingView)
         </button>
 
+        {language === "pinescript" && (
+          <label
+            className="ml-2 flex items-center gap-1.5 text-sm text-muted-foreground"
+            title={t("emitAlertsTooltip")}
+          >
+            <input
+              type="checkbox"
+              checked={emitAlerts}
+              onChange={(e) => setEmitAlerts(e.target.checked)}
+            />
+            {t("emitAlerts")}
+          </label>
+        )}
+
+        <label
+          className="ml-2 flex items-center gap-1.5 text-sm text-muted-foreground"
+          title={t("verificationSamplesTooltip")}
+        >
+          <input
+            type="checkbox"
+            checked={embedSamples}
+            disabled={!selectedSymbolId}
+            onChange={(e) => setEmbedSamples(e.target.checked)}
+          />
+          {t("verificationSamples")}
+        </label>
+
         <div className="ml-auto flex items-center gap-2">
           <button
             onClick={generate}
@@ -200,6 +269,16 @@ function ExportPageContent() {
             )}
             {copied ? t("copied") : t("copy")}
           </button>
+          {totalFiles > 1 && (
+            <button
+              onClick={handleDownloadZip}
+              disabled={!result}
+              className="flex items-center gap-1 rounded bg-muted px-2.5 py-1.5 text-sm font-medium transition-colors hover:bg-muted/80 disabled:opacity-40"
+            >
+              <FileArchive className="h-3 w-3" />
+              {t("downloadZip")}
+            </button>
+          )}
           {totalFiles > 1 ? (
             <button
               onClick={handleDownloadAll}
@@ -267,6 +346,15 @@ function ExportPageContent() {
         </div>
       )}
 
+      {/* Validation warnings */}
+      {result?.warnings && result.warnings.length > 0 && (
+        <div className="rounded border border-amber-500/30 bg-amber-500/10 px-3 py-2 text-sm text-amber-500">
+          {result.warnings.map((w) => (
+            <p key={w}>{w}</p>
+          ))}
+        </div>
+      )}
+
       {/* Code preview */}
       <Card className="flex-1 overflow-hidden">
         <CardHeader className="py-3">
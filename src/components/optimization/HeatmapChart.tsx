@@ -43,7 +43,7 @@ export function HeatmapChart({ results, parameterRanges }: HeatmapChartProps) {
   const yName = parameterRanges[1].display_name;
 
   const data = useMemo(() => {
-    return results.map((r) => ({
+    return results.filter((r) => !r.failure_reason).map((r) => ({
       x: r.params[xName] ?? 0,
       y: r.params[yName] ?? 0,
       objective: r.objective_value,
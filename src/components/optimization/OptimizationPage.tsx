@@ -1,9 +1,13 @@
 import { useState, useCallback, useRef } from "react";
 import { useTranslation } from "react-i18next";
+import { toast } from "sonner";
+import { save } from "@tauri-apps/plugin-dialog";
+import { Download, Loader2 } from "lucide-react";
 import { useAppStore } from "@/stores/useAppStore";
-import { runBacktest } from "@/lib/tauri";
+import { runBacktest, exportOptimizationCsv } from "@/lib/tauri";
 import type { ParameterRange, Strategy, BacktestConfig } from "@/lib/types";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/Card";
+import { Button } from "@/components/ui/Button";
 import { OptimizerPanel } from "./OptimizerPanel";
 import { ParameterRanges } from "./ParameterRanges";
 import { ResultsTable } from "./ResultsTable";
@@ -19,8 +23,10 @@ export function OptimizationPage() {
 
 function OptimizationPageContent() {
   const { t } = useTranslation("optimization");
+  const { t: tc } = useTranslation("common");
   const {
     optimizationResults,
+    optimizationRetainedCount,
     currentStrategy,
     setLongEntryRules,
     setShortEntryRules,
@@ -48,6 +54,25 @@ function OptimizationPageContent() {
   } = useAppStore();
 
   const [applyError, setApplyError] = useState<string | null>(null);
+  const [exportingCsv, setExportingCsv] = useState(false);
+
+  const handleExportCsv = async () => {
+    const path = await save({
+      defaultPath: "optimization.csv",
+      filters: [{ name: "CSV", extensions: ["csv"] }],
+    });
+    if (!path) return;
+    setExportingCsv(true);
+    try {
+      await exportOptimizationCsv(path);
+      toast.success(t("exportCsvOk"));
+    } catch (err) {
+      const msg = err instanceof Error ? err.message : String(err);
+      toast.error(`${tc("toast.exportErr")}: ${msg}`);
+    } finally {
+      setExportingCsv(false);
+    }
+  };
 
   const [parameterRanges, setParameterRanges] = useState<ParameterRange[]>(optimizationParamRanges);
   // Keep a ref always in sync so handleApplyParams never has stale ranges
@@ -225,10 +250,24 @@ function OptimizationPageContent() {
       {/* Results (only shown when there are results) */}
       {optimizationResults.length > 0 && (
         <Card>
-          <CardHeader className="pb-3">
+          <CardHeader className="flex flex-row items-center justify-between pb-3">
             <CardTitle className="text-sm">
               {t("topResults")} ({optimizationResults.length})
             </CardTitle>
+            {optimizationRetainedCount !== null && (
+              <Button
+                variant="outline"
+                size="sm"
+                className="h-8 text-sm"
+                onClick={handleExportCsv}
+                disabled={exportingCsv}
+              >
+                {exportingCsv
+                  ? <Loader2 className="mr-1.5 h-3.5 w-3.5 animate-spin" />
+                  : <Download className="mr-1.5 h-3.5 w-3.5" />}
+                {t("exportAllCsv", { count: optimizationRetainedCount })}
+              </Button>
+            )}
           </CardHeader>
           <CardContent>
             <ResultsTable
@@ -12,8 +12,12 @@ import type {
   OptimizationConfig,
   OptimizationMethod,
   ObjectiveFunction,
+  WeightedObjective,
   ParameterRange,
   GeneticAlgorithmConfig,
+  CrossoverType,
+  MutationType,
+  RandomSearchConfig,
 } from "@/lib/types";
 import { useAvailablePrecisions } from "@/hooks/useAvailablePrecisions";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/Card";
@@ -58,6 +62,10 @@ const OBJECTIVE_KEYS: { value: ObjectiveFunction; tKey: string }[] = [
   { value: "ProfitFactor", tKey: "profitFactor" },
   { value: "WinRate", tKey: "winRate" },
   { value: "ReturnDdRatio", tKey: "returnDdRatio" },
+  { value: "SortinoRatio", tKey: "sortinoRatio" },
+  { value: "CalmarRatio", tKey: "calmarRatio" },
+  { value: "Expectancy", tKey: "expectancy" },
+  { value: "RecoveryFactor", tKey: "recoveryFactor" },
   { value: "MinStagnation", tKey: "minStagnation" },
   { value: "MinUlcerIndex", tKey: "minUlcerIndex" },
 ];
@@ -91,13 +99,20 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
     progressPercent,
     setProgress,
     setOptimizationResults,
+    setOptimizationSensitivity,
+    setOptimizationRetainedCount,
     optimizationOosPeriods: oosPeriods,
     setOptimizationOosPeriods: setOosPeriods,
   } = useAppStore();
 
   const [method, setMethod] = useState<OptimizationMethod>("GridSearch");
-  const [objectives, setObjectives] = useState<ObjectiveFunction[]>(["SharpeRatio"]);
+  const [objectives, setObjectives] = useState<WeightedObjective[]>([{ objective: "SharpeRatio", weight: 1 }]);
+  const [allowOosOverlap, setAllowOosOverlap] = useState(false);
+  const updateObjective = (objective: ObjectiveFunction, patch: Partial<WeightedObjective>) =>
+    setObjectives(objectives.map((o) => (o.objective === objective ? { ...o, ...patch } : o)));
   const [error, setError] = useState<string | null>(null);
+  const [partialNotice, setPartialNotice] = useState<string | null>(null);
+  const [runWarnings, setRunWarnings] = useState<string[]>([]);
   const [bestSoFar, setBestSoFar] = useState<number | null>(null);
   const [etaDisplay, setEtaDisplay] = useState<string>("");
   const unlistenRef = useRef<(() => void) | null>(null);
@@ -107,6 +122,12 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
   const [generations, setGenerations] = useState(20);
   const [mutationRate, setMutationRate] = useState(0.1);
   const [crossoverRate, setCrossoverRate] = useState(0.7);
+  const [crossoverType, setCrossoverType] = useState<CrossoverType>("OnePoint");
+  const [mutationType, setMutationType] = useState<MutationType>("UniformReset");
+  const [seed, setSeed] = useState<string>("");
+
+  // Random search config
+  const [samples, setSamples] = useState(500);
 
   const selectedSymbol = symbols.find((s) => s.id === selectedSymbolId);
   const availableTimeframes = selectedSymbol
@@ -187,6 +208,8 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
       if (crossoverRate < 0 || crossoverRate > 1)
         return t("validation.crossoverRange");
     }
+    if (method === "RandomSearch" && samples < 1)
+      return t("validation.samplesMin");
     return null;
   };
 
@@ -198,10 +221,14 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
     }
     if (!selectedSymbolId) return;
     setError(null);
+    setPartialNotice(null);
+    setRunWarnings([]);
     setBestSoFar(null);
     setEtaDisplay("");
     setLoading(true, t("runningOptimization"));
     setOptimizationResults([]);
+    setOptimizationSensitivity(null);
+    setOptimizationRetainedCount(null);
 
     unlistenRef.current = await listen<{
       percent: number;
@@ -257,6 +284,17 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
               generations,
               mutation_rate: mutationRate,
               crossover_rate: crossoverRate,
+              crossover_type: crossoverType,
+              mutation_type: mutationType,
+              seed: seed.trim() === "" ? undefined : Math.max(0, Math.floor(Number(seed))),
+            }
+          : undefined;
+
+      const rsConfig: RandomSearchConfig | undefined =
+        method === "RandomSearch"
+          ? {
+              samples,
+              seed: seed.trim() === "" ? undefined : Math.max(0, Math.floor(Number(seed))),
             }
           : undefined;
 
@@ -269,11 +307,20 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
         objectives,
         backtest_config: btConfig,
         ga_config: gaConfig,
+        random_search_config: rsConfig,
         oos_periods: validOos,
+        allow_oos_overlap: allowOosOverlap,
+        keep_all_results: true,
       };
 
-      const results = await runOptimization(strategy, optConfig);
-      setOptimizationResults(results);
+      const output = await runOptimization(strategy, optConfig);
+      setOptimizationResults(output.results);
+      setOptimizationSensitivity(output.sensitivity ?? null);
+      setOptimizationRetainedCount(output.retained_count ?? null);
+      setRunWarnings(output.warnings ?? []);
+      if (output.cancelled) {
+        setPartialNotice(t("partialResults", { count: output.results.length }));
+      }
     } catch (err) {
       const msg = typeof err === "string" ? err : err instanceof Error ? err.message : JSON.stringify(err);
       if (msg.includes("Cancelled") || msg.includes("cancelled") || msg.includes("cancel")) {
@@ -349,6 +396,22 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
         </div>
       )}
 
+      {/* Partial results after cancel */}
+      {partialNotice && (
+        <div className="flex items-start gap-2 rounded border border-amber-500/50 bg-amber-500/10 p-3">
+          <AlertTriangle className="mt-0.5 h-4 w-4 shrink-0 text-amber-500" />
+          <p className="text-sm text-amber-600 dark:text-amber-400">{partialNotice}</p>
+        </div>
+      )}
+
+      {/* Run-level warnings (e.g. precision fallback) */}
+      {runWarnings.map((w) => (
+        <div key={w} className="flex items-start gap-2 rounded border border-amber-500/50 bg-amber-500/10 p-3">
+          <AlertTriangle className="mt-0.5 h-4 w-4 shrink-0 text-amber-500" />
+          <p className="text-sm text-amber-600 dark:text-amber-400">{w}</p>
+        </div>
+      ))}
+
       {/* 2-column config grid */}
       <div className="grid grid-cols-1 gap-4 lg:grid-cols-2">
         {/* Left: Optimization Setup */}
@@ -373,6 +436,9 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                     <SelectItem value="GeneticAlgorithm">
                       {t("geneticAlgorithm")}
                     </SelectItem>
+                    <SelectItem value="RandomSearch">
+                      {t("randomSearch")}
+                    </SelectItem>
                   </SelectContent>
                 </Select>
               </div>
@@ -383,7 +449,7 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                 </label>
                 <div className="flex flex-wrap gap-1">
                   {OBJECTIVE_KEYS.map((o) => {
-                    const selected = objectives.includes(o.value);
+                    const selected = objectives.some((v) => v.objective === o.value);
                     return (
                       <button
                         key={o.value}
@@ -391,10 +457,10 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                         onClick={() => {
                           if (selected) {
                             if (objectives.length > 1) {
-                              setObjectives(objectives.filter((v) => v !== o.value));
+                              setObjectives(objectives.filter((v) => v.objective !== o.value));
                             }
                           } else {
-                            setObjectives([...objectives, o.value]);
+                            setObjectives([...objectives, { objective: o.value, weight: 1 }]);
                           }
                         }}
                         className={`rounded border px-2 py-0.5 text-xs font-medium transition-colors ${
@@ -408,6 +474,36 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                     );
                   })}
                 </div>
+                {objectives.length > 1 && (
+                  <div className="space-y-1 pt-1">
+                    {objectives.map((o) => {
+                      const key = OBJECTIVE_KEYS.find((k) => k.value === o.objective)?.tKey ?? o.objective;
+                      const minimize = (o.direction ?? (o.objective.startsWith("Min") ? "Minimize" : "Maximize")) === "Minimize";
+                      return (
+                        <div key={o.objective} className="flex items-center gap-2 text-xs">
+                          <span className="flex-1 truncate text-muted-foreground">{t(key)}</span>
+                          <button
+                            type="button"
+                            title={t(minimize ? "minimize" : "maximize")}
+                            onClick={() => updateObjective(o.objective, { direction: minimize ? "Maximize" : "Minimize" })}
+                            className="rounded border border-border px-1.5 py-0.5 font-mono text-muted-foreground hover:border-primary/50"
+                          >
+                            {minimize ? "↓" : "↑"}
+                          </button>
+                          <Input
+                            type="number"
+                            className="h-7 w-16 text-xs"
+                            min={0}
+                            step={0.5}
+                            value={o.weight}
+                            title={t("objectiveWeight")}
+                            onChange={(e) => updateObjective(o.objective, { weight: Math.max(0, Number(e.target.value)) })}
+                          />
+                        </div>
+                      );
+                    })}
+                  </div>
+                )}
               </div>
             </div>
 
@@ -481,6 +577,20 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                   </button>
                 </div>
               ))}
+              {oosPeriods.length > 0 && (
+                <label
+                  className="flex cursor-pointer items-center gap-1.5 text-sm text-muted-foreground"
+                  title={t("allowOosOverlapTooltip")}
+                >
+                  <input
+                    type="checkbox"
+                    checked={allowOosOverlap}
+                    onChange={(e) => setAllowOosOverlap(e.target.checked)}
+                    className="h-4 w-4 accent-primary"
+                  />
+                  {t("allowOosOverlap")}
+                </label>
+              )}
             </div>
 
             {/* GA Config (only for Genetic Algorithm) */}
@@ -540,6 +650,88 @@ export function OptimizerPanel({ parameterRanges }: OptimizerPanelProps) {
                     onChange={(e) => setCrossoverRate(Number(e.target.value))}
                   />
                 </div>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">
+                    {t("mutationType")}
+                  </label>
+                  <Select
+                    value={mutationType}
+                    onValueChange={(v) => setMutationType(v as MutationType)}
+                  >
+                    <SelectTrigger className="h-9 text-sm">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="UniformReset">{t("mutationUniformReset")}</SelectItem>
+                      <SelectItem value="Gaussian">{t("mutationGaussian")}</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">
+                    {t("crossoverType")}
+                  </label>
+                  <Select
+                    value={crossoverType}
+                    onValueChange={(v) => setCrossoverType(v as CrossoverType)}
+                  >
+                    <SelectTrigger className="h-9 text-sm">
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="OnePoint">{t("crossoverOnePoint")}</SelectItem>
+                      <SelectItem value="Uniform">{t("crossoverUniform")}</SelectItem>
+                      <SelectItem value="Blend">{t("crossoverBlend")}</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">
+                    {t("seed")}
+                  </label>
+                  <Input
+                    type="number"
+                    className="h-9 text-sm"
+                    min={0}
+                    step={1}
+                    placeholder={t("seedRandom")}
+                    value={seed}
+                    onChange={(e) => setSeed(e.target.value)}
+                  />
+                </div>
+              </div>
+            )}
+
+            {/* Random Search config */}
+            {method === "RandomSearch" && (
+              <div className="grid grid-cols-2 gap-3">
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">
+                    {t("samples")}
+                  </label>
+                  <Input
+                    type="number"
+                    className="h-9 text-sm"
+                    min={1}
+                    step={50}
+                    value={samples}
+                    onChange={(e) => setSamples(Number(e.target.value))}
+                  />
+                </div>
+                <div className="space-y-1">
+                  <label className="text-sm text-muted-foreground">
+                    {t("seed")}
+                  </label>
+                  <Input
+                    type="number"
+                    className="h-9 text-sm"
+                    min={0}
+                    step={1}
+                    placeholder={t("seedRandom")}
+                    value={seed}
+                    onChange={(e) => setSeed(e.target.value)}
+                  />
+                </div>
               </div>
             )}
           </CardContent>
@@ -130,13 +130,13 @@ function detectParams(strategy: Pick<Strategy, "long_entry_rules" | "short_entry
       ruleLabel: `Stop Loss (${sl.sl_type})`,
       paramKey: "value",
       paramLabel: "Value",
-      defaultMin: sl.sl_type === "Percentage" ? 0.1 : sl.sl_type === "ATR" ? 0.5 : 1,
+      defaultMin: sl.sl_type === "Percentage" ? 0.1 : sl.sl_type === "ATR" || sl.sl_type === "ChandeKroll" ? 0.5 : 1,
       defaultMax: Math.max(sl.value * 3, sl.sl_type === "Percentage" ? 5 : 100),
-      defaultStep: sl.sl_type === "Percentage" ? 0.1 : sl.sl_type === "ATR" ? 0.1 : 1,
+      defaultStep: sl.sl_type === "Percentage" ? 0.1 : sl.sl_type === "ATR" || sl.sl_type === "ChandeKroll" ? 0.1 : 1,
       currentValue: sl.value,
       paramSource: "stop_loss",
     });
-    if (sl.sl_type === "ATR" && sl.atr_period) {
+    if ((sl.sl_type === "ATR" || sl.sl_type === "ChandeKroll") && sl.atr_period) {
       params.push({
         ruleIndex: -1,
         operandSide: "left",
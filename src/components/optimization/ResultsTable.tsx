@@ -87,6 +87,8 @@ export function ResultsTable({
   const sorted = useMemo(() => {
     const arr = [...results];
     arr.sort((a, b) => {
+      // Failed combinations stay at the bottom whatever the sort
+      if (!!a.failure_reason !== !!b.failure_reason) return a.failure_reason ? 1 : -1;
       if (sortKey === "rank") return 0;
 
       let va: number;
@@ -226,7 +228,8 @@ export function ResultsTable({
           {sorted.map((result, idx) => (
             <TableRow
               key={idx}
-              className={idx === 0 ? "bg-emerald-500/[0.03]" : ""}
+              className={result.failure_reason ? "opacity-50" : idx === 0 ? "bg-emerald-500/[0.03]" : ""}
+              title={result.failure_reason ? t("table.failed", { reason: result.failure_reason }) : undefined}
             >
               <TableCell className="text-sm font-mono text-muted-foreground">
                 {idx + 1}
@@ -281,6 +284,7 @@ export function ResultsTable({
                   size="sm"
                   className="h-6 w-6 p-0"
                   onClick={() => onApply(result.params)}
+                  disabled={!!result.failure_reason}
                   title="Apply these parameters"
                 >
                   <Check className="h-3.5 w-3.5" />
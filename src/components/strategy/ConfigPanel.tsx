@@ -13,6 +13,7 @@ import type {
   TradeDirection,
   TradingHours,
   CloseTradesAt,
+  SessionExit,
 } from "@/lib/types";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/Tabs";
 import {
@@ -35,6 +36,9 @@ interface ConfigPanelProps {
   tradingHours?: TradingHours;
   maxDailyTrades?: number;
   closeTradesAt?: CloseTradesAt;
+  closeOnOppositeSignal: boolean;
+  reverseOnOppositeSignal: boolean;
+  sessionExit?: SessionExit;
   onPositionSizingChange: (ps: PositionSizing) => void;
   onStopLossChange: (sl: StopLoss | undefined) => void;
   onTakeProfitChange: (tp: TakeProfit | undefined) => void;
@@ -44,6 +48,8 @@ interface ConfigPanelProps {
   onTradingHoursChange: (hours: TradingHours | undefined) => void;
   onMaxDailyTradesChange: (max: number | undefined) => void;
   onCloseTradesAtChange: (ct: CloseTradesAt | undefined) => void;
+  onOppositeSignalChange: (close: boolean, reverse: boolean) => void;
+  onSessionExitChange: (exit: SessionExit | undefined) => void;
 }
 
 const SIZING_TYPE_OPTIONS: { value: PositionSizingType; labelKey: string }[] = [
@@ -66,6 +72,7 @@ const SL_TYPE_OPTIONS: { value: StopLossType; labelKey: string }[] = [
   { value: "Pips", labelKey: "config.pips" },
   { value: "Percentage", labelKey: "config.percentage" },
   { value: "ATR", labelKey: "config.atrMultiplier" },
+  { value: "ChandeKroll", labelKey: "config.chandeKroll" },
 ];
 
 const TP_TYPE_OPTIONS: { value: TakeProfitType; labelKey: string }[] = [
@@ -77,6 +84,7 @@ const TP_TYPE_OPTIONS: { value: TakeProfitType; labelKey: string }[] = [
 const TS_TYPE_OPTIONS: { value: TrailingStopType; labelKey: string }[] = [
   { value: "ATR", labelKey: "config.atrMultiplier" },
   { value: "RiskReward", labelKey: "config.riskReward" },
+  { value: "Step", labelKey: "config.trailingStep" },
 ];
 
 const COMMISSION_TYPE_OPTIONS: { value: CommissionType; labelKey: string }[] = [
@@ -148,6 +156,9 @@ export function ConfigPanel({
   tradingHours,
   maxDailyTrades,
   closeTradesAt,
+  closeOnOppositeSignal,
+  reverseOnOppositeSignal,
+  sessionExit,
   onPositionSizingChange,
   onStopLossChange,
   onTakeProfitChange,
@@ -157,6 +168,8 @@ export function ConfigPanel({
   onTradingHoursChange,
   onMaxDailyTradesChange,
   onCloseTradesAtChange,
+  onOppositeSignalChange,
+  onSessionExitChange,
 }: ConfigPanelProps) {
   const { t } = useTranslation("strategy");
 
@@ -233,7 +246,7 @@ export function ConfigPanel({
                   sl_type: v as StopLossType,
                   value: stopLoss.value,
                 };
-                if (v === "ATR") newSl.atr_period = stopLoss.atr_period ?? 14;
+                if (v === "ATR" || v === "ChandeKroll") newSl.atr_period = stopLoss.atr_period ?? 14;
                 onStopLossChange(newSl);
               }}
             >
@@ -248,7 +261,7 @@ export function ConfigPanel({
                 ))}
               </SelectContent>
             </Select>
-            {stopLoss.sl_type === "ATR" && (
+            {(stopLoss.sl_type === "ATR" || stopLoss.sl_type === "ChandeKroll") && (
               <LabeledInput
                 label={t("config.atrPeriod")}
                 value={stopLoss.atr_period ?? 14}
@@ -260,7 +273,7 @@ export function ConfigPanel({
               />
             )}
             <LabeledInput
-              label={stopLoss.sl_type === "ATR" ? t("config.multiplier") : t("config.value")}
+              label={stopLoss.sl_type === "ATR" || stopLoss.sl_type === "ChandeKroll" ? t("config.multiplier") : t("config.value")}
               value={stopLoss.value}
               onChange={(value) =>
                 onStopLossChange({ ...stopLoss, value })
@@ -346,8 +359,10 @@ export function ConfigPanel({
                 const newTs: TrailingStop = {
                   ts_type: v as TrailingStopType,
                   value: trailingStop.value,
+                  activation_pips: trailingStop.activation_pips,
                 };
                 if (v === "ATR") newTs.atr_period = trailingStop.atr_period ?? 14;
+                if (v === "Step") newTs.step_pips = trailingStop.step_pips ?? trailingStop.value;
                 onTrailingStopChange(newTs);
               }}
             >
@@ -374,12 +389,39 @@ export function ConfigPanel({
               />
             )}
             <LabeledInput
-              label={trailingStop.ts_type === "ATR" ? t("config.multiplier") : t("config.value")}
+              label={
+                trailingStop.ts_type === "ATR"
+                  ? t("config.multiplier")
+                  : trailingStop.ts_type === "Step"
+                    ? t("config.trailingDistancePips")
+                    : t("config.value")
+              }
               value={trailingStop.value}
               onChange={(value) =>
                 onTrailingStopChange({ ...trailingStop, value })
               }
             />
+            {trailingStop.ts_type === "Step" && (
+              <LabeledInput
+                label={t("config.trailingStepPips")}
+                value={trailingStop.step_pips ?? trailingStop.value}
+                onChange={(step_pips) =>
+                  onTrailingStopChange({ ...trailingStop, step_pips })
+                }
+                min={0}
+              />
+            )}
+            <LabeledInput
+              label={t("config.trailingActivationPips")}
+              value={trailingStop.activation_pips ?? 0}
+              onChange={(pips) =>
+                onTrailingStopChange({
+                  ...trailingStop,
+                  activation_pips: pips > 0 ? pips : undefined,
+                })
+              }
+              min={0}
+            />
           </>
         )}
       </TabsContent>
@@ -474,6 +516,26 @@ export function ConfigPanel({
           </div>
         </div>
 
+        {/* Opposite entry signal while in a position */}
+        <div className="space-y-1">
+          <label className="text-sm text-muted-foreground">{t("config.oppositeSignal")}</label>
+          <Select
+            value={reverseOnOppositeSignal ? "reverse" : closeOnOppositeSignal ? "close" : "ignore"}
+            onValueChange={(v) => onOppositeSignalChange(v === "close", v === "reverse")}
+          >
+            <SelectTrigger className="h-9 text-sm">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="ignore">{t("config.oppositeIgnore")}</SelectItem>
+              <SelectItem value="close">{t("config.oppositeClose")}</SelectItem>
+              <SelectItem value="reverse" disabled={tradeDirection !== "Both"}>
+                {t("config.oppositeReverse")}
+              </SelectItem>
+            </SelectContent>
+          </Select>
+        </div>
+
         {/* Trading Hours */}
         <div className="space-y-2">
           <ToggleCheckbox
@@ -611,6 +673,16 @@ export function ConfigPanel({
             </div>
           )}
         </div>
+
+        {/* Market-on-close at the end of each session */}
+        <div className="space-y-1">
+          <ToggleCheckbox
+            label={t("config.marketOnClose")}
+            checked={sessionExit === "MarketOnClose"}
+            onChange={(checked) => onSessionExitChange(checked ? "MarketOnClose" : undefined)}
+          />
+          <p className="text-xs text-muted-foreground">{t("config.marketOnCloseDesc")}</p>
+        </div>
       </TabsContent>
     </Tabs>
   );
@@ -1,4 +1,4 @@
-import type { IndicatorConfig, IndicatorType } from "@/lib/types";
+import type { AtrSmoothing, EmaSeed, IndicatorConfig, IndicatorType, PivotType, RsiSmoothing } from "@/lib/types";
 import {
   Select,
   SelectContent,
@@ -9,9 +9,16 @@ import {
 import { Input } from "@/components/ui/Input";
 import {
   INDICATOR_OPTIONS,
+  ATR_SMOOTHING_OPTIONS,
+  RSI_SMOOTHING_OPTIONS,
+  EMA_SEED_OPTIONS,
+  PIVOT_TYPE_OPTIONS,
   getIndicatorParamFields,
   getOutputFieldOptions,
   createDefaultIndicatorConfig,
+  usesAtrSmoothing,
+  usesSampleStdDev,
+  usesEmaSeed,
 } from "./utils";
 
 interface IndicatorSelectorProps {
@@ -38,6 +45,41 @@ export function IndicatorSelector({ value, onChange }: IndicatorSelectorProps) {
     onChange({ ...value, output_field: field });
   };
 
+  const handleStdDevModeChange = (mode: string) => {
+    onChange({
+      ...value,
+      params: { ...value.params, sample_stddev: mode === "sample" },
+    });
+  };
+
+  const handleSmoothingChange = (smoothing: string) => {
+    onChange({
+      ...value,
+      params: { ...value.params, atr_smoothing: smoothing as AtrSmoothing },
+    });
+  };
+
+  const handleRsiSmoothingChange = (smoothing: string) => {
+    onChange({
+      ...value,
+      params: { ...value.params, rsi_smoothing: smoothing as RsiSmoothing },
+    });
+  };
+
+  const handleEmaSeedChange = (seed: string) => {
+    onChange({
+      ...value,
+      params: { ...value.params, ema_seed: seed as EmaSeed },
+    });
+  };
+
+  const handlePivotTypeChange = (pivotType: string) => {
+    onChange({
+      ...value,
+      params: { ...value.params, pivot_type: pivotType as PivotType },
+    });
+  };
+
   return (
     <div className="flex flex-wrap items-center gap-1.5">
       <Select value={value.indicator_type} onValueChange={handleTypeChange}>
@@ -68,6 +110,93 @@ export function IndicatorSelector({ value, onChange }: IndicatorSelectorProps) {
         </div>
       ))}
 
+      {usesAtrSmoothing(value.indicator_type) && (
+        <Select
+          value={value.params.atr_smoothing ?? "wilder"}
+          onValueChange={handleSmoothingChange}
+        >
+          <SelectTrigger className="h-8 w-[90px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {ATR_SMOOTHING_OPTIONS.map((opt) => (
+              <SelectItem key={opt.value} value={opt.value}>
+                {opt.label}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      )}
+
+      {value.indicator_type === "RSI" && (
+        <Select
+          value={value.params.rsi_smoothing ?? "wilder"}
+          onValueChange={handleRsiSmoothingChange}
+        >
+          <SelectTrigger className="h-8 w-[120px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {RSI_SMOOTHING_OPTIONS.map((opt) => (
+              <SelectItem key={opt.value} value={opt.value}>
+                {opt.label}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      )}
+
+      {usesEmaSeed(value.indicator_type) && (
+        <Select
+          value={value.params.ema_seed ?? "sma_seed"}
+          onValueChange={handleEmaSeedChange}
+        >
+          <SelectTrigger className="h-8 w-[140px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {EMA_SEED_OPTIONS.map((opt) => (
+              <SelectItem key={opt.value} value={opt.value}>
+                {opt.label}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      )}
+
+      {value.indicator_type === "Pivots" && (
+        <Select
+          value={value.params.pivot_type ?? "classic"}
+          onValueChange={handlePivotTypeChange}
+        >
+          <SelectTrigger className="h-8 w-[110px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            {PIVOT_TYPE_OPTIONS.map((opt) => (
+              <SelectItem key={opt.value} value={opt.value}>
+                {opt.label}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      )}
+
+      {usesSampleStdDev(value.indicator_type) && (
+        <Select
+          value={value.params.sample_stddev ? "sample" : "population"}
+          onValueChange={handleStdDevModeChange}
+        >
+          <SelectTrigger className="h-8 w-[110px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="population">Population</SelectItem>
+            <SelectItem value="sample">Sample</SelectItem>
+          </SelectContent>
+        </Select>
+      )}
+
       {outputFields && (
         <Select
           value={value.output_field ?? outputFields[0].value}
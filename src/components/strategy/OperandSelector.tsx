@@ -39,6 +39,8 @@ const PRICE_FIELD_KEYS: { value: PriceField; key: string }[] = [
   { value: "DailyHigh", key: "priceFields.dailyHigh" },
   { value: "DailyLow", key: "priceFields.dailyLow" },
   { value: "DailyClose", key: "priceFields.dailyClose" },
+  { value: "OpenInterest", key: "priceFields.openInterest" },
+  { value: "AvgSpread", key: "priceFields.avgSpread" },
 ];
 
 const MONTH_KEYS = [
@@ -532,6 +534,30 @@ export function OperandSelector({
         />
       ) : null}
 
+      {value.operand_type === "Constant" && !timeContext && (
+        <Select
+          value={value.percent_of ?? "none"}
+          onValueChange={(pf) =>
+            onChange({
+              ...value,
+              percent_of: pf === "none" ? undefined : (pf as PriceField),
+            })
+          }
+        >
+          <SelectTrigger className="h-8 w-[130px] text-sm">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value="none">{t("constantAbsolute")}</SelectItem>
+            {PRICE_FIELD_KEYS.map((opt) => (
+              <SelectItem key={opt.value} value={opt.value}>
+                {t("percentOf")} {t(opt.key)}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      )}
+
       {value.operand_type !== "Constant" && (
         <div className="flex items-center gap-1">
           <span className="text-sm text-muted-foreground">{t("offset")}:</span>
@@ -545,7 +571,8 @@ export function OperandSelector({
             onChange={(e) =>
               onChange({
                 ...value,
-                offset: Number(e.target.value) || undefined,
+                // Offsets only look back: clamp to 0..500 like the backend check
+                offset: Math.min(500, Math.max(0, Math.floor(Number(e.target.value)))) || undefined,
               })
             }
           />
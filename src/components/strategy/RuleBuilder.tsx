@@ -8,6 +8,7 @@ import {
   SelectValue,
 } from "@/components/ui/Select";
 import { Button } from "@/components/ui/Button";
+import { Input } from "@/components/ui/Input";
 import { Trash2 } from "lucide-react";
 import { OperandSelector } from "./OperandSelector";
 import { COMPARATOR_OPTIONS } from "./utils";
@@ -111,6 +112,26 @@ export function RuleBuilder({
                 </SelectContent>
               </Select>
             )}
+            {!isCandlePattern && rule.comparator === "Equal" && (
+              <div className="flex items-center gap-1" title={t("toleranceTooltip")}>
+                <span className="text-sm text-muted-foreground">{t("tolerance")}:</span>
+                <Input
+                  type="number"
+                  className="h-8 w-[90px] text-sm"
+                  min={0}
+                  step="any"
+                  placeholder={t("toleranceAuto")}
+                  value={rule.tolerance ?? ""}
+                  onChange={(e) =>
+                    onChange({
+                      ...rule,
+                      tolerance:
+                        e.target.value === "" ? undefined : Math.max(0, Number(e.target.value)),
+                    })
+                  }
+                />
+              </div>
+            )}
           </div>
 
           {/* Right operand — locked to Constant(1) for candle patterns */}
@@ -39,6 +39,8 @@ export function StrategyPage() {
     setTradingHours,
     setMaxDailyTrades,
     setCloseTradesAt,
+    setOppositeSignal,
+    setSessionExit,
     setCurrentStrategy,
     setSavedStrategies,
     resetStrategy,
@@ -57,10 +59,12 @@ export function StrategyPage() {
   const strategyFingerprint = useMemo(() => {
     const { long_entry_rules, short_entry_rules, long_exit_rules, short_exit_rules,
       position_sizing, stop_loss, take_profit, trailing_stop, trading_costs,
-      trade_direction, trading_hours, max_daily_trades, close_trades_at, name } = currentStrategy;
+      trade_direction, trading_hours, max_daily_trades, close_trades_at,
+      close_on_opposite_signal, reverse_on_opposite_signal, session_exit, name } = currentStrategy;
     return JSON.stringify({ name, long_entry_rules, short_entry_rules, long_exit_rules,
       short_exit_rules, position_sizing, stop_loss, take_profit, trailing_stop,
-      trading_costs, trade_direction, trading_hours, max_daily_trades, close_trades_at });
+      trading_costs, trade_direction, trading_hours, max_daily_trades, close_trades_at,
+      close_on_opposite_signal, reverse_on_opposite_signal, session_exit });
   }, [currentStrategy]);
 
   const isDirty = strategyFingerprint !== savedSnapshot.current;
@@ -98,6 +102,9 @@ export function StrategyPage() {
       trading_hours: currentStrategy.trading_hours,
       max_daily_trades: currentStrategy.max_daily_trades,
       close_trades_at: currentStrategy.close_trades_at,
+      close_on_opposite_signal: currentStrategy.close_on_opposite_signal,
+      reverse_on_opposite_signal: currentStrategy.reverse_on_opposite_signal,
+      session_exit: currentStrategy.session_exit,
     };
 
     const id = await saveStrategy(strategyToSave);
@@ -121,11 +128,12 @@ export function StrategyPage() {
     // Reset snapshot so the loaded strategy is not considered dirty
     const { long_entry_rules, short_entry_rules, long_exit_rules, short_exit_rules,
       position_sizing, stop_loss, take_profit, trailing_stop, trading_costs,
-      trade_direction, trading_hours, max_daily_trades, close_trades_at, name } = strategy;
+      trade_direction, trading_hours, max_daily_trades, close_trades_at,
+      close_on_opposite_signal, reverse_on_opposite_signal, session_exit, name } = strategy;
     savedSnapshot.current = JSON.stringify({ name, long_entry_rules, short_entry_rules,
       long_exit_rules, short_exit_rules, position_sizing, stop_loss, take_profit,
       trailing_stop, trading_costs, trade_direction, trading_hours, max_daily_trades,
-      close_trades_at });
+      close_trades_at, close_on_opposite_signal, reverse_on_opposite_signal, session_exit });
     setShowLoadDialog(false);
   };
 
@@ -266,6 +274,11 @@ export function StrategyPage() {
                 onMaxDailyTradesChange={setMaxDailyTrades}
                 closeTradesAt={currentStrategy.close_trades_at}
                 onCloseTradesAtChange={setCloseTradesAt}
+                closeOnOppositeSignal={!!currentStrategy.close_on_opposite_signal}
+                reverseOnOppositeSignal={!!currentStrategy.reverse_on_opposite_signal}
+                onOppositeSignalChange={setOppositeSignal}
+                sessionExit={currentStrategy.session_exit}
+                onSessionExitChange={setSessionExit}
               />
             </CardContent>
           </Card>
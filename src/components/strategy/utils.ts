@@ -1,4 +1,8 @@
 import type {
+  AtrSmoothing,
+  EmaSeed,
+  PivotType,
+  RsiSmoothing,
   IndicatorType,
   IndicatorConfig,
   IndicatorParams,
@@ -51,6 +55,7 @@ export const INDICATOR_OPTIONS: { value: IndicatorType; label: string }[] = [
   { value: "HullMA", label: "Hull MA" },
   { value: "Ichimoku", label: "Ichimoku" },
   { value: "KeltnerChannel", label: "Keltner Channel" },
+  { value: "DonchianChannel", label: "Donchian Channel" },
   { value: "LaguerreRSI", label: "Laguerre RSI" },
   { value: "LinearRegression", label: "Linear Regression" },
   { value: "Momentum", label: "Momentum" },
@@ -61,6 +66,26 @@ export const INDICATOR_OPTIONS: { value: IndicatorType; label: string }[] = [
   { value: "Pivots", label: "Pivots" },
   { value: "UlcerIndex", label: "Ulcer Index" },
   { value: "Vortex", label: "Vortex" },
+  { value: "Choppiness", label: "Choppiness Index" },
+  { value: "StochasticRSI", label: "Stochastic RSI" },
+  { value: "AccumulationDistribution", label: "Accumulation/Distribution" },
+  { value: "ChaikinMoneyFlow", label: "Chaikin Money Flow" },
+  { value: "TRIX", label: "TRIX" },
+  { value: "DPO", label: "Detrended Price Oscillator" },
+  { value: "Coppock", label: "Coppock Curve" },
+  { value: "PPO", label: "Percentage Price Osc." },
+  { value: "SuperSmoother", label: "Super Smoother" },
+  { value: "RoofingFilter", label: "Roofing Filter" },
+  { value: "ConnorsRSI", label: "Connors RSI" },
+  { value: "ForceIndex", label: "Force Index" },
+  { value: "EaseOfMovement", label: "Ease of Movement" },
+  { value: "VWMA", label: "Volume Weighted MA" },
+  { value: "WMA", label: "Weighted MA" },
+  { value: "ZLEMA", label: "Zero-Lag EMA" },
+  { value: "ElderImpulse", label: "Elder Impulse" },
+  { value: "MassIndex", label: "Mass Index" },
+  { value: "Klinger", label: "Klinger Volume Oscillator" },
+  { value: "SchaffTrendCycle", label: "Schaff Trend Cycle" },
 ];
 
 export const COMPARATOR_OPTIONS: { value: Comparator; label: string }[] = [
@@ -96,11 +121,52 @@ export const CANDLE_PATTERN_OPTIONS: { value: CandlePatternType; label: string }
   { value: "PiercingLine", label: "Piercing Line" },
 ];
 
+export const ATR_SMOOTHING_OPTIONS: { value: AtrSmoothing; label: string }[] = [
+  { value: "wilder", label: "Wilder" },
+  { value: "sma", label: "SMA" },
+  { value: "ema", label: "EMA" },
+];
+
+/** Indicators whose True Range averaging is selectable via `atr_smoothing`. */
+export function usesAtrSmoothing(type: IndicatorType): boolean {
+  return type === "ATR" || type === "SuperTrend";
+}
+
+/** Indicators whose standard deviation can use the sample (N-1) divisor. */
+export function usesSampleStdDev(type: IndicatorType): boolean {
+  return type === "BollingerBands" || type === "StdDev";
+}
+
+export const RSI_SMOOTHING_OPTIONS: { value: RsiSmoothing; label: string }[] = [
+  { value: "wilder", label: "Wilder" },
+  { value: "sma", label: "Cutler (SMA)" },
+];
+
+/** Indicators whose EMAs can seed from the first value instead of an SMA. */
+export function usesEmaSeed(type: IndicatorType): boolean {
+  return type === "EMA" || type === "MACD";
+}
+
+export const EMA_SEED_OPTIONS: { value: EmaSeed; label: string }[] = [
+  { value: "sma_seed", label: "SMA seed" },
+  { value: "first_value", label: "First value (TV)" },
+];
+
+export const PIVOT_TYPE_OPTIONS: { value: PivotType; label: string }[] = [
+  { value: "classic", label: "Classic" },
+  { value: "fibonacci", label: "Fibonacci" },
+  { value: "camarilla", label: "Camarilla" },
+  { value: "woodie", label: "Woodie" },
+];
+
 /** Returns which parameter fields to show for a given indicator type. */
 export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
   switch (type) {
     case "SMA":
     case "EMA":
+    case "VWMA":
+    case "WMA":
+    case "ZLEMA":
     case "RSI":
     case "ATR":
     case "ADX":
@@ -111,6 +177,7 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
         { key: "period", label: "Period", defaultValue: 14, min: 1, max: 500, step: 1 },
       ];
     case "MACD":
+    case "PPO":
       return [
         { key: "fast_period", label: "Fast", defaultValue: 12, min: 1, max: 200, step: 1 },
         { key: "slow_period", label: "Slow", defaultValue: 26, min: 1, max: 200, step: 1 },
@@ -126,6 +193,12 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
         { key: "k_period", label: "%K Period", defaultValue: 14, min: 1, max: 200, step: 1 },
         { key: "d_period", label: "%D Period", defaultValue: 3, min: 1, max: 200, step: 1 },
       ];
+    case "StochasticRSI":
+      return [
+        { key: "period", label: "RSI Period", defaultValue: 14, min: 1, max: 200, step: 1 },
+        { key: "k_period", label: "%K Period", defaultValue: 14, min: 1, max: 200, step: 1 },
+        { key: "d_period", label: "%D Period", defaultValue: 3, min: 1, max: 200, step: 1 },
+      ];
     case "ParabolicSAR":
       return [
         { key: "acceleration_factor", label: "Accel", defaultValue: 0.02, min: 0.001, max: 0.5, step: 0.001 },
@@ -147,7 +220,6 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
     case "Fibonacci":
     case "GannHiLo":
     case "HullMA":
-    case "LinearRegression":
     case "Momentum":
     case "StdDev":
     case "Reflex":
@@ -156,6 +228,11 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
       return [
         { key: "period", label: "Period", defaultValue: 14, min: 1, max: 500, step: 1 },
       ];
+    case "LinearRegression":
+      return [
+        { key: "period", label: "Period", defaultValue: 14, min: 2, max: 500, step: 1 },
+        { key: "std_dev", label: "Channel Std Dev", defaultValue: 2.0, min: 0.1, max: 5, step: 0.1 },
+      ];
     case "BearsPower":
     case "BullsPower":
       return [
@@ -172,6 +249,10 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
         { key: "period", label: "Period", defaultValue: 20, min: 1, max: 500, step: 1 },
         { key: "multiplier", label: "Mult", defaultValue: 1.5, min: 0.1, max: 10, step: 0.1 },
       ];
+    case "DonchianChannel":
+      return [
+        { key: "period", label: "Period", defaultValue: 20, min: 1, max: 500, step: 1 },
+      ];
     case "LaguerreRSI":
       return [
         { key: "gamma", label: "Gamma", defaultValue: 0.8, min: 0.01, max: 0.99, step: 0.01 },
@@ -181,6 +262,78 @@ export function getIndicatorParamFields(type: IndicatorType): ParamField[] {
         { key: "period", label: "Period", defaultValue: 10, min: 1, max: 500, step: 1 },
         { key: "multiplier", label: "Mult", defaultValue: 3.0, min: 0.1, max: 10, step: 0.1 },
       ];
+    case "Choppiness":
+      return [
+        { key: "period", label: "Period", defaultValue: 14, min: 2, max: 500, step: 1 },
+      ];
+    case "ChaikinMoneyFlow":
+      return [
+        { key: "period", label: "Period", defaultValue: 20, min: 1, max: 500, step: 1 },
+      ];
+    case "TRIX":
+      return [
+        { key: "period", label: "Period", defaultValue: 15, min: 1, max: 200, step: 1 },
+        { key: "signal_period", label: "Signal", defaultValue: 9, min: 1, max: 200, step: 1 },
+      ];
+    case "DPO":
+      return [
+        { key: "period", label: "Period", defaultValue: 20, min: 2, max: 500, step: 1 },
+      ];
+    case "Coppock":
+      return [
+        { key: "period", label: "WMA", defaultValue: 10, min: 1, max: 200, step: 1 },
+        { key: "fast_period", label: "Short ROC", defaultValue: 11, min: 1, max: 200, step: 1 },
+        { key: "slow_period", label: "Long ROC", defaultValue: 14, min: 1, max: 200, step: 1 },
+      ];
+    case "SuperSmoother":
+      return [
+        { key: "period", label: "Period", defaultValue: 10, min: 2, max: 500, step: 1 },
+      ];
+    case "RoofingFilter":
+      return [
+        { key: "period", label: "Smoother", defaultValue: 10, min: 2, max: 200, step: 1 },
+        { key: "slow_period", label: "High-Pass", defaultValue: 48, min: 2, max: 500, step: 1 },
+      ];
+    case "ConnorsRSI":
+      return [
+        { key: "period", label: "RSI", defaultValue: 3, min: 1, max: 100, step: 1 },
+        { key: "fast_period", label: "Streak RSI", defaultValue: 2, min: 1, max: 50, step: 1 },
+        { key: "slow_period", label: "Rank Lookback", defaultValue: 100, min: 2, max: 500, step: 1 },
+      ];
+    case "ForceIndex":
+      return [
+        { key: "period", label: "Period", defaultValue: 13, min: 1, max: 500, step: 1 },
+      ];
+    case "MassIndex":
+      return [
+        { key: "period", label: "Sum Period", defaultValue: 25, min: 1, max: 500, step: 1 },
+        { key: "fast_period", label: "EMA Period", defaultValue: 9, min: 1, max: 100, step: 1 },
+      ];
+    case "SchaffTrendCycle":
+      return [
+        { key: "period", label: "Cycle", defaultValue: 10, min: 2, max: 200, step: 1 },
+        { key: "fast_period", label: "MACD Fast", defaultValue: 20, min: 1, max: 200, step: 1 },
+        { key: "slow_period", label: "MACD Slow", defaultValue: 50, min: 1, max: 500, step: 1 },
+        { key: "slowing", label: "Smoothing", defaultValue: 3, min: 1, max: 50, step: 1 },
+      ];
+    case "Klinger":
+      return [
+        { key: "fast_period", label: "Fast", defaultValue: 34, min: 1, max: 200, step: 1 },
+        { key: "slow_period", label: "Slow", defaultValue: 55, min: 1, max: 500, step: 1 },
+        { key: "signal_period", label: "Signal", defaultValue: 13, min: 1, max: 200, step: 1 },
+      ];
+    case "ElderImpulse":
+      return [
+        { key: "period", label: "EMA Period", defaultValue: 13, min: 1, max: 500, step: 1 },
+        { key: "fast_period", label: "MACD Fast", defaultValue: 12, min: 1, max: 200, step: 1 },
+        { key: "slow_period", label: "MACD Slow", defaultValue: 26, min: 1, max: 200, step: 1 },
+        { key: "signal_period", label: "MACD Signal", defaultValue: 9, min: 1, max: 200, step: 1 },
+      ];
+    case "EaseOfMovement":
+      return [
+        { key: "period", label: "Period", defaultValue: 14, min: 1, max: 500, step: 1 },
+        { key: "multiplier", label: "Volume Scale", defaultValue: 10000, min: 1, max: 100000000, step: 1 },
+      ];
     default:
       return [];
   }
@@ -198,6 +351,7 @@ export function getOutputFieldOptions(
         { value: "lower", label: "Lower" },
       ];
     case "Stochastic":
+    case "StochasticRSI":
       return [
         { value: "k", label: "%K" },
         { value: "d", label: "%D" },
@@ -208,6 +362,22 @@ export function getOutputFieldOptions(
         { value: "signal", label: "Signal Line" },
         { value: "histogram", label: "Histogram" },
       ];
+    case "PPO":
+      return [
+        { value: "ppo", label: "PPO Line" },
+        { value: "signal", label: "Signal Line" },
+        { value: "histogram", label: "Histogram" },
+      ];
+    case "TRIX":
+      return [
+        { value: "trix", label: "TRIX Line" },
+        { value: "signal", label: "Signal Line" },
+      ];
+    case "Klinger":
+      return [
+        { value: "kvo", label: "KVO Line" },
+        { value: "signal", label: "Signal Line" },
+      ];
     case "Aroon":
       return [
         { value: "aroon_up", label: "Aroon Up" },
@@ -229,11 +399,19 @@ export function getOutputFieldOptions(
         { value: "vi_minus", label: "VI-" },
       ];
     case "KeltnerChannel":
+    case "DonchianChannel":
       return [
         { value: "upper", label: "Upper" },
         { value: "middle", label: "Middle" },
         { value: "lower", label: "Lower" },
       ];
+    case "LinearRegression":
+      return [
+        { value: "value", label: "Fitted" },
+        { value: "upper", label: "Upper Band" },
+        { value: "lower", label: "Lower Band" },
+        { value: "slope", label: "Slope" },
+      ];
     case "Ichimoku":
       return [
         { value: "tenkan", label: "Tenkan" },
@@ -241,6 +419,8 @@ export function getOutputFieldOptions(
         { value: "senkou_a", label: "Senkou A" },
         { value: "senkou_b", label: "Senkou B" },
         { value: "chikou", label: "Chikou" },
+        { value: "cloud_top", label: "Cloud Top" },
+        { value: "cloud_bottom", label: "Cloud Bottom" },
       ];
     case "Fibonacci":
       return [
@@ -1,18 +1,27 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  AccountSettings,
   Symbol,
   Strategy,
   InstrumentConfig,
   BacktestConfig,
   BacktestResults,
-  OptimizationResult,
+  BatchBacktestResult,
+  SavedBacktest,
+  SavedBacktestMeta,
+  ImportedBundle,
+  OptimizationOutput,
+  TradeJournalOptions,
+  EquityPngOptions,
   OptimizationConfig,
   MonteCarloConfig,
   MonteCarloResult,
+  SeasonalityReport,
   Timeframe,
   TickStorageFormat,
   TickPipeline,
   CodeGenerationResult,
+  Candle,
   LicenseResponse,
   SavedCredentials,
   BuilderConfig,
@@ -26,13 +35,15 @@ export async function uploadCsv(
   filePath: string,
   symbolName: string,
   instrumentConfig: InstrumentConfig,
-  tickStorageFormat?: TickStorageFormat
+  tickStorageFormat?: TickStorageFormat,
+  repairBars?: boolean
 ): Promise<Symbol> {
   return invoke<Symbol>("upload_csv", {
     filePath,
     symbolName,
     instrumentConfig,
     tickStorageFormat,
+    repairBars,
   });
 }
 
@@ -46,19 +57,26 @@ export async function deleteSymbol(symbolId: string): Promise<void> {
   return invoke<void>("delete_symbol", { symbolId });
 }
 
-/// Preview first N rows of data for a symbol.
+/// Preview a page of `limit` rows starting at `offset` for a symbol.
 export async function previewData(
   symbolId: string,
   timeframe: Timeframe,
+  offset: number,
   limit: number
 ): Promise<Record<string, unknown>[]> {
   return invoke<Record<string, unknown>[]>("preview_data", {
     symbolId,
     timeframe,
+    offset,
     limit,
   });
 }
 
+/// Total number of rows stored for a symbol's timeframe.
+export async function countRows(symbolId: string, timeframe: Timeframe): Promise<number> {
+  return invoke<number>("count_rows", { symbolId, timeframe });
+}
+
 /// Run a backtest with the given strategy and configuration.
 export async function runBacktest(
   strategy: Strategy,
@@ -67,6 +85,48 @@ export async function runBacktest(
   return invoke<BacktestResults>("run_backtest", { strategy, config });
 }
 
+/// Run a backtest bar by bar, emitting a "backtest-step" snapshot every `stepInterval` bars.
+export async function runBacktestStepped(
+  strategy: Strategy,
+  config: BacktestConfig,
+  stepInterval: number,
+  stepDelayMs?: number
+): Promise<BacktestResults> {
+  return invoke<BacktestResults>("run_backtest_stepped", { strategy, config, stepInterval, stepDelayMs });
+}
+
+/// Run several strategies on the same symbol and range; one metrics row per strategy.
+export async function runBacktestBatch(
+  strategies: Strategy[],
+  config: BacktestConfig
+): Promise<BatchBacktestResult[]> {
+  return invoke<BatchBacktestResult[]>("run_backtest_batch", { strategies, config });
+}
+
+/// Save a completed backtest with its strategy. Without a name, one is generated.
+export async function saveBacktestResult(
+  strategy: Strategy,
+  results: BacktestResults,
+  name?: string
+): Promise<SavedBacktestMeta> {
+  return invoke<SavedBacktestMeta>("save_backtest_result", { name, strategy, results });
+}
+
+/// List saved backtests (metadata only), most recent first.
+export async function listBacktestResults(): Promise<SavedBacktestMeta[]> {
+  return invoke<SavedBacktestMeta[]>("list_backtest_results");
+}
+
+/// Load a saved backtest with its strategy and full results.
+export async function loadBacktestResult(resultId: string): Promise<SavedBacktest> {
+  return invoke<SavedBacktest>("load_backtest_result", { resultId });
+}
+
+/// Delete a saved backtest.
+export async function deleteBacktestResult(resultId: string): Promise<void> {
+  return invoke<void>("delete_backtest_result", { resultId });
+}
+
 /// Cancel a running backtest.
 export async function cancelBacktest(): Promise<void> {
   return invoke<void>("cancel_backtest");
@@ -76,8 +136,8 @@ export async function cancelBacktest(): Promise<void> {
 export async function runOptimization(
   strategy: Strategy,
   optimizationConfig: OptimizationConfig
-): Promise<OptimizationResult[]> {
-  return invoke<OptimizationResult[]>("run_optimization", {
+): Promise<OptimizationOutput> {
+  return invoke<OptimizationOutput>("run_optimization", {
     strategy,
     optimizationConfig,
   });
@@ -101,6 +161,13 @@ export async function runMonteCarlo(
   });
 }
 
+/// Bucket realized trade PnL by entry hour and weekday.
+export async function seasonalityReport(
+  trades: BacktestResults["trades"]
+): Promise<SeasonalityReport> {
+  return invoke<SeasonalityReport>("seasonality_report", { trades });
+}
+
 /// Save a strategy.
 export async function saveStrategy(strategy: Strategy): Promise<string> {
   return invoke<string>("save_strategy", { strategy });
@@ -116,12 +183,32 @@ export async function deleteStrategy(strategyId: string): Promise<void> {
   return invoke<void>("delete_strategy", { strategyId });
 }
 
+/// Export a strategy with its backtest config and symbol metadata as a JSON bundle.
+export async function exportStrategyBundle(
+  strategy: Strategy,
+  backtestConfig: BacktestConfig,
+  filePath: string
+): Promise<void> {
+  return invoke<void>("export_strategy_bundle", { strategy, backtestConfig, filePath });
+}
+
+/// Import a strategy bundle. The symbol is mapped to a local one by name.
+export async function importStrategyBundle(filePath: string): Promise<ImportedBundle> {
+  return invoke<ImportedBundle>("import_strategy_bundle", { filePath });
+}
+
 /// Export trades to CSV.
 export async function exportTradesCsv(
   trades: unknown[],
-  filePath: string
+  filePath: string,
+  journal?: TradeJournalOptions
 ): Promise<void> {
-  return invoke<void>("export_trades_csv", { trades, filePath });
+  return invoke<void>("export_trades_csv", { trades, filePath, journal });
+}
+
+/// Export every combination kept by the last optimization run to CSV.
+export async function exportOptimizationCsv(filePath: string): Promise<void> {
+  return invoke<void>("export_optimization_csv", { filePath });
 }
 
 /// Export metrics report to CSV.
@@ -140,6 +227,15 @@ export async function exportReportHtml(
   return invoke<void>("export_report_html", { results, filePath });
 }
 
+/// Export the equity (and optionally drawdown) curve as a PNG image.
+export async function exportEquityPng(
+  results: BacktestResults,
+  filePath: string,
+  options?: EquityPngOptions
+): Promise<void> {
+  return invoke<void>("export_equity_png", { results, filePath, options });
+}
+
 /// Export raw tick data for a symbol to a CSV file in MetaTrader 5 import format.
 /// Returns the number of rows written.
 /// Only available for symbols with base_timeframe === "Tick".
@@ -153,9 +249,29 @@ export async function exportTickDataMt5(
 /// Generate strategy code for MQL5 or PineScript.
 export async function generateStrategyCode(
   language: "mql5" | "pinescript",
-  strategy: Strategy
+  strategy: Strategy,
+  account?: AccountSettings,
+  symbolId?: string,
+  emitAlerts?: boolean,
+  verificationSamples?: Candle[]
 ): Promise<CodeGenerationResult> {
-  return invoke<CodeGenerationResult>("generate_strategy_code", { language, strategy });
+  return invoke<CodeGenerationResult>("generate_strategy_code", {
+    language,
+    strategy,
+    account,
+    symbolId,
+    emitAlerts,
+    verificationSamples,
+  });
+}
+
+/// Save generated files into a directory, or into a single zip archive at `path`.
+export async function saveGeneratedCode(
+  result: CodeGenerationResult,
+  path: string,
+  zip?: boolean
+): Promise<string[]> {
+  return invoke<string[]>("save_generated_code", { result, path, zip });
 }
 
 /// Download historical tick data from Dukascopy servers.
@@ -172,7 +288,9 @@ export async function downloadDukascopy(
   keepCsv?: boolean,
   ignoreFlats?: boolean,
   retryOnEmpty?: boolean,
-  useCache?: boolean
+  useCache?: boolean,
+  concurrency?: number,
+  maxRequestsPerSecond?: number
 ): Promise<Symbol> {
   return invoke<Symbol>("download_dukascopy", {
     symbolName,
@@ -188,6 +306,8 @@ export async function downloadDukascopy(
     ignoreFlats,
     retryOnEmpty,
     useCache,
+    concurrency,
+    maxRequestsPerSecond,
   });
 }
 
@@ -331,7 +451,8 @@ export async function runSrBacktest(
 /// Generate MQL5 code for an SR strategy.
 export async function generateSrCode(
   strategy: SrStrategy,
-  name: string
+  name: string,
+  symbolId?: string
 ): Promise<import("./types").CodeGenerationResult> {
-  return invoke("generate_sr_code", { strategy, name });
+  return invoke("generate_sr_code", { strategy, name, symbolId });
 }
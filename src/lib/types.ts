@@ -6,11 +6,17 @@
 
 export type SwapMode = "InPips" | "InPoints" | "InMoney" | "AsPercent";
 
+export type SubBarBoundary = "StartInclusive" | "EndInclusive";
+
+export type BarTimestamp = "BarOpen" | "BarClose";
+
 export interface InstrumentConfig {
   pip_size: number;
   pip_value: number;
   lot_size: number;
   min_lot: number;
+  lot_step?: number; // lot increment (SYMBOL_VOLUME_STEP); 0 = step by min_lot
+  max_lot?: number; // per-position cap (SYMBOL_VOLUME_MAX); 0 = no cap
   tick_size: number;
   digits: number;
   // Swap / overnight financing
@@ -23,6 +29,12 @@ export interface InstrumentConfig {
   min_stop_distance_pips?: number;
   // Timezone shift applied at import time (hours, e.g. -2 for UTC-2, 5.5 for UTC+5:30)
   tz_offset_hours?: number;
+  // Hour at which the trading day starts for daily resets (pivots, Daily fields, max daily trades); default 0 = midnight
+  session_start_hour?: number;
+  // Who owns a sub-bar/tick stamped exactly on a candle open; default StartInclusive = [open, next_open)
+  subbar_boundary?: SubBarBoundary;
+  // Whether source bar timestamps mark the bar open or close; close-stamped bars are shifted back one bar at import
+  timestamp_is?: BarTimestamp;
 }
 
 export const INSTRUMENT_PRESETS: Record<string, InstrumentConfig> = {
@@ -221,6 +233,8 @@ export interface DownloadResumeParams {
   ignore_flats?: boolean;
   retry_on_empty?: boolean;
   use_cache?: boolean;
+  concurrency?: number;
+  max_requests_per_second?: number;
 }
 
 export interface Symbol {
@@ -239,6 +253,16 @@ export interface Symbol {
   download_params?: DownloadResumeParams;
 }
 
+/** Payload of the "bar-integrity" event emitted when an imported bar file has inconsistent OHLC. */
+export interface BarIntegrityReport {
+  inverted_high_low: number;
+  open_out_of_range: number;
+  close_out_of_range: number;
+  /** Bars with a missing or non-finite price; never repaired. */
+  non_finite: number;
+  repaired: boolean;
+}
+
 // ── Indicators ──
 
 export type IndicatorType =
@@ -280,7 +304,28 @@ export type IndicatorType =
   | "Reflex"
   | "Pivots"
   | "UlcerIndex"
-  | "Vortex";
+  | "Vortex"
+  | "Choppiness"
+  | "StochasticRSI"
+  | "AccumulationDistribution"
+  | "ChaikinMoneyFlow"
+  | "TRIX"
+  | "DPO"
+  | "Coppock"
+  | "PPO"
+  | "DonchianChannel"
+  | "SuperSmoother"
+  | "RoofingFilter"
+  | "ConnorsRSI"
+  | "ForceIndex"
+  | "EaseOfMovement"
+  | "VWMA"
+  | "WMA"
+  | "ZLEMA"
+  | "ElderImpulse"
+  | "MassIndex"
+  | "Klinger"
+  | "SchaffTrendCycle";
 
 export interface IndicatorParams {
   period?: number;
@@ -290,12 +335,26 @@ export interface IndicatorParams {
   std_dev?: number;
   k_period?: number;
   d_period?: number;
+  slowing?: number; // Stochastic %K slowing; STC smoothing period
   acceleration_factor?: number;
   maximum_factor?: number;
   gamma?: number;
   multiplier?: number;
+  atr_smoothing?: AtrSmoothing; // ATR / SuperTrend only; default "wilder"
+  sample_stddev?: boolean; // StdDev / Bollinger: N-1 divisor instead of N
+  rsi_smoothing?: RsiSmoothing; // RSI only; default "wilder"
+  pivot_type?: PivotType; // Pivots only; default "classic"
+  ema_seed?: EmaSeed; // EMA / MACD only; default "sma_seed"
 }
 
+export type AtrSmoothing = "wilder" | "sma" | "ema";
+
+export type RsiSmoothing = "wilder" | "sma";
+
+export type PivotType = "classic" | "fibonacci" | "camarilla" | "woodie";
+
+export type EmaSeed = "sma_seed" | "first_value";
+
 export interface IndicatorConfig {
   indicator_type: IndicatorType;
   params: IndicatorParams;
@@ -319,7 +378,7 @@ export type OperandType = "Indicator" | "Price" | "Constant" | "BarTime" | "Cand
 
 export type ArithmeticOp = "Add" | "Sub" | "Mul" | "Div";
 
-export type PriceField = "Open" | "High" | "Low" | "Close" | "DailyOpen" | "DailyHigh" | "DailyLow" | "DailyClose";
+export type PriceField = "Open" | "High" | "Low" | "Close" | "DailyOpen" | "DailyHigh" | "DailyLow" | "DailyClose" | "OpenInterest" | "AvgSpread";
 
 export type TimeField =
   | "CurrentBar"
@@ -354,8 +413,16 @@ export interface Operand {
   compound_left?: Operand;
   compound_op?: ArithmeticOp;
   compound_right?: Operand;
+  // Constant as a percentage of a price field on the same bar: price * (1 + value / 100)
+  percent_of?: PriceField;
+  // Rolling transform of the operand's own history
+  transform?: OperandTransform;
 }
 
+export type OperandTransform =
+  | { type: "percent_rank"; period: number }
+  | { type: "zscore"; period: number };
+
 export interface RuleGroup {
   id: string;
   rules: Rule[];
@@ -369,6 +436,9 @@ export interface Rule {
   comparator: Comparator;
   right_operand: Operand;
   logical_operator?: LogicalOperator; // connector to next rule
+  open_parens?: number; // "(" placed before this rule
+  close_parens?: number; // ")" placed after this rule
+  tolerance?: number; // Equal comparator: absolute tolerance (default: relative 1e-9)
 }
 
 // ── Position Sizing ──
@@ -384,7 +454,7 @@ export interface PositionSizing {
 
 // ── Stop Loss ──
 
-export type StopLossType = "Pips" | "Percentage" | "ATR";
+export type StopLossType = "Pips" | "Percentage" | "ATR" | "ChandeKroll";
 
 export interface StopLoss {
   sl_type: StopLossType;
@@ -404,12 +474,16 @@ export interface TakeProfit {
 
 // ── Trailing Stop ──
 
-export type TrailingStopType = "ATR" | "RiskReward";
+export type TrailingStopType = "ATR" | "RiskReward" | "Step";
 
 export interface TrailingStop {
   ts_type: TrailingStopType;
   value: number;
   atr_period?: number;
+  /** Start trailing only once price is this many pips in profit. Omit to trail from entry. */
+  activation_pips?: number;
+  /** Step type: profit in pips between stop moves. Defaults to `value`. */
+  step_pips?: number;
 }
 
 // ── Trading Costs ──
@@ -466,10 +540,22 @@ export interface Strategy {
   entry_order?: "market" | "limit" | "stop";
   entry_order_offset_pips?: number;
   close_after_bars?: number;
+  /** Ignore exit rules until the position has been open this many bars. */
+  min_hold_bars?: number;
+  /** Force-close (reason "MaxHold") once the position has been open this many bars. */
+  max_hold_bars?: number;
   move_sl_to_be?: boolean;
+  /** Close the open position when the opposite entry signal fires. */
+  close_on_opposite_signal?: boolean;
+  /** Close on the opposite entry signal and open the opposite position on the same bar. */
+  reverse_on_opposite_signal?: boolean;
+  /** End-of-session exit: "MarketOnClose" fills at the close of each session's final bar. */
+  session_exit?: SessionExit;
   entry_order_indicator?: OrderPriceConfig;
 }
 
+export type SessionExit = "MarketOnClose";
+
 export type OrderPriceBaseField = "open" | "high" | "low" | "close";
 
 export interface OrderPriceConfig {
@@ -517,11 +603,72 @@ export interface BacktestConfig {
   precision: BacktestPrecision;
   /** How many bars a pending Limit/Stop order lives before being cancelled. Defaults to 20. */
   pending_order_expiry_bars?: number;
+  /** Evaluate rules on Heikin-Ashi candles; fills and costs still use raw prices. */
+  use_heikin_ashi?: boolean;
+  /** Max equity/drawdown points kept in the results (evenly spaced bars plus trade events). Omit for one per bar. */
+  equity_sampling?: number;
+  /** Real-tick modes: load ticks in windows of this many bars to lower peak RAM. */
+  tick_chunk_bars?: number;
+  /** Size percent/risk positions from running equity (default true) or from initial capital. */
+  compounding?: boolean;
+  /** End the run (closing any position) once equity falls to this level. */
+  stop_out_equity?: number;
+  /** M1 tick simulation: sub-bar resolution ("m1", "m5" or "m15"), finer than `timeframe`. Defaults to M1. */
+  magnifier_timeframe?: Timeframe;
+  /** Currency capital, P&L and metrics are denominated in, e.g. "USD". */
+  account_currency?: string;
+  /** Converts P&L from the quote currency into the account currency (cross pairs). */
+  quote_conversion?: QuoteConversion;
+  /** Bars skipped before the first signal, on top of the strategy lookback. Omit for lookback only. */
+  warmup_bars?: number;
+  /** Which exit wins when a bar-open exit and SL/TP both qualify on one bar. Defaults to SignalFirst. */
+  exit_priority?: ExitPriority;
+  /** Run RealTickRealSpread as RealTickCustomSpread (with a warning) when the symbol has no raw bid/ask ticks. */
+  fallback_on_missing_raw?: boolean;
+  /** Equity the drawdown curve is measured on. Defaults to BarClose. */
+  drawdown_basis?: DrawdownBasis;
+  /** Flag overtrading above this many entries in any rolling hour. Defaults to 5. */
+  overtrading_threshold?: number;
+  /** Tag trades as trending/ranging at entry and break metrics down per regime. */
+  regime?: RegimeConfig;
+  /** Seed for random slippage so a run can be repeated exactly. Unset = OS entropy. */
+  slippage_seed?: number;
+}
+
+/** ADX: trending at or above the threshold. Choppiness: trending below it. */
+export type RegimeIndicator = "ADX" | "Choppiness";
+
+export interface RegimeConfig {
+  indicator: RegimeIndicator;
+  period: number;
+  threshold: number;
+}
+
+export type MarketRegime = "Trending" | "Ranging";
+
+/** SignalFirst: exit signals / bar-count exits fill at the open. StopsFirst: an SL/TP hit in the bar wins. */
+export type ExitPriority = "SignalFirst" | "StopsFirst";
+
+/** BarClose: equity at each bar close. Intrabar: worst equity within the bar from sub-bar lows/highs. */
+export type DrawdownBasis = "BarClose" | "Intrabar";
+
+/** Quote→account rate source: a fixed rate, or another symbol's close (`invert` uses 1 / close). */
+export type QuoteConversion =
+  | { type: "constant"; rate: number }
+  | { type: "symbol"; symbol_id: string; invert?: boolean };
+
+/** Strategy bundle read back by `import_strategy_bundle` (not yet saved). */
+export interface ImportedBundle {
+  strategy: Strategy;
+  /** `symbol_id` and a symbol quote conversion point at the matching local symbols, or are empty when none matched. */
+  backtest_config: BacktestConfig;
+  symbol_id: string | null;
+  warnings: string[];
 }
 
 // ── Trade Result ──
 
-export type TradeCloseReason = "Signal" | "StopLoss" | "TakeProfit" | "TrailingStop" | "EndOfData" | "TimeClose" | "ExitAfterBars";
+export type TradeCloseReason = "Signal" | "StopLoss" | "TakeProfit" | "TrailingStop" | "EndOfData" | "TimeClose" | "ExitAfterBars" | "MaxHold" | "StopOut" | "SessionClose";
 
 export interface TradeResult {
   id: string;
@@ -536,11 +683,38 @@ export interface TradeResult {
   commission: number;
   /** Total swap charged over the life of this trade (negative = cost). */
   swap: number;
+  /** Spread paid on entry and exit fills, in account currency. */
+  spread_cost: number;
+  /** Slippage paid on entry and exit fills, in account currency. */
+  slippage_cost: number;
   close_reason: TradeCloseReason;
   duration_bars: number;
   duration_time: string;
   mae: number;
   mfe: number;
+  /** Pips from entry to the initial stop loss. Absent without a stop loss. */
+  initial_risk_pips?: number;
+  /** Entry rule set that fired (e.g. "Long entry group 2"). */
+  entry_reason?: string;
+  /** Exit trigger: exit rule set that fired, or the SL/TP/trailing level hit. */
+  exit_reason?: string;
+  /** Regime of the last closed bar before entry, when `BacktestConfig.regime` is set. */
+  regime?: MarketRegime;
+}
+
+/** Account context for the detailed trade journal CSV export. */
+export interface TradeJournalOptions {
+  initial_capital: number;
+  /** Account-currency value of one pip per lot. */
+  pip_value: number;
+}
+
+/** Size and content of the equity curve PNG export. */
+export interface EquityPngOptions {
+  width: number;
+  height: number;
+  /** Draw the drawdown curve in a panel under the equity curve. */
+  include_drawdown: boolean;
 }
 
 // ── Metrics ──
@@ -617,6 +791,54 @@ export interface BacktestMetrics {
   // Costs breakdown
   total_swap_charged: number;
   total_commission_charged: number;
+  total_spread_cost: number;
+  total_slippage_cost: number;
+  /** Spread + slippage + commission − swap. */
+  total_costs: number;
+  /** net_profit + total_costs: what the trades made before any trading costs. */
+  gross_pnl_before_costs: number;
+
+  // Benchmark (buy-and-hold over the same bars)
+  benchmark_return_pct: number;
+  alpha: number;
+  beta: number;
+  correlation: number;
+
+  // Exposure
+  /** % of bars with at least one open position (time in market). */
+  exposure_pct: number;
+  /** Average open positions over the bars that had any position open. */
+  avg_simultaneous_positions: number;
+  long_exposure_pct: number;
+  short_exposure_pct: number;
+
+  // Drawdown episodes (peak → recovery to the prior peak)
+  drawdown_episodes: number;
+  longest_drawdown_bars: number;
+  longest_drawdown_time: string;
+  /** Bars from the max-drawdown trough back to the prior peak. */
+  max_drawdown_recovery_bars: number;
+  max_drawdown_recovery_time: string;
+  avg_drawdown_depth_pct: number;
+  avg_drawdown_duration_bars: number;
+
+  // Trade clustering (from entry times)
+  avg_minutes_between_trades: number;
+  /** Trades entered within an hour of the previous entry. */
+  clustered_trades: number;
+  /** Most entries inside any rolling one-hour window. */
+  max_trades_per_hour: number;
+  /** `max_trades_per_hour` is above the overtrading threshold. */
+  overtrading: boolean;
+
+  // Market regime breakdown (trades tagged via `BacktestConfig.regime`)
+  trending_trades: number;
+  trending_win_rate_pct: number;
+  /** Net P&L after commission and swap. */
+  trending_net_profit: number;
+  ranging_trades: number;
+  ranging_win_rate_pct: number;
+  ranging_net_profit: number;
 }
 
 // ── Equity/Drawdown points ──
@@ -631,6 +853,23 @@ export interface DrawdownPoint {
   drawdown_pct: number;
 }
 
+/** Incremental snapshot emitted as "backtest-step" by a stepped (replay) backtest. */
+export interface BacktestStep {
+  bar_index: number;
+  total_bars: number;
+  equity_points: EquityPoint[];
+  drawdown_points: DrawdownPoint[];
+  new_trades: TradeResult[];
+  in_position: boolean;
+}
+
+/** Rolling Sharpe/Sortino at one equity point; null until the first full window. */
+export interface RollingRatioPoint {
+  timestamp: string;
+  sharpe: number | null;
+  sortino: number | null;
+}
+
 // ── Backtest Results ──
 
 export interface BacktestResults {
@@ -645,13 +884,57 @@ export interface BacktestResults {
   long_metrics?: BacktestMetrics;
   /** Metrics for short trades only. Absent when there are no short trades. */
   short_metrics?: BacktestMetrics;
+  /** Buy-and-hold equity aligned with `equity_curve`. Absent in optimizer/builder runs. */
+  benchmark_curve?: EquityPoint[];
+  /** True when the run ended early at `stop_out_equity`. */
+  stopped_out?: boolean;
+  /** Rolling Sharpe/Sortino aligned with `equity_curve`. Absent in optimizer/builder runs. */
+  rolling_ratios?: RollingRatioPoint[];
+}
+
+/** One row of `runBacktestBatch`: a strategy's metrics on the shared symbol and range. */
+export interface BatchBacktestResult {
+  strategy_id: string;
+  strategy_name: string;
+  metrics: BacktestMetrics;
+  warnings?: string[];
+}
+
+/** Listing metadata of a saved backtest. */
+export interface SavedBacktestMeta {
+  id: string;
+  name: string;
+  created_at: string;
+  strategy_name: string;
+  symbol_id: string;
+  timeframe: string;
+  start_date: string;
+  end_date: string;
+  total_trades: number;
+  net_profit: number;
+  total_return_pct: number;
+}
+
+/** A saved backtest with the strategy that produced it (`results.backtest_config` holds the config). */
+export interface SavedBacktest extends SavedBacktestMeta {
+  strategy: Strategy;
+  results: BacktestResults;
 }
 
 // ── Optimization ──
 
-export type OptimizationMethod = "GridSearch" | "GeneticAlgorithm";
+export type OptimizationMethod = "GridSearch" | "GeneticAlgorithm" | "RandomSearch";
+
+export type ObjectiveFunction = "TotalProfit" | "SharpeRatio" | "ProfitFactor" | "WinRate" | "ReturnDdRatio" | "MinStagnation" | "MinUlcerIndex" | "SortinoRatio" | "CalmarRatio" | "Expectancy" | "RecoveryFactor";
+
+export type ObjectiveDirection = "Maximize" | "Minimize";
 
-export type ObjectiveFunction = "TotalProfit" | "SharpeRatio" | "ProfitFactor" | "WinRate" | "ReturnDdRatio" | "MinStagnation" | "MinUlcerIndex";
+/** An objective with its composite-score weight. Omitting `direction` uses the objective's natural one. */
+export interface WeightedObjective {
+  objective: ObjectiveFunction;
+  weight: number;
+  direction?: ObjectiveDirection;
+}
 
 export type ParamSource = "long_entry" | "short_entry" | "long_exit" | "short_exit" | "stop_loss" | "take_profit" | "trailing_stop" | "trading_hours" | "close_trades_at";
 
@@ -666,11 +949,28 @@ export interface ParameterRange {
   param_source: ParamSource;
 }
 
+/** GA recombination operator: one-point cut, per-gene swap, or BLX-0.5 blend. */
+export type CrossoverType = "OnePoint" | "Uniform" | "Blend";
+
+/** GA mutation operator: redraw from the range, or add gaussian noise (10% of range). */
+export type MutationType = "UniformReset" | "Gaussian";
+
 export interface GeneticAlgorithmConfig {
   population_size: number;
   generations: number;
   mutation_rate: number;
   crossover_rate: number;
+  crossover_type?: CrossoverType;
+  mutation_type?: MutationType;
+  /** Fixed RNG seed for reproducible runs. Omit for a random seed. */
+  seed?: number;
+}
+
+export interface RandomSearchConfig {
+  /** Number of Latin-hypercube samples to evaluate. */
+  samples: number;
+  /** Fixed RNG seed for reproducible runs. Omit for a random seed. */
+  seed?: number;
 }
 
 export interface OosPeriod {
@@ -691,10 +991,15 @@ export interface OosResult {
 export interface OptimizationConfig {
   method: OptimizationMethod;
   parameter_ranges: ParameterRange[];
-  objectives: ObjectiveFunction[];
+  objectives: WeightedObjective[];
   backtest_config: BacktestConfig;
   ga_config?: GeneticAlgorithmConfig;
+  random_search_config?: RandomSearchConfig;
   oos_periods: OosPeriod[];
+  /** Accept OOS periods overlapping the in-sample range instead of rejecting the run. */
+  allow_oos_overlap?: boolean;
+  /** Keep every evaluated combination on the backend for `exportOptimizationCsv`. */
+  keep_all_results?: boolean;
 }
 
 export interface OptimizationResult {
@@ -709,8 +1014,51 @@ export interface OptimizationResult {
   return_dd_ratio: number;
   stagnation_bars: number;
   ulcer_index_pct: number;
+  net_profit: number;
+  sortino_ratio: number;
+  calmar_ratio: number;
+  expectancy: number;
+  recovery_factor: number;
   oos_results: OosResult[];
   equity_curve: EquityPoint[];
+  /** Set when the backtest failed for these parameters; the row is ranked last. */
+  failure_reason?: string;
+}
+
+/** Mean/best objective per value of one parameter, marginalized over the others. */
+export interface ParameterMarginal {
+  param: string;
+  values: number[];
+  mean_objective: number[];
+  best_objective: number[];
+  /** max − min of `mean_objective`; larger means the parameter matters more. */
+  importance: number;
+}
+
+/** Mean objective over two parameters. `cells[row][col]` ↔ (`x_values[col]`, `y_values[row]`); null = not evaluated. */
+export interface ObjectiveGrid {
+  param_x: string;
+  param_y: string;
+  x_values: number[];
+  y_values: number[];
+  cells: (number | null)[][];
+}
+
+export interface ParameterSensitivity {
+  marginals: ParameterMarginal[];
+  grids: ObjectiveGrid[];
+}
+
+export interface OptimizationOutput {
+  results: OptimizationResult[];
+  /** Computed from every evaluated combination. Grid Search only. */
+  sensitivity?: ParameterSensitivity;
+  /** Combinations kept for CSV export (set when `keep_all_results` was requested). */
+  retained_count?: number;
+  /** Set when the run was cancelled; `results` are the best evaluated before that. */
+  cancelled?: boolean;
+  /** Run-level warnings, e.g. a tick precision fallback. */
+  warnings?: string[];
 }
 
 // ── Progress Events ──
@@ -749,8 +1097,26 @@ export interface CodeFile {
   is_main: boolean;
 }
 
+/** Backtest account carried into generated code (PineScript `initial_capital`/margin, MQL5 tester notes). */
+export interface AccountSettings {
+  initial_capital: number;
+  leverage: number;
+}
+
 export interface CodeGenerationResult {
   files: CodeFile[];
+  /** Strategy validation warnings ("field: message"). */
+  warnings?: string[];
+}
+
+/** OHLCV bar sent to the backend (e.g. codegen verification samples). */
+export interface Candle {
+  datetime: string;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
 }
 
 // ── App Section ──
@@ -814,6 +1180,19 @@ export interface MonteCarloResult {
 
 // ── New BacktestMetrics fields (P4.4) ──
 
+export interface SeasonalityBucket {
+  bucket: number; // entry hour 0–23 or ISO weekday Mon=1…Sun=7
+  trade_count: number;
+  total_pnl: number;
+  avg_pnl: number;
+  win_rate_pct: number;
+}
+
+export interface SeasonalityReport {
+  by_hour: SeasonalityBucket[];
+  by_weekday: SeasonalityBucket[];
+}
+
 export interface MonthlyReturn {
   year: number;
   month: number;
@@ -824,15 +1203,22 @@ export interface MonthlyReturn {
 
 export type LicenseTier = "free" | "pro";
 
+/** "unreachable" with valid=true means the cached tier is used during the offline grace period. */
+export type LicenseValidationStatus = "valid" | "invalid" | "unreachable";
+
 export interface LicenseResponse {
   valid: boolean;
   tier: LicenseTier;
+  status: LicenseValidationStatus;
   message?: string;
 }
 
 export interface SavedCredentials {
   username: string;
   license_key: string;
+  last_validated_at?: number;
+  last_tier?: LicenseTier;
+  signature?: string;
 }
 
 // ── Builder / Strategy Miner ──────────────────────────────────────────────────
@@ -5,6 +5,7 @@ import type {
   Strategy,
   BacktestResults,
   OptimizationResult,
+  ParameterSensitivity,
   Timeframe,
   BacktestPrecision,
   Rule,
@@ -16,6 +17,7 @@ import type {
   TradeDirection,
   TradingHours,
   CloseTradesAt,
+  SessionExit,
   OosPeriod,
   ParameterRange,
   LicenseTier,
@@ -458,6 +460,8 @@ interface AppState {
   setTradingHours: (hours: TradingHours | undefined) => void;
   setMaxDailyTrades: (max: number | undefined) => void;
   setCloseTradesAt: (ct: CloseTradesAt | undefined) => void;
+  setOppositeSignal: (close: boolean, reverse: boolean) => void;
+  setSessionExit: (exit: SessionExit | undefined) => void;
   resetStrategy: () => void;
 
   // Backtest
@@ -480,9 +484,14 @@ interface AppState {
 
   // Optimization
   optimizationResults: OptimizationResult[];
+  optimizationSensitivity: ParameterSensitivity | null;
+  /** Combinations the backend kept from the last run for CSV export. */
+  optimizationRetainedCount: number | null;
   optimizationParamRanges: ParameterRange[];
   optimizationOosPeriods: OosPeriod[];
   setOptimizationResults: (results: OptimizationResult[]) => void;
+  setOptimizationSensitivity: (sensitivity: ParameterSensitivity | null) => void;
+  setOptimizationRetainedCount: (count: number | null) => void;
   setOptimizationParamRanges: (ranges: ParameterRange[]) => void;
   setOptimizationOosPeriods: (periods: OosPeriod[]) => void;
 
@@ -685,6 +694,18 @@ export const useAppStore = create<AppState>((set, get) => ({
     set((state) => ({
       currentStrategy: { ...state.currentStrategy, close_trades_at: ct },
     })),
+  setOppositeSignal: (close, reverse) =>
+    set((state) => ({
+      currentStrategy: {
+        ...state.currentStrategy,
+        close_on_opposite_signal: close,
+        reverse_on_opposite_signal: reverse,
+      },
+    })),
+  setSessionExit: (exit) =>
+    set((state) => ({
+      currentStrategy: { ...state.currentStrategy, session_exit: exit },
+    })),
   resetStrategy: () => set({ currentStrategy: { ...emptyStrategy } }),
 
   // Backtest
@@ -707,9 +728,13 @@ export const useAppStore = create<AppState>((set, get) => ({
 
   // Optimization
   optimizationResults: [],
+  optimizationSensitivity: null,
+  optimizationRetainedCount: null,
   optimizationParamRanges: [],
   optimizationOosPeriods: [],
   setOptimizationResults: (results) => set({ optimizationResults: results }),
+  setOptimizationSensitivity: (sensitivity) => set({ optimizationSensitivity: sensitivity }),
+  setOptimizationRetainedCount: (count) => set({ optimizationRetainedCount: count }),
   setOptimizationParamRanges: (ranges) => set({ optimizationParamRanges: ranges }),
   setOptimizationOosPeriods: (periods) => set({ optimizationOosPeriods: periods }),
 